@@ -0,0 +1,395 @@
+//! Parses Bencode data into values allocated from a caller-provided arena.
+//!
+//! Parsing many small messages (e.g. KRPC messages received by a DHT node)
+//! into a [`crate::Value`] makes many small heap allocations. The [Value] type
+//! in this module instead allocates every list, dictionary, and byte string
+//! from a [`Bump`] arena. All of the memory can be released at once by
+//! resetting the arena.
+//!
+//! ```rust
+//! use bumpalo::Bump;
+//!
+//! let mut bump = Bump::new();
+//!
+//! for _ in 0..3 {
+//!     let value = bt_bencode::arena::from_slice_in(b"d1:q4:ping1:y1:qe", &bump)?;
+//!     assert_eq!(value.get(b"q").and_then(|q| q.as_byte_str()), Some(&b"ping"[..]));
+//!
+//!     bump.reset();
+//! }
+//! # Ok::<(), bt_bencode::Error>(())
+//! ```
+
+use crate::error::Error;
+use crate::read;
+use crate::value::Number;
+use bumpalo::{collections::Vec as BumpVec, Bump};
+use core::fmt;
+use serde::{
+    de::{self, DeserializeSeed, MapAccess, SeqAccess, Visitor},
+    ser::{Serialize, SerializeMap, SerializeSeq},
+};
+use serde_bytes::Bytes;
+
+#[cfg(feature = "std")]
+use std::io;
+
+/// Represents a valid Bencode value allocated in an arena.
+///
+/// Unlike [`crate::Value`], the data is immutable once parsed.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(not(feature = "big-int"), derive(Copy))]
+pub enum Value<'bump> {
+    /// A byte string.
+    ByteStr(&'bump [u8]),
+    /// An integer which can be signed or unsigned.
+    Int(Number),
+    /// A list of values.
+    List(&'bump [Value<'bump>]),
+    /// A dictionary of values.
+    ///
+    /// The entries are sorted by key and the keys are unique.
+    Dict(&'bump [(&'bump [u8], Value<'bump>)]),
+}
+
+impl<'bump> Value<'bump> {
+    /// Deserializes a value from any deserializer and allocates the data in the arena.
+    ///
+    /// # Errors
+    ///
+    /// Deserialization can fail if the data is not valid.
+    pub fn deserialize_in<'de, D>(deserializer: D, bump: &'bump Bump) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        ValueSeed { bump }.deserialize(deserializer)
+    }
+
+    /// If the value is a byte string, returns the underlying value.
+    #[must_use]
+    pub fn as_byte_str(&self) -> Option<&'bump [u8]> {
+        match self {
+            Value::ByteStr(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    /// If the value is a UTF-8 string, returns the underlying value.
+    #[must_use]
+    pub fn as_str(&self) -> Option<&'bump str> {
+        self.as_byte_str()
+            .and_then(|b| core::str::from_utf8(b).ok())
+    }
+
+    /// If the value is a number, returns the underlying value.
+    #[must_use]
+    pub fn as_number(&self) -> Option<Number> {
+        match self {
+            Value::Int(n) => Some(Number::clone(n)),
+            _ => None,
+        }
+    }
+
+    /// If the value is a list, returns the underlying value.
+    #[must_use]
+    pub fn as_list(&self) -> Option<&'bump [Value<'bump>]> {
+        match self {
+            Value::List(l) => Some(l),
+            _ => None,
+        }
+    }
+
+    /// If the value is a dictionary, returns the underlying value.
+    #[must_use]
+    pub fn as_dict(&self) -> Option<&'bump [(&'bump [u8], Value<'bump>)]> {
+        match self {
+            Value::Dict(d) => Some(d),
+            _ => None,
+        }
+    }
+
+    /// If the value is a dictionary, returns the value for the key.
+    #[must_use]
+    pub fn get(&self, key: &[u8]) -> Option<&'bump Value<'bump>> {
+        let d = self.as_dict()?;
+        d.binary_search_by(|(k, _)| (*k).cmp(key))
+            .ok()
+            .map(|idx| &d[idx].1)
+    }
+
+    /// Copies the value into a heap allocated [`crate::Value`].
+    #[must_use]
+    pub fn to_value(&self) -> crate::Value {
+        use crate::{key::ByteStrKey, value::SmallBytes};
+
+        match self {
+            Value::ByteStr(b) => crate::Value::ByteStr(SmallBytes::from(*b)),
+            Value::Int(n) => crate::Value::Int(Number::clone(n)),
+            Value::List(l) => crate::Value::List(l.iter().map(Value::to_value).collect()),
+            Value::Dict(d) => crate::Value::Dict(
+                d.iter()
+                    .map(|(k, v)| (ByteStrKey::from(*k), v.to_value()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl<'bump> Serialize for Value<'bump> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            Value::ByteStr(b) => serializer.serialize_bytes(b),
+            Value::Int(i) => i.serialize(serializer),
+            Value::List(l) => {
+                let mut seq = serializer.serialize_seq(Some(l.len()))?;
+                for v in l.iter() {
+                    seq.serialize_element(v)?;
+                }
+                seq.end()
+            }
+            Value::Dict(d) => {
+                let mut map = serializer.serialize_map(Some(d.len()))?;
+                for (k, v) in d.iter() {
+                    map.serialize_entry(Bytes::new(k), v)?;
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+/// Deserializes a [Value] from a slice of bytes with all data allocated in the arena.
+///
+/// The returned value does not borrow from the input, so the input buffer can
+/// be reused immediately (e.g. for the next received datagram).
+///
+/// # Errors
+///
+/// Deserialization can fail if the data is not valid.
+pub fn from_slice_in<'bump>(s: &[u8], bump: &'bump Bump) -> Result<Value<'bump>, Error> {
+    let mut de = crate::Deserializer::new(read::SliceRead::new(s));
+    let value = Value::deserialize_in(&mut de, bump)?;
+    de.end()?;
+    Ok(value)
+}
+
+/// Deserializes a [Value] from an [`io::Read`] type with all data allocated in the arena.
+///
+/// # Errors
+///
+/// Deserialization can fail if the data is not valid and other IO errors.
+#[cfg(feature = "std")]
+pub fn from_reader_in<R>(r: R, bump: &Bump) -> Result<Value<'_>, Error>
+where
+    R: io::Read,
+{
+    let mut de = crate::Deserializer::new(read::IoRead::new(r));
+    let value = Value::deserialize_in(&mut de, bump)?;
+    de.end()?;
+    Ok(value)
+}
+
+struct ValueSeed<'bump> {
+    bump: &'bump Bump,
+}
+
+impl<'de, 'bump> DeserializeSeed<'de> for ValueSeed<'bump> {
+    type Value = Value<'bump>;
+
+    #[inline]
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de, 'bump> Visitor<'de> for ValueSeed<'bump> {
+    type Value = Value<'bump>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("any valid Bencode value")
+    }
+
+    #[inline]
+    fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E> {
+        Ok(Value::Int(Number::Signed(value)))
+    }
+
+    #[inline]
+    fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E> {
+        Ok(Value::Int(Number::Unsigned(value)))
+    }
+
+    #[inline]
+    fn visit_i128<E>(self, value: i128) -> Result<Self::Value, E> {
+        Ok(Value::Int(Number::from(value)))
+    }
+
+    #[inline]
+    fn visit_u128<E>(self, value: u128) -> Result<Self::Value, E> {
+        Ok(Value::Int(Number::from(value)))
+    }
+
+    #[cfg(feature = "big-int")]
+    #[inline]
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        crate::value::deserialize_big_int(deserializer).map(Value::Int)
+    }
+
+    #[inline]
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E> {
+        Ok(Value::ByteStr(self.bump.alloc_slice_copy(value.as_bytes())))
+    }
+
+    #[inline]
+    fn visit_bytes<E>(self, value: &[u8]) -> Result<Self::Value, E> {
+        Ok(Value::ByteStr(self.bump.alloc_slice_copy(value)))
+    }
+
+    fn visit_seq<V>(self, mut visitor: V) -> Result<Self::Value, V::Error>
+    where
+        V: SeqAccess<'de>,
+    {
+        let mut list = BumpVec::with_capacity_in(visitor.size_hint().unwrap_or(0), self.bump);
+        while let Some(elem) = visitor.next_element_seed(ValueSeed { bump: self.bump })? {
+            list.push(elem);
+        }
+        Ok(Value::List(list.into_bump_slice()))
+    }
+
+    fn visit_map<V>(self, mut visitor: V) -> Result<Self::Value, V::Error>
+    where
+        V: MapAccess<'de>,
+    {
+        let mut dict = BumpVec::with_capacity_in(visitor.size_hint().unwrap_or(0), self.bump);
+        while let Some(key) = visitor.next_key_seed(KeySeed { bump: self.bump })? {
+            let value = visitor.next_value_seed(ValueSeed { bump: self.bump })?;
+            dict.push((key, value));
+        }
+
+        // Match the semantics of a `BTreeMap`: sorted keys where the last
+        // duplicate key wins.
+        dict.reverse();
+        dict.sort_by_key(|(k, _)| *k);
+        dict.dedup_by(|(a, _), (b, _)| a == b);
+
+        Ok(Value::Dict(dict.into_bump_slice()))
+    }
+}
+
+struct KeySeed<'bump> {
+    bump: &'bump Bump,
+}
+
+impl<'de, 'bump> DeserializeSeed<'de> for KeySeed<'bump> {
+    type Value = &'bump [u8];
+
+    #[inline]
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_bytes(self)
+    }
+}
+
+impl<'de, 'bump> Visitor<'de> for KeySeed<'bump> {
+    type Value = &'bump [u8];
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a byte string key")
+    }
+
+    #[inline]
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E> {
+        Ok(self.bump.alloc_slice_copy(value.as_bytes()))
+    }
+
+    #[inline]
+    fn visit_bytes<E>(self, value: &[u8]) -> Result<Self::Value, E> {
+        Ok(self.bump.alloc_slice_copy(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Result;
+
+    #[cfg(all(feature = "alloc", not(feature = "std")))]
+    use alloc::vec::Vec;
+    #[cfg(feature = "std")]
+    use std::vec::Vec;
+
+    #[test]
+    fn test_from_slice_in() -> Result<()> {
+        let bump = Bump::new();
+        let input = b"d1:ad2:id20:abcdefghij0123456789e1:q4:ping1:t2:aa1:y1:qe";
+        let value = from_slice_in(input, &bump)?;
+
+        assert_eq!(
+            value.get(b"a").and_then(|a| a.get(b"id")),
+            Some(&Value::ByteStr(b"abcdefghij0123456789"))
+        );
+        assert_eq!(value.get(b"q").and_then(Value::as_str), Some("ping"));
+        assert_eq!(value.get(b"z"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_list_and_integers() -> Result<()> {
+        let bump = Bump::new();
+        let value = from_slice_in(b"li-3ei4e4:spame", &bump)?;
+        assert_eq!(
+            value,
+            Value::List(&[
+                Value::Int(Number::Signed(-3)),
+                Value::Int(Number::Unsigned(4)),
+                Value::ByteStr(b"spam"),
+            ])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_unsorted_and_duplicate_keys() -> Result<()> {
+        let bump = Bump::new();
+        let value = from_slice_in(b"d1:bi1e1:ai2e1:bi3ee", &bump)?;
+        assert_eq!(
+            value,
+            Value::Dict(&[
+                (&b"a"[..], Value::Int(Number::Unsigned(2))),
+                (&b"b"[..], Value::Int(Number::Unsigned(3))),
+            ])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_round_trip() -> Result<()> {
+        let bump = Bump::new();
+        let input = b"d4:spaml1:a1:bee";
+        let value = from_slice_in(input, &bump)?;
+        let output: Vec<u8> = crate::to_vec(&value)?;
+        assert_eq!(&output[..], &input[..]);
+
+        let owned = value.to_value();
+        assert_eq!(owned, crate::from_slice::<crate::Value>(input)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let bump = Bump::new();
+        assert!(from_slice_in(b"d1:a", &bump).is_err());
+        assert!(from_slice_in(b"i1ei2e", &bump).is_err());
+    }
+}
@@ -0,0 +1,314 @@
+//! [`tokio_util::codec`] implementation for streams of Bencode values.
+//!
+//! Bencode values are self-delimiting, so a stream of values does not need
+//! any additional framing. [`BencodeCodec`] decodes a value once all of its
+//! bytes have been received and waits for more bytes if a value is
+//! incomplete.
+//!
+//! ```rust
+//! use bt_bencode::{codec::BencodeCodec, Value};
+//! use bytes::BytesMut;
+//! use tokio_util::codec::{Decoder, Encoder};
+//!
+//! let mut codec = BencodeCodec::<Value>::new();
+//! let mut buf = BytesMut::new();
+//!
+//! buf.extend_from_slice(b"d1:y1:qed1:y");
+//! let msg = codec.decode(&mut buf)?.unwrap();
+//! assert_eq!(msg["y"].as_str(), Some("q"));
+//! assert!(codec.decode(&mut buf)?.is_none());
+//!
+//! buf.extend_from_slice(b"1:re");
+//! let msg = codec.decode(&mut buf)?.unwrap();
+//! assert_eq!(msg["y"].as_str(), Some("r"));
+//!
+//! codec.encode(&msg, &mut buf)?;
+//! assert_eq!(&buf[..], b"d1:y1:re");
+//! # Ok::<(), bt_bencode::Error>(())
+//! ```
+
+use crate::error::{Error, Result};
+use crate::incremental::{frame_too_long, Incremental, Status};
+use crate::write::Write;
+use bytes::{Buf, BytesMut};
+use core::{fmt, marker::PhantomData};
+use serde::{de::DeserializeOwned, Serialize};
+use tokio_util::codec::{Decoder, Encoder};
+
+/// Decodes values of type `T` and encodes any serializable value.
+///
+/// Partially received values are scanned only once; the codec remembers
+/// where it stopped and continues from there when more bytes arrive.
+///
+/// A codec from [`new()`][BencodeCodec::new] buffers an incomplete value
+/// until it is complete, however long it is. Use
+/// [`with_max_frame_len()`][BencodeCodec::with_max_frame_len] and
+/// [`with_max_depth()`][BencodeCodec::with_max_depth] when the values come
+/// from an untrusted peer.
+pub struct BencodeCodec<T> {
+    parser: Incremental,
+    scanned: usize,
+    max_frame_len: Option<usize>,
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> BencodeCodec<T> {
+    /// Constructs a new codec.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            parser: Incremental::new(),
+            scanned: 0,
+            max_frame_len: None,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Constructs a new codec which returns an error when a value is longer
+    /// than `max_frame_len` bytes.
+    ///
+    /// The error is returned as soon as more than `max_frame_len` bytes of
+    /// an incomplete value are buffered, so a peer cannot make the buffer
+    /// grow without bound by announcing a huge byte string.
+    ///
+    /// ```rust
+    /// use bt_bencode::{codec::BencodeCodec, Value};
+    /// use bytes::BytesMut;
+    /// use tokio_util::codec::Decoder;
+    ///
+    /// let mut codec = BencodeCodec::<Value>::with_max_frame_len(16);
+    /// let mut buf = BytesMut::from(&b"99999999999:"[..]);
+    /// assert!(codec.decode(&mut buf)?.is_none());
+    /// buf.extend_from_slice(&[0; 8]);
+    /// assert!(codec.decode(&mut buf).is_err());
+    /// # Ok::<(), bt_bencode::Error>(())
+    /// ```
+    #[must_use]
+    pub fn with_max_frame_len(max_frame_len: usize) -> Self {
+        Self {
+            max_frame_len: Some(max_frame_len),
+            ..Self::new()
+        }
+    }
+
+    /// Returns the maximum length of a value, if any.
+    #[must_use]
+    pub fn max_frame_len(&self) -> Option<usize> {
+        self.max_frame_len
+    }
+
+    /// Limits the number of nested lists and dictionaries in a value.
+    ///
+    /// See [`Incremental::with_max_depth()`] for how the depth is counted.
+    ///
+    /// ```rust
+    /// use bt_bencode::{codec::BencodeCodec, Value};
+    /// use bytes::BytesMut;
+    /// use tokio_util::codec::Decoder;
+    ///
+    /// let mut codec = BencodeCodec::<Value>::with_max_frame_len(1024).with_max_depth(1);
+    /// let mut buf = BytesMut::from(&b"llee"[..]);
+    /// assert!(codec.decode(&mut buf).is_err());
+    /// # Ok::<(), bt_bencode::Error>(())
+    /// ```
+    #[must_use]
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.parser = self.parser.with_max_depth(max_depth);
+        self
+    }
+
+    /// Returns the maximum number of nested lists and dictionaries, if any.
+    #[must_use]
+    pub fn max_depth(&self) -> Option<usize> {
+        self.parser.max_depth()
+    }
+
+    fn fail(&mut self, err: Error) -> Result<Option<T>> {
+        self.parser.reset();
+        self.scanned = 0;
+        Err(err)
+    }
+}
+
+impl<T> Default for BencodeCodec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for BencodeCodec<T> {
+    fn clone(&self) -> Self {
+        Self {
+            parser: self.parser.clone(),
+            scanned: self.scanned,
+            max_frame_len: self.max_frame_len,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T> fmt::Debug for BencodeCodec<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BencodeCodec")
+            .field("parser", &self.parser)
+            .field("scanned", &self.scanned)
+            .field("max_frame_len", &self.max_frame_len)
+            .finish()
+    }
+}
+
+impl<T> Decoder for BencodeCodec<T>
+where
+    T: DeserializeOwned,
+{
+    type Item = T;
+    type Error = Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<T>> {
+        let status = match self.parser.feed(&src[self.scanned..]) {
+            Ok(status) => status,
+            Err(err) => return self.fail(err),
+        };
+
+        let len = match status {
+            Status::NeedMore => src.len(),
+            Status::Complete(n) => self.scanned + n,
+        };
+        if let Some(max_frame_len) = self.max_frame_len {
+            if len > max_frame_len {
+                return self.fail(frame_too_long(max_frame_len));
+            }
+        }
+
+        match status {
+            Status::NeedMore => {
+                self.scanned = len;
+                Ok(None)
+            }
+            Status::Complete(_) => {
+                self.scanned = 0;
+                let value = crate::from_slice(&src[..len]);
+                src.advance(len);
+                value.map(Some)
+            }
+        }
+    }
+}
+
+impl<T, I> Encoder<I> for BencodeCodec<T>
+where
+    I: Serialize,
+{
+    type Error = Error;
+
+    fn encode(&mut self, item: I, dst: &mut BytesMut) -> Result<()> {
+        let mut ser = crate::Serializer::new(BytesMutWrite(dst));
+        item.serialize(&mut ser)
+    }
+}
+
+pub(crate) struct BytesMutWrite<'a>(pub(crate) &'a mut BytesMut);
+
+impl<'a> Write for BytesMutWrite<'a> {
+    #[inline]
+    fn write_all(&mut self, buf: &[u8]) -> Result<()> {
+        self.0.extend_from_slice(buf);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Value;
+    use futures::{SinkExt, StreamExt};
+    use tokio_util::codec::{FramedRead, FramedWrite};
+
+    #[test]
+    fn test_decode_partial() -> Result<()> {
+        let mut codec = BencodeCodec::<Value>::new();
+        let input = b"d4:spaml1:ai-12ee3:cow3:mooei1e";
+        let mut buf = BytesMut::new();
+
+        for b in &input[..input.len() - 3] {
+            assert!(codec.decode(&mut buf)?.is_none());
+            buf.extend_from_slice(&[*b]);
+        }
+        let value = codec.decode(&mut buf)?.unwrap();
+        assert_eq!(value["cow"].as_str(), Some("moo"));
+        assert!(buf.is_empty());
+
+        buf.extend_from_slice(&input[input.len() - 3..]);
+        assert_eq!(codec.decode(&mut buf)?, Some(Value::from(1u64)));
+        assert!(buf.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_invalid() {
+        let mut codec = BencodeCodec::<Value>::new();
+        let mut buf = BytesMut::from(&b"i1x"[..]);
+        assert!(codec.decode(&mut buf).is_err());
+    }
+
+    #[test]
+    fn test_decode_max_frame_len() -> Result<()> {
+        let mut codec = BencodeCodec::<Value>::with_max_frame_len(8);
+        assert_eq!(codec.max_frame_len(), Some(8));
+        let mut buf = BytesMut::from(&b"l4:spam"[..]);
+        assert!(codec.decode(&mut buf)?.is_none());
+        buf.extend_from_slice(b"e3:ab");
+        assert_eq!(codec.decode(&mut buf)?, Some(Value::from(vec!["spam"])));
+        assert!(codec.decode(&mut buf)?.is_none());
+
+        let mut codec = BencodeCodec::<Value>::with_max_frame_len(8);
+        let mut buf = BytesMut::from(&b"99999999999:"[..]);
+        match codec.decode(&mut buf) {
+            Err(Error::Deserialize(msg)) => {
+                assert_eq!(msg, "value is longer than the maximum length of 8 bytes");
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let mut buf = BytesMut::from(&b"l4:spam4:eggse"[..]);
+        assert!(codec.decode(&mut buf).is_err());
+        assert_eq!(BencodeCodec::<Value>::new().max_frame_len(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_max_depth() -> Result<()> {
+        let mut codec = BencodeCodec::<Value>::new().with_max_depth(2);
+        assert_eq!(codec.max_depth(), Some(2));
+        let mut buf = BytesMut::from(&b"llelee"[..]);
+        assert!(codec.decode(&mut buf)?.is_some());
+
+        let mut buf = BytesMut::from(&[b'l'; 100_000][..]);
+        assert!(codec.decode(&mut buf).is_err());
+        assert_eq!(codec.max_depth(), Some(2));
+        assert_eq!(BencodeCodec::<Value>::new().max_depth(), None);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_framed() -> Result<()> {
+        let mut output = Vec::new();
+        let mut sink = FramedWrite::new(&mut output, BencodeCodec::<Value>::new());
+        sink.send(("spam", 1)).await?;
+        sink.send(("eggs", 2)).await?;
+        drop(sink);
+        assert_eq!(output, b"l4:spami1eel4:eggsi2ee");
+
+        let mut stream = FramedRead::new(&output[..], BencodeCodec::<(String, u32)>::new());
+        assert_eq!(
+            stream.next().await.transpose()?,
+            Some((String::from("spam"), 1))
+        );
+        assert_eq!(
+            stream.next().await.transpose()?,
+            Some((String::from("eggs"), 2))
+        );
+        assert_eq!(stream.next().await.transpose()?, None);
+        Ok(())
+    }
+}
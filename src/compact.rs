@@ -0,0 +1,560 @@
+//! Compact peer info used in tracker responses and DHT `get_peers` replies.
+//!
+//! An IPv4 peer is encoded as a 4 byte address followed by a 2 byte port, and
+//! an IPv6 peer as a 16 byte address followed by a 2 byte port. Both are in
+//! network byte order.
+//!
+//! The [`peers`] and [`peers6`] modules can be used with `#[serde(with = "...")]`
+//! for fields which are a single byte string of concatenated peers, and
+//! [`values`] for a list of byte strings with one peer each. The [`addr`]
+//! module is for fields with a single address of any type implementing
+//! [`CompactAddr`], including raw 4 or 16 byte IP addresses such as the
+//! `yourip` field of the extended handshake.
+//!
+//! ```rust
+//! use serde_derive::Deserialize;
+//! use std::net::SocketAddrV4;
+//!
+//! #[derive(Deserialize)]
+//! struct Announce {
+//!     #[serde(with = "bt_bencode::compact::peers")]
+//!     peers: Vec<SocketAddrV4>,
+//! }
+//!
+//! let resp: Announce = bt_bencode::from_slice(b"d5:peers6:\xc0\x00\x02\x01\x1a\xe1e")?;
+//!
+//! assert_eq!(resp.peers, vec!["192.0.2.1:6881".parse::<SocketAddrV4>().unwrap()]);
+//! # Ok::<(), bt_bencode::Error>(())
+//! ```
+//!
+//! Trackers may return the `peers` field as either compact peer info or a list
+//! of peer dictionaries. Both forms can be accepted with an untagged enum:
+//!
+//! ```rust
+//! use serde_derive::{Deserialize, Serialize};
+//! use std::net::SocketAddrV4;
+//!
+//! #[derive(Debug, PartialEq, Deserialize, Serialize)]
+//! struct Peer {
+//!     ip: String,
+//!     port: u16,
+//! }
+//!
+//! #[derive(Debug, PartialEq, Deserialize, Serialize)]
+//! #[serde(untagged)]
+//! enum Peers {
+//!     Compact(#[serde(with = "bt_bencode::compact::peers")] Vec<SocketAddrV4>),
+//!     List(Vec<Peer>),
+//! }
+//!
+//! let compact: Peers = bt_bencode::from_slice(b"6:\xc0\x00\x02\x01\x1a\xe1")?;
+//! assert_eq!(compact, Peers::Compact(vec!["192.0.2.1:6881".parse().unwrap()]));
+//!
+//! let list: Peers = bt_bencode::from_slice(b"ld2:ip9:192.0.2.14:porti6881eee")?;
+//! assert_eq!(list, Peers::List(vec![Peer { ip: String::from("192.0.2.1"), port: 6881 }]));
+//! assert_eq!(bt_bencode::to_vec(&list)?, b"ld2:ip9:192.0.2.14:porti6881eee");
+//! # Ok::<(), bt_bencode::Error>(())
+//! ```
+
+use crate::error::{Error, Result};
+use core::convert::TryFrom;
+use std::{
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6},
+    string::String,
+    vec::Vec,
+};
+
+/// The length of a compact IPv4 peer.
+pub const COMPACT_PEER_LEN: usize = 6;
+
+/// The length of a compact IPv6 peer.
+pub const COMPACT_PEER6_LEN: usize = 18;
+
+fn invalid_length(len: usize, expected: usize) -> Error {
+    Error::Deserialize(format!(
+        "compact peer info length {} is not a multiple of {}",
+        len, expected
+    ))
+}
+
+fn peer_v4(bytes: &[u8]) -> SocketAddrV4 {
+    let ip = Ipv4Addr::new(bytes[0], bytes[1], bytes[2], bytes[3]);
+    SocketAddrV4::new(ip, u16::from_be_bytes([bytes[4], bytes[5]]))
+}
+
+fn peer_v6(bytes: &[u8]) -> SocketAddrV6 {
+    let ip = <[u8; 16]>::try_from(&bytes[..16]).expect("peer is 18 bytes");
+    SocketAddrV6::new(
+        Ipv6Addr::from(ip),
+        u16::from_be_bytes([bytes[16], bytes[17]]),
+        0,
+        0,
+    )
+}
+
+/// Decodes concatenated compact IPv4 peers.
+///
+/// # Errors
+///
+/// An error is returned if the length is not a multiple of 6.
+pub fn decode_peers(bytes: &[u8]) -> Result<Vec<SocketAddrV4>> {
+    if bytes.len() % COMPACT_PEER_LEN != 0 {
+        return Err(invalid_length(bytes.len(), COMPACT_PEER_LEN));
+    }
+    Ok(bytes.chunks_exact(COMPACT_PEER_LEN).map(peer_v4).collect())
+}
+
+/// Decodes concatenated compact IPv6 peers.
+///
+/// # Errors
+///
+/// An error is returned if the length is not a multiple of 18.
+pub fn decode_peers6(bytes: &[u8]) -> Result<Vec<SocketAddrV6>> {
+    if bytes.len() % COMPACT_PEER6_LEN != 0 {
+        return Err(invalid_length(bytes.len(), COMPACT_PEER6_LEN));
+    }
+    Ok(bytes.chunks_exact(COMPACT_PEER6_LEN).map(peer_v6).collect())
+}
+
+/// Decodes a single compact peer of either address family.
+///
+/// # Errors
+///
+/// An error is returned if the length is not 6 or 18.
+pub fn decode_peer(bytes: &[u8]) -> Result<SocketAddr> {
+    match bytes.len() {
+        COMPACT_PEER_LEN => Ok(SocketAddr::V4(peer_v4(bytes))),
+        COMPACT_PEER6_LEN => Ok(SocketAddr::V6(peer_v6(bytes))),
+        len => Err(Error::Deserialize(format!(
+            "invalid compact peer length {}",
+            len
+        ))),
+    }
+}
+
+/// Encodes IPv4 peers as concatenated compact peers.
+#[must_use]
+pub fn encode_peers(peers: &[SocketAddrV4]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(peers.len() * COMPACT_PEER_LEN);
+    for peer in peers {
+        out.extend_from_slice(&peer.ip().octets());
+        out.extend_from_slice(&peer.port().to_be_bytes());
+    }
+    out
+}
+
+/// Encodes IPv6 peers as concatenated compact peers.
+#[must_use]
+pub fn encode_peers6(peers: &[SocketAddrV6]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(peers.len() * COMPACT_PEER6_LEN);
+    for peer in peers {
+        out.extend_from_slice(&peer.ip().octets());
+        out.extend_from_slice(&peer.port().to_be_bytes());
+    }
+    out
+}
+
+/// Encodes a single peer of either address family.
+#[must_use]
+pub fn encode_peer(peer: &SocketAddr) -> Vec<u8> {
+    match peer {
+        SocketAddr::V4(peer) => encode_peers(core::slice::from_ref(peer)),
+        SocketAddr::V6(peer) => encode_peers6(core::slice::from_ref(peer)),
+    }
+}
+
+fn invalid_ip(len: usize) -> Error {
+    Error::Deserialize(format!("invalid IP address length {}", len))
+}
+
+/// Decodes a 4 byte IPv4 address or a 16 byte IPv6 address.
+///
+/// # Errors
+///
+/// An error is returned if the length is not 4 or 16.
+pub fn decode_ip(bytes: &[u8]) -> Result<IpAddr> {
+    match bytes.len() {
+        4 => Ok(IpAddr::V4(Ipv4Addr::new(
+            bytes[0], bytes[1], bytes[2], bytes[3],
+        ))),
+        16 => {
+            let ip = <[u8; 16]>::try_from(bytes).expect("address is 16 bytes");
+            Ok(IpAddr::V6(Ipv6Addr::from(ip)))
+        }
+        len => Err(invalid_ip(len)),
+    }
+}
+
+/// Encodes an IP address as 4 or 16 bytes.
+#[must_use]
+pub fn encode_ip(ip: &IpAddr) -> Vec<u8> {
+    match ip {
+        IpAddr::V4(ip) => ip.octets().to_vec(),
+        IpAddr::V6(ip) => ip.octets().to_vec(),
+    }
+}
+
+/// An address with a compact byte string encoding.
+///
+/// Socket addresses are encoded as compact peers and IP addresses as their 4
+/// or 16 byte octets.
+pub trait CompactAddr: Sized {
+    /// Encodes the address.
+    fn to_compact(&self) -> Vec<u8>;
+
+    /// Decodes the address.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the bytes are not a valid encoding of the address type.
+    fn from_compact(bytes: &[u8]) -> Result<Self>;
+}
+
+impl CompactAddr for SocketAddr {
+    fn to_compact(&self) -> Vec<u8> {
+        encode_peer(self)
+    }
+
+    fn from_compact(bytes: &[u8]) -> Result<Self> {
+        decode_peer(bytes)
+    }
+}
+
+impl CompactAddr for SocketAddrV4 {
+    fn to_compact(&self) -> Vec<u8> {
+        encode_peers(core::slice::from_ref(self))
+    }
+
+    fn from_compact(bytes: &[u8]) -> Result<Self> {
+        match decode_peer(bytes)? {
+            SocketAddr::V4(peer) => Ok(peer),
+            SocketAddr::V6(_) => Err(Error::Deserialize(String::from(
+                "expected a compact IPv4 peer",
+            ))),
+        }
+    }
+}
+
+impl CompactAddr for SocketAddrV6 {
+    fn to_compact(&self) -> Vec<u8> {
+        encode_peers6(core::slice::from_ref(self))
+    }
+
+    fn from_compact(bytes: &[u8]) -> Result<Self> {
+        match decode_peer(bytes)? {
+            SocketAddr::V6(peer) => Ok(peer),
+            SocketAddr::V4(_) => Err(Error::Deserialize(String::from(
+                "expected a compact IPv6 peer",
+            ))),
+        }
+    }
+}
+
+impl CompactAddr for IpAddr {
+    fn to_compact(&self) -> Vec<u8> {
+        encode_ip(self)
+    }
+
+    fn from_compact(bytes: &[u8]) -> Result<Self> {
+        decode_ip(bytes)
+    }
+}
+
+impl CompactAddr for Ipv4Addr {
+    fn to_compact(&self) -> Vec<u8> {
+        self.octets().to_vec()
+    }
+
+    fn from_compact(bytes: &[u8]) -> Result<Self> {
+        match decode_ip(bytes)? {
+            IpAddr::V4(ip) => Ok(ip),
+            IpAddr::V6(_) => Err(invalid_ip(bytes.len())),
+        }
+    }
+}
+
+impl CompactAddr for Ipv6Addr {
+    fn to_compact(&self) -> Vec<u8> {
+        self.octets().to_vec()
+    }
+
+    fn from_compact(bytes: &[u8]) -> Result<Self> {
+        match decode_ip(bytes)? {
+            IpAddr::V6(ip) => Ok(ip),
+            IpAddr::V4(_) => Err(invalid_ip(bytes.len())),
+        }
+    }
+}
+
+/// Serializes and deserializes a single [`CompactAddr`] as a byte string.
+///
+/// ```rust
+/// use serde_derive::{Deserialize, Serialize};
+/// use std::net::{IpAddr, SocketAddr};
+///
+/// #[derive(Debug, PartialEq, Serialize, Deserialize)]
+/// struct Node {
+///     #[serde(with = "bt_bencode::compact::addr")]
+///     addr: SocketAddr,
+///     #[serde(
+///         default,
+///         with = "bt_bencode::compact::addr::option",
+///         skip_serializing_if = "Option::is_none"
+///     )]
+///     yourip: Option<IpAddr>,
+/// }
+///
+/// let input = b"d4:addr6:\xc0\x00\x02\x01\x1a\xe16:yourip4:\xc0\x00\x02\x02e";
+/// let node: Node = bt_bencode::from_slice(input)?;
+/// assert_eq!(node.addr, "192.0.2.1:6881".parse().unwrap());
+/// assert_eq!(node.yourip, Some("192.0.2.2".parse().unwrap()));
+/// assert_eq!(bt_bencode::to_vec(&node)?, &input[..]);
+/// # Ok::<(), bt_bencode::Error>(())
+/// ```
+pub mod addr {
+    use super::CompactAddr;
+    use serde::{de, Deserialize, Deserializer, Serializer};
+    use serde_bytes::ByteBuf;
+
+    /// Serializes the address as a byte string.
+    pub fn serialize<T, S>(addr: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: CompactAddr,
+        S: Serializer,
+    {
+        serializer.serialize_bytes(&addr.to_compact())
+    }
+
+    /// Deserializes the address from a byte string.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the value is not a byte string with a valid
+    /// encoding of the address type.
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: CompactAddr,
+        D: Deserializer<'de>,
+    {
+        let bytes = ByteBuf::deserialize(deserializer)?;
+        T::from_compact(&bytes).map_err(de::Error::custom)
+    }
+
+    /// Serializes and deserializes an optional [`CompactAddr`] as a byte string.
+    ///
+    /// Use with `#[serde(default, skip_serializing_if = "Option::is_none")]`.
+    pub mod option {
+        use super::CompactAddr;
+        use serde::{Deserializer, Serializer};
+
+        /// Serializes the address as a byte string.
+        pub fn serialize<T, S>(addr: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            T: CompactAddr,
+            S: Serializer,
+        {
+            match addr {
+                Some(addr) => super::serialize(addr, serializer),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        /// Deserializes the address from a byte string.
+        ///
+        /// # Errors
+        ///
+        /// An error is returned if the value is not a byte string with a valid
+        /// encoding of the address type.
+        pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+        where
+            T: CompactAddr,
+            D: Deserializer<'de>,
+        {
+            super::deserialize(deserializer).map(Some)
+        }
+    }
+}
+
+/// Serializes and deserializes a `Vec<SocketAddrV4>` as concatenated compact peers.
+pub mod peers {
+    use serde::{de, Deserialize, Deserializer, Serializer};
+    use serde_bytes::ByteBuf;
+    use std::{net::SocketAddrV4, vec::Vec};
+
+    /// Serializes the peers as a byte string.
+    pub fn serialize<S>(peers: &[SocketAddrV4], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_bytes(&super::encode_peers(peers))
+    }
+
+    /// Deserializes the peers from a byte string.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the value is not a byte string with a length
+    /// which is a multiple of 6.
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<SocketAddrV4>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let bytes = ByteBuf::deserialize(deserializer)?;
+        super::decode_peers(&bytes).map_err(de::Error::custom)
+    }
+}
+
+/// Serializes and deserializes a `Vec<SocketAddrV6>` as concatenated compact peers.
+pub mod peers6 {
+    use serde::{de, Deserialize, Deserializer, Serializer};
+    use serde_bytes::ByteBuf;
+    use std::{net::SocketAddrV6, vec::Vec};
+
+    /// Serializes the peers as a byte string.
+    pub fn serialize<S>(peers: &[SocketAddrV6], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_bytes(&super::encode_peers6(peers))
+    }
+
+    /// Deserializes the peers from a byte string.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the value is not a byte string with a length
+    /// which is a multiple of 18.
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<SocketAddrV6>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let bytes = ByteBuf::deserialize(deserializer)?;
+        super::decode_peers6(&bytes).map_err(de::Error::custom)
+    }
+}
+
+/// Serializes and deserializes a `Vec<SocketAddr>` as a list of compact peers.
+///
+/// This is the encoding of the `values` field in a DHT `get_peers` response.
+pub mod values {
+    use serde::{de, ser::SerializeSeq, Deserialize, Deserializer, Serializer};
+    use serde_bytes::{ByteBuf, Bytes};
+    use std::{net::SocketAddr, vec::Vec};
+
+    /// Serializes the peers as a list of byte strings.
+    pub fn serialize<S>(peers: &[SocketAddr], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut seq = serializer.serialize_seq(Some(peers.len()))?;
+        for peer in peers {
+            seq.serialize_element(Bytes::new(&super::encode_peer(peer)))?;
+        }
+        seq.end()
+    }
+
+    /// Deserializes the peers from a list of byte strings.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the value is not a list of 6 or 18 byte strings.
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<SocketAddr>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Vec::<ByteBuf>::deserialize(deserializer)?
+            .iter()
+            .map(|bytes| super::decode_peer(bytes).map_err(de::Error::custom))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_peers() -> Result<()> {
+        let bytes = [192, 0, 2, 1, 0x1a, 0xe1, 10, 0, 0, 1, 0, 80];
+        let peers = decode_peers(&bytes)?;
+        assert_eq!(
+            peers,
+            vec![
+                "192.0.2.1:6881".parse::<SocketAddrV4>().unwrap(),
+                "10.0.0.1:80".parse().unwrap()
+            ]
+        );
+        assert_eq!(encode_peers(&peers), bytes);
+        assert!(decode_peers(&bytes[..5]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_peers6() -> Result<()> {
+        let mut bytes = vec![0x20, 0x01, 0x0d, 0xb8];
+        bytes.extend_from_slice(&[0; 11]);
+        bytes.extend_from_slice(&[1, 0x1a, 0xe1]);
+        let peers = decode_peers6(&bytes)?;
+        assert_eq!(
+            peers,
+            vec!["[2001:db8::1]:6881".parse::<SocketAddrV6>().unwrap()]
+        );
+        assert_eq!(encode_peers6(&peers), bytes);
+        assert!(decode_peers6(&bytes[..17]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_adapters() -> Result<()> {
+        #[derive(Debug, PartialEq, serde_derive::Serialize, serde_derive::Deserialize)]
+        struct Peers {
+            #[serde(with = "peers")]
+            peers: Vec<SocketAddrV4>,
+            #[serde(with = "peers6")]
+            peers6: Vec<SocketAddrV6>,
+            #[serde(with = "values")]
+            values: Vec<SocketAddr>,
+        }
+
+        let input = b"d5:peers6:\xc0\x00\x02\x01\x1a\xe16:peers60:6:valuesl6:\x0a\x00\x00\x01\x00\x5018:\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x01\x00\x50ee";
+        let peers: Peers = crate::from_slice(input)?;
+        assert_eq!(
+            peers,
+            Peers {
+                peers: vec!["192.0.2.1:6881".parse().unwrap()],
+                peers6: vec![],
+                values: vec!["10.0.0.1:80".parse().unwrap(), "[::1]:80".parse().unwrap()],
+            }
+        );
+        assert_eq!(crate::to_vec(&peers)?, &input[..]);
+
+        assert!(crate::from_slice::<Peers>(b"d5:peers5:aaaaa6:peers60:6:valuesleee").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_addrs() -> Result<()> {
+        let v4: SocketAddrV4 = "192.0.2.1:6881".parse().unwrap();
+        let v6: SocketAddrV6 = "[2001:db8::1]:6881".parse().unwrap();
+        assert_eq!(SocketAddrV4::from_compact(&v4.to_compact())?, v4);
+        assert_eq!(SocketAddrV6::from_compact(&v6.to_compact())?, v6);
+        assert_eq!(
+            SocketAddr::from_compact(&v6.to_compact())?,
+            SocketAddr::V6(v6)
+        );
+        assert!(SocketAddrV4::from_compact(&v6.to_compact()).is_err());
+        assert!(SocketAddrV6::from_compact(&v4.to_compact()).is_err());
+
+        let ip: IpAddr = "2001:db8::1".parse().unwrap();
+        assert_eq!(ip.to_compact().len(), 16);
+        assert_eq!(IpAddr::from_compact(&ip.to_compact())?, ip);
+        assert_eq!(
+            Ipv4Addr::from_compact(&[192, 0, 2, 1])?,
+            Ipv4Addr::new(192, 0, 2, 1)
+        );
+        assert!(Ipv4Addr::from_compact(&ip.to_compact()).is_err());
+        assert!(Ipv6Addr::from_compact(&[192, 0, 2, 1]).is_err());
+        assert!(IpAddr::from_compact(&[0; 5]).is_err());
+        Ok(())
+    }
+}
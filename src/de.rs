@@ -1,13 +1,18 @@
 //! Deserializes Bencode data.
 
 use crate::error::{Error, Result};
+use crate::event::{Event, Parser};
 use crate::read::{self, Read, Ref};
+use crate::value::{pretty::PrettyPrinter, Number};
+use core::cell::Cell;
+#[cfg(feature = "big-int")]
+use serde::de::IntoDeserializer as _;
 use serde::de::{self, Expected, Unexpected};
 
 #[cfg(all(feature = "alloc", not(feature = "std")))]
-use alloc::vec::Vec;
+use alloc::{collections::BTreeSet, format, string::String, vec::Vec};
 #[cfg(feature = "std")]
-use std::{io, vec::Vec};
+use std::{collections::BTreeSet, io, string::String, vec::Vec};
 
 /// Deserializes an instance of `T` from the bytes of an [`io::Read`] type.
 ///
@@ -27,6 +32,628 @@ where
     Ok(value)
 }
 
+/// Deserializes an instance of `T` from a [`bytes::Buf`] type.
+///
+/// The buffer can be made of non-contiguous chunks, such as a chain of
+/// received frames.
+///
+/// ```rust
+/// use bytes::Buf;
+///
+/// let frames = (&b"d4:name"[..]).chain(&b"4:spame"[..]);
+/// let value: bt_bencode::Value = bt_bencode::from_buf(frames)?;
+/// assert_eq!(value["name"].as_str(), Some("spam"));
+/// # Ok::<(), bt_bencode::Error>(())
+/// ```
+///
+/// # Errors
+///
+/// Deserialization can fail if the data is not valid, if the data cannot be deserialized into an
+/// instance of `T`, or if there is trailing data.
+#[cfg(feature = "bytes")]
+pub fn from_buf<B, T>(buf: B) -> Result<T>
+where
+    B: bytes::Buf,
+    T: de::DeserializeOwned,
+{
+    let mut de = Deserializer::from_buf(buf);
+    let value = T::deserialize(&mut de)?;
+    de.end()?;
+    Ok(value)
+}
+
+/// Deserializes an instance of `T` from the next value of an
+/// [`AsyncBufRead`][tokio::io::AsyncBufRead] type.
+///
+/// The data is read in chunks and checked as it is read, so a syntax error
+/// is reported without reading the rest of the source. Only the bytes of the
+/// value are consumed, so the next value can be read from the same source
+/// (e.g. a TCP stream of messages wrapped in a
+/// [`BufReader`][tokio::io::BufReader]). Deserialization in serde is
+/// synchronous, so the encoded value is buffered in memory and `T` is
+/// deserialized once the end of the value is read.
+///
+/// ```rust
+/// # async fn run() -> bt_bencode::Result<()> {
+/// let mut input: &[u8] = b"4:spami42e";
+/// let s: String = bt_bencode::from_async_reader(&mut input).await?;
+/// let n: u64 = bt_bencode::from_async_reader(&mut input).await?;
+/// assert_eq!((s.as_str(), n), ("spam", 42));
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// Deserialization can fail if the data is not valid, if the data cannot be deserialized
+/// into an instance of `T`, if the source ends before the value is complete, and other IO
+/// errors.
+#[cfg(feature = "tokio")]
+pub async fn from_async_reader<R, T>(mut r: R) -> Result<T>
+where
+    R: tokio::io::AsyncBufRead + Unpin,
+    T: de::DeserializeOwned,
+{
+    use tokio::io::AsyncBufReadExt;
+
+    use crate::incremental::{Incremental, Status};
+
+    let mut buf = Vec::new();
+    let mut parser = Incremental::new();
+
+    loop {
+        let chunk = r.fill_buf().await.map_err(Error::IoError)?;
+        if chunk.is_empty() {
+            return Err(Error::EofWhileParsingValue);
+        }
+        match parser.feed(chunk)? {
+            Status::NeedMore => {
+                let n = chunk.len();
+                buf.extend_from_slice(chunk);
+                r.consume(n);
+            }
+            Status::Complete(len) if buf.is_empty() => {
+                let value = from_slice(&chunk[..len]);
+                r.consume(len);
+                return value;
+            }
+            Status::Complete(len) => {
+                buf.extend_from_slice(&chunk[..len]);
+                r.consume(len);
+                return from_slice(&buf);
+            }
+        }
+    }
+}
+
+/// Returns a [Stream][futures_core::Stream] of `T` values deserialized from an
+/// [`AsyncRead`][tokio::io::AsyncRead] type.
+///
+/// The source is expected to contain consecutive Bencode values without any
+/// additional framing (e.g. a TCP stream of messages). Data is only read from
+/// the source when the next value is polled.
+///
+/// ```rust
+/// # async fn run() -> bt_bencode::Result<()> {
+/// use futures::StreamExt;
+///
+/// let input: &[u8] = b"d1:y1:qed1:y1:re";
+/// let mut messages = bt_bencode::into_stream::<_, bt_bencode::Value>(input);
+///
+/// while let Some(msg) = messages.next().await {
+///     assert!(msg?["y"].is_byte_str());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "futures")]
+pub fn into_stream<R, T>(reader: R) -> ValueStream<R, T>
+where
+    R: tokio::io::AsyncRead + Unpin,
+    T: de::DeserializeOwned,
+{
+    ValueStream {
+        reader,
+        buf: Vec::new(),
+        filled: 0,
+        scanned: 0,
+        max_frame_len: None,
+        parser: crate::incremental::Incremental::new(),
+        is_done: false,
+        phantom: core::marker::PhantomData,
+    }
+}
+
+/// A [Stream][futures_core::Stream] of values deserialized from an
+/// [`AsyncRead`][tokio::io::AsyncRead] type.
+///
+/// See [`into_stream()`].
+#[cfg(feature = "futures")]
+#[derive(Debug)]
+pub struct ValueStream<R, T> {
+    reader: R,
+    /// The read buffer. Only the first `filled` bytes hold data; the rest is
+    /// kept initialized so it can be reused by the next read.
+    buf: Vec<u8>,
+    filled: usize,
+    scanned: usize,
+    max_frame_len: Option<usize>,
+    parser: crate::incremental::Incremental,
+    is_done: bool,
+    phantom: core::marker::PhantomData<fn() -> T>,
+}
+
+#[cfg(feature = "futures")]
+impl<R, T> ValueStream<R, T> {
+    /// The number of bytes to read from the source at a time.
+    const CHUNK_LEN: usize = 8 * 1024;
+
+    /// Limits the length of each value to `max_frame_len` bytes.
+    ///
+    /// By default, the read buffer grows until a value is complete, so a peer
+    /// which never finishes a value can use an unbounded amount of memory.
+    /// With a limit, an error is returned as soon as more than
+    /// `max_frame_len` bytes of a value have been read and the stream ends.
+    /// The limit is checked the same way as the maximum frame length of
+    /// `codec::BencodeCodec`.
+    ///
+    /// ```rust
+    /// # async fn run() -> bt_bencode::Result<()> {
+    /// use futures::StreamExt;
+    ///
+    /// let input: &[u8] = b"d1:y1:qe30:012345678901234567890123456789";
+    /// let mut messages =
+    ///     bt_bencode::into_stream::<_, bt_bencode::Value>(input).with_max_frame_len(16);
+    ///
+    /// assert!(messages.next().await.unwrap().is_ok());
+    /// assert!(messages.next().await.unwrap().is_err());
+    /// assert!(messages.next().await.is_none());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn with_max_frame_len(mut self, max_frame_len: usize) -> Self {
+        self.max_frame_len = Some(max_frame_len);
+        self
+    }
+
+    /// Returns the maximum length of a value, if any.
+    #[must_use]
+    pub fn max_frame_len(&self) -> Option<usize> {
+        self.max_frame_len
+    }
+
+    /// Limits the number of nested lists and dictionaries in each value.
+    ///
+    /// If a value is nested deeper, an error is returned and the stream
+    /// ends. See [`Incremental::with_max_depth()`][crate::incremental::Incremental::with_max_depth]
+    /// for how the depth is counted.
+    #[must_use]
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.parser = self.parser.with_max_depth(max_depth);
+        self
+    }
+
+    /// Returns the maximum number of nested lists and dictionaries, if any.
+    #[must_use]
+    pub fn max_depth(&self) -> Option<usize> {
+        self.parser.max_depth()
+    }
+
+    /// Returns the inner reader.
+    ///
+    /// Any bytes which were read but not yet deserialized are discarded.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<R, T> futures_core::Stream for ValueStream<R, T>
+where
+    R: tokio::io::AsyncRead + Unpin,
+    T: de::DeserializeOwned,
+{
+    type Item = Result<T>;
+
+    fn poll_next(
+        self: core::pin::Pin<&mut Self>,
+        cx: &mut core::task::Context<'_>,
+    ) -> core::task::Poll<Option<Self::Item>> {
+        use crate::incremental::Status;
+        use core::{pin::Pin, task::Poll};
+
+        let this = self.get_mut();
+        loop {
+            if this.scanned < this.filled {
+                let status = this
+                    .parser
+                    .feed(&this.buf[this.scanned..this.filled])
+                    .and_then(|status| {
+                        let len = match status {
+                            Status::NeedMore => this.filled,
+                            Status::Complete(n) => this.scanned + n,
+                        };
+                        match this.max_frame_len {
+                            Some(max_frame_len) if len > max_frame_len => {
+                                Err(crate::incremental::frame_too_long(max_frame_len))
+                            }
+                            _ => Ok((status, len)),
+                        }
+                    });
+                match status {
+                    Ok((Status::NeedMore, len)) => this.scanned = len,
+                    Ok((Status::Complete(_), len)) => {
+                        let value = from_slice(&this.buf[..len]);
+                        this.buf.copy_within(len..this.filled, 0);
+                        this.filled -= len;
+                        this.scanned = 0;
+                        return Poll::Ready(Some(value));
+                    }
+                    Err(err) => {
+                        this.is_done = true;
+                        this.filled = 0;
+                        this.scanned = 0;
+                        return Poll::Ready(Some(Err(err)));
+                    }
+                }
+            }
+
+            if this.is_done {
+                return Poll::Ready(None);
+            }
+
+            // The buffer only grows when the unused space runs out, so the
+            // spare bytes are zeroed once instead of on every read.
+            if this.buf.len() - this.filled < Self::CHUNK_LEN {
+                this.buf.resize(this.filled + Self::CHUNK_LEN, 0);
+            }
+            let mut read_buf = tokio::io::ReadBuf::new(&mut this.buf[this.filled..]);
+            let result = Pin::new(&mut this.reader).poll_read(cx, &mut read_buf);
+            let n = read_buf.filled().len();
+            this.filled += n;
+
+            match result {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Err(err)) => {
+                    this.is_done = true;
+                    return Poll::Ready(Some(Err(Error::IoError(err))));
+                }
+                Poll::Ready(Ok(())) if n == 0 => {
+                    this.is_done = true;
+                    if this.filled != 0 {
+                        this.filled = 0;
+                        return Poll::Ready(Some(Err(Error::EofWhileParsingValue)));
+                    }
+                }
+                Poll::Ready(Ok(())) => {}
+            }
+        }
+    }
+}
+
+/// Returns an iterator over the consecutive Bencode values in a slice of bytes.
+///
+/// Each item is the span of a complete value in the slice and the value's
+/// bytes. If invalid or incomplete data is found, an error is returned and
+/// the iteration ends. Values before the invalid data can still be used
+/// (e.g. to salvage the leading message in a datagram with trailing garbage).
+///
+/// The values are found with an [`Incremental`][crate::incremental::Incremental]
+/// parser without recursion, so deeply nested data cannot overflow the stack.
+/// As with the parser, integers are not checked to be in range.
+///
+/// ```rust
+/// let datagram = b"d1:y1:qed1:y1:rexyz";
+/// let mut values = bt_bencode::raw_values(datagram);
+///
+/// assert_eq!(values.next().unwrap()?, (0..8, &b"d1:y1:qe"[..]));
+/// assert_eq!(values.next().unwrap()?, (8..16, &b"d1:y1:re"[..]));
+/// assert!(values.next().unwrap().is_err());
+/// assert!(values.next().is_none());
+/// # Ok::<(), bt_bencode::Error>(())
+/// ```
+pub fn raw_values(s: &[u8]) -> RawValues<'_> {
+    RawValues {
+        slice: s,
+        offset: 0,
+        is_done: false,
+    }
+}
+
+/// An iterator over the consecutive Bencode values in a slice of bytes.
+///
+/// See [`raw_values()`].
+#[derive(Debug, Clone)]
+pub struct RawValues<'a> {
+    slice: &'a [u8],
+    offset: usize,
+    is_done: bool,
+}
+
+impl<'a> Iterator for RawValues<'a> {
+    type Item = Result<(core::ops::Range<usize>, &'a [u8])>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.is_done || self.offset == self.slice.len() {
+            return None;
+        }
+
+        let rest = &self.slice[self.offset..];
+        let len =
+            crate::incremental::Incremental::new()
+                .feed(rest)
+                .and_then(|status| match status {
+                    crate::incremental::Status::Complete(len) => Ok(len),
+                    crate::incremental::Status::NeedMore => Err(Error::EofWhileParsingValue),
+                });
+        match len {
+            Ok(len) => {
+                let span = self.offset..self.offset + len;
+                self.offset += len;
+                Some(Ok((span, &rest[..len])))
+            }
+            Err(err) => {
+                self.is_done = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+/// A step in a path to a nested value for [`get_raw()`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PathSegment<'a> {
+    /// A dictionary key.
+    Key(&'a [u8]),
+    /// A list index.
+    Index(usize),
+}
+
+impl<'a> From<&'a [u8]> for PathSegment<'a> {
+    fn from(key: &'a [u8]) -> Self {
+        PathSegment::Key(key)
+    }
+}
+
+impl<'a> From<&'a str> for PathSegment<'a> {
+    fn from(key: &'a str) -> Self {
+        PathSegment::Key(key.as_bytes())
+    }
+}
+
+impl<'a> From<usize> for PathSegment<'a> {
+    fn from(index: usize) -> Self {
+        PathSegment::Index(index)
+    }
+}
+
+/// An owned [`PathSegment`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum PathSegmentBuf {
+    /// A dictionary key.
+    Key(Vec<u8>),
+    /// A list index.
+    Index(usize),
+}
+
+impl PathSegmentBuf {
+    /// Returns the segment as a borrowed [`PathSegment`].
+    #[must_use]
+    pub fn as_segment(&self) -> PathSegment<'_> {
+        match self {
+            PathSegmentBuf::Key(key) => PathSegment::Key(key),
+            PathSegmentBuf::Index(index) => PathSegment::Index(*index),
+        }
+    }
+}
+
+impl<'a> From<PathSegment<'a>> for PathSegmentBuf {
+    fn from(segment: PathSegment<'a>) -> Self {
+        match segment {
+            PathSegment::Key(key) => PathSegmentBuf::Key(key.to_vec()),
+            PathSegment::Index(index) => PathSegmentBuf::Index(index),
+        }
+    }
+}
+
+/// Returns the raw encoding of the value at a path of keys and indexes.
+///
+/// The path is walked over the encoded bytes. Sibling values are skipped
+/// without being decoded or recursed into (byte strings are skipped by their
+/// length prefix), and data after the target value is not read, so trailing
+/// data is not detected.
+///
+/// If a key or index is not found, or a step does not match the kind of
+/// value (e.g. a key for a list), `None` is returned. If a key is repeated,
+/// the first entry is used.
+///
+/// ```rust
+/// use bt_bencode::get_raw;
+///
+/// let torrent = b"d8:announce8:http://a4:infod5:filesld6:lengthi1eeeee";
+///
+/// assert_eq!(
+///     get_raw(&["info".into()], torrent)?,
+///     Some(&b"d5:filesld6:lengthi1eeee"[..])
+/// );
+/// assert_eq!(
+///     get_raw(&["info".into(), "files".into(), 0.into(), "length".into()], torrent)?,
+///     Some(&b"i1e"[..])
+/// );
+/// assert_eq!(get_raw(&["comment".into()], torrent)?, None);
+/// # Ok::<(), bt_bencode::Error>(())
+/// ```
+///
+/// # Errors
+///
+/// An error is returned if invalid data is found before the target value or
+/// in the target value.
+pub fn get_raw<'a>(path: &[PathSegment<'_>], s: &'a [u8]) -> Result<Option<&'a [u8]>> {
+    let mut read = read::SliceRead::new(s);
+    let mut buf = Vec::new();
+
+    for segment in path {
+        match (read.peek().ok_or(Error::EofWhileParsingValue)??, segment) {
+            (b'd', PathSegment::Key(key)) => {
+                read.next();
+                loop {
+                    match read.peek().ok_or(Error::EofWhileParsingValue)?? {
+                        b'e' => return Ok(None),
+                        b'0'..=b'9' => {}
+                        _ => return Err(Error::KeyMustBeAByteStr),
+                    }
+                    buf.clear();
+                    if *read.parse_byte_str(&mut buf)? == **key {
+                        break;
+                    }
+                    read.skip_value()?;
+                }
+            }
+            (b'l', PathSegment::Index(index)) => {
+                read.next();
+                for _ in 0..*index {
+                    if read.peek().ok_or(Error::EofWhileParsingValue)?? == b'e' {
+                        return Ok(None);
+                    }
+                    read.skip_value()?;
+                }
+                if read.peek().ok_or(Error::EofWhileParsingValue)?? == b'e' {
+                    return Ok(None);
+                }
+            }
+            (b, _) if b == b'i' || b == b'l' || b == b'd' || b.is_ascii_digit() => return Ok(None),
+            _ => return Err(Error::ExpectedSomeValue),
+        }
+    }
+
+    let start_idx = read.byte_offset();
+    read.skip_value()?;
+    Ok(Some(&s[start_idx..read.byte_offset()]))
+}
+
+/// Counts of the values in encoded data returned by [`stats()`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// The number of integers.
+    pub ints: usize,
+    /// The number of byte string values, not including dictionary keys.
+    pub byte_strs: usize,
+    /// The number of dictionary keys.
+    pub keys: usize,
+    /// The number of lists.
+    pub lists: usize,
+    /// The number of dictionaries.
+    pub dicts: usize,
+    /// The deepest nesting of lists and dictionaries.
+    pub max_depth: usize,
+    /// The length of the longest byte string, including dictionary keys.
+    pub max_byte_str_len: usize,
+    /// The total length of all byte strings, including dictionary keys.
+    pub total_byte_str_len: usize,
+}
+
+/// Returns counts of the values in a single encoded value.
+///
+/// The data is scanned once without building a value tree, so the counts can
+/// be used to reject suspicious payloads (e.g. deeply nested or with a huge
+/// number of values) before deserializing them.
+///
+/// ```rust
+/// let stats = bt_bencode::stats(b"d1:ali1ei2ee1:bd1:c4:spamee")?;
+///
+/// assert_eq!(stats.ints, 2);
+/// assert_eq!(stats.byte_strs, 1);
+/// assert_eq!(stats.keys, 3);
+/// assert_eq!((stats.lists, stats.dicts), (1, 2));
+/// assert_eq!(stats.max_depth, 2);
+/// assert_eq!(stats.max_byte_str_len, 4);
+/// assert_eq!(stats.total_byte_str_len, 7);
+/// # Ok::<(), bt_bencode::Error>(())
+/// ```
+///
+/// # Errors
+///
+/// An error is returned if the data is not exactly one valid value.
+pub fn stats(s: &[u8]) -> Result<Stats> {
+    let mut stats = Stats::default();
+    let mut depth = 0_usize;
+    let mut parser = Parser::new(s);
+    for event in parser.by_ref() {
+        let (_, event) = event?;
+        match event {
+            Event::DictStart => {
+                stats.dicts += 1;
+                depth += 1;
+            }
+            Event::ListStart => {
+                stats.lists += 1;
+                depth += 1;
+            }
+            Event::Key(key) => {
+                stats.keys += 1;
+                stats.add_byte_str(key);
+            }
+            Event::ByteStr(value) => {
+                stats.byte_strs += 1;
+                stats.add_byte_str(value);
+            }
+            Event::Int(_) => stats.ints += 1,
+            Event::End => depth -= 1,
+        }
+        stats.max_depth = core::cmp::max(stats.max_depth, depth);
+    }
+    parser.end()?;
+    Ok(stats)
+}
+
+impl Stats {
+    fn add_byte_str(&mut self, bytes: &[u8]) {
+        self.max_byte_str_len = core::cmp::max(self.max_byte_str_len, bytes.len());
+        self.total_byte_str_len += bytes.len();
+    }
+}
+
+/// Renders a single encoded value as indented text for debugging.
+///
+/// The output is the same as [`to_string_pretty()`][crate::value::to_string_pretty]
+/// for the decoded value, except that dictionary entries are rendered in the
+/// order they are encoded. No value tree is built.
+///
+/// ```rust
+/// let s = bt_bencode::pretty_print(b"d1:ad2:id3:\xffab1:ti2ee1:y1:qe")?;
+/// assert_eq!(
+///     s,
+///     r#"{
+///   "a": {
+///     "id": "\xffab",
+///     "t": 2
+///   },
+///   "y": "q"
+/// }"#
+/// );
+/// # Ok::<(), bt_bencode::Error>(())
+/// ```
+///
+/// Every line is indented by its depth, so the length of the output grows
+/// with the square of the depth. Lists and dictionaries may be nested at
+/// most 128 levels deep. Use
+/// [`DeserializerBuilder::pretty_print()`][Builder::pretty_print] for a
+/// different limit.
+///
+/// # Errors
+///
+/// An error is returned if the data is not exactly one valid value or if the
+/// value is nested too deeply.
+pub fn pretty_print(s: &[u8]) -> Result<String> {
+    Builder::new()
+        .max_depth(PRETTY_PRINT_MAX_DEPTH)
+        .pretty_print(s)
+}
+
+/// The maximum depth of the values rendered by [`pretty_print()`].
+const PRETTY_PRINT_MAX_DEPTH: usize = 128;
+
 /// Deserializes an instance of `T` from a slice of bytes.
 ///
 /// # Errors
@@ -49,6 +676,18 @@ pub struct Deserializer<R> {
     read: R,
     /// Temporary buffer used to reduce allocations made
     buf: Vec<u8>,
+    options: Builder,
+    /// The number of lists and dictionaries currently being deserialized
+    depth: usize,
+    /// If the next parsed byte string should be copied into `key`
+    capture_key: bool,
+    /// The last dictionary key parsed while `capture_key` was set
+    key: Vec<u8>,
+    /// The path to the value being deserialized if ignored keys are tracked
+    path: Vec<PathSegmentBuf>,
+    /// If an ignored value is being deserialized
+    ignoring: bool,
+    ignored_keys: Vec<Vec<PathSegmentBuf>>,
 }
 
 impl<'a, R> Deserializer<R>
@@ -57,12 +696,88 @@ where
 {
     /// Constructs a Deserializer from a readable source.
     pub fn new(read: R) -> Self {
+        Deserializer::with_buffer(read, Vec::default())
+    }
+
+    /// Constructs a Deserializer from a readable source and an existing scratch buffer.
+    ///
+    /// The buffer is used to temporarily store data which cannot be borrowed
+    /// from the source. Reusing a buffer across many messages avoids
+    /// allocating a new buffer for every message. The existing contents of
+    /// the buffer are ignored.
+    pub fn with_buffer(read: R, buf: Vec<u8>) -> Self {
         Deserializer {
             read,
-            buf: Vec::default(),
+            buf,
+            options: Builder::new(),
+            depth: 0,
+            capture_key: false,
+            key: Vec::new(),
+            path: Vec::new(),
+            ignoring: false,
+            ignored_keys: Vec::new(),
         }
     }
 
+    /// Returns the paths to the dictionary keys which have been ignored.
+    ///
+    /// Keys are only tracked if enabled with
+    /// [`Builder::track_ignored_keys()`]. A key is ignored if the type being
+    /// deserialized skips its value, such as an unknown field in a struct
+    /// without `#[serde(deny_unknown_fields)]`. The keys in an ignored value
+    /// are not included, nor are keys dropped by types which buffer their
+    /// input (see [`Builder::deny_unknown_keys()`]).
+    ///
+    /// ```rust
+    /// use bt_bencode::{DeserializerBuilder, PathSegment};
+    /// use serde::Deserialize;
+    /// use serde_derive::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Info {
+    ///     length: u64,
+    /// }
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Metainfo {
+    ///     info: Info,
+    /// }
+    ///
+    /// let input = b"d7:comment2:hi4:infod6:lengthi1e4:name4:spamee";
+    /// let mut de = DeserializerBuilder::new()
+    ///     .track_ignored_keys(true)
+    ///     .build_from_slice(input);
+    /// let metainfo = Metainfo::deserialize(&mut de)?;
+    /// de.end()?;
+    ///
+    /// assert_eq!(metainfo.info.length, 1);
+    /// let ignored: Vec<Vec<PathSegment<'_>>> = de
+    ///     .ignored_keys()
+    ///     .iter()
+    ///     .map(|path| path.iter().map(|s| s.as_segment()).collect())
+    ///     .collect();
+    /// assert_eq!(
+    ///     ignored,
+    ///     [
+    ///         vec![PathSegment::Key(b"comment")],
+    ///         vec![PathSegment::Key(b"info"), PathSegment::Key(b"name")],
+    ///     ]
+    /// );
+    /// # Ok::<(), bt_bencode::Error>(())
+    /// ```
+    #[must_use]
+    pub fn ignored_keys(&self) -> &[Vec<PathSegmentBuf>] {
+        &self.ignored_keys
+    }
+
+    /// Returns the scratch buffer so it can be reused by another Deserializer.
+    ///
+    /// The contents of the returned buffer are not specified.
+    #[inline]
+    pub fn into_buffer(self) -> Vec<u8> {
+        self.buf
+    }
+
     /// Should be called after a value from the source is deserialized to validate that the entire
     /// source was read.
     ///
@@ -99,8 +814,7 @@ where
     fn unexpected_type_err(&mut self, exp: &dyn Expected) -> Result<Error> {
         match self.parse_peek()? {
             b'0'..=b'9' => {
-                self.buf.clear();
-                let bytes = self.read.parse_byte_str(&mut self.buf)?;
+                let bytes = self.parse_byte_str()?;
                 Ok(de::Error::invalid_type(Unexpected::Bytes(&bytes), exp))
             }
             b'i' => {
@@ -134,6 +848,65 @@ where
     fn parse_next(&mut self) -> Result<u8> {
         self.read.next().ok_or(Error::EofWhileParsingValue)?
     }
+
+    fn parse_byte_str(&mut self) -> Result<Ref<'a, '_, [u8]>> {
+        self.buf.clear();
+        let bytes = match self.options.max_byte_str_len {
+            Some(max_len) => self.read.parse_byte_str_max_len(&mut self.buf, max_len)?,
+            None => self.read.parse_byte_str(&mut self.buf)?,
+        };
+        if self.capture_key {
+            self.capture_key = false;
+            self.key.clear();
+            self.key.extend_from_slice(&bytes);
+        }
+        Ok(bytes)
+    }
+
+    fn parse_integer(&mut self) -> Result<Ref<'a, '_, str>> {
+        self.buf.clear();
+        let num_str = self.read.parse_integer(&mut self.buf)?;
+        if self.options.canonical_integers
+            && num_str.len() > 1
+            && (num_str.starts_with('0') || num_str.starts_with("-0"))
+        {
+            return Err(Error::InvalidInteger);
+        }
+        Ok(num_str)
+    }
+
+    /// Should be called before the values in a list or dictionary are deserialized.
+    fn enter(&mut self) -> Result<()> {
+        if let Some(max_depth) = self.options.max_depth {
+            if self.depth >= max_depth {
+                return Err(Error::Deserialize(String::from(
+                    "maximum nesting depth exceeded",
+                )));
+            }
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    /// Deserializes a list, or returns an error for any other value.
+    fn deserialize_list<V>(&mut self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'a>,
+    {
+        match self.parse_peek()? {
+            b'l' => {
+                self.parse_next()?;
+                self.enter()?;
+                let ret = visitor.visit_seq(SeqAccess::new(self));
+                self.depth -= 1;
+                match (ret, self.on_end_seq()) {
+                    (Ok(ret), Ok(())) => Ok(ret),
+                    (Err(err), _) | (_, Err(err)) => Err(err),
+                }
+            }
+            _ => Err(self.unexpected_type_err(&visitor)?),
+        }
+    }
 }
 
 #[cfg(feature = "std")]
@@ -146,6 +919,44 @@ where
     pub fn from_reader(reader: R) -> Self {
         Deserializer::new(read::IoRead::new(reader))
     }
+
+    /// Constructs a Deserializer from an [`std::io::Read`][std::io::Read] source and an existing
+    /// scratch buffer.
+    ///
+    /// Useful in long-lived loops which read many messages. See
+    /// [`Deserializer::with_buffer()`] and [`Deserializer::into_buffer()`].
+    ///
+    /// ```rust
+    /// use bt_bencode::{Deserializer, Value};
+    /// use serde::Deserialize;
+    ///
+    /// let mut input: &[u8] = b"d1:y1:qed1:y1:re";
+    /// let mut buf = Vec::new();
+    ///
+    /// for expected in &["q", "r"] {
+    ///     let mut de = Deserializer::from_reader_with_buffer(&mut input, buf);
+    ///     let msg = Value::deserialize(&mut de)?;
+    ///     assert_eq!(msg["y"].as_str(), Some(*expected));
+    ///     buf = de.into_buffer();
+    /// }
+    /// # Ok::<(), bt_bencode::Error>(())
+    /// ```
+    #[must_use]
+    pub fn from_reader_with_buffer(reader: R, buf: Vec<u8>) -> Self {
+        Deserializer::with_buffer(read::IoRead::new(reader), buf)
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl<B> Deserializer<read::BufRead<B>>
+where
+    B: bytes::Buf,
+{
+    /// Constructs a Deserializer from a [`bytes::Buf`] source.
+    #[must_use]
+    pub fn from_buf(buf: B) -> Self {
+        Deserializer::new(read::BufRead::new(buf))
+    }
 }
 
 impl<'a> Deserializer<read::SliceRead<'a>> {
@@ -156,6 +967,291 @@ impl<'a> Deserializer<read::SliceRead<'a>> {
     }
 }
 
+/// Configures and constructs a [`Deserializer`].
+///
+/// By default, a Deserializer accepts any data which can be parsed, including
+/// integers with leading zeros and dictionaries with unsorted or duplicate
+/// keys, and does not limit how deeply lists and dictionaries are nested or
+/// how long byte strings are.
+///
+/// ```rust
+/// use bt_bencode::{DeserializerBuilder, Value};
+///
+/// let builder = DeserializerBuilder::new().strict(true).max_depth(8);
+///
+/// let value: Value = builder.from_slice(b"d1:ai1e1:bli2eee")?;
+/// assert_eq!(value["b"][0].as_u64(), Some(2));
+///
+/// assert!(builder.from_slice::<Value>(b"d1:bi1e1:ai2ee").is_err());
+/// assert!(builder.from_slice::<Value>(b"i01e").is_err());
+/// assert!(builder.from_slice::<Value>(b"lllllllll0:eeeeeeeee").is_err());
+/// # Ok::<(), bt_bencode::Error>(())
+/// ```
+///
+/// The checks apply to the values which the Deserializer parses. A value
+/// captured as raw bytes (e.g. into a [`RawValue`][crate::value::RawValue] or
+/// a byte string field) is not checked.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Builder {
+    max_depth: Option<usize>,
+    max_byte_str_len: Option<usize>,
+    canonical_integers: bool,
+    sorted_keys: bool,
+    deny_duplicate_keys: bool,
+    track_ignored_keys: bool,
+    deny_unknown_keys: bool,
+}
+
+impl Builder {
+    /// Constructs a builder with the default options.
+    #[must_use]
+    pub fn new() -> Self {
+        Builder::default()
+    }
+
+    /// Sets the maximum number of nested lists and dictionaries.
+    ///
+    /// A value at the top level has a depth of 0, so a maximum depth of 0
+    /// rejects any list or dictionary.
+    #[must_use]
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Sets the maximum length of a byte string.
+    ///
+    /// The length prefix is checked before the contents are read, so a
+    /// reader source does not buffer a byte string which is too long. Raw
+    /// values are not checked.
+    ///
+    /// ```rust
+    /// use bt_bencode::{DeserializerBuilder, Value};
+    ///
+    /// let builder = DeserializerBuilder::new().max_byte_str_len(4);
+    /// assert!(builder.from_slice::<Value>(b"l4:spame").is_ok());
+    ///
+    /// let err = builder.from_slice::<Value>(b"l5:spamse").unwrap_err();
+    /// assert_eq!(
+    ///     err.to_string(),
+    ///     "byte string is longer than the maximum length of 4 bytes"
+    /// );
+    /// # Ok::<(), bt_bencode::Error>(())
+    /// ```
+    #[must_use]
+    pub fn max_byte_str_len(mut self, max_byte_str_len: usize) -> Self {
+        self.max_byte_str_len = Some(max_byte_str_len);
+        self
+    }
+
+    /// Sets if integers must be canonically encoded without leading zeros or
+    /// a negative zero.
+    #[must_use]
+    pub fn canonical_integers(mut self, canonical_integers: bool) -> Self {
+        self.canonical_integers = canonical_integers;
+        self
+    }
+
+    /// Sets if dictionary keys must be sorted, which also rejects duplicate
+    /// keys.
+    #[must_use]
+    pub fn sorted_keys(mut self, sorted_keys: bool) -> Self {
+        self.sorted_keys = sorted_keys;
+        self
+    }
+
+    /// Sets if a dictionary key which appears more than once is an error.
+    ///
+    /// Unlike [`Builder::sorted_keys()`], the keys may be in any order.
+    #[must_use]
+    pub fn deny_duplicate_keys(mut self, deny_duplicate_keys: bool) -> Self {
+        self.deny_duplicate_keys = deny_duplicate_keys;
+        self
+    }
+
+    /// Sets if the dictionary keys ignored by the deserialized type are
+    /// recorded.
+    ///
+    /// See [`Deserializer::ignored_keys()`].
+    #[must_use]
+    pub fn track_ignored_keys(mut self, track_ignored_keys: bool) -> Self {
+        self.track_ignored_keys = track_ignored_keys;
+        self
+    }
+
+    /// Sets if a dictionary key ignored by the deserialized type is an error.
+    ///
+    /// Unlike `#[serde(deny_unknown_fields)]`, the check applies to every
+    /// struct (and any other type which skips dictionary values) in the
+    /// deserialized value.
+    ///
+    /// The check only sees values which the type asks the deserializer to
+    /// skip. Structs with a `#[serde(flatten)]` field and untagged or
+    /// internally tagged enums first deserialize the whole dictionary into a
+    /// buffer and drop the unknown keys from the buffer, so unknown keys in
+    /// those types are not rejected.
+    ///
+    /// ```rust
+    /// use bt_bencode::DeserializerBuilder;
+    /// use serde_derive::Deserialize;
+    ///
+    /// #[derive(Debug, Deserialize)]
+    /// struct Peer {
+    ///     port: u16,
+    /// }
+    ///
+    /// let builder = DeserializerBuilder::new().deny_unknown_keys(true);
+    /// assert_eq!(builder.from_slice::<Peer>(b"d4:porti6881ee")?.port, 6881);
+    ///
+    /// let err = builder.from_slice::<Peer>(b"d2:ip0:4:porti6881ee").unwrap_err();
+    /// assert_eq!(err.to_string(), "unknown dictionary key \"ip\"");
+    /// # Ok::<(), bt_bencode::Error>(())
+    /// ```
+    #[must_use]
+    pub fn deny_unknown_keys(mut self, deny_unknown_keys: bool) -> Self {
+        self.deny_unknown_keys = deny_unknown_keys;
+        self
+    }
+
+    /// Sets both [`Builder::canonical_integers()`] and [`Builder::sorted_keys()`].
+    #[must_use]
+    pub fn strict(self, strict: bool) -> Self {
+        self.canonical_integers(strict).sorted_keys(strict)
+    }
+
+    #[inline]
+    fn tracks_paths(self) -> bool {
+        self.track_ignored_keys || self.deny_unknown_keys
+    }
+
+    #[inline]
+    fn captures_keys(self) -> bool {
+        self.sorted_keys || self.deny_duplicate_keys || self.tracks_paths()
+    }
+
+    /// Constructs a Deserializer from a readable source.
+    #[must_use]
+    pub fn build<'a, R>(self, read: R) -> Deserializer<R>
+    where
+        R: Read<'a>,
+    {
+        self.build_with_buffer(read, Vec::new())
+    }
+
+    /// Constructs a Deserializer from a readable source and an existing
+    /// scratch buffer.
+    ///
+    /// See [`Deserializer::with_buffer()`].
+    #[must_use]
+    pub fn build_with_buffer<'a, R>(self, read: R, buf: Vec<u8>) -> Deserializer<R>
+    where
+        R: Read<'a>,
+    {
+        let mut de = Deserializer::with_buffer(read, buf);
+        de.options = self;
+        de
+    }
+
+    /// Constructs a Deserializer from a `&[u8]`.
+    ///
+    /// Byte strings are borrowed from the slice when possible.
+    #[must_use]
+    pub fn build_from_slice(self, bytes: &[u8]) -> Deserializer<read::SliceRead<'_>> {
+        self.build(read::SliceRead::new(bytes))
+    }
+
+    /// Constructs a Deserializer from an [`std::io::Read`][std::io::Read] source.
+    #[cfg(feature = "std")]
+    #[must_use]
+    pub fn build_from_reader<R>(self, reader: R) -> Deserializer<read::IoRead<R>>
+    where
+        R: io::Read,
+    {
+        self.build(read::IoRead::new(reader))
+    }
+
+    /// Renders a single encoded value as indented text like
+    /// [`pretty_print()`].
+    ///
+    /// Only the [maximum depth][Builder::max_depth] applies. Without a
+    /// maximum depth, any depth is rendered.
+    ///
+    /// ```rust
+    /// use bt_bencode::DeserializerBuilder;
+    ///
+    /// let input = [&[b'l'; 200][..], &[b'e'; 200]].concat();
+    /// assert!(bt_bencode::pretty_print(&input).is_err());
+    /// assert!(DeserializerBuilder::new().max_depth(256).pretty_print(&input).is_ok());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the data is not exactly one valid value or if
+    /// the value is nested deeper than the maximum depth.
+    pub fn pretty_print(self, s: &[u8]) -> Result<String> {
+        let mut printer = PrettyPrinter::new();
+        let mut parser = Parser::new(s);
+        while let Some(event) = parser.next() {
+            let (_, event) = event?;
+            if let Some(max_depth) = self.max_depth {
+                if matches!(event, Event::DictStart | Event::ListStart)
+                    && parser.depth() > max_depth
+                {
+                    return Err(Error::Deserialize(String::from(
+                        "maximum nesting depth exceeded",
+                    )));
+                }
+            }
+            match event {
+                Event::DictStart => printer.dict_start(),
+                Event::ListStart => printer.list_start(),
+                Event::Key(key) => printer.key(key),
+                Event::ByteStr(value) => printer.byte_str(value),
+                Event::Int(value) => printer.int(&value),
+                Event::End => printer.end(),
+            }
+        }
+        parser.end()?;
+        Ok(printer.into_string())
+    }
+
+    /// Deserializes an instance of `T` from a slice of bytes.
+    ///
+    /// # Errors
+    ///
+    /// Deserialization can fail if the data is not valid, if the data does
+    /// not pass the configured checks, or if the data cannot be deserialized
+    /// into an instance of `T`.
+    pub fn from_slice<'a, T>(self, s: &'a [u8]) -> Result<T>
+    where
+        T: de::Deserialize<'a>,
+    {
+        let mut de = self.build_from_slice(s);
+        let value = T::deserialize(&mut de)?;
+        de.end()?;
+        Ok(value)
+    }
+
+    /// Deserializes an instance of `T` from the bytes of an [`io::Read`] type.
+    ///
+    /// # Errors
+    ///
+    /// Deserialization can fail if the data is not valid, if the data does
+    /// not pass the configured checks, if the data cannot be deserialized
+    /// into an instance of `T`, and other IO errors.
+    #[cfg(feature = "std")]
+    pub fn from_reader<R, T>(self, r: R) -> Result<T>
+    where
+        R: io::Read,
+        T: de::DeserializeOwned,
+    {
+        let mut de = self.build_from_reader(r);
+        let value = T::deserialize(&mut de)?;
+        de.end()?;
+        Ok(value)
+    }
+}
+
 macro_rules! forward_deserialize_signed_integer {
     ($method:ident) => {
         #[inline]
@@ -180,7 +1276,7 @@ macro_rules! forward_deserialize_unsigned_integer {
     };
 }
 
-impl<'de, 'a, R: Read<'de>> de::Deserializer<'de> for &'a mut Deserializer<R> {
+impl<'de, R: Read<'de>> de::Deserializer<'de> for &mut Deserializer<R> {
     type Error = Error;
 
     fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
@@ -188,26 +1284,20 @@ impl<'de, 'a, R: Read<'de>> de::Deserializer<'de> for &'a mut Deserializer<R> {
         V: de::Visitor<'de>,
     {
         match self.parse_peek()? {
-            b'0'..=b'9' => {
-                self.buf.clear();
-                match self.read.parse_byte_str(&mut self.buf)? {
-                    Ref::Source(bytes) => visitor.visit_borrowed_bytes(bytes),
-                    Ref::Buffer(bytes) => visitor.visit_bytes(bytes),
-                }
-            }
+            b'0'..=b'9' => match self.parse_byte_str()? {
+                Ref::Source(bytes) => visitor.visit_borrowed_bytes(bytes),
+                Ref::Buffer(bytes) => visitor.visit_bytes(bytes),
+            },
             b'i' => {
                 self.parse_next()?;
-                self.buf.clear();
-                let num_str = self.read.parse_integer(&mut self.buf)?;
-                if num_str.starts_with('-') {
-                    visitor.visit_i64(num_str.parse()?)
-                } else {
-                    visitor.visit_u64(num_str.parse()?)
-                }
+                let n = self.parse_integer()?.parse()?;
+                visit_number(n, visitor)
             }
             b'l' => {
                 self.parse_next()?;
-                let ret = visitor.visit_seq(SeqAccess { de: self });
+                self.enter()?;
+                let ret = visitor.visit_seq(SeqAccess::new(self));
+                self.depth -= 1;
                 match (ret, self.on_end_seq()) {
                     (Ok(ret), Ok(())) => Ok(ret),
                     (Err(err), _) | (_, Err(err)) => Err(err),
@@ -215,7 +1305,9 @@ impl<'de, 'a, R: Read<'de>> de::Deserializer<'de> for &'a mut Deserializer<R> {
             }
             b'd' => {
                 self.parse_next()?;
-                let ret = visitor.visit_map(MapAccess { de: self });
+                self.enter()?;
+                let ret = visitor.visit_map(MapAccess::new(self));
+                self.depth -= 1;
                 match (ret, self.on_end_map()) {
                     (Ok(ret), Ok(())) => Ok(ret),
                     (Err(err), _) | (_, Err(err)) => Err(err),
@@ -228,9 +1320,31 @@ impl<'de, 'a, R: Read<'de>> de::Deserializer<'de> for &'a mut Deserializer<R> {
     forward_to_deserialize_any! {
         bool f32 f64 unit unit_struct
 
-        char str string
+        char
 
-        struct enum identifier ignored_any
+        struct enum identifier
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        if !self.options.tracks_paths() || self.ignoring {
+            return self.deserialize_any(visitor);
+        }
+        if let Some(PathSegmentBuf::Key(key)) = self.path.last() {
+            if self.options.deny_unknown_keys {
+                return Err(Error::Deserialize(format!(
+                    "unknown dictionary key {:?}",
+                    String::from_utf8_lossy(key)
+                )));
+            }
+            self.ignored_keys.push(self.path.clone());
+        }
+        self.ignoring = true;
+        let value = self.deserialize_any(visitor);
+        self.ignoring = false;
+        value
     }
 
     forward_deserialize_signed_integer!(deserialize_i8);
@@ -244,18 +1358,21 @@ impl<'de, 'a, R: Read<'de>> de::Deserializer<'de> for &'a mut Deserializer<R> {
         match self.parse_peek()? {
             b'i' => {
                 self.parse_next()?;
-                self.buf.clear();
-                let num_str = self.read.parse_integer(&mut self.buf)?;
-                if num_str.starts_with('-') {
-                    visitor.visit_i64(num_str.parse()?)
-                } else {
-                    visitor.visit_u64(num_str.parse()?)
-                }
+                let n = self.parse_integer()?.parse()?;
+                visit_number(n, visitor)
             }
             _ => Err(self.unexpected_type_err(&visitor)?),
         }
     }
 
+    #[inline]
+    fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_i64(visitor)
+    }
+
     forward_deserialize_unsigned_integer!(deserialize_u8);
     forward_deserialize_unsigned_integer!(deserialize_u16);
     forward_deserialize_unsigned_integer!(deserialize_u32);
@@ -268,6 +1385,35 @@ impl<'de, 'a, R: Read<'de>> de::Deserializer<'de> for &'a mut Deserializer<R> {
         self.deserialize_i64(visitor)
     }
 
+    #[inline]
+    fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_i64(visitor)
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.parse_peek()? {
+            b'0'..=b'9' => match self.parse_byte_str()? {
+                Ref::Source(bytes) => visitor.visit_borrowed_str(core::str::from_utf8(bytes)?),
+                Ref::Buffer(bytes) => visitor.visit_str(core::str::from_utf8(bytes)?),
+            },
+            _ => Err(self.unexpected_type_err(&visitor)?),
+        }
+    }
+
+    #[inline]
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
     fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
@@ -287,13 +1433,10 @@ impl<'de, 'a, R: Read<'de>> de::Deserializer<'de> for &'a mut Deserializer<R> {
         // BitTorrent metainfo. The `info` value would be captured as-is without
         // parsing which allows the infohash to be generated according to the specification.
         match self.parse_peek()? {
-            b'0'..=b'9' => {
-                self.buf.clear();
-                match self.read.parse_byte_str(&mut self.buf)? {
-                    Ref::Source(bytes) => visitor.visit_borrowed_bytes(bytes),
-                    Ref::Buffer(bytes) => visitor.visit_bytes(bytes),
-                }
-            }
+            b'0'..=b'9' => match self.parse_byte_str()? {
+                Ref::Source(bytes) => visitor.visit_borrowed_bytes(bytes),
+                Ref::Buffer(bytes) => visitor.visit_bytes(bytes),
+            },
             b'i' => {
                 self.buf.clear();
                 match self.read.parse_raw_integer(&mut self.buf)? {
@@ -336,10 +1479,26 @@ impl<'de, 'a, R: Read<'de>> de::Deserializer<'de> for &'a mut Deserializer<R> {
     }
 
     #[inline]
-    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
+        if name == crate::value::raw::TOKEN {
+            self.buf.clear();
+            let bytes = self.read.parse_raw_value(&mut self.buf)?;
+            if self.capture_key {
+                // The raw value of a key is a byte string which includes the
+                // length prefix.
+                let start = bytes.iter().position(|&b| b == b':').map_or(0, |i| i + 1);
+                self.capture_key = false;
+                self.key.clear();
+                self.key.extend_from_slice(&bytes[start..]);
+            }
+            return match bytes {
+                Ref::Source(bytes) => visitor.visit_borrowed_bytes(bytes),
+                Ref::Buffer(bytes) => visitor.visit_bytes(bytes),
+            };
+        }
         visitor.visit_newtype_struct(self)
     }
 
@@ -348,15 +1507,12 @@ impl<'de, 'a, R: Read<'de>> de::Deserializer<'de> for &'a mut Deserializer<R> {
         V: de::Visitor<'de>,
     {
         match self.parse_peek()? {
-            b'l' => {
-                self.parse_next()?;
-                let ret = visitor.visit_seq(SeqAccess { de: self });
-                match (ret, self.on_end_seq()) {
-                    (Ok(ret), Ok(())) => Ok(ret),
-                    (Err(err), _) | (_, Err(err)) => Err(err),
-                }
+            b'0'..=b'9' => {
+                // Allows a byte string to be deserialized into a `Vec<u8>`.
+                let bytes = self.parse_byte_str()?;
+                visit_byte_seq(&bytes, visitor)
             }
-            _ => Err(self.unexpected_type_err(&visitor)?),
+            _ => self.deserialize_list(visitor),
         }
     }
 
@@ -365,7 +1521,7 @@ impl<'de, 'a, R: Read<'de>> de::Deserializer<'de> for &'a mut Deserializer<R> {
     where
         V: de::Visitor<'de>,
     {
-        self.deserialize_seq(visitor)
+        self.deserialize_list(visitor)
     }
 
     #[inline]
@@ -378,7 +1534,7 @@ impl<'de, 'a, R: Read<'de>> de::Deserializer<'de> for &'a mut Deserializer<R> {
     where
         V: de::Visitor<'de>,
     {
-        self.deserialize_seq(visitor)
+        self.deserialize_list(visitor)
     }
 
     fn deserialize_map<V>(self, visitor: V) -> Result<V::Value>
@@ -388,7 +1544,9 @@ impl<'de, 'a, R: Read<'de>> de::Deserializer<'de> for &'a mut Deserializer<R> {
         match self.parse_peek()? {
             b'd' => {
                 self.parse_next()?;
-                let ret = visitor.visit_map(MapAccess { de: self });
+                self.enter()?;
+                let ret = visitor.visit_map(MapAccess::new(self));
+                self.depth -= 1;
                 match (ret, self.on_end_map()) {
                     (Ok(ret), Ok(())) => Ok(ret),
                     (Err(err), _) | (_, Err(err)) => Err(err),
@@ -404,8 +1562,125 @@ impl<'de, 'a, R: Read<'de>> de::Deserializer<'de> for &'a mut Deserializer<R> {
     }
 }
 
+fn visit_number<'de, V>(n: Number, visitor: V) -> Result<V::Value>
+where
+    V: de::Visitor<'de>,
+{
+    match n {
+        Number::Signed(n) => visitor.visit_i64(n),
+        Number::Unsigned(n) => visitor.visit_u64(n),
+        Number::Signed128(n) => visitor.visit_i128(n),
+        Number::Unsigned128(n) => visitor.visit_u128(n),
+        #[cfg(feature = "big-int")]
+        Number::Big(n) => visitor.visit_newtype_struct(n.as_str().into_deserializer()),
+    }
+}
+
+/// Visits the bytes of a byte string as a sequence.
+///
+/// Only `u8` elements are accepted, so a byte string can be deserialized into
+/// a `Vec<u8>` but a sequence of any other type is a type error.
+pub(crate) fn visit_byte_seq<'de, V>(bytes: &[u8], visitor: V) -> Result<V::Value>
+where
+    V: de::Visitor<'de>,
+{
+    let mut seq = ByteSeq(bytes.iter());
+    let value = visitor.visit_seq(&mut seq)?;
+    if seq.0.len() == 0 {
+        Ok(value)
+    } else {
+        Err(de::Error::invalid_length(
+            bytes.len(),
+            &"fewer elements in sequence",
+        ))
+    }
+}
+
+struct ByteSeq<'b>(core::slice::Iter<'b, u8>);
+
+impl<'de> de::SeqAccess<'de> for ByteSeq<'_> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        match self.0.next() {
+            Some(&b) => seed.deserialize(ByteDeserializer(b)).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.0.len())
+    }
+}
+
+/// Deserializes a byte of a byte string which is visited as a sequence.
+struct ByteDeserializer(u8);
+
+impl<'de> de::Deserializer<'de> for ByteDeserializer {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        Err(de::Error::invalid_type(
+            Unexpected::Other("byte string"),
+            &visitor,
+        ))
+    }
+
+    fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_u8(self.0)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
 struct SeqAccess<'a, R> {
     de: &'a mut Deserializer<R>,
+    index: usize,
+    /// The number of remaining elements, once the reader is asked for a hint
+    remaining: Cell<Option<Option<usize>>>,
+}
+
+impl<'a, R> SeqAccess<'a, R> {
+    fn new(de: &'a mut Deserializer<R>) -> Self {
+        SeqAccess {
+            de,
+            index: 0,
+            remaining: Cell::new(None),
+        }
+    }
+}
+
+/// Returns the cached hint, or asks the reader for a hint only once.
+fn cached_hint<F>(cache: &Cell<Option<Option<usize>>>, f: F) -> Option<usize>
+where
+    F: FnOnce() -> Option<usize>,
+{
+    if let Some(hint) = cache.get() {
+        return hint;
+    }
+    let hint = f();
+    cache.set(Some(hint));
+    hint
+}
+
+/// Counts one consumed element against a cached hint.
+fn consume_hint(cache: &Cell<Option<Option<usize>>>) {
+    if let Some(Some(n)) = cache.get() {
+        cache.set(Some(Some(n.saturating_sub(1))));
+    }
 }
 
 impl<'de, 'a, R: Read<'de> + 'a> de::SeqAccess<'de> for SeqAccess<'a, R> {
@@ -415,15 +1690,46 @@ impl<'de, 'a, R: Read<'de> + 'a> de::SeqAccess<'de> for SeqAccess<'a, R> {
     where
         T: de::DeserializeSeed<'de>,
     {
-        match self.de.parse_peek()? {
-            b'e' => Ok(None),
-            _ => Ok(Some(seed.deserialize(&mut *self.de)?)),
+        if self.de.parse_peek()? == b'e' {
+            return Ok(None);
+        }
+        consume_hint(&self.remaining);
+        if self.de.options.tracks_paths() {
+            self.de.path.push(PathSegmentBuf::Index(self.index));
+            self.index += 1;
+            let value = seed.deserialize(&mut *self.de);
+            self.de.path.pop();
+            value.map(Some)
+        } else {
+            Ok(Some(seed.deserialize(&mut *self.de)?))
         }
     }
+
+    #[inline]
+    fn size_hint(&self) -> Option<usize> {
+        cached_hint(&self.remaining, || self.de.read.item_count_hint())
+    }
 }
 
 struct MapAccess<'a, R> {
     de: &'a mut Deserializer<R>,
+    /// The previous key if the keys must be sorted or ignored keys are tracked
+    prev_key: Option<Vec<u8>>,
+    /// The keys seen so far if duplicate keys are denied but keys may be unsorted
+    seen_keys: BTreeSet<Vec<u8>>,
+    /// The number of remaining entries, once the reader is asked for a hint
+    remaining: Cell<Option<Option<usize>>>,
+}
+
+impl<'a, R> MapAccess<'a, R> {
+    fn new(de: &'a mut Deserializer<R>) -> Self {
+        MapAccess {
+            de,
+            prev_key: None,
+            seen_keys: BTreeSet::new(),
+            remaining: Cell::new(None),
+        }
+    }
 }
 
 impl<'de, 'a, R: Read<'de> + 'a> de::MapAccess<'de> for MapAccess<'a, R> {
@@ -434,6 +1740,36 @@ impl<'de, 'a, R: Read<'de> + 'a> de::MapAccess<'de> for MapAccess<'a, R> {
         K: de::DeserializeSeed<'de>,
     {
         match self.de.parse_peek()? {
+            b'0'..=b'9' if self.de.options.captures_keys() => {
+                self.de.capture_key = true;
+                let key = seed.deserialize(MapKey { de: &mut *self.de });
+                let captured = !self.de.capture_key;
+                self.de.capture_key = false;
+                let key = key?;
+                if captured {
+                    if self.de.options.deny_duplicate_keys
+                        && !self.de.options.sorted_keys
+                        && !self.seen_keys.insert(self.de.key.clone())
+                    {
+                        return Err(Error::Deserialize(format!(
+                            "duplicate dictionary key {:?}",
+                            String::from_utf8_lossy(&self.de.key)
+                        )));
+                    }
+                    if let Some(prev_key) = &mut self.prev_key {
+                        if self.de.options.sorted_keys && self.de.key <= *prev_key {
+                            return Err(Error::Deserialize(String::from(
+                                "dictionary keys are not sorted or are duplicated",
+                            )));
+                        }
+                        prev_key.clear();
+                        prev_key.extend_from_slice(&self.de.key);
+                    } else {
+                        self.prev_key = Some(self.de.key.clone());
+                    }
+                }
+                Ok(Some(key))
+            }
             b'0'..=b'9' => seed.deserialize(MapKey { de: &mut *self.de }).map(Some),
             b'e' => Ok(None),
             _ => Err(Error::KeyMustBeAByteStr),
@@ -445,8 +1781,28 @@ impl<'de, 'a, R: Read<'de> + 'a> de::MapAccess<'de> for MapAccess<'a, R> {
     where
         V: de::DeserializeSeed<'de>,
     {
+        consume_hint(&self.remaining);
+        if self.de.options.tracks_paths() {
+            if let Some(key) = &self.prev_key {
+                self.de.path.push(PathSegmentBuf::Key(key.clone()));
+                let value = seed.deserialize(&mut *self.de);
+                self.de.path.pop();
+                return value;
+            }
+        }
         seed.deserialize(&mut *self.de)
     }
+
+    #[inline]
+    fn size_hint(&self) -> Option<usize> {
+        cached_hint(&self.remaining, || {
+            self.de
+                .read
+                .item_count_hint()
+                .filter(|count| count % 2 == 0)
+                .map(|count| count / 2)
+        })
+    }
 }
 
 struct MapKey<'a, R> {
@@ -476,22 +1832,58 @@ where
     }
 
     #[inline]
-    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
+        if name == crate::value::raw::TOKEN {
+            return self.de.deserialize_newtype_struct(name, visitor);
+        }
         visitor.visit_newtype_struct(self)
     }
 
+    #[inline]
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.de.deserialize_str(visitor)
+    }
+
+    #[inline]
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.de.deserialize_string(visitor)
+    }
+
+    #[inline]
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.de.deserialize_bytes(visitor)
+    }
+
+    #[inline]
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.de.deserialize_byte_buf(visitor)
+    }
+
     forward_to_deserialize_any! {
         bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 unit unit_struct seq tuple tuple_struct map
-        char str string bytes byte_buf enum struct identifier ignored_any
+        char enum struct identifier ignored_any
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use serde::de::Deserialize as _;
     use serde_bytes::ByteBuf;
     use serde_derive::Deserialize;
 
@@ -500,6 +1892,176 @@ mod tests {
     #[cfg(feature = "std")]
     use std::{collections::BTreeMap, string::String, vec};
 
+    #[tokio::test]
+    #[cfg(feature = "futures")]
+    async fn test_into_stream() -> Result<()> {
+        use futures::StreamExt;
+
+        let input = [&b"l4:spami1eel4:eggs"[..], &[b'9'; 10000], &b"e4:sp"[..]].concat();
+        let mut stream = into_stream::<_, (String, u64)>(&input[..]);
+        assert_eq!(
+            stream.next().await.transpose()?,
+            Some((String::from("spam"), 1))
+        );
+        assert!(matches!(
+            stream.next().await,
+            Some(Err(Error::InvalidByteStrLen))
+        ));
+        assert!(stream.next().await.is_none());
+
+        let mut stream = into_stream::<_, String>(&b"4:spam4:eg"[..]);
+        assert_eq!(stream.next().await.transpose()?, Some(String::from("spam")));
+        assert!(matches!(
+            stream.next().await,
+            Some(Err(Error::EofWhileParsingValue))
+        ));
+        assert!(stream.next().await.is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "futures")]
+    async fn test_into_stream_max_frame_len() -> Result<()> {
+        use futures::StreamExt;
+
+        let input = [&b"4:spam4:eggsl"[..], &b"i1e".repeat(100)].concat();
+        let mut stream = into_stream::<_, String>(&input[..]).with_max_frame_len(6);
+        assert_eq!(stream.max_frame_len(), Some(6));
+        assert_eq!(stream.next().await.transpose()?, Some(String::from("spam")));
+        assert_eq!(stream.next().await.transpose()?, Some(String::from("eggs")));
+        let err = stream.next().await.unwrap().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "value is longer than the maximum length of 6 bytes"
+        );
+        assert!(stream.next().await.is_none());
+
+        let input = b"le".repeat(10000);
+        let mut stream = into_stream::<_, Vec<u8>>(&input[..]).with_max_frame_len(2);
+        for _ in 0..10000 {
+            assert_eq!(stream.next().await.transpose()?, Some(Vec::new()));
+        }
+        assert!(stream.next().await.is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "tokio")]
+    async fn test_from_async_reader() -> Result<()> {
+        let s: String = from_async_reader(&b"4:spam"[..]).await?;
+        assert_eq!(s, "spam");
+
+        let input = [&b"l"[..], &[b'i'; 20000], &b"1e"[..]].concat();
+        let result: Result<Vec<i64>> = from_async_reader(&input[..]).await;
+        assert!(matches!(result, Err(Error::InvalidInteger)));
+
+        let result: Result<String> = from_async_reader(&b"4:spa"[..]).await;
+        assert!(matches!(result, Err(Error::EofWhileParsingValue)));
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "tokio")]
+    async fn test_from_async_reader_stream() -> Result<()> {
+        use tokio::io::{AsyncWriteExt, BufReader};
+
+        let (client, server) = tokio::io::duplex(16);
+        let writer = async move {
+            let mut client = client;
+            crate::to_async_writer(&mut client, &("spam", 42)).await?;
+            client
+                .write_all(b"d1:y1:qe")
+                .await
+                .map_err(Error::IoError)?;
+            Ok::<_, Error>(())
+        };
+        let reader = async move {
+            let mut server = BufReader::new(server);
+            let first: (String, u64) = from_async_reader(&mut server).await?;
+            let second: crate::Value = from_async_reader(&mut server).await?;
+            let end: Result<crate::Value> = from_async_reader(&mut server).await;
+            assert!(matches!(end, Err(Error::EofWhileParsingValue)));
+            Ok::<_, Error>((first, second))
+        };
+        let (written, read) = tokio::join!(writer, reader);
+        written?;
+        let (first, second) = read?;
+        assert_eq!(first, (String::from("spam"), 42));
+        assert_eq!(second["y"], "q");
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_raw() -> Result<()> {
+        let input = &b"d1:ali1e0:d1:bi2eee1:c3:xyze"[..];
+        assert_eq!(get_raw(&[], input)?, Some(input));
+        assert_eq!(
+            get_raw(&["a".into()], input)?,
+            Some(&b"li1e0:d1:bi2eee"[..])
+        );
+        assert_eq!(
+            get_raw(&["a".into(), 2.into(), "b".into()], input)?,
+            Some(&b"i2e"[..])
+        );
+        assert_eq!(get_raw(&["a".into(), 3.into()], input)?, None);
+        assert_eq!(get_raw(&["c".into()], input)?, Some(&b"3:xyz"[..]));
+        assert_eq!(get_raw(&["d".into()], input)?, None);
+        assert_eq!(get_raw(&[0.into()], input)?, None);
+        assert_eq!(get_raw(&["a".into(), "b".into()], input)?, None);
+        assert_eq!(get_raw(&["c".into(), 0.into()], input)?, None);
+
+        assert_eq!(
+            get_raw(&["a".into()], b"d1:ai1eegarbage")?,
+            Some(&b"i1e"[..])
+        );
+        assert!(get_raw(&["b".into()], b"d1:ai1").is_err());
+        assert!(get_raw(&["b".into()], b"di1ei2ee").is_err());
+        assert!(get_raw(&["a".into()], b"x").is_err());
+
+        let nested = [vec![b'l'; 200_000], vec![b'e'; 200_000]].concat();
+        assert!(matches!(
+            get_raw(&[], &nested[..200_000]),
+            Err(Error::EofWhileParsingValue)
+        ));
+        assert!(get_raw(&["b".into()], &[&b"d1:a"[..], &nested[..200_000]].concat()).is_err());
+        let input = [&b"d1:a"[..], &nested, b"1:bi1ee"].concat();
+        assert_eq!(get_raw(&["b".into()], &input)?, Some(&b"i1e"[..]));
+        assert_eq!(get_raw(&["a".into()], &input)?, Some(&nested[..]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_raw_values() -> Result<()> {
+        let values = raw_values(b"i1e0:le").collect::<Result<Vec<_>>>()?;
+        assert_eq!(
+            values,
+            [(0..3, &b"i1e"[..]), (3..5, &b"0:"[..]), (5..7, &b"le"[..])]
+        );
+
+        let mut values = raw_values(b"i1ed1:a");
+        assert_eq!(values.next().transpose()?, Some((0..3, &b"i1e"[..])));
+        assert!(matches!(
+            values.next(),
+            Some(Err(Error::EofWhileParsingValue))
+        ));
+        assert!(values.next().is_none());
+
+        assert!(raw_values(b"").next().is_none());
+
+        let mut input = vec![b'l'; 200_000];
+        let mut values = raw_values(&input);
+        assert!(matches!(
+            values.next(),
+            Some(Err(Error::EofWhileParsingValue))
+        ));
+        input.extend_from_slice(&[b'e'; 200_000]);
+        assert_eq!(
+            raw_values(&input).next().transpose()?,
+            Some((0..400_000, &input[..]))
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_deserialize_str() -> Result<()> {
         let s: &str = from_slice("4:spam".as_bytes())?;
@@ -514,6 +2076,50 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_deserialize_string_invalid_utf8() {
+        let result: Result<String> = from_slice(b"2:\xff\xfe");
+        assert!(matches!(result, Err(Error::Utf8Error(_))));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_deserialize_string_from_reader() -> Result<()> {
+        let s: String = from_reader(&b"4:spam"[..])?;
+        assert_eq!(s, "spam");
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_byte_str_as_vec() -> Result<()> {
+        let v: Vec<u8> = from_slice(b"4:spam")?;
+        assert_eq!(v, b"spam");
+        let v: Vec<u8> = from_slice(b"0:")?;
+        assert!(v.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_byte_str_as_other_seq() {
+        assert!(from_slice::<Vec<u64>>(b"3:abc").is_err());
+        assert!(from_slice::<Vec<u16>>(b"3:abc").is_err());
+        assert!(from_slice::<Vec<i8>>(b"3:abc").is_err());
+        assert!(from_slice::<Vec<String>>(b"3:abc").is_err());
+        assert!(from_slice::<(u8, u8)>(b"2:ab").is_err());
+        assert!(from_slice::<[u8; 4]>(b"4:spam").is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_deserialize_byte_buf_from_reader() -> Result<()> {
+        let v: ByteBuf = from_reader(&b"4:spam"[..])?;
+        assert_eq!(v, ByteBuf::from(b"spam".to_vec()));
+
+        let v: ByteBuf = from_reader(&b"li1ee"[..])?;
+        assert_eq!(v, ByteBuf::from(b"li1ee".to_vec()));
+        Ok(())
+    }
+
     #[test]
     fn test_deserialize_integer_1() -> Result<()> {
         let input = "i3e";
@@ -546,6 +2152,23 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_deserialize_integer_128() -> Result<()> {
+        let i: i128 = from_slice(b"i-170141183460469231731687303715884105728e")?;
+        assert_eq!(i, i128::MIN);
+        let i: u128 = from_slice(b"i340282366920938463463374607431768211455e")?;
+        assert_eq!(i, u128::MAX);
+        let i: u128 = from_slice(b"i3e")?;
+        assert_eq!(i, 3);
+        let i: (i128, i128) = from_slice(b"li-3ei3ee")?;
+        assert_eq!(i, (-3, 3));
+
+        assert!(from_slice::<u64>(b"i18446744073709551616e").is_err());
+        assert!(from_slice::<u128>(b"i-1e").is_err());
+        assert!(from_slice::<u128>(b"i340282366920938463463374607431768211456e").is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_deserialize_list() -> Result<()> {
         let input = "l4:spam4:eggse";
@@ -639,6 +2262,97 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_deserialize_with_reused_buffer() -> Result<()> {
+        use de::Deserialize;
+
+        let mut input: &[u8] = b"4:spam4:eggs";
+        let mut buf = Vec::with_capacity(64);
+        let ptr = buf.as_ptr();
+
+        let mut de = Deserializer::from_reader_with_buffer(&mut input, buf);
+        assert_eq!(String::deserialize(&mut de)?, "spam");
+        buf = de.into_buffer();
+
+        let mut de = Deserializer::from_reader_with_buffer(&mut input, buf);
+        assert_eq!(String::deserialize(&mut de)?, "eggs");
+        de.end()?;
+        buf = de.into_buffer();
+
+        assert_eq!(buf.as_ptr(), ptr);
+        Ok(())
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct SizeHints(Vec<Option<usize>>);
+
+    impl<'de> de::Deserialize<'de> for SizeHints {
+        fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
+        where
+            D: de::Deserializer<'de>,
+        {
+            struct SizeHintsVisitor;
+
+            impl<'de> de::Visitor<'de> for SizeHintsVisitor {
+                type Value = SizeHints;
+
+                fn expecting(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                    f.write_str("a list or dictionary")
+                }
+
+                fn visit_seq<A>(self, mut seq: A) -> core::result::Result<SizeHints, A::Error>
+                where
+                    A: de::SeqAccess<'de>,
+                {
+                    let mut hints = vec![seq.size_hint()];
+                    while seq.next_element::<de::IgnoredAny>()?.is_some() {
+                        hints.push(seq.size_hint());
+                    }
+                    Ok(SizeHints(hints))
+                }
+
+                fn visit_map<A>(self, mut map: A) -> core::result::Result<SizeHints, A::Error>
+                where
+                    A: de::MapAccess<'de>,
+                {
+                    let mut hints = vec![map.size_hint()];
+                    while map
+                        .next_entry::<de::IgnoredAny, de::IgnoredAny>()?
+                        .is_some()
+                    {
+                        hints.push(map.size_hint());
+                    }
+                    Ok(SizeHints(hints))
+                }
+            }
+
+            deserializer.deserialize_any(SizeHintsVisitor)
+        }
+    }
+
+    #[test]
+    fn test_deserialize_list_size_hint() -> Result<()> {
+        let hints: SizeHints = from_slice(b"li1e4:spaml1:aee")?;
+        assert_eq!(hints, SizeHints(vec![Some(3), Some(2), Some(1), Some(0)]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_dict_size_hint() -> Result<()> {
+        let hints: SizeHints = from_slice(b"d1:ai1e1:bd1:ci2eee")?;
+        assert_eq!(hints, SizeHints(vec![Some(2), Some(1), Some(0)]));
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_deserialize_reader_size_hint() -> Result<()> {
+        let hints: SizeHints = from_reader(&b"li1ee"[..])?;
+        assert_eq!(hints, SizeHints(vec![None, None]));
+        Ok(())
+    }
+
     #[test]
     fn test_deserialize_integer_as_raw_slice() -> Result<()> {
         #[derive(Debug, PartialEq, Deserialize)]
@@ -740,4 +2454,574 @@ mod tests {
         assert_eq!(s, expected);
         Ok(())
     }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Peer<'a> {
+        ip: &'a str,
+        port: u16,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Common<'a> {
+        interval: i64,
+        #[serde(borrow)]
+        name: &'a str,
+        id: ByteBuf,
+        tags: Vec<String>,
+        #[serde(default)]
+        missing: Option<i64>,
+        peers: Vec<Peer<'a>>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Flattened<'a> {
+        complete: u64,
+        #[serde(borrow, flatten)]
+        common: Common<'a>,
+        #[serde(flatten)]
+        rest: BTreeMap<String, crate::Value>,
+    }
+
+    #[test]
+    fn test_deserialize_flatten() -> Result<()> {
+        let input = b"d8:completei2e2:id2:\x00\xff8:intervali1800e4:name4:spam\
+            5:peersld2:ip5:1.2.34:porti80eee4:tagsl1:a1:be7:warning3:olde";
+        let value: Flattened<'_> = from_slice(input)?;
+
+        let mut rest = BTreeMap::new();
+        rest.insert(String::from("warning"), crate::Value::from("old"));
+        let expected = Flattened {
+            complete: 2,
+            common: Common {
+                interval: 1800,
+                name: "spam",
+                id: ByteBuf::from(vec![0x00, 0xff]),
+                tags: vec![String::from("a"), String::from("b")],
+                missing: None,
+                peers: vec![Peer {
+                    ip: "1.2.3",
+                    port: 80,
+                }],
+            },
+            rest,
+        };
+        assert_eq!(value, expected);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_deserialize_flatten_from_reader() -> Result<()> {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Inner {
+            a: i64,
+            b: String,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Outer {
+            c: ByteBuf,
+            #[serde(flatten)]
+            inner: Inner,
+        }
+
+        let value: Outer = from_reader(&b"d1:ai-1e1:b4:spam1:c1:\xffe"[..])?;
+        assert_eq!(
+            value,
+            Outer {
+                c: ByteBuf::from(vec![0xff]),
+                inner: Inner {
+                    a: -1,
+                    b: String::from("spam"),
+                },
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_flatten_internally_tagged() -> Result<()> {
+        #[derive(Debug, Deserialize, PartialEq)]
+        #[serde(tag = "y")]
+        enum Body {
+            #[serde(rename = "q")]
+            Query { q: String },
+            #[serde(rename = "r")]
+            Response { r: BTreeMap<String, i64> },
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Message {
+            t: ByteBuf,
+            #[serde(flatten)]
+            body: Body,
+        }
+
+        let query: Message = from_slice(b"d1:q4:ping1:t2:aa1:y1:qe")?;
+        assert_eq!(
+            query,
+            Message {
+                t: ByteBuf::from(&b"aa"[..]),
+                body: Body::Query {
+                    q: String::from("ping"),
+                },
+            }
+        );
+
+        let response: Message = from_slice(b"d1:rd1:ni7ee1:t2:aa1:y1:re")?;
+        let mut r = BTreeMap::new();
+        r.insert(String::from("n"), 7);
+        assert_eq!(
+            response,
+            Message {
+                t: ByteBuf::from(&b"aa"[..]),
+                body: Body::Response { r },
+            }
+        );
+
+        assert!(from_slice::<Message>(b"d1:t2:aa1:y1:xe").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_flatten_raw_value() -> Result<()> {
+        #[derive(Debug, Deserialize)]
+        struct Inner {
+            info: crate::value::RawValueBuf,
+        }
+
+        #[derive(Debug, Deserialize)]
+        struct Outer {
+            announce: String,
+            #[serde(flatten)]
+            inner: Inner,
+        }
+
+        let value: Outer = from_slice(b"d8:announce4:spam4:infod1:ai1e1:bl1:xeee")?;
+        assert_eq!(value.announce, "spam");
+        assert_eq!(value.inner.info.as_bytes(), b"d1:ai1e1:bl1:xee");
+        Ok(())
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(untagged)]
+    enum Untagged {
+        Int(i64),
+        Str(String),
+        // A `ByteBuf` also accepts a list of small integers, so lists are tried first.
+        List(Vec<Untagged>),
+        Bytes(ByteBuf),
+        Struct { a: i64 },
+        Dict(BTreeMap<String, Untagged>),
+    }
+
+    fn expected_untagged() -> Vec<Untagged> {
+        let mut d = BTreeMap::new();
+        d.insert(String::from("b"), Untagged::Int(-2));
+        vec![
+            Untagged::Int(1),
+            Untagged::Str(String::from("spam")),
+            Untagged::Bytes(ByteBuf::from(vec![0xff])),
+            Untagged::List(vec![
+                Untagged::Int(2),
+                Untagged::List(vec![Untagged::Int(3)]),
+            ]),
+            Untagged::Struct { a: 3 },
+            Untagged::Dict(d),
+        ]
+    }
+
+    const UNTAGGED: &[u8] = b"li1e4:spam1:\xffli2eli3eeed1:ai3eed1:bi-2eee";
+
+    #[test]
+    fn test_deserialize_untagged() -> Result<()> {
+        let value: Vec<Untagged> = from_slice(UNTAGGED)?;
+        assert_eq!(value, expected_untagged());
+
+        assert!(matches!(
+            from_slice::<Untagged>(b"d1:\xffi1ee"),
+            Err(Error::Deserialize(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_deserialize_untagged_from_reader() -> Result<()> {
+        let value: Vec<Untagged> = from_reader(UNTAGGED)?;
+        assert_eq!(value, expected_untagged());
+        Ok(())
+    }
+
+    #[test]
+    fn test_stats() -> Result<()> {
+        assert_eq!(
+            stats(b"i1e")?,
+            Stats {
+                ints: 1,
+                ..Stats::default()
+            }
+        );
+        assert_eq!(
+            stats(b"llleee")?,
+            Stats {
+                lists: 3,
+                max_depth: 3,
+                ..Stats::default()
+            }
+        );
+        assert_eq!(
+            stats(b"d3:keyl0:5:spam!ee")?,
+            Stats {
+                byte_strs: 2,
+                keys: 1,
+                lists: 1,
+                dicts: 1,
+                max_depth: 2,
+                max_byte_str_len: 5,
+                total_byte_str_len: 8,
+                ..Stats::default()
+            }
+        );
+        assert!(stats(b"lli1ee").is_err());
+        assert!(stats(b"i1ei2e").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_pretty_print() -> Result<()> {
+        assert_eq!(
+            pretty_print(b"d1:bi1e1:ald1:xleeee")?,
+            "{\n  \"b\": 1,\n  \"a\": [\n    {\n      \"x\": []\n    }\n  ]\n}"
+        );
+        assert_eq!(pretty_print(b"de")?, "{}");
+        assert!(pretty_print(b"li1e").is_err());
+        assert!(pretty_print(b"i1ei2e").is_err());
+
+        let nested = |depth: usize| [vec![b'l'; depth], vec![b'e'; depth]].concat();
+        assert!(pretty_print(&nested(128)).is_ok());
+        assert!(matches!(
+            pretty_print(&nested(129)),
+            Err(Error::Deserialize(_))
+        ));
+        assert!(pretty_print(&nested(200_000)).is_err());
+        assert!(pretty_print(&[b'l'; 200_000]).is_err());
+
+        assert!(Builder::new().max_depth(1).pretty_print(b"le").is_ok());
+        assert!(Builder::new().max_depth(1).pretty_print(b"llee").is_err());
+        assert!(Builder::new().pretty_print(&nested(129)).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_builder_canonical_integers() -> Result<()> {
+        let lenient = Builder::new();
+        assert_eq!(lenient.from_slice::<i64>(b"i-0e")?, 0);
+        assert_eq!(lenient.from_slice::<u64>(b"i01e")?, 1);
+
+        let builder = Builder::new().canonical_integers(true);
+        assert_eq!(builder.from_slice::<u64>(b"i0e")?, 0);
+        assert_eq!(builder.from_slice::<u64>(b"i10e")?, 10);
+        assert_eq!(builder.from_slice::<i64>(b"i-10e")?, -10);
+        for input in &[&b"i-0e"[..], b"i00e", b"i01e", b"i-01e"] {
+            assert!(matches!(
+                builder.from_slice::<i64>(input),
+                Err(Error::InvalidInteger)
+            ));
+            assert!(matches!(
+                builder.from_slice::<crate::Value>(input),
+                Err(Error::InvalidInteger)
+            ));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_builder_max_byte_str_len() -> Result<()> {
+        let builder = Builder::new().max_byte_str_len(4);
+        assert_eq!(builder.from_slice::<String>(b"4:spam")?, "spam");
+        let value: crate::Value = builder.from_slice(b"d4:spam0:e")?;
+        assert_eq!(value["spam"].as_str(), Some(""));
+
+        for input in &[&b"5:spams"[..], b"d5:spamsi1ee", b"l1:a5:spamse"] {
+            match builder.from_slice::<crate::Value>(input) {
+                Err(Error::Deserialize(msg)) => assert_eq!(
+                    msg,
+                    "byte string is longer than the maximum length of 4 bytes"
+                ),
+                result => panic!("unexpected result {:?} for {:?}", result, input),
+            }
+        }
+
+        #[cfg(feature = "std")]
+        {
+            // The contents are not read once the length prefix is too long.
+            let mut de = builder.build(read::IoRead::new(&b"10:"[..]));
+            assert!(matches!(
+                String::deserialize(&mut de),
+                Err(Error::Deserialize(_))
+            ));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_builder_deny_duplicate_keys() -> Result<()> {
+        let builder = Builder::new().deny_duplicate_keys(true);
+        let value: crate::Value = builder.from_slice(b"d1:bi1e1:ai2e1:cd1:zi1e1:yi2eee")?;
+        assert_eq!(value["a"].as_u64(), Some(2));
+        assert!(Builder::new()
+            .from_slice::<crate::Value>(b"d1:bi1e1:ai2e1:bi3ee")
+            .is_ok());
+
+        for (input, key) in &[
+            (&b"d1:bi1e1:ai2e1:bi3ee"[..], "b"),
+            (b"d1:ai1e1:ai1ee", "a"),
+            (b"ld1:ai1e1:ai1eee", "a"),
+        ] {
+            match builder.from_slice::<crate::Value>(input) {
+                Err(Error::Deserialize(msg)) => {
+                    assert_eq!(msg, format!("duplicate dictionary key {:?}", key));
+                }
+                result => panic!("unexpected result {:?} for {:?}", result, input),
+            }
+        }
+
+        let builder = builder.sorted_keys(true);
+        assert!(builder
+            .from_slice::<crate::Value>(b"d1:ai1e1:ai1ee")
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_builder_sorted_keys() -> Result<()> {
+        #[derive(Debug, Deserialize, PartialEq, Eq)]
+        struct S<'a> {
+            a: u64,
+            b: &'a str,
+        }
+
+        let lenient = Builder::new();
+        assert_eq!(
+            lenient.from_slice::<S<'_>>(b"d1:b2:hi1:ai1ee")?,
+            S { a: 1, b: "hi" }
+        );
+
+        let builder = Builder::new().sorted_keys(true);
+        assert_eq!(
+            builder.from_slice::<S<'_>>(b"d1:ai1e1:b2:hi1:ci2ee")?,
+            S { a: 1, b: "hi" }
+        );
+        assert_eq!(
+            builder
+                .from_slice::<BTreeMap<String, crate::Value>>(b"d1:ad1:zi1ee1:bd1:ai1eee")?
+                .len(),
+            2
+        );
+        for input in &[
+            &b"d1:b2:hi1:ai1ee"[..],
+            b"d1:ai1e1:ai1e1:b2:hie",
+            b"d1:ai1e1:b2:hi1:cd1:yi1e1:xi2eee",
+        ] {
+            assert!(matches!(
+                builder.from_slice::<S<'_>>(input),
+                Err(Error::Deserialize(_))
+            ));
+            assert!(matches!(
+                builder.from_slice::<crate::Value>(input),
+                Err(Error::Deserialize(_))
+            ));
+        }
+        assert!(matches!(
+            builder.from_slice::<BTreeMap<ByteBuf, u64>>(b"d1:bi1e1:ai2ee"),
+            Err(Error::Deserialize(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_builder_max_depth() -> Result<()> {
+        let builder = Builder::new().max_depth(2);
+        assert_eq!(builder.from_slice::<Vec<Vec<u64>>>(b"lli1eee")?, [[1]]);
+        assert_eq!(
+            builder.from_slice::<crate::Value>(b"d1:ad1:bi1eee")?["a"]["b"],
+            crate::Value::from(1_u64)
+        );
+        assert!(matches!(
+            builder.from_slice::<Vec<Vec<Vec<u64>>>>(b"llleee"),
+            Err(Error::Deserialize(_))
+        ));
+        assert!(matches!(
+            builder.from_slice::<crate::Value>(b"d1:ad1:bleee"),
+            Err(Error::Deserialize(_))
+        ));
+
+        let builder = Builder::new().max_depth(0);
+        assert_eq!(builder.from_slice::<u64>(b"i1e")?, 1);
+        assert!(matches!(
+            builder.from_slice::<BTreeMap<String, u64>>(b"de"),
+            Err(Error::Deserialize(_))
+        ));
+
+        // The limit is for the nesting depth, not the number of containers.
+        let builder = Builder::new().max_depth(2);
+        assert_eq!(
+            builder
+                .from_slice::<crate::Value>(b"llelelee")?
+                .as_array()
+                .map(Vec::len),
+            Some(3)
+        );
+        let mut de = Builder::new().max_depth(1).build_from_slice(b"lele");
+        assert!(Vec::<u64>::deserialize(&mut de)?.is_empty());
+        assert!(Vec::<u64>::deserialize(&mut de)?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_builder_from_reader() -> Result<()> {
+        let builder = Builder::new().strict(true).max_depth(1);
+        let value: BTreeMap<String, String> = builder.from_reader(&b"d1:a1:x1:b1:ye"[..])?;
+        assert_eq!(value.len(), 2);
+        for input in &[&b"d1:b1:x1:a1:ye"[..], b"d1:ai01ee", b"d1:ad1:ai1eee"] {
+            assert!(builder.from_reader::<_, crate::Value>(*input).is_err());
+        }
+
+        let mut de = builder.build_with_buffer(read::IoRead::new(&b"d1:ai1ee"[..]), Vec::new());
+        let value = crate::Value::deserialize(&mut de)?;
+        de.end()?;
+        assert_eq!(value["a"].as_u64(), Some(1));
+        Ok(())
+    }
+
+    #[test]
+    fn test_builder_track_ignored_keys() -> Result<()> {
+        #[derive(Debug, Deserialize)]
+        struct File {
+            length: u64,
+        }
+
+        #[derive(Debug, Deserialize)]
+        struct Info {
+            files: Vec<File>,
+            name: Option<String>,
+        }
+
+        #[derive(Debug, Deserialize)]
+        struct Metainfo {
+            info: Info,
+            #[serde(default)]
+            nodes: Vec<crate::Value>,
+        }
+
+        let input = b"d8:announce3:url4:infod5:filesld6:lengthi1e4:pathl1:aeed6:lengthi2e3:md50:ee4:name4:spam6:sourced1:xi1eee5:nodesld1:yi1eeee";
+
+        let mut de = Deserializer::from_slice(input);
+        Metainfo::deserialize(&mut de)?;
+        de.end()?;
+        assert!(de.ignored_keys().is_empty());
+
+        for builder in &[
+            Builder::new().track_ignored_keys(true),
+            Builder::new().track_ignored_keys(true).sorted_keys(true),
+        ] {
+            let mut de = builder.build_from_slice(input);
+            let metainfo = Metainfo::deserialize(&mut de)?;
+            de.end()?;
+            assert_eq!(metainfo.info.files[1].length, 2);
+            assert_eq!(metainfo.info.name.as_deref(), Some("spam"));
+            assert_eq!(metainfo.nodes.len(), 1);
+            assert_eq!(
+                de.ignored_keys(),
+                [
+                    vec![PathSegmentBuf::Key(b"announce".to_vec())],
+                    vec![
+                        PathSegmentBuf::Key(b"info".to_vec()),
+                        PathSegmentBuf::Key(b"files".to_vec()),
+                        PathSegmentBuf::Index(0),
+                        PathSegmentBuf::Key(b"path".to_vec()),
+                    ],
+                    vec![
+                        PathSegmentBuf::Key(b"info".to_vec()),
+                        PathSegmentBuf::Key(b"files".to_vec()),
+                        PathSegmentBuf::Index(1),
+                        PathSegmentBuf::Key(b"md5".to_vec()),
+                    ],
+                    vec![
+                        PathSegmentBuf::Key(b"info".to_vec()),
+                        PathSegmentBuf::Key(b"source".to_vec()),
+                    ],
+                ]
+            );
+        }
+
+        let mut de = Builder::new()
+            .track_ignored_keys(true)
+            .build_from_slice(b"d1:ad1:bi1eee");
+        de::IgnoredAny::deserialize(&mut de)?;
+        de.end()?;
+        assert!(de.ignored_keys().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_builder_deny_unknown_keys() -> Result<()> {
+        #[derive(Debug, Deserialize)]
+        struct Inner {
+            a: u64,
+        }
+
+        #[derive(Debug, Deserialize)]
+        struct Outer {
+            inner: Vec<Inner>,
+            rest: BTreeMap<String, crate::Value>,
+        }
+
+        let builder = Builder::new().deny_unknown_keys(true);
+        let outer: Outer = builder.from_slice(b"d5:innerld1:ai1eee4:restd1:bd1:ci1eeee")?;
+        assert_eq!(outer.inner[0].a, 1);
+        assert_eq!(outer.rest["b"]["c"].as_u64(), Some(1));
+
+        for (input, key) in &[
+            (&b"d5:innerld1:ai1e1:bi2eee4:restdee"[..], "b"),
+            (b"d5:extrali1ee5:innerle4:restdee", "extra"),
+            (b"d5:innerle4:restde1:\xffi1ee", "\u{fffd}"),
+        ] {
+            assert!(Builder::new().from_slice::<Outer>(input).is_ok());
+            match builder.from_slice::<Outer>(input) {
+                Err(Error::Deserialize(msg)) => {
+                    assert_eq!(msg, format!("unknown dictionary key {:?}", key));
+                }
+                _ => panic!(),
+            }
+        }
+
+        let mut de = builder
+            .track_ignored_keys(true)
+            .build_from_slice(b"d1:ai1e1:bi2ee");
+        assert!(Inner::deserialize(&mut de).is_err());
+        assert!(de.ignored_keys().is_empty());
+
+        // Buffered types drop unknown keys without the deserializer seeing them.
+        #[derive(Debug, Deserialize)]
+        struct Flattened {
+            #[serde(flatten)]
+            inner: Inner,
+        }
+
+        #[derive(Debug, Deserialize)]
+        #[serde(untagged)]
+        enum Untagged {
+            Inner(Inner),
+        }
+
+        let input = b"d1:ai1e1:ci2ee";
+        assert!(builder.from_slice::<Inner>(input).is_err());
+        assert_eq!(builder.from_slice::<Flattened>(input)?.inner.a, 1);
+        let Untagged::Inner(inner) = builder.from_slice::<Untagged>(input)?;
+        assert_eq!(inner.a, 1);
+        Ok(())
+    }
 }
@@ -135,6 +135,13 @@ impl From<Error> for io::Error {
     }
 }
 
+#[cfg(feature = "std")]
+impl From<io::Error> for Error {
+    fn from(other: io::Error) -> Self {
+        Error::IoError(other)
+    }
+}
+
 impl From<Utf8Error> for Error {
     fn from(other: Utf8Error) -> Self {
         Error::Utf8Error(other)
@@ -0,0 +1,813 @@
+//! A pull parser which reports the structure of Bencode data as events.
+//!
+//! A [Parser] yields an [Event] for every byte string, integer, and the start
+//! and end of every list and dictionary, along with the byte offset where the
+//! event starts. Tools such as pretty printers, linters, and filters can be
+//! built on the events without serde or a [Value][crate::Value] tree.
+//!
+//! ```rust
+//! use bt_bencode::event::{Event, Parser};
+//! use bt_bencode::value::Number;
+//!
+//! let mut parser = Parser::new(b"d1:ali1e4:spamee");
+//!
+//! assert_eq!(parser.next().transpose()?, Some((0, Event::DictStart)));
+//! assert_eq!(parser.next().transpose()?, Some((1, Event::Key(b"a"))));
+//! assert_eq!(parser.next().transpose()?, Some((4, Event::ListStart)));
+//! assert_eq!(parser.next().transpose()?, Some((5, Event::Int(Number::Unsigned(1)))));
+//! assert_eq!(parser.next().transpose()?, Some((8, Event::ByteStr(b"spam"))));
+//! assert_eq!(parser.next().transpose()?, Some((14, Event::End)));
+//! assert_eq!(parser.next().transpose()?, Some((15, Event::End)));
+//! assert_eq!(parser.next().transpose()?, None);
+//! parser.end()?;
+//! # Ok::<(), bt_bencode::Error>(())
+//! ```
+//!
+//! [`parse_with()`] pushes the same events to the callbacks of a
+//! [`BencodeSink`] instead, and [`rewrite()`] copies the events to a writer
+//! while dropping or replacing selected keys and values.
+//!
+//! Like the [Incremental][crate::incremental::Incremental] parser, the
+//! structure of the data is checked but dictionary keys are not checked for
+//! sorted order.
+
+use crate::{
+    de::PathSegment,
+    error::{Error, Result},
+    read::{Read, Ref, SliceRead},
+    value::{Number, RawValueBuf},
+    write::Write,
+};
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{borrow::Cow, string::String, vec::Vec};
+#[cfg(feature = "std")]
+use std::{borrow::Cow, string::String, vec::Vec};
+
+/// A structural element of Bencode data.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(not(feature = "big-int"), derive(Copy))]
+pub enum Event<'a> {
+    /// The start of a dictionary.
+    DictStart,
+    /// The start of a list.
+    ListStart,
+    /// A dictionary key.
+    Key(&'a [u8]),
+    /// A byte string value.
+    ByteStr(&'a [u8]),
+    /// An integer value.
+    Int(Number),
+    /// The end of the innermost list or dictionary.
+    End,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Container {
+    List,
+    DictKey,
+    DictValue,
+}
+
+/// A pull parser which yields [Event]s for a single value in a slice.
+///
+/// The parser is an [Iterator] of events and the byte offsets where they
+/// start. After the value is complete, the iterator returns `None` and
+/// [`end()`][Parser::end] can be called to check for trailing data. After an
+/// error is returned, the iterator returns `None`.
+#[derive(Debug)]
+pub struct Parser<'a> {
+    read: SliceRead<'a>,
+    stack: Vec<Container>,
+    done: bool,
+    failed: bool,
+}
+
+impl<'a> Parser<'a> {
+    /// Constructs a parser for the value at the start of the slice.
+    #[must_use]
+    pub fn new(s: &'a [u8]) -> Self {
+        Parser {
+            read: SliceRead::new(s),
+            stack: Vec::new(),
+            done: false,
+            failed: false,
+        }
+    }
+
+    /// Returns the byte offset of the next event.
+    #[must_use]
+    pub fn offset(&self) -> usize {
+        self.read.byte_offset()
+    }
+
+    /// Returns the number of lists and dictionaries which are currently open.
+    #[must_use]
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Should be called after the value is parsed to validate that the entire
+    /// slice was read.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the value is not complete or if there are
+    /// unconsumed bytes after the value.
+    pub fn end(&mut self) -> Result<()> {
+        if !self.done || self.failed {
+            return Err(Error::EofWhileParsingValue);
+        }
+        match self.read.peek() {
+            Some(r) => r.and(Err(Error::TrailingData)),
+            None => Ok(()),
+        }
+    }
+
+    fn parse_event(&mut self) -> Result<Event<'a>> {
+        let b = self.read.peek().ok_or(Error::EofWhileParsingValue)??;
+        if self.stack.last() == Some(&Container::DictKey) {
+            return match b {
+                b'e' => self.end_container(),
+                b'0'..=b'9' => {
+                    let key = self.parse_byte_str()?;
+                    self.set_top(Container::DictValue);
+                    Ok(Event::Key(key))
+                }
+                _ => Err(Error::KeyMustBeAByteStr),
+            };
+        }
+
+        let event = match b {
+            b'0'..=b'9' => Event::ByteStr(self.parse_byte_str()?),
+            b'i' => {
+                self.read.next();
+                let mut buf = Vec::new();
+                let num_str = self.read.parse_integer(&mut buf)?;
+                Event::Int(num_str.parse()?)
+            }
+            b'l' => {
+                self.read.next();
+                self.stack.push(Container::List);
+                return Ok(Event::ListStart);
+            }
+            b'd' => {
+                self.read.next();
+                self.stack.push(Container::DictKey);
+                return Ok(Event::DictStart);
+            }
+            b'e' if self.stack.last() == Some(&Container::List) => return self.end_container(),
+            b'e' if self.stack.last() == Some(&Container::DictValue) => {
+                return Err(Error::InvalidDict)
+            }
+            _ => return Err(Error::ExpectedSomeValue),
+        };
+        self.end_value();
+        Ok(event)
+    }
+
+    fn parse_byte_str(&mut self) -> Result<&'a [u8]> {
+        let mut buf = Vec::new();
+        match self.read.parse_byte_str(&mut buf)? {
+            Ref::Source(bytes) => Ok(bytes),
+            Ref::Buffer(_) => unreachable!("slices are always borrowed"),
+        }
+    }
+
+    fn end_container(&mut self) -> Result<Event<'a>> {
+        self.read.next();
+        self.stack.pop();
+        self.end_value();
+        Ok(Event::End)
+    }
+
+    /// Updates the state after a complete value.
+    fn end_value(&mut self) {
+        match self.stack.last() {
+            None => self.done = true,
+            Some(Container::DictValue) => self.set_top(Container::DictKey),
+            Some(_) => {}
+        }
+    }
+
+    fn set_top(&mut self, container: Container) {
+        if let Some(top) = self.stack.last_mut() {
+            *top = container;
+        }
+    }
+}
+
+impl<'a> Iterator for Parser<'a> {
+    type Item = Result<(usize, Event<'a>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let offset = self.offset();
+        match self.parse_event() {
+            Ok(event) => Some(Ok((offset, event))),
+            Err(err) => {
+                self.done = true;
+                self.failed = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+/// Receives the events of [`parse_with()`].
+///
+/// Every method does nothing by default, so a sink only needs to implement
+/// the events it is interested in. Each method is given the byte offset
+/// where the event starts. Returning an error stops parsing and the error is
+/// returned from [`parse_with()`].
+pub trait BencodeSink<'a> {
+    /// Called at the start of a dictionary.
+    ///
+    /// # Errors
+    ///
+    /// An error stops parsing.
+    fn dict_start(&mut self, offset: usize) -> Result<()> {
+        let _ = offset;
+        Ok(())
+    }
+
+    /// Called at the start of a list.
+    ///
+    /// # Errors
+    ///
+    /// An error stops parsing.
+    fn list_start(&mut self, offset: usize) -> Result<()> {
+        let _ = offset;
+        Ok(())
+    }
+
+    /// Called for a dictionary key.
+    ///
+    /// # Errors
+    ///
+    /// An error stops parsing.
+    fn key(&mut self, offset: usize, key: &'a [u8]) -> Result<()> {
+        let _ = (offset, key);
+        Ok(())
+    }
+
+    /// Called for a byte string value.
+    ///
+    /// # Errors
+    ///
+    /// An error stops parsing.
+    fn byte_str(&mut self, offset: usize, value: &'a [u8]) -> Result<()> {
+        let _ = (offset, value);
+        Ok(())
+    }
+
+    /// Called for an integer value.
+    ///
+    /// # Errors
+    ///
+    /// An error stops parsing.
+    fn int(&mut self, offset: usize, value: Number) -> Result<()> {
+        let _ = (offset, value);
+        Ok(())
+    }
+
+    /// Called at the end of a list or dictionary.
+    ///
+    /// # Errors
+    ///
+    /// An error stops parsing.
+    fn end(&mut self, offset: usize) -> Result<()> {
+        let _ = offset;
+        Ok(())
+    }
+}
+
+impl<'a, S> BencodeSink<'a> for &mut S
+where
+    S: BencodeSink<'a> + ?Sized,
+{
+    fn dict_start(&mut self, offset: usize) -> Result<()> {
+        (**self).dict_start(offset)
+    }
+
+    fn list_start(&mut self, offset: usize) -> Result<()> {
+        (**self).list_start(offset)
+    }
+
+    fn key(&mut self, offset: usize, key: &'a [u8]) -> Result<()> {
+        (**self).key(offset, key)
+    }
+
+    fn byte_str(&mut self, offset: usize, value: &'a [u8]) -> Result<()> {
+        (**self).byte_str(offset, value)
+    }
+
+    fn int(&mut self, offset: usize, value: Number) -> Result<()> {
+        (**self).int(offset, value)
+    }
+
+    fn end(&mut self, offset: usize) -> Result<()> {
+        (**self).end(offset)
+    }
+}
+
+/// Parses a single value and pushes its events to a sink in one forward pass.
+///
+/// No value tree is built and byte strings are passed to the sink as
+/// borrowed slices of the input.
+///
+/// ```rust
+/// use bt_bencode::event::{parse_with, BencodeSink};
+/// use bt_bencode::Result;
+///
+/// #[derive(Default)]
+/// struct Counter {
+///     byte_strs: usize,
+///     ints: usize,
+/// }
+///
+/// impl<'a> BencodeSink<'a> for Counter {
+///     fn byte_str(&mut self, _offset: usize, _value: &'a [u8]) -> Result<()> {
+///         self.byte_strs += 1;
+///         Ok(())
+///     }
+///
+///     fn int(&mut self, _offset: usize, _value: bt_bencode::value::Number) -> Result<()> {
+///         self.ints += 1;
+///         Ok(())
+///     }
+/// }
+///
+/// let mut counter = Counter::default();
+/// parse_with(b"d1:ali1ei2ee1:b4:spame", &mut counter)?;
+/// assert_eq!(counter.byte_strs, 1);
+/// assert_eq!(counter.ints, 2);
+/// # Ok::<(), bt_bencode::Error>(())
+/// ```
+///
+/// # Errors
+///
+/// An error is returned if the input is not exactly one valid value or if
+/// the sink returns an error.
+pub fn parse_with<'a, S>(input: &'a [u8], mut sink: S) -> Result<()>
+where
+    S: BencodeSink<'a>,
+{
+    let mut parser = Parser::new(input);
+    for event in parser.by_ref() {
+        let (offset, event) = event?;
+        match event {
+            Event::DictStart => sink.dict_start(offset)?,
+            Event::ListStart => sink.list_start(offset)?,
+            Event::Key(key) => sink.key(offset, key)?,
+            Event::ByteStr(value) => sink.byte_str(offset, value)?,
+            Event::Int(value) => sink.int(offset, value)?,
+            Event::End => sink.end(offset)?,
+        }
+    }
+    parser.end()
+}
+
+/// What [`rewrite()`] does with an event.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Action {
+    /// Writes the event unchanged.
+    Keep,
+    /// Drops the value, including the values nested in a list or
+    /// dictionary. For a key, the key and its value are dropped.
+    Skip,
+    /// Writes a different key. Only valid for [`Event::Key`].
+    RenameKey(Vec<u8>),
+    /// Writes a different value instead of the value, including the values
+    /// nested in a list or dictionary. Not valid for [`Event::Key`].
+    Replace(RawValueBuf),
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Frame {
+    List(usize),
+    Dict,
+}
+
+fn write_event<W: Write>(writer: &mut W, event: &Event<'_>) -> Result<()> {
+    match event {
+        Event::DictStart => writer.write_all(b"d"),
+        Event::ListStart => writer.write_all(b"l"),
+        Event::Key(bytes) | Event::ByteStr(bytes) => write_byte_str(writer, bytes),
+        Event::Int(n) => {
+            let mut buf = itoa::Buffer::new();
+            writer.write_all_vectored(&[b"i", n.format(&mut buf).as_bytes(), b"e"])
+        }
+        Event::End => writer.write_all(b"e"),
+    }
+}
+
+fn write_byte_str<W: Write>(writer: &mut W, bytes: &[u8]) -> Result<()> {
+    let mut buf = itoa::Buffer::new();
+    writer.write_all_vectored(&[buf.format(bytes.len()).as_bytes(), b":", bytes])
+}
+
+fn invalid_action(reason: &str) -> Error {
+    Error::Serialize(String::from(reason))
+}
+
+/// Copies a value to a writer while dropping, renaming, or replacing parts of it.
+///
+/// The closure is called with the path to the event and the event for every
+/// key and value, but not for [`Event::End`]. For a value, the path ends with
+/// the value's key or list index. For a key, the path is the path of the
+/// dictionary. Keys and list indexes in the path are the ones in the input,
+/// before any keys are renamed or values are skipped. The closure is not
+/// called for values nested in a value which is skipped or replaced.
+///
+/// Only the path to the current event is kept in memory, so large inputs can
+/// be rewritten without building a value tree. Renamed keys are written in
+/// the order they are encoded in the input, so renaming a key may leave a
+/// dictionary unsorted. Skipping the top level value writes nothing.
+///
+/// ```rust
+/// use bt_bencode::event::{rewrite, Action, Event};
+/// use bt_bencode::PathSegment;
+///
+/// let input = b"d8:announce8:http://a7:comment9:long text4:infod6:lengthi1eee";
+/// let mut output = Vec::new();
+/// rewrite(input, &mut output, |path, event| match (path, event) {
+///     ([], Event::Key(b"comment")) => Ok(Action::Skip),
+///     ([PathSegment::Key(b"announce")], Event::ByteStr(_)) => {
+///         Ok(Action::Replace(bt_bencode::value::to_raw_value("http://b")?))
+///     }
+///     _ => Ok(Action::Keep),
+/// })?;
+/// assert_eq!(output, b"d8:announce8:http://b4:infod6:lengthi1eee");
+/// # Ok::<(), bt_bencode::Error>(())
+/// ```
+///
+/// # Errors
+///
+/// An error is returned if the input is not exactly one valid value, if the
+/// closure returns an error or an action which is not valid for the event,
+/// or if writing fails. Part of the output may have been written when an
+/// error is returned.
+pub fn rewrite<'a, W, F>(input: &'a [u8], mut writer: W, mut f: F) -> Result<()>
+where
+    W: Write,
+    F: FnMut(&[PathSegment<'a>], &Event<'a>) -> Result<Action>,
+{
+    let mut parser = Parser::new(input);
+    let mut frames: Vec<Frame> = Vec::new();
+    let mut path: Vec<PathSegment<'a>> = Vec::new();
+    let mut pending_key: Option<Cow<'a, [u8]>> = None;
+    let mut skip_value = false;
+    let mut skip_depth = 0_usize;
+
+    for event in parser.by_ref() {
+        let (_, event) = event?;
+
+        if skip_depth > 0 {
+            match event {
+                Event::DictStart | Event::ListStart => skip_depth += 1,
+                Event::End => skip_depth -= 1,
+                Event::Key(_) | Event::ByteStr(_) | Event::Int(_) => {}
+            }
+            if skip_depth == 0 && !frames.is_empty() {
+                path.pop();
+            }
+            continue;
+        }
+
+        match event {
+            Event::End => {
+                frames.pop();
+                writer.write_all(b"e")?;
+                if !frames.is_empty() {
+                    path.pop();
+                }
+            }
+            Event::Key(key) => {
+                let action = f(&path, &event)?;
+                path.push(PathSegment::Key(key));
+                match action {
+                    Action::Keep => pending_key = Some(Cow::Borrowed(key)),
+                    Action::RenameKey(key) => pending_key = Some(Cow::Owned(key)),
+                    Action::Skip => skip_value = true,
+                    Action::Replace(_) => return Err(invalid_action("a key cannot be replaced")),
+                }
+            }
+            Event::DictStart | Event::ListStart | Event::ByteStr(_) | Event::Int(_) => {
+                if let Some(Frame::List(index)) = frames.last_mut() {
+                    path.push(PathSegment::Index(*index));
+                    *index += 1;
+                }
+                let action = if skip_value {
+                    skip_value = false;
+                    Action::Skip
+                } else {
+                    f(&path, &event)?
+                };
+                let is_container = event == Event::DictStart || event == Event::ListStart;
+                let kept = action == Action::Keep;
+                match action {
+                    Action::Keep => {
+                        if let Some(key) = pending_key.take() {
+                            write_byte_str(&mut writer, &key)?;
+                        }
+                        write_event(&mut writer, &event)?;
+                        match event {
+                            Event::DictStart => frames.push(Frame::Dict),
+                            Event::ListStart => frames.push(Frame::List(0)),
+                            _ => {}
+                        }
+                    }
+                    Action::Skip => pending_key = None,
+                    Action::Replace(value) => {
+                        if let Some(key) = pending_key.take() {
+                            write_byte_str(&mut writer, &key)?;
+                        }
+                        writer.write_all(value.as_bytes())?;
+                    }
+                    Action::RenameKey(_) => {
+                        return Err(invalid_action("only a key can be renamed"));
+                    }
+                }
+                if !is_container {
+                    if !frames.is_empty() {
+                        path.pop();
+                    }
+                } else if !kept {
+                    skip_depth = 1;
+                }
+            }
+        }
+    }
+    parser.end()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(all(feature = "alloc", not(feature = "std")))]
+    use alloc::vec;
+
+    fn events(input: &[u8]) -> Result<Vec<Event<'_>>> {
+        let mut parser = Parser::new(input);
+        let events = parser
+            .by_ref()
+            .map(|r| r.map(|(_, event)| event))
+            .collect::<Result<Vec<_>>>()?;
+        parser.end()?;
+        Ok(events)
+    }
+
+    #[test]
+    fn test_events() -> Result<()> {
+        assert_eq!(events(b"i-12e")?, [Event::Int(Number::Signed(-12))]);
+        assert_eq!(events(b"0:")?, [Event::ByteStr(b"")]);
+        assert_eq!(events(b"le")?, [Event::ListStart, Event::End]);
+        assert_eq!(events(b"de")?, [Event::DictStart, Event::End]);
+        assert_eq!(
+            events(b"d1:bd1:ale1:cli1eee1:a0:e")?,
+            [
+                Event::DictStart,
+                Event::Key(b"b"),
+                Event::DictStart,
+                Event::Key(b"a"),
+                Event::ListStart,
+                Event::End,
+                Event::Key(b"c"),
+                Event::ListStart,
+                Event::Int(Number::Unsigned(1)),
+                Event::End,
+                Event::End,
+                Event::Key(b"a"),
+                Event::ByteStr(b""),
+                Event::End,
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_offsets() -> Result<()> {
+        let input = b"ld1:xi1ee2:abe";
+        let mut parser = Parser::new(&input[..]);
+        let offsets = parser
+            .by_ref()
+            .map(|r| r.map(|(offset, _)| offset))
+            .collect::<Result<Vec<_>>>()?;
+        assert_eq!(offsets, [0, 1, 2, 5, 8, 9, 13]);
+        assert_eq!(parser.offset(), input.len());
+        assert_eq!(parser.depth(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid() {
+        for input in &[
+            &b""[..],
+            b"e",
+            b"x",
+            b"l",
+            b"li1e",
+            b"di1ei2ee",
+            b"d1:ae",
+            b"ie",
+            b"i1x",
+            b"5:spam",
+            b"i1ei2e",
+        ] {
+            assert!(events(input).is_err(), "{:?}", input);
+        }
+
+        let mut parser = Parser::new(b"lxe");
+        assert!(parser.next().unwrap().is_ok());
+        assert!(parser.next().unwrap().is_err());
+        assert!(parser.next().is_none());
+        assert!(parser.end().is_err());
+
+        let mut parser = Parser::new(b"x");
+        assert!(parser.next().unwrap().is_err());
+        assert!(parser.end().is_err());
+    }
+
+    #[test]
+    fn test_parse_with() -> Result<()> {
+        /// Finds the `interval` of a tracker response.
+        #[derive(Default)]
+        struct Interval {
+            depth: usize,
+            in_interval: bool,
+            interval: Option<Number>,
+            ends: Vec<usize>,
+        }
+
+        impl<'a> BencodeSink<'a> for Interval {
+            fn dict_start(&mut self, _offset: usize) -> Result<()> {
+                self.depth += 1;
+                Ok(())
+            }
+
+            fn list_start(&mut self, _offset: usize) -> Result<()> {
+                self.depth += 1;
+                Ok(())
+            }
+
+            fn key(&mut self, _offset: usize, key: &'a [u8]) -> Result<()> {
+                self.in_interval = self.depth == 1 && key == b"interval";
+                Ok(())
+            }
+
+            fn int(&mut self, _offset: usize, value: Number) -> Result<()> {
+                if self.in_interval {
+                    self.interval = Some(value);
+                }
+                Ok(())
+            }
+
+            fn end(&mut self, offset: usize) -> Result<()> {
+                self.depth -= 1;
+                self.ends.push(offset);
+                Ok(())
+            }
+        }
+
+        let mut sink = Interval::default();
+        parse_with(b"d5:filesd8:intervali1ee8:intervali1800ee", &mut sink)?;
+        assert_eq!(sink.interval, Some(Number::Unsigned(1800)));
+        assert_eq!(sink.ends, [22, 39]);
+
+        assert!(parse_with(b"d8:intervali1800e", &mut Interval::default()).is_err());
+        assert!(parse_with(b"lei1e", &mut Interval::default()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_with_sink_error() {
+        struct Stop;
+
+        impl<'a> BencodeSink<'a> for Stop {
+            fn int(&mut self, _offset: usize, _value: Number) -> Result<()> {
+                Err(Error::Deserialize("stop".into()))
+            }
+        }
+
+        assert!(parse_with(b"l0:e", Stop).is_ok());
+        assert!(matches!(
+            parse_with(b"l0:i1ee", Stop),
+            Err(Error::Deserialize(_))
+        ));
+    }
+
+    fn rewrite_vec<'a, F>(input: &'a [u8], f: F) -> Result<Vec<u8>>
+    where
+        F: FnMut(&[PathSegment<'a>], &Event<'a>) -> Result<Action>,
+    {
+        let mut output = Vec::new();
+        rewrite(input, &mut output, f)?;
+        Ok(output)
+    }
+
+    #[test]
+    fn test_rewrite_keep() -> Result<()> {
+        let input = b"d1:ad1:bli1ei-2e0:e1:c4:spame1:dlee";
+        let mut paths = Vec::new();
+        let output = rewrite_vec(input, |path, event| {
+            paths.push((path.to_vec(), Event::clone(event)));
+            Ok(Action::Keep)
+        })?;
+        assert_eq!(output, &input[..]);
+        assert_eq!(
+            paths,
+            [
+                (vec![], Event::DictStart),
+                (vec![], Event::Key(b"a")),
+                (vec![PathSegment::Key(b"a")], Event::DictStart),
+                (vec![PathSegment::Key(b"a")], Event::Key(b"b")),
+                (
+                    vec![PathSegment::Key(b"a"), PathSegment::Key(b"b")],
+                    Event::ListStart
+                ),
+                (
+                    vec![
+                        PathSegment::Key(b"a"),
+                        PathSegment::Key(b"b"),
+                        PathSegment::Index(0)
+                    ],
+                    Event::Int(Number::Unsigned(1))
+                ),
+                (
+                    vec![
+                        PathSegment::Key(b"a"),
+                        PathSegment::Key(b"b"),
+                        PathSegment::Index(1)
+                    ],
+                    Event::Int(Number::Signed(-2))
+                ),
+                (
+                    vec![
+                        PathSegment::Key(b"a"),
+                        PathSegment::Key(b"b"),
+                        PathSegment::Index(2)
+                    ],
+                    Event::ByteStr(b"")
+                ),
+                (vec![PathSegment::Key(b"a")], Event::Key(b"c")),
+                (
+                    vec![PathSegment::Key(b"a"), PathSegment::Key(b"c")],
+                    Event::ByteStr(b"spam")
+                ),
+                (vec![], Event::Key(b"d")),
+                (vec![PathSegment::Key(b"d")], Event::ListStart),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_rewrite() -> Result<()> {
+        let input = b"d1:ad1:bli1ei2ei3eee1:cl1:x1:ye1:d4:spame";
+        let output = rewrite_vec(input, |path, event| match (path, event) {
+            ([], Event::Key(b"c")) => Ok(Action::RenameKey(b"e".to_vec())),
+            ([PathSegment::Key(b"a"), PathSegment::Key(b"b"), PathSegment::Index(1)], _) => {
+                Ok(Action::Skip)
+            }
+            ([PathSegment::Key(b"c"), PathSegment::Index(0)], _) => Ok(Action::Skip),
+            ([PathSegment::Key(b"d")], _) => Ok(Action::Skip),
+            _ => Ok(Action::Keep),
+        })?;
+        assert_eq!(output, b"d1:ad1:bli1ei3eee1:el1:yee");
+
+        let output = rewrite_vec(input, |path, _| match path {
+            [PathSegment::Key(b"a")] => Ok(Action::Replace(crate::value::to_raw_value(&1)?)),
+            [PathSegment::Key(b"c")] => Ok(Action::Skip),
+            _ => Ok(Action::Keep),
+        })?;
+        assert_eq!(output, b"d1:ai1e1:d4:spame");
+
+        let output = rewrite_vec(input, |_, event| match event {
+            Event::Key(b"a") => Ok(Action::Skip),
+            Event::Key(_) => Ok(Action::RenameKey(b"z".to_vec())),
+            _ => Ok(Action::Keep),
+        })?;
+        assert_eq!(output, b"d1:zl1:x1:ye1:z4:spame");
+        Ok(())
+    }
+
+    #[test]
+    fn test_rewrite_invalid() {
+        assert!(rewrite_vec(b"d1:ai1ee", |_, event| match event {
+            Event::Key(_) => Ok(Action::Replace(crate::value::to_raw_value(&1)?)),
+            _ => Ok(Action::Keep),
+        })
+        .is_err());
+        assert!(rewrite_vec(b"li1ee", |_, event| match event {
+            Event::Int(_) => Ok(Action::RenameKey(b"a".to_vec())),
+            _ => Ok(Action::Keep),
+        })
+        .is_err());
+        assert!(rewrite_vec(b"li1e", |_, _| Ok(Action::Keep)).is_err());
+        assert!(rewrite_vec(b"lei1e", |_, _| Ok(Action::Keep)).is_err());
+    }
+}
@@ -0,0 +1,25 @@
+//! Types for messages in the extension protocol.
+//!
+//! [BEP 10][bep_0010] defines an extended message (message ID 20) whose
+//! payload starts with an extended message ID. An ID of 0 is the extended
+//! handshake, which maps extension names to the IDs used for their messages.
+//!
+//! [bep_0010]: https://www.bittorrent.org/beps/bep_0010.html
+
+mod donthave;
+mod handshake;
+mod holepunch;
+mod metadata;
+mod pex;
+
+pub use donthave::DontHave;
+pub use handshake::ExtendedHandshake;
+pub use holepunch::{HolepunchError, HolepunchMessage};
+pub use metadata::{metadata_piece_count, MetadataMessage, METADATA_PIECE_LEN};
+pub use pex::{PexFlags, PexMessage};
+
+/// The peer wire message ID of an extended message.
+pub const EXTENDED_MESSAGE_ID: u8 = 20;
+
+/// The extended message ID of the extended handshake.
+pub const HANDSHAKE_ID: u8 = 0;
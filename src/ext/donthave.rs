@@ -0,0 +1,61 @@
+//! Messages for the `lt_donthave` extension.
+
+use crate::error::{Error, Result};
+
+/// An `lt_donthave` message from [BEP 54][bep_0054].
+///
+/// The payload is the 4 byte big-endian index of a piece which the sender no
+/// longer has.
+///
+/// ```rust
+/// use bt_bencode::ext::DontHave;
+///
+/// let msg = DontHave { piece: 258 };
+///
+/// assert_eq!(msg.to_bytes(), [0, 0, 1, 2]);
+/// assert_eq!(DontHave::parse(&[0, 0, 1, 2])?, msg);
+/// # Ok::<(), bt_bencode::Error>(())
+/// ```
+///
+/// [bep_0054]: https://www.bittorrent.org/beps/bep_0054.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DontHave {
+    /// The index of the piece.
+    pub piece: u32,
+}
+
+impl DontHave {
+    /// Parses a message.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the payload is not 4 bytes long.
+    pub fn parse(s: &[u8]) -> Result<Self> {
+        match s {
+            [a, b, c, d] => Ok(DontHave {
+                piece: u32::from_be_bytes([*a, *b, *c, *d]),
+            }),
+            _ if s.len() < 4 => Err(Error::EofWhileParsingValue),
+            _ => Err(Error::TrailingData),
+        }
+    }
+
+    /// Encodes the message.
+    #[must_use]
+    pub fn to_bytes(self) -> [u8; 4] {
+        self.piece.to_be_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() -> Result<()> {
+        assert_eq!(DontHave::parse(&[0xff, 0, 0, 0])?.piece, 0xff00_0000);
+        assert!(DontHave::parse(&[0, 0, 0]).is_err());
+        assert!(DontHave::parse(&[0, 0, 0, 0, 0]).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,127 @@
+//! The extended handshake.
+
+use crate::{compact, error::Result};
+use serde_derive::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    string::String,
+    vec::Vec,
+};
+
+/// The payload of an extended handshake message.
+///
+/// ```rust
+/// use bt_bencode::ext::ExtendedHandshake;
+///
+/// let input = b"d1:md11:ut_metadatai3e6:ut_pexi1ee13:metadata_sizei31235e1:pi6881e4:reqqi500e1:v13:\xc2\xb5Torrent 1.26:yourip4:\xc0\x00\x02\x01e";
+/// let handshake = ExtendedHandshake::from_slice(input)?;
+///
+/// assert_eq!(handshake.extension_id("ut_metadata"), Some(3));
+/// assert_eq!(handshake.extension_id("lt_donthave"), None);
+/// assert_eq!(handshake.metadata_size, Some(31235));
+/// assert_eq!(handshake.yourip, Some("192.0.2.1".parse().unwrap()));
+/// assert_eq!(handshake.to_vec()?, &input[..]);
+/// # Ok::<(), bt_bencode::Error>(())
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExtendedHandshake {
+    /// The extended message IDs keyed by extension name.
+    ///
+    /// An ID of 0 means the extension is disabled.
+    #[serde(default)]
+    pub m: BTreeMap<String, u8>,
+    /// The local TCP listen port.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub p: Option<u16>,
+    /// The client name and version.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub v: Option<String>,
+    /// The IP address of the receiving peer as seen by the sender.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "compact::addr::option"
+    )]
+    pub yourip: Option<IpAddr>,
+    /// The sender's IPv6 address.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "compact::addr::option"
+    )]
+    pub ipv6: Option<Ipv6Addr>,
+    /// The sender's IPv4 address.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "compact::addr::option"
+    )]
+    pub ipv4: Option<Ipv4Addr>,
+    /// The number of outstanding request messages the sender supports.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reqq: Option<u32>,
+    /// The size of the info dictionary in bytes from [BEP 9][bep_0009].
+    ///
+    /// [bep_0009]: https://www.bittorrent.org/beps/bep_0009.html
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata_size: Option<u64>,
+}
+
+impl ExtendedHandshake {
+    /// Returns the extended message ID for an extension if it is enabled.
+    #[must_use]
+    pub fn extension_id(&self, name: &str) -> Option<u8> {
+        self.m.get(name).cloned().filter(|id| *id != 0)
+    }
+
+    /// Returns true if the extension is enabled.
+    #[must_use]
+    pub fn supports(&self, name: &str) -> bool {
+        self.extension_id(name).is_some()
+    }
+
+    /// Deserializes an extended handshake with [`from_slice()`][crate::from_slice].
+    pub fn from_slice(s: &[u8]) -> Result<Self> {
+        crate::from_slice(s)
+    }
+
+    /// Serializes the extended handshake with [`to_vec()`][crate::to_vec].
+    pub fn to_vec(&self) -> Result<Vec<u8>> {
+        crate::to_vec(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() -> Result<()> {
+        let mut handshake = ExtendedHandshake::default();
+        handshake.m.insert(String::from("ut_pex"), 1);
+        handshake.m.insert(String::from("lt_donthave"), 0);
+        handshake.ipv4 = Some(Ipv4Addr::new(192, 0, 2, 1));
+        handshake.ipv6 = Some("2001:db8::1".parse().unwrap());
+        handshake.yourip = Some("2001:db8::2".parse().unwrap());
+
+        let encoded = handshake.to_vec()?;
+        assert_eq!(
+            &encoded[..26],
+            b"d4:ipv44:\xc0\x00\x02\x014:ipv616:\x20\x01\x0d\xb8"
+        );
+        let decoded = ExtendedHandshake::from_slice(&encoded)?;
+        assert_eq!(decoded, handshake);
+        assert!(decoded.supports("ut_pex"));
+        assert!(!decoded.supports("lt_donthave"));
+        assert_eq!(ExtendedHandshake::default().to_vec()?, b"d1:mdee");
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(ExtendedHandshake::from_slice(b"d6:yourip3:abce").is_err());
+        assert!(ExtendedHandshake::from_slice(b"d4:ipv416:aaaaaaaaaaaaaaaae").is_err());
+        assert!(ExtendedHandshake::from_slice(b"d1:md3:fooi300eee").is_err());
+    }
+}
@@ -0,0 +1,187 @@
+//! Messages for the `ut_holepunch` extension.
+//!
+//! Unlike most extension messages, the payload is a fixed binary layout
+//! rather than a bencoded dictionary.
+
+use crate::error::{Error, Result};
+use core::convert::TryFrom;
+use std::{
+    net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6},
+    string::String,
+    vec::Vec,
+};
+
+/// The error code in a holepunch error message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum HolepunchError {
+    /// The target peer is unknown to the relay (1).
+    NoSuchPeer,
+    /// The relay is no longer connected to the target peer (2).
+    NotConnected,
+    /// The target peer does not support the extension (3).
+    NoSupport,
+    /// The target is the relay itself (4).
+    NoSelf,
+    /// Any other code.
+    Other(u32),
+}
+
+impl HolepunchError {
+    /// Returns the numeric code.
+    #[must_use]
+    pub fn code(self) -> u32 {
+        match self {
+            HolepunchError::NoSuchPeer => 1,
+            HolepunchError::NotConnected => 2,
+            HolepunchError::NoSupport => 3,
+            HolepunchError::NoSelf => 4,
+            HolepunchError::Other(code) => code,
+        }
+    }
+}
+
+impl From<u32> for HolepunchError {
+    fn from(code: u32) -> Self {
+        match code {
+            1 => HolepunchError::NoSuchPeer,
+            2 => HolepunchError::NotConnected,
+            3 => HolepunchError::NoSupport,
+            4 => HolepunchError::NoSelf,
+            code => HolepunchError::Other(code),
+        }
+    }
+}
+
+/// A `ut_holepunch` message from [BEP 55][bep_0055].
+///
+/// ```rust
+/// use bt_bencode::ext::HolepunchMessage;
+///
+/// let msg = HolepunchMessage::Connect("192.0.2.1:6881".parse().unwrap());
+/// let encoded = msg.to_vec();
+///
+/// assert_eq!(encoded, [1, 0, 192, 0, 2, 1, 0x1a, 0xe1, 0, 0, 0, 0]);
+/// assert_eq!(HolepunchMessage::parse(&encoded)?, msg);
+/// # Ok::<(), bt_bencode::Error>(())
+/// ```
+///
+/// [bep_0055]: https://www.bittorrent.org/beps/bep_0055.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HolepunchMessage {
+    /// Asks the relay to connect the sender with the target peer (`msg_type` 0).
+    Rendezvous(SocketAddr),
+    /// Tells a peer to connect to the given peer (`msg_type` 1).
+    Connect(SocketAddr),
+    /// Tells the sender of a rendezvous that it failed (`msg_type` 2).
+    Error(SocketAddr, HolepunchError),
+}
+
+fn invalid(msg: &str) -> Error {
+    Error::Deserialize(String::from(msg))
+}
+
+impl HolepunchMessage {
+    /// Returns the address in the message.
+    #[must_use]
+    pub fn addr(&self) -> SocketAddr {
+        match self {
+            HolepunchMessage::Rendezvous(addr)
+            | HolepunchMessage::Connect(addr)
+            | HolepunchMessage::Error(addr, _) => *addr,
+        }
+    }
+
+    /// Parses a message.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the message type or address type is unknown or
+    /// if the length does not match the address type.
+    pub fn parse(s: &[u8]) -> Result<Self> {
+        if s.len() < 2 {
+            return Err(Error::EofWhileParsingValue);
+        }
+        let (addr, rest) = match s[1] {
+            0 if s.len() == 12 => {
+                let ip = Ipv4Addr::new(s[2], s[3], s[4], s[5]);
+                let port = u16::from_be_bytes([s[6], s[7]]);
+                (SocketAddr::V4(SocketAddrV4::new(ip, port)), &s[8..])
+            }
+            1 if s.len() == 24 => {
+                let ip = <[u8; 16]>::try_from(&s[2..18]).expect("length is checked");
+                let port = u16::from_be_bytes([s[18], s[19]]);
+                (
+                    SocketAddr::V6(SocketAddrV6::new(Ipv6Addr::from(ip), port, 0, 0)),
+                    &s[20..],
+                )
+            }
+            0 | 1 => return Err(invalid("invalid ut_holepunch message length")),
+            _ => return Err(invalid("unknown ut_holepunch address type")),
+        };
+        let err_code = u32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]);
+        match s[0] {
+            0 => Ok(HolepunchMessage::Rendezvous(addr)),
+            1 => Ok(HolepunchMessage::Connect(addr)),
+            2 => Ok(HolepunchMessage::Error(
+                addr,
+                HolepunchError::from(err_code),
+            )),
+            _ => Err(invalid("unknown ut_holepunch message type")),
+        }
+    }
+
+    /// Encodes the message.
+    #[must_use]
+    pub fn to_vec(&self) -> Vec<u8> {
+        let (msg_type, err_code) = match self {
+            HolepunchMessage::Rendezvous(_) => (0, 0),
+            HolepunchMessage::Connect(_) => (1, 0),
+            HolepunchMessage::Error(_, err) => (2, err.code()),
+        };
+        let mut out = Vec::with_capacity(24);
+        out.push(msg_type);
+        match self.addr() {
+            SocketAddr::V4(addr) => {
+                out.push(0);
+                out.extend_from_slice(&addr.ip().octets());
+                out.extend_from_slice(&addr.port().to_be_bytes());
+            }
+            SocketAddr::V6(addr) => {
+                out.push(1);
+                out.extend_from_slice(&addr.ip().octets());
+                out.extend_from_slice(&addr.port().to_be_bytes());
+            }
+        }
+        out.extend_from_slice(&err_code.to_be_bytes());
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() -> Result<()> {
+        let v6: SocketAddr = "[2001:db8::1]:6881".parse().unwrap();
+        for msg in &[
+            HolepunchMessage::Rendezvous(v6),
+            HolepunchMessage::Error(v6, HolepunchError::NoSupport),
+            HolepunchMessage::Error("192.0.2.1:1".parse().unwrap(), HolepunchError::Other(9)),
+        ] {
+            assert_eq!(HolepunchMessage::parse(&msg.to_vec())?, *msg);
+        }
+        let encoded = HolepunchMessage::Error(v6, HolepunchError::NoSelf).to_vec();
+        assert_eq!(encoded.len(), 24);
+        assert_eq!(&encoded[20..], &[0, 0, 0, 4]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(HolepunchMessage::parse(&[0]).is_err());
+        assert!(HolepunchMessage::parse(&[0, 0, 1, 2, 3, 4, 5, 6, 0, 0, 0]).is_err());
+        assert!(HolepunchMessage::parse(&[0, 2, 192, 0, 2, 1, 0, 80, 0, 0, 0, 0]).is_err());
+        assert!(HolepunchMessage::parse(&[3, 0, 192, 0, 2, 1, 0, 80, 0, 0, 0, 0]).is_err());
+    }
+}
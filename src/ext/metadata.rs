@@ -0,0 +1,201 @@
+//! Messages for the `ut_metadata` extension.
+
+use crate::error::{Error, Result};
+use serde_derive::{Deserialize, Serialize};
+use std::{string::String, vec::Vec};
+
+/// The length of every metadata piece except the last.
+pub const METADATA_PIECE_LEN: usize = 16 * 1024;
+
+/// Returns the number of pieces for metadata of the given size.
+#[must_use]
+pub fn metadata_piece_count(metadata_size: u64) -> u64 {
+    (metadata_size + METADATA_PIECE_LEN as u64 - 1) / METADATA_PIECE_LEN as u64
+}
+
+#[derive(Serialize, Deserialize)]
+struct RawMetadataMessage {
+    msg_type: u8,
+    piece: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    total_size: Option<u64>,
+}
+
+/// A `ut_metadata` message from [BEP 9][bep_0009].
+///
+/// A `data` message is a dictionary immediately followed by the piece of
+/// metadata, so [`parse`][MetadataMessage::parse] returns the bytes after the
+/// dictionary as well.
+///
+/// ```rust
+/// use bt_bencode::ext::MetadataMessage;
+///
+/// let (msg, payload) = MetadataMessage::parse(b"d8:msg_typei1e5:piecei0e10:total_sizei8eed4:spami1ee")?;
+///
+/// assert_eq!(msg, MetadataMessage::Data { piece: 0, total_size: 8 });
+/// assert_eq!(payload, b"d4:spami1ee");
+/// # Ok::<(), bt_bencode::Error>(())
+/// ```
+///
+/// [bep_0009]: https://www.bittorrent.org/beps/bep_0009.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MetadataMessage {
+    /// Requests a piece of the metadata (`msg_type` 0).
+    Request {
+        /// The index of the piece.
+        piece: u32,
+    },
+    /// Sends a piece of the metadata (`msg_type` 1).
+    Data {
+        /// The index of the piece.
+        piece: u32,
+        /// The size of the entire metadata in bytes.
+        total_size: u64,
+    },
+    /// Rejects a request for a piece (`msg_type` 2).
+    Reject {
+        /// The index of the piece.
+        piece: u32,
+    },
+}
+
+impl MetadataMessage {
+    /// Returns the index of the piece.
+    #[must_use]
+    pub fn piece(&self) -> u32 {
+        match self {
+            MetadataMessage::Request { piece }
+            | MetadataMessage::Data { piece, .. }
+            | MetadataMessage::Reject { piece } => *piece,
+        }
+    }
+
+    /// Parses a message and returns the bytes following the dictionary.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the dictionary is not a valid message, if a
+    /// `data` message's piece is longer than [`METADATA_PIECE_LEN`], or if
+    /// another message has trailing bytes.
+    pub fn parse(s: &[u8]) -> Result<(Self, &[u8])> {
+        let (span, dict) = crate::raw_values(s)
+            .next()
+            .unwrap_or(Err(Error::EofWhileParsingValue))?;
+        let raw: RawMetadataMessage = crate::from_slice(dict)?;
+        let payload = &s[span.end..];
+
+        let msg = match raw.msg_type {
+            0 => MetadataMessage::Request { piece: raw.piece },
+            1 => MetadataMessage::Data {
+                piece: raw.piece,
+                total_size: raw
+                    .total_size
+                    .ok_or_else(|| <Error as serde::de::Error>::missing_field("total_size"))?,
+            },
+            2 => MetadataMessage::Reject { piece: raw.piece },
+            msg_type => {
+                return Err(Error::Deserialize(format!(
+                    "unknown ut_metadata message type {}",
+                    msg_type
+                )))
+            }
+        };
+
+        match msg {
+            MetadataMessage::Data { .. } if payload.len() > METADATA_PIECE_LEN => Err(
+                Error::Deserialize(String::from("metadata piece is too long")),
+            ),
+            MetadataMessage::Data { .. } => Ok((msg, payload)),
+            _ if payload.is_empty() => Ok((msg, payload)),
+            _ => Err(Error::TrailingData),
+        }
+    }
+
+    /// Serializes the message's dictionary with [`to_vec()`][crate::to_vec].
+    ///
+    /// The piece of a `data` message must be appended after the dictionary;
+    /// see [`encode_data`][MetadataMessage::encode_data].
+    pub fn to_vec(&self) -> Result<Vec<u8>> {
+        let raw = match *self {
+            MetadataMessage::Request { piece } => RawMetadataMessage {
+                msg_type: 0,
+                piece,
+                total_size: None,
+            },
+            MetadataMessage::Data { piece, total_size } => RawMetadataMessage {
+                msg_type: 1,
+                piece,
+                total_size: Some(total_size),
+            },
+            MetadataMessage::Reject { piece } => RawMetadataMessage {
+                msg_type: 2,
+                piece,
+                total_size: None,
+            },
+        };
+        crate::to_vec(&raw)
+    }
+
+    /// Serializes a `data` message followed by the piece of metadata.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the piece is longer than [`METADATA_PIECE_LEN`].
+    pub fn encode_data(piece: u32, total_size: u64, data: &[u8]) -> Result<Vec<u8>> {
+        if data.len() > METADATA_PIECE_LEN {
+            return Err(Error::Serialize(String::from("metadata piece is too long")));
+        }
+        let mut out = MetadataMessage::Data { piece, total_size }.to_vec()?;
+        out.extend_from_slice(data);
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_messages() -> Result<()> {
+        for (msg, encoded) in &[
+            (
+                MetadataMessage::Request { piece: 0 },
+                &b"d8:msg_typei0e5:piecei0ee"[..],
+            ),
+            (
+                MetadataMessage::Reject { piece: 3 },
+                b"d8:msg_typei2e5:piecei3ee",
+            ),
+        ] {
+            assert_eq!(msg.to_vec()?, *encoded);
+            assert_eq!(MetadataMessage::parse(encoded)?, (*msg, &b""[..]));
+        }
+
+        let encoded = MetadataMessage::encode_data(1, 16385, b"x")?;
+        assert_eq!(
+            encoded,
+            &b"d8:msg_typei1e5:piecei1e10:total_sizei16385eex"[..]
+        );
+        let (msg, payload) = MetadataMessage::parse(&encoded)?;
+        assert_eq!(msg.piece(), 1);
+        assert_eq!(payload, b"x");
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(MetadataMessage::parse(b"").is_err());
+        assert!(MetadataMessage::parse(b"d8:msg_typei0e5:piecei0eex").is_err());
+        assert!(MetadataMessage::parse(b"d8:msg_typei1e5:piecei0ee").is_err());
+        assert!(MetadataMessage::parse(b"d8:msg_typei9e5:piecei0ee").is_err());
+        assert!(MetadataMessage::encode_data(0, 0, &[0; METADATA_PIECE_LEN + 1]).is_err());
+    }
+
+    #[test]
+    fn test_piece_count() {
+        assert_eq!(metadata_piece_count(0), 0);
+        assert_eq!(metadata_piece_count(1), 1);
+        assert_eq!(metadata_piece_count(16384), 1);
+        assert_eq!(metadata_piece_count(16385), 2);
+    }
+}
@@ -0,0 +1,225 @@
+//! Messages for the `ut_pex` extension.
+
+use crate::{compact, error::Result};
+use core::fmt;
+use serde_derive::{Deserialize, Serialize};
+use std::{
+    net::{SocketAddr, SocketAddrV4, SocketAddrV6},
+    vec::Vec,
+};
+
+/// The flags for a peer added in a peer exchange message.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct PexFlags(pub u8);
+
+impl PexFlags {
+    /// The peer prefers encryption.
+    pub const PREFERS_ENCRYPTION: PexFlags = PexFlags(0x01);
+    /// The peer is a seed or only uploads.
+    pub const SEED: PexFlags = PexFlags(0x02);
+    /// The peer supports uTP.
+    pub const SUPPORTS_UTP: PexFlags = PexFlags(0x04);
+    /// The peer supports the `ut_holepunch` extension.
+    pub const SUPPORTS_HOLEPUNCH: PexFlags = PexFlags(0x08);
+    /// The peer is reachable (the sender received an outgoing connection from it).
+    pub const REACHABLE: PexFlags = PexFlags(0x10);
+
+    /// Returns true if all of the flags in `other` are set.
+    #[must_use]
+    pub fn contains(self, other: PexFlags) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Sets the flags in `other`.
+    pub fn insert(&mut self, other: PexFlags) {
+        self.0 |= other.0;
+    }
+}
+
+impl core::ops::BitOr for PexFlags {
+    type Output = PexFlags;
+
+    fn bitor(self, rhs: PexFlags) -> PexFlags {
+        PexFlags(self.0 | rhs.0)
+    }
+}
+
+impl fmt::Debug for PexFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PexFlags({:#04x})", self.0)
+    }
+}
+
+/// A `ut_pex` message from [BEP 11][bep_0011].
+///
+/// The peers are encoded as compact peer info, and the flags for added peers
+/// are one byte per peer in the same order.
+///
+/// ```rust
+/// use bt_bencode::ext::{PexFlags, PexMessage};
+///
+/// let mut msg = PexMessage::default();
+/// msg.add_peer("192.0.2.1:6881".parse().unwrap(), PexFlags::SEED);
+/// msg.drop_peer("[2001:db8::1]:6881".parse().unwrap());
+///
+/// let decoded = PexMessage::from_slice(&msg.to_vec()?)?;
+/// assert_eq!(
+///     decoded.added_peers().collect::<Vec<_>>(),
+///     vec![("192.0.2.1:6881".parse().unwrap(), PexFlags::SEED)]
+/// );
+/// assert_eq!(decoded.dropped_peers().count(), 1);
+/// # Ok::<(), bt_bencode::Error>(())
+/// ```
+///
+/// [bep_0011]: https://www.bittorrent.org/beps/bep_0011.html
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PexMessage {
+    /// The IPv4 peers which have been added.
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        with = "compact::peers"
+    )]
+    pub added: Vec<SocketAddrV4>,
+    /// The flags for each added IPv4 peer.
+    #[serde(
+        rename = "added.f",
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        with = "serde_bytes"
+    )]
+    pub added_flags: Vec<u8>,
+    /// The IPv6 peers which have been added.
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        with = "compact::peers6"
+    )]
+    pub added6: Vec<SocketAddrV6>,
+    /// The flags for each added IPv6 peer.
+    #[serde(
+        rename = "added6.f",
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        with = "serde_bytes"
+    )]
+    pub added6_flags: Vec<u8>,
+    /// The IPv4 peers which have been dropped.
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        with = "compact::peers"
+    )]
+    pub dropped: Vec<SocketAddrV4>,
+    /// The IPv6 peers which have been dropped.
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        with = "compact::peers6"
+    )]
+    pub dropped6: Vec<SocketAddrV6>,
+}
+
+impl PexMessage {
+    /// Adds a peer with its flags.
+    pub fn add_peer(&mut self, peer: SocketAddr, flags: PexFlags) {
+        match peer {
+            SocketAddr::V4(peer) => {
+                self.added_flags.resize(self.added.len(), 0);
+                self.added.push(peer);
+                self.added_flags.push(flags.0);
+            }
+            SocketAddr::V6(peer) => {
+                self.added6_flags.resize(self.added6.len(), 0);
+                self.added6.push(peer);
+                self.added6_flags.push(flags.0);
+            }
+        }
+    }
+
+    /// Drops a peer.
+    pub fn drop_peer(&mut self, peer: SocketAddr) {
+        match peer {
+            SocketAddr::V4(peer) => self.dropped.push(peer),
+            SocketAddr::V6(peer) => self.dropped6.push(peer),
+        }
+    }
+
+    /// Returns an iterator over the added peers of both address families and their flags.
+    ///
+    /// Peers without a flag byte have no flags set.
+    pub fn added_peers(&self) -> impl Iterator<Item = (SocketAddr, PexFlags)> + '_ {
+        let flag = |flags: &[u8], i: usize| PexFlags(flags.get(i).cloned().unwrap_or(0));
+        let v4 = self
+            .added
+            .iter()
+            .enumerate()
+            .map(move |(i, peer)| (SocketAddr::V4(*peer), flag(&self.added_flags, i)));
+        let v6 = self
+            .added6
+            .iter()
+            .enumerate()
+            .map(move |(i, peer)| (SocketAddr::V6(*peer), flag(&self.added6_flags, i)));
+        v4.chain(v6)
+    }
+
+    /// Returns an iterator over the dropped peers of both address families.
+    pub fn dropped_peers(&self) -> impl Iterator<Item = SocketAddr> + '_ {
+        self.dropped
+            .iter()
+            .map(|peer| SocketAddr::V4(*peer))
+            .chain(self.dropped6.iter().map(|peer| SocketAddr::V6(*peer)))
+    }
+
+    /// Deserializes a peer exchange message with [`from_slice()`][crate::from_slice].
+    pub fn from_slice(s: &[u8]) -> Result<Self> {
+        crate::from_slice(s)
+    }
+
+    /// Serializes the peer exchange message with [`to_vec()`][crate::to_vec].
+    pub fn to_vec(&self) -> Result<Vec<u8>> {
+        crate::to_vec(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode() -> Result<()> {
+        let input = b"d5:added12:\xc0\x00\x02\x01\x1a\xe1\x0a\x00\x00\x01\x00\x507:added.f1:\x126:added60:7:dropped6:\x0a\x00\x00\x02\x00\x508:dropped60:e";
+        let msg = PexMessage::from_slice(input)?;
+        assert_eq!(msg.added.len(), 2);
+        assert_eq!(
+            msg.added_peers().collect::<Vec<_>>(),
+            vec![
+                (
+                    "192.0.2.1:6881".parse().unwrap(),
+                    PexFlags::SEED | PexFlags::REACHABLE
+                ),
+                ("10.0.0.1:80".parse().unwrap(), PexFlags::default()),
+            ]
+        );
+        assert_eq!(
+            msg.dropped_peers().collect::<Vec<_>>(),
+            vec!["10.0.0.2:80".parse::<SocketAddr>().unwrap()]
+        );
+        assert_eq!(
+            msg.to_vec()?,
+            &b"d5:added12:\xc0\x00\x02\x01\x1a\xe1\x0a\x00\x00\x01\x00\x507:added.f1:\x127:dropped6:\x0a\x00\x00\x02\x00\x50e"[..]
+        );
+
+        assert!(PexMessage::from_slice(b"d5:added5:aaaaae").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_flags() {
+        let mut flags = PexFlags::PREFERS_ENCRYPTION;
+        flags.insert(PexFlags::SUPPORTS_UTP);
+        assert!(flags.contains(PexFlags::SUPPORTS_UTP));
+        assert!(!flags.contains(PexFlags::SUPPORTS_HOLEPUNCH));
+        assert_eq!(flags.0, 0x05);
+    }
+}
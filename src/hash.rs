@@ -0,0 +1,419 @@
+//! Computes digests (e.g. BitTorrent info hashes) while serializing and deserializing.
+//!
+//! [`HashWrite`] wraps a [Write] target and updates a digest with every byte
+//! written by the serializer. Creating a torrent can write the `info`
+//! dictionary and compute its info hash in a single pass.
+//!
+//! [`Hashed`] is a field type which deserializes a value and computes the
+//! digest of exactly the bytes the value was encoded with.
+//!
+//! ```rust
+//! # #[cfg(feature = "sha1")]
+//! # {
+//! use bt_bencode::{hash::HashWrite, Serializer, Value};
+//! use serde::Serialize;
+//!
+//! let info: Value = bt_bencode::from_slice(b"d6:lengthi1e4:name4:spame")?;
+//!
+//! let mut ser = Serializer::new(HashWrite::<_, sha1::Sha1>::new(Vec::new()));
+//! info.serialize(&mut ser)?;
+//! let (output, info_hash) = ser.into_inner().finalize();
+//!
+//! assert_eq!(output, b"d6:lengthi1e4:name4:spame");
+//! assert_eq!(info_hash.len(), 20);
+//! # }
+//! # Ok::<(), bt_bencode::Error>(())
+//! ```
+
+use crate::error::Result;
+use crate::write::Write;
+use core::{fmt, marker::PhantomData, ops::Deref};
+use digest::{Digest, FixedOutput, Output, OutputSizeUser, Update};
+use serde::{
+    de::{self, DeserializeOwned},
+    Deserialize, Serialize,
+};
+
+/// A [Write] target which updates a digest with all of the written bytes.
+#[derive(Clone, Debug)]
+pub struct HashWrite<W, D> {
+    writer: W,
+    digest: D,
+}
+
+impl<W, D> HashWrite<W, D>
+where
+    D: Default,
+{
+    /// Wraps the writer with a new digest.
+    pub fn new(writer: W) -> Self {
+        Self::with_digest(writer, D::default())
+    }
+}
+
+impl<W, D> HashWrite<W, D> {
+    /// Wraps the writer with an existing digest.
+    pub fn with_digest(writer: W, digest: D) -> Self {
+        Self { writer, digest }
+    }
+
+    /// Returns a reference to the inner writer.
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Returns a reference to the digest.
+    pub fn digest(&self) -> &D {
+        &self.digest
+    }
+
+    /// Returns the inner writer and the digest.
+    pub fn into_parts(self) -> (W, D) {
+        (self.writer, self.digest)
+    }
+}
+
+impl<W, D> HashWrite<W, D>
+where
+    D: FixedOutput,
+{
+    /// Returns the inner writer and the digest output of all of the written bytes.
+    pub fn finalize(self) -> (W, Output<D>) {
+        (self.writer, self.digest.finalize_fixed())
+    }
+}
+
+impl<W, D> Write for HashWrite<W, D>
+where
+    W: Write,
+    D: Update,
+{
+    #[inline]
+    fn write_all(&mut self, buf: &[u8]) -> Result<()> {
+        self.writer.write_all(buf)?;
+        self.digest.update(buf);
+        Ok(())
+    }
+
+    fn write_all_vectored(&mut self, bufs: &[&[u8]]) -> Result<()> {
+        self.writer.write_all_vectored(bufs)?;
+        for buf in bufs {
+            self.digest.update(buf);
+        }
+        Ok(())
+    }
+}
+
+/// A [`HashWrite`] which computes a SHA-1 digest (e.g. a v1 info hash).
+#[cfg(feature = "sha1")]
+#[cfg_attr(docsrs, doc(cfg(feature = "sha1")))]
+pub type Sha1Write<W> = HashWrite<W, sha1::Sha1>;
+
+/// A [`HashWrite`] which computes a SHA-256 digest (e.g. a v2 info hash).
+#[cfg(feature = "sha2")]
+#[cfg_attr(docsrs, doc(cfg(feature = "sha2")))]
+pub type Sha256Write<W> = HashWrite<W, sha2::Sha256>;
+
+/// A deserialized value and the digest of its encoded bytes.
+///
+/// The exact encoded bytes of the value are captured from the input (in
+/// the same way as [`RawValue`][crate::value::RawValue]), hashed, and then
+/// deserialized into `T`. When deserializing from a slice,
+/// the raw bytes are borrowed instead of copied.
+///
+/// ```rust
+/// # #[cfg(feature = "sha1")]
+/// # {
+/// use bt_bencode::{hash::Hashed, Value};
+/// use serde_derive::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Metainfo {
+///     info: Hashed<Value, sha1::Sha1>,
+/// }
+///
+/// let metainfo: Metainfo = bt_bencode::from_slice(b"d4:infod6:lengthi1e4:name4:spamee")?;
+/// assert_eq!(metainfo.info["name"].as_str(), Some("spam"));
+///
+/// use sha1::Digest;
+/// assert_eq!(
+///     metainfo.info.digest(),
+///     &sha1::Sha1::digest(b"d6:lengthi1e4:name4:spame")
+/// );
+/// # }
+/// # Ok::<(), bt_bencode::Error>(())
+/// ```
+pub struct Hashed<T, D>
+where
+    D: OutputSizeUser,
+{
+    value: T,
+    digest: Output<D>,
+}
+
+impl<T, D> Hashed<T, D>
+where
+    D: OutputSizeUser,
+{
+    /// Returns a reference to the value.
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    /// Returns the digest of the value's encoded bytes.
+    pub fn digest(&self) -> &Output<D> {
+        &self.digest
+    }
+
+    /// Returns the value and the digest.
+    pub fn into_parts(self) -> (T, Output<D>) {
+        (self.value, self.digest)
+    }
+}
+
+impl<T, D> Deref for Hashed<T, D>
+where
+    D: OutputSizeUser,
+{
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T, D> Clone for Hashed<T, D>
+where
+    T: Clone,
+    D: OutputSizeUser,
+{
+    fn clone(&self) -> Self {
+        Self {
+            value: self.value.clone(),
+            digest: self.digest.clone(),
+        }
+    }
+}
+
+impl<T, D> fmt::Debug for Hashed<T, D>
+where
+    T: fmt::Debug,
+    D: OutputSizeUser,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Hashed")
+            .field("value", &self.value)
+            .field("digest", &self.digest)
+            .finish()
+    }
+}
+
+impl<T, D> PartialEq for Hashed<T, D>
+where
+    T: PartialEq,
+    D: OutputSizeUser,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value && self.digest == other.digest
+    }
+}
+
+impl<T, D> Eq for Hashed<T, D>
+where
+    T: Eq,
+    D: OutputSizeUser,
+{
+}
+
+impl<T, D> Serialize for Hashed<T, D>
+where
+    T: Serialize,
+    D: OutputSizeUser,
+{
+    fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.value.serialize(serializer)
+    }
+}
+
+impl<'de, T, D> Deserialize<'de> for Hashed<T, D>
+where
+    T: DeserializeOwned,
+    D: Digest,
+{
+    fn deserialize<De>(deserializer: De) -> core::result::Result<Self, De::Error>
+    where
+        De: de::Deserializer<'de>,
+    {
+        struct HashedVisitor<T, D>(PhantomData<fn() -> (T, D)>);
+
+        impl<'de, T, D> de::Visitor<'de> for HashedVisitor<T, D>
+        where
+            T: DeserializeOwned,
+            D: Digest,
+        {
+            type Value = Hashed<T, D>;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("the raw bytes of a Bencode value")
+            }
+
+            fn visit_bytes<E>(self, value: &[u8]) -> core::result::Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                let digest = D::digest(value);
+                let value = crate::from_slice(value).map_err(E::custom)?;
+                Ok(Hashed { value, digest })
+            }
+
+            // Deserializers which do not recognize the raw value token pass
+            // the value through as a newtype struct. The value is re-encoded,
+            // so the digest is of the canonical encoding.
+            fn visit_newtype_struct<De>(self, d: De) -> core::result::Result<Self::Value, De::Error>
+            where
+                De: de::Deserializer<'de>,
+            {
+                let value = crate::Value::deserialize(d)?;
+                let bytes = crate::to_vec(&value).map_err(de::Error::custom)?;
+                self.visit_bytes(&bytes)
+            }
+        }
+
+        deserializer
+            .deserialize_newtype_struct(crate::value::raw::TOKEN, HashedVisitor(PhantomData))
+    }
+}
+
+/// A [`Hashed`] value with a SHA-1 digest (e.g. a v1 info hash).
+#[cfg(feature = "sha1")]
+#[cfg_attr(docsrs, doc(cfg(feature = "sha1")))]
+pub type Sha1Hashed<T> = Hashed<T, sha1::Sha1>;
+
+/// A [`Hashed`] value with a SHA-256 digest (e.g. a v2 info hash).
+#[cfg(feature = "sha2")]
+#[cfg_attr(docsrs, doc(cfg(feature = "sha2")))]
+pub type Sha256Hashed<T> = Hashed<T, sha2::Sha256>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Serializer;
+    use serde::Serialize;
+
+    #[cfg(all(feature = "alloc", not(feature = "std")))]
+    use alloc::vec::Vec;
+    #[cfg(feature = "std")]
+    use std::vec::Vec;
+
+    #[derive(Default)]
+    struct Collect(Vec<u8>);
+
+    impl Update for Collect {
+        fn update(&mut self, data: &[u8]) {
+            self.0.extend_from_slice(data);
+        }
+    }
+
+    #[test]
+    fn test_digest_matches_output() -> Result<()> {
+        let mut ser = Serializer::new(HashWrite::<_, Collect>::new(Vec::new()));
+        ("spam", 42, ["a", "b"]).serialize(&mut ser)?;
+        let (output, digest) = ser.into_inner().into_parts();
+        assert_eq!(output, b"l4:spami42el1:a1:bee");
+        assert_eq!(digest.0, output);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "sha1")]
+    fn test_sha1() -> Result<()> {
+        use sha1::Digest;
+
+        let mut ser = Serializer::new(Sha1Write::new(Vec::new()));
+        "spam".serialize(&mut ser)?;
+        let (output, hash) = ser.into_inner().finalize();
+        assert_eq!(hash, sha1::Sha1::digest(&output));
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "sha2")]
+    fn test_sha256() -> Result<()> {
+        use sha2::Digest;
+
+        let mut ser = Serializer::new(Sha256Write::new(Vec::new()));
+        "spam".serialize(&mut ser)?;
+        let (output, hash) = ser.into_inner().finalize();
+        assert_eq!(hash, sha2::Sha256::digest(&output));
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "sha1")]
+    fn test_hashed_from_slice_and_reader() -> Result<()> {
+        use crate::Value;
+        use serde_derive::Deserialize;
+        use sha1::Digest;
+
+        #[cfg(all(feature = "alloc", not(feature = "std")))]
+        use alloc::string::String;
+
+        #[derive(Deserialize)]
+        struct Metainfo {
+            announce: String,
+            info: Sha1Hashed<Value>,
+        }
+
+        let input = b"d8:announce4:test4:infod6:lengthi1e4:name4:spamee";
+        let expected = sha1::Sha1::digest(b"d6:lengthi1e4:name4:spame");
+
+        let metainfo: Metainfo = crate::from_slice(input)?;
+        assert_eq!(metainfo.announce, "test");
+        assert_eq!(metainfo.info.digest(), &expected);
+        assert_eq!(metainfo.info.get("length").and_then(Value::as_u64), Some(1));
+
+        #[cfg(feature = "std")]
+        {
+            let metainfo: Metainfo = crate::from_reader(&input[..])?;
+            assert_eq!(metainfo.info.digest(), &expected);
+        }
+
+        let output = crate::to_vec(&metainfo.info)?;
+        assert_eq!(output, b"d6:lengthi1e4:name4:spame");
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "sha1")]
+    fn test_hashed_byte_str() -> Result<()> {
+        use serde_derive::Deserialize;
+        use sha1::Digest;
+
+        #[derive(Deserialize)]
+        struct Message {
+            info: Sha1Hashed<u64>,
+        }
+
+        let message: Message = crate::from_slice(b"d4:infoi1ee")?;
+        assert_eq!(*message.info, 1);
+        assert_eq!(message.info.digest(), &sha1::Sha1::digest(b"i1e"));
+        assert!(crate::from_slice::<Message>(b"d4:info3:i1ee").is_err());
+
+        #[derive(Deserialize)]
+        struct Name {
+            name: Sha1Hashed<serde_bytes::ByteBuf>,
+        }
+
+        let message: Name = crate::from_slice(b"d4:name4:spame")?;
+        assert_eq!(message.name.as_slice(), b"spam");
+        assert_eq!(message.name.digest(), &sha1::Sha1::digest(b"4:spam"));
+
+        let value: crate::Value = crate::from_slice(b"d4:name4:spame")?;
+        let message: Name = crate::from_value(value)?;
+        assert_eq!(message.name.digest(), &sha1::Sha1::digest(b"4:spam"));
+        Ok(())
+    }
+}
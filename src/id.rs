@@ -0,0 +1,280 @@
+//! Fixed length identifiers such as node IDs and info hashes.
+//!
+//! [`Id20`] holds a 20 byte identifier (a DHT node ID or a v1 info hash) and
+//! [`Id32`] holds a 32 byte identifier (a v2 info hash). Both are serialized
+//! as byte strings and formatted as lowercase hex.
+//!
+//! ```rust
+//! use bt_bencode::id::Id20;
+//!
+//! let a: Id20 = "0000000000000000000000000000000000000001".parse()?;
+//! let b: Id20 = "8000000000000000000000000000000000000001".parse()?;
+//!
+//! assert_eq!(a.distance(&b).to_string(), "8000000000000000000000000000000000000000");
+//! assert_eq!(a.bucket_index(&b), Some(159));
+//! assert_eq!(bt_bencode::to_vec(&a)?.len(), 23);
+//! # Ok::<(), bt_bencode::Error>(())
+//! ```
+
+use crate::error::{Error, Result};
+use core::{convert::TryFrom, fmt, str::FromStr};
+use serde::{
+    de::{self, Deserialize, Deserializer, Visitor},
+    ser::{Serialize, Serializer},
+};
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::string::String;
+#[cfg(feature = "std")]
+use std::string::String;
+
+fn hex_value(b: u8) -> Option<u8> {
+    match b {
+        b'0'..=b'9' => Some(b - b'0'),
+        b'a'..=b'f' => Some(b - b'a' + 10),
+        b'A'..=b'F' => Some(b - b'A' + 10),
+        _ => None,
+    }
+}
+
+fn decode_hex(s: &str, out: &mut [u8]) -> Result<()> {
+    let s = s.as_bytes();
+    if s.len() != out.len() * 2 {
+        return Err(Error::Deserialize(String::from("invalid hex ID length")));
+    }
+    for (b, pair) in out.iter_mut().zip(s.chunks(2)) {
+        match (hex_value(pair[0]), hex_value(pair[1])) {
+            (Some(hi), Some(lo)) => *b = hi << 4 | lo,
+            _ => return Err(Error::Deserialize(String::from("invalid hex in ID"))),
+        }
+    }
+    Ok(())
+}
+
+macro_rules! id_type {
+    ($name:ident, $len:expr, $doc:expr) => {
+        #[doc = $doc]
+        #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+        pub struct $name(pub [u8; $len]);
+
+        impl $name {
+            /// The length of the identifier in bytes.
+            pub const LEN: usize = $len;
+
+            /// The number of bits in the identifier.
+            pub const BITS: usize = $len * 8;
+
+            /// Constructs an identifier from bytes.
+            #[must_use]
+            pub const fn new(bytes: [u8; $len]) -> Self {
+                $name(bytes)
+            }
+
+            /// Returns the identifier as bytes.
+            #[must_use]
+            pub fn as_bytes(&self) -> &[u8; $len] {
+                &self.0
+            }
+
+            /// Returns the XOR distance to another identifier.
+            #[must_use]
+            pub fn distance(&self, other: &Self) -> Self {
+                let mut out = [0; $len];
+                for (o, (a, b)) in out.iter_mut().zip(self.0.iter().zip(other.0.iter())) {
+                    *o = a ^ b;
+                }
+                $name(out)
+            }
+
+            /// Returns the number of leading zero bits.
+            #[must_use]
+            pub fn leading_zeros(&self) -> usize {
+                let mut zeros = 0;
+                for b in &self.0 {
+                    zeros += b.leading_zeros() as usize;
+                    if *b != 0 {
+                        break;
+                    }
+                }
+                zeros
+            }
+
+            /// Returns the index of the routing table bucket which `other` belongs in.
+            ///
+            /// The index is the position of the highest bit which differs
+            /// between the identifiers, counted from the least significant
+            /// bit, so identifiers which share a longer prefix have a lower
+            /// index. `None` is returned if the identifiers are equal.
+            #[must_use]
+            pub fn bucket_index(&self, other: &Self) -> Option<usize> {
+                let zeros = self.distance(other).leading_zeros();
+                if zeros == Self::BITS {
+                    None
+                } else {
+                    Some(Self::BITS - 1 - zeros)
+                }
+            }
+        }
+
+        impl From<[u8; $len]> for $name {
+            fn from(bytes: [u8; $len]) -> Self {
+                $name(bytes)
+            }
+        }
+
+        impl<'a> TryFrom<&'a [u8]> for $name {
+            type Error = Error;
+
+            fn try_from(bytes: &'a [u8]) -> Result<Self> {
+                <[u8; $len]>::try_from(bytes)
+                    .map($name)
+                    .map_err(|_| Error::InvalidByteStrLen)
+            }
+        }
+
+        impl AsRef<[u8]> for $name {
+            fn as_ref(&self) -> &[u8] {
+                &self.0
+            }
+        }
+
+        impl fmt::LowerHex for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                for b in &self.0 {
+                    write!(f, "{:02x}", b)?;
+                }
+                Ok(())
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt::LowerHex::fmt(self, f)
+            }
+        }
+
+        impl fmt::Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}({:x})", stringify!($name), self)
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = Error;
+
+            fn from_str(s: &str) -> Result<Self> {
+                let mut out = [0; $len];
+                decode_hex(s, &mut out)?;
+                Ok($name(out))
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                serializer.serialize_bytes(&self.0)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                struct IdVisitor;
+
+                impl<'de> Visitor<'de> for IdVisitor {
+                    type Value = $name;
+
+                    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                        write!(f, "a {} byte string", $len)
+                    }
+
+                    fn visit_bytes<E>(self, v: &[u8]) -> core::result::Result<$name, E>
+                    where
+                        E: de::Error,
+                    {
+                        <[u8; $len]>::try_from(v)
+                            .map($name)
+                            .map_err(|_| de::Error::invalid_length(v.len(), &self))
+                    }
+                }
+
+                deserializer.deserialize_bytes(IdVisitor)
+            }
+        }
+    };
+}
+
+id_type!(
+    Id20,
+    20,
+    "A 20 byte identifier such as a DHT node ID or a v1 info hash."
+);
+id_type!(Id32, 32, "A 32 byte identifier such as a v2 info hash.");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(all(feature = "alloc", not(feature = "std")))]
+    use alloc::{format, string::ToString};
+
+    #[test]
+    fn test_hex() -> Result<()> {
+        let id: Id20 = "b44a0e20fa5b7cecb77156333b4268dfd7c30afb".parse()?;
+        assert_eq!(id.0[0], 0xb4);
+        assert_eq!(id.to_string(), "b44a0e20fa5b7cecb77156333b4268dfd7c30afb");
+        assert_eq!(
+            format!("{:?}", id),
+            "Id20(b44a0e20fa5b7cecb77156333b4268dfd7c30afb)"
+        );
+        assert_eq!(
+            "B44A0E20FA5B7CECB77156333B4268DFD7C30AFB".parse::<Id20>()?,
+            id
+        );
+        assert!("b44a".parse::<Id20>().is_err());
+        assert!("g44a0e20fa5b7cecb77156333b4268dfd7c30afb"
+            .parse::<Id20>()
+            .is_err());
+
+        let id = Id32::new([0xab; 32]);
+        assert_eq!(id.to_string().parse::<Id32>()?, id);
+        Ok(())
+    }
+
+    #[test]
+    fn test_serde() -> Result<()> {
+        let id = Id20::new(*b"abcdefghij0123456789");
+        let encoded = crate::to_vec(&id)?;
+        assert_eq!(encoded, b"20:abcdefghij0123456789");
+        assert_eq!(crate::from_slice::<Id20>(&encoded)?, id);
+        assert!(crate::from_slice::<Id20>(b"3:abc").is_err());
+        assert!(crate::from_slice::<Id32>(&encoded).is_err());
+        assert_eq!(Id20::try_from(&b"abcdefghij0123456789"[..])?, id);
+        Ok(())
+    }
+
+    #[test]
+    fn test_distance() {
+        let zero = Id20::default();
+        let mut bytes = [0; 20];
+        bytes[19] = 1;
+        let one = Id20::new(bytes);
+        bytes[0] = 0x10;
+        let far = Id20::new(bytes);
+
+        assert_eq!(zero.distance(&one), one);
+        assert_eq!(one.distance(&one), zero);
+        assert_eq!(zero.leading_zeros(), 160);
+        assert_eq!(one.leading_zeros(), 159);
+        assert_eq!(far.leading_zeros(), 3);
+
+        assert_eq!(zero.bucket_index(&zero), None);
+        assert_eq!(zero.bucket_index(&one), Some(0));
+        assert_eq!(one.bucket_index(&far), Some(156));
+        assert!(zero.distance(&one) < zero.distance(&far));
+    }
+}
@@ -0,0 +1,380 @@
+//! Finds the boundaries of Bencode values in a stream of bytes.
+//!
+//! An [Incremental] parser is fed chunks of data as they arrive (e.g. from a
+//! TCP stream) and reports when a complete value has been seen. The parser
+//! only keeps a small amount of state between calls, so the data does not
+//! need to be buffered or re-scanned from the start to find where a value
+//! ends.
+//!
+//! ```rust
+//! use bt_bencode::incremental::{Incremental, Status};
+//!
+//! let mut parser = Incremental::new();
+//!
+//! assert_eq!(parser.feed(b"d1:y1")?, Status::NeedMore);
+//! assert_eq!(parser.feed(b":qed1:")?, Status::Complete(3));
+//! # Ok::<(), bt_bencode::Error>(())
+//! ```
+//!
+//! The parser checks the structure of the data (e.g. that dictionary keys are
+//! byte strings and integers only contain digits) but does not check whether
+//! integers are in range or whether dictionary keys are sorted.
+//!
+//! The parser keeps one entry per open list or dictionary, so data from an
+//! untrusted peer should be fed to a parser with a
+//! [maximum depth][Incremental::with_max_depth].
+
+use crate::error::{Error, Result};
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{string::String, vec::Vec};
+#[cfg(feature = "std")]
+use std::{string::String, vec::Vec};
+
+/// The result of feeding data to an [Incremental] parser.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Status {
+    /// All of the data was consumed and the value is not complete yet.
+    NeedMore,
+    /// The value is complete after the given number of bytes from the last fed data.
+    ///
+    /// The remaining bytes were not consumed and are the start of the next value.
+    Complete(usize),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Container {
+    List,
+    DictKey,
+    DictValue,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum State {
+    Value,
+    IntSign,
+    IntFirstDigit,
+    IntDigits,
+    ByteStrLen(usize),
+    ByteStr(usize),
+}
+
+/// A push-style parser which finds the end of a Bencode value.
+///
+/// After a value is complete, the parser is reset and can be fed the data for
+/// the next value. If an error is returned, the parser should be
+/// [reset][Incremental::reset] before it is used again.
+#[derive(Clone, Debug)]
+pub struct Incremental {
+    stack: Vec<Container>,
+    state: State,
+    max_depth: Option<usize>,
+}
+
+impl Default for Incremental {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Incremental {
+    /// Constructs a new parser.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            stack: Vec::new(),
+            state: State::Value,
+            max_depth: None,
+        }
+    }
+
+    /// Sets the maximum number of nested lists and dictionaries.
+    ///
+    /// The depth is counted the same way as
+    /// [`DeserializerBuilder::max_depth()`][crate::DeserializerBuilder::max_depth],
+    /// so a maximum depth of 0 rejects any list or dictionary. An error is
+    /// returned as soon as a list or dictionary would exceed the depth.
+    ///
+    /// ```rust
+    /// use bt_bencode::incremental::{Incremental, Status};
+    ///
+    /// let mut parser = Incremental::new().with_max_depth(2);
+    /// assert_eq!(parser.feed(b"llee")?, Status::Complete(4));
+    /// assert!(parser.feed(b"lll").is_err());
+    /// # Ok::<(), bt_bencode::Error>(())
+    /// ```
+    #[must_use]
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Returns the maximum number of nested lists and dictionaries, if any.
+    #[must_use]
+    pub fn max_depth(&self) -> Option<usize> {
+        self.max_depth
+    }
+
+    /// Resets the parser to expect the start of a new value.
+    pub fn reset(&mut self) {
+        self.stack.clear();
+        self.state = State::Value;
+    }
+
+    /// Returns true if no data for the current value has been fed.
+    #[must_use]
+    pub fn is_idle(&self) -> bool {
+        self.stack.is_empty() && self.state == State::Value
+    }
+
+    /// Returns the number of lists and dictionaries which are currently open.
+    #[must_use]
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Consumes data until the current value is complete or the data is exhausted.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the data is not valid Bencode.
+    pub fn feed(&mut self, data: &[u8]) -> Result<Status> {
+        let mut idx = 0;
+        while idx < data.len() {
+            if let State::ByteStr(remaining) = self.state {
+                let n = core::cmp::min(remaining, data.len() - idx);
+                idx += n;
+                if n < remaining {
+                    self.state = State::ByteStr(remaining - n);
+                    continue;
+                }
+                if self.end_value() {
+                    return Ok(Status::Complete(idx));
+                }
+                continue;
+            }
+
+            let b = data[idx];
+            idx += 1;
+
+            let is_complete = match self.state {
+                State::Value => self.start_value(b)?,
+                State::IntSign => {
+                    self.state = match b {
+                        b'-' => State::IntFirstDigit,
+                        b'0'..=b'9' => State::IntDigits,
+                        _ => return Err(Error::InvalidInteger),
+                    };
+                    false
+                }
+                State::IntFirstDigit => {
+                    self.state = match b {
+                        b'0'..=b'9' => State::IntDigits,
+                        _ => return Err(Error::InvalidInteger),
+                    };
+                    false
+                }
+                State::IntDigits => match b {
+                    b'0'..=b'9' => false,
+                    b'e' => self.end_value(),
+                    _ => return Err(Error::InvalidInteger),
+                },
+                State::ByteStrLen(len) => match b {
+                    b'0'..=b'9' => {
+                        let len = len
+                            .checked_mul(10)
+                            .and_then(|len| len.checked_add(usize::from(b - b'0')))
+                            .ok_or(Error::InvalidByteStrLen)?;
+                        self.state = State::ByteStrLen(len);
+                        false
+                    }
+                    b':' if len == 0 => self.end_value(),
+                    b':' => {
+                        self.state = State::ByteStr(len);
+                        false
+                    }
+                    _ => return Err(Error::InvalidByteStrLen),
+                },
+                State::ByteStr(_) => unreachable!(),
+            };
+
+            if is_complete {
+                return Ok(Status::Complete(idx));
+            }
+        }
+
+        Ok(Status::NeedMore)
+    }
+
+    fn start_value(&mut self, b: u8) -> Result<bool> {
+        let is_key = self.stack.last() == Some(&Container::DictKey);
+        match b {
+            b'0'..=b'9' => {
+                self.state = State::ByteStrLen(usize::from(b - b'0'));
+                Ok(false)
+            }
+            b'e' => match self.stack.pop() {
+                Some(Container::List) | Some(Container::DictKey) => Ok(self.end_value()),
+                Some(Container::DictValue) | None => Err(Error::ExpectedSomeValue),
+            },
+            _ if is_key => Err(Error::KeyMustBeAByteStr),
+            b'i' => {
+                self.state = State::IntSign;
+                Ok(false)
+            }
+            b'l' => {
+                self.enter(Container::List)?;
+                Ok(false)
+            }
+            b'd' => {
+                self.enter(Container::DictKey)?;
+                Ok(false)
+            }
+            _ => Err(Error::ExpectedSomeValue),
+        }
+    }
+
+    fn enter(&mut self, container: Container) -> Result<()> {
+        if let Some(max_depth) = self.max_depth {
+            if self.stack.len() >= max_depth {
+                return Err(Error::Deserialize(String::from(
+                    "maximum nesting depth exceeded",
+                )));
+            }
+        }
+        self.stack.push(container);
+        Ok(())
+    }
+
+    /// Called when a value ends and returns true if the top-level value is complete.
+    fn end_value(&mut self) -> bool {
+        self.state = State::Value;
+        match self.stack.last_mut() {
+            None => true,
+            Some(Container::List) => false,
+            Some(top @ Container::DictKey) => {
+                *top = Container::DictValue;
+                false
+            }
+            Some(top @ Container::DictValue) => {
+                *top = Container::DictKey;
+                false
+            }
+        }
+    }
+}
+
+/// Returns the error for a value which is longer than the maximum length.
+#[cfg(any(feature = "tokio-util", feature = "futures"))]
+pub(crate) fn frame_too_long(max_frame_len: usize) -> Error {
+    Error::Deserialize(format!(
+        "value is longer than the maximum length of {} bytes",
+        max_frame_len
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(all(feature = "alloc", not(feature = "std")))]
+    use alloc::format;
+
+    fn feed_bytewise(input: &[u8]) -> Result<Option<usize>> {
+        let mut parser = Incremental::new();
+        for (idx, b) in input.iter().enumerate() {
+            match parser.feed(&[*b])? {
+                Status::NeedMore => {}
+                Status::Complete(n) => {
+                    assert_eq!(n, 1);
+                    assert!(parser.is_idle());
+                    return Ok(Some(idx + 1));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    #[test]
+    fn test_complete_values() -> Result<()> {
+        for input in &[
+            &b"i-12e"[..],
+            b"0:",
+            b"4:spam",
+            b"le",
+            b"de",
+            b"d4:spaml1:ai-12ee3:cow3:mooe",
+            b"lli1eed1:ad1:b0:eee",
+        ] {
+            assert_eq!(feed_bytewise(input)?, Some(input.len()));
+            assert_eq!(
+                Incremental::new().feed(input)?,
+                Status::Complete(input.len())
+            );
+            assert_eq!(feed_bytewise(&input[..input.len() - 1])?, None);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_multiple_values() -> Result<()> {
+        let mut parser = Incremental::new();
+        assert_eq!(parser.feed(b"i1ei2")?, Status::Complete(3));
+        assert_eq!(parser.feed(b"i2")?, Status::NeedMore);
+        assert_eq!(parser.depth(), 0);
+        assert_eq!(parser.feed(b"eli3e")?, Status::Complete(1));
+        assert_eq!(parser.feed(b"li3")?, Status::NeedMore);
+        assert_eq!(parser.depth(), 1);
+        assert_eq!(parser.feed(b"ee")?, Status::Complete(2));
+        Ok(())
+    }
+
+    #[test]
+    fn test_max_depth() -> Result<()> {
+        let mut parser = Incremental::new().with_max_depth(2);
+        assert_eq!(parser.feed(b"ld1:ai1eee")?, Status::Complete(10));
+        assert_eq!(parser.feed(b"lli1eei2ee")?, Status::Complete(10));
+
+        let err = parser.feed(&[b'l'; 100_000]).unwrap_err();
+        assert!(matches!(err, Error::Deserialize(_)));
+        assert_eq!(parser.depth(), 2);
+
+        parser.reset();
+        assert_eq!(parser.max_depth(), Some(2));
+        assert!(Incremental::new().with_max_depth(0).feed(b"de").is_err());
+        assert_eq!(
+            Incremental::new().with_max_depth(0).feed(b"i1e")?,
+            Status::Complete(3)
+        );
+
+        let input = b"lllleeee";
+        for max_depth in 0..6 {
+            let builder = crate::DeserializerBuilder::new().max_depth(max_depth);
+            let de_result = builder.from_slice::<crate::Value>(input).is_ok();
+            let parser_result = Incremental::new()
+                .with_max_depth(max_depth)
+                .feed(input)
+                .is_ok();
+            assert_eq!(de_result, parser_result, "{}", max_depth);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid() {
+        for (input, expected) in &[
+            (&b"ix"[..], "InvalidInteger"),
+            (b"i-e", "InvalidInteger"),
+            (b"i1-e", "InvalidInteger"),
+            (b"1x", "InvalidByteStrLen"),
+            (b"99999999999999999999999:", "InvalidByteStrLen"),
+            (b"di1ei2ee", "KeyMustBeAByteStr"),
+            (b"d1:ae", "ExpectedSomeValue"),
+            (b"e", "ExpectedSomeValue"),
+            (b"x", "ExpectedSomeValue"),
+        ] {
+            let err = Incremental::new().feed(input).unwrap_err();
+            assert!(format!("{:?}", err).starts_with(expected), "{:?}", input);
+        }
+    }
+}
@@ -0,0 +1,460 @@
+//! Parses Bencode data into values with deduplicated byte strings.
+//!
+//! Large responses such as tracker scrapes or DHT routing table dumps repeat
+//! the same short byte strings many times (e.g. dictionary keys like `id`,
+//! `ip`, and `port`). The [Value] type in this module stores byte strings as
+//! shared [`Arc<[u8]>`][Arc] values and an [Interner] hands out the same
+//! allocation for equal byte strings.
+//!
+//! ```rust
+//! use bt_bencode::intern::{self, Interner};
+//!
+//! let mut interner = Interner::new();
+//!
+//! let a = intern::from_slice_in(b"d2:ip9:127.0.0.14:porti6881ee", &mut interner)?;
+//! let b = intern::from_slice_in(b"d2:ip9:127.0.0.24:porti6882ee", &mut interner)?;
+//!
+//! assert_eq!(a.get(b"ip").and_then(|ip| ip.as_str()), Some("127.0.0.1"));
+//! assert_eq!(b.get(b"ip").and_then(|ip| ip.as_str()), Some("127.0.0.2"));
+//!
+//! // The "ip" and "port" keys are shared between both values.
+//! assert_eq!(interner.len(), 4);
+//! # Ok::<(), bt_bencode::Error>(())
+//! ```
+
+use crate::error::Error;
+use crate::read;
+use crate::value::Number;
+use core::fmt;
+use serde::{
+    de::{self, DeserializeSeed, MapAccess, SeqAccess, Visitor},
+    ser::{Serialize, SerializeMap, SerializeSeq},
+};
+use serde_bytes::Bytes;
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+    vec::Vec,
+};
+#[cfg(feature = "std")]
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io,
+    sync::Arc,
+    vec::Vec,
+};
+
+/// The default maximum length of a byte string which is interned.
+///
+/// Longer byte strings (e.g. the `pieces` of a metainfo) are rarely repeated.
+pub const DEFAULT_MAX_LEN: usize = 64;
+
+/// A set of shared byte strings.
+///
+/// Byte strings longer than the maximum length are not stored in the set but
+/// are still returned as [`Arc<[u8]>`][Arc] values.
+#[derive(Clone, Debug)]
+pub struct Interner {
+    set: BTreeSet<Arc<[u8]>>,
+    max_len: usize,
+}
+
+impl Default for Interner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Interner {
+    /// Constructs an empty interner which interns byte strings up to [`DEFAULT_MAX_LEN`] bytes.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_max_len(DEFAULT_MAX_LEN)
+    }
+
+    /// Constructs an empty interner which interns byte strings up to `max_len` bytes.
+    #[must_use]
+    pub fn with_max_len(max_len: usize) -> Self {
+        Self {
+            set: BTreeSet::new(),
+            max_len,
+        }
+    }
+
+    /// Returns a shared copy of the byte string.
+    ///
+    /// If an equal byte string was previously interned, the existing
+    /// allocation is returned.
+    pub fn intern(&mut self, bytes: &[u8]) -> Arc<[u8]> {
+        if bytes.len() > self.max_len {
+            return Arc::from(bytes);
+        }
+
+        if let Some(existing) = self.set.get(bytes) {
+            return Arc::clone(existing);
+        }
+
+        let value: Arc<[u8]> = Arc::from(bytes);
+        self.set.insert(Arc::clone(&value));
+        value
+    }
+
+    /// Returns the number of interned byte strings.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.set.len()
+    }
+
+    /// Returns true if no byte strings have been interned.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.set.is_empty()
+    }
+
+    /// Removes all interned byte strings.
+    ///
+    /// Values which were already returned keep their allocations.
+    pub fn clear(&mut self) {
+        self.set.clear();
+    }
+}
+
+/// Represents a valid Bencode value with shared byte strings.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Value {
+    /// A byte string.
+    ByteStr(Arc<[u8]>),
+    /// An integer which can be signed or unsigned.
+    Int(Number),
+    /// A list of values.
+    List(Vec<Value>),
+    /// A dictionary of values.
+    Dict(BTreeMap<Arc<[u8]>, Value>),
+}
+
+impl Value {
+    /// Deserializes a value from any deserializer and interns the byte strings.
+    ///
+    /// # Errors
+    ///
+    /// Deserialization can fail if the data is not valid.
+    pub fn deserialize_in<'de, D>(
+        deserializer: D,
+        interner: &mut Interner,
+    ) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        ValueSeed { interner }.deserialize(deserializer)
+    }
+
+    /// If the value is a byte string, returns the underlying value.
+    #[must_use]
+    pub fn as_byte_str(&self) -> Option<&[u8]> {
+        match self {
+            Value::ByteStr(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    /// If the value is a UTF-8 string, returns the underlying value.
+    #[must_use]
+    pub fn as_str(&self) -> Option<&str> {
+        self.as_byte_str()
+            .and_then(|b| core::str::from_utf8(b).ok())
+    }
+
+    /// If the value is a number, returns the underlying value.
+    #[must_use]
+    pub fn as_number(&self) -> Option<Number> {
+        match self {
+            Value::Int(n) => Some(Number::clone(n)),
+            _ => None,
+        }
+    }
+
+    /// If the value is a list, returns the underlying value.
+    #[must_use]
+    pub fn as_list(&self) -> Option<&[Value]> {
+        match self {
+            Value::List(l) => Some(l),
+            _ => None,
+        }
+    }
+
+    /// If the value is a dictionary, returns the underlying value.
+    #[must_use]
+    pub fn as_dict(&self) -> Option<&BTreeMap<Arc<[u8]>, Value>> {
+        match self {
+            Value::Dict(d) => Some(d),
+            _ => None,
+        }
+    }
+
+    /// If the value is a dictionary, returns the value for the key.
+    #[must_use]
+    pub fn get(&self, key: &[u8]) -> Option<&Value> {
+        self.as_dict().and_then(|d| d.get(key))
+    }
+
+    /// Copies the value into a [`crate::Value`].
+    #[must_use]
+    pub fn to_value(&self) -> crate::Value {
+        use crate::{key::ByteStrKey, value::SmallBytes};
+
+        match self {
+            Value::ByteStr(b) => crate::Value::ByteStr(SmallBytes::from(&b[..])),
+            Value::Int(n) => crate::Value::Int(Number::clone(n)),
+            Value::List(l) => crate::Value::List(l.iter().map(Value::to_value).collect()),
+            Value::Dict(d) => crate::Value::Dict(
+                d.iter()
+                    .map(|(k, v)| (ByteStrKey::from(&k[..]), v.to_value()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl Serialize for Value {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            Value::ByteStr(b) => serializer.serialize_bytes(b),
+            Value::Int(i) => i.serialize(serializer),
+            Value::List(l) => {
+                let mut seq = serializer.serialize_seq(Some(l.len()))?;
+                for v in l {
+                    seq.serialize_element(v)?;
+                }
+                seq.end()
+            }
+            Value::Dict(d) => {
+                let mut map = serializer.serialize_map(Some(d.len()))?;
+                for (k, v) in d {
+                    map.serialize_entry(Bytes::new(k), v)?;
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+/// Deserializes a [Value] from a slice of bytes and interns the byte strings.
+///
+/// # Errors
+///
+/// Deserialization can fail if the data is not valid.
+pub fn from_slice_in(s: &[u8], interner: &mut Interner) -> Result<Value, Error> {
+    let mut de = crate::Deserializer::new(read::SliceRead::new(s));
+    let value = Value::deserialize_in(&mut de, interner)?;
+    de.end()?;
+    Ok(value)
+}
+
+/// Deserializes a [Value] from an [`io::Read`] type and interns the byte strings.
+///
+/// # Errors
+///
+/// Deserialization can fail if the data is not valid and other IO errors.
+#[cfg(feature = "std")]
+pub fn from_reader_in<R>(r: R, interner: &mut Interner) -> Result<Value, Error>
+where
+    R: io::Read,
+{
+    let mut de = crate::Deserializer::new(read::IoRead::new(r));
+    let value = Value::deserialize_in(&mut de, interner)?;
+    de.end()?;
+    Ok(value)
+}
+
+struct ValueSeed<'a> {
+    interner: &'a mut Interner,
+}
+
+impl<'de, 'a> DeserializeSeed<'de> for ValueSeed<'a> {
+    type Value = Value;
+
+    #[inline]
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de, 'a> Visitor<'de> for ValueSeed<'a> {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("any valid Bencode value")
+    }
+
+    #[inline]
+    fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E> {
+        Ok(Value::Int(Number::Signed(value)))
+    }
+
+    #[inline]
+    fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E> {
+        Ok(Value::Int(Number::Unsigned(value)))
+    }
+
+    #[inline]
+    fn visit_i128<E>(self, value: i128) -> Result<Self::Value, E> {
+        Ok(Value::Int(Number::from(value)))
+    }
+
+    #[inline]
+    fn visit_u128<E>(self, value: u128) -> Result<Self::Value, E> {
+        Ok(Value::Int(Number::from(value)))
+    }
+
+    #[cfg(feature = "big-int")]
+    #[inline]
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        crate::value::deserialize_big_int(deserializer).map(Value::Int)
+    }
+
+    #[inline]
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E> {
+        Ok(Value::ByteStr(self.interner.intern(value.as_bytes())))
+    }
+
+    #[inline]
+    fn visit_bytes<E>(self, value: &[u8]) -> Result<Self::Value, E> {
+        Ok(Value::ByteStr(self.interner.intern(value)))
+    }
+
+    fn visit_seq<V>(self, mut visitor: V) -> Result<Self::Value, V::Error>
+    where
+        V: SeqAccess<'de>,
+    {
+        let mut list = Vec::with_capacity(visitor.size_hint().unwrap_or(0));
+        while let Some(elem) = visitor.next_element_seed(ValueSeed {
+            interner: &mut *self.interner,
+        })? {
+            list.push(elem);
+        }
+        Ok(Value::List(list))
+    }
+
+    fn visit_map<V>(self, mut visitor: V) -> Result<Self::Value, V::Error>
+    where
+        V: MapAccess<'de>,
+    {
+        let mut dict = BTreeMap::new();
+        while let Some(key) = visitor.next_key_seed(KeySeed {
+            interner: &mut *self.interner,
+        })? {
+            let value = visitor.next_value_seed(ValueSeed {
+                interner: &mut *self.interner,
+            })?;
+            dict.insert(key, value);
+        }
+        Ok(Value::Dict(dict))
+    }
+}
+
+struct KeySeed<'a> {
+    interner: &'a mut Interner,
+}
+
+impl<'de, 'a> DeserializeSeed<'de> for KeySeed<'a> {
+    type Value = Arc<[u8]>;
+
+    #[inline]
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_bytes(self)
+    }
+}
+
+impl<'de, 'a> Visitor<'de> for KeySeed<'a> {
+    type Value = Arc<[u8]>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a byte string key")
+    }
+
+    #[inline]
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E> {
+        Ok(self.interner.intern(value.as_bytes()))
+    }
+
+    #[inline]
+    fn visit_bytes<E>(self, value: &[u8]) -> Result<Self::Value, E> {
+        Ok(self.interner.intern(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Result;
+
+    #[test]
+    fn test_shared_keys_and_values() -> Result<()> {
+        let mut interner = Interner::new();
+        let value = from_slice_in(b"ld2:ip1:ae1:ad2:ip1:aee", &mut interner)?;
+        assert_eq!(interner.len(), 2);
+
+        let list = value.as_list().unwrap();
+        let (first_key, first_value) = list[0].as_dict().unwrap().iter().next().unwrap();
+        let (second_key, second_value) = list[2].as_dict().unwrap().iter().next().unwrap();
+        assert!(Arc::ptr_eq(first_key, second_key));
+        match (first_value, second_value, &list[1]) {
+            (Value::ByteStr(a), Value::ByteStr(b), Value::ByteStr(c)) => {
+                assert!(Arc::ptr_eq(a, b));
+                assert!(Arc::ptr_eq(a, c));
+            }
+            _ => panic!("expected byte strings"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_max_len() -> Result<()> {
+        let mut interner = Interner::with_max_len(2);
+        let value = from_slice_in(b"l2:ab3:abc3:abce", &mut interner)?;
+        assert_eq!(interner.len(), 1);
+
+        let list = value.as_list().unwrap();
+        match (&list[1], &list[2]) {
+            (Value::ByteStr(a), Value::ByteStr(b)) => {
+                assert_eq!(a, b);
+                assert!(!Arc::ptr_eq(a, b));
+            }
+            _ => panic!("expected byte strings"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_round_trip() -> Result<()> {
+        let mut interner = Interner::new();
+        let input = b"d4:spaml1:ai-1ee1:zi2ee";
+        let value = from_slice_in(input, &mut interner)?;
+        let output: Vec<u8> = crate::to_vec(&value)?;
+        assert_eq!(&output[..], &input[..]);
+
+        assert_eq!(value.to_value(), crate::from_slice::<crate::Value>(input)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let mut interner = Interner::new();
+        assert!(from_slice_in(b"d1:a", &mut interner).is_err());
+        assert!(from_slice_in(b"i1ei2e", &mut interner).is_err());
+    }
+}
@@ -0,0 +1,317 @@
+//! Dictionary keys which are not valid UTF-8.
+//!
+//! Dictionary keys are byte strings, and some real data has keys which are not
+//! valid UTF-8. For instance, the `files` dictionary in a scrape response is
+//! keyed by raw 20 byte info hashes.
+//!
+//! A struct field is matched against the UTF-8 bytes of its name (or its
+//! `#[serde(rename = "...")]` value). Because Rust strings are always valid
+//! UTF-8, a struct field cannot match a key such as `b"\xff"`. Use a map with
+//! [`ByteStrKey`] keys for such dictionaries instead:
+//!
+//! ```rust
+//! use bt_bencode::key::ByteStrKey;
+//! use std::collections::BTreeMap;
+//!
+//! let files: BTreeMap<ByteStrKey, u64> = bt_bencode::from_slice(b"d2:\xff\xfei1e4:spami2ee")?;
+//!
+//! assert_eq!(files.get(&b"\xff\xfe"[..]), Some(&1));
+//! assert_eq!(files.get(&b"spam"[..]), Some(&2));
+//! assert_eq!(
+//!     files.keys().map(ToString::to_string).collect::<Vec<_>>(),
+//!     vec!["spam", "\\xff\\xfe"]
+//! );
+//! assert_eq!(bt_bencode::to_vec(&files)?, b"d4:spami2e2:\xff\xfei1ee");
+//! # Ok::<(), bt_bencode::Error>(())
+//! ```
+
+use core::{borrow::Borrow, fmt, ops::Deref, str};
+use serde::{
+    de::{Deserialize, Deserializer, SeqAccess, Visitor},
+    ser::{Serialize, Serializer},
+};
+use serde_bytes::ByteBuf;
+
+use crate::value::SmallBytes;
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{borrow::Cow, string::String, vec::Vec};
+#[cfg(feature = "std")]
+use std::{borrow::Cow, string::String, vec::Vec};
+
+/// A dictionary key which may contain any bytes.
+///
+/// Keys are ordered by their raw bytes like the keys in an encoded
+/// dictionary. The type implements [`Borrow<[u8]>`][Borrow] so maps can be
+/// queried with byte slices.
+///
+/// The bytes are stored in a [`SmallBytes`], so short keys such as 20 byte
+/// info hashes do not allocate.
+///
+/// Keys are displayed as UTF-8 with bytes which are not valid UTF-8
+/// escaped as `\xNN`. Keys which are binary data such as info hashes can be
+/// formatted as hex with `{:x}` or `{:X}`.
+///
+/// ```rust
+/// use bt_bencode::key::ByteStrKey;
+///
+/// let key = ByteStrKey::from(&b"a\xff"[..]);
+/// assert_eq!(key.to_string(), "a\\xff");
+/// assert_eq!(key.to_string_lossy(), "a\u{fffd}");
+/// assert_eq!(format!("{:x}", key), "61ff");
+/// assert_eq!(format!("{:X}", key), "61FF");
+/// ```
+#[derive(Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ByteStrKey(pub SmallBytes);
+
+impl ByteStrKey {
+    /// Returns the key as a slice.
+    #[must_use]
+    pub fn as_slice(&self) -> &[u8] {
+        self.0.as_slice()
+    }
+
+    /// Returns the key as a string if it is valid UTF-8.
+    #[must_use]
+    pub fn as_str(&self) -> Option<&str> {
+        str::from_utf8(self.as_slice()).ok()
+    }
+
+    /// Returns the key's bytes.
+    #[must_use]
+    pub fn into_vec(self) -> Vec<u8> {
+        self.0.into_vec()
+    }
+
+    /// Returns the key as a string with invalid UTF-8 sequences replaced
+    /// with `U+FFFD`.
+    #[must_use]
+    pub fn to_string_lossy(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(self.as_slice())
+    }
+}
+
+/// Writes bytes as UTF-8 with invalid bytes escaped as `\xNN`.
+fn write_escaped(f: &mut fmt::Formatter<'_>, mut bytes: &[u8], debug: bool) -> fmt::Result {
+    loop {
+        let (valid, invalid) = match str::from_utf8(bytes) {
+            Ok(s) => (s, 0),
+            Err(e) => {
+                let (valid, rest) = bytes.split_at(e.valid_up_to());
+                let valid = str::from_utf8(valid).expect("bytes are valid UTF-8");
+                (valid, e.error_len().unwrap_or(rest.len()))
+            }
+        };
+        if debug {
+            for c in valid.chars() {
+                write!(f, "{}", c.escape_debug())?;
+            }
+        } else {
+            f.write_str(valid)?;
+        }
+        if invalid == 0 {
+            return Ok(());
+        }
+        let rest = &bytes[valid.len()..];
+        for b in &rest[..invalid] {
+            write!(f, "\\x{:02x}", b)?;
+        }
+        bytes = &rest[invalid..];
+    }
+}
+
+impl fmt::Display for ByteStrKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_escaped(f, self.as_slice(), false)
+    }
+}
+
+impl fmt::LowerHex for ByteStrKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for b in self.as_slice() {
+            write!(f, "{:02x}", b)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::UpperHex for ByteStrKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for b in self.as_slice() {
+            write!(f, "{:02X}", b)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for ByteStrKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ByteStrKey(\"")?;
+        write_escaped(f, self.as_slice(), true)?;
+        f.write_str("\")")
+    }
+}
+
+impl Deref for ByteStrKey {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl AsRef<[u8]> for ByteStrKey {
+    fn as_ref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl Borrow<[u8]> for ByteStrKey {
+    fn borrow(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl<'a> From<&'a [u8]> for ByteStrKey {
+    fn from(other: &'a [u8]) -> Self {
+        ByteStrKey(SmallBytes::from(other))
+    }
+}
+
+impl<'a> From<&'a str> for ByteStrKey {
+    fn from(other: &'a str) -> Self {
+        ByteStrKey(SmallBytes::from(other))
+    }
+}
+
+impl From<Vec<u8>> for ByteStrKey {
+    fn from(other: Vec<u8>) -> Self {
+        ByteStrKey(SmallBytes::from(other))
+    }
+}
+
+impl From<String> for ByteStrKey {
+    fn from(other: String) -> Self {
+        ByteStrKey(SmallBytes::from(other))
+    }
+}
+
+impl From<ByteBuf> for ByteStrKey {
+    fn from(other: ByteBuf) -> Self {
+        ByteStrKey(SmallBytes::from(other))
+    }
+}
+
+impl From<ByteStrKey> for Vec<u8> {
+    fn from(other: ByteStrKey) -> Self {
+        other.into_vec()
+    }
+}
+
+impl Serialize for ByteStrKey {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_bytes(self.as_slice())
+    }
+}
+
+impl<'de> Deserialize<'de> for ByteStrKey {
+    fn deserialize<D>(deserializer: D) -> Result<ByteStrKey, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct ByteStrKeyVisitor;
+
+        impl<'de> Visitor<'de> for ByteStrKeyVisitor {
+            type Value = ByteStrKey;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a byte string")
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<ByteStrKey, E> {
+                Ok(ByteStrKey::from(value))
+            }
+
+            fn visit_string<E>(self, value: String) -> Result<ByteStrKey, E> {
+                Ok(ByteStrKey::from(value))
+            }
+
+            fn visit_bytes<E>(self, value: &[u8]) -> Result<ByteStrKey, E> {
+                Ok(ByteStrKey::from(value))
+            }
+
+            fn visit_byte_buf<E>(self, value: Vec<u8>) -> Result<ByteStrKey, E> {
+                Ok(ByteStrKey::from(value))
+            }
+
+            fn visit_seq<V>(self, mut seq: V) -> Result<ByteStrKey, V::Error>
+            where
+                V: SeqAccess<'de>,
+            {
+                let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+                while let Some(b) = seq.next_element()? {
+                    bytes.push(b);
+                }
+                Ok(ByteStrKey::from(bytes))
+            }
+        }
+
+        deserializer.deserialize_byte_buf(ByteStrKeyVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Result;
+
+    #[cfg(all(feature = "alloc", not(feature = "std")))]
+    use alloc::{collections::BTreeMap, format};
+    #[cfg(feature = "std")]
+    use std::{collections::BTreeMap, format};
+
+    #[test]
+    fn test_display() {
+        assert_eq!(format!("{}", ByteStrKey::from("spam")), "spam");
+        assert_eq!(
+            format!("{}", ByteStrKey::from(&b"a\xffb\xe2\x82"[..])),
+            "a\\xffb\\xe2\\x82"
+        );
+        assert_eq!(
+            format!("{:?}", ByteStrKey::from(&b"\"\xc3\xa9\x00"[..])),
+            "ByteStrKey(\"\\\"\u{e9}\\0\")"
+        );
+        assert_eq!(format!("{:x}", ByteStrKey::from(&b"\x00\xab"[..])), "00ab");
+        assert_eq!(format!("{:X}", ByteStrKey::from(&b"\x00\xab"[..])), "00AB");
+        assert_eq!(
+            ByteStrKey::from(&b"a\xffb"[..]).to_string_lossy(),
+            "a\u{fffd}b"
+        );
+    }
+
+    #[test]
+    fn test_map() -> Result<()> {
+        let input = b"d2:\x00\x01i1e1:ai2e1:\xffi3ee";
+        let map: BTreeMap<ByteStrKey, i64> = crate::from_slice(input)?;
+        assert_eq!(map.get(&b"\xff"[..]), Some(&3));
+        assert_eq!(map.get(&b"a"[..]), Some(&2));
+        assert_eq!(
+            map.keys().next().map(ByteStrKey::as_slice),
+            Some(&[0, 1][..])
+        );
+        assert_eq!(map.keys().nth(1).and_then(ByteStrKey::as_str), Some("a"));
+        assert_eq!(crate::to_vec(&map)?, &input[..]);
+
+        let value: crate::Value = crate::from_slice(input)?;
+        let map: BTreeMap<ByteStrKey, i64> = crate::from_value(value)?;
+        assert_eq!(map.len(), 3);
+
+        let map: BTreeMap<ByteStrKey, i64> = crate::from_slice(b"d20:aaaaaaaaaaaaaaaaaaaai1ee")?;
+        assert!(map.keys().all(|key| key.0.is_inline()));
+        assert_eq!(map.get(&[b'a'; 20][..]), Some(&1));
+        Ok(())
+    }
+}
@@ -0,0 +1,1038 @@
+//! Types for KRPC messages used by the mainline DHT.
+//!
+//! [BEP 5][bep_0005] defines the KRPC protocol. Every message is a dictionary
+//! with a transaction ID (`t`), a message type (`y`), and either query
+//! arguments, response values, or an error.
+//!
+//! ```rust
+//! use bt_bencode::krpc::{Message, MessageKind, NodeId, Query};
+//!
+//! let input = b"d1:ad2:id20:abcdefghij0123456789e1:q4:ping1:t2:aa1:y1:qe";
+//! let msg = Message::from_slice(input)?;
+//!
+//! assert_eq!(msg.transaction_id, b"aa");
+//! assert_eq!(
+//!     msg.kind,
+//!     MessageKind::Query(Query::Ping {
+//!         id: NodeId::new(*b"abcdefghij0123456789")
+//!     })
+//! );
+//! assert_eq!(msg.to_vec()?, &input[..]);
+//! # Ok::<(), bt_bencode::Error>(())
+//! ```
+//!
+//! [bep_0005]: https://www.bittorrent.org/beps/bep_0005.html
+
+use crate::{
+    compact,
+    error::{Error, Result},
+    id::Id20,
+    serde_helpers::bool_from_int,
+    value::Value,
+};
+use core::convert::TryFrom;
+use serde::de;
+use serde_bytes::ByteBuf;
+use serde_derive::{Deserialize, Serialize};
+use std::{boxed::Box, net::SocketAddr, string::String, vec::Vec};
+
+mod bloom;
+#[cfg(feature = "tokio-util")]
+mod codec;
+mod error;
+mod item;
+mod nodes;
+mod snapshot;
+mod transaction;
+
+pub use bloom::{BloomFilter, BLOOM_FILTER_LEN};
+#[cfg(feature = "tokio-util")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio-util")))]
+pub use codec::KrpcCodec;
+pub use error::{ErrorCode, ErrorMessage};
+pub use snapshot::{RoutingTableSnapshot, SnapshotBucket, SnapshotNode, SNAPSHOT_VERSION};
+pub use transaction::Transactions;
+
+#[cfg(feature = "sha1")]
+pub use item::{immutable_target, mutable_target};
+pub use item::{signable_bytes, MutableItem};
+
+pub use nodes::{
+    decode_nodes, decode_nodes6, encode_nodes, encode_nodes6, COMPACT_NODE6_LEN, COMPACT_NODE_LEN,
+};
+
+/// A 20 byte node ID.
+pub type NodeId = Id20;
+
+/// The address families of nodes requested with the `want` argument.
+///
+/// [BEP 32][bep_0032] allows a query to ask for IPv4 (`n4`) and IPv6 (`n6`)
+/// nodes regardless of the address family the query was sent over.
+///
+/// [bep_0032]: https://www.bittorrent.org/beps/bep_0032.html
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Want {
+    /// Requests IPv4 nodes in the `nodes` field.
+    pub n4: bool,
+    /// Requests IPv6 nodes in the `nodes6` field.
+    pub n6: bool,
+}
+
+impl Want {
+    /// Requests both IPv4 and IPv6 nodes.
+    #[must_use]
+    pub fn both() -> Self {
+        Want { n4: true, n6: true }
+    }
+}
+
+impl serde::Serialize for Want {
+    fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut want = Vec::new();
+        if self.n4 {
+            want.push(serde_bytes::Bytes::new(b"n4"));
+        }
+        if self.n6 {
+            want.push(serde_bytes::Bytes::new(b"n6"));
+        }
+        want.serialize(serializer)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Want {
+    fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let mut want = Want::default();
+        for family in Vec::<ByteBuf>::deserialize(deserializer)? {
+            match family.as_slice() {
+                b"n4" => want.n4 = true,
+                b"n6" => want.n6 = true,
+                _ => {}
+            }
+        }
+        Ok(want)
+    }
+}
+
+/// A query.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Query {
+    /// A `ping` query.
+    Ping {
+        /// The querying node's ID.
+        id: NodeId,
+    },
+    /// A `find_node` query.
+    FindNode {
+        /// The querying node's ID.
+        id: NodeId,
+        /// The ID of the node being searched for.
+        target: NodeId,
+        /// The address families of the nodes to return.
+        want: Option<Want>,
+    },
+    /// A `get_peers` query.
+    GetPeers {
+        /// The querying node's ID.
+        id: NodeId,
+        /// The info hash of the torrent.
+        info_hash: NodeId,
+        /// The address families of the nodes to return.
+        want: Option<Want>,
+        /// Requests the [BEP 33][bep_0033] bloom filters instead of peers.
+        ///
+        /// [bep_0033]: https://www.bittorrent.org/beps/bep_0033.html
+        scrape: bool,
+        /// Requests that seeds are not returned.
+        noseed: bool,
+    },
+    /// An `announce_peer` query.
+    AnnouncePeer {
+        /// The querying node's ID.
+        id: NodeId,
+        /// If true, the port should be ignored and the source port of the packet used instead.
+        implied_port: bool,
+        /// The info hash of the torrent.
+        info_hash: NodeId,
+        /// The port the peer is listening on.
+        port: u16,
+        /// The token from a previous `get_peers` response.
+        token: ByteBuf,
+    },
+    /// A `get` query for a [BEP 44][bep_0044] item.
+    ///
+    /// [bep_0044]: https://www.bittorrent.org/beps/bep_0044.html
+    Get {
+        /// The querying node's ID.
+        id: NodeId,
+        /// The target of the item.
+        target: NodeId,
+        /// If set, a mutable item is only returned if its sequence number is greater.
+        seq: Option<i64>,
+    },
+    /// A `put` query for a [BEP 44][bep_0044] item.
+    ///
+    /// [bep_0044]: https://www.bittorrent.org/beps/bep_0044.html
+    Put {
+        /// The querying node's ID.
+        id: NodeId,
+        /// The token from a previous `get` response.
+        token: ByteBuf,
+        /// The item's value.
+        v: Value,
+        /// The key, salt, sequence number, and signature if the item is mutable.
+        mutable: Option<MutableItem>,
+        /// If set, the item is only stored if the current sequence number is equal.
+        cas: Option<i64>,
+    },
+    /// A `sample_infohashes` query from [BEP 51][bep_0051].
+    ///
+    /// [bep_0051]: https://www.bittorrent.org/beps/bep_0051.html
+    SampleInfohashes {
+        /// The querying node's ID.
+        id: NodeId,
+        /// The ID used to find the closest nodes.
+        target: NodeId,
+    },
+    /// A query with a method which is not known.
+    Unknown {
+        /// The method name.
+        method: ByteBuf,
+        /// The querying node's ID.
+        id: NodeId,
+    },
+}
+
+impl Query {
+    /// Returns the method name.
+    #[must_use]
+    pub fn method(&self) -> &[u8] {
+        match self {
+            Query::Ping { .. } => b"ping",
+            Query::FindNode { .. } => b"find_node",
+            Query::GetPeers { .. } => b"get_peers",
+            Query::AnnouncePeer { .. } => b"announce_peer",
+            Query::Get { .. } => b"get",
+            Query::Put { .. } => b"put",
+            Query::SampleInfohashes { .. } => b"sample_infohashes",
+            Query::Unknown { method, .. } => method,
+        }
+    }
+
+    /// Returns the querying node's ID.
+    #[must_use]
+    pub fn id(&self) -> &NodeId {
+        match self {
+            Query::Ping { id }
+            | Query::FindNode { id, .. }
+            | Query::GetPeers { id, .. }
+            | Query::AnnouncePeer { id, .. }
+            | Query::Get { id, .. }
+            | Query::Put { id, .. }
+            | Query::SampleInfohashes { id, .. }
+            | Query::Unknown { id, .. } => id,
+        }
+    }
+}
+
+/// The values in a response.
+///
+/// The values which are present depend on the query the response is for.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Response {
+    /// The responding node's ID.
+    pub id: NodeId,
+    /// The number of seconds before the responding node should be queried again for samples.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interval: Option<i64>,
+    /// The bloom filter of peers which are downloading the torrent (`BFpe`).
+    #[serde(rename = "BFpe", default, skip_serializing_if = "Option::is_none")]
+    pub peers_filter: Option<Box<BloomFilter>>,
+    /// The bloom filter of peers which are seeding the torrent (`BFsd`).
+    #[serde(rename = "BFsd", default, skip_serializing_if = "Option::is_none")]
+    pub seeds_filter: Option<Box<BloomFilter>>,
+    /// The public key of a mutable item.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub k: Option<ByteBuf>,
+    /// The compact IPv4 node info of the closest nodes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nodes: Option<ByteBuf>,
+    /// The compact IPv6 node info of the closest nodes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nodes6: Option<ByteBuf>,
+    /// The number of info hashes the responding node has stored.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub num: Option<i64>,
+    /// The concatenated 20 byte info hashes sampled from the responding node's storage.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub samples: Option<ByteBuf>,
+    /// The sequence number of a mutable item.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seq: Option<i64>,
+    /// The signature of a mutable item.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sig: Option<ByteBuf>,
+    /// The token for a future `announce_peer` or `put` query.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<ByteBuf>,
+    /// The value of an item.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub v: Option<Value>,
+    /// The compact peer info of peers for the torrent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub values: Option<Vec<ByteBuf>>,
+}
+
+impl Response {
+    /// Constructs a response with only the responding node's ID.
+    #[must_use]
+    pub fn new(id: NodeId) -> Self {
+        Response {
+            id,
+            interval: None,
+            peers_filter: None,
+            seeds_filter: None,
+            k: None,
+            nodes: None,
+            nodes6: None,
+            num: None,
+            samples: None,
+            seq: None,
+            sig: None,
+            token: None,
+            v: None,
+            values: None,
+        }
+    }
+
+    /// Decodes the nodes from both the `nodes` and `nodes6` fields.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if either field is not valid compact node info.
+    pub fn decode_nodes(&self) -> Result<Vec<(NodeId, SocketAddr)>> {
+        let mut nodes = decode_nodes(self.nodes.as_ref().map_or(&[][..], |n| n.as_slice()))?;
+        nodes.extend(decode_nodes6(
+            self.nodes6.as_ref().map_or(&[][..], |n| n.as_slice()),
+        )?);
+        Ok(nodes)
+    }
+
+    /// Decodes the compact peers in the `values` field.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if any value is not a 6 or 18 byte string.
+    pub fn decode_peers(&self) -> Result<Vec<SocketAddr>> {
+        self.values
+            .as_ref()
+            .map_or(&[][..], |v| v.as_slice())
+            .iter()
+            .map(|peer| compact::decode_peer(peer))
+            .collect()
+    }
+
+    /// Sets the `values` field from peers with either address family.
+    pub fn set_peers(&mut self, peers: &[SocketAddr]) {
+        self.values = Some(
+            peers
+                .iter()
+                .map(|peer| ByteBuf::from(compact::encode_peer(peer)))
+                .collect(),
+        );
+    }
+
+    /// Splits the `samples` field into info hashes.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the length is not a multiple of 20.
+    pub fn decode_samples(&self) -> Result<Vec<Id20>> {
+        let samples = self.samples.as_ref().map_or(&[][..], |s| s.as_slice());
+        if samples.len() % Id20::LEN != 0 {
+            return Err(Error::Deserialize(String::from(
+                "samples length is not a multiple of 20",
+            )));
+        }
+        samples
+            .chunks_exact(Id20::LEN)
+            .map(Id20::try_from)
+            .collect()
+    }
+
+    /// Sets the `samples` field from info hashes.
+    pub fn set_samples(&mut self, samples: &[Id20]) {
+        let mut out = Vec::with_capacity(samples.len() * Id20::LEN);
+        for sample in samples {
+            out.extend_from_slice(sample.as_bytes());
+        }
+        self.samples = Some(ByteBuf::from(out));
+    }
+
+    /// Sets the `nodes` and `nodes6` fields from nodes with either address family.
+    ///
+    /// A field is cleared if there are no nodes with its address family.
+    pub fn set_nodes(&mut self, nodes: &[(NodeId, SocketAddr)]) {
+        let (v4, v6): (Vec<_>, Vec<_>) = nodes.iter().partition(|(_, addr)| addr.is_ipv4());
+        self.nodes = if v4.is_empty() {
+            None
+        } else {
+            Some(ByteBuf::from(encode_nodes(&v4).expect("nodes are IPv4")))
+        };
+        self.nodes6 = if v6.is_empty() {
+            None
+        } else {
+            Some(ByteBuf::from(encode_nodes6(&v6).expect("nodes are IPv6")))
+        };
+    }
+}
+
+/// The type specific part of a message.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MessageKind {
+    /// A query (`y` is `q`).
+    Query(Query),
+    /// A response (`y` is `r`).
+    Response(Response),
+    /// An error (`y` is `e`).
+    Error(ErrorMessage),
+}
+
+/// A KRPC message.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "RawMessage", into = "RawMessage")]
+pub struct Message {
+    /// The transaction ID (`t`) which is echoed in the response.
+    pub transaction_id: ByteBuf,
+    /// The client version (`v`).
+    pub version: Option<ByteBuf>,
+    /// Set if the sending node is read-only (`ro`) and should not be queried.
+    pub read_only: bool,
+    /// The query, response, or error.
+    pub kind: MessageKind,
+}
+
+impl Message {
+    /// Constructs a message with the transaction ID and kind.
+    #[must_use]
+    pub fn new(transaction_id: ByteBuf, kind: MessageKind) -> Self {
+        Message {
+            transaction_id,
+            version: None,
+            read_only: false,
+            kind,
+        }
+    }
+
+    /// Constructs a response message.
+    #[must_use]
+    pub fn response(transaction_id: ByteBuf, response: Response) -> Self {
+        Self::new(transaction_id, MessageKind::Response(response))
+    }
+
+    /// Constructs an error message.
+    #[must_use]
+    pub fn error(transaction_id: ByteBuf, err: ErrorMessage) -> Self {
+        Self::new(transaction_id, MessageKind::Error(err))
+    }
+
+    /// Constructs an error reply to this message with the same transaction ID.
+    #[must_use]
+    pub fn error_reply(&self, err: ErrorMessage) -> Self {
+        Self::error(self.transaction_id.clone(), err)
+    }
+
+    /// Deserializes a message with [`from_slice()`][crate::from_slice].
+    pub fn from_slice(s: &[u8]) -> Result<Self> {
+        crate::from_slice(s)
+    }
+
+    /// Serializes the message with [`to_vec()`][crate::to_vec].
+    pub fn to_vec(&self) -> Result<Vec<u8>> {
+        crate::to_vec(self)
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct RawArgs {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cas: Option<i64>,
+    id: NodeId,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    implied_port: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    info_hash: Option<NodeId>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    k: Option<ByteBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    noseed: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    port: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    salt: Option<ByteBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    scrape: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    seq: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sig: Option<ByteBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    target: Option<NodeId>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    token: Option<ByteBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    v: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    want: Option<Want>,
+}
+
+impl RawArgs {
+    fn new(id: NodeId) -> Self {
+        RawArgs {
+            cas: None,
+            id,
+            implied_port: None,
+            info_hash: None,
+            k: None,
+            noseed: None,
+            port: None,
+            salt: None,
+            scrape: None,
+            seq: None,
+            sig: None,
+            target: None,
+            token: None,
+            v: None,
+            want: None,
+        }
+    }
+}
+
+/// The encoded form of a [`Message`].
+#[derive(Clone, Serialize, Deserialize)]
+struct RawMessage {
+    t: ByteBuf,
+    y: ByteBuf,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    q: Option<ByteBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    a: Option<RawArgs>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    r: Option<Response>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    e: Option<(i64, String)>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    v: Option<ByteBuf>,
+    #[serde(
+        default,
+        skip_serializing_if = "bool_from_int::is_false",
+        with = "bool_from_int"
+    )]
+    ro: bool,
+}
+
+fn missing(field: &'static str) -> Error {
+    <Error as de::Error>::missing_field(field)
+}
+
+impl TryFrom<RawMessage> for Message {
+    type Error = Error;
+
+    fn try_from(raw: RawMessage) -> Result<Self> {
+        let kind = match raw.y.as_slice() {
+            b"q" => {
+                let method = raw.q.ok_or_else(|| missing("q"))?;
+                let args = raw.a.ok_or_else(|| missing("a"))?;
+                let id = args.id;
+                MessageKind::Query(match method.as_slice() {
+                    b"ping" => Query::Ping { id },
+                    b"find_node" => Query::FindNode {
+                        id,
+                        target: args.target.ok_or_else(|| missing("target"))?,
+                        want: args.want,
+                    },
+                    b"get_peers" => Query::GetPeers {
+                        id,
+                        info_hash: args.info_hash.ok_or_else(|| missing("info_hash"))?,
+                        want: args.want,
+                        scrape: args.scrape.map_or(false, |s| s != 0),
+                        noseed: args.noseed.map_or(false, |s| s != 0),
+                    },
+                    b"announce_peer" => Query::AnnouncePeer {
+                        id,
+                        implied_port: args.implied_port.map_or(false, |p| p != 0),
+                        info_hash: args.info_hash.ok_or_else(|| missing("info_hash"))?,
+                        port: args.port.ok_or_else(|| missing("port"))?,
+                        token: args.token.ok_or_else(|| missing("token"))?,
+                    },
+                    b"sample_infohashes" => Query::SampleInfohashes {
+                        id,
+                        target: args.target.ok_or_else(|| missing("target"))?,
+                    },
+                    b"get" => Query::Get {
+                        id,
+                        target: args.target.ok_or_else(|| missing("target"))?,
+                        seq: args.seq,
+                    },
+                    b"put" => {
+                        let mutable = match args.k {
+                            Some(k) => Some(MutableItem {
+                                k,
+                                salt: args.salt,
+                                seq: args.seq.ok_or_else(|| missing("seq"))?,
+                                sig: args.sig.ok_or_else(|| missing("sig"))?,
+                            }),
+                            None => None,
+                        };
+                        Query::Put {
+                            id,
+                            token: args.token.ok_or_else(|| missing("token"))?,
+                            v: args.v.ok_or_else(|| missing("v"))?,
+                            mutable,
+                            cas: args.cas,
+                        }
+                    }
+                    _ => Query::Unknown { method, id },
+                })
+            }
+            b"r" => MessageKind::Response(raw.r.ok_or_else(|| missing("r"))?),
+            b"e" => {
+                let (code, message) = raw.e.ok_or_else(|| missing("e"))?;
+                MessageKind::Error(ErrorMessage {
+                    code: ErrorCode::from(code),
+                    message,
+                })
+            }
+            _ => {
+                return Err(Error::Deserialize(String::from(
+                    "unknown KRPC message type",
+                )))
+            }
+        };
+
+        Ok(Message {
+            transaction_id: raw.t,
+            version: raw.v,
+            read_only: raw.ro,
+            kind,
+        })
+    }
+}
+
+impl From<Message> for RawMessage {
+    fn from(msg: Message) -> Self {
+        let mut raw = RawMessage {
+            t: msg.transaction_id,
+            y: ByteBuf::new(),
+            q: None,
+            a: None,
+            r: None,
+            e: None,
+            v: msg.version,
+            ro: msg.read_only,
+        };
+        match msg.kind {
+            MessageKind::Query(query) => {
+                raw.y = ByteBuf::from("q");
+                raw.q = Some(ByteBuf::from(query.method()));
+                raw.a = Some(match query {
+                    Query::Ping { id } | Query::Unknown { id, .. } => RawArgs::new(id),
+                    Query::FindNode { id, target, want } => RawArgs {
+                        target: Some(target),
+                        want,
+                        ..RawArgs::new(id)
+                    },
+                    Query::GetPeers {
+                        id,
+                        info_hash,
+                        want,
+                        scrape,
+                        noseed,
+                    } => RawArgs {
+                        info_hash: Some(info_hash),
+                        noseed: if noseed { Some(1) } else { None },
+                        scrape: if scrape { Some(1) } else { None },
+                        want,
+                        ..RawArgs::new(id)
+                    },
+                    Query::AnnouncePeer {
+                        id,
+                        implied_port,
+                        info_hash,
+                        port,
+                        token,
+                    } => RawArgs {
+                        implied_port: if implied_port { Some(1) } else { None },
+                        info_hash: Some(info_hash),
+                        port: Some(port),
+                        token: Some(token),
+                        ..RawArgs::new(id)
+                    },
+                    Query::SampleInfohashes { id, target } => RawArgs {
+                        target: Some(target),
+                        ..RawArgs::new(id)
+                    },
+                    Query::Get { id, target, seq } => RawArgs {
+                        seq,
+                        target: Some(target),
+                        ..RawArgs::new(id)
+                    },
+                    Query::Put {
+                        id,
+                        token,
+                        v,
+                        mutable,
+                        cas,
+                    } => {
+                        let mut args = RawArgs {
+                            cas,
+                            token: Some(token),
+                            v: Some(v),
+                            ..RawArgs::new(id)
+                        };
+                        if let Some(item) = mutable {
+                            args.k = Some(item.k);
+                            args.salt = item.salt;
+                            args.seq = Some(item.seq);
+                            args.sig = Some(item.sig);
+                        }
+                        args
+                    }
+                });
+            }
+            MessageKind::Response(response) => {
+                raw.y = ByteBuf::from("r");
+                raw.r = Some(response);
+            }
+            MessageKind::Error(err) => {
+                raw.y = ByteBuf::from("e");
+                raw.e = Some((err.code.code(), err.message));
+            }
+        }
+        raw
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ID: NodeId = NodeId::new(*b"abcdefghij0123456789");
+    const OTHER_ID: NodeId = NodeId::new(*b"mnopqrstuvwxyz123456");
+
+    fn roundtrip(input: &[u8]) -> Result<Message> {
+        let msg = Message::from_slice(input)?;
+        assert_eq!(msg.to_vec()?, input);
+        Ok(msg)
+    }
+
+    #[test]
+    fn test_queries() -> Result<()> {
+        let msg = roundtrip(b"d1:ad2:id20:abcdefghij01234567896:target20:mnopqrstuvwxyz123456e1:q9:find_node1:t2:aa1:y1:qe")?;
+        assert_eq!(
+            msg.kind,
+            MessageKind::Query(Query::FindNode {
+                id: ID,
+                target: OTHER_ID,
+                want: None,
+            })
+        );
+
+        let msg = roundtrip(b"d1:ad2:id20:abcdefghij01234567899:info_hash20:mnopqrstuvwxyz123456e1:q9:get_peers1:t2:aa1:y1:qe")?;
+        assert_eq!(
+            msg.kind,
+            MessageKind::Query(Query::GetPeers {
+                id: ID,
+                info_hash: OTHER_ID,
+                want: None,
+                scrape: false,
+                noseed: false,
+            })
+        );
+
+        let msg = roundtrip(b"d1:ad2:id20:abcdefghij012345678912:implied_porti1e9:info_hash20:mnopqrstuvwxyz1234564:porti6881e5:token8:aoeusnthe1:q13:announce_peer1:t2:aa1:y1:qe")?;
+        match &msg.kind {
+            MessageKind::Query(query @ Query::AnnouncePeer { .. }) => {
+                assert_eq!(query.method(), b"announce_peer");
+                assert_eq!(
+                    query,
+                    &Query::AnnouncePeer {
+                        id: ID,
+                        implied_port: true,
+                        info_hash: OTHER_ID,
+                        port: 6881,
+                        token: ByteBuf::from("aoeusnth"),
+                    }
+                );
+            }
+            kind => panic!("unexpected kind: {:?}", kind),
+        }
+
+        let msg = roundtrip(b"d1:ad2:id20:abcdefghij0123456789e1:q3:foo1:t2:aa1:y1:qe")?;
+        assert_eq!(
+            msg.kind,
+            MessageKind::Query(Query::Unknown {
+                method: ByteBuf::from("foo"),
+                id: ID
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_items() -> Result<()> {
+        let msg = roundtrip(b"d1:ad2:id20:abcdefghij01234567893:seqi4e6:target20:mnopqrstuvwxyz123456e1:q3:get1:t2:aa1:y1:qe")?;
+        assert_eq!(
+            msg.kind,
+            MessageKind::Query(Query::Get {
+                id: ID,
+                target: OTHER_ID,
+                seq: Some(4)
+            })
+        );
+
+        let msg = roundtrip(b"d1:ad2:id20:abcdefghij01234567895:token4:toke1:v12:Hello World!e1:q3:put1:t2:aa1:y1:qe")?;
+        assert_eq!(
+            msg.kind,
+            MessageKind::Query(Query::Put {
+                id: ID,
+                token: ByteBuf::from("toke"),
+                v: Value::from("Hello World!"),
+                mutable: None,
+                cas: None,
+            })
+        );
+
+        let msg = roundtrip(b"d1:ad3:casi1e2:id20:abcdefghij01234567891:k4:key14:salt4:salt3:seqi2e3:sig4:sig15:token4:toke1:vli1eee1:q3:put1:t2:aa1:y1:qe")?;
+        match msg.kind {
+            MessageKind::Query(Query::Put { mutable, cas, .. }) => {
+                assert_eq!(cas, Some(1));
+                assert_eq!(
+                    mutable,
+                    Some(MutableItem {
+                        k: ByteBuf::from("key1"),
+                        salt: Some(ByteBuf::from("salt")),
+                        seq: 2,
+                        sig: ByteBuf::from("sig1"),
+                    })
+                );
+            }
+            kind => panic!("unexpected kind: {:?}", kind),
+        }
+
+        let msg = roundtrip(b"d1:rd2:id20:mnopqrstuvwxyz1234561:k4:key13:seqi2e3:sig4:sig15:token4:toke1:vi1ee1:t2:aa1:y1:re")?;
+        match msg.kind {
+            MessageKind::Response(response) => {
+                assert_eq!(response.seq, Some(2));
+                assert_eq!(response.v, Some(Value::from(1u64)));
+            }
+            kind => panic!("unexpected kind: {:?}", kind),
+        }
+
+        assert!(Message::from_slice(
+            b"d1:ad2:id20:abcdefghij01234567891:k4:key15:token4:toke1:vi1ee1:q3:put1:t2:aa1:y1:qe"
+        )
+        .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_want_and_scrape() -> Result<()> {
+        let msg = roundtrip(b"d1:ad2:id20:abcdefghij01234567896:target20:mnopqrstuvwxyz1234564:wantl2:n42:n6ee1:q9:find_node1:t2:aa1:y1:qe")?;
+        assert_eq!(
+            msg.kind,
+            MessageKind::Query(Query::FindNode {
+                id: ID,
+                target: OTHER_ID,
+                want: Some(Want::both()),
+            })
+        );
+
+        let msg = Message::from_slice(b"d1:ad2:id20:abcdefghij01234567899:info_hash20:mnopqrstuvwxyz1234566:noseedi1e6:scrapei1e4:wantl2:n62:n8ee1:q9:get_peers1:t2:aa1:y1:qe")?;
+        assert_eq!(
+            msg.kind,
+            MessageKind::Query(Query::GetPeers {
+                id: ID,
+                info_hash: OTHER_ID,
+                want: Some(Want {
+                    n4: false,
+                    n6: true
+                }),
+                scrape: true,
+                noseed: true,
+            })
+        );
+        assert_eq!(
+            msg.to_vec()?,
+            &b"d1:ad2:id20:abcdefghij01234567899:info_hash20:mnopqrstuvwxyz1234566:noseedi1e6:scrapei1e4:wantl2:n6ee1:q9:get_peers1:t2:aa1:y1:qe"[..]
+        );
+
+        let mut response = Response::new(OTHER_ID);
+        let mut filter = BloomFilter::new();
+        filter.0[1] = 0xff;
+        response.seeds_filter = Some(Box::new(filter));
+        let msg = Message::response(ByteBuf::from("aa"), response);
+        let encoded = msg.to_vec()?;
+        assert_eq!(&encoded[..14], b"d1:rd4:BFsd256");
+        match Message::from_slice(&encoded)?.kind {
+            MessageKind::Response(response) => {
+                assert_eq!(response.seeds_filter, Some(Box::new(filter)));
+                assert_eq!(response.peers_filter, None);
+            }
+            kind => panic!("unexpected kind: {:?}", kind),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_sample_infohashes() -> Result<()> {
+        let msg = roundtrip(b"d1:ad2:id20:abcdefghij01234567896:target20:mnopqrstuvwxyz123456e1:q17:sample_infohashes1:t2:aa1:y1:qe")?;
+        assert_eq!(
+            msg.kind,
+            MessageKind::Query(Query::SampleInfohashes {
+                id: ID,
+                target: OTHER_ID
+            })
+        );
+
+        let msg = roundtrip(b"d1:rd2:id20:mnopqrstuvwxyz1234568:intervali21600e3:numi2e7:samples40:abcdefghij0123456789mnopqrstuvwxyz123456e1:t2:aa1:y1:re")?;
+        match msg.kind {
+            MessageKind::Response(mut response) => {
+                assert_eq!(response.interval, Some(21600));
+                assert_eq!(response.num, Some(2));
+                assert_eq!(response.decode_samples()?, vec![ID, OTHER_ID]);
+
+                response.set_samples(&[OTHER_ID]);
+                assert_eq!(response.decode_samples()?, vec![OTHER_ID]);
+                response.samples = Some(ByteBuf::from("short"));
+                assert!(response.decode_samples().is_err());
+            }
+            kind => panic!("unexpected kind: {:?}", kind),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_response() -> Result<()> {
+        let msg = roundtrip(b"d1:rd2:id20:mnopqrstuvwxyz1234565:token8:aoeusnth6:valuesl6:axje.u6:idhtnmee1:t2:aa1:v4:UT011:y1:re")?;
+        assert_eq!(msg.version, Some(ByteBuf::from("UT01")));
+        match msg.kind {
+            MessageKind::Response(response) => {
+                assert_eq!(response.id, OTHER_ID);
+                assert_eq!(response.token, Some(ByteBuf::from("aoeusnth")));
+                assert_eq!(response.values.map(|v| v.len()), Some(2));
+                assert_eq!(response.nodes, None);
+            }
+            kind => panic!("unexpected kind: {:?}", kind),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_response_nodes() -> Result<()> {
+        let v4: (NodeId, SocketAddr) = (ID, "192.0.2.1:6881".parse().unwrap());
+        let v6: (NodeId, SocketAddr) = (OTHER_ID, "[2001:db8::1]:6881".parse().unwrap());
+
+        let mut response = Response::new(ID);
+        response.set_nodes(&[v6, v4]);
+        assert_eq!(response.nodes.as_ref().map(|n| n.len()), Some(26));
+        assert_eq!(response.nodes6.as_ref().map(|n| n.len()), Some(38));
+        assert_eq!(response.decode_nodes()?, vec![v4, v6]);
+
+        response.set_nodes(&[v4]);
+        assert_eq!(response.nodes6, None);
+        assert_eq!(response.decode_nodes()?, vec![v4]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_response_peers() -> Result<()> {
+        let peers: Vec<SocketAddr> = vec![
+            "192.0.2.1:6881".parse().unwrap(),
+            "[2001:db8::1]:6881".parse().unwrap(),
+        ];
+        let mut response = Response::new(ID);
+        assert_eq!(response.decode_peers()?, vec![]);
+        response.set_peers(&peers);
+        assert_eq!(response.decode_peers()?, peers);
+
+        response.values = Some(vec![ByteBuf::from("short")]);
+        assert!(response.decode_peers().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_error_and_read_only() -> Result<()> {
+        let msg = roundtrip(b"d1:eli201e23:A Generic Error Ocurrede1:t2:aa1:y1:ee")?;
+        assert_eq!(
+            msg.kind,
+            MessageKind::Error(ErrorMessage {
+                code: ErrorCode::Generic,
+                message: String::from("A Generic Error Ocurred"),
+            })
+        );
+
+        let mut msg = Message::new(
+            ByteBuf::from("aa"),
+            MessageKind::Query(Query::Ping { id: ID }),
+        );
+        msg.read_only = true;
+        let encoded = msg.to_vec()?;
+        assert_eq!(
+            encoded,
+            &b"d1:ad2:id20:abcdefghij0123456789e1:q4:ping2:roi1e1:t2:aa1:y1:qe"[..]
+        );
+        assert!(Message::from_slice(&encoded)?.read_only);
+        Ok(())
+    }
+
+    #[test]
+    fn test_error_reply() -> Result<()> {
+        let query =
+            Message::from_slice(b"d1:ad2:id20:abcdefghij0123456789e1:q3:foo1:t2:xy1:y1:qe")?;
+        assert!(
+            matches!(query.kind, MessageKind::Query(Query::Unknown { .. })),
+            "{:?}",
+            query.kind
+        );
+        let reply = query.error_reply(ErrorMessage::method_unknown());
+        assert_eq!(
+            reply.to_vec()?,
+            &b"d1:eli204e14:Method Unknowne1:t2:xy1:y1:ee"[..]
+        );
+
+        let err = Message::from_slice(b"d1:t2:aa1:y1:qe").unwrap_err();
+        let reply = Message::error(ByteBuf::from("aa"), ErrorMessage::from(&err));
+        let reply = Message::from_slice(&reply.to_vec()?)?;
+        assert!(
+            matches!(
+                reply.kind,
+                MessageKind::Error(ErrorMessage {
+                    code: ErrorCode::Protocol,
+                    ..
+                })
+            ),
+            "{:?}",
+            reply.kind
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid() {
+        for input in &[
+            &b"d1:t2:aa1:y1:xe"[..],
+            b"d1:t2:aa1:y1:qe",
+            b"d1:ad2:id3:abce1:q4:ping1:t2:aa1:y1:qe",
+            b"d1:ad2:id20:abcdefghij0123456789e1:q9:find_node1:t2:aa1:y1:qe",
+            b"d1:t2:aa1:y1:re",
+        ] {
+            assert!(Message::from_slice(input).is_err(), "{:?}", input);
+        }
+    }
+}
@@ -0,0 +1,208 @@
+//! Bloom filters for scraping torrents over the DHT.
+//!
+//! [BEP 33][bep_0033] adds `BFsd` (seeds) and `BFpe` (peers) bloom filters of
+//! peer IP addresses to `get_peers` responses so the number of peers for a
+//! torrent can be estimated without retrieving them.
+//!
+//! [bep_0033]: https://www.bittorrent.org/beps/bep_0033.html
+
+use core::{convert::TryFrom, fmt};
+use serde::{
+    de::{self, Deserialize, Deserializer, Visitor},
+    ser::{Serialize, Serializer},
+};
+
+#[cfg(feature = "sha1")]
+use std::net::IpAddr;
+
+/// The length of a bloom filter in bytes.
+pub const BLOOM_FILTER_LEN: usize = 256;
+
+const BITS: usize = BLOOM_FILTER_LEN * 8;
+
+/// A 256 byte bloom filter of IP addresses.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct BloomFilter(pub [u8; BLOOM_FILTER_LEN]);
+
+impl BloomFilter {
+    /// Constructs an empty bloom filter.
+    #[must_use]
+    pub fn new() -> Self {
+        BloomFilter([0; BLOOM_FILTER_LEN])
+    }
+
+    #[cfg(feature = "sha1")]
+    fn indexes(ip: &IpAddr) -> [usize; 2] {
+        use sha1::{Digest, Sha1};
+
+        let hash = match ip {
+            IpAddr::V4(ip) => Sha1::digest(ip.octets()),
+            IpAddr::V6(ip) => Sha1::digest(ip.octets()),
+        };
+        [
+            (usize::from(hash[0]) | usize::from(hash[1]) << 8) % BITS,
+            (usize::from(hash[2]) | usize::from(hash[3]) << 8) % BITS,
+        ]
+    }
+
+    /// Inserts an IP address.
+    #[cfg(feature = "sha1")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "sha1")))]
+    pub fn insert(&mut self, ip: &IpAddr) {
+        for index in &Self::indexes(ip) {
+            self.0[index / 8] |= 1 << (index % 8);
+        }
+    }
+
+    /// Returns true if the IP address may have been inserted.
+    ///
+    /// False positives are possible, but false negatives are not.
+    #[cfg(feature = "sha1")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "sha1")))]
+    #[must_use]
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        Self::indexes(ip)
+            .iter()
+            .all(|index| self.0[index / 8] & (1 << (index % 8)) != 0)
+    }
+
+    /// Merges another filter into this filter.
+    ///
+    /// The result is the filter which would be built by inserting the IP
+    /// addresses from both filters, so filters from different nodes should be
+    /// merged before estimating the count.
+    pub fn merge(&mut self, other: &BloomFilter) {
+        for (a, b) in self.0.iter_mut().zip(other.0.iter()) {
+            *a |= b;
+        }
+    }
+
+    /// Returns true if no IP addresses have been inserted.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|b| *b == 0)
+    }
+
+    /// Estimates the number of IP addresses which have been inserted.
+    #[must_use]
+    pub fn estimate_count(&self) -> f64 {
+        let zeros: u32 = self.0.iter().map(|b| b.count_zeros()).sum();
+        let zeros = core::cmp::min(zeros, BITS as u32 - 1);
+        let m = BITS as f64;
+        (f64::from(zeros) / m).ln() / (2.0 * (1.0 - 1.0 / m).ln())
+    }
+}
+
+impl Default for BloomFilter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for BloomFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BloomFilter")
+            .field("estimate_count", &self.estimate_count())
+            .finish()
+    }
+}
+
+impl Serialize for BloomFilter {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_bytes(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for BloomFilter {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct BloomFilterVisitor;
+
+        impl<'de> Visitor<'de> for BloomFilterVisitor {
+            type Value = BloomFilter;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a 256 byte string")
+            }
+
+            fn visit_bytes<E>(self, v: &[u8]) -> Result<BloomFilter, E>
+            where
+                E: de::Error,
+            {
+                if v.len() != BLOOM_FILTER_LEN {
+                    return Err(de::Error::invalid_length(v.len(), &self));
+                }
+                let mut bits = [0; BLOOM_FILTER_LEN];
+                bits.copy_from_slice(v);
+                Ok(BloomFilter(bits))
+            }
+        }
+
+        deserializer.deserialize_bytes(BloomFilterVisitor)
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for BloomFilter {
+    type Error = crate::Error;
+
+    fn try_from(v: &'a [u8]) -> crate::error::Result<Self> {
+        if v.len() != BLOOM_FILTER_LEN {
+            return Err(crate::Error::InvalidByteStrLen);
+        }
+        let mut bits = [0; BLOOM_FILTER_LEN];
+        bits.copy_from_slice(v);
+        Ok(BloomFilter(bits))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Result;
+
+    #[test]
+    fn test_serde() -> Result<()> {
+        let mut filter = BloomFilter::new();
+        assert!(filter.is_empty());
+        assert!(filter.estimate_count() < 1.0);
+        filter.0[0] = 1;
+
+        let encoded = crate::to_vec(&filter)?;
+        assert_eq!(encoded.len(), 4 + BLOOM_FILTER_LEN);
+        assert_eq!(crate::from_slice::<BloomFilter>(&encoded)?, filter);
+        assert!(crate::from_slice::<BloomFilter>(b"3:abc").is_err());
+        assert!(BloomFilter::try_from(&[0; 3][..]).is_err());
+        Ok(())
+    }
+
+    #[cfg(feature = "sha1")]
+    #[test]
+    fn test_insert_and_estimate() {
+        use std::net::{Ipv4Addr, Ipv6Addr};
+
+        // The example from BEP 33: 256 IPv4 and 1000 IPv6 addresses.
+        let mut v4 = BloomFilter::new();
+        for i in 0..=255 {
+            v4.insert(&IpAddr::V4(Ipv4Addr::new(192, 0, 2, i)));
+        }
+        let mut v6 = BloomFilter::new();
+        for i in 0..1000 {
+            v6.insert(&IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, i)));
+        }
+
+        assert!(v4.contains(&IpAddr::V4(Ipv4Addr::new(192, 0, 2, 7))));
+        let mut merged = v4;
+        merged.merge(&v6);
+        assert!(merged.contains(&IpAddr::V4(Ipv4Addr::new(192, 0, 2, 7))));
+        assert!(merged.contains(&IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 7))));
+
+        let estimate = merged.estimate_count();
+        assert!((estimate - 1256.0).abs() < 1256.0 * 0.1, "{}", estimate);
+        assert!(estimate > v4.estimate_count());
+    }
+}
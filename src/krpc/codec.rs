@@ -0,0 +1,99 @@
+//! [`tokio_util::codec`] implementation for KRPC datagrams.
+
+use super::Message;
+use crate::{codec::BytesMutWrite, error::Result};
+use bytes::BytesMut;
+use serde::Serialize;
+use tokio_util::codec::{Decoder, Encoder};
+
+/// Decodes and encodes KRPC messages, one per datagram.
+///
+/// The codec is intended to be used with a `UdpFramed` stream. Every call to
+/// `decode` consumes the entire buffer, so a malformed datagram does not
+/// affect later datagrams.
+///
+/// ```rust
+/// use bt_bencode::krpc::{KrpcCodec, Message, MessageKind};
+/// use bytes::BytesMut;
+/// use tokio_util::codec::{Decoder, Encoder};
+///
+/// let mut codec = KrpcCodec::new();
+/// let mut buf = BytesMut::from(&b"d1:ad2:id20:abcdefghij0123456789e1:q4:ping1:t2:aa1:y1:qe"[..]);
+///
+/// let msg = codec.decode(&mut buf)?.unwrap();
+/// assert!(buf.is_empty());
+/// assert!(matches!(msg.kind, MessageKind::Query(_)));
+///
+/// codec.encode(&msg, &mut buf)?;
+/// assert_eq!(Message::from_slice(&buf)?, msg);
+/// # Ok::<(), bt_bencode::Error>(())
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct KrpcCodec;
+
+impl KrpcCodec {
+    /// Constructs a new codec.
+    #[must_use]
+    pub fn new() -> Self {
+        KrpcCodec
+    }
+}
+
+impl Decoder for KrpcCodec {
+    type Item = Message;
+    type Error = crate::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Message>> {
+        if src.is_empty() {
+            return Ok(None);
+        }
+        let msg = Message::from_slice(src);
+        src.clear();
+        msg.map(Some)
+    }
+}
+
+impl Encoder<Message> for KrpcCodec {
+    type Error = crate::Error;
+
+    fn encode(&mut self, item: Message, dst: &mut BytesMut) -> Result<()> {
+        self.encode(&item, dst)
+    }
+}
+
+impl<'a> Encoder<&'a Message> for KrpcCodec {
+    type Error = crate::Error;
+
+    fn encode(&mut self, item: &'a Message, dst: &mut BytesMut) -> Result<()> {
+        let mut ser = crate::Serializer::new(BytesMutWrite(dst));
+        item.serialize(&mut ser)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::krpc::{MessageKind, NodeId, Query};
+    use serde_bytes::ByteBuf;
+
+    #[test]
+    fn test_decode() -> Result<()> {
+        let mut codec = KrpcCodec::new();
+        let mut buf = BytesMut::new();
+        assert!(codec.decode(&mut buf)?.is_none());
+
+        buf.extend_from_slice(b"d1:t2:aa1:y1:x");
+        assert!(codec.decode(&mut buf).is_err());
+        assert!(buf.is_empty());
+
+        let msg = Message::new(
+            ByteBuf::from("aa"),
+            MessageKind::Query(Query::Ping {
+                id: NodeId::new(*b"abcdefghij0123456789"),
+            }),
+        );
+        codec.encode(msg.clone(), &mut buf)?;
+        assert_eq!(codec.decode(&mut buf)?, Some(msg));
+        Ok(())
+    }
+}
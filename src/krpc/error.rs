@@ -0,0 +1,190 @@
+//! Error messages sent in reply to queries.
+
+use crate::error::Error;
+use core::fmt;
+use std::string::{String, ToString};
+
+/// The code in an error message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ErrorCode {
+    /// A generic error (201).
+    Generic,
+    /// A server error (202).
+    Server,
+    /// A protocol error such as a malformed packet or an invalid argument (203).
+    Protocol,
+    /// The query's method is unknown (204).
+    MethodUnknown,
+    /// The `v` value of a BEP 44 item is too big (205).
+    MessageTooBig,
+    /// The signature of a BEP 44 item is invalid (206).
+    InvalidSignature,
+    /// The salt of a BEP 44 item is too big (207).
+    SaltTooBig,
+    /// The `cas` value of a BEP 44 `put` does not match the stored sequence number (301).
+    CasMismatch,
+    /// The sequence number of a BEP 44 `put` is less than the stored sequence number (302).
+    SequenceNumberTooLow,
+    /// Any other code.
+    Other(i64),
+}
+
+impl ErrorCode {
+    /// Returns the numeric code.
+    #[must_use]
+    pub fn code(self) -> i64 {
+        match self {
+            ErrorCode::Generic => 201,
+            ErrorCode::Server => 202,
+            ErrorCode::Protocol => 203,
+            ErrorCode::MethodUnknown => 204,
+            ErrorCode::MessageTooBig => 205,
+            ErrorCode::InvalidSignature => 206,
+            ErrorCode::SaltTooBig => 207,
+            ErrorCode::CasMismatch => 301,
+            ErrorCode::SequenceNumberTooLow => 302,
+            ErrorCode::Other(code) => code,
+        }
+    }
+
+    /// Returns the description used when a reply is built without a message.
+    #[must_use]
+    pub fn description(self) -> &'static str {
+        match self {
+            ErrorCode::Generic => "Generic Error",
+            ErrorCode::Server => "Server Error",
+            ErrorCode::Protocol => "Protocol Error",
+            ErrorCode::MethodUnknown => "Method Unknown",
+            ErrorCode::MessageTooBig => "Message Too Big",
+            ErrorCode::InvalidSignature => "Invalid Signature",
+            ErrorCode::SaltTooBig => "Salt Too Big",
+            ErrorCode::CasMismatch => "CAS Mismatch",
+            ErrorCode::SequenceNumberTooLow => "Sequence Number Less Than Current",
+            ErrorCode::Other(_) => "Error",
+        }
+    }
+}
+
+impl From<i64> for ErrorCode {
+    fn from(code: i64) -> Self {
+        match code {
+            201 => ErrorCode::Generic,
+            202 => ErrorCode::Server,
+            203 => ErrorCode::Protocol,
+            204 => ErrorCode::MethodUnknown,
+            205 => ErrorCode::MessageTooBig,
+            206 => ErrorCode::InvalidSignature,
+            207 => ErrorCode::SaltTooBig,
+            301 => ErrorCode::CasMismatch,
+            302 => ErrorCode::SequenceNumberTooLow,
+            code => ErrorCode::Other(code),
+        }
+    }
+}
+
+impl From<ErrorCode> for i64 {
+    fn from(code: ErrorCode) -> Self {
+        code.code()
+    }
+}
+
+/// An error message.
+///
+/// ```rust
+/// use bt_bencode::krpc::{ErrorCode, ErrorMessage, Message};
+/// use serde_bytes::ByteBuf;
+///
+/// let reply = Message::error(ByteBuf::from("aa"), ErrorMessage::method_unknown());
+///
+/// assert_eq!(reply.to_vec()?, &b"d1:eli204e14:Method Unknowne1:t2:aa1:y1:ee"[..]);
+/// # Ok::<(), bt_bencode::Error>(())
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ErrorMessage {
+    /// The error code.
+    pub code: ErrorCode,
+    /// A description of the error.
+    pub message: String,
+}
+
+impl ErrorMessage {
+    /// Constructs an error message.
+    pub fn new<M: Into<String>>(code: ErrorCode, message: M) -> Self {
+        ErrorMessage {
+            code,
+            message: message.into(),
+        }
+    }
+
+    /// Constructs a generic error (201).
+    pub fn generic<M: Into<String>>(message: M) -> Self {
+        Self::new(ErrorCode::Generic, message)
+    }
+
+    /// Constructs a server error (202).
+    pub fn server<M: Into<String>>(message: M) -> Self {
+        Self::new(ErrorCode::Server, message)
+    }
+
+    /// Constructs a protocol error (203).
+    pub fn protocol<M: Into<String>>(message: M) -> Self {
+        Self::new(ErrorCode::Protocol, message)
+    }
+
+    /// Constructs a method unknown error (204).
+    #[must_use]
+    pub fn method_unknown() -> Self {
+        Self::from(ErrorCode::MethodUnknown)
+    }
+}
+
+impl From<ErrorCode> for ErrorMessage {
+    fn from(code: ErrorCode) -> Self {
+        Self::new(code, code.description())
+    }
+}
+
+/// A query which could not be decoded is answered with a protocol error.
+impl<'a> From<&'a Error> for ErrorMessage {
+    fn from(err: &'a Error) -> Self {
+        Self::protocol(err.to_string())
+    }
+}
+
+impl From<ErrorMessage> for Error {
+    fn from(err: ErrorMessage) -> Self {
+        Error::Deserialize(format!("KRPC error {}: {}", err.code.code(), err.message))
+    }
+}
+
+impl fmt::Display for ErrorMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.message, self.code.code())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codes() {
+        for code in 200..=303 {
+            assert_eq!(ErrorCode::from(code).code(), code);
+        }
+        assert_eq!(ErrorCode::from(203), ErrorCode::Protocol);
+        assert_eq!(ErrorCode::from(999), ErrorCode::Other(999));
+        assert_eq!(i64::from(ErrorCode::SequenceNumberTooLow), 302);
+    }
+
+    #[test]
+    fn test_conversions() {
+        let err = ErrorMessage::from(&Error::EofWhileParsingValue);
+        assert_eq!(err.code, ErrorCode::Protocol);
+        assert_eq!(err.message, Error::EofWhileParsingValue.to_string());
+
+        let err = Error::from(ErrorMessage::generic("oops"));
+        assert_eq!(err.to_string(), "KRPC error 201: oops");
+        assert_eq!(ErrorMessage::server("down").to_string(), "down (202)");
+    }
+}
@@ -0,0 +1,132 @@
+//! Items stored in the DHT with the `get` and `put` queries.
+//!
+//! [BEP 44][bep_0044] defines immutable items, which are addressed by the
+//! SHA-1 digest of their value, and mutable items, which are addressed by the
+//! SHA-1 digest of a public key and an optional salt.
+//!
+//! [bep_0044]: https://www.bittorrent.org/beps/bep_0044.html
+
+use crate::{error::Result, write::Write};
+use serde::Serialize;
+use serde_bytes::ByteBuf;
+use std::vec::Vec;
+
+/// The fields which make an item mutable.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MutableItem {
+    /// The 32 byte ed25519 public key (`k`).
+    pub k: ByteBuf,
+    /// The salt (`salt`) which is combined with the public key in the target.
+    pub salt: Option<ByteBuf>,
+    /// The sequence number (`seq`).
+    pub seq: i64,
+    /// The 64 byte ed25519 signature (`sig`) of the [`signable_bytes`].
+    pub sig: ByteBuf,
+}
+
+/// Returns the bytes which are signed for a mutable item.
+///
+/// The bytes are the bencoded `salt` (if it is not empty), `seq`, and `v`
+/// dictionary entries without the surrounding `d` and `e`.
+///
+/// ```rust
+/// use bt_bencode::krpc::signable_bytes;
+///
+/// assert_eq!(
+///     signable_bytes(None, 1, "Hello World!")?,
+///     b"3:seqi1e1:v12:Hello World!"
+/// );
+/// assert_eq!(
+///     signable_bytes(Some(b"foobar"), 1, "Hello World!")?,
+///     b"4:salt6:foobar3:seqi1e1:v12:Hello World!"
+/// );
+/// # Ok::<(), bt_bencode::Error>(())
+/// ```
+///
+/// # Errors
+///
+/// Serialization can fail if `v` contains an unsupported value.
+pub fn signable_bytes<T>(salt: Option<&[u8]>, seq: i64, v: &T) -> Result<Vec<u8>>
+where
+    T: ?Sized + Serialize,
+{
+    let mut out = Vec::new();
+    if let Some(salt) = salt.filter(|salt| !salt.is_empty()) {
+        out.write_all(b"4:salt")?;
+        out.write_all(itoa::Buffer::new().format(salt.len()).as_bytes())?;
+        out.write_all(b":")?;
+        out.write_all(salt)?;
+    }
+    out.write_all(b"3:seqi")?;
+    out.write_all(itoa::Buffer::new().format(seq).as_bytes())?;
+    out.write_all(b"e1:v")?;
+    crate::to_writer(&mut out, v)?;
+    Ok(out)
+}
+
+/// Returns the target of an immutable item, which is the SHA-1 digest of the encoded value.
+///
+/// # Errors
+///
+/// Serialization can fail if `v` contains an unsupported value.
+#[cfg(feature = "sha1")]
+#[cfg_attr(docsrs, doc(cfg(feature = "sha1")))]
+pub fn immutable_target<T>(v: &T) -> Result<[u8; 20]>
+where
+    T: ?Sized + Serialize,
+{
+    use sha1::{Digest, Sha1};
+
+    Ok(Sha1::digest(crate::to_vec(v)?).into())
+}
+
+/// Returns the target of a mutable item, which is the SHA-1 digest of the public key and salt.
+#[cfg(feature = "sha1")]
+#[cfg_attr(docsrs, doc(cfg(feature = "sha1")))]
+#[must_use]
+pub fn mutable_target(k: &[u8], salt: Option<&[u8]>) -> [u8; 20] {
+    use sha1::{Digest, Sha1};
+
+    let mut hasher = Sha1::new();
+    hasher.update(k);
+    hasher.update(salt.unwrap_or_default());
+    hasher.finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Value;
+
+    #[test]
+    fn test_signable_bytes() -> Result<()> {
+        assert_eq!(
+            signable_bytes(Some(b""), -1, &Value::from(12))?,
+            b"3:seqi-1e1:vi12e"
+        );
+        let v: Value = crate::from_slice(b"d1:bi1e1:a1:ze")?;
+        assert_eq!(
+            signable_bytes(Some(b"s"), 42, &v)?,
+            &b"4:salt1:s3:seqi42e1:vd1:a1:z1:bi1ee"[..]
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "sha1")]
+    #[test]
+    fn test_targets() -> Result<()> {
+        // From the test vectors in BEP 44.
+        let target = immutable_target("Hello World!")?;
+        assert_eq!(
+            target,
+            [
+                0xe5, 0xf9, 0x6f, 0x6f, 0x38, 0x32, 0x0f, 0x0f, 0x33, 0x95, 0x9c, 0xb4, 0xd3, 0xd6,
+                0x56, 0x45, 0x21, 0x17, 0xaa, 0xdb
+            ]
+        );
+        let k = [7; 32];
+        assert_ne!(mutable_target(&k, None), mutable_target(&k, Some(b"salt")));
+        assert_eq!(mutable_target(&k, None), mutable_target(&k, Some(b"")));
+        Ok(())
+    }
+}
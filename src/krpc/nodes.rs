@@ -0,0 +1,156 @@
+//! Compact node info from the `nodes` and `nodes6` fields.
+
+use super::NodeId;
+use crate::error::{Error, Result};
+use core::convert::TryFrom;
+use std::{
+    net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6},
+    string::String,
+    vec::Vec,
+};
+
+/// The length of a compact IPv4 node info entry.
+pub const COMPACT_NODE_LEN: usize = 26;
+
+/// The length of a compact IPv6 node info entry.
+pub const COMPACT_NODE6_LEN: usize = 38;
+
+fn split_entries(bytes: &[u8], len: usize) -> Result<core::slice::ChunksExact<'_, u8>> {
+    if bytes.len() % len != 0 {
+        return Err(Error::Deserialize(format!(
+            "compact node info length is not a multiple of {}",
+            len
+        )));
+    }
+    Ok(bytes.chunks_exact(len))
+}
+
+fn node_id(bytes: &[u8]) -> NodeId {
+    let mut id = [0; 20];
+    id.copy_from_slice(&bytes[..20]);
+    NodeId::new(id)
+}
+
+fn port(bytes: &[u8]) -> u16 {
+    u16::from_be_bytes([bytes[0], bytes[1]])
+}
+
+/// Decodes compact IPv4 node info from a `nodes` field.
+///
+/// Each entry is a 20 byte node ID, a 4 byte IPv4 address, and a 2 byte port
+/// in network byte order.
+///
+/// # Errors
+///
+/// An error is returned if the length is not a multiple of 26.
+pub fn decode_nodes(bytes: &[u8]) -> Result<Vec<(NodeId, SocketAddr)>> {
+    Ok(split_entries(bytes, COMPACT_NODE_LEN)?
+        .map(|entry| {
+            let ip = Ipv4Addr::new(entry[20], entry[21], entry[22], entry[23]);
+            let addr = SocketAddrV4::new(ip, port(&entry[24..]));
+            (node_id(entry), SocketAddr::V4(addr))
+        })
+        .collect())
+}
+
+/// Decodes compact IPv6 node info from a `nodes6` field.
+///
+/// Each entry is a 20 byte node ID, a 16 byte IPv6 address, and a 2 byte port
+/// in network byte order.
+///
+/// # Errors
+///
+/// An error is returned if the length is not a multiple of 38.
+pub fn decode_nodes6(bytes: &[u8]) -> Result<Vec<(NodeId, SocketAddr)>> {
+    Ok(split_entries(bytes, COMPACT_NODE6_LEN)?
+        .map(|entry| {
+            let ip = <[u8; 16]>::try_from(&entry[20..36]).expect("entry is 38 bytes");
+            let addr = SocketAddrV6::new(Ipv6Addr::from(ip), port(&entry[36..]), 0, 0);
+            (node_id(entry), SocketAddr::V6(addr))
+        })
+        .collect())
+}
+
+/// Encodes nodes with IPv4 addresses as compact node info for a `nodes` field.
+///
+/// # Errors
+///
+/// An error is returned if any address is an IPv6 address.
+pub fn encode_nodes(nodes: &[(NodeId, SocketAddr)]) -> Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(nodes.len() * COMPACT_NODE_LEN);
+    for (id, addr) in nodes {
+        match addr {
+            SocketAddr::V4(addr) => {
+                out.extend_from_slice(&id.0);
+                out.extend_from_slice(&addr.ip().octets());
+                out.extend_from_slice(&addr.port().to_be_bytes());
+            }
+            SocketAddr::V6(_) => {
+                return Err(Error::Serialize(String::from(
+                    "IPv6 address in compact IPv4 node info",
+                )))
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Encodes nodes with IPv6 addresses as compact node info for a `nodes6` field.
+///
+/// # Errors
+///
+/// An error is returned if any address is an IPv4 address.
+pub fn encode_nodes6(nodes: &[(NodeId, SocketAddr)]) -> Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(nodes.len() * COMPACT_NODE6_LEN);
+    for (id, addr) in nodes {
+        match addr {
+            SocketAddr::V6(addr) => {
+                out.extend_from_slice(&id.0);
+                out.extend_from_slice(&addr.ip().octets());
+                out.extend_from_slice(&addr.port().to_be_bytes());
+            }
+            SocketAddr::V4(_) => {
+                return Err(Error::Serialize(String::from(
+                    "IPv4 address in compact IPv6 node info",
+                )))
+            }
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ID: NodeId = NodeId::new(*b"abcdefghij0123456789");
+
+    #[test]
+    fn test_nodes() -> Result<()> {
+        let mut bytes = b"abcdefghij0123456789".to_vec();
+        bytes.extend_from_slice(&[192, 0, 2, 1, 0x1a, 0xe1]);
+        let nodes = decode_nodes(&bytes)?;
+        assert_eq!(nodes, vec![(ID, "192.0.2.1:6881".parse().unwrap())]);
+        assert_eq!(encode_nodes(&nodes)?, bytes);
+
+        assert!(decode_nodes(&bytes[..25]).is_err());
+        assert!(encode_nodes6(&nodes).is_err());
+        assert_eq!(decode_nodes(b"")?, vec![]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_nodes6() -> Result<()> {
+        let mut bytes = b"abcdefghij0123456789".to_vec();
+        bytes.extend_from_slice(&[0x20, 0x01, 0x0d, 0xb8]);
+        bytes.extend_from_slice(&[0; 11]);
+        bytes.extend_from_slice(&[1, 0xc8, 0xd5]);
+        let nodes = decode_nodes6(&bytes)?;
+        assert_eq!(nodes, vec![(ID, "[2001:db8::1]:51413".parse().unwrap())]);
+        assert_eq!(encode_nodes6(&nodes)?, bytes);
+
+        assert!(decode_nodes6(&bytes[..26]).is_err());
+        assert!(encode_nodes(&nodes).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,177 @@
+//! Persists the state of a routing table across restarts.
+
+use super::NodeId;
+use crate::error::{Error, Result};
+use serde_derive::{Deserialize, Serialize};
+use std::{net::SocketAddr, string::String, vec::Vec};
+
+/// The current version of the snapshot format.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+mod compact_addr {
+    use crate::compact;
+    use serde::{de, Deserialize, Deserializer, Serializer};
+    use serde_bytes::ByteBuf;
+    use std::net::SocketAddr;
+
+    pub(super) fn serialize<S>(addr: &SocketAddr, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_bytes(&compact::encode_peer(addr))
+    }
+
+    pub(super) fn deserialize<'de, D>(deserializer: D) -> Result<SocketAddr, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let bytes = ByteBuf::deserialize(deserializer)?;
+        compact::decode_peer(&bytes).map_err(de::Error::custom)
+    }
+}
+
+/// A node in a routing table snapshot.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotNode {
+    /// The node's ID.
+    pub id: NodeId,
+    /// The node's address, encoded as compact peer info.
+    #[serde(with = "compact_addr")]
+    pub addr: SocketAddr,
+    /// When the node last responded, in seconds since the Unix epoch.
+    #[serde(rename = "last seen", default)]
+    pub last_seen: u64,
+}
+
+/// A bucket in a routing table snapshot.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotBucket {
+    /// When a node in the bucket last changed, in seconds since the Unix epoch.
+    #[serde(rename = "last changed", default)]
+    pub last_changed: u64,
+    /// The nodes in the bucket.
+    #[serde(default)]
+    pub nodes: Vec<SnapshotNode>,
+}
+
+/// A snapshot of a routing table.
+///
+/// ```rust
+/// use bt_bencode::krpc::{NodeId, RoutingTableSnapshot, SnapshotBucket, SnapshotNode};
+///
+/// let mut snapshot = RoutingTableSnapshot::new(NodeId::default());
+/// snapshot.boot_nodes.push(String::from("router.bittorrent.com:6881"));
+/// snapshot.buckets.push(SnapshotBucket {
+///     last_changed: 1_600_000_000,
+///     nodes: vec![SnapshotNode {
+///         id: NodeId::new([1; 20]),
+///         addr: "192.0.2.1:6881".parse().unwrap(),
+///         last_seen: 1_600_000_000,
+///     }],
+/// });
+///
+/// let encoded = snapshot.to_vec()?;
+/// assert_eq!(RoutingTableSnapshot::from_slice(&encoded)?, snapshot);
+/// # Ok::<(), bt_bencode::Error>(())
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoutingTableSnapshot {
+    /// The version of the snapshot format.
+    pub version: u32,
+    /// The local node's ID.
+    pub id: NodeId,
+    /// The `host:port` addresses of nodes used to bootstrap the routing table.
+    #[serde(rename = "boot nodes", default)]
+    pub boot_nodes: Vec<String>,
+    /// The buckets in order of increasing distance from the local node.
+    #[serde(default)]
+    pub buckets: Vec<SnapshotBucket>,
+}
+
+impl RoutingTableSnapshot {
+    /// Constructs an empty snapshot with the current version.
+    #[must_use]
+    pub fn new(id: NodeId) -> Self {
+        RoutingTableSnapshot {
+            version: SNAPSHOT_VERSION,
+            id,
+            boot_nodes: Vec::new(),
+            buckets: Vec::new(),
+        }
+    }
+
+    /// Returns an iterator over the nodes in all of the buckets.
+    pub fn nodes(&self) -> impl Iterator<Item = &SnapshotNode> {
+        self.buckets.iter().flat_map(|bucket| bucket.nodes.iter())
+    }
+
+    /// Deserializes a snapshot from a slice of bytes.
+    ///
+    /// # Errors
+    ///
+    /// Deserialization can fail if the data is not a valid snapshot or if the
+    /// snapshot was written by a newer version of the format.
+    pub fn from_slice(s: &[u8]) -> Result<Self> {
+        let snapshot: Self = crate::from_slice(s)?;
+        if snapshot.version > SNAPSHOT_VERSION {
+            return Err(Error::Deserialize(format!(
+                "unsupported routing table snapshot version {}",
+                snapshot.version
+            )));
+        }
+        Ok(snapshot)
+    }
+
+    /// Serializes the snapshot with [`to_vec()`][crate::to_vec].
+    pub fn to_vec(&self) -> Result<Vec<u8>> {
+        crate::to_vec(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() -> Result<()> {
+        let mut snapshot = RoutingTableSnapshot::new(NodeId::new(*b"abcdefghij0123456789"));
+        snapshot.buckets.push(SnapshotBucket {
+            last_changed: 2,
+            nodes: vec![
+                SnapshotNode {
+                    id: NodeId::new(*b"mnopqrstuvwxyz123456"),
+                    addr: "192.0.2.1:6881".parse().unwrap(),
+                    last_seen: 1,
+                },
+                SnapshotNode {
+                    id: NodeId::default(),
+                    addr: "[2001:db8::1]:6881".parse().unwrap(),
+                    last_seen: 2,
+                },
+            ],
+        });
+
+        let encoded = snapshot.to_vec()?;
+        assert_eq!(
+            &encoded[..],
+            &b"d10:boot nodesle7:bucketsld12:last changedi2e5:nodesld4:addr6:\xc0\x00\x02\x01\x1a\xe12:id20:mnopqrstuvwxyz1234569:last seeni1eed4:addr18:\x20\x01\x0d\xb8\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x01\x1a\xe12:id20:\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x009:last seeni2eeeee2:id20:abcdefghij01234567897:versioni1ee"[..]
+        );
+        assert_eq!(RoutingTableSnapshot::from_slice(&encoded)?, snapshot);
+        assert_eq!(snapshot.nodes().count(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_versions() -> Result<()> {
+        let snapshot =
+            RoutingTableSnapshot::from_slice(b"d2:id20:abcdefghij01234567897:versioni1ee")?;
+        assert!(snapshot.buckets.is_empty());
+        assert!(snapshot.boot_nodes.is_empty());
+
+        assert!(
+            RoutingTableSnapshot::from_slice(b"d2:id20:abcdefghij01234567897:versioni2ee").is_err()
+        );
+        assert!(RoutingTableSnapshot::from_slice(b"d2:id20:abcdefghij0123456789e").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,145 @@
+//! Correlates responses with outstanding queries.
+
+use super::{Message, MessageKind};
+use serde_bytes::ByteBuf;
+use std::collections::BTreeMap;
+
+/// Generates transaction IDs and tracks outstanding queries.
+///
+/// Each query is started with some state (e.g. the query and the address it
+/// was sent to) and is given a 2 byte transaction ID which is not used by any
+/// other outstanding query. When a response or error arrives, the state is
+/// returned and the transaction ID can be reused.
+///
+/// ```rust
+/// use bt_bencode::krpc::{Message, MessageKind, NodeId, Response, Transactions};
+///
+/// let mut transactions = Transactions::new();
+/// let id = transactions.start("ping");
+///
+/// let reply = Message::response(id, Response::new(NodeId::default()));
+/// assert_eq!(transactions.finish(&reply), Some("ping"));
+/// assert_eq!(transactions.finish(&reply), None);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Transactions<T> {
+    next: u16,
+    pending: BTreeMap<Vec<u8>, T>,
+}
+
+impl<T> Transactions<T> {
+    /// Constructs an empty set of transactions.
+    #[must_use]
+    pub fn new() -> Self {
+        Transactions {
+            next: 0,
+            pending: BTreeMap::new(),
+        }
+    }
+
+    /// Starts a transaction and returns its ID.
+    ///
+    /// # Panics
+    ///
+    /// Panics if all 65536 transaction IDs are outstanding.
+    pub fn start(&mut self, state: T) -> ByteBuf {
+        assert!(
+            self.pending.len() <= usize::from(u16::MAX),
+            "all transaction IDs are in use"
+        );
+        loop {
+            let id = self.next.to_be_bytes().to_vec();
+            self.next = self.next.wrapping_add(1);
+            if !self.pending.contains_key(&id) {
+                self.pending.insert(id.clone(), state);
+                return ByteBuf::from(id);
+            }
+        }
+    }
+
+    /// Finishes the transaction for a response or error message and returns its state.
+    ///
+    /// `None` is returned if the message is a query or if its transaction ID
+    /// is not outstanding.
+    pub fn finish(&mut self, msg: &Message) -> Option<T> {
+        match msg.kind {
+            MessageKind::Response(_) | MessageKind::Error(_) => {
+                self.pending.remove(msg.transaction_id.as_slice())
+            }
+            MessageKind::Query(_) => None,
+        }
+    }
+
+    /// Removes an outstanding transaction (e.g. after a timeout) and returns its state.
+    pub fn cancel(&mut self, transaction_id: &[u8]) -> Option<T> {
+        self.pending.remove(transaction_id)
+    }
+
+    /// Returns the state of an outstanding transaction.
+    #[must_use]
+    pub fn get(&self, transaction_id: &[u8]) -> Option<&T> {
+        self.pending.get(transaction_id)
+    }
+
+    /// Returns the number of outstanding transactions.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Returns true if there are no outstanding transactions.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
+impl<T> Default for Transactions<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::krpc::{ErrorMessage, NodeId, Query};
+
+    #[test]
+    fn test_transactions() {
+        let mut transactions = Transactions::new();
+        let a = transactions.start(1);
+        let b = transactions.start(2);
+        assert_ne!(a, b);
+        assert_eq!(a.len(), 2);
+        assert_eq!(transactions.len(), 2);
+        assert_eq!(transactions.get(&b), Some(&2));
+
+        let query = Message::new(
+            b.clone(),
+            MessageKind::Query(Query::Ping {
+                id: NodeId::default(),
+            }),
+        );
+        assert_eq!(transactions.finish(&query), None);
+        assert_eq!(
+            transactions.finish(&Message::error(b, ErrorMessage::generic("err"))),
+            Some(2)
+        );
+        assert_eq!(transactions.cancel(&a), Some(1));
+        assert!(transactions.is_empty());
+    }
+
+    #[test]
+    fn test_wrapping_ids() {
+        let mut transactions = Transactions::new();
+        let first = transactions.start(());
+        transactions.next = u16::MAX;
+        let last = transactions.start(());
+        assert_eq!(last, ByteBuf::from(vec![0xff, 0xff]));
+        // The counter wraps around and skips the outstanding ID.
+        let next = transactions.start(());
+        assert_ne!(next, first);
+        assert_eq!(next, ByteBuf::from(vec![0, 1]));
+    }
+}
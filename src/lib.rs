@@ -69,6 +69,48 @@
 //! # Ok::<(), bt_bencode::Error>(())
 //! ```
 //!
+//! ## Flattened fields and untagged enums
+//!
+//! `#[serde(flatten)]` is supported when deserializing dictionaries. Serde
+//! buffers the entries which are not consumed by the outer type, so fields in
+//! a flattened type see the self-describing form of each value: byte strings
+//! are byte strings, integers are integers, and so on.
+//!
+//! ```rust
+//! use serde_derive::Deserialize;
+//!
+//! #[derive(Deserialize)]
+//! struct Common {
+//!     interval: u64,
+//! }
+//!
+//! #[derive(Deserialize)]
+//! struct Response {
+//!     peers: serde_bytes::ByteBuf,
+//!     #[serde(flatten)]
+//!     common: Common,
+//! }
+//!
+//! let response: Response = bt_bencode::from_slice(b"d8:intervali1800e5:peers0:e")?;
+//! assert_eq!(response.common.interval, 1800);
+//! # Ok::<(), bt_bencode::Error>(())
+//! ```
+//!
+//! `#[serde(untagged)]` enums buffer a value the same way and try each variant
+//! in order. As a `ByteBuf` also accepts a list of small integers, a variant
+//! for lists should come before a variant for byte strings. See the
+//! [`compact`] module for an example of a `peers` field which may be compact
+//! peer info or a list of dictionaries.
+//!
+//! Some conversions are not available to flattened fields or untagged
+//! variants because the buffered values no longer come from this crate's
+//! deserializer. A `Vec<u8>` or fixed size array cannot be read from a byte
+//! string (use [`serde_bytes`] instead), a byte string field cannot capture
+//! the raw encoding of a non byte string value, and a `char` or unit enum
+//! variant cannot be read from a byte string. A
+//! [`RawValueBuf`][value::RawValueBuf] field is re-encoded, so its dictionary
+//! keys are sorted.
+//!
 //! ## License
 //!
 //! Licensed under either of [Apache License, Version 2.0][LICENSE_APACHE] or [MIT
@@ -108,14 +150,67 @@ extern crate serde;
 mod de;
 mod error;
 
+#[cfg(feature = "bumpalo")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bumpalo")))]
+pub mod arena;
+
+#[cfg(feature = "tokio-util")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio-util")))]
+pub mod codec;
+
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub mod compact;
+
+#[cfg(feature = "ext")]
+#[cfg_attr(docsrs, doc(cfg(feature = "ext")))]
+pub mod ext;
+
+#[cfg(feature = "digest")]
+#[cfg_attr(docsrs, doc(cfg(feature = "digest")))]
+pub mod hash;
+
+#[cfg(feature = "memmap2")]
+#[cfg_attr(docsrs, doc(cfg(feature = "memmap2")))]
+pub mod mmap;
+
+pub mod event;
+pub mod id;
+pub mod incremental;
+pub mod intern;
+pub mod key;
 pub mod read;
 pub mod write;
 
 mod ser;
+pub mod serde_helpers;
 pub mod value;
 
+#[cfg(feature = "krpc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "krpc")))]
+pub mod krpc;
+
+#[cfg(feature = "resume")]
+#[cfg_attr(docsrs, doc(cfg(feature = "resume")))]
+pub mod resume;
+
+#[cfg(feature = "torrent")]
+#[cfg_attr(docsrs, doc(cfg(feature = "torrent")))]
+pub mod torrent;
+
+#[cfg(feature = "json")]
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+pub mod transcode;
+
+#[cfg(feature = "tracker")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tracker")))]
+pub mod tracker;
+
 #[doc(inline)]
-pub use de::{from_slice, Deserializer};
+pub use de::{
+    from_slice, get_raw, pretty_print, raw_values, stats, Builder as DeserializerBuilder,
+    Deserializer, PathSegment, PathSegmentBuf, RawValues, Stats,
+};
 #[doc(inline)]
 pub use error::{Error, Result};
 #[doc(inline)]
@@ -126,8 +221,39 @@ pub use value::{from_value, to_value, Value};
 pub use ser::to_writer;
 
 #[doc(inline)]
-pub use ser::{to_vec, Serializer};
+pub use ser::{to_segments, to_vec, Builder as SerializerBuilder, Serializer};
 
 #[doc(inline)]
 #[cfg(feature = "std")]
 pub use de::from_reader;
+
+#[cfg(feature = "memmap2")]
+#[cfg_attr(docsrs, doc(cfg(feature = "memmap2")))]
+pub use mmap::from_file;
+
+#[cfg(feature = "bytes")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bytes")))]
+pub use de::from_buf;
+
+#[cfg(feature = "bytes")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bytes")))]
+pub use ser::to_buf_mut;
+
+#[cfg(feature = "json")]
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+pub use transcode::{transcode_from_json, transcode_to_json};
+
+#[doc(inline)]
+#[cfg(feature = "tokio")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+pub use de::from_async_reader;
+
+#[doc(inline)]
+#[cfg(feature = "futures")]
+#[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+pub use de::{into_stream, ValueStream};
+
+#[doc(inline)]
+#[cfg(feature = "tokio")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+pub use ser::to_async_writer;
@@ -0,0 +1,147 @@
+//! Parses files through a memory map.
+//!
+//! Large metainfo files (e.g. v2 torrents with big `piece layers`) can be
+//! parsed without reading the whole file into a buffer first. Byte strings
+//! can be borrowed directly from the mapped memory with
+//! [`MappedFile::parse()`].
+
+use crate::error::{Error, Result};
+use memmap2::Mmap;
+use serde::de;
+use std::{fs::File, path::Path};
+
+/// A read-only memory map of a file.
+#[derive(Debug)]
+pub struct MappedFile {
+    mmap: Mmap,
+}
+
+impl MappedFile {
+    /// Maps the file at the path into memory.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the file cannot be opened or mapped.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated, by this or any other
+    /// process, while the returned value (or anything borrowed from it) is
+    /// alive. See [`memmap2::Mmap::map()`].
+    pub unsafe fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file = File::open(path).map_err(Error::IoError)?;
+        let mmap = Mmap::map(&file).map_err(Error::IoError)?;
+        Ok(Self { mmap })
+    }
+
+    /// Returns the mapped bytes.
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.mmap
+    }
+
+    /// Deserializes an instance of `T` from the mapped bytes.
+    ///
+    /// `T` can borrow byte strings and strings from the map.
+    ///
+    /// ```rust
+    /// use bt_bencode::mmap::MappedFile;
+    ///
+    /// let path = concat!(
+    ///     env!("CARGO_MANIFEST_DIR"),
+    ///     "/tests/ubuntu-20.04.4-live-server-amd64.iso.torrent"
+    /// );
+    /// // The test fixture is not modified while the map is alive.
+    /// let file = unsafe { MappedFile::open(path)? };
+    /// let value: bt_bencode::Value = file.parse()?;
+    /// assert!(value.get("info").is_some());
+    /// # Ok::<(), bt_bencode::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Deserialization can fail if the data is not valid, if the data cannot be deserialized into
+    /// an instance of `T`, or if there is trailing data.
+    pub fn parse<'a, T>(&'a self) -> Result<T>
+    where
+        T: de::Deserialize<'a>,
+    {
+        crate::from_slice(self.as_bytes())
+    }
+}
+
+impl AsRef<[u8]> for MappedFile {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+/// Deserializes an instance of `T` from a memory mapped file.
+///
+/// The file is unmapped before returning, so `T` must own its data. Use
+/// [`MappedFile`] to borrow from the map.
+///
+/// # Errors
+///
+/// Deserialization can fail if the file cannot be mapped, if the data is not valid, if the data
+/// cannot be deserialized into an instance of `T`, or if there is trailing data.
+///
+/// # Safety
+///
+/// The file must not be modified or truncated while it is being parsed. See
+/// [`MappedFile::open()`].
+pub unsafe fn from_file<P, T>(path: P) -> Result<T>
+where
+    P: AsRef<Path>,
+    T: de::DeserializeOwned,
+{
+    MappedFile::open(path)?.parse()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_bytes::Bytes;
+
+    const TORRENT_PATH: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/ubuntu-20.04.4-live-server-amd64.iso.torrent"
+    );
+
+    #[test]
+    fn test_parse_borrowed() -> Result<()> {
+        #[derive(serde_derive::Deserialize)]
+        struct Info<'a> {
+            name: &'a str,
+            #[serde(borrow)]
+            pieces: &'a Bytes,
+        }
+
+        #[derive(serde_derive::Deserialize)]
+        struct Torrent<'a> {
+            announce: &'a str,
+            #[serde(borrow)]
+            info: Info<'a>,
+        }
+
+        let file = unsafe { MappedFile::open(TORRENT_PATH)? };
+        let torrent: Torrent<'_> = file.parse()?;
+        assert_eq!(torrent.announce, "https://torrent.ubuntu.com/announce");
+        assert_eq!(torrent.info.name, "ubuntu-20.04.4-live-server-amd64.iso");
+
+        let map = file.as_bytes().as_ptr_range();
+        assert!(map.contains(&torrent.info.pieces.as_ptr()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_file() -> Result<()> {
+        let value: crate::Value = unsafe { from_file(TORRENT_PATH)? };
+        assert_eq!(
+            value["announce"].as_str(),
+            Some("https://torrent.ubuntu.com/announce")
+        );
+        assert!(unsafe { from_file::<_, crate::Value>("/nonexistent/file") }.is_err());
+        Ok(())
+    }
+}
@@ -4,10 +4,10 @@ use crate::error::{Error, Result};
 use core::ops::Deref;
 
 #[cfg(all(feature = "alloc", not(feature = "std")))]
-use alloc::vec::Vec;
+use alloc::{format, vec::Vec};
 
 #[cfg(feature = "std")]
-use std::{io, vec::Vec};
+use std::{convert::TryFrom, format, io, io::Read as _, vec::Vec};
 
 /// A reference to borrowed data.
 ///
@@ -56,6 +56,17 @@ pub trait Read<'a> {
     /// Returns the position in the stream of bytes.
     fn byte_offset(&self) -> usize;
 
+    /// Returns the number of encoded values before the end of the current
+    /// list or dictionary, if the number can be cheaply determined.
+    ///
+    /// The number is used as a size hint to pre-allocate collections. For a
+    /// dictionary, both keys and values are counted. The default
+    /// implementation returns `None`.
+    #[inline]
+    fn item_count_hint(&self) -> Option<usize> {
+        None
+    }
+
     /// Consumes and returns the next integer.
     ///
     /// The buffer can be used as a temporary buffer for storing any bytes which need to be read.
@@ -127,6 +138,32 @@ pub trait Read<'a> {
         Ok(Ref::Buffer(&buf[..]))
     }
 
+    /// Returns the next byte string like [`parse_byte_str()`][Read::parse_byte_str]
+    /// but fails if the byte string is longer than `max_len`.
+    ///
+    /// The default implementation checks the length after the byte string is
+    /// read. Implementations which copy the contents into the buffer should
+    /// check the length prefix before reading the contents.
+    ///
+    /// # Errors
+    ///
+    /// Errors include:
+    ///
+    /// - malformatted input
+    /// - end of file
+    /// - a byte string which is longer than `max_len`
+    fn parse_byte_str_max_len<'b>(
+        &'b mut self,
+        buf: &'b mut Vec<u8>,
+        max_len: usize,
+    ) -> Result<Ref<'a, 'b, [u8]>> {
+        let bytes = self.parse_byte_str(buf)?;
+        if bytes.len() > max_len {
+            return Err(byte_str_too_long(max_len));
+        }
+        Ok(bytes)
+    }
+
     /// Consumes and returns the next integer raw encoding.
     ///
     /// The buffer can be used as a temporary buffer for storing any bytes which need to be read.
@@ -195,6 +232,27 @@ pub trait Read<'a> {
         Ok(Ref::Buffer(&buf[start_idx..]))
     }
 
+    /// Consumes and returns the next value's raw encoding.
+    ///
+    /// The buffer can be used as a temporary buffer for storing any bytes which need to be read.
+    /// The contents of the buffer is not guaranteed before or after the method is called.
+    ///
+    /// # Errors
+    ///
+    /// Errors include:
+    ///
+    /// - malformatted input
+    /// - end of file
+    fn parse_raw_value<'b>(&'b mut self, buf: &'b mut Vec<u8>) -> Result<Ref<'a, 'b, [u8]>> {
+        match self.peek().ok_or(Error::EofWhileParsingValue)?? {
+            b'0'..=b'9' => self.parse_raw_byte_str(buf),
+            b'i' => self.parse_raw_integer(buf),
+            b'l' => self.parse_raw_list(buf),
+            b'd' => self.parse_raw_dict(buf),
+            _ => Err(Error::ExpectedSomeValue),
+        }
+    }
+
     /// Consumes and returns the next list raw encoding.
     ///
     /// The buffer can be used as a temporary buffer for storing any bytes which need to be read.
@@ -284,6 +342,10 @@ pub trait Read<'a> {
 }
 
 /// A wrapper to implement this crate's [Read] trait for [`std::io::Read`] trait implementations.
+///
+/// Individual bytes are read with separate calls to the underlying reader, so
+/// a buffered reader (e.g. [`std::io::BufReader`]) should be used for sources
+/// like files and sockets.
 #[cfg(feature = "std")]
 #[derive(Debug)]
 #[allow(clippy::module_name_repetitions)]
@@ -291,7 +353,7 @@ pub struct IoRead<R>
 where
     R: io::Read,
 {
-    iter: io::Bytes<R>,
+    reader: R,
     peeked_byte: Option<u8>,
     byte_offset: usize,
 }
@@ -304,11 +366,60 @@ where
     /// Instantiates a new reader.
     pub fn new(reader: R) -> Self {
         IoRead {
-            iter: reader.bytes(),
+            reader,
             peeked_byte: None,
             byte_offset: 0,
         }
     }
+
+    fn read_byte(&mut self) -> Option<Result<u8>> {
+        let mut byte = [0];
+        loop {
+            match self.reader.read(&mut byte) {
+                Ok(0) => return None,
+                Ok(_) => return Some(Ok(byte[0])),
+                Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Some(Err(Error::IoError(err))),
+            }
+        }
+    }
+
+    /// Consumes a byte string length prefix and the `:` separator.
+    ///
+    /// The digits are appended to the buffer.
+    fn parse_byte_str_len(&mut self, buf: &mut Vec<u8>) -> Result<usize> {
+        let start_idx = buf.len();
+        loop {
+            match Read::next(self).ok_or(Error::EofWhileParsingValue)?? {
+                b':' => return Ok(core::str::from_utf8(&buf[start_idx..])?.parse()?),
+                n @ b'0'..=b'9' => buf.push(n),
+                _ => return Err(Error::InvalidByteStrLen),
+            }
+        }
+    }
+
+    /// Appends exactly `len` bytes to the buffer.
+    ///
+    /// The bytes are copied in bulk instead of one byte at a time. The buffer
+    /// grows as data is read, so an invalid length does not cause a large
+    /// up-front allocation.
+    fn read_exact_into(&mut self, len: usize, buf: &mut Vec<u8>) -> Result<()> {
+        let start_idx = buf.len();
+        let mut remaining = len;
+        if remaining > 0 {
+            if let Some(b) = self.peeked_byte.take() {
+                buf.push(b);
+                remaining -= 1;
+            }
+        }
+
+        let result = io::Read::take(&mut self.reader, remaining as u64).read_to_end(buf);
+        self.byte_offset += buf.len() - start_idx;
+        if result.map_err(Error::IoError)? < remaining {
+            return Err(Error::EofWhileParsingValue);
+        }
+        Ok(())
+    }
 }
 
 #[cfg(feature = "std")]
@@ -318,33 +429,26 @@ where
 {
     #[inline]
     fn next(&mut self) -> Option<Result<u8>> {
-        match self.peeked_byte.take() {
-            Some(b) => {
-                self.byte_offset += 1;
-                Some(Ok(b))
-            }
-            None => match self.iter.next() {
-                Some(Ok(b)) => {
-                    self.byte_offset += 1;
-                    Some(Ok(b))
-                }
-                Some(Err(err)) => Some(Err(Error::IoError(err))),
-                None => None,
-            },
+        let result = match self.peeked_byte.take() {
+            Some(b) => Some(Ok(b)),
+            None => self.read_byte(),
+        };
+        if let Some(Ok(_)) = result {
+            self.byte_offset += 1;
         }
+        result
     }
 
     #[inline]
     fn peek(&mut self) -> Option<Result<u8>> {
         match self.peeked_byte {
             Some(b) => Some(Ok(b)),
-            None => match self.iter.next() {
+            None => match self.read_byte() {
                 Some(Ok(b)) => {
                     self.peeked_byte = Some(b);
                     Some(Ok(b))
                 }
-                Some(Err(err)) => Some(Err(Error::IoError(err))),
-                None => None,
+                result => result,
             },
         }
     }
@@ -353,8 +457,55 @@ where
     fn byte_offset(&self) -> usize {
         self.byte_offset
     }
+
+    fn parse_byte_str<'b>(&'b mut self, buf: &'b mut Vec<u8>) -> Result<Ref<'a, 'b, [u8]>> {
+        debug_assert!(buf.is_empty());
+
+        let len = self.parse_byte_str_len(buf)?;
+        buf.clear();
+        self.read_exact_into(len, buf)?;
+        Ok(Ref::Buffer(&buf[..]))
+    }
+
+    fn parse_byte_str_max_len<'b>(
+        &'b mut self,
+        buf: &'b mut Vec<u8>,
+        max_len: usize,
+    ) -> Result<Ref<'a, 'b, [u8]>> {
+        debug_assert!(buf.is_empty());
+
+        let len = self.parse_byte_str_len(buf)?;
+        if len > max_len {
+            return Err(byte_str_too_long(max_len));
+        }
+        buf.clear();
+        self.read_exact_into(len, buf)?;
+        Ok(Ref::Buffer(&buf[..]))
+    }
+
+    fn parse_raw_byte_str<'b>(&mut self, buf: &'b mut Vec<u8>) -> Result<Ref<'a, 'b, [u8]>> {
+        let start_idx = buf.len();
+        let len = self.parse_byte_str_len(buf)?;
+        buf.push(b':');
+        self.read_exact_into(len, buf)?;
+        Ok(Ref::Buffer(&buf[start_idx..]))
+    }
 }
 
+/// Returns the error for a byte string which is longer than the maximum length.
+fn byte_str_too_long(max_len: usize) -> Error {
+    Error::Deserialize(format!(
+        "byte string is longer than the maximum length of {} bytes",
+        max_len
+    ))
+}
+
+/// The maximum number of bytes scanned by [`SliceRead`] to determine an item count hint.
+///
+/// The bytes are scanned again for every nested list or dictionary, so the
+/// limit bounds the extra work per container.
+const ITEM_COUNT_HINT_LOOKAHEAD: usize = 64;
+
 /// A wrapper to implement this crate's [Read] trait for byte slices.
 #[derive(Debug)]
 #[allow(clippy::module_name_repetitions)]
@@ -374,6 +525,85 @@ impl<'a> SliceRead<'a> {
     }
 }
 
+impl<'a> SliceRead<'a> {
+    /// Consumes the next value without recursing into lists and dictionaries.
+    ///
+    /// On error, the byte offset is not moved.
+    pub(crate) fn skip_value(&mut self) -> Result<()> {
+        let rest = &self.slice[self.byte_offset..];
+        match crate::incremental::Incremental::new().feed(rest)? {
+            crate::incremental::Status::Complete(len) => {
+                self.byte_offset += len;
+                Ok(())
+            }
+            crate::incremental::Status::NeedMore => Err(Error::EofWhileParsingValue),
+        }
+    }
+
+    /// Scans the digits (and optional leading `-`) of an integer starting at
+    /// `start_idx` and returns the index of the terminating `e`.
+    ///
+    /// On error, the byte offset is moved to where the error was detected.
+    #[inline]
+    fn scan_integer(&mut self, start_idx: usize) -> Result<usize> {
+        let rest = &self.slice[start_idx..];
+        let sign_len = match rest.first() {
+            Some(b'-') => 1,
+            Some(b'0'..=b'9') => 0,
+            Some(_) => {
+                self.byte_offset = start_idx + 1;
+                return Err(Error::InvalidInteger);
+            }
+            None => {
+                self.byte_offset = start_idx;
+                return Err(Error::EofWhileParsingValue);
+            }
+        };
+
+        let end_idx = start_idx + sign_len + leading_digits(&rest[sign_len..]);
+        match self.slice.get(end_idx) {
+            Some(b'e') => Ok(end_idx),
+            Some(_) => {
+                self.byte_offset = end_idx + 1;
+                Err(Error::InvalidInteger)
+            }
+            None => {
+                self.byte_offset = end_idx;
+                Err(Error::EofWhileParsingValue)
+            }
+        }
+    }
+
+    /// Scans the length prefix and contents of a byte string starting at
+    /// `start_idx` and returns the start and end indexes of the contents.
+    ///
+    /// On error, the byte offset is moved to where the error was detected.
+    #[inline]
+    fn scan_byte_str(&mut self, start_idx: usize) -> Result<(usize, usize)> {
+        let len_end_idx = start_idx + leading_digits(&self.slice[start_idx..]);
+        match self.slice.get(len_end_idx) {
+            Some(b':') => {}
+            Some(_) => {
+                self.byte_offset = len_end_idx + 1;
+                return Err(Error::InvalidByteStrLen);
+            }
+            None => {
+                self.byte_offset = len_end_idx;
+                return Err(Error::EofWhileParsingValue);
+            }
+        }
+
+        let len = parse_len(&self.slice[start_idx..len_end_idx])?;
+        let data_start_idx = len_end_idx + 1;
+        if self.slice.len() - data_start_idx < len {
+            self.byte_offset = self.slice.len();
+            return Err(Error::EofWhileParsingValue);
+        }
+
+        Ok((data_start_idx, data_start_idx + len))
+    }
+}
+
 impl<'a> Read<'a> for SliceRead<'a> {
     #[inline]
     fn next(&mut self) -> Option<Result<u8>> {
@@ -400,103 +630,85 @@ impl<'a> Read<'a> for SliceRead<'a> {
         self.byte_offset
     }
 
-    #[inline]
-    fn parse_integer<'b>(&'b mut self, _buf: &'b mut Vec<u8>) -> Result<Ref<'a, 'b, str>> {
-        let start_idx = self.byte_offset;
+    fn item_count_hint(&self) -> Option<usize> {
+        // Only look ahead a bounded number of bytes so that the hint stays cheap.
+        let end_idx = core::cmp::min(
+            self.slice.len(),
+            self.byte_offset.saturating_add(ITEM_COUNT_HINT_LOOKAHEAD),
+        );
+        let slice = &self.slice[..end_idx];
 
-        match self.next().ok_or(Error::EofWhileParsingValue)?? {
-            b'-' | b'0'..=b'9' => loop {
-                match self.next().ok_or(Error::EofWhileParsingValue)?? {
-                    b'0'..=b'9' => {}
-                    b'e' => {
-                        return Ok(Ref::Source(core::str::from_utf8(
-                            &self.slice[start_idx..self.byte_offset - 1],
-                        )?));
+        // The values are skipped without being validated or copied.
+        let mut idx = self.byte_offset;
+        let mut depth = 0_usize;
+        let mut count = 0;
+        loop {
+            match *slice.get(idx)? {
+                b'e' if depth == 0 => return Some(count),
+                b'e' => {
+                    depth -= 1;
+                    idx += 1;
+                }
+                b'l' | b'd' => {
+                    depth += 1;
+                    idx += 1;
+                    continue;
+                }
+                b'i' => {
+                    idx += slice[idx..].iter().position(|&b| b == b'e')? + 1;
+                }
+                b'0'..=b'9' => {
+                    let len_end_idx = idx + leading_digits(&slice[idx..]);
+                    if slice.get(len_end_idx) != Some(&b':') {
+                        return None;
+                    }
+                    let len = parse_len(&slice[idx..len_end_idx]).ok()?;
+                    idx = (len_end_idx + 1).checked_add(len)?;
+                    if idx > slice.len() {
+                        return None;
                     }
-                    _ => return Err(Error::InvalidInteger),
                 }
-            },
-            _ => Err(Error::InvalidInteger),
+                _ => return None,
+            }
+            if depth == 0 {
+                count += 1;
+            }
         }
     }
 
     #[inline]
-    fn parse_byte_str<'b>(&'b mut self, _buf: &'b mut Vec<u8>) -> Result<Ref<'a, 'b, [u8]>> {
-        let start_idx = self.byte_offset;
-
-        let len: usize;
-        loop {
-            match self.next().ok_or(Error::EofWhileParsingValue)?? {
-                b':' => {
-                    len = core::str::from_utf8(&self.slice[start_idx..self.byte_offset - 1])?
-                        .parse()?;
-                    break;
-                }
-                b'0'..=b'9' => {}
-                _ => return Err(Error::InvalidByteStrLen),
-            }
-        }
-
+    fn parse_integer<'b>(&'b mut self, _buf: &'b mut Vec<u8>) -> Result<Ref<'a, 'b, str>> {
         let start_idx = self.byte_offset;
-        self.byte_offset += len;
-
-        let slice_len = self.slice.len();
-        if slice_len < self.byte_offset {
-            self.byte_offset = slice_len;
-            return Err(Error::EofWhileParsingValue);
-        }
+        let end_idx = self.scan_integer(start_idx)?;
+        self.byte_offset = end_idx + 1;
+        Ok(Ref::Source(core::str::from_utf8(
+            &self.slice[start_idx..end_idx],
+        )?))
+    }
 
-        Ok(Ref::Source(&self.slice[start_idx..self.byte_offset]))
+    #[inline]
+    fn parse_byte_str<'b>(&'b mut self, _buf: &'b mut Vec<u8>) -> Result<Ref<'a, 'b, [u8]>> {
+        let (start_idx, end_idx) = self.scan_byte_str(self.byte_offset)?;
+        self.byte_offset = end_idx;
+        Ok(Ref::Source(&self.slice[start_idx..end_idx]))
     }
 
     fn parse_raw_integer<'b>(&'b mut self, _buf: &'b mut Vec<u8>) -> Result<Ref<'a, 'b, [u8]>> {
         let start_idx = self.byte_offset;
-
-        self.next().ok_or(Error::EofWhileParsingValue)??;
-
-        match self.peek().ok_or(Error::EofWhileParsingValue)?? {
-            b'-' => {
-                self.next().ok_or(Error::EofWhileParsingValue)??;
-            }
-            b'0'..=b'9' => {}
-            _ => return Err(Error::InvalidInteger),
+        if start_idx >= self.slice.len() {
+            return Err(Error::EofWhileParsingValue);
         }
 
-        loop {
-            match self.next().ok_or(Error::EofWhileParsingValue)?? {
-                b'e' => {
-                    return Ok(Ref::Source(&self.slice[start_idx..self.byte_offset]));
-                }
-                b'0'..=b'9' => {}
-                _ => return Err(Error::InvalidInteger),
-            }
-        }
+        let end_idx = self.scan_integer(start_idx + 1)?;
+        self.byte_offset = end_idx + 1;
+        Ok(Ref::Source(&self.slice[start_idx..self.byte_offset]))
     }
 
     fn parse_raw_byte_str<'b>(&mut self, _buf: &'b mut Vec<u8>) -> Result<Ref<'a, 'b, [u8]>> {
         let start_idx = self.byte_offset;
-
-        let len: usize;
-        loop {
-            match self.next().ok_or(Error::EofWhileParsingValue)?? {
-                b':' => {
-                    len = core::str::from_utf8(&self.slice[start_idx..self.byte_offset - 1])?
-                        .parse()?;
-                    break;
-                }
-                b'0'..=b'9' => {}
-                _ => return Err(Error::InvalidByteStrLen),
-            }
-        }
-        self.byte_offset += len;
-
-        let slice_len = self.slice.len();
-        if slice_len < self.byte_offset {
-            self.byte_offset = slice_len;
-            return Err(Error::EofWhileParsingValue);
-        }
-
-        Ok(Ref::Source(&self.slice[start_idx..self.byte_offset]))
+        let (_, end_idx) = self.scan_byte_str(start_idx)?;
+        self.byte_offset = end_idx;
+        Ok(Ref::Source(&self.slice[start_idx..end_idx]))
     }
 
     fn parse_raw_list<'b>(&'b mut self, buf: &'b mut Vec<u8>) -> Result<Ref<'a, 'b, [u8]>> {
@@ -566,3 +778,961 @@ impl<'a> Read<'a> for SliceRead<'a> {
         }
     }
 }
+
+/// A wrapper to implement this crate's [Read] trait for [`bytes::Buf`] implementations.
+///
+/// The buffer may be made of non-contiguous chunks (e.g. a
+/// [`Chain`][bytes::buf::Chain]). Byte strings are copied chunk by chunk into
+/// the deserializer's scratch buffer.
+#[cfg(feature = "bytes")]
+#[derive(Debug)]
+#[allow(clippy::module_name_repetitions)]
+pub struct BufRead<B> {
+    buf: B,
+    byte_offset: usize,
+}
+
+#[cfg(feature = "bytes")]
+impl<B> BufRead<B>
+where
+    B: bytes::Buf,
+{
+    /// Instantiates a new reader.
+    pub fn new(buf: B) -> Self {
+        BufRead {
+            buf,
+            byte_offset: 0,
+        }
+    }
+
+    /// Returns the remaining unread buffer.
+    pub fn into_inner(self) -> B {
+        self.buf
+    }
+
+    /// Consumes a byte string length prefix and the `:` separator.
+    ///
+    /// The digits are appended to the buffer.
+    fn parse_byte_str_len(&mut self, buf: &mut Vec<u8>) -> Result<usize> {
+        let start_idx = buf.len();
+        loop {
+            match Read::next(self).ok_or(Error::EofWhileParsingValue)?? {
+                b':' => return parse_len(&buf[start_idx..]),
+                n @ b'0'..=b'9' => buf.push(n),
+                _ => return Err(Error::InvalidByteStrLen),
+            }
+        }
+    }
+
+    /// Appends exactly `len` bytes to the buffer.
+    fn read_exact_into(&mut self, len: usize, buf: &mut Vec<u8>) -> Result<()> {
+        if self.buf.remaining() < len {
+            self.byte_offset += self.buf.remaining();
+            self.buf.advance(self.buf.remaining());
+            return Err(Error::EofWhileParsingValue);
+        }
+        buf.reserve(len);
+        let mut remaining = len;
+        while remaining > 0 {
+            let chunk = self.buf.chunk();
+            let n = core::cmp::min(chunk.len(), remaining);
+            buf.extend_from_slice(&chunk[..n]);
+            self.buf.advance(n);
+            remaining -= n;
+        }
+        self.byte_offset += len;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl<'a, B> Read<'a> for BufRead<B>
+where
+    B: bytes::Buf,
+{
+    #[inline]
+    fn next(&mut self) -> Option<Result<u8>> {
+        if self.buf.has_remaining() {
+            self.byte_offset += 1;
+            Some(Ok(self.buf.get_u8()))
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn peek(&mut self) -> Option<Result<u8>> {
+        self.buf.chunk().first().map(|b| Ok(*b))
+    }
+
+    #[inline]
+    fn byte_offset(&self) -> usize {
+        self.byte_offset
+    }
+
+    fn parse_byte_str<'b>(&'b mut self, buf: &'b mut Vec<u8>) -> Result<Ref<'a, 'b, [u8]>> {
+        debug_assert!(buf.is_empty());
+
+        let len = self.parse_byte_str_len(buf)?;
+        buf.clear();
+        self.read_exact_into(len, buf)?;
+        Ok(Ref::Buffer(&buf[..]))
+    }
+
+    fn parse_byte_str_max_len<'b>(
+        &'b mut self,
+        buf: &'b mut Vec<u8>,
+        max_len: usize,
+    ) -> Result<Ref<'a, 'b, [u8]>> {
+        debug_assert!(buf.is_empty());
+
+        let len = self.parse_byte_str_len(buf)?;
+        if len > max_len {
+            return Err(byte_str_too_long(max_len));
+        }
+        buf.clear();
+        self.read_exact_into(len, buf)?;
+        Ok(Ref::Buffer(&buf[..]))
+    }
+
+    fn parse_raw_byte_str<'b>(&mut self, buf: &'b mut Vec<u8>) -> Result<Ref<'a, 'b, [u8]>> {
+        let start_idx = buf.len();
+        let len = self.parse_byte_str_len(buf)?;
+        buf.push(b':');
+        self.read_exact_into(len, buf)?;
+        Ok(Ref::Buffer(&buf[start_idx..]))
+    }
+}
+
+/// The maximum number of digits accepted in a byte string length prefix by [`ByteStrReader`].
+#[cfg(feature = "std")]
+const MAX_LEN_DIGITS: usize = 32;
+
+/// Accumulates the digits of a byte string length prefix.
+#[cfg(feature = "std")]
+#[derive(Debug, Default)]
+struct LenPrefix {
+    digits: [u8; MAX_LEN_DIGITS],
+    count: usize,
+}
+
+#[cfg(feature = "std")]
+impl LenPrefix {
+    /// Adds the next byte of the prefix and returns the length once the `:` is reached.
+    fn push(&mut self, b: u8) -> Result<Option<usize>> {
+        match b {
+            b':' => parse_len(&self.digits[..self.count]).map(Some),
+            b'0'..=b'9' if self.count < MAX_LEN_DIGITS => {
+                self.digits[self.count] = b;
+                self.count += 1;
+                Ok(None)
+            }
+            _ => Err(Error::InvalidByteStrLen),
+        }
+    }
+}
+
+/// The next action for the caller of a [`FieldSeeker`].
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Seek {
+    /// Read the next byte and pass it to [`FieldSeeker::byte()`].
+    Byte,
+    /// Read and discard the given number of bytes, then read the next byte.
+    Discard(usize),
+    /// Read a key of the given length and pass it to [`FieldSeeker::key()`].
+    Key(usize),
+    /// The source is positioned at the start of the field's value.
+    Found,
+}
+
+#[cfg(feature = "std")]
+#[derive(Debug)]
+enum SeekState {
+    /// Expecting the start of the dictionary which contains the next key.
+    Dict,
+    /// Expecting a key or the end of the dictionary.
+    Key,
+    /// Reading the length prefix of a key.
+    KeyLen(LenPrefix),
+    /// Skipping a value inside the given number of unfinished lists and dictionaries.
+    Skip(usize),
+    /// Reading the length prefix of a byte string which is skipped.
+    SkipLen(usize, LenPrefix),
+    /// Skipping the digits of an integer.
+    SkipInt(usize),
+}
+
+/// Finds a dictionary field by its path while the input is read one byte at a time.
+///
+/// The values before the field are skipped without buffering them, so the
+/// same logic can be driven by synchronous and asynchronous sources.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+struct FieldSeeker<'p> {
+    path: &'p [&'p [u8]],
+    matched: usize,
+    state: SeekState,
+}
+
+#[cfg(feature = "std")]
+impl<'p> FieldSeeker<'p> {
+    fn new(path: &'p [&'p [u8]]) -> Self {
+        Self {
+            path,
+            matched: 0,
+            state: SeekState::Dict,
+        }
+    }
+
+    fn start(&self) -> Seek {
+        if self.path.is_empty() {
+            Seek::Found
+        } else {
+            Seek::Byte
+        }
+    }
+
+    /// Returns the state after a skipped value ends.
+    fn end_skipped(depth: usize) -> SeekState {
+        if depth == 0 {
+            SeekState::Key
+        } else {
+            SeekState::Skip(depth)
+        }
+    }
+
+    fn byte(&mut self, b: u8) -> Result<Seek> {
+        self.state = match core::mem::replace(&mut self.state, SeekState::Dict) {
+            SeekState::Dict => match b {
+                b'd' => SeekState::Key,
+                _ => return Err(Error::InvalidDict),
+            },
+            SeekState::Key => match b {
+                b'e' => {
+                    return Err(Error::Deserialize(format!(
+                        "missing field `{}`",
+                        String::from_utf8_lossy(self.path[self.matched])
+                    )))
+                }
+                b'0'..=b'9' => {
+                    let mut prefix = LenPrefix::default();
+                    prefix.push(b)?;
+                    SeekState::KeyLen(prefix)
+                }
+                _ => return Err(Error::KeyMustBeAByteStr),
+            },
+            SeekState::KeyLen(mut prefix) => match prefix.push(b)? {
+                Some(len) if len == self.path[self.matched].len() => return Ok(Seek::Key(len)),
+                Some(len) => {
+                    self.state = SeekState::Skip(0);
+                    return Ok(Seek::Discard(len));
+                }
+                None => SeekState::KeyLen(prefix),
+            },
+            SeekState::Skip(depth) => match b {
+                b'i' => SeekState::SkipInt(depth),
+                b'l' | b'd' => SeekState::Skip(depth + 1),
+                b'e' if depth > 0 => Self::end_skipped(depth - 1),
+                b'0'..=b'9' => {
+                    let mut prefix = LenPrefix::default();
+                    prefix.push(b)?;
+                    SeekState::SkipLen(depth, prefix)
+                }
+                _ => return Err(Error::ExpectedSomeValue),
+            },
+            SeekState::SkipLen(depth, mut prefix) => match prefix.push(b)? {
+                Some(len) => {
+                    self.state = Self::end_skipped(depth);
+                    return Ok(Seek::Discard(len));
+                }
+                None => SeekState::SkipLen(depth, prefix),
+            },
+            SeekState::SkipInt(depth) => match b {
+                b'-' | b'0'..=b'9' => SeekState::SkipInt(depth),
+                b'e' => Self::end_skipped(depth),
+                _ => return Err(Error::InvalidInteger),
+            },
+        };
+        Ok(Seek::Byte)
+    }
+
+    fn key(&mut self, key: &[u8]) -> Seek {
+        if key != self.path[self.matched] {
+            self.state = SeekState::Skip(0);
+            return Seek::Byte;
+        }
+        self.matched += 1;
+        if self.matched == self.path.len() {
+            Seek::Found
+        } else {
+            self.state = SeekState::Dict;
+            Seek::Byte
+        }
+    }
+}
+
+/// Reads the contents of a single byte string from an [`std::io::Read`] source.
+///
+/// Large byte strings (e.g. the `pieces` of a metainfo or the `v` value of a
+/// BEP 46 item) can be copied to another destination (e.g. a file) without
+/// holding the entire contents in memory.
+///
+/// ```rust
+/// use bt_bencode::read::ByteStrReader;
+/// use std::io::Read;
+///
+/// let mut input: &[u8] = b"4:spami1e";
+/// let mut reader = ByteStrReader::new(&mut input)?;
+/// assert_eq!(reader.len(), 4);
+///
+/// let mut contents = Vec::new();
+/// reader.read_to_end(&mut contents)?;
+/// assert_eq!(contents, b"spam");
+///
+/// // The source is positioned after the byte string.
+/// assert_eq!(input, b"i1e");
+/// # Ok::<(), bt_bencode::Error>(())
+/// ```
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct ByteStrReader<R> {
+    reader: R,
+    len: usize,
+    remaining: usize,
+}
+
+#[cfg(feature = "std")]
+impl<R> ByteStrReader<R>
+where
+    R: io::Read,
+{
+    /// Reads the length prefix of a byte string from the source.
+    ///
+    /// The source should be positioned at the start of the byte string's
+    /// length. Bytes are read one at a time until the `:` separator.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the length prefix is not valid or if there is
+    /// an IO error.
+    pub fn new(mut reader: R) -> Result<Self> {
+        let mut prefix = LenPrefix::default();
+        loop {
+            if let Some(len) = prefix.push(read_byte(&mut reader)?)? {
+                return Ok(Self {
+                    reader,
+                    len,
+                    remaining: len,
+                });
+            }
+        }
+    }
+
+    /// Finds a byte string field by its path of dictionary keys and reads
+    /// its length prefix.
+    ///
+    /// The source should be positioned at the start of the outermost
+    /// dictionary. The values before the field are read and discarded without
+    /// being buffered, so a large field (e.g. `info.pieces`) can be copied
+    /// from a file or socket in place. Bytes are read from the source one at
+    /// a time, so a buffered reader should be used.
+    ///
+    /// ```rust
+    /// use bt_bencode::read::ByteStrReader;
+    /// use std::io::Read;
+    ///
+    /// let mut input: &[u8] = b"d4:infod6:lengthi4e6:pieces4:\x01\x02\x03\x04e1:xi1ee";
+    /// let mut reader = ByteStrReader::at_path(&mut input, &[b"info", b"pieces"])?;
+    ///
+    /// let mut pieces = Vec::new();
+    /// reader.read_to_end(&mut pieces)?;
+    /// assert_eq!(pieces, [1, 2, 3, 4]);
+    ///
+    /// // The source is positioned after the field's value.
+    /// assert_eq!(input, b"e1:xi1ee");
+    /// # Ok::<(), bt_bencode::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if a key in the path is not found, if a value
+    /// on the path is not a dictionary, if the field is not a byte string, if
+    /// the skipped data is not valid, or if there is an IO error.
+    pub fn at_path(mut reader: R, path: &[&[u8]]) -> Result<Self> {
+        let mut seeker = FieldSeeker::new(path);
+        let mut next = seeker.start();
+        loop {
+            next = match next {
+                Seek::Byte => seeker.byte(read_byte(&mut reader)?)?,
+                Seek::Discard(len) => {
+                    let len = u64::try_from(len).map_err(|_| Error::InvalidByteStrLen)?;
+                    let n = io::copy(&mut (&mut reader).take(len), &mut io::sink())?;
+                    if n != len {
+                        return Err(Error::EofWhileParsingValue);
+                    }
+                    Seek::Byte
+                }
+                Seek::Key(len) => {
+                    let mut key = vec![0; len];
+                    reader
+                        .read_exact(&mut key)
+                        .map_err(|err| match err.kind() {
+                            io::ErrorKind::UnexpectedEof => Error::EofWhileParsingValue,
+                            _ => Error::IoError(err),
+                        })?;
+                    seeker.key(&key)
+                }
+                Seek::Found => return Self::new(reader),
+            };
+        }
+    }
+}
+
+/// Reads a single byte from the source.
+#[cfg(feature = "std")]
+fn read_byte<R>(reader: &mut R) -> Result<u8>
+where
+    R: io::Read,
+{
+    let mut byte = [0];
+    loop {
+        match reader.read(&mut byte) {
+            Ok(0) => return Err(Error::EofWhileParsingValue),
+            Ok(_) => return Ok(byte[0]),
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(Error::IoError(err)),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R> ByteStrReader<R> {
+    /// Returns the total length of the byte string contents.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the byte string is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of content bytes which have not been read yet.
+    #[must_use]
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+
+    /// Returns the inner source.
+    ///
+    /// If the contents were not read entirely, the source is positioned in
+    /// the middle of the byte string.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R> io::Read for ByteStrReader<R>
+where
+    R: io::Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.remaining == 0 || buf.is_empty() {
+            return Ok(0);
+        }
+
+        let max = core::cmp::min(buf.len(), self.remaining);
+        let n = self.reader.read(&mut buf[..max])?;
+        if n == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        self.remaining -= n;
+        Ok(n)
+    }
+}
+
+/// Reads the contents of a single byte string from an [`AsyncRead`][tokio::io::AsyncRead] source.
+///
+/// See [`ByteStrReader`] for the synchronous version.
+#[cfg(feature = "tokio")]
+#[derive(Debug)]
+pub struct AsyncByteStrReader<R> {
+    reader: R,
+    len: usize,
+    remaining: usize,
+}
+
+#[cfg(feature = "tokio")]
+impl<R> AsyncByteStrReader<R>
+where
+    R: tokio::io::AsyncRead + Unpin,
+{
+    /// Reads the length prefix of a byte string from the source.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the length prefix is not valid or if there is
+    /// an IO error.
+    pub async fn new(mut reader: R) -> Result<Self> {
+        let mut prefix = LenPrefix::default();
+        loop {
+            if let Some(len) = prefix.push(read_byte_async(&mut reader).await?)? {
+                return Ok(Self {
+                    reader,
+                    len,
+                    remaining: len,
+                });
+            }
+        }
+    }
+
+    /// Finds a byte string field by its path of dictionary keys and reads
+    /// its length prefix.
+    ///
+    /// See [`ByteStrReader::at_path()`].
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if a key in the path is not found, if a value
+    /// on the path is not a dictionary, if the field is not a byte string, if
+    /// the skipped data is not valid, or if there is an IO error.
+    pub async fn at_path(mut reader: R, path: &[&[u8]]) -> Result<Self> {
+        use tokio::io::AsyncReadExt;
+
+        let mut seeker = FieldSeeker::new(path);
+        let mut next = seeker.start();
+        loop {
+            next = match next {
+                Seek::Byte => seeker.byte(read_byte_async(&mut reader).await?)?,
+                Seek::Discard(len) => {
+                    let len = u64::try_from(len).map_err(|_| Error::InvalidByteStrLen)?;
+                    let n = tokio::io::copy(&mut (&mut reader).take(len), &mut tokio::io::sink())
+                        .await?;
+                    if n != len {
+                        return Err(Error::EofWhileParsingValue);
+                    }
+                    Seek::Byte
+                }
+                Seek::Key(len) => {
+                    let mut key = vec![0; len];
+                    reader
+                        .read_exact(&mut key)
+                        .await
+                        .map_err(|err| match err.kind() {
+                            io::ErrorKind::UnexpectedEof => Error::EofWhileParsingValue,
+                            _ => Error::IoError(err),
+                        })?;
+                    seeker.key(&key)
+                }
+                Seek::Found => return Self::new(reader).await,
+            };
+        }
+    }
+}
+
+/// Reads a single byte from the source.
+#[cfg(feature = "tokio")]
+async fn read_byte_async<R>(reader: &mut R) -> Result<u8>
+where
+    R: tokio::io::AsyncRead + Unpin,
+{
+    use tokio::io::AsyncReadExt;
+
+    let mut byte = [0];
+    if reader.read(&mut byte).await.map_err(Error::IoError)? == 0 {
+        return Err(Error::EofWhileParsingValue);
+    }
+    Ok(byte[0])
+}
+
+#[cfg(feature = "tokio")]
+impl<R> AsyncByteStrReader<R> {
+    /// Returns the total length of the byte string contents.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the byte string is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of content bytes which have not been read yet.
+    #[must_use]
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+
+    /// Returns the inner source.
+    ///
+    /// If the contents were not read entirely, the source is positioned in
+    /// the middle of the byte string.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<R> tokio::io::AsyncRead for AsyncByteStrReader<R>
+where
+    R: tokio::io::AsyncRead + Unpin,
+{
+    fn poll_read(
+        self: core::pin::Pin<&mut Self>,
+        cx: &mut core::task::Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> core::task::Poll<io::Result<()>> {
+        use core::{pin::Pin, task::Poll};
+
+        let this = self.get_mut();
+        let max = core::cmp::min(buf.remaining(), this.remaining);
+        if max == 0 {
+            return Poll::Ready(Ok(()));
+        }
+
+        let mut limited = tokio::io::ReadBuf::new(buf.initialize_unfilled_to(max));
+        match Pin::new(&mut this.reader).poll_read(cx, &mut limited) {
+            Poll::Ready(Ok(())) => {
+                let n = limited.filled().len();
+                if n == 0 {
+                    return Poll::Ready(Err(io::ErrorKind::UnexpectedEof.into()));
+                }
+                buf.advance(n);
+                this.remaining -= n;
+                Poll::Ready(Ok(()))
+            }
+            result => result,
+        }
+    }
+}
+
+/// Returns the number of leading ASCII digits in the slice.
+///
+/// Eight bytes are checked at a time before falling back to checking
+/// individual bytes.
+#[inline]
+fn leading_digits(bytes: &[u8]) -> usize {
+    const HIGH_NIBBLES: u64 = 0xF0F0_F0F0_F0F0_F0F0;
+    const ZEROS: u64 = 0x3030_3030_3030_3030;
+    const SIXES: u64 = 0x0606_0606_0606_0606;
+
+    let mut idx = 0;
+    while let Some(chunk) = bytes.get(idx..idx + 8) {
+        let mut word = [0; 8];
+        word.copy_from_slice(chunk);
+        let word = u64::from_le_bytes(word);
+
+        // Every byte must be in `0x30..=0x3F` and stay in the range after
+        // adding 6 (which excludes `:` through `?`).
+        if word & HIGH_NIBBLES != ZEROS || word.wrapping_add(SIXES) & HIGH_NIBBLES != ZEROS {
+            break;
+        }
+        idx += 8;
+    }
+
+    idx + bytes[idx..]
+        .iter()
+        .take_while(|b| b.is_ascii_digit())
+        .count()
+}
+
+/// Parses the ASCII digits of a byte string length.
+#[inline]
+fn parse_len(digits: &[u8]) -> Result<usize> {
+    let len = digits.iter().try_fold(0usize, |len, d| {
+        len.checked_mul(10)?.checked_add(usize::from(d - b'0'))
+    });
+    match len {
+        Some(len) if !digits.is_empty() => Ok(len),
+        // Defer to the standard library for a descriptive error
+        _ => Ok(core::str::from_utf8(digits)?.parse()?),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_leading_digits() {
+        assert_eq!(leading_digits(b""), 0);
+        assert_eq!(leading_digits(b"e"), 0);
+        assert_eq!(leading_digits(b"1234e"), 4);
+        assert_eq!(leading_digits(b"12345678"), 8);
+        assert_eq!(leading_digits(b"123456789:"), 9);
+        assert_eq!(leading_digits(b"1234567:90"), 7);
+        assert_eq!(leading_digits(b"01234567890123456789e"), 20);
+        assert_eq!(leading_digits(b"0123/567"), 4);
+        assert_eq!(leading_digits(b"0123?567"), 4);
+    }
+
+    #[test]
+    fn test_parse_len() -> Result<()> {
+        assert_eq!(parse_len(b"0")?, 0);
+        assert_eq!(parse_len(b"0042")?, 42);
+        assert!(matches!(parse_len(b""), Err(Error::ParseIntError(_))));
+        assert!(matches!(
+            parse_len(b"99999999999999999999999"),
+            Err(Error::ParseIntError(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_slice_read_integer() -> Result<()> {
+        let mut buf = Vec::new();
+
+        let mut read = SliceRead::new(b"-1234567890123e4:spam");
+        assert_eq!(&*read.parse_integer(&mut buf)?, "-1234567890123");
+        assert_eq!(read.byte_offset(), 15);
+        assert_eq!(&*read.parse_byte_str(&mut buf)?, b"spam");
+
+        assert!(matches!(
+            SliceRead::new(b"12a4e").parse_integer(&mut buf),
+            Err(Error::InvalidInteger)
+        ));
+        assert!(matches!(
+            SliceRead::new(b"e").parse_integer(&mut buf),
+            Err(Error::InvalidInteger)
+        ));
+        assert!(matches!(
+            SliceRead::new(b"123456789").parse_integer(&mut buf),
+            Err(Error::EofWhileParsingValue)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_slice_read_byte_str() -> Result<()> {
+        let mut buf = Vec::new();
+
+        let mut read = SliceRead::new(b"10:0123456789i1e");
+        assert_eq!(&*read.parse_byte_str(&mut buf)?, b"0123456789");
+        assert_eq!(&*read.parse_raw_integer(&mut buf)?, b"i1e");
+
+        assert!(matches!(
+            SliceRead::new(b"4:spa").parse_byte_str(&mut buf),
+            Err(Error::EofWhileParsingValue)
+        ));
+        assert!(matches!(
+            SliceRead::new(b"18446744073709551615:a").parse_byte_str(&mut buf),
+            Err(Error::EofWhileParsingValue)
+        ));
+        assert!(matches!(
+            SliceRead::new(b"4a:spam").parse_byte_str(&mut buf),
+            Err(Error::InvalidByteStrLen)
+        ));
+        assert!(matches!(
+            SliceRead::new(b"12345").parse_raw_byte_str(&mut buf),
+            Err(Error::EofWhileParsingValue)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_slice_read_item_count_hint() {
+        let mut read = SliceRead::new(b"li1e3:abcld1:ai2eeee");
+        read.next();
+        assert_eq!(read.item_count_hint(), Some(3));
+        assert_eq!(read.byte_offset(), 1);
+
+        assert_eq!(SliceRead::new(b"e").item_count_hint(), Some(0));
+        assert_eq!(SliceRead::new(b"i1e").item_count_hint(), None);
+        assert_eq!(SliceRead::new(b"i1ex").item_count_hint(), None);
+        assert_eq!(SliceRead::new(b"d1:ali1eee1:be").item_count_hint(), Some(2));
+        assert_eq!(SliceRead::new(b"1x").item_count_hint(), None);
+        assert_eq!(SliceRead::new(b"5:abe").item_count_hint(), None);
+
+        let mut long = Vec::new();
+        long.extend_from_slice(b"2000:");
+        long.resize(long.len() + 2000, b'a');
+        long.push(b'e');
+        assert_eq!(SliceRead::new(&long).item_count_hint(), None);
+
+        // 21 integers and the end of the list fill the lookahead exactly.
+        let mut short = b"i1e".repeat(21);
+        short.push(b'e');
+        assert_eq!(SliceRead::new(&short).item_count_hint(), Some(21));
+        short.extend_from_slice(b"i1e");
+        short.rotate_right(3);
+        assert_eq!(SliceRead::new(&short).item_count_hint(), None);
+
+        let nested = [b'l'; 100_000];
+        let mut read = SliceRead::new(&nested);
+        for _ in 0..nested.len() {
+            assert_eq!(read.item_count_hint(), None);
+            read.next();
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_byte_str_reader() -> Result<()> {
+        let mut input: &[u8] = b"10:0123456789e";
+        let mut reader = ByteStrReader::new(&mut input)?;
+        assert_eq!(reader.len(), 10);
+
+        let mut buf = [0; 4];
+        assert_eq!(reader.read(&mut buf)?, 4);
+        assert_eq!(&buf, b"0123");
+        assert_eq!(reader.remaining(), 6);
+
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest)?;
+        assert_eq!(rest, b"456789");
+        assert_eq!(reader.read(&mut buf)?, 0);
+        assert_eq!(input, b"e");
+
+        let mut reader = ByteStrReader::new(&b"5:abc"[..])?;
+        let mut rest = Vec::new();
+        assert!(reader.read_to_end(&mut rest).is_err());
+
+        assert!(matches!(
+            ByteStrReader::new(&b"i1e"[..]),
+            Err(Error::InvalidByteStrLen)
+        ));
+        assert!(matches!(
+            ByteStrReader::new(&b"12"[..]),
+            Err(Error::EofWhileParsingValue)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_byte_str_reader_at_path() -> Result<()> {
+        let mut input: &[u8] =
+            b"d1:ali-1el0:d1:ai1eeee1:bd3:key5:value1:pi7e1:qd6:pieces1:xe6:pieces3:abce1:ci2ee";
+        let mut reader = ByteStrReader::at_path(&mut input, &[b"b", b"pieces"])?;
+        let mut contents = Vec::new();
+        reader.read_to_end(&mut contents)?;
+        assert_eq!(contents, b"abc");
+        assert_eq!(input, b"e1:ci2ee");
+
+        let mut reader = ByteStrReader::at_path(&b"3:abc"[..], &[])?;
+        assert_eq!(reader.len(), 3);
+        let mut contents = Vec::new();
+        reader.read_to_end(&mut contents)?;
+        assert_eq!(contents, b"abc");
+
+        let result = ByteStrReader::at_path(&b"d1:ai1ee"[..], &[b"b"]);
+        assert_eq!(result.unwrap_err().to_string(), "missing field `b`");
+        assert!(matches!(
+            ByteStrReader::at_path(&b"d1:ai1ee"[..], &[b"a"]),
+            Err(Error::InvalidByteStrLen)
+        ));
+        assert!(matches!(
+            ByteStrReader::at_path(&b"d1:ai1ee"[..], &[b"a", b"b"]),
+            Err(Error::InvalidDict)
+        ));
+        assert!(matches!(
+            ByteStrReader::at_path(&b"d1:a5:abc"[..], &[b"b"]),
+            Err(Error::EofWhileParsingValue)
+        ));
+        assert!(matches!(
+            ByteStrReader::at_path(&b"di1e1:ae"[..], &[b"b"]),
+            Err(Error::KeyMustBeAByteStr)
+        ));
+        assert!(matches!(
+            ByteStrReader::at_path(&b"d1:ai1xe"[..], &[b"b"]),
+            Err(Error::InvalidInteger)
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "tokio")]
+    async fn test_async_byte_str_reader() -> Result<()> {
+        use tokio::io::AsyncReadExt;
+
+        let mut input: &[u8] = b"4:spam4:eggs";
+        let mut reader = AsyncByteStrReader::new(&mut input).await?;
+        let mut contents = Vec::new();
+        reader.read_to_end(&mut contents).await?;
+        assert_eq!(contents, b"spam");
+        assert_eq!(input, b"4:eggs");
+
+        let mut reader = AsyncByteStrReader::new(&b"5:abc"[..]).await?;
+        let mut contents = Vec::new();
+        assert!(reader.read_to_end(&mut contents).await.is_err());
+
+        let mut input: &[u8] = b"d1:a5:abcde1:bd1:c3:xyzee";
+        let mut reader = AsyncByteStrReader::at_path(&mut input, &[b"b", b"c"]).await?;
+        let mut contents = Vec::new();
+        reader.read_to_end(&mut contents).await?;
+        assert_eq!(contents, b"xyz");
+        assert_eq!(input, b"ee");
+
+        assert!(matches!(
+            AsyncByteStrReader::at_path(&b"d1:ali1ee"[..], &[b"b"]).await,
+            Err(Error::EofWhileParsingValue)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_io_read_byte_str() -> Result<()> {
+        let mut buf = Vec::new();
+
+        let mut read = IoRead::new(&b"4:spam5:eggs"[..]);
+        assert_eq!(read.peek().unwrap()?, b'4');
+        assert_eq!(&*read.parse_byte_str(&mut buf)?, b"spam");
+        assert_eq!(read.byte_offset(), 6);
+
+        buf.clear();
+        assert!(matches!(
+            read.parse_byte_str(&mut buf),
+            Err(Error::EofWhileParsingValue)
+        ));
+        assert_eq!(read.byte_offset(), 12);
+
+        buf.clear();
+        let mut read = IoRead::new(&b"li1e0:e"[..]);
+        assert_eq!(&*read.parse_raw_list(&mut buf)?, b"li1e0:e");
+
+        buf.clear();
+        let mut read = IoRead::new(&b"999999999:a"[..]);
+        assert!(matches!(
+            read.parse_byte_str(&mut buf),
+            Err(Error::EofWhileParsingValue)
+        ));
+        assert!(buf.capacity() < 1024);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "bytes")]
+    fn test_buf_read() -> Result<()> {
+        let mut buf = Vec::new();
+
+        let mut read = BufRead::new(bytes::Buf::chain(&b"4:sp"[..], &b"am3:eg"[..]));
+        assert_eq!(read.peek().unwrap()?, b'4');
+        assert_eq!(&*read.parse_byte_str(&mut buf)?, b"spam");
+        assert_eq!(read.byte_offset(), 6);
+        assert_eq!(read.peek().unwrap()?, b'3');
+
+        buf.clear();
+        assert!(matches!(
+            read.parse_byte_str(&mut buf),
+            Err(Error::EofWhileParsingValue)
+        ));
+        assert_eq!(read.byte_offset(), 10);
+        assert!(read.next().is_none());
+
+        buf.clear();
+        let mut read = BufRead::new(bytes::Buf::chain(
+            bytes::Buf::chain(&b"li1"[..], &b"e2:"[..]),
+            &b"abe"[..],
+        ));
+        assert_eq!(&*read.parse_raw_list(&mut buf)?, b"li1e2:abe");
+        assert!(read.peek().is_none());
+        Ok(())
+    }
+}
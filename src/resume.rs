@@ -0,0 +1,23 @@
+//! Types for fast-resume and session files.
+//!
+//! BitTorrent clients save the state of each torrent (which pieces have been
+//! downloaded, file priorities, known peers, transfer statistics) in bencoded
+//! files so downloads can continue after a restart.
+//!
+//! [`ResumeData`] models the libtorrent resume file format. Every field is
+//! optional or has a default so files written by different libtorrent
+//! versions can be read. [`QbtFastResume`] adds qBittorrent's fields, and
+//! [`RtorrentSession`] and [`RtorrentResume`] model rtorrent's session files.
+//! [`SessionTorrent`] is a unified view of the state saved by any of them.
+
+mod libtorrent;
+mod qbittorrent;
+mod rtorrent;
+mod session;
+
+pub use libtorrent::{ResumeData, UnfinishedPiece};
+pub use qbittorrent::{QbtFastResume, QbtFields};
+pub use rtorrent::{
+    RtorrentBitfield, RtorrentFile, RtorrentResume, RtorrentSession, RtorrentTracker,
+};
+pub use session::{SessionSource, SessionTorrent};
@@ -0,0 +1,339 @@
+//! The libtorrent resume file format.
+
+use crate::{
+    compact,
+    error::Result,
+    id::Id20,
+    serde_helpers::bool_from_int::{self, is_false},
+    torrent::UrlList,
+    value::Value,
+};
+use core::convert::TryFrom;
+use serde_bytes::ByteBuf;
+use serde_derive::{Deserialize, Serialize};
+use std::{
+    net::{SocketAddrV4, SocketAddrV6},
+    string::String,
+    vec::Vec,
+};
+
+/// The value of the `file-format` field in libtorrent resume files.
+const FILE_FORMAT: &str = "libtorrent resume file";
+
+fn is_zero(value: &i64) -> bool {
+    *value == 0
+}
+
+/// A piece which has only been partially downloaded.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnfinishedPiece {
+    /// The index of the piece.
+    pub piece: u32,
+    /// A bitmask of the 16 KiB blocks in the piece which have been downloaded.
+    pub bitmask: ByteBuf,
+}
+
+/// A libtorrent resume file.
+///
+/// ```rust
+/// use bt_bencode::resume::ResumeData;
+///
+/// let input = b"d11:file-format22:libtorrent resume file12:file-versioni1e6:pausedi1e6:pieces3:\x01\x00\x0114:total_uploadedi1024ee";
+/// let resume = ResumeData::from_slice(input)?;
+///
+/// assert!(resume.paused);
+/// assert!(resume.has_piece(2));
+/// assert!(!resume.has_piece(1));
+/// assert_eq!(resume.completed_pieces(), 2);
+/// assert_eq!(resume.total_uploaded, 1024);
+/// assert_eq!(resume.to_vec()?, &input[..]);
+/// # Ok::<(), bt_bencode::Error>(())
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResumeData {
+    /// The file format, which is `libtorrent resume file`.
+    #[serde(
+        rename = "file-format",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub file_format: Option<String>,
+    /// The version of the file format.
+    #[serde(
+        rename = "file-version",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub file_version: Option<i64>,
+    /// The version of libtorrent which wrote the file.
+    #[serde(
+        rename = "libtorrent-version",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub libtorrent_version: Option<String>,
+    /// The v1 info hash.
+    #[serde(rename = "info-hash", default, skip_serializing_if = "Option::is_none")]
+    pub info_hash: Option<ByteBuf>,
+    /// The v2 info hash.
+    #[serde(
+        rename = "info-hash2",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub info_hash2: Option<ByteBuf>,
+    /// The torrent's name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// The directory the torrent is saved in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub save_path: Option<String>,
+    /// The embedded info dictionary, if the torrent's metadata is stored in the resume file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub info: Option<Value>,
+
+    /// One byte per piece where bit 0 is set if the piece has been downloaded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pieces: Option<ByteBuf>,
+    /// One byte per piece with the piece's priority.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub piece_priority: Option<ByteBuf>,
+    /// The priority of each file.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub file_priority: Vec<i64>,
+    /// Paths of files which have been renamed, indexed by file.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mapped_files: Vec<String>,
+    /// The partially downloaded pieces.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unfinished: Vec<UnfinishedPiece>,
+
+    /// The tracker URLs in tiers.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trackers: Vec<Vec<String>>,
+    /// The [BEP 19][bep_0019] web seeds.
+    ///
+    /// [bep_0019]: https://www.bittorrent.org/beps/bep_0019.html
+    #[serde(rename = "url-list", default, skip_serializing_if = "Option::is_none")]
+    pub url_list: Option<UrlList>,
+    /// The [BEP 17][bep_0017] HTTP seeds.
+    ///
+    /// [bep_0017]: https://www.bittorrent.org/beps/bep_0017.html
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub httpseeds: Option<UrlList>,
+    /// Known IPv4 peers.
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        with = "compact::peers"
+    )]
+    pub peers: Vec<SocketAddrV4>,
+    /// Known IPv6 peers.
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        with = "compact::peers6"
+    )]
+    pub peers6: Vec<SocketAddrV6>,
+    /// Banned IPv4 peers.
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        with = "compact::peers"
+    )]
+    pub banned_peers: Vec<SocketAddrV4>,
+    /// Banned IPv6 peers.
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        with = "compact::peers6"
+    )]
+    pub banned_peers6: Vec<SocketAddrV6>,
+
+    /// The total number of bytes uploaded.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub total_uploaded: i64,
+    /// The total number of bytes downloaded.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub total_downloaded: i64,
+    /// The number of seconds the torrent has been active.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub active_time: i64,
+    /// The number of seconds the torrent has been finished.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub finished_time: i64,
+    /// The number of seconds the torrent has been seeding.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub seeding_time: i64,
+    /// When the torrent was added, in seconds since the Unix epoch.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub added_time: i64,
+    /// When the torrent completed, in seconds since the Unix epoch.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub completed_time: i64,
+    /// When a payload byte was last downloaded, in seconds since the Unix epoch.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub last_download: i64,
+    /// When a payload byte was last uploaded, in seconds since the Unix epoch.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub last_upload: i64,
+    /// The number of seeds reported by the tracker.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub num_complete: Option<i64>,
+    /// The number of peers reported by the tracker.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub num_incomplete: Option<i64>,
+    /// The number of completed downloads reported by the tracker.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub num_downloaded: Option<i64>,
+
+    /// The upload rate limit in bytes per second (-1 is unlimited).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upload_rate_limit: Option<i64>,
+    /// The download rate limit in bytes per second (-1 is unlimited).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub download_rate_limit: Option<i64>,
+    /// The maximum number of connections (-1 is unlimited).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_connections: Option<i64>,
+    /// The maximum number of unchoked peers (-1 is unlimited).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_uploads: Option<i64>,
+
+    /// Set if the torrent is paused.
+    #[serde(default, skip_serializing_if = "is_false", with = "bool_from_int")]
+    pub paused: bool,
+    /// Set if the torrent is automatically managed.
+    #[serde(default, skip_serializing_if = "is_false", with = "bool_from_int")]
+    pub auto_managed: bool,
+    /// Set if the torrent is assumed to be complete without checking.
+    #[serde(default, skip_serializing_if = "is_false", with = "bool_from_int")]
+    pub seed_mode: bool,
+    /// Set if super seeding is enabled.
+    #[serde(default, skip_serializing_if = "is_false", with = "bool_from_int")]
+    pub super_seeding: bool,
+    /// Set if pieces are downloaded in order.
+    #[serde(default, skip_serializing_if = "is_false", with = "bool_from_int")]
+    pub sequential_download: bool,
+    /// Set if the torrent stops once it has been checked.
+    #[serde(default, skip_serializing_if = "is_false", with = "bool_from_int")]
+    pub stop_when_ready: bool,
+}
+
+impl ResumeData {
+    /// Constructs resume data with the libtorrent file format and version fields set.
+    #[must_use]
+    pub fn new() -> Self {
+        ResumeData {
+            file_format: Some(String::from(FILE_FORMAT)),
+            file_version: Some(1),
+            ..ResumeData::default()
+        }
+    }
+
+    /// Returns the v1 info hash if it is 20 bytes long.
+    #[must_use]
+    pub fn info_hash_v1(&self) -> Option<Id20> {
+        self.info_hash
+            .as_ref()
+            .and_then(|h| Id20::try_from(h.as_slice()).ok())
+    }
+
+    /// Returns true if the piece has been downloaded.
+    #[must_use]
+    pub fn has_piece(&self, index: usize) -> bool {
+        self.pieces
+            .as_ref()
+            .and_then(|pieces| pieces.get(index))
+            .map_or(false, |b| b & 1 != 0)
+    }
+
+    /// Returns the number of pieces which have been downloaded.
+    #[must_use]
+    pub fn completed_pieces(&self) -> usize {
+        self.pieces
+            .as_ref()
+            .map_or(0, |pieces| pieces.iter().filter(|b| *b & 1 != 0).count())
+    }
+
+    /// Sets the `pieces` field from a flag for each piece.
+    pub fn set_pieces<I>(&mut self, have: I)
+    where
+        I: IntoIterator<Item = bool>,
+    {
+        self.pieces = Some(ByteBuf::from(
+            have.into_iter().map(u8::from).collect::<Vec<_>>(),
+        ));
+    }
+
+    /// Returns an iterator over the tracker URLs in tier order.
+    pub fn tracker_urls(&self) -> impl Iterator<Item = &str> {
+        self.trackers
+            .iter()
+            .flat_map(|tier| tier.iter().map(String::as_str))
+    }
+
+    /// Deserializes resume data with [`from_slice()`][crate::from_slice].
+    pub fn from_slice(s: &[u8]) -> Result<Self> {
+        crate::from_slice(s)
+    }
+
+    /// Serializes the resume data with [`to_vec()`][crate::to_vec].
+    pub fn to_vec(&self) -> Result<Vec<u8>> {
+        crate::to_vec(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() -> Result<()> {
+        let mut resume = ResumeData::new();
+        resume.info_hash = Some(ByteBuf::from(vec![0xab; 20]));
+        resume.name = Some(String::from("spam"));
+        resume.set_pieces(vec![true, false, true, true]);
+        resume.file_priority = vec![4, 0];
+        resume.trackers = vec![
+            vec![String::from("http://a/announce")],
+            vec![String::from("udp://b:80")],
+        ];
+        resume.url_list = Some(UrlList::One(String::from("http://seed/")));
+        resume.peers = vec!["192.0.2.1:6881".parse().unwrap()];
+        resume.unfinished = vec![UnfinishedPiece {
+            piece: 1,
+            bitmask: ByteBuf::from(vec![0x80]),
+        }];
+        resume.upload_rate_limit = Some(-1);
+        resume.auto_managed = true;
+
+        let decoded = ResumeData::from_slice(&resume.to_vec()?)?;
+        assert_eq!(decoded, resume);
+        assert_eq!(decoded.completed_pieces(), 3);
+        assert_eq!(decoded.info_hash_v1(), Some(Id20::new([0xab; 20])));
+        assert_eq!(
+            decoded.tracker_urls().collect::<Vec<_>>(),
+            vec!["http://a/announce", "udp://b:80"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_tolerant() -> Result<()> {
+        // Older versions without the format fields, with unknown fields and a
+        // truncated info hash.
+        let resume = ResumeData::from_slice(
+            b"d9:info-hash3:abc10:allocation6:sparse6:pausedi0e8:url-listl6:http:/ee",
+        )?;
+        assert_eq!(resume.file_format, None);
+        assert_eq!(resume.info_hash_v1(), None);
+        assert!(!resume.paused);
+        assert!(!resume.has_piece(0));
+        assert_eq!(resume.url_list.map(|u| u.as_slice().len()), Some(1));
+
+        assert!(ResumeData::from_slice(b"d6:pausedle").is_err());
+        assert!(ResumeData::from_slice(b"i1e").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,152 @@
+//! qBittorrent fast-resume files.
+
+use super::ResumeData;
+use crate::{
+    error::{Error, Result},
+    serde_helpers::bool_from_int::{self, is_false},
+    value::Value,
+};
+use serde_derive::{Deserialize, Serialize};
+use std::{string::String, vec::Vec};
+
+/// The fields qBittorrent adds to a libtorrent resume file.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QbtFields {
+    /// The torrent's category.
+    #[serde(
+        rename = "qBt-category",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub category: Option<String>,
+    /// The torrent's tags.
+    #[serde(rename = "qBt-tags", default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// The name shown in the user interface if it differs from the torrent's name.
+    #[serde(rename = "qBt-name", default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// The save path.
+    #[serde(
+        rename = "qBt-savePath",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub save_path: Option<String>,
+    /// The path for incomplete downloads.
+    #[serde(
+        rename = "qBt-downloadPath",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub download_path: Option<String>,
+    /// The share ratio limit multiplied by 1000 (-2000 uses the global limit, -1000 is unlimited).
+    #[serde(
+        rename = "qBt-ratioLimit",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub ratio_limit: Option<i64>,
+    /// The seeding time limit in minutes (-2 uses the global limit, -1 is unlimited).
+    #[serde(
+        rename = "qBt-seedingTimeLimit",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub seeding_time_limit: Option<i64>,
+    /// The position in the download queue.
+    #[serde(
+        rename = "qBt-queuePosition",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub queue_position: Option<i64>,
+    /// Set if the first and last pieces of each file are downloaded first.
+    #[serde(
+        rename = "qBt-firstLastPiecePriority",
+        default,
+        skip_serializing_if = "is_false",
+        with = "bool_from_int"
+    )]
+    pub first_last_piece_priority: bool,
+    /// The content layout (`Original`, `Subfolder`, or `NoSubfolder`).
+    #[serde(
+        rename = "qBt-contentLayout",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub content_layout: Option<String>,
+}
+
+/// A qBittorrent `.fastresume` file.
+///
+/// The file is a libtorrent resume file with additional `qBt-` fields. The
+/// torrent's metainfo is stored in a separate `.torrent` file with the same
+/// name.
+///
+/// ```rust
+/// use bt_bencode::resume::QbtFastResume;
+///
+/// let input = b"d6:pausedi1e12:qBt-category5:linux8:qBt-tagsl3:isoee";
+/// let fastresume = QbtFastResume::from_slice(input)?;
+///
+/// assert!(fastresume.resume.paused);
+/// assert_eq!(fastresume.qbt.category.as_deref(), Some("linux"));
+/// assert_eq!(fastresume.to_vec()?, &input[..]);
+/// # Ok::<(), bt_bencode::Error>(())
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct QbtFastResume {
+    /// The libtorrent resume data.
+    pub resume: ResumeData,
+    /// The qBittorrent fields.
+    pub qbt: QbtFields,
+}
+
+impl QbtFastResume {
+    /// Deserializes a fast-resume file from a slice of bytes.
+    ///
+    /// # Errors
+    ///
+    /// Deserialization can fail if the data is not a dictionary or if a field has an invalid type.
+    pub fn from_slice(s: &[u8]) -> Result<Self> {
+        Ok(QbtFastResume {
+            resume: crate::from_slice(s)?,
+            qbt: crate::from_slice(s)?,
+        })
+    }
+
+    /// Serializes the fast-resume file with [`to_vec()`][crate::to_vec].
+    pub fn to_vec(&self) -> Result<Vec<u8>> {
+        let mut value = crate::to_value(&self.resume)?;
+        let dict = value
+            .as_dict_mut()
+            .ok_or_else(|| Error::Serialize(String::from("resume data is not a dictionary")))?;
+        if let Value::Dict(qbt) = crate::to_value(&self.qbt)? {
+            dict.extend(qbt);
+        }
+        crate::to_vec(&value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() -> Result<()> {
+        let mut fastresume = QbtFastResume::default();
+        fastresume.resume.total_uploaded = 5;
+        fastresume.resume.save_path = Some(String::from("/downloads"));
+        fastresume.qbt.tags = vec![String::from("a"), String::from("b")];
+        fastresume.qbt.ratio_limit = Some(-2000);
+        fastresume.qbt.first_last_piece_priority = true;
+
+        let encoded = fastresume.to_vec()?;
+        assert_eq!(
+            encoded,
+            &b"d26:qBt-firstLastPiecePriorityi1e14:qBt-ratioLimiti-2000e8:qBt-tagsl1:a1:be9:save_path10:/downloads14:total_uploadedi5ee"[..]
+        );
+        assert_eq!(QbtFastResume::from_slice(&encoded)?, fastresume);
+        Ok(())
+    }
+}
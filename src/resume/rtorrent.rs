@@ -0,0 +1,244 @@
+//! rtorrent session files.
+
+use crate::error::Result;
+use core::fmt;
+use serde::{
+    de::{self, Deserializer, Visitor},
+    ser::Serializer,
+};
+use serde_bytes::ByteBuf;
+use serde_derive::{Deserialize, Serialize};
+use std::{collections::BTreeMap, string::String, vec::Vec};
+
+/// An rtorrent `.rtorrent` file with the torrent's session state.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RtorrentSession {
+    /// The download directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub directory: Option<String>,
+    /// The state (1 is started, 0 is stopped).
+    #[serde(default)]
+    pub state: i64,
+    /// Set if the download is complete.
+    #[serde(default)]
+    pub complete: i64,
+    /// The download priority.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<i64>,
+    /// The total number of bytes uploaded.
+    #[serde(default)]
+    pub total_uploaded: i64,
+    /// The total number of bytes downloaded.
+    #[serde(default)]
+    pub total_downloaded: i64,
+    /// The number of chunks which have been downloaded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunks_done: Option<i64>,
+    /// The first custom field, which is used as the label by ruTorrent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom1: Option<String>,
+    /// Additional custom fields.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub custom: BTreeMap<String, String>,
+    /// When the download was started, in seconds since the Unix epoch.
+    #[serde(
+        rename = "timestamp.started",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub timestamp_started: Option<i64>,
+    /// When the download finished, in seconds since the Unix epoch.
+    #[serde(
+        rename = "timestamp.finished",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub timestamp_finished: Option<i64>,
+    /// The path of the `.torrent` file the download is tied to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tied_to_file: Option<String>,
+    /// The views the download is in.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub views: Vec<String>,
+}
+
+impl RtorrentSession {
+    /// Returns true if the download is started.
+    #[must_use]
+    pub fn is_started(&self) -> bool {
+        self.state != 0
+    }
+
+    /// Deserializes a session file with [`from_slice()`][crate::from_slice].
+    pub fn from_slice(s: &[u8]) -> Result<Self> {
+        crate::from_slice(s)
+    }
+
+    /// Serializes the session file with [`to_vec()`][crate::to_vec].
+    pub fn to_vec(&self) -> Result<Vec<u8>> {
+        crate::to_vec(self)
+    }
+}
+
+/// The downloaded pieces in an rtorrent resume file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RtorrentBitfield {
+    /// Every piece has been downloaded; the value is the number of pieces.
+    Complete(u64),
+    /// A bitfield with the most significant bit of the first byte for piece 0.
+    Bits(ByteBuf),
+}
+
+impl RtorrentBitfield {
+    /// Returns true if the piece has been downloaded.
+    #[must_use]
+    pub fn has_piece(&self, index: usize) -> bool {
+        match self {
+            RtorrentBitfield::Complete(count) => (index as u64) < *count,
+            RtorrentBitfield::Bits(bits) => bits
+                .get(index / 8)
+                .map_or(false, |b| b & (0x80 >> (index % 8)) != 0),
+        }
+    }
+}
+
+impl serde::Serialize for RtorrentBitfield {
+    fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            RtorrentBitfield::Complete(count) => serializer.serialize_u64(*count),
+            RtorrentBitfield::Bits(bits) => serializer.serialize_bytes(bits),
+        }
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for RtorrentBitfield {
+    fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct BitfieldVisitor;
+
+        impl<'de> Visitor<'de> for BitfieldVisitor {
+            type Value = RtorrentBitfield;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a piece count or a bitfield")
+            }
+
+            fn visit_u64<E>(self, v: u64) -> core::result::Result<RtorrentBitfield, E> {
+                Ok(RtorrentBitfield::Complete(v))
+            }
+
+            fn visit_i64<E>(self, v: i64) -> core::result::Result<RtorrentBitfield, E>
+            where
+                E: de::Error,
+            {
+                if v < 0 {
+                    return Err(de::Error::invalid_value(de::Unexpected::Signed(v), &self));
+                }
+                Ok(RtorrentBitfield::Complete(v as u64))
+            }
+
+            fn visit_bytes<E>(self, v: &[u8]) -> core::result::Result<RtorrentBitfield, E> {
+                Ok(RtorrentBitfield::Bits(ByteBuf::from(v)))
+            }
+
+            fn visit_str<E>(self, v: &str) -> core::result::Result<RtorrentBitfield, E> {
+                Ok(RtorrentBitfield::Bits(ByteBuf::from(v)))
+            }
+        }
+
+        deserializer.deserialize_any(BitfieldVisitor)
+    }
+}
+
+/// The state of a file in an rtorrent resume file.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RtorrentFile {
+    /// The number of chunks of the file which have been downloaded.
+    #[serde(default)]
+    pub completed: i64,
+    /// The file's modification time, in seconds since the Unix epoch.
+    #[serde(default)]
+    pub mtime: i64,
+    /// The file's priority (0 is off).
+    #[serde(default)]
+    pub priority: i64,
+}
+
+/// The state of a tracker in an rtorrent resume file.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RtorrentTracker {
+    /// Set if the tracker is enabled.
+    #[serde(default)]
+    pub enabled: i64,
+}
+
+/// An rtorrent `.libtorrent_resume` file.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RtorrentResume {
+    /// The downloaded pieces.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bitfield: Option<RtorrentBitfield>,
+    /// The state of each file.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<RtorrentFile>,
+    /// The state of each tracker keyed by URL.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub trackers: BTreeMap<String, RtorrentTracker>,
+}
+
+impl RtorrentResume {
+    /// Deserializes a resume file with [`from_slice()`][crate::from_slice].
+    pub fn from_slice(s: &[u8]) -> Result<Self> {
+        crate::from_slice(s)
+    }
+
+    /// Serializes the resume file with [`to_vec()`][crate::to_vec].
+    pub fn to_vec(&self) -> Result<Vec<u8>> {
+        crate::to_vec(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session() -> Result<()> {
+        let input = b"d8:completei0e6:customd1:x1:ye7:custom14:iso59:directory10:/downloads5:statei1e17:timestamp.startedi1600000000e16:total_downloadedi3e14:total_uploadedi2ee";
+        let session = RtorrentSession::from_slice(input)?;
+        assert!(session.is_started());
+        assert_eq!(session.custom1.as_deref(), Some("iso5"));
+        assert_eq!(session.custom.get("x").map(String::as_str), Some("y"));
+        assert_eq!(session.timestamp_started, Some(1_600_000_000));
+        assert_eq!(RtorrentSession::from_slice(&session.to_vec()?)?, session);
+        Ok(())
+    }
+
+    #[test]
+    fn test_resume() -> Result<()> {
+        let resume = RtorrentResume::from_slice(b"d8:bitfield1:\xa0e")?;
+        let bitfield = resume.bitfield.unwrap();
+        assert!(bitfield.has_piece(0));
+        assert!(!bitfield.has_piece(1));
+        assert!(bitfield.has_piece(2));
+        assert!(!bitfield.has_piece(8));
+
+        let resume = RtorrentResume::from_slice(
+            b"d8:bitfieldi3e5:filesld9:completedi3e5:mtimei1e8:priorityi1eee8:trackersd17:http://a/announced7:enabledi1eeee",
+        )?;
+        assert_eq!(resume.bitfield, Some(RtorrentBitfield::Complete(3)));
+        assert!(resume.bitfield.as_ref().unwrap().has_piece(2));
+        assert!(!resume.bitfield.as_ref().unwrap().has_piece(3));
+        assert_eq!(resume.files[0].completed, 3);
+        assert_eq!(resume.trackers.len(), 1);
+        assert_eq!(RtorrentResume::from_slice(&resume.to_vec()?)?, resume);
+
+        assert!(RtorrentResume::from_slice(b"d8:bitfieldi-1ee").is_err());
+        Ok(())
+    }
+}
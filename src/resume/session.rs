@@ -0,0 +1,316 @@
+//! A unified view of the resume state saved by different clients.
+
+use super::{QbtFastResume, ResumeData, RtorrentResume, RtorrentSession};
+use crate::{error::Result, id::Id20, torrent::MetaInfo};
+use std::{string::String, vec::Vec};
+
+/// The client which wrote a session.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SessionSource {
+    /// A libtorrent resume file.
+    Libtorrent,
+    /// A qBittorrent `.fastresume` file.
+    QBittorrent,
+    /// rtorrent `.rtorrent` and `.libtorrent_resume` files.
+    Rtorrent,
+}
+
+/// The state of a torrent saved by a client.
+///
+/// The fields common to the supported clients are extracted so migration
+/// tools can read one client's session and write another's.
+///
+/// ```rust
+/// use bt_bencode::resume::{SessionSource, SessionTorrent};
+///
+/// let fastresume = b"d6:pausedi1e6:pieces2:\x01\x0012:qBt-category5:linux12:qBt-savePath4:/dl/e";
+/// let session = SessionTorrent::from_qbittorrent(fastresume, None)?;
+///
+/// assert_eq!(session.source, SessionSource::QBittorrent);
+/// assert_eq!(session.label.as_deref(), Some("linux"));
+/// assert_eq!(session.save_path.as_deref(), Some("/dl/"));
+/// assert_eq!(session.pieces, Some(vec![true, false]));
+/// assert!(session.paused);
+/// # Ok::<(), bt_bencode::Error>(())
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct SessionTorrent {
+    /// The client which wrote the session.
+    pub source: SessionSource,
+    /// The v1 info hash.
+    pub info_hash: Option<Id20>,
+    /// The torrent's name.
+    pub name: Option<String>,
+    /// The directory the torrent is saved in.
+    pub save_path: Option<String>,
+    /// The category or label.
+    pub label: Option<String>,
+    /// The tags.
+    pub tags: Vec<String>,
+    /// Set if the torrent is paused or stopped.
+    pub paused: bool,
+    /// A flag for each piece which is set if the piece has been downloaded.
+    pub pieces: Option<Vec<bool>>,
+    /// The total number of bytes uploaded.
+    pub total_uploaded: i64,
+    /// The total number of bytes downloaded.
+    pub total_downloaded: i64,
+    /// When the torrent was added, in seconds since the Unix epoch.
+    pub added_time: Option<i64>,
+    /// The tracker URLs in tiers.
+    pub trackers: Vec<Vec<String>>,
+    /// The torrent's metainfo.
+    pub metainfo: Option<MetaInfo>,
+}
+
+fn non_zero(value: i64) -> Option<i64> {
+    if value == 0 {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+fn owned_tiers(tiers: Vec<Vec<&str>>) -> Vec<Vec<String>> {
+    tiers
+        .into_iter()
+        .map(|tier| tier.into_iter().map(String::from).collect())
+        .collect()
+}
+
+/// Returns the v1 info hash of an encoded metainfo file if it can be computed.
+#[cfg(feature = "sha1")]
+fn metainfo_hash(torrent: &[u8]) -> Option<Id20> {
+    crate::torrent::info_hash_v1(torrent).ok().map(Id20::new)
+}
+
+#[cfg(not(feature = "sha1"))]
+fn metainfo_hash(_torrent: &[u8]) -> Option<Id20> {
+    None
+}
+
+impl SessionTorrent {
+    /// Constructs a session from libtorrent resume data.
+    #[must_use]
+    pub fn from_libtorrent(resume: &ResumeData) -> Self {
+        let mut metainfo = None;
+        if let Some(info) = &resume.info {
+            metainfo = crate::from_value(info.clone()).ok().map(|info| MetaInfo {
+                announce: None,
+                announce_list: None,
+                comment: None,
+                created_by: None,
+                creation_date: None,
+                encoding: None,
+                httpseeds: resume.httpseeds.clone(),
+                info,
+                piece_layers: None,
+                url_list: resume.url_list.clone(),
+            });
+        }
+        SessionTorrent {
+            source: SessionSource::Libtorrent,
+            info_hash: resume.info_hash_v1(),
+            name: resume.name.clone(),
+            save_path: resume.save_path.clone(),
+            label: None,
+            tags: Vec::new(),
+            paused: resume.paused,
+            pieces: resume
+                .pieces
+                .as_ref()
+                .map(|pieces| pieces.iter().map(|b| b & 1 != 0).collect()),
+            total_uploaded: resume.total_uploaded,
+            total_downloaded: resume.total_downloaded,
+            added_time: non_zero(resume.added_time),
+            trackers: resume.trackers.clone(),
+            metainfo,
+        }
+    }
+
+    /// Constructs a session from a qBittorrent `.fastresume` file and its `.torrent` file.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if either file cannot be deserialized.
+    pub fn from_qbittorrent(fastresume: &[u8], torrent: Option<&[u8]>) -> Result<Self> {
+        let fastresume = QbtFastResume::from_slice(fastresume)?;
+        let mut session = SessionTorrent::from_libtorrent(&fastresume.resume);
+        session.source = SessionSource::QBittorrent;
+        session.label = fastresume.qbt.category;
+        session.tags = fastresume.qbt.tags;
+        if fastresume.qbt.name.is_some() {
+            session.name = fastresume.qbt.name;
+        }
+        if fastresume.qbt.save_path.is_some() {
+            session.save_path = fastresume.qbt.save_path;
+        }
+        if let Some(torrent) = torrent {
+            session.set_metainfo(torrent)?;
+        }
+        Ok(session)
+    }
+
+    /// Constructs a session from an rtorrent session directory's files for a torrent.
+    ///
+    /// `torrent` is the `<hash>.torrent` file, `rtorrent` is the
+    /// `<hash>.torrent.rtorrent` file, and `libtorrent_resume` is the
+    /// `<hash>.torrent.libtorrent_resume` file if it exists.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if any file cannot be deserialized.
+    pub fn from_rtorrent(
+        torrent: &[u8],
+        rtorrent: &[u8],
+        libtorrent_resume: Option<&[u8]>,
+    ) -> Result<Self> {
+        let state = RtorrentSession::from_slice(rtorrent)?;
+        let paused = !state.is_started();
+        let mut session = SessionTorrent {
+            source: SessionSource::Rtorrent,
+            info_hash: None,
+            name: None,
+            save_path: state.directory,
+            label: state.custom1.filter(|label| !label.is_empty()),
+            tags: Vec::new(),
+            paused,
+            pieces: None,
+            total_uploaded: state.total_uploaded,
+            total_downloaded: state.total_downloaded,
+            added_time: state.timestamp_started,
+            trackers: Vec::new(),
+            metainfo: None,
+        };
+        session.set_metainfo(torrent)?;
+
+        if let Some(resume) = libtorrent_resume {
+            let resume = RtorrentResume::from_slice(resume)?;
+            let piece_count = session
+                .metainfo
+                .as_ref()
+                .map_or(0, |metainfo| metainfo.info.piece_count());
+            if let Some(bitfield) = resume.bitfield {
+                session.pieces = Some((0..piece_count).map(|i| bitfield.has_piece(i)).collect());
+            }
+            let disabled: Vec<&str> = resume
+                .trackers
+                .iter()
+                .filter(|(_, tracker)| tracker.enabled == 0)
+                .map(|(url, _)| url.as_str())
+                .collect();
+            for tier in &mut session.trackers {
+                tier.retain(|url| !disabled.contains(&url.as_str()));
+            }
+            session.trackers.retain(|tier| !tier.is_empty());
+        }
+        Ok(session)
+    }
+
+    /// Sets the metainfo and the fields derived from it.
+    fn set_metainfo(&mut self, torrent: &[u8]) -> Result<()> {
+        let metainfo = MetaInfo::from_slice(torrent)?;
+        if self.info_hash.is_none() {
+            self.info_hash = metainfo_hash(torrent);
+        }
+        if self.name.is_none() {
+            self.name = String::from_utf8(metainfo.info.name.to_vec()).ok();
+        }
+        if self.trackers.is_empty() {
+            self.trackers = owned_tiers(metainfo.tracker_tiers());
+        }
+        self.metainfo = Some(metainfo);
+        Ok(())
+    }
+
+    /// Converts the session into libtorrent resume data.
+    #[must_use]
+    pub fn to_libtorrent(&self) -> ResumeData {
+        let mut resume = ResumeData::new();
+        resume.info_hash = self
+            .info_hash
+            .map(|h| serde_bytes::ByteBuf::from(h.as_bytes().to_vec()));
+        resume.name = self.name.clone();
+        resume.save_path = self.save_path.clone();
+        resume.paused = self.paused;
+        if let Some(pieces) = &self.pieces {
+            resume.set_pieces(pieces.iter().cloned());
+        }
+        resume.total_uploaded = self.total_uploaded;
+        resume.total_downloaded = self.total_downloaded;
+        resume.added_time = self.added_time.unwrap_or(0);
+        resume.trackers = self.trackers.clone();
+        resume
+    }
+
+    /// Converts the session into a qBittorrent `.fastresume` file.
+    ///
+    /// The metainfo must be written separately to the `.torrent` file.
+    #[must_use]
+    pub fn to_qbittorrent(&self) -> QbtFastResume {
+        let mut fastresume = QbtFastResume {
+            resume: self.to_libtorrent(),
+            ..QbtFastResume::default()
+        };
+        fastresume.qbt.category = self.label.clone();
+        fastresume.qbt.tags = self.tags.clone();
+        fastresume.qbt.save_path = self.save_path.clone();
+        fastresume
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TORRENT: &[u8] = b"d8:announce17:http://a/announce4:infod6:lengthi3e4:name4:spam12:piece lengthi1e6:pieces60:aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaee";
+
+    #[test]
+    fn test_rtorrent() -> Result<()> {
+        let session = SessionTorrent::from_rtorrent(
+            TORRENT,
+            b"d7:custom15:linux9:directory4:/dl/5:statei0e14:total_uploadedi7ee",
+            Some(b"d8:bitfield1:\xa0e"),
+        )?;
+        assert_eq!(session.source, SessionSource::Rtorrent);
+        assert_eq!(session.name.as_deref(), Some("spam"));
+        assert_eq!(session.label.as_deref(), Some("linux"));
+        assert!(session.paused);
+        assert_eq!(session.pieces, Some(vec![true, false, true]));
+        assert_eq!(session.total_uploaded, 7);
+        assert_eq!(
+            session.trackers,
+            vec![vec![String::from("http://a/announce")]]
+        );
+        #[cfg(feature = "sha1")]
+        assert!(session.info_hash.is_some());
+
+        let disabled = SessionTorrent::from_rtorrent(
+            TORRENT,
+            b"d5:statei1ee",
+            Some(b"d8:trackersd17:http://a/announced7:enabledi0eeee"),
+        )?;
+        assert!(disabled.trackers.is_empty());
+        assert!(!disabled.paused);
+        Ok(())
+    }
+
+    #[test]
+    fn test_migrate() -> Result<()> {
+        let session = SessionTorrent::from_rtorrent(
+            TORRENT,
+            b"d7:custom15:linux9:directory4:/dl/5:statei1ee",
+            Some(b"d8:bitfieldi3ee"),
+        )?;
+        let fastresume = session.to_qbittorrent();
+        assert_eq!(fastresume.resume.completed_pieces(), 3);
+
+        let migrated = SessionTorrent::from_qbittorrent(&fastresume.to_vec()?, Some(TORRENT))?;
+        assert_eq!(migrated.label, session.label);
+        assert_eq!(migrated.save_path, session.save_path);
+        assert_eq!(migrated.pieces, session.pieces);
+        assert_eq!(migrated.trackers, session.trackers);
+        assert_eq!(migrated.metainfo, session.metainfo);
+        Ok(())
+    }
+}
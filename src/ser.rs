@@ -1,18 +1,21 @@
 //! Serializes Bencode data.
 
 use crate::error::{Error, Result};
+use core::fmt::Write as _;
 use serde::{ser, Serialize};
 
 #[cfg(all(feature = "alloc", not(feature = "std")))]
-use alloc::{collections::BTreeMap, vec::Vec};
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
 
+#[cfg(feature = "tokio")]
+use std::collections::VecDeque;
 #[cfg(feature = "std")]
-use std::{collections::BTreeMap, io, vec::Vec};
+use std::{collections::BTreeMap, io, string::String, vec::Vec};
 
-#[cfg(feature = "std")]
+#[cfg(any(feature = "std", feature = "bytes"))]
 use crate::write;
 
-use crate::write::Write;
+use crate::write::{Segments, Write};
 
 /// Serializes an instance of `T` into the writer `W` as `Bencode` data.
 ///
@@ -34,6 +37,153 @@ where
     Ok(())
 }
 
+/// Serializes an instance of `T` into the [`bytes::BufMut`] buffer `B` as `Bencode` data.
+///
+/// ```rust
+/// use bytes::BytesMut;
+///
+/// let mut buf = BytesMut::new();
+/// bt_bencode::to_buf_mut(&mut buf, &vec!["a", "b"])?;
+/// assert_eq!(&buf[..], b"l1:a1:be");
+/// # Ok::<(), bt_bencode::Error>(())
+/// ```
+///
+/// # Errors
+///
+/// Serialization can fail if `T`'s implementation of
+/// [Serialize][serde::ser::Serialize] decides to fail, if `T` contains
+/// unsupported types for serialization, if `T` contains a map with
+/// non-string keys, or if the buffer does not have enough remaining capacity.
+#[cfg(feature = "bytes")]
+#[inline]
+pub fn to_buf_mut<B, T>(buf: B, value: &T) -> Result<()>
+where
+    B: bytes::BufMut,
+    T: ?Sized + Serialize,
+{
+    let mut ser = Serializer::new(write::BufMutWrite::new(buf));
+    value.serialize(&mut ser)?;
+    Ok(())
+}
+
+/// Serializes an instance of `T` into the [`AsyncWrite`][tokio::io::AsyncWrite] writer `W` as
+/// `Bencode` data.
+///
+/// The value is serialized into segments of
+/// [`Segments::DEFAULT_SEGMENT_SIZE`] bytes, and each segment is written as soon
+/// as it is full if the writer accepts it without waiting. Serialization in
+/// serde is synchronous, so the segments which the writer is not ready for are
+/// kept in memory and written after the value is serialized.
+///
+/// # Errors
+///
+/// Serialization can fail if `T`'s implementation of
+/// [Serialize][serde::ser::Serialize] decides to fail, if `T` contains
+/// unsupported types for serialization, if `T` contains a map with
+/// non-string keys, or if the bytes could not be written.
+#[cfg(feature = "tokio")]
+pub async fn to_async_writer<W, T>(mut writer: W, value: &T) -> Result<()>
+where
+    W: tokio::io::AsyncWrite + Unpin,
+    T: ?Sized + Serialize,
+{
+    use tokio::io::AsyncWriteExt;
+
+    let (pending, written) = core::future::poll_fn(|cx| {
+        let mut ser = Serializer::new(AsyncSegmentWrite {
+            writer: &mut writer,
+            cx,
+            pending: VecDeque::new(),
+            written: 0,
+            last: Vec::new(),
+        });
+        let result = value.serialize(&mut ser).map(|()| {
+            let mut w = ser.into_inner();
+            w.pending.push_back(w.last);
+            (w.pending, w.written)
+        });
+        core::task::Poll::Ready(result)
+    })
+    .await?;
+
+    for (idx, segment) in pending.iter().enumerate() {
+        let start = if idx == 0 { written } else { 0 };
+        writer
+            .write_all(&segment[start..])
+            .await
+            .map_err(Error::IoError)?;
+    }
+    Ok(())
+}
+
+/// Writes the serialized bytes to an [`AsyncWrite`][tokio::io::AsyncWrite] writer
+/// from a synchronous serializer.
+///
+/// Whenever a segment is full, the pending segments are written until the
+/// writer is not ready.
+#[cfg(feature = "tokio")]
+struct AsyncSegmentWrite<'a, 'b, 'c, W> {
+    writer: &'a mut W,
+    cx: &'b mut core::task::Context<'c>,
+    /// The full segments which are not completely written yet
+    pending: VecDeque<Vec<u8>>,
+    /// The number of bytes of the first pending segment which are written
+    written: usize,
+    last: Vec<u8>,
+}
+
+#[cfg(feature = "tokio")]
+impl<W> AsyncSegmentWrite<'_, '_, '_, W>
+where
+    W: tokio::io::AsyncWrite + Unpin,
+{
+    fn poll_pending(&mut self) -> Result<()> {
+        use core::{pin::Pin, task::Poll};
+
+        while let Some(segment) = self.pending.front() {
+            let poll = Pin::new(&mut *self.writer).poll_write(self.cx, &segment[self.written..]);
+            match poll {
+                Poll::Ready(Ok(0)) => {
+                    return Err(Error::IoError(io::ErrorKind::WriteZero.into()));
+                }
+                Poll::Ready(Ok(n)) => {
+                    self.written += n;
+                    if self.written == segment.len() {
+                        self.pending.pop_front();
+                        self.written = 0;
+                    }
+                }
+                Poll::Ready(Err(err)) => return Err(Error::IoError(err)),
+                Poll::Pending => return Ok(()),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<W> Write for AsyncSegmentWrite<'_, '_, '_, W>
+where
+    W: tokio::io::AsyncWrite + Unpin,
+{
+    fn write_all(&mut self, mut buf: &[u8]) -> Result<()> {
+        while !buf.is_empty() {
+            let n = core::cmp::min(Segments::DEFAULT_SEGMENT_SIZE - self.last.len(), buf.len());
+            self.last.extend_from_slice(&buf[..n]);
+            buf = &buf[n..];
+            if self.last.len() == Segments::DEFAULT_SEGMENT_SIZE {
+                let full = core::mem::replace(
+                    &mut self.last,
+                    Vec::with_capacity(Segments::DEFAULT_SEGMENT_SIZE),
+                );
+                self.pending.push_back(full);
+                self.poll_pending()?;
+            }
+        }
+        Ok(())
+    }
+}
+
 /// Serializes an instance of `T` into a new [Vec] as `Bencode` data.
 ///
 /// # Errors
@@ -53,10 +203,34 @@ where
     Ok(writer)
 }
 
+/// Serializes an instance of `T` into a new list of [Segments] as `Bencode` data.
+///
+/// Unlike [`to_vec`], the output is not stored in a single contiguous
+/// allocation, which is useful for very large values.
+///
+/// # Errors
+///
+/// Serialization can fail if `T`'s implemenation of
+/// [Serialize][serde::ser::Serialize] decides to fail, if `T` contains
+/// unsupported types for serialization, or if `T` contains a map with
+/// non-string keys.
+#[inline]
+pub fn to_segments<T>(value: &T) -> Result<Segments>
+where
+    T: ?Sized + Serialize,
+{
+    let mut ser = Serializer::new(Segments::new());
+    value.serialize(&mut ser)?;
+    Ok(ser.into_inner())
+}
+
 /// A `Bencode` Serializer for types which implement [Serialize][serde::ser::Serialize].
 #[derive(Debug)]
 pub struct Serializer<W> {
     writer: W,
+    options: Builder,
+    /// If the value being serialized is a dictionary value which can be omitted
+    dict_value: bool,
 }
 
 impl<W> Serializer<W>
@@ -65,7 +239,7 @@ where
 {
     /// Constructs a Serializer with an [Write] target.
     pub fn new(writer: W) -> Self {
-        Serializer { writer }
+        Builder::new().build(writer)
     }
 }
 
@@ -82,6 +256,205 @@ where
     }
 }
 
+/// Configures and constructs a [`Serializer`].
+///
+/// By default, a Serializer sorts dictionary keys and returns an
+/// [`Error::UnsupportedType`] for `None`, `bool`, floating point, and enum
+/// values as Bencode has no encoding for them. The policies for those types
+/// can be changed, but the output may not be deserialized back into the same
+/// types.
+///
+/// ```rust
+/// use bt_bencode::SerializerBuilder;
+/// use serde_derive::Serialize;
+///
+/// #[derive(Serialize)]
+/// enum Event {
+///     Started,
+///     Stopped { uploaded: u64 },
+/// }
+///
+/// #[derive(Serialize)]
+/// struct Announce {
+///     compact: bool,
+///     event: Event,
+///     key: Option<String>,
+///     ratio: f64,
+/// }
+///
+/// let builder = SerializerBuilder::new()
+///     .bool_as_int(true)
+///     .skip_none(true)
+///     .float_as_str(true)
+///     .externally_tagged_enums(true);
+///
+/// let announce = Announce {
+///     compact: true,
+///     event: Event::Started,
+///     key: None,
+///     ratio: 1.5,
+/// };
+/// assert_eq!(
+///     builder.to_vec(&announce)?,
+///     b"d7:compacti1e5:event7:Started5:ratio3:1.5e"
+/// );
+///
+/// let event = Event::Stopped { uploaded: 10 };
+/// assert_eq!(builder.to_vec(&event)?, b"d7:Stoppedd8:uploadedi10eee");
+/// # Ok::<(), bt_bencode::Error>(())
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Builder {
+    sort_keys: bool,
+    skip_none: bool,
+    bool_as_int: bool,
+    float_as_str: bool,
+    externally_tagged_enums: bool,
+}
+
+impl Default for Builder {
+    fn default() -> Self {
+        Builder {
+            sort_keys: true,
+            skip_none: false,
+            bool_as_int: false,
+            float_as_str: false,
+            externally_tagged_enums: false,
+        }
+    }
+}
+
+impl Builder {
+    /// Constructs a builder with the default options.
+    #[must_use]
+    pub fn new() -> Self {
+        Builder::default()
+    }
+
+    /// Sets if dictionary keys are sorted.
+    ///
+    /// Bencode requires dictionary keys to be sorted, so this should only be
+    /// disabled if the entries are known to be in order (e.g. a struct with
+    /// fields declared in order) or the output does not need to be valid.
+    /// Unsorted dictionaries are written as their entries are serialized, so
+    /// duplicate keys are not removed.
+    #[must_use]
+    pub fn sort_keys(mut self, sort_keys: bool) -> Self {
+        self.sort_keys = sort_keys;
+        self
+    }
+
+    /// Sets if dictionary entries with a `None` value are omitted.
+    ///
+    /// A `None` value which is not a dictionary value is still unsupported.
+    #[must_use]
+    pub fn skip_none(mut self, skip_none: bool) -> Self {
+        self.skip_none = skip_none;
+        self
+    }
+
+    /// Sets if `bool` values are serialized as the integers `0` and `1`.
+    #[must_use]
+    pub fn bool_as_int(mut self, bool_as_int: bool) -> Self {
+        self.bool_as_int = bool_as_int;
+        self
+    }
+
+    /// Sets if floating point values are serialized as byte strings with
+    /// their decimal representation.
+    #[must_use]
+    pub fn float_as_str(mut self, float_as_str: bool) -> Self {
+        self.float_as_str = float_as_str;
+        self
+    }
+
+    /// Sets if enum values are serialized in serde's externally tagged
+    /// representation.
+    ///
+    /// A unit variant is serialized as a byte string with the variant name,
+    /// and other variants are serialized as a dictionary with the variant
+    /// name as the only key.
+    #[must_use]
+    pub fn externally_tagged_enums(mut self, externally_tagged_enums: bool) -> Self {
+        self.externally_tagged_enums = externally_tagged_enums;
+        self
+    }
+
+    /// Constructs a Serializer with an [Write] target.
+    #[must_use]
+    pub fn build<W>(self, writer: W) -> Serializer<W>
+    where
+        W: Write,
+    {
+        Serializer {
+            writer,
+            options: self,
+            dict_value: false,
+        }
+    }
+
+    /// Constructs a Serializer with an [`io::Write`] target.
+    #[cfg(feature = "std")]
+    #[must_use]
+    pub fn build_writer<W>(self, writer: W) -> Serializer<write::IoWrite<W>>
+    where
+        W: io::Write,
+    {
+        self.build(write::IoWrite::new(writer))
+    }
+
+    /// Serializes an instance of `T` into the writer `W` as `Bencode` data.
+    ///
+    /// # Errors
+    ///
+    /// Serialization can fail if `T`'s implementation of
+    /// [Serialize][serde::ser::Serialize] decides to fail, if `T` contains
+    /// unsupported types for serialization, or if `T` contains a map with
+    /// non-string keys.
+    #[cfg(feature = "std")]
+    pub fn to_writer<W, T>(self, writer: W, value: &T) -> Result<()>
+    where
+        W: io::Write,
+        T: ?Sized + Serialize,
+    {
+        value.serialize(&mut self.build_writer(writer))
+    }
+
+    /// Serializes an instance of `T` into a new [Vec] as `Bencode` data.
+    ///
+    /// # Errors
+    ///
+    /// Serialization can fail if `T`'s implementation of
+    /// [Serialize][serde::ser::Serialize] decides to fail, if `T` contains
+    /// unsupported types for serialization, or if `T` contains a map with
+    /// non-string keys.
+    pub fn to_vec<T>(self, value: &T) -> Result<Vec<u8>>
+    where
+        T: ?Sized + Serialize,
+    {
+        let mut writer = Vec::new();
+        value.serialize(&mut self.build(&mut writer))?;
+        Ok(writer)
+    }
+
+    /// Serializes an instance of `T` into a new list of [Segments] as `Bencode` data.
+    ///
+    /// # Errors
+    ///
+    /// Serialization can fail if `T`'s implementation of
+    /// [Serialize][serde::ser::Serialize] decides to fail, if `T` contains
+    /// unsupported types for serialization, or if `T` contains a map with
+    /// non-string keys.
+    pub fn to_segments<T>(self, value: &T) -> Result<Segments>
+    where
+        T: ?Sized + Serialize,
+    {
+        let mut ser = self.build(Segments::new());
+        value.serialize(&mut ser)?;
+        Ok(ser.into_inner())
+    }
+}
+
 impl<'a, W> ser::Serializer for &'a mut Serializer<W>
 where
     W: Write,
@@ -92,13 +465,16 @@ where
     type SerializeSeq = Self;
     type SerializeTuple = Self;
     type SerializeTupleStruct = Self;
-    type SerializeTupleVariant = ser::Impossible<(), Error>;
+    type SerializeTupleVariant = Self;
     type SerializeMap = SerializeMap<'a, W>;
     type SerializeStruct = SerializeMap<'a, W>;
-    type SerializeStructVariant = ser::Impossible<(), Error>;
+    type SerializeStructVariant = SerializeMap<'a, W>;
 
     #[inline]
-    fn serialize_bool(self, _value: bool) -> Result<()> {
+    fn serialize_bool(self, value: bool) -> Result<()> {
+        if self.options.bool_as_int {
+            return self.serialize_u64(u64::from(value));
+        }
         Err(Error::UnsupportedType)
     }
 
@@ -119,11 +495,9 @@ where
 
     #[inline]
     fn serialize_i64(self, value: i64) -> Result<()> {
-        self.writer.write_all(b"i")?;
+        let mut buf = itoa::Buffer::new();
         self.writer
-            .write_all(itoa::Buffer::new().format(value).as_bytes())?;
-        self.writer.write_all(b"e")?;
-        Ok(())
+            .write_all_vectored(&[b"i", buf.format(value).as_bytes(), b"e"])
     }
 
     #[inline]
@@ -143,20 +517,42 @@ where
 
     #[inline]
     fn serialize_u64(self, value: u64) -> Result<()> {
-        self.writer.write_all(b"i")?;
+        let mut buf = itoa::Buffer::new();
         self.writer
-            .write_all(itoa::Buffer::new().format(value).as_bytes())?;
-        self.writer.write_all(b"e")?;
-        Ok(())
+            .write_all_vectored(&[b"i", buf.format(value).as_bytes(), b"e"])
+    }
+
+    #[inline]
+    fn serialize_i128(self, value: i128) -> Result<()> {
+        let mut buf = itoa::Buffer::new();
+        self.writer
+            .write_all_vectored(&[b"i", buf.format(value).as_bytes(), b"e"])
     }
 
     #[inline]
-    fn serialize_f32(self, _value: f32) -> Result<()> {
+    fn serialize_u128(self, value: u128) -> Result<()> {
+        let mut buf = itoa::Buffer::new();
+        self.writer
+            .write_all_vectored(&[b"i", buf.format(value).as_bytes(), b"e"])
+    }
+
+    #[inline]
+    fn serialize_f32(self, value: f32) -> Result<()> {
+        if self.options.float_as_str {
+            let mut buf = String::new();
+            let _ = write!(buf, "{}", value);
+            return self.serialize_str(&buf);
+        }
         Err(Error::UnsupportedType)
     }
 
     #[inline]
-    fn serialize_f64(self, _value: f64) -> Result<()> {
+    fn serialize_f64(self, value: f64) -> Result<()> {
+        if self.options.float_as_str {
+            let mut buf = String::new();
+            let _ = write!(buf, "{}", value);
+            return self.serialize_str(&buf);
+        }
         Err(Error::UnsupportedType)
     }
 
@@ -168,22 +564,22 @@ where
 
     #[inline]
     fn serialize_str(self, value: &str) -> Result<()> {
-        self.writer
-            .write_all(itoa::Buffer::new().format(value.len()).as_bytes())?;
-        self.writer.write_all(b":")?;
-        self.writer.write_all(value.as_bytes())
+        self.serialize_bytes(value.as_bytes())
     }
 
     #[inline]
     fn serialize_bytes(self, value: &[u8]) -> Result<()> {
+        let mut buf = itoa::Buffer::new();
         self.writer
-            .write_all(itoa::Buffer::new().format(value.len()).as_bytes())?;
-        self.writer.write_all(b":")?;
-        self.writer.write_all(value)
+            .write_all_vectored(&[buf.format(value.len()).as_bytes(), b":", value])
     }
 
     #[inline]
     fn serialize_none(self) -> Result<()> {
+        if self.options.skip_none && self.dict_value {
+            // Nothing is written so the dictionary entry is omitted.
+            return Ok(());
+        }
         self.serialize_unit()
     }
 
@@ -210,16 +606,28 @@ where
         self,
         _name: &'static str,
         _variant_index: u32,
-        _variant: &'static str,
+        variant: &'static str,
     ) -> Result<()> {
+        if self.options.externally_tagged_enums {
+            return self.serialize_str(variant);
+        }
         Err(Error::UnsupportedType)
     }
 
     #[inline]
-    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<()>
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<()>
     where
         T: ?Sized + Serialize,
     {
+        if name == crate::value::raw::TOKEN {
+            let raw = value.serialize(&mut MapKeySerializer {})?;
+            return self.writer.write_all(&raw);
+        }
+        #[cfg(feature = "big-int")]
+        if name == crate::value::BIG_INT_TOKEN {
+            let digits = value.serialize(&mut MapKeySerializer {})?;
+            return self.writer.write_all_vectored(&[b"i", &digits, b"e"]);
+        }
         value.serialize(self)
     }
 
@@ -228,17 +636,23 @@ where
         self,
         _name: &'static str,
         _variant_index: u32,
-        _variant: &'static str,
-        _value: &T,
+        variant: &'static str,
+        value: &T,
     ) -> Result<()>
     where
         T: ?Sized + Serialize,
     {
-        Err(Error::UnsupportedType)
+        if !self.options.externally_tagged_enums {
+            return Err(Error::UnsupportedType);
+        }
+        self.start_variant(variant)?;
+        value.serialize(&mut *self)?;
+        self.writer.write_all(b"e")
     }
 
     #[inline]
     fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        self.dict_value = false;
         self.writer.write_all(b"l")?;
         Ok(self)
     }
@@ -262,14 +676,20 @@ where
         self,
         _name: &'static str,
         _variant_index: u32,
-        _variant: &'static str,
+        variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeTupleVariant> {
-        Err(Error::UnsupportedType)
+        if !self.options.externally_tagged_enums {
+            return Err(Error::UnsupportedType);
+        }
+        self.start_variant(variant)?;
+        self.writer.write_all(b"l")?;
+        Ok(self)
     }
 
     #[inline]
     fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        self.dict_value = false;
         self.writer.write_all(b"d")?;
         Ok(SerializeMap::new(self))
     }
@@ -284,10 +704,15 @@ where
         self,
         _name: &'static str,
         _variant_index: u32,
-        _variant: &'static str,
+        variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeStructVariant> {
-        Err(Error::UnsupportedType)
+        if !self.options.externally_tagged_enums {
+            return Err(Error::UnsupportedType);
+        }
+        self.start_variant(variant)?;
+        self.writer.write_all(b"d")?;
+        Ok(SerializeMap::new(self))
     }
 
     fn is_human_readable(&self) -> bool {
@@ -295,7 +720,19 @@ where
     }
 }
 
-impl<'a, W> ser::SerializeSeq for &'a mut Serializer<W>
+impl<W> Serializer<W>
+where
+    W: Write,
+{
+    /// Writes the start of a dictionary with the variant name as the key.
+    fn start_variant(&mut self, variant: &str) -> Result<()> {
+        self.dict_value = false;
+        self.writer.write_all(b"d")?;
+        ser::Serializer::serialize_str(&mut *self, variant)
+    }
+}
+
+impl<W> ser::SerializeSeq for &mut Serializer<W>
 where
     W: Write,
 {
@@ -317,7 +754,7 @@ where
     }
 }
 
-impl<'a, W> ser::SerializeTuple for &'a mut Serializer<W>
+impl<W> ser::SerializeTuple for &mut Serializer<W>
 where
     W: Write,
 {
@@ -339,7 +776,29 @@ where
     }
 }
 
-impl<'a, W> ser::SerializeTupleStruct for &'a mut Serializer<W>
+impl<W> ser::SerializeTupleVariant for &mut Serializer<W>
+where
+    W: Write,
+{
+    type Ok = ();
+    type Error = Error;
+
+    #[inline]
+    fn serialize_field<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(&mut **self)
+    }
+
+    #[inline]
+    fn end(self) -> Result<()> {
+        self.writer.write_all(b"ee")?;
+        Ok(())
+    }
+}
+
+impl<W> ser::SerializeTupleStruct for &mut Serializer<W>
 where
     W: Write,
 {
@@ -366,7 +825,7 @@ where
 #[derive(Debug)]
 pub struct SerializeMap<'a, W> {
     ser: &'a mut Serializer<W>,
-    entries: BTreeMap<Vec<u8>, Vec<u8>>,
+    entries: BTreeMap<Vec<u8>, Segments>,
     current_key: Option<Vec<u8>>,
 }
 
@@ -390,12 +849,44 @@ where
         }
 
         for (k, v) in &self.entries {
-            ser::Serializer::serialize_bytes(&mut *self.ser, k.as_ref())?;
-            self.ser.writer.write_all(v)?;
+            write_entry(self.ser, k, v)?;
         }
 
         Ok(())
     }
+
+    /// Returns a Serializer for buffering a dictionary value.
+    #[inline]
+    fn value_serializer(&self) -> Serializer<Segments> {
+        let mut ser = self.ser.options.build(Segments::new());
+        ser.dict_value = true;
+        ser
+    }
+
+    #[inline]
+    fn insert_entry(&mut self, key: Vec<u8>, value: Segments) -> Result<()> {
+        if value.is_empty() {
+            // The value was skipped.
+            return Ok(());
+        }
+        if self.ser.options.sort_keys {
+            self.entries.insert(key, value);
+            Ok(())
+        } else {
+            write_entry(self.ser, &key, &value)
+        }
+    }
+}
+
+fn write_entry<W>(ser: &mut Serializer<W>, key: &[u8], value: &Segments) -> Result<()>
+where
+    W: Write,
+{
+    ser::Serializer::serialize_bytes(&mut *ser, key)?;
+    for segment in value.iter() {
+        ser.writer.write_all(segment)?;
+    }
+    Ok(())
 }
 
 impl<'a, W> ser::SerializeMap for SerializeMap<'a, W>
@@ -423,11 +914,11 @@ where
         T: ?Sized + Serialize,
     {
         let key = self.current_key.take().ok_or(Error::ValueWithoutKey)?;
-        let buf: Vec<u8> = Vec::new();
-        let mut ser = Serializer::new(buf);
+        // Buffer the value in segments so large values (e.g. the `info`
+        // dictionary of a large torrent) do not need a contiguous allocation.
+        let mut ser = self.value_serializer();
         value.serialize(&mut ser)?;
-        self.entries.insert(key, ser.into_inner());
-        Ok(())
+        self.insert_entry(key, ser.into_inner())
     }
 
     #[inline]
@@ -452,11 +943,9 @@ where
     {
         let key = key.serialize(&mut MapKeySerializer {})?;
 
-        let buf: Vec<u8> = Vec::new();
-        let mut ser = Serializer::new(buf);
+        let mut ser = self.value_serializer();
         value.serialize(&mut ser)?;
-        self.entries.insert(key, ser.into_inner());
-        Ok(())
+        self.insert_entry(key, ser.into_inner())
     }
 
     #[inline]
@@ -467,9 +956,32 @@ where
     }
 }
 
+impl<'a, W> ser::SerializeStructVariant for SerializeMap<'a, W>
+where
+    W: Write,
+{
+    type Ok = ();
+    type Error = Error;
+
+    #[inline]
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        ser::SerializeStruct::serialize_field(self, key, value)
+    }
+
+    #[inline]
+    fn end(mut self) -> Result<()> {
+        self.end_map()?;
+        self.ser.writer.write_all(b"ee")?;
+        Ok(())
+    }
+}
+
 struct MapKeySerializer;
 
-impl<'a> ser::Serializer for &'a mut MapKeySerializer {
+impl ser::Serializer for &mut MapKeySerializer {
     type Ok = Vec<u8>;
     type Error = Error;
 
@@ -517,6 +1029,14 @@ impl<'a> ser::Serializer for &'a mut MapKeySerializer {
         Err(Error::UnsupportedType)
     }
 
+    fn serialize_i128(self, _value: i128) -> Result<Vec<u8>> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_u128(self, _value: u128) -> Result<Vec<u8>> {
+        Err(Error::UnsupportedType)
+    }
+
     fn serialize_f32(self, _value: f32) -> Result<Vec<u8>> {
         Err(Error::UnsupportedType)
     }
@@ -730,7 +1250,20 @@ mod tests {
 
     #[test]
     fn test_serialize_u64_greater_than_i64_max() {
-        let value: u64 = (i64::max_value() as u64) + 1;
+        let value: u64 = (i64::MAX as u64) + 1;
+        assert_eq!(to_vec(&value).unwrap(), format!("i{}e", value).into_bytes());
+    }
+
+    #[test]
+    fn test_serialize_i128() {
+        let value = i128::MIN;
+        assert_eq!(to_vec(&value).unwrap(), format!("i{}e", value).into_bytes());
+        assert_eq!(to_vec(&-3_i128).unwrap(), b"i-3e");
+    }
+
+    #[test]
+    fn test_serialize_u128() {
+        let value = u128::MAX;
         assert_eq!(to_vec(&value).unwrap(), format!("i{}e", value).into_bytes());
     }
 
@@ -921,4 +1454,204 @@ mod tests {
             String::from("d3:inti3e1:s13:Hello, World!e").into_bytes()
         );
     }
+
+    #[tokio::test]
+    #[cfg(feature = "tokio")]
+    async fn test_to_async_writer() -> Result<()> {
+        let mut output = Vec::new();
+        to_async_writer(&mut output, &("spam", 42)).await?;
+        assert_eq!(output, b"l4:spami42ee");
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "tokio")]
+    async fn test_to_async_writer_segments() -> Result<()> {
+        use serde_bytes::Bytes;
+        use std::sync::{Arc, Mutex};
+
+        /// Records the number of written bytes when it is serialized.
+        struct Probe(Arc<Mutex<Vec<u8>>>, Arc<Mutex<usize>>);
+
+        impl Serialize for Probe {
+            fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
+            where
+                S: ser::Serializer,
+            {
+                *self.1.lock().unwrap() = self.0.lock().unwrap().len();
+                serializer.serialize_u64(1)
+            }
+        }
+
+        struct SharedWriter(Arc<Mutex<Vec<u8>>>);
+
+        impl tokio::io::AsyncWrite for SharedWriter {
+            fn poll_write(
+                self: core::pin::Pin<&mut Self>,
+                _cx: &mut core::task::Context<'_>,
+                buf: &[u8],
+            ) -> core::task::Poll<io::Result<usize>> {
+                self.0.lock().unwrap().extend_from_slice(buf);
+                core::task::Poll::Ready(Ok(buf.len()))
+            }
+
+            fn poll_flush(
+                self: core::pin::Pin<&mut Self>,
+                _cx: &mut core::task::Context<'_>,
+            ) -> core::task::Poll<io::Result<()>> {
+                core::task::Poll::Ready(Ok(()))
+            }
+
+            fn poll_shutdown(
+                self: core::pin::Pin<&mut Self>,
+                _cx: &mut core::task::Context<'_>,
+            ) -> core::task::Poll<io::Result<()>> {
+                core::task::Poll::Ready(Ok(()))
+            }
+        }
+
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let written = Arc::new(Mutex::new(0));
+        let large = vec![b'a'; Segments::DEFAULT_SEGMENT_SIZE * 2];
+        let value = (
+            Bytes::new(&large),
+            Probe(Arc::clone(&output), Arc::clone(&written)),
+        );
+        to_async_writer(SharedWriter(Arc::clone(&output)), &value).await?;
+        assert_eq!(*written.lock().unwrap(), Segments::DEFAULT_SEGMENT_SIZE * 2);
+
+        let output = core::mem::take(&mut *output.lock().unwrap());
+        assert_eq!(output, to_vec(&value)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_serialize_to_segments() {
+        use serde_bytes::Bytes;
+
+        let large = vec![b'a'; Segments::DEFAULT_SEGMENT_SIZE * 2 + 1];
+        let mut map = BTreeMap::new();
+        map.insert("pieces", Bytes::new(&large));
+        map.insert("name", Bytes::new(b"spam"));
+
+        let segments = to_segments(&map).unwrap();
+        assert!(segments.iter().count() > 1);
+        assert!(segments
+            .iter()
+            .all(|s| s.len() <= Segments::DEFAULT_SEGMENT_SIZE));
+        assert_eq!(segments.to_vec(), to_vec(&map).unwrap());
+    }
+
+    #[test]
+    fn test_builder_sort_keys() -> Result<()> {
+        #[derive(serde_derive::Serialize)]
+        struct Test {
+            z: u32,
+            a: u32,
+        }
+
+        let test = Test { z: 1, a: 2 };
+        assert_eq!(Builder::new().to_vec(&test)?, b"d1:ai2e1:zi1ee");
+        let builder = Builder::new().sort_keys(false);
+        assert_eq!(builder.to_vec(&test)?, b"d1:zi1e1:ai2ee");
+
+        let entries = [("b", 1), ("a", 2), ("b", 3)];
+        let mut ser = builder.build(Vec::new());
+        ser::Serializer::collect_map(&mut ser, entries.iter().copied())?;
+        assert_eq!(ser.into_inner(), b"d1:bi1e1:ai2e1:bi3ee");
+        Ok(())
+    }
+
+    #[test]
+    fn test_builder_skip_none() -> Result<()> {
+        #[derive(serde_derive::Serialize)]
+        struct Test {
+            a: Option<u32>,
+            b: Option<u32>,
+            c: Vec<Option<u32>>,
+        }
+
+        let test = Test {
+            a: None,
+            b: Some(1),
+            c: vec![],
+        };
+        assert_matches!(Builder::new().to_vec(&test), Err(Error::UnsupportedType));
+        let builder = Builder::new().skip_none(true);
+        assert_eq!(builder.to_vec(&test)?, b"d1:bi1e1:clee");
+        assert_eq!(builder.sort_keys(false).to_vec(&test)?, b"d1:bi1e1:clee");
+
+        let test = Test {
+            a: None,
+            b: None,
+            c: vec![None],
+        };
+        assert_matches!(builder.to_vec(&test), Err(Error::UnsupportedType));
+        assert_matches!(builder.to_vec(&None::<u32>), Err(Error::UnsupportedType));
+
+        let mut map = BTreeMap::new();
+        map.insert("a", Some(Some(1)));
+        map.insert("b", Some(None));
+        map.insert("c", None);
+        assert_eq!(builder.to_vec(&map)?, b"d1:ai1ee");
+        Ok(())
+    }
+
+    #[test]
+    fn test_builder_bool_and_float() -> Result<()> {
+        let builder = Builder::new().bool_as_int(true);
+        assert_eq!(builder.to_vec(&(true, false))?, b"li1ei0ee");
+        assert_matches!(builder.to_vec(&1.5_f64), Err(Error::UnsupportedType));
+
+        let builder = Builder::new().float_as_str(true);
+        assert_eq!(builder.to_vec(&(1.5_f32, -0.25_f64))?, b"l3:1.55:-0.25e");
+        assert_eq!(builder.to_vec(&1e21_f64)?, b"22:1000000000000000000000");
+        assert_matches!(builder.to_vec(&true), Err(Error::UnsupportedType));
+        Ok(())
+    }
+
+    #[test]
+    fn test_builder_externally_tagged_enums() -> Result<()> {
+        #[derive(serde_derive::Serialize)]
+        enum Test {
+            Unit,
+            Newtype(u32),
+            Tuple(u32, &'static str),
+            Struct { z: u32, a: u32 },
+        }
+
+        let values = vec![
+            Test::Unit,
+            Test::Newtype(1),
+            Test::Tuple(2, "spam"),
+            Test::Struct { z: 3, a: 4 },
+        ];
+        for value in &values {
+            assert_matches!(Builder::new().to_vec(value), Err(Error::UnsupportedType));
+        }
+
+        let builder = Builder::new().externally_tagged_enums(true);
+        assert_eq!(
+            builder.to_vec(&values)?,
+            &b"l4:Unitd7:Newtypei1eed5:Tupleli2e4:spameed6:Structd1:ai4e1:zi3eeee"[..]
+        );
+
+        let mut map = BTreeMap::new();
+        map.insert("b", Test::Unit);
+        map.insert("a", Test::Newtype(5));
+        assert_eq!(builder.to_vec(&map)?, b"d1:ad7:Newtypei5ee1:b4:Unite");
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_builder_to_writer() -> Result<()> {
+        let builder = Builder::new().bool_as_int(true);
+        let mut output = Vec::new();
+        builder.to_writer(&mut output, &[true])?;
+        assert_eq!(output, b"li1ee");
+
+        assert_eq!(builder.to_segments(&false)?.to_vec(), b"i0e");
+        Ok(())
+    }
 }
@@ -0,0 +1,25 @@
+//! Adapters for use with `#[serde(with = "...")]` on fields of derived types.
+//!
+//! Data from the wild often encodes the same logical value in different
+//! ways. The modules here map the bencoded forms to the Rust types which are
+//! most convenient to work with.
+//!
+//! Adapters for compact peer and address encodings are in the `compact`
+//! module.
+
+pub mod bool_from_int;
+pub mod hex;
+pub mod lenient_int;
+pub mod one_or_many;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub mod os_string;
+#[cfg(any(feature = "std", feature = "time", feature = "chrono"))]
+pub mod timestamp;
+#[cfg(feature = "url")]
+#[cfg_attr(docsrs, doc(cfg(feature = "url")))]
+pub mod url;
+
+pub use bool_from_int::BoolFromInt;
+pub use lenient_int::LenientInt;
+pub use one_or_many::OneOrMany;
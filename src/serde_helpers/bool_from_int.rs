@@ -0,0 +1,193 @@
+//! Maps integer flags such as `private` and `ro` to [`bool`]s.
+//!
+//! A flag is serialized as `1` or `0`. Any non-zero integer is deserialized
+//! as `true`, and a boolean from a self-describing format (e.g. a
+//! [`Value`][crate::Value] converted from another format) is also accepted.
+//!
+//! A flag which may be missing should use `#[serde(default)]` so it is
+//! `false` when it is missing, and [`is_false`] to skip it when it is
+//! `false`. The [`option`] module keeps whether the flag was present.
+//!
+//! ```rust
+//! use bt_bencode::serde_helpers::BoolFromInt;
+//! use serde_derive::{Deserialize, Serialize};
+//!
+//! #[derive(Debug, PartialEq, Serialize, Deserialize)]
+//! struct Flags {
+//!     #[serde(
+//!         default,
+//!         with = "bt_bencode::serde_helpers::bool_from_int",
+//!         skip_serializing_if = "bt_bencode::serde_helpers::bool_from_int::is_false"
+//!     )]
+//!     private: bool,
+//!     #[serde(
+//!         default,
+//!         with = "bt_bencode::serde_helpers::bool_from_int::option",
+//!         skip_serializing_if = "Option::is_none"
+//!     )]
+//!     ro: Option<bool>,
+//!     seed: BoolFromInt,
+//! }
+//!
+//! let flags: Flags = bt_bencode::from_slice(b"d4:seedi0ee")?;
+//! assert!(!flags.private);
+//! assert_eq!(flags.ro, None);
+//! assert_eq!(flags.seed, BoolFromInt(false));
+//!
+//! let flags: Flags = bt_bencode::from_slice(b"d7:privatei1e2:roi0e4:seedi2ee")?;
+//! assert!(flags.private);
+//! assert_eq!(flags.ro, Some(false));
+//! assert_eq!(flags.seed, BoolFromInt(true));
+//! assert_eq!(bt_bencode::to_vec(&flags)?, b"d7:privatei1e2:roi0e4:seedi1ee");
+//! # Ok::<(), bt_bencode::Error>(())
+//! ```
+
+use core::fmt;
+use serde::{
+    de::{Deserialize, Deserializer, Visitor},
+    ser::{Serialize, Serializer},
+};
+
+/// A flag which is encoded as an integer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BoolFromInt(pub bool);
+
+impl From<bool> for BoolFromInt {
+    fn from(value: bool) -> Self {
+        BoolFromInt(value)
+    }
+}
+
+impl From<BoolFromInt> for bool {
+    fn from(value: BoolFromInt) -> Self {
+        value.0
+    }
+}
+
+impl Serialize for BoolFromInt {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize(&self.0, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for BoolFromInt {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserialize(deserializer).map(BoolFromInt)
+    }
+}
+
+/// Returns true if the flag is not set.
+///
+/// Use with `#[serde(skip_serializing_if = "...")]` to omit unset flags.
+#[must_use]
+pub fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+/// Serializes a flag as `1` or `0`.
+pub fn serialize<S>(value: &bool, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_i64(i64::from(*value))
+}
+
+/// Deserializes a flag from an integer or a boolean.
+///
+/// # Errors
+///
+/// An error is returned if the value is not an integer or a boolean.
+pub fn deserialize<'de, D>(deserializer: D) -> Result<bool, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct BoolFromIntVisitor;
+
+    impl<'de> Visitor<'de> for BoolFromIntVisitor {
+        type Value = bool;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("an integer flag")
+        }
+
+        fn visit_bool<E>(self, v: bool) -> Result<bool, E> {
+            Ok(v)
+        }
+
+        fn visit_i64<E>(self, v: i64) -> Result<bool, E> {
+            Ok(v != 0)
+        }
+
+        fn visit_u64<E>(self, v: u64) -> Result<bool, E> {
+            Ok(v != 0)
+        }
+    }
+
+    deserializer.deserialize_any(BoolFromIntVisitor)
+}
+
+/// Maps an optional integer flag to an optional [`bool`].
+///
+/// Use with `#[serde(default)]` so a missing flag is `None`.
+pub mod option {
+    use serde::{Deserializer, Serializer};
+
+    /// Serializes a flag as `1` or `0`.
+    ///
+    /// `None` cannot be serialized, so it should be skipped with
+    /// `skip_serializing_if = "Option::is_none"`.
+    pub fn serialize<S>(value: &Option<bool>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match value {
+            Some(value) => super::serialize(value, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    /// Deserializes a flag from an integer or a boolean.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the value is not an integer or a boolean.
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<bool>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        super::deserialize(deserializer).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{error::Result, Value};
+
+    #[test]
+    fn test_bool_from_int() -> Result<()> {
+        assert_eq!(
+            crate::from_slice::<BoolFromInt>(b"i0e")?,
+            BoolFromInt(false)
+        );
+        assert_eq!(crate::from_slice::<BoolFromInt>(b"i1e")?, BoolFromInt(true));
+        assert_eq!(
+            crate::from_slice::<BoolFromInt>(b"i-1e")?,
+            BoolFromInt(true)
+        );
+        assert!(crate::from_slice::<BoolFromInt>(b"1:1").is_err());
+        assert_eq!(crate::to_vec(&BoolFromInt(true))?, b"i1e");
+        assert_eq!(crate::to_vec(&BoolFromInt(false))?, b"i0e");
+        assert_eq!(
+            crate::from_value::<BoolFromInt>(Value::from(1u64))?,
+            BoolFromInt(true)
+        );
+        Ok(())
+    }
+}
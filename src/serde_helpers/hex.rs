@@ -0,0 +1,126 @@
+//! Exposes a byte string field as a hex [String].
+//!
+//! The field is serialized as the byte string which the hex string encodes.
+//! Deserializing formats the byte string as lowercase hex.
+//!
+//! ```rust
+//! use serde_derive::{Deserialize, Serialize};
+//!
+//! #[derive(Debug, PartialEq, Serialize, Deserialize)]
+//! struct Config {
+//!     #[serde(with = "bt_bencode::serde_helpers::hex")]
+//!     info_hash: String,
+//! }
+//!
+//! let config: Config = bt_bencode::from_slice(b"d9:info_hash2:\xab\x01e")?;
+//! assert_eq!(config.info_hash, "ab01");
+//! assert_eq!(bt_bencode::to_vec(&config)?, b"d9:info_hash2:\xab\x01e");
+//! # Ok::<(), bt_bencode::Error>(())
+//! ```
+
+use crate::error::{Error, Result};
+use serde::{de::Deserialize, ser, Deserializer, Serializer};
+use serde_bytes::ByteBuf;
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{string::String, vec::Vec};
+#[cfg(feature = "std")]
+use std::{string::String, vec::Vec};
+
+const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+fn hex_value(b: u8) -> Option<u8> {
+    match b {
+        b'0'..=b'9' => Some(b - b'0'),
+        b'a'..=b'f' => Some(b - b'a' + 10),
+        b'A'..=b'F' => Some(b - b'A' + 10),
+        _ => None,
+    }
+}
+
+/// Formats bytes as lowercase hex.
+#[must_use]
+pub fn encode(bytes: &[u8]) -> String {
+    let mut s = String::with_capacity(bytes.len() * 2);
+    for b in bytes {
+        s.push(char::from(HEX_DIGITS[usize::from(b >> 4)]));
+        s.push(char::from(HEX_DIGITS[usize::from(b & 0xf)]));
+    }
+    s
+}
+
+/// Parses a hex string into bytes.
+///
+/// Both uppercase and lowercase digits are accepted.
+///
+/// # Errors
+///
+/// An error is returned if the string has an odd length or contains a character
+/// which is not a hex digit.
+pub fn decode(s: &str) -> Result<Vec<u8>> {
+    let s = s.as_bytes();
+    if s.len() % 2 != 0 {
+        return Err(Error::Deserialize(String::from(
+            "hex string has an odd length",
+        )));
+    }
+    s.chunks(2)
+        .map(|pair| match (hex_value(pair[0]), hex_value(pair[1])) {
+            (Some(hi), Some(lo)) => Ok(hi << 4 | lo),
+            _ => Err(Error::Deserialize(String::from("invalid hex digit"))),
+        })
+        .collect()
+}
+
+/// Serializes a hex string as the byte string it encodes.
+///
+/// # Errors
+///
+/// An error is returned if the value is not a valid hex string.
+pub fn serialize<T, S>(value: &T, serializer: S) -> core::result::Result<S::Ok, S::Error>
+where
+    T: AsRef<str> + ?Sized,
+    S: Serializer,
+{
+    let bytes = decode(value.as_ref()).map_err(ser::Error::custom)?;
+    serializer.serialize_bytes(&bytes)
+}
+
+/// Deserializes a byte string as a lowercase hex string.
+///
+/// # Errors
+///
+/// An error is returned if the value is not a byte string.
+pub fn deserialize<'de, T, D>(deserializer: D) -> core::result::Result<T, D::Error>
+where
+    T: From<String>,
+    D: Deserializer<'de>,
+{
+    let bytes = ByteBuf::deserialize(deserializer)?;
+    Ok(T::from(encode(&bytes)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_decode() -> Result<()> {
+        assert_eq!(encode(b""), "");
+        assert_eq!(encode(&[0x00, 0x0f, 0xf0, 0xff]), "000ff0ff");
+        assert_eq!(decode("000fF0fF")?, &[0x00, 0x0f, 0xf0, 0xff][..]);
+        assert!(decode("abc").is_err());
+        assert!(decode("zz").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_serialize_invalid() {
+        #[derive(serde_derive::Serialize)]
+        struct Hash<'a> {
+            #[serde(with = "super")]
+            v: &'a str,
+        }
+        assert!(crate::to_vec(&Hash { v: "0g" }).is_err());
+    }
+}
@@ -0,0 +1,169 @@
+//! Accepts an integer encoded as either an integer or a byte string.
+//!
+//! Some trackers encode numeric fields as byte strings containing decimal
+//! digits (e.g. `4:1234`) instead of integers (`i1234e`). Either form is
+//! accepted when deserializing and the value is always serialized as an
+//! integer.
+//!
+//! ```rust
+//! use bt_bencode::serde_helpers::LenientInt;
+//! use serde_derive::{Deserialize, Serialize};
+//!
+//! #[derive(Debug, PartialEq, Serialize, Deserialize)]
+//! struct Stats {
+//!     #[serde(with = "bt_bencode::serde_helpers::lenient_int")]
+//!     complete: u64,
+//!     incomplete: LenientInt<u32>,
+//! }
+//!
+//! let stats: Stats = bt_bencode::from_slice(b"d8:complete2:1210:incompletei3ee")?;
+//! assert_eq!(stats.complete, 12);
+//! assert_eq!(stats.incomplete, LenientInt(3));
+//! assert_eq!(bt_bencode::to_vec(&stats)?, b"d8:completei12e10:incompletei3ee");
+//! # Ok::<(), bt_bencode::Error>(())
+//! ```
+
+use core::{convert::TryFrom, fmt, marker::PhantomData, str, str::FromStr};
+use serde::{
+    de::{self, Deserialize, Deserializer, Unexpected, Visitor},
+    ser::{Serialize, Serializer},
+};
+
+/// An integer which is deserialized from either an integer or a byte string.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LenientInt<T>(pub T);
+
+impl<T> LenientInt<T> {
+    /// Returns the integer.
+    #[must_use]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for LenientInt<T> {
+    fn from(value: T) -> Self {
+        LenientInt(value)
+    }
+}
+
+impl<T: Serialize> Serialize for LenientInt<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for LenientInt<T>
+where
+    T: TryFrom<i64> + TryFrom<u64> + FromStr,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserialize(deserializer).map(LenientInt)
+    }
+}
+
+/// Serializes the value as an integer.
+pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Serialize,
+    S: Serializer,
+{
+    value.serialize(serializer)
+}
+
+/// Deserializes an integer or a byte string containing a decimal integer.
+///
+/// # Errors
+///
+/// An error is returned if the value is not an integer or a byte string
+/// containing an integer, or if the integer does not fit in the type.
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: TryFrom<i64> + TryFrom<u64> + FromStr,
+    D: Deserializer<'de>,
+{
+    struct LenientIntVisitor<T>(PhantomData<T>);
+
+    impl<'de, T> Visitor<'de> for LenientIntVisitor<T>
+    where
+        T: TryFrom<i64> + TryFrom<u64> + FromStr,
+    {
+        type Value = T;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("an integer or a byte string containing an integer")
+        }
+
+        fn visit_i64<E>(self, value: i64) -> Result<T, E>
+        where
+            E: de::Error,
+        {
+            T::try_from(value).map_err(|_| E::invalid_value(Unexpected::Signed(value), &self))
+        }
+
+        fn visit_u64<E>(self, value: u64) -> Result<T, E>
+        where
+            E: de::Error,
+        {
+            T::try_from(value).map_err(|_| E::invalid_value(Unexpected::Unsigned(value), &self))
+        }
+
+        fn visit_str<E>(self, value: &str) -> Result<T, E>
+        where
+            E: de::Error,
+        {
+            value
+                .parse()
+                .map_err(|_| E::invalid_value(Unexpected::Str(value), &self))
+        }
+
+        fn visit_bytes<E>(self, value: &[u8]) -> Result<T, E>
+        where
+            E: de::Error,
+        {
+            str::from_utf8(value)
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .ok_or_else(|| E::invalid_value(Unexpected::Bytes(value), &self))
+        }
+    }
+
+    deserializer.deserialize_any(LenientIntVisitor(PhantomData))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Result;
+
+    #[test]
+    fn test_lenient_int() -> Result<()> {
+        assert_eq!(
+            crate::from_slice::<LenientInt<i64>>(b"i-5e")?,
+            LenientInt(-5)
+        );
+        assert_eq!(
+            crate::from_slice::<LenientInt<i64>>(b"2:-5")?,
+            LenientInt(-5)
+        );
+        assert_eq!(
+            crate::from_slice::<LenientInt<u8>>(b"3:255")?,
+            LenientInt(255)
+        );
+        assert_eq!(crate::to_vec(&LenientInt(7u16))?, b"i7e");
+
+        assert!(crate::from_slice::<LenientInt<u8>>(b"i256e").is_err());
+        assert!(crate::from_slice::<LenientInt<u8>>(b"i-1e").is_err());
+        assert!(crate::from_slice::<LenientInt<u8>>(b"3:256").is_err());
+        assert!(crate::from_slice::<LenientInt<u8>>(b"1:a").is_err());
+        assert!(crate::from_slice::<LenientInt<u8>>(b"0:").is_err());
+        assert!(crate::from_slice::<LenientInt<u8>>(b"le").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,225 @@
+//! Accepts either a single value or a list of values.
+//!
+//! Many fields appear as both a single value and a list of values in the
+//! wild (e.g. a single URL or a list of URLs). Either form is accepted when
+//! deserializing and the values are always serialized as a list.
+//!
+//! A single value which is itself a list cannot be distinguished from a list
+//! of values, so `T` should not be a sequence type.
+//!
+//! ```rust
+//! use bt_bencode::serde_helpers::OneOrMany;
+//! use serde_derive::{Deserialize, Serialize};
+//!
+//! #[derive(Debug, PartialEq, Serialize, Deserialize)]
+//! struct Seeds {
+//!     #[serde(with = "bt_bencode::serde_helpers::one_or_many")]
+//!     a: Vec<String>,
+//!     b: OneOrMany<u64>,
+//! }
+//!
+//! let seeds: Seeds = bt_bencode::from_slice(b"d1:a8:http://a1:bli1ei2eee")?;
+//! assert_eq!(seeds.a, vec![String::from("http://a")]);
+//! assert_eq!(seeds.b.as_slice(), &[1, 2]);
+//! assert_eq!(bt_bencode::to_vec(&seeds)?, b"d1:al8:http://ae1:bli1ei2eee");
+//! # Ok::<(), bt_bencode::Error>(())
+//! ```
+
+use core::{fmt, marker::PhantomData};
+use serde::{
+    de::{
+        self,
+        value::{BorrowedBytesDeserializer, BorrowedStrDeserializer, MapAccessDeserializer},
+        Deserialize, Deserializer, IntoDeserializer, MapAccess, SeqAccess, Visitor,
+    },
+    ser::{Serialize, Serializer},
+};
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{vec, vec::Vec};
+#[cfg(feature = "std")]
+use std::{vec, vec::Vec};
+
+/// Values which are deserialized from either a single value or a list.
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct OneOrMany<T>(pub Vec<T>);
+
+impl<T> OneOrMany<T> {
+    /// Returns the values as a slice.
+    #[must_use]
+    pub fn as_slice(&self) -> &[T] {
+        self.0.as_slice()
+    }
+
+    /// Returns the values.
+    #[must_use]
+    pub fn into_vec(self) -> Vec<T> {
+        self.0
+    }
+}
+
+impl<T> From<Vec<T>> for OneOrMany<T> {
+    fn from(values: Vec<T>) -> Self {
+        OneOrMany(values)
+    }
+}
+
+impl<T: Serialize> Serialize for OneOrMany<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for OneOrMany<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserialize(deserializer).map(OneOrMany)
+    }
+}
+
+/// Serializes the values as a list.
+pub fn serialize<T, S>(values: &[T], serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Serialize,
+    S: Serializer,
+{
+    values.serialize(serializer)
+}
+
+/// Deserializes either a single value or a list of values.
+///
+/// # Errors
+///
+/// An error is returned if the value or any value in the list cannot be
+/// deserialized as a `T`.
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    struct OneOrManyVisitor<T>(PhantomData<T>);
+
+    impl<'de, T: Deserialize<'de>> Visitor<'de> for OneOrManyVisitor<T> {
+        type Value = Vec<T>;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("a value or a list of values")
+        }
+
+        fn visit_i64<E>(self, value: i64) -> Result<Vec<T>, E>
+        where
+            E: de::Error,
+        {
+            T::deserialize(value.into_deserializer()).map(|v| vec![v])
+        }
+
+        fn visit_u64<E>(self, value: u64) -> Result<Vec<T>, E>
+        where
+            E: de::Error,
+        {
+            T::deserialize(value.into_deserializer()).map(|v| vec![v])
+        }
+
+        fn visit_borrowed_str<E>(self, value: &'de str) -> Result<Vec<T>, E>
+        where
+            E: de::Error,
+        {
+            T::deserialize(BorrowedStrDeserializer::new(value)).map(|v| vec![v])
+        }
+
+        fn visit_str<E>(self, value: &str) -> Result<Vec<T>, E>
+        where
+            E: de::Error,
+        {
+            T::deserialize(value.into_deserializer()).map(|v| vec![v])
+        }
+
+        fn visit_borrowed_bytes<E>(self, value: &'de [u8]) -> Result<Vec<T>, E>
+        where
+            E: de::Error,
+        {
+            T::deserialize(BorrowedBytesDeserializer::new(value)).map(|v| vec![v])
+        }
+
+        fn visit_bytes<E>(self, value: &[u8]) -> Result<Vec<T>, E>
+        where
+            E: de::Error,
+        {
+            T::deserialize(value.into_deserializer()).map(|v| vec![v])
+        }
+
+        fn visit_seq<V>(self, mut seq: V) -> Result<Vec<T>, V::Error>
+        where
+            V: SeqAccess<'de>,
+        {
+            let mut values = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+            while let Some(value) = seq.next_element()? {
+                values.push(value);
+            }
+            Ok(values)
+        }
+
+        fn visit_map<V>(self, map: V) -> Result<Vec<T>, V::Error>
+        where
+            V: MapAccess<'de>,
+        {
+            T::deserialize(MapAccessDeserializer::new(map)).map(|v| vec![v])
+        }
+    }
+
+    deserializer.deserialize_any(OneOrManyVisitor(PhantomData))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Result;
+    use serde_bytes::ByteBuf;
+    use serde_derive::Deserialize;
+
+    #[cfg(all(feature = "alloc", not(feature = "std")))]
+    use alloc::string::String;
+
+    #[test]
+    fn test_one_or_many() -> Result<()> {
+        let one: OneOrMany<i64> = crate::from_slice(b"i-1e")?;
+        assert_eq!(one.as_slice(), &[-1]);
+        assert_eq!(crate::to_vec(&one)?, b"li-1ee");
+
+        let many: OneOrMany<ByteBuf> = crate::from_slice(b"l1:a1:be")?;
+        assert_eq!(many.as_slice().len(), 2);
+        let one: OneOrMany<ByteBuf> = crate::from_slice(b"2:\xff\xfe")?;
+        assert_eq!(one.into_vec(), vec![ByteBuf::from(vec![0xff, 0xfe])]);
+
+        let empty: OneOrMany<String> = crate::from_slice(b"le")?;
+        assert!(empty.as_slice().is_empty());
+
+        assert!(crate::from_slice::<OneOrMany<String>>(b"i1e").is_err());
+        assert!(crate::from_slice::<OneOrMany<String>>(b"l1:ai1ee").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_one_or_many_dicts() -> Result<()> {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Node {
+            ip: String,
+        }
+
+        let one: OneOrMany<Node> = crate::from_slice(b"d2:ip1:ae")?;
+        assert_eq!(
+            one.as_slice(),
+            &[Node {
+                ip: String::from("a")
+            }]
+        );
+        let many: OneOrMany<Node> = crate::from_slice(b"ld2:ip1:aed2:ip1:bee")?;
+        assert_eq!(many.as_slice().len(), 2);
+        Ok(())
+    }
+}
@@ -0,0 +1,203 @@
+//! Converts byte strings to and from [`OsString`]s and [`PathBuf`][std::path::PathBuf]s.
+//!
+//! File paths in metainfo files are not guaranteed to be UTF-8. On Unix, the
+//! bytes are used as the platform's native bytes without any conversion. On
+//! other platforms, the bytes must be valid UTF-8; the [`lossy`] module
+//! replaces invalid sequences with `U+FFFD` instead of failing.
+//!
+//! ```rust
+//! use serde_derive::{Deserialize, Serialize};
+//! use std::path::PathBuf;
+//!
+//! #[derive(Debug, PartialEq, Serialize, Deserialize)]
+//! struct File {
+//!     #[serde(with = "bt_bencode::serde_helpers::os_string")]
+//!     path: PathBuf,
+//! }
+//!
+//! let file: File = bt_bencode::from_slice(b"d4:path9:a/b/c.txte")?;
+//! assert_eq!(file.path, PathBuf::from("a/b/c.txt"));
+//! assert_eq!(bt_bencode::to_vec(&file)?, b"d4:path9:a/b/c.txte");
+//! # Ok::<(), bt_bencode::Error>(())
+//! ```
+
+use crate::error::Result;
+use serde::{de, ser, Deserialize, Deserializer, Serializer};
+use serde_bytes::ByteBuf;
+use std::{
+    borrow::Cow,
+    ffi::{OsStr, OsString},
+    vec::Vec,
+};
+
+#[cfg(not(unix))]
+use crate::error::Error;
+#[cfg(not(unix))]
+use std::string::String;
+
+/// Converts bytes to an [`OsString`].
+///
+/// # Errors
+///
+/// On platforms other than Unix, an error is returned if the bytes are not valid UTF-8.
+pub fn from_bytes(bytes: Vec<u8>) -> Result<OsString> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStringExt;
+
+        Ok(OsString::from_vec(bytes))
+    }
+    #[cfg(not(unix))]
+    {
+        String::from_utf8(bytes)
+            .map(OsString::from)
+            .map_err(|e| Error::from(e.utf8_error()))
+    }
+}
+
+/// Converts bytes to an [`OsString`], replacing invalid UTF-8 on platforms other than Unix.
+#[must_use]
+pub fn from_bytes_lossy(bytes: Vec<u8>) -> OsString {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStringExt;
+
+        OsString::from_vec(bytes)
+    }
+    #[cfg(not(unix))]
+    {
+        OsString::from(String::from_utf8_lossy(&bytes).into_owned())
+    }
+}
+
+/// Converts an [`OsStr`] to bytes.
+///
+/// # Errors
+///
+/// On platforms other than Unix, an error is returned if the string is not valid Unicode.
+pub fn to_bytes(s: &OsStr) -> Result<Cow<'_, [u8]>> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+
+        Ok(Cow::Borrowed(s.as_bytes()))
+    }
+    #[cfg(not(unix))]
+    {
+        s.to_str()
+            .map(|s| Cow::Borrowed(s.as_bytes()))
+            .ok_or_else(|| Error::Serialize(String::from("OS string is not valid Unicode")))
+    }
+}
+
+/// Serializes the value as a byte string.
+///
+/// # Errors
+///
+/// On platforms other than Unix, an error is returned if the value is not valid Unicode.
+pub fn serialize<T, S>(value: &T, serializer: S) -> core::result::Result<S::Ok, S::Error>
+where
+    T: AsRef<OsStr> + ?Sized,
+    S: Serializer,
+{
+    let bytes = to_bytes(value.as_ref()).map_err(ser::Error::custom)?;
+    serializer.serialize_bytes(&bytes)
+}
+
+/// Deserializes the value from a byte string.
+///
+/// # Errors
+///
+/// An error is returned if the value is not a byte string. On platforms other
+/// than Unix, an error is also returned if the byte string is not valid UTF-8.
+pub fn deserialize<'de, T, D>(deserializer: D) -> core::result::Result<T, D::Error>
+where
+    T: From<OsString>,
+    D: Deserializer<'de>,
+{
+    let bytes = ByteBuf::deserialize(deserializer)?;
+    from_bytes(bytes.into_vec())
+        .map(T::from)
+        .map_err(de::Error::custom)
+}
+
+/// Converts byte strings, replacing invalid UTF-8 on platforms other than Unix.
+pub mod lossy {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use serde_bytes::ByteBuf;
+    use std::ffi::{OsStr, OsString};
+
+    /// Serializes the value as a byte string.
+    ///
+    /// # Errors
+    ///
+    /// On platforms other than Unix, an error is returned if the value is not valid Unicode.
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: AsRef<OsStr> + ?Sized,
+        S: Serializer,
+    {
+        super::serialize(value, serializer)
+    }
+
+    /// Deserializes the value from a byte string.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the value is not a byte string.
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: From<OsString>,
+        D: Deserializer<'de>,
+    {
+        let bytes = ByteBuf::deserialize(deserializer)?;
+        Ok(T::from(super::from_bytes_lossy(bytes.into_vec())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_derive::{Deserialize, Serialize};
+    use std::path::PathBuf;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct File {
+        #[serde(with = "super")]
+        name: OsString,
+        #[serde(with = "super::lossy")]
+        path: PathBuf,
+    }
+
+    #[test]
+    fn test_utf8() -> Result<()> {
+        let input = b"d4:name4:spam4:path5:a.txte";
+        let file: File = crate::from_slice(input)?;
+        assert_eq!(file.name, OsString::from("spam"));
+        assert_eq!(file.path, PathBuf::from("a.txt"));
+        assert_eq!(crate::to_vec(&file)?, &input[..]);
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_native_bytes() -> Result<()> {
+        use std::os::unix::ffi::OsStrExt;
+
+        let input = b"d4:name2:\xff\xfe4:path1:\xffe";
+        let file: File = crate::from_slice(input)?;
+        assert_eq!(file.name.as_bytes(), b"\xff\xfe");
+        assert_eq!(file.path.as_os_str().as_bytes(), b"\xff");
+        assert_eq!(crate::to_vec(&file)?, &input[..]);
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    #[test]
+    fn test_lossy() -> Result<()> {
+        assert!(crate::from_slice::<File>(b"d4:name2:\xff\xfe4:path1:ae").is_err());
+        let file: File = crate::from_slice(b"d4:name1:a4:path1:\xffe")?;
+        assert_eq!(file.path, PathBuf::from("\u{fffd}"));
+        Ok(())
+    }
+}
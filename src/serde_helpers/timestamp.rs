@@ -0,0 +1,378 @@
+//! Converts integer Unix timestamps such as the `creation date` field.
+//!
+//! The functions in this module convert between the number of seconds since
+//! the Unix epoch and [`SystemTime`][std::time::SystemTime]. The
+//! [`offset_date_time`] and [`chrono`] modules convert to the types from the
+//! `time` and `chrono` crates when the features of the same name are
+//! enabled.
+//!
+//! Files in the wild contain timestamps which cannot be represented, such as
+//! values in milliseconds overflowing the target type or unsigned values
+//! larger than [`i64::MAX`]. The `option` modules deserialize such values as
+//! `None` instead of failing. Bencode cannot represent `None`, so optional
+//! fields should also be skipped when serializing if they are `None`.
+//!
+//! ```rust
+//! # #[cfg(feature = "std")]
+//! # {
+//! use serde_derive::{Deserialize, Serialize};
+//! use std::time::{Duration, SystemTime, UNIX_EPOCH};
+//!
+//! #[derive(Debug, PartialEq, Serialize, Deserialize)]
+//! struct Torrent {
+//!     #[serde(
+//!         rename = "creation date",
+//!         default,
+//!         with = "bt_bencode::serde_helpers::timestamp::option",
+//!         skip_serializing_if = "Option::is_none"
+//!     )]
+//!     creation_date: Option<SystemTime>,
+//! }
+//!
+//! let torrent: Torrent = bt_bencode::from_slice(b"d13:creation datei1600000000ee")?;
+//! assert_eq!(
+//!     torrent.creation_date,
+//!     Some(UNIX_EPOCH + Duration::from_secs(1_600_000_000))
+//! );
+//! assert_eq!(bt_bencode::to_vec(&torrent)?, b"d13:creation datei1600000000ee");
+//!
+//! let torrent: Torrent = bt_bencode::from_slice(b"d13:creation datei18446744073709551615ee")?;
+//! assert_eq!(torrent.creation_date, None);
+//! # }
+//! # Ok::<(), bt_bencode::Error>(())
+//! ```
+
+use core::fmt;
+use serde::de::{self, Deserializer, Visitor};
+
+/// Deserializes an integer as seconds, returning `None` if it does not fit in an [`i64`].
+fn deserialize_secs<'de, D>(deserializer: D) -> Result<Option<i64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct SecsVisitor;
+
+    impl<'de> Visitor<'de> for SecsVisitor {
+        type Value = Option<i64>;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("an integer timestamp")
+        }
+
+        fn visit_i64<E>(self, value: i64) -> Result<Option<i64>, E> {
+            Ok(Some(value))
+        }
+
+        fn visit_u64<E>(self, value: u64) -> Result<Option<i64>, E> {
+            Ok(if value > i64::MAX as u64 {
+                None
+            } else {
+                Some(value as i64)
+            })
+        }
+    }
+
+    deserializer.deserialize_i64(SecsVisitor)
+}
+
+fn out_of_range<E: de::Error>() -> E {
+    E::custom("timestamp out of range")
+}
+
+#[cfg(feature = "std")]
+pub(crate) mod system_time {
+    use std::{
+        convert::TryFrom,
+        time::{Duration, SystemTime, UNIX_EPOCH},
+    };
+
+    pub(crate) fn from_secs(secs: i64) -> Option<SystemTime> {
+        if secs >= 0 {
+            UNIX_EPOCH.checked_add(Duration::from_secs(secs as u64))
+        } else {
+            secs.checked_neg()
+                .and_then(|secs| UNIX_EPOCH.checked_sub(Duration::from_secs(secs as u64)))
+        }
+    }
+
+    pub(crate) fn to_secs(time: SystemTime) -> Option<i64> {
+        match time.duration_since(UNIX_EPOCH) {
+            Ok(d) => i64::try_from(d.as_secs()).ok(),
+            Err(e) => i64::try_from(e.duration().as_secs()).ok().map(|secs| -secs),
+        }
+    }
+}
+
+/// Serializes a [`SystemTime`][std::time::SystemTime] as seconds since the Unix epoch.
+///
+/// # Errors
+///
+/// An error is returned if the time is too far from the epoch to fit in an [`i64`].
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub fn serialize<S>(time: &std::time::SystemTime, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    let secs = system_time::to_secs(*time)
+        .ok_or_else(|| <S::Error as serde::ser::Error>::custom("timestamp out of range"))?;
+    serializer.serialize_i64(secs)
+}
+
+/// Deserializes seconds since the Unix epoch as a [`SystemTime`][std::time::SystemTime].
+///
+/// # Errors
+///
+/// An error is returned if the value is not an integer or cannot be represented.
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub fn deserialize<'de, D>(deserializer: D) -> Result<std::time::SystemTime, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserialize_secs(deserializer)?
+        .and_then(system_time::from_secs)
+        .ok_or_else(out_of_range)
+}
+
+/// Converts an optional [`SystemTime`][std::time::SystemTime].
+///
+/// Timestamps which cannot be represented are deserialized as `None`.
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub mod option {
+    use serde::{Deserializer, Serializer};
+    use std::time::SystemTime;
+
+    /// Serializes an optional time as seconds since the Unix epoch.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the time is too far from the epoch to fit in an [`i64`].
+    pub fn serialize<S>(time: &Option<SystemTime>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match time {
+            Some(time) => super::serialize(time, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    /// Deserializes seconds since the Unix epoch as an optional time.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the value is not an integer.
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<SystemTime>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(super::deserialize_secs(deserializer)?.and_then(super::system_time::from_secs))
+    }
+}
+
+/// Converts a [`time::OffsetDateTime`] in UTC.
+#[cfg(feature = "time")]
+#[cfg_attr(docsrs, doc(cfg(feature = "time")))]
+pub mod offset_date_time {
+    use serde::{Deserializer, Serializer};
+    use time::OffsetDateTime;
+
+    /// Serializes a date time as seconds since the Unix epoch.
+    pub fn serialize<S>(time: &OffsetDateTime, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_i64(time.unix_timestamp())
+    }
+
+    /// Deserializes seconds since the Unix epoch as a date time.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the value is not an integer or cannot be represented.
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<OffsetDateTime, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        super::deserialize_secs(deserializer)?
+            .and_then(|secs| OffsetDateTime::from_unix_timestamp(secs).ok())
+            .ok_or_else(super::out_of_range)
+    }
+
+    /// Converts an optional [`time::OffsetDateTime`] in UTC.
+    ///
+    /// Timestamps which cannot be represented are deserialized as `None`.
+    pub mod option {
+        use serde::{Deserializer, Serializer};
+        use time::OffsetDateTime;
+
+        /// Serializes an optional date time as seconds since the Unix epoch.
+        pub fn serialize<S>(time: &Option<OffsetDateTime>, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            match time {
+                Some(time) => super::serialize(time, serializer),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        /// Deserializes seconds since the Unix epoch as an optional date time.
+        ///
+        /// # Errors
+        ///
+        /// An error is returned if the value is not an integer.
+        pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<OffsetDateTime>, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            Ok(super::super::deserialize_secs(deserializer)?
+                .and_then(|secs| OffsetDateTime::from_unix_timestamp(secs).ok()))
+        }
+    }
+}
+
+/// Converts a [`DateTime`][::chrono::DateTime] in UTC.
+#[cfg(feature = "chrono")]
+#[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
+pub mod chrono {
+    use chrono::{DateTime, Utc};
+    use serde::{Deserializer, Serializer};
+
+    /// Serializes a date time as seconds since the Unix epoch.
+    pub fn serialize<S>(time: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_i64(time.timestamp())
+    }
+
+    /// Deserializes seconds since the Unix epoch as a date time.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the value is not an integer or cannot be represented.
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        super::deserialize_secs(deserializer)?
+            .and_then(|secs| DateTime::from_timestamp(secs, 0))
+            .ok_or_else(super::out_of_range)
+    }
+
+    /// Converts an optional [`chrono::DateTime`] in UTC.
+    ///
+    /// Timestamps which cannot be represented are deserialized as `None`.
+    pub mod option {
+        use chrono::{DateTime, Utc};
+        use serde::{Deserializer, Serializer};
+
+        /// Serializes an optional date time as seconds since the Unix epoch.
+        pub fn serialize<S>(time: &Option<DateTime<Utc>>, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            match time {
+                Some(time) => super::serialize(time, serializer),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        /// Deserializes seconds since the Unix epoch as an optional date time.
+        ///
+        /// # Errors
+        ///
+        /// An error is returned if the value is not an integer.
+        pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            Ok(super::super::deserialize_secs(deserializer)?
+                .and_then(|secs| DateTime::from_timestamp(secs, 0)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Result;
+    use serde_derive::{Deserialize, Serialize};
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_system_time() -> Result<()> {
+        use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Times {
+            #[serde(with = "super")]
+            a: SystemTime,
+            #[serde(default, with = "super::option")]
+            b: Option<SystemTime>,
+        }
+
+        let times: Times = crate::from_slice(b"d1:ai-10e1:bi5ee")?;
+        assert_eq!(times.a, UNIX_EPOCH - Duration::from_secs(10));
+        assert_eq!(times.b, Some(UNIX_EPOCH + Duration::from_secs(5)));
+        assert_eq!(crate::to_vec(&times)?, b"d1:ai-10e1:bi5ee");
+
+        let times: Times = crate::from_slice(b"d1:ai0e1:bi-9223372036854775808ee")?;
+        assert_eq!(times.b, None);
+        assert!(crate::from_slice::<Times>(b"d1:ai18446744073709551615ee").is_err());
+        assert!(crate::from_slice::<Times>(b"d1:a1:1e").is_err());
+        Ok(())
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn test_offset_date_time() -> Result<()> {
+        use time::OffsetDateTime;
+
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Times {
+            #[serde(with = "super::offset_date_time")]
+            a: OffsetDateTime,
+            #[serde(
+                default,
+                with = "super::offset_date_time::option",
+                skip_serializing_if = "Option::is_none"
+            )]
+            b: Option<OffsetDateTime>,
+        }
+
+        let times: Times = crate::from_slice(b"d1:ai1600000000e1:bi9223372036854775807ee")?;
+        assert_eq!(times.a.year(), 2020);
+        assert_eq!(times.b, None);
+        assert_eq!(crate::to_vec(&times)?, b"d1:ai1600000000ee");
+        assert!(crate::from_slice::<Times>(b"d1:ai9223372036854775807ee").is_err());
+        Ok(())
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_chrono() -> Result<()> {
+        use ::chrono::{DateTime, Datelike, Utc};
+
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Times {
+            #[serde(with = "super::chrono")]
+            a: DateTime<Utc>,
+            #[serde(
+                default,
+                with = "super::chrono::option",
+                skip_serializing_if = "Option::is_none"
+            )]
+            b: Option<DateTime<Utc>>,
+        }
+
+        let times: Times = crate::from_slice(b"d1:ai1600000000e1:bi9223372036854775807ee")?;
+        assert_eq!(times.a.year(), 2020);
+        assert_eq!(times.b, None);
+        assert_eq!(crate::to_vec(&times)?, b"d1:ai1600000000ee");
+        assert!(crate::from_slice::<Times>(b"d1:ai9223372036854775807ee").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,271 @@
+//! Parses URL fields such as `announce` and `url-list` as [`Url`]s.
+//!
+//! URLs are validated when the data is deserialized. An invalid URL is an
+//! error which includes the URL and the reason it is invalid. URLs are
+//! serialized as byte strings.
+//!
+//! The module handles a single URL. The [`option`], [`seq`], and [`tiers`]
+//! modules handle an optional URL, a list of URLs (e.g. `url-list`), and a
+//! list of lists of URLs (e.g. `announce-list`).
+//!
+//! ```rust
+//! use serde_derive::{Deserialize, Serialize};
+//! use url::Url;
+//!
+//! #[derive(Debug, PartialEq, Serialize, Deserialize)]
+//! struct Torrent {
+//!     #[serde(with = "bt_bencode::serde_helpers::url")]
+//!     announce: Url,
+//!     #[serde(
+//!         rename = "announce-list",
+//!         default,
+//!         with = "bt_bencode::serde_helpers::url::tiers"
+//!     )]
+//!     announce_list: Vec<Vec<Url>>,
+//! }
+//!
+//! let input = b"d8:announce17:http://a/announce13:announce-listll17:http://a/announceeee";
+//! let torrent: Torrent = bt_bencode::from_slice(input)?;
+//! assert_eq!(torrent.announce.host_str(), Some("a"));
+//! assert_eq!(torrent.announce_list, vec![vec![torrent.announce.clone()]]);
+//! assert_eq!(bt_bencode::to_vec(&torrent)?, &input[..]);
+//!
+//! assert!(bt_bencode::from_slice::<Torrent>(b"d8:announce5:a/b/ce").is_err());
+//! # Ok::<(), bt_bencode::Error>(())
+//! ```
+
+use ::url::Url;
+use core::fmt;
+use serde::{
+    de::{self, Deserialize, Deserializer, Unexpected, Visitor},
+    ser::{Serialize, Serializer},
+};
+use std::str;
+
+#[cfg(feature = "torrent")]
+use std::vec::Vec;
+
+/// A URL which is deserialized with a descriptive error.
+struct ParsedUrl(Url);
+
+impl<'de> Deserialize<'de> for ParsedUrl {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct UrlVisitor;
+
+        impl<'de> Visitor<'de> for UrlVisitor {
+            type Value = ParsedUrl;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a URL")
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<ParsedUrl, E>
+            where
+                E: de::Error,
+            {
+                Url::parse(value)
+                    .map(ParsedUrl)
+                    .map_err(|e| E::custom(format_args!("invalid URL {:?}: {}", value, e)))
+            }
+
+            fn visit_bytes<E>(self, value: &[u8]) -> Result<ParsedUrl, E>
+            where
+                E: de::Error,
+            {
+                let value = str::from_utf8(value)
+                    .map_err(|_| E::invalid_value(Unexpected::Bytes(value), &self))?;
+                self.visit_str(value)
+            }
+        }
+
+        deserializer.deserialize_str(UrlVisitor)
+    }
+}
+
+/// Serializes a URL as a byte string.
+pub fn serialize<S>(url: &Url, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_bytes(url.as_str().as_bytes())
+}
+
+/// Deserializes a URL from a byte string.
+///
+/// # Errors
+///
+/// An error is returned if the value is not a byte string containing a valid URL.
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Url, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    ParsedUrl::deserialize(deserializer).map(|url| url.0)
+}
+
+/// Converts an optional URL.
+///
+/// Use with `#[serde(default, skip_serializing_if = "Option::is_none")]`.
+pub mod option {
+    use super::ParsedUrl;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use url::Url;
+
+    /// Serializes a URL as a byte string.
+    pub fn serialize<S>(url: &Option<Url>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match url {
+            Some(url) => super::serialize(url, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    /// Deserializes a URL from a byte string.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the value is not a byte string containing a valid URL.
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Url>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        ParsedUrl::deserialize(deserializer).map(|url| Some(url.0))
+    }
+}
+
+/// Converts a list of URLs.
+pub mod seq {
+    use super::ParsedUrl;
+    use serde::{ser::SerializeSeq, Deserialize, Deserializer, Serializer};
+    use std::vec::Vec;
+    use url::Url;
+
+    /// Serializes URLs as a list of byte strings.
+    pub fn serialize<S>(urls: &[Url], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut seq = serializer.serialize_seq(Some(urls.len()))?;
+        for url in urls {
+            seq.serialize_element(serde_bytes::Bytes::new(url.as_str().as_bytes()))?;
+        }
+        seq.end()
+    }
+
+    /// Deserializes URLs from a list of byte strings.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the value is not a list of byte strings containing valid URLs.
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<Url>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(Vec::<ParsedUrl>::deserialize(deserializer)?
+            .into_iter()
+            .map(|url| url.0)
+            .collect())
+    }
+}
+
+/// Converts tiers of URLs.
+pub mod tiers {
+    use super::ParsedUrl;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::vec::Vec;
+    use url::Url;
+
+    /// Serializes tiers of URLs as a list of lists of byte strings.
+    pub fn serialize<S>(tiers: &[Vec<Url>], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_seq(tiers.iter().map(|tier| super::Urls(tier)))
+    }
+
+    /// Deserializes tiers of URLs from a list of lists of byte strings.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the value is not a list of lists of byte
+    /// strings containing valid URLs.
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<Vec<Url>>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(Vec::<Vec<ParsedUrl>>::deserialize(deserializer)?
+            .into_iter()
+            .map(|tier| tier.into_iter().map(|url| url.0).collect())
+            .collect())
+    }
+}
+
+/// Serializes a slice of URLs as a list of byte strings.
+struct Urls<'a>(&'a [Url]);
+
+impl<'a> Serialize for Urls<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        seq::serialize(self.0, serializer)
+    }
+}
+
+/// Parses a list of URL strings.
+#[cfg(feature = "torrent")]
+pub(crate) fn parse_all<'a, I>(urls: I) -> crate::error::Result<Vec<Url>>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    urls.into_iter()
+        .map(|url| {
+            Url::parse(url)
+                .map_err(|e| crate::Error::Deserialize(format!("invalid URL {:?}: {}", url, e)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Result;
+    use serde_derive::{Deserialize, Serialize};
+    use url::Url;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Seeds {
+        #[serde(
+            default,
+            with = "super::option",
+            skip_serializing_if = "Option::is_none"
+        )]
+        a: Option<Url>,
+        #[serde(with = "super::seq")]
+        b: Vec<Url>,
+    }
+
+    #[test]
+    fn test_urls() -> Result<()> {
+        let input = b"d1:bl9:http://a/9:http://b/ee";
+        let seeds: Seeds = crate::from_slice(input)?;
+        assert_eq!(seeds.a, None);
+        assert_eq!(seeds.b.len(), 2);
+        assert_eq!(crate::to_vec(&seeds)?, &input[..]);
+
+        let seeds: Seeds = crate::from_slice(b"d1:a9:http://c/1:blee")?;
+        assert_eq!(seeds.a.as_ref().map(Url::as_str), Some("http://c/"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_url() {
+        let err = crate::from_slice::<Seeds>(b"d1:bl1:xee").unwrap_err();
+        assert!(err.to_string().contains("invalid URL \"x\""), "{}", err);
+        assert!(crate::from_slice::<Seeds>(b"d1:bl1:\xffee").is_err());
+        assert!(crate::from_slice::<Seeds>(b"d1:bli1eee").is_err());
+    }
+}
@@ -0,0 +1,837 @@
+//! Types for BitTorrent metainfo (`.torrent`) files.
+//!
+//! The types model the common fields from [BEP 3][bep_0003],
+//! [BEP 12][bep_0012], and the BitTorrent v2 fields from [BEP 52][bep_0052].
+//! Fields which are not modeled are ignored when deserializing.
+//!
+//! ```rust
+//! use bt_bencode::torrent::MetaInfo;
+//!
+//! let input = b"d8:announce19:http://example.com/4:infod6:lengthi4e4:name8:spam.txt12:piece lengthi16384e6:pieces20:aaaaaaaaaaaaaaaaaaaaee";
+//! let metainfo = MetaInfo::from_slice(input)?;
+//!
+//! assert_eq!(metainfo.announce.as_deref(), Some("http://example.com/"));
+//! assert_eq!(metainfo.info.name, b"spam.txt");
+//! assert_eq!(metainfo.info.total_length(), 4);
+//! assert_eq!(metainfo.to_vec()?, &input[..]);
+//! # Ok::<(), bt_bencode::Error>(())
+//! ```
+//!
+//! [bep_0003]: https://www.bittorrent.org/beps/bep_0003.html
+//! [bep_0012]: https://www.bittorrent.org/beps/bep_0012.html
+//! [bep_0052]: https://www.bittorrent.org/beps/bep_0052.html
+
+mod attr;
+#[cfg(all(feature = "std", feature = "sha1"))]
+mod builder;
+mod editor;
+#[cfg(feature = "encoding_rs")]
+mod encoding;
+mod file_tree;
+mod magnet;
+#[cfg(feature = "std")]
+mod path;
+mod pieces;
+mod url_list;
+
+pub use attr::FileFlags;
+#[cfg(all(feature = "std", feature = "sha1"))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "std", feature = "sha1"))))]
+pub use builder::{TorrentBuilder, Version};
+pub use editor::{DictEditor, Editor};
+#[cfg(feature = "encoding_rs")]
+#[cfg_attr(docsrs, doc(cfg(feature = "encoding_rs")))]
+pub use encoding::{decode_text, decode_text_lossy};
+pub use file_tree::{FileAttrs, FileTree, FileTreeNode};
+pub use magnet::MagnetLink;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub use path::safe_path;
+pub use pieces::{Pieces, PiecesIter};
+pub use url_list::UrlList;
+
+use crate::{error::Result, key::ByteStrKey};
+use serde_bytes::ByteBuf;
+use serde_derive::{Deserialize, Serialize};
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{collections::BTreeMap, string::String, vec, vec::Vec};
+#[cfg(feature = "std")]
+use std::{collections::BTreeMap, string::String, vec::Vec};
+
+/// The length of a SHA-1 piece hash in the `pieces` field.
+pub const PIECE_HASH_LEN: usize = 20;
+
+/// The raw encoding of the `info` value in a metainfo file.
+#[cfg(any(feature = "sha1", feature = "sha2"))]
+#[derive(Deserialize)]
+struct RawInfo<'a> {
+    #[serde(borrow)]
+    info: crate::value::RawValue<'a>,
+}
+
+/// Returns the exact bytes of the `info` dictionary in an encoded metainfo file.
+#[cfg(any(feature = "sha1", feature = "sha2"))]
+fn raw_info(s: &[u8]) -> Result<&[u8]> {
+    let raw: RawInfo<'_> = crate::from_slice(s)?;
+    let info = raw.info.as_bytes();
+    if info.first() != Some(&b'd') {
+        return Err(crate::Error::Deserialize(String::from(
+            "the info value is not a dictionary",
+        )));
+    }
+    Ok(info)
+}
+
+/// Computes the v1 info hash of an encoded metainfo file.
+///
+/// The info hash is the SHA-1 digest of the `info` value exactly as it is
+/// encoded in the original data. The info dictionary is not parsed and
+/// re-serialized, so unknown fields and non-canonical encodings do not change
+/// the hash.
+///
+/// ```rust
+/// let input = b"d4:infod6:lengthi4e4:name8:spam.txt12:piece lengthi16384e6:pieces0:ee";
+/// let info_hash = bt_bencode::torrent::info_hash_v1(input)?;
+/// assert_eq!(info_hash.len(), 20);
+/// # Ok::<(), bt_bencode::Error>(())
+/// ```
+///
+/// # Errors
+///
+/// An error is returned if the data is not a dictionary with an `info`
+/// dictionary.
+#[cfg(feature = "sha1")]
+#[cfg_attr(docsrs, doc(cfg(feature = "sha1")))]
+pub fn info_hash_v1(s: &[u8]) -> Result<[u8; 20]> {
+    use sha1::{Digest, Sha1};
+
+    let info = raw_info(s)?;
+    Ok(Sha1::digest(info).into())
+}
+
+/// Computes the v2 info hash of an encoded metainfo file.
+///
+/// The info hash is the SHA-256 digest of the `info` value exactly as it is
+/// encoded in the original data.
+///
+/// # Errors
+///
+/// An error is returned if the data is not a dictionary with an `info`
+/// dictionary.
+#[cfg(feature = "sha2")]
+#[cfg_attr(docsrs, doc(cfg(feature = "sha2")))]
+pub fn info_hash_v2(s: &[u8]) -> Result<[u8; 32]> {
+    use sha2::{Digest, Sha256};
+
+    let info = raw_info(s)?;
+    Ok(Sha256::digest(info).into())
+}
+
+/// The info hashes of a metainfo file.
+#[cfg(all(feature = "sha1", feature = "sha2"))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "sha1", feature = "sha2"))))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct InfoHashes {
+    /// The v1 info hash if the info dictionary has v1 fields.
+    pub v1: Option<[u8; 20]>,
+    /// The v2 info hash if the info dictionary has v2 fields.
+    pub v2: Option<[u8; 32]>,
+}
+
+/// Computes the info hashes for the versions which an encoded metainfo file supports.
+///
+/// A v1 torrent only has a v1 info hash, a v2 torrent only has a v2 info
+/// hash, and a hybrid torrent has both.
+///
+/// # Errors
+///
+/// An error is returned if the data is not a dictionary with an `info`
+/// dictionary.
+#[cfg(all(feature = "sha1", feature = "sha2"))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "sha1", feature = "sha2"))))]
+pub fn info_hashes(s: &[u8]) -> Result<InfoHashes> {
+    use serde::de::IgnoredAny;
+    use sha1::{Digest, Sha1};
+    use sha2::Sha256;
+
+    #[derive(Deserialize)]
+    struct Versions {
+        pieces: Option<IgnoredAny>,
+        #[serde(rename = "meta version")]
+        meta_version: Option<u64>,
+    }
+
+    let info = raw_info(s)?;
+    let versions: Versions = crate::from_slice(info)?;
+    Ok(InfoHashes {
+        v1: versions.pieces.map(|_| Sha1::digest(info).into()),
+        v2: versions
+            .meta_version
+            .filter(|v| *v == 2)
+            .map(|_| Sha256::digest(info).into()),
+    })
+}
+
+/// A metainfo file.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MetaInfo {
+    /// The URL of the tracker.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub announce: Option<String>,
+    /// Tiers of tracker URLs.
+    #[serde(
+        rename = "announce-list",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub announce_list: Option<Vec<Vec<String>>>,
+    /// A free-form comment.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    /// The name and version of the program which created the file.
+    #[serde(
+        rename = "created by",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub created_by: Option<String>,
+    /// The creation time as seconds since the Unix epoch.
+    #[serde(
+        rename = "creation date",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub creation_date: Option<i64>,
+    /// The string encoding used for the `pieces` and `name` fields.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<String>,
+    /// The [BEP 17][bep_0017] HTTP seed URLs.
+    ///
+    /// [bep_0017]: https://www.bittorrent.org/beps/bep_0017.html
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub httpseeds: Option<UrlList>,
+    /// The info dictionary.
+    pub info: Info,
+    /// The hashes for each piece of the files in a v2 torrent keyed by the
+    /// files' `pieces root`.
+    #[serde(
+        rename = "piece layers",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub piece_layers: Option<BTreeMap<ByteStrKey, ByteBuf>>,
+    /// The [BEP 19][bep_0019] web seed URLs.
+    ///
+    /// [bep_0019]: https://www.bittorrent.org/beps/bep_0019.html
+    #[serde(rename = "url-list", default, skip_serializing_if = "Option::is_none")]
+    pub url_list: Option<UrlList>,
+}
+
+impl MetaInfo {
+    /// Deserializes a metainfo file with [`from_slice()`][crate::from_slice].
+    pub fn from_slice(s: &[u8]) -> Result<Self> {
+        crate::from_slice(s)
+    }
+
+    /// Serializes the metainfo with [`to_vec()`][crate::to_vec].
+    pub fn to_vec(&self) -> Result<Vec<u8>> {
+        crate::to_vec(self)
+    }
+
+    /// Returns the tiers of tracker URLs.
+    ///
+    /// If there is an `announce-list`, its tiers are returned without empty
+    /// URLs, duplicate URLs, or empty tiers. Otherwise, the `announce` URL is
+    /// the only tier.
+    #[must_use]
+    pub fn tracker_tiers(&self) -> Vec<Vec<&str>> {
+        let mut seen: Vec<&str> = Vec::new();
+        let mut tiers = Vec::new();
+        if let Some(announce_list) = &self.announce_list {
+            for tier in announce_list {
+                let mut urls = Vec::new();
+                for url in tier {
+                    if !url.is_empty() && !seen.contains(&url.as_str()) {
+                        seen.push(url);
+                        urls.push(url.as_str());
+                    }
+                }
+                if !urls.is_empty() {
+                    tiers.push(urls);
+                }
+            }
+        }
+        if tiers.is_empty() {
+            if let Some(announce) = self.announce.as_ref().filter(|a| !a.is_empty()) {
+                tiers.push(vec![announce.as_str()]);
+            }
+        }
+        tiers
+    }
+
+    /// Returns every tracker URL without duplicates.
+    ///
+    /// The URLs from the [tiers][MetaInfo::tracker_tiers] are returned in
+    /// order followed by the `announce` URL if it was not in a tier.
+    #[must_use]
+    pub fn trackers(&self) -> Vec<&str> {
+        let mut trackers = self
+            .tracker_tiers()
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+        if let Some(announce) = self.announce.as_ref().filter(|a| !a.is_empty()) {
+            if !trackers.contains(&announce.as_str()) {
+                trackers.push(announce);
+            }
+        }
+        trackers
+    }
+
+    /// Returns the creation time.
+    ///
+    /// `None` is returned if there is no creation date or if it cannot be represented.
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    #[must_use]
+    pub fn creation_time(&self) -> Option<std::time::SystemTime> {
+        self.creation_date
+            .and_then(crate::serde_helpers::timestamp::system_time::from_secs)
+    }
+
+    /// Returns the non-empty web seed URLs.
+    #[must_use]
+    pub fn web_seeds(&self) -> Vec<&str> {
+        self.url_list.iter().flat_map(UrlList::iter).collect()
+    }
+
+    /// Returns the non-empty HTTP seed URLs.
+    #[must_use]
+    pub fn http_seeds(&self) -> Vec<&str> {
+        self.httpseeds.iter().flat_map(UrlList::iter).collect()
+    }
+
+    /// Parses every [tracker URL][MetaInfo::trackers].
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if any URL is invalid.
+    #[cfg(feature = "url")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "url")))]
+    pub fn tracker_urls(&self) -> Result<Vec<url::Url>> {
+        crate::serde_helpers::url::parse_all(self.trackers())
+    }
+
+    /// Parses the non-empty [web seed URLs][MetaInfo::web_seeds].
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if any URL is invalid.
+    #[cfg(feature = "url")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "url")))]
+    pub fn web_seed_urls(&self) -> Result<Vec<url::Url>> {
+        crate::serde_helpers::url::parse_all(self.web_seeds())
+    }
+}
+
+/// The info dictionary of a metainfo file.
+///
+/// A single file v1 torrent has a [`length`][Info::length]. A multiple file
+/// v1 torrent has a list of [`files`][Info::files] and the
+/// [`name`][Info::name] is the name of the top-level directory. A v2 torrent
+/// has a [`file_tree`][Info::file_tree] instead. A hybrid torrent has both
+/// the v1 and v2 fields.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Info {
+    /// The suggested name of the file or directory.
+    pub name: ByteBuf,
+    /// The number of bytes in each piece.
+    #[serde(rename = "piece length")]
+    pub piece_length: u64,
+    /// The concatenated SHA-1 hashes of each piece in a v1 torrent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pieces: Option<ByteBuf>,
+    /// The length of the file for a single file torrent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub length: Option<u64>,
+    /// The files for a multiple file torrent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub files: Option<Vec<FileEntry>>,
+    /// Set to `1` if the torrent is private.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub private: Option<u8>,
+    /// The version of the metainfo format. Set to `2` for v2 torrents.
+    #[serde(
+        rename = "meta version",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub meta_version: Option<u64>,
+    /// The files for a v2 torrent.
+    #[serde(rename = "file tree", default, skip_serializing_if = "Option::is_none")]
+    pub file_tree: Option<FileTree>,
+    /// The root hash of a [BEP 30][bep_0030] merkle torrent's hash tree.
+    ///
+    /// A merkle torrent has a root hash instead of the `pieces` field.
+    ///
+    /// [bep_0030]: https://www.bittorrent.org/beps/bep_0030.html
+    #[serde(rename = "root hash", default, skip_serializing_if = "Option::is_none")]
+    pub root_hash: Option<ByteBuf>,
+    /// The [BEP 47][bep_0047] attributes of the file in a single file torrent.
+    ///
+    /// [bep_0047]: https://www.bittorrent.org/beps/bep_0047.html
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attr: Option<ByteBuf>,
+    /// The path components of the target if the file in a single file torrent
+    /// is a symbolic link.
+    #[serde(
+        rename = "symlink path",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub symlink_path: Option<Vec<ByteBuf>>,
+}
+
+impl Info {
+    /// Returns true if the torrent is private.
+    #[must_use]
+    pub fn is_private(&self) -> bool {
+        self.private.map_or(false, |private| private != 0)
+    }
+
+    /// Returns true if the info dictionary has the v1 fields.
+    #[must_use]
+    pub fn is_v1(&self) -> bool {
+        self.pieces.is_some()
+    }
+
+    /// Returns true if the info dictionary has the v2 fields.
+    #[must_use]
+    pub fn is_v2(&self) -> bool {
+        self.meta_version == Some(2) && self.file_tree.is_some()
+    }
+
+    /// Returns true if the info dictionary is for a [BEP 30][bep_0030] merkle torrent.
+    ///
+    /// [bep_0030]: https://www.bittorrent.org/beps/bep_0030.html
+    #[must_use]
+    pub fn is_merkle(&self) -> bool {
+        self.root_hash.is_some()
+    }
+
+    /// Returns the attribute flags of the file in a single file torrent.
+    #[must_use]
+    pub fn flags(&self) -> FileFlags {
+        attr::flags(&self.attr)
+    }
+
+    /// Returns true if the info dictionary has both the v1 and v2 fields.
+    #[must_use]
+    pub fn is_hybrid(&self) -> bool {
+        self.is_v1() && self.is_v2()
+    }
+
+    /// Returns true if the torrent has a single file.
+    #[must_use]
+    pub fn is_single_file(&self) -> bool {
+        match &self.file_tree {
+            Some(tree) if self.files.is_none() && self.length.is_none() => {
+                tree.0.len() == 1 && tree.0.values().all(|n| n.as_file().is_some())
+            }
+            _ => self.files.is_none(),
+        }
+    }
+
+    /// Returns the total length of all of the files.
+    #[must_use]
+    pub fn total_length(&self) -> u64 {
+        match (&self.files, self.length, &self.file_tree) {
+            (Some(files), _, _) => files.iter().map(|f| f.length).sum(),
+            (None, Some(length), _) => length,
+            (None, None, Some(tree)) => tree.total_length(),
+            (None, None, None) => 0,
+        }
+    }
+
+    /// Returns the validated relative path of every file in the torrent.
+    ///
+    /// The first component of each path is the [`name`][Info::name]. The
+    /// paths are in the same order as the [`files`][Info::files] (including
+    /// padding files) or, for a v2 only torrent, the
+    /// [`file_tree`][Info::file_tree]. See [`safe_path`] for the checks on
+    /// each component.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if any path is unsafe.
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub fn file_paths(&self) -> Result<Vec<std::path::PathBuf>> {
+        let name = safe_path(&[&self.name])?;
+        if let Some(files) = &self.files {
+            return files
+                .iter()
+                .map(|f| Ok(name.join(safe_path(&f.path)?)))
+                .collect();
+        }
+        match (&self.file_tree, self.length) {
+            (Some(tree), None) => {
+                let files = tree.files();
+                if let [(path, _)] = files.as_slice() {
+                    if path.len() == 1 && path[0] == self.name.as_slice() {
+                        return Ok(vec![name]);
+                    }
+                }
+                files
+                    .iter()
+                    .map(|(path, _)| Ok(name.join(safe_path(path)?)))
+                    .collect()
+            }
+            _ => Ok(vec![name]),
+        }
+    }
+
+    /// Returns the piece hashes in the v1 `pieces` field.
+    ///
+    /// If the field is not present, there are no piece hashes.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the length of the field is not a multiple of 20.
+    pub fn piece_hashes(&self) -> Result<Pieces<'_>> {
+        Pieces::new(self.pieces.as_ref().map_or(&[][..], |p| p.as_slice()))
+    }
+
+    /// Returns the number of piece hashes in the v1 `pieces` field.
+    #[must_use]
+    pub fn piece_count(&self) -> usize {
+        self.pieces.as_ref().map_or(0, |p| p.len() / PIECE_HASH_LEN)
+    }
+}
+
+/// A file in a multiple file torrent.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileEntry {
+    /// The length of the file.
+    pub length: u64,
+    /// The path components of the file relative to the top-level directory.
+    pub path: Vec<ByteBuf>,
+    /// The file's attributes from [BEP 47][bep_0047] (e.g. `p` for a padding file).
+    ///
+    /// [bep_0047]: https://www.bittorrent.org/beps/bep_0047.html
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attr: Option<ByteBuf>,
+    /// The path components of the target relative to the top-level directory
+    /// if the file is a symbolic link.
+    #[serde(
+        rename = "symlink path",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub symlink_path: Option<Vec<ByteBuf>>,
+}
+
+impl FileEntry {
+    /// Returns the file's attribute flags.
+    #[must_use]
+    pub fn flags(&self) -> FileFlags {
+        attr::flags(&self.attr)
+    }
+
+    /// Returns true if the file is a padding file.
+    #[must_use]
+    pub fn is_padding(&self) -> bool {
+        self.flags().contains(FileFlags::PADDING)
+    }
+
+    /// Returns the target of the file if it is a symbolic link.
+    #[must_use]
+    pub fn symlink_target(&self) -> Option<&[ByteBuf]> {
+        if self.flags().contains(FileFlags::SYMLINK) {
+            self.symlink_path.as_deref()
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(all(feature = "alloc", not(feature = "std")))]
+    use alloc::vec;
+
+    static TORRENT_BYTES: &[u8] =
+        include_bytes!("../tests/ubuntu-20.04.4-live-server-amd64.iso.torrent");
+
+    #[test]
+    fn test_single_file() -> Result<()> {
+        let metainfo = MetaInfo::from_slice(TORRENT_BYTES)?;
+        assert_eq!(
+            metainfo.announce.as_deref(),
+            Some("https://torrent.ubuntu.com/announce")
+        );
+        assert_eq!(metainfo.announce_list.as_ref().map(Vec::len), Some(2));
+        assert_eq!(metainfo.creation_date, Some(1_645_734_525));
+        assert_eq!(metainfo.created_by.as_deref(), Some("mktorrent 1.1"));
+
+        let info = &metainfo.info;
+        assert!(info.is_single_file());
+        assert_eq!(info.name, b"ubuntu-20.04.4-live-server-amd64.iso");
+        assert_eq!(info.piece_length, 262_144);
+        assert_eq!(info.total_length(), 1_331_691_520);
+        assert_eq!(info.piece_count(), 5080);
+        assert_eq!(
+            metainfo.trackers(),
+            vec![
+                "https://torrent.ubuntu.com/announce",
+                "https://ipv6.torrent.ubuntu.com/announce"
+            ]
+        );
+        assert!(metainfo.web_seeds().is_empty());
+        let pieces = info.piece_hashes()?;
+        assert_eq!(pieces.len(), 5080);
+        assert_eq!(pieces.iter().nth(5079).as_ref(), pieces.get(5079));
+
+        assert_eq!(metainfo.to_vec()?, TORRENT_BYTES);
+        Ok(())
+    }
+
+    #[cfg(any(feature = "sha1", feature = "sha2"))]
+    #[test]
+    fn test_raw_info() -> Result<()> {
+        let input = b"d8:announce4:spam4:infod3:fooi01e3:bar0:e1:z0:e";
+        assert_eq!(raw_info(input)?, b"d3:fooi01e3:bar0:e");
+        assert!(raw_info(b"d8:announce4:spame").is_err());
+        Ok(())
+    }
+
+    #[cfg(feature = "sha1")]
+    #[test]
+    fn test_info_hash_v1() -> Result<()> {
+        assert_eq!(
+            info_hash_v1(TORRENT_BYTES)?,
+            [
+                0xb4, 0x4a, 0x0e, 0x20, 0xfa, 0x5b, 0x7c, 0xec, 0xb7, 0x71, 0x56, 0x33, 0x3b, 0x42,
+                0x68, 0xdf, 0xd7, 0xc3, 0x0a, 0xfb,
+            ]
+        );
+        Ok(())
+    }
+
+    #[cfg(all(feature = "sha1", feature = "sha2"))]
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    static HYBRID_BYTES: &[u8] = b"d4:infod9:file treed5:a.txtd0:d6:lengthi4e11:pieces root32:RRRRRRRRRRRRRRRRRRRRRRRRRRRRRRRReee6:lengthi4e12:meta versioni2e4:name5:a.txt12:piece lengthi16384e6:pieces20:PPPPPPPPPPPPPPPPPPPPe12:piece layersd32:RRRRRRRRRRRRRRRRRRRRRRRRRRRRRRRR32:HHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHee";
+
+    #[test]
+    fn test_hybrid() -> Result<()> {
+        let metainfo = MetaInfo::from_slice(HYBRID_BYTES)?;
+        let info = &metainfo.info;
+        assert!(info.is_v1());
+        assert!(info.is_v2());
+        assert!(info.is_hybrid());
+        assert!(info.is_single_file());
+        assert_eq!(info.total_length(), 4);
+
+        let tree = info.file_tree.as_ref().unwrap();
+        let attrs = tree.0[&b"a.txt"[..]].as_file().unwrap();
+        assert_eq!(attrs.length, 4);
+        let root = attrs.pieces_root.as_ref().unwrap();
+        assert_eq!(
+            metainfo.piece_layers.as_ref().unwrap()[root.as_slice()],
+            [b'H'; 32][..]
+        );
+
+        assert_eq!(metainfo.to_vec()?, HYBRID_BYTES);
+        Ok(())
+    }
+
+    #[test]
+    fn test_v2_only() -> Result<()> {
+        let input = b"d4:infod9:file treed3:dird1:ad0:d6:lengthi3eee1:bd0:d6:lengthi0eeeee12:meta versioni2e4:name3:dir12:piece lengthi16384eee";
+        let metainfo = MetaInfo::from_slice(input)?;
+        let info = &metainfo.info;
+        assert!(!info.is_v1());
+        assert!(info.is_v2());
+        assert!(!info.is_single_file());
+        assert_eq!(info.total_length(), 3);
+        assert_eq!(info.piece_count(), 0);
+        assert_eq!(metainfo.to_vec()?, &input[..]);
+        Ok(())
+    }
+
+    #[cfg(all(feature = "sha1", feature = "sha2"))]
+    #[test]
+    fn test_info_hashes() -> Result<()> {
+        let hashes = info_hashes(HYBRID_BYTES)?;
+        assert_eq!(
+            hashes.v1.map(|h| h.to_vec()),
+            Some(hex("ea73ac7bca39d0f4588e40d41191d02838781db6"))
+        );
+        assert_eq!(
+            hashes.v2.map(|h| h.to_vec()),
+            Some(hex(
+                "161fa45ce4eb82fb00d36add7660d95dd9e560d9925638b170a2150f10e8e237"
+            ))
+        );
+        assert_eq!(hashes.v2, Some(info_hash_v2(HYBRID_BYTES)?));
+
+        let hashes = info_hashes(TORRENT_BYTES)?;
+        assert_eq!(hashes.v1, Some(info_hash_v1(TORRENT_BYTES)?));
+        assert_eq!(hashes.v2, None);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "sha1")]
+    fn test_info_hash_non_dict() {
+        for input in [&b"d4:info3:i1ee"[..], b"d4:infoi1ee", b"d4:infolee", b"de"] {
+            assert!(info_hash_v1(input).is_err(), "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_trackers_and_web_seeds() -> Result<()> {
+        let input = b"d8:announce8:http://x13:announce-listll8:http://a8:http://bel0:el8:http://a8:http://cee4:infod4:name1:a12:piece lengthi16384ee8:url-list8:http://we";
+        let metainfo = MetaInfo::from_slice(input)?;
+        assert_eq!(
+            metainfo.tracker_tiers(),
+            vec![vec!["http://a", "http://b"], vec!["http://c"]]
+        );
+        assert_eq!(
+            metainfo.trackers(),
+            vec!["http://a", "http://b", "http://c", "http://x"]
+        );
+        assert_eq!(metainfo.web_seeds(), vec!["http://w"]);
+        #[cfg(feature = "url")]
+        {
+            assert_eq!(metainfo.tracker_urls()?.len(), 4);
+            assert_eq!(metainfo.web_seed_urls()?[0].as_str(), "http://w/");
+        }
+        assert_eq!(metainfo.to_vec()?, &input[..]);
+
+        let input = b"d8:announce8:http://x4:infod4:name1:a12:piece lengthi16384ee8:url-listl8:http://v8:http://wee";
+        let metainfo = MetaInfo::from_slice(input)?;
+        assert_eq!(metainfo.tracker_tiers(), vec![vec!["http://x"]]);
+        assert_eq!(metainfo.web_seeds(), vec!["http://v", "http://w"]);
+        assert!(metainfo.http_seeds().is_empty());
+        assert_eq!(metainfo.to_vec()?, &input[..]);
+
+        let input =
+            b"d9:httpseedsl8:http://h0:e4:infod4:name1:a12:piece lengthi16384ee8:url-list0:e";
+        let metainfo = MetaInfo::from_slice(input)?;
+        assert_eq!(metainfo.http_seeds(), vec!["http://h"]);
+        assert!(metainfo.web_seeds().is_empty());
+        assert_eq!(metainfo.url_list, Some(UrlList::One(String::new())));
+        assert_eq!(metainfo.to_vec()?, &input[..]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_multiple_files() -> Result<()> {
+        let input = b"d4:infod5:filesld6:lengthi3e4:pathl1:a5:b.txteed6:lengthi4e4:pathl5:c.txteee4:name3:dir12:piece lengthi16384e6:pieces0:7:privatei1eee";
+        let metainfo = MetaInfo::from_slice(input)?;
+        let info = &metainfo.info;
+        assert!(!info.is_single_file());
+        assert_eq!(info.total_length(), 7);
+        assert_eq!(info.private, Some(1));
+        assert!(info.is_private());
+        assert_eq!(
+            info.files.as_ref().unwrap()[0].path,
+            vec![ByteBuf::from(&b"a"[..]), ByteBuf::from(&b"b.txt"[..])]
+        );
+        assert_eq!(metainfo.to_vec()?, &input[..]);
+
+        #[cfg(feature = "std")]
+        {
+            use std::path::PathBuf;
+
+            assert_eq!(
+                info.file_paths()?,
+                vec![
+                    ["dir", "a", "b.txt"].iter().collect::<PathBuf>(),
+                    ["dir", "c.txt"].iter().collect::<PathBuf>(),
+                ]
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_attrs() -> Result<()> {
+        let input = b"d4:infod5:filesld4:attr2:xh6:lengthi3e4:pathl1:aeed4:attr1:l6:lengthi0e4:pathl1:be12:symlink pathl1:aeed4:attr1:p6:lengthi5e4:pathl4:.padeee4:name3:dir12:piece lengthi16384e6:pieces0:ee";
+        let metainfo = MetaInfo::from_slice(input)?;
+        let files = metainfo.info.files.as_ref().unwrap();
+        assert_eq!(files[0].flags(), FileFlags::EXECUTABLE | FileFlags::HIDDEN);
+        assert_eq!(files[0].symlink_target(), None);
+        assert_eq!(files[1].flags(), FileFlags::SYMLINK);
+        assert_eq!(
+            files[1].symlink_target(),
+            Some(&[ByteBuf::from(&b"a"[..])][..])
+        );
+        assert!(files[2].is_padding());
+        assert!(!files[1].is_padding());
+        assert_eq!(metainfo.to_vec()?, &input[..]);
+        Ok(())
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_creation_time() -> Result<()> {
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let input = b"d13:creation datei1600000000e4:infod4:name1:a12:piece lengthi16384eee";
+        let metainfo = MetaInfo::from_slice(input)?;
+        assert_eq!(
+            metainfo.creation_time(),
+            Some(UNIX_EPOCH + Duration::from_secs(1_600_000_000))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_merkle() -> Result<()> {
+        let input = b"d4:infod4:attr1:x6:lengthi4e4:name1:a12:piece lengthi16384e9:root hash20:aaaaaaaaaaaaaaaaaaaaee";
+        let metainfo = MetaInfo::from_slice(input)?;
+        let info = &metainfo.info;
+        assert!(info.is_merkle());
+        assert!(!info.is_v1());
+        assert_eq!(info.root_hash.as_ref().map(|h| h.len()), Some(20));
+        assert_eq!(info.flags(), FileFlags::EXECUTABLE);
+        assert_eq!(metainfo.to_vec()?, &input[..]);
+        Ok(())
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_file_paths() -> Result<()> {
+        use std::path::PathBuf;
+
+        let metainfo = MetaInfo::from_slice(TORRENT_BYTES)?;
+        assert_eq!(
+            metainfo.info.file_paths()?,
+            vec![PathBuf::from("ubuntu-20.04.4-live-server-amd64.iso")]
+        );
+
+        let metainfo = MetaInfo::from_slice(HYBRID_BYTES)?;
+        assert_eq!(metainfo.info.file_paths()?, vec![PathBuf::from("a.txt")]);
+
+        for input in &[
+            &b"d4:infod5:filesld6:lengthi3e4:pathl2:..6:passwdeee4:name3:dir12:piece lengthi16384e6:pieces0:ee"[..],
+            b"d4:infod6:lengthi1e4:name2:..12:piece lengthi16384e6:pieces0:ee",
+            b"d4:infod9:file treed2:..d0:d6:lengthi3eeee12:meta versioni2e4:name3:dir12:piece lengthi16384eee",
+        ] {
+            let metainfo = MetaInfo::from_slice(input)?;
+            assert!(metainfo.info.file_paths().is_err());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,126 @@
+//! The file `attr` field from BEP 47.
+
+use core::{fmt, ops};
+use serde_bytes::ByteBuf;
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::vec::Vec;
+
+/// The characters for each flag in the order they are encoded.
+const ATTR_CHARS: [(u8, FileFlags); 4] = [
+    (b'l', FileFlags::SYMLINK),
+    (b'x', FileFlags::EXECUTABLE),
+    (b'h', FileFlags::HIDDEN),
+    (b'p', FileFlags::PADDING),
+];
+
+/// The file attribute flags from [BEP 47][bep_0047].
+///
+/// The flags are encoded in a file's `attr` field as a string with one
+/// character per flag. Unknown characters are ignored.
+///
+/// ```rust
+/// use bt_bencode::torrent::FileFlags;
+///
+/// let flags = FileFlags::from_attr(b"xh");
+/// assert!(flags.contains(FileFlags::EXECUTABLE | FileFlags::HIDDEN));
+/// assert!(!flags.contains(FileFlags::PADDING));
+/// assert_eq!(flags.to_attr(), b"xh");
+/// ```
+///
+/// [bep_0047]: https://www.bittorrent.org/beps/bep_0047.html
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct FileFlags(pub u8);
+
+impl FileFlags {
+    /// The file is a padding file (`p`).
+    pub const PADDING: FileFlags = FileFlags(0x01);
+    /// The file is executable (`x`).
+    pub const EXECUTABLE: FileFlags = FileFlags(0x02);
+    /// The file is hidden (`h`).
+    pub const HIDDEN: FileFlags = FileFlags(0x04);
+    /// The file is a symbolic link (`l`) to its `symlink path`.
+    pub const SYMLINK: FileFlags = FileFlags(0x08);
+
+    /// Parses the flags from an `attr` field.
+    #[must_use]
+    pub fn from_attr(attr: &[u8]) -> Self {
+        let mut flags = FileFlags::default();
+        for (c, flag) in &ATTR_CHARS {
+            if attr.contains(c) {
+                flags.insert(*flag);
+            }
+        }
+        flags
+    }
+
+    /// Encodes the flags as an `attr` field.
+    #[must_use]
+    pub fn to_attr(self) -> ByteBuf {
+        ByteBuf::from(
+            ATTR_CHARS
+                .iter()
+                .filter(|(_, flag)| self.contains(*flag))
+                .map(|(c, _)| *c)
+                .collect::<Vec<u8>>(),
+        )
+    }
+
+    /// Returns true if no flags are set.
+    #[must_use]
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns true if all of the flags in `other` are set.
+    #[must_use]
+    pub fn contains(self, other: FileFlags) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Sets the flags in `other`.
+    pub fn insert(&mut self, other: FileFlags) {
+        self.0 |= other.0;
+    }
+}
+
+impl ops::BitOr for FileFlags {
+    type Output = FileFlags;
+
+    fn bitor(self, rhs: FileFlags) -> FileFlags {
+        FileFlags(self.0 | rhs.0)
+    }
+}
+
+impl fmt::Debug for FileFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FileFlags({:#04x})", self.0)
+    }
+}
+
+/// Parses the flags from an optional `attr` field.
+pub(super) fn flags(attr: &Option<ByteBuf>) -> FileFlags {
+    attr.as_ref()
+        .map_or(FileFlags::default(), |attr| FileFlags::from_attr(attr))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attr() {
+        assert!(FileFlags::from_attr(b"").is_empty());
+        assert!(FileFlags::from_attr(b"z").is_empty());
+        let flags = FileFlags::from_attr(b"phxl");
+        assert_eq!(
+            flags,
+            FileFlags::PADDING | FileFlags::EXECUTABLE | FileFlags::HIDDEN | FileFlags::SYMLINK
+        );
+        assert_eq!(flags.to_attr(), b"lxhp");
+        assert_eq!(FileFlags::PADDING.to_attr(), b"p");
+        assert!(FileFlags::default().to_attr().is_empty());
+    }
+}
@@ -0,0 +1,813 @@
+//! Creates metainfo files from files on disk.
+
+use super::{FileEntry, Info, MetaInfo};
+use crate::error::{Error, Result};
+use serde_bytes::ByteBuf;
+use sha1::{Digest, Sha1};
+use std::{
+    fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+    string::String,
+    vec,
+    vec::Vec,
+};
+
+#[cfg(feature = "sha2")]
+use super::{FileAttrs, FileFlags, FileTree, FileTreeNode};
+#[cfg(feature = "sha2")]
+use crate::key::ByteStrKey;
+#[cfg(feature = "sha2")]
+use sha2::Sha256;
+#[cfg(feature = "sha2")]
+use std::{collections::BTreeMap, string::ToString};
+
+/// The size of the blocks which are the leaves of a v2 merkle tree.
+const BLOCK_SIZE: usize = 16 * 1024;
+
+/// The number of bytes read from a file at a time.
+///
+/// With the `rayon` feature, many blocks are read at once so that they can be
+/// hashed in parallel.
+#[cfg(not(feature = "rayon"))]
+const READ_SIZE: usize = BLOCK_SIZE;
+#[cfg(feature = "rayon")]
+const READ_SIZE: usize = 256 * BLOCK_SIZE;
+
+/// The piece length used if one is not set.
+pub const DEFAULT_PIECE_LENGTH: u64 = 256 * 1024;
+
+/// The versions of the metainfo format which a [`TorrentBuilder`] can create.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Version {
+    /// A v1 torrent with SHA-1 piece hashes.
+    V1,
+    /// A v2 torrent with a file tree and SHA-256 merkle trees.
+    #[cfg(feature = "sha2")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "sha2")))]
+    V2,
+    /// A torrent with both the v1 and v2 fields.
+    ///
+    /// Padding files are added between files so that every non-empty file
+    /// starts at a piece boundary.
+    #[cfg(feature = "sha2")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "sha2")))]
+    Hybrid,
+}
+
+impl Version {
+    fn has_v1(self) -> bool {
+        match self {
+            Version::V1 => true,
+            #[cfg(feature = "sha2")]
+            Version::V2 => false,
+            #[cfg(feature = "sha2")]
+            Version::Hybrid => true,
+        }
+    }
+
+    #[cfg(feature = "sha2")]
+    fn has_v2(self) -> bool {
+        self != Version::V1
+    }
+}
+
+/// Creates a [`MetaInfo`] for a file or a directory by hashing its contents.
+///
+/// ```no_run
+/// use bt_bencode::torrent::TorrentBuilder;
+///
+/// let metainfo = TorrentBuilder::new("path/to/files")
+///     .announce("https://tracker.example.com/announce")
+///     .piece_length(512 * 1024)
+///     .build()?;
+/// std::fs::write("files.torrent", metainfo.to_vec()?)?;
+/// # Ok::<(), bt_bencode::Error>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct TorrentBuilder {
+    path: PathBuf,
+    piece_length: u64,
+    version: Version,
+    announce: Option<String>,
+    announce_list: Vec<Vec<String>>,
+    comment: Option<String>,
+    created_by: Option<String>,
+    creation_date: Option<i64>,
+    private: bool,
+}
+
+/// A file found while walking the path.
+struct SourceFile {
+    full_path: PathBuf,
+    path: Vec<ByteBuf>,
+}
+
+impl TorrentBuilder {
+    /// Constructs a builder for the file or directory at the path.
+    #[must_use]
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            piece_length: DEFAULT_PIECE_LENGTH,
+            version: Version::V1,
+            announce: None,
+            announce_list: Vec::new(),
+            comment: None,
+            created_by: None,
+            creation_date: None,
+            private: false,
+        }
+    }
+
+    /// Sets the number of bytes in each piece.
+    ///
+    /// The piece length must be a power of two and at least 16 KiB.
+    #[must_use]
+    pub fn piece_length(mut self, piece_length: u64) -> Self {
+        self.piece_length = piece_length;
+        self
+    }
+
+    /// Sets the version of the metainfo format to create.
+    #[must_use]
+    pub fn version(mut self, version: Version) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Sets the URL of the tracker.
+    #[must_use]
+    pub fn announce<S: Into<String>>(mut self, url: S) -> Self {
+        self.announce = Some(url.into());
+        self
+    }
+
+    /// Adds a tier of tracker URLs to the `announce-list`.
+    #[must_use]
+    pub fn announce_tier<I, S>(mut self, urls: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.announce_list
+            .push(urls.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Sets the comment.
+    #[must_use]
+    pub fn comment<S: Into<String>>(mut self, comment: S) -> Self {
+        self.comment = Some(comment.into());
+        self
+    }
+
+    /// Sets the name and version of the program which created the file.
+    #[must_use]
+    pub fn created_by<S: Into<String>>(mut self, created_by: S) -> Self {
+        self.created_by = Some(created_by.into());
+        self
+    }
+
+    /// Sets the creation time as seconds since the Unix epoch.
+    #[must_use]
+    pub fn creation_date(mut self, creation_date: i64) -> Self {
+        self.creation_date = Some(creation_date);
+        self
+    }
+
+    /// Sets whether the torrent is private.
+    #[must_use]
+    pub fn private(mut self, private: bool) -> Self {
+        self.private = private;
+        self
+    }
+
+    /// Reads and hashes the files and returns the metainfo.
+    ///
+    /// Files in a directory are added in path order. Entries which are not
+    /// files or directories are skipped, including symbolic links (which are
+    /// not followed, so a link cycle does not recurse forever).
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the piece length is invalid, if there are no
+    /// files, if a file name is not valid UTF-8, or if a file cannot be read.
+    pub fn build(&self) -> Result<MetaInfo> {
+        if !self.piece_length.is_power_of_two() || self.piece_length < BLOCK_SIZE as u64 {
+            return Err(invalid_input(
+                "piece length must be a power of two and at least 16 KiB",
+            ));
+        }
+
+        let name = self
+            .path
+            .file_name()
+            .ok_or_else(|| invalid_input("path does not have a file name"))
+            .and_then(name_to_bytes)?;
+
+        let is_dir = fs::metadata(&self.path)?.is_dir();
+        let mut sources = Vec::new();
+        if is_dir {
+            walk_dir(&self.path, &mut Vec::new(), &mut sources)?;
+            if sources.is_empty() {
+                return Err(invalid_input("directory does not have any files"));
+            }
+        } else {
+            sources.push(SourceFile {
+                full_path: self.path.clone(),
+                path: vec![name.clone()],
+            });
+        }
+
+        let mut v1 = if self.version.has_v1() {
+            Some(PieceHasher::new(self.piece_length))
+        } else {
+            None
+        };
+        let mut files = Vec::new();
+        #[cfg(feature = "sha2")]
+        let mut file_tree = FileTree::default();
+        #[cfg(feature = "sha2")]
+        let mut piece_layers = BTreeMap::new();
+
+        let mut buf = vec![0; READ_SIZE];
+        for source in &sources {
+            #[cfg(feature = "sha2")]
+            let mut leaves = Vec::new();
+            let mut length = 0;
+            let mut file = fs::File::open(&source.full_path)?;
+            loop {
+                let n = read_block(&mut file, &mut buf)?;
+                if n == 0 {
+                    break;
+                }
+                #[cfg(feature = "sha2")]
+                {
+                    if let (Some(v1), true, 0) = (&mut v1, self.version.has_v2(), length) {
+                        let pad = v1.pad_to_piece();
+                        if pad > 0 {
+                            files.push(FileEntry {
+                                length: pad,
+                                path: vec![ByteBuf::from(".pad"), ByteBuf::from(pad.to_string())],
+                                attr: Some(FileFlags::PADDING.to_attr()),
+                                symlink_path: None,
+                            });
+                        }
+                    }
+                }
+                length += n as u64;
+                if let Some(v1) = &mut v1 {
+                    v1.update(&buf[..n]);
+                }
+                #[cfg(feature = "sha2")]
+                {
+                    if self.version.has_v2() {
+                        leaves.extend(hash_blocks(&buf[..n]));
+                    }
+                }
+            }
+
+            if is_dir {
+                files.push(FileEntry {
+                    length,
+                    path: source.path.clone(),
+                    attr: None,
+                    symlink_path: None,
+                });
+            }
+
+            #[cfg(feature = "sha2")]
+            {
+                if self.version.has_v2() {
+                    let pieces_root = if leaves.is_empty() {
+                        None
+                    } else {
+                        let (root, layer) = merkle_tree(leaves, self.piece_length);
+                        if let Some(layer) = layer {
+                            piece_layers
+                                .insert(ByteStrKey::from(root.to_vec()), ByteBuf::from(layer));
+                        }
+                        Some(ByteBuf::from(root.to_vec()))
+                    };
+                    let path = if is_dir {
+                        &source.path[..]
+                    } else {
+                        &source.path[..1]
+                    };
+                    insert_file(
+                        &mut file_tree,
+                        path,
+                        FileAttrs {
+                            length,
+                            pieces_root,
+                            attr: None,
+                            symlink_path: None,
+                        },
+                    );
+                }
+            }
+        }
+
+        let info = Info {
+            name,
+            piece_length: self.piece_length,
+            length: match (&v1, is_dir) {
+                (Some(v1), false) => Some(v1.total),
+                _ => None,
+            },
+            pieces: v1.map(|v1| ByteBuf::from(v1.finish())),
+            files: if is_dir && self.version.has_v1() {
+                Some(files)
+            } else {
+                None
+            },
+            private: if self.private { Some(1) } else { None },
+            #[cfg(feature = "sha2")]
+            meta_version: if self.version.has_v2() { Some(2) } else { None },
+            #[cfg(not(feature = "sha2"))]
+            meta_version: None,
+            #[cfg(feature = "sha2")]
+            file_tree: if self.version.has_v2() {
+                Some(file_tree)
+            } else {
+                None
+            },
+            #[cfg(not(feature = "sha2"))]
+            file_tree: None,
+            root_hash: None,
+            attr: None,
+            symlink_path: None,
+        };
+
+        Ok(MetaInfo {
+            announce: self.announce.clone(),
+            announce_list: if self.announce_list.is_empty() {
+                None
+            } else {
+                Some(self.announce_list.clone())
+            },
+            comment: self.comment.clone(),
+            created_by: self.created_by.clone(),
+            creation_date: self.creation_date,
+            encoding: None,
+            httpseeds: None,
+            info,
+            #[cfg(feature = "sha2")]
+            piece_layers: if self.version.has_v2() {
+                Some(piece_layers)
+            } else {
+                None
+            },
+            #[cfg(not(feature = "sha2"))]
+            piece_layers: None,
+            url_list: None,
+        })
+    }
+}
+
+fn invalid_input(msg: &'static str) -> Error {
+    Error::IoError(io::Error::new(io::ErrorKind::InvalidInput, msg))
+}
+
+fn name_to_bytes(name: &std::ffi::OsStr) -> Result<ByteBuf> {
+    name.to_str()
+        .map(ByteBuf::from)
+        .ok_or_else(|| invalid_input("file name is not valid UTF-8"))
+}
+
+fn walk_dir(dir: &Path, path: &mut Vec<ByteBuf>, sources: &mut Vec<SourceFile>) -> Result<()> {
+    let mut entries = fs::read_dir(dir)?.collect::<io::Result<Vec<_>>>()?;
+    entries.sort_by_key(fs::DirEntry::file_name);
+    for entry in entries {
+        let full_path = entry.path();
+        // The entry's own type is used, so symbolic links are skipped
+        // instead of followed.
+        let file_type = entry.file_type()?;
+        path.push(name_to_bytes(&entry.file_name())?);
+        if file_type.is_dir() {
+            walk_dir(&full_path, path, sources)?;
+        } else if file_type.is_file() {
+            sources.push(SourceFile {
+                full_path,
+                path: path.clone(),
+            });
+        }
+        path.pop();
+    }
+    Ok(())
+}
+
+/// Fills the buffer from the reader unless the end of the data is reached.
+fn read_block<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+/// Hashes a stream of data into v1 SHA-1 piece hashes.
+#[cfg(not(feature = "rayon"))]
+struct PieceHasher {
+    hasher: Sha1,
+    piece_length: u64,
+    filled: u64,
+    total: u64,
+    pieces: Vec<u8>,
+}
+
+#[cfg(not(feature = "rayon"))]
+impl PieceHasher {
+    fn new(piece_length: u64) -> Self {
+        Self {
+            hasher: Sha1::new(),
+            piece_length,
+            filled: 0,
+            total: 0,
+            pieces: Vec::new(),
+        }
+    }
+
+    fn update(&mut self, mut data: &[u8]) {
+        self.total += data.len() as u64;
+        while !data.is_empty() {
+            let remaining = self.piece_length - self.filled;
+            let n = core::cmp::min(remaining, data.len() as u64) as usize;
+            self.hasher.update(&data[..n]);
+            self.filled += n as u64;
+            data = &data[n..];
+            if self.filled == self.piece_length {
+                self.pieces.extend_from_slice(&self.hasher.finalize_reset());
+                self.filled = 0;
+            }
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.filled > 0 {
+            self.pieces.extend_from_slice(&self.hasher.finalize());
+        }
+        self.pieces
+    }
+}
+
+/// Hashes a stream of data into v1 SHA-1 piece hashes.
+///
+/// Data is buffered until there is a piece for every thread and then the
+/// pieces are hashed in parallel.
+#[cfg(feature = "rayon")]
+struct PieceHasher {
+    pending: Vec<u8>,
+    piece_length: u64,
+    batch_len: usize,
+    filled: u64,
+    total: u64,
+    pieces: Vec<u8>,
+}
+
+#[cfg(feature = "rayon")]
+impl PieceHasher {
+    fn new(piece_length: u64) -> Self {
+        Self {
+            pending: Vec::new(),
+            piece_length,
+            batch_len: piece_length as usize * rayon::current_num_threads(),
+            filled: 0,
+            total: 0,
+            pieces: Vec::new(),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.total += data.len() as u64;
+        self.filled = (self.filled + data.len() as u64) % self.piece_length;
+        self.pending.extend_from_slice(data);
+        if self.pending.len() >= self.batch_len {
+            self.hash_pending(false);
+        }
+    }
+
+    /// Hashes the complete pieces (and the final partial piece if `all` is set).
+    fn hash_pending(&mut self, all: bool) {
+        use rayon::prelude::*;
+
+        let piece_length = self.piece_length as usize;
+        let len = if all {
+            self.pending.len()
+        } else {
+            self.pending.len() / piece_length * piece_length
+        };
+        let hashes = self.pending[..len]
+            .par_chunks(piece_length)
+            .map(Sha1::digest)
+            .collect::<Vec<_>>();
+        for hash in hashes {
+            self.pieces.extend_from_slice(&hash);
+        }
+        self.pending.drain(..len);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        self.hash_pending(true);
+        self.pieces
+    }
+}
+
+impl PieceHasher {
+    /// Hashes zeros until the end of the current piece and returns the number of zeros.
+    #[cfg(feature = "sha2")]
+    fn pad_to_piece(&mut self) -> u64 {
+        if self.filled == 0 {
+            return 0;
+        }
+        let pad = self.piece_length - self.filled;
+        let zeros = [0; BLOCK_SIZE];
+        let mut remaining = pad;
+        while remaining > 0 {
+            let n = core::cmp::min(remaining, BLOCK_SIZE as u64) as usize;
+            self.update(&zeros[..n]);
+            remaining -= n as u64;
+        }
+        pad
+    }
+}
+
+/// Hashes the data as a sequence of merkle tree leaf blocks.
+#[cfg(all(feature = "sha2", not(feature = "rayon")))]
+fn hash_blocks(data: &[u8]) -> Vec<[u8; 32]> {
+    data.chunks(BLOCK_SIZE)
+        .map(|block| Sha256::digest(block).into())
+        .collect()
+}
+
+/// Hashes the data as a sequence of merkle tree leaf blocks in parallel.
+#[cfg(all(feature = "sha2", feature = "rayon"))]
+fn hash_blocks(data: &[u8]) -> Vec<[u8; 32]> {
+    use rayon::prelude::*;
+
+    data.par_chunks(BLOCK_SIZE)
+        .map(|block| Sha256::digest(block).into())
+        .collect()
+}
+
+/// Computes the root of a merkle tree with the leaves padded to the width.
+#[cfg(feature = "sha2")]
+fn merkle_root(mut layer: Vec<[u8; 32]>, width: usize, pad: [u8; 32]) -> [u8; 32] {
+    layer.resize(width, pad);
+    while layer.len() > 1 {
+        layer = layer
+            .chunks(2)
+            .map(|pair| {
+                let mut hasher = Sha256::new();
+                hasher.update(pair[0]);
+                hasher.update(pair[1]);
+                hasher.finalize().into()
+            })
+            .collect();
+    }
+    layer[0]
+}
+
+/// Computes a file's `pieces root` and, if the file is larger than a piece,
+/// its concatenated piece layer hashes.
+#[cfg(feature = "sha2")]
+fn merkle_tree(leaves: Vec<[u8; 32]>, piece_length: u64) -> ([u8; 32], Option<Vec<u8>>) {
+    let blocks_per_piece = (piece_length / BLOCK_SIZE as u64) as usize;
+    if leaves.len() <= blocks_per_piece {
+        let width = leaves.len().next_power_of_two();
+        return (merkle_root(leaves, width, [0; 32]), None);
+    }
+
+    let layer = leaves
+        .chunks(blocks_per_piece)
+        .map(|piece| merkle_root(piece.to_vec(), blocks_per_piece, [0; 32]))
+        .collect::<Vec<_>>();
+    let pad = merkle_root(Vec::new(), blocks_per_piece, [0; 32]);
+    let hashes = layer.iter().flat_map(|h| h.iter().copied()).collect();
+    let width = layer.len().next_power_of_two();
+    (merkle_root(layer, width, pad), Some(hashes))
+}
+
+#[cfg(feature = "sha2")]
+fn insert_file(tree: &mut FileTree, path: &[ByteBuf], attrs: FileAttrs) {
+    let (name, dirs) = path.split_last().expect("path is not empty");
+    let mut tree = tree;
+    for dir in dirs {
+        let node = tree
+            .0
+            .entry(ByteStrKey::from(dir.clone()))
+            .or_insert_with(|| FileTreeNode::Dir(FileTree::default()));
+        tree = match node {
+            FileTreeNode::Dir(tree) => tree,
+            FileTreeNode::File(_) => unreachable!("file and directory have the same path"),
+        };
+    }
+    tree.0
+        .insert(ByteStrKey::from(name.clone()), FileTreeNode::File(attrs));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> Result<PathBuf> {
+        let dir = std::env::temp_dir()
+            .join(format!("bt_bencode_builder_{}", std::process::id()))
+            .join(name);
+        if dir.exists() {
+            fs::remove_dir_all(&dir)?;
+        }
+        fs::create_dir_all(&dir)?;
+        Ok(dir)
+    }
+
+    fn test_data() -> Vec<u8> {
+        (0..40000_u32).map(|i| (i % 251) as u8).collect()
+    }
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_v1_single_file() -> Result<()> {
+        let path = temp_dir("v1_single_file")?.join("data.bin");
+        fs::write(&path, test_data())?;
+
+        let metainfo = TorrentBuilder::new(&path)
+            .announce("http://example.com/announce")
+            .announce_tier(vec!["http://example.com/announce"])
+            .creation_date(1)
+            .private(true)
+            .piece_length(16 * 1024)
+            .build()?;
+
+        assert_eq!(
+            metainfo.announce.as_deref(),
+            Some("http://example.com/announce")
+        );
+        assert_eq!(metainfo.announce_list.as_ref().map(Vec::len), Some(1));
+        let info = &metainfo.info;
+        assert_eq!(info.name, b"data.bin");
+        assert_eq!(info.length, Some(40000));
+        assert_eq!(info.files, None);
+        assert_eq!(info.private, Some(1));
+        assert_eq!(info.file_tree, None);
+        assert_eq!(
+            info.pieces.as_ref().unwrap().as_slice(),
+            &hex("68f3b81a11de1e1629e81555b4e70aed955d1140de9ee0222cd528efc5e01227e4bf16cf6ac6836a7b9d67e14ed5a5e1695fac5611004b00d76da5ae")[..]
+        );
+
+        let encoded = metainfo.to_vec()?;
+        assert_eq!(MetaInfo::from_slice(&encoded)?, metainfo);
+        Ok(())
+    }
+
+    #[test]
+    fn test_v1_dir() -> Result<()> {
+        let dir = temp_dir("v1_dir")?.join("files");
+        fs::create_dir_all(dir.join("sub"))?;
+        fs::write(dir.join("sub").join("b.txt"), b"hello")?;
+        fs::write(dir.join("a.bin"), test_data())?;
+
+        let metainfo = TorrentBuilder::new(&dir).piece_length(32 * 1024).build()?;
+        let info = &metainfo.info;
+        assert_eq!(info.name, b"files");
+        assert_eq!(info.length, None);
+        let files = info.files.as_ref().unwrap();
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].path, vec![ByteBuf::from("a.bin")]);
+        assert_eq!(files[0].length, 40000);
+        assert_eq!(
+            files[1].path,
+            vec![ByteBuf::from("sub"), ByteBuf::from("b.txt")]
+        );
+        assert_eq!(info.total_length(), 40005);
+        assert_eq!(info.piece_count(), 2);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_dir_skips_symlinks() -> Result<()> {
+        let dir = temp_dir("symlinks")?.join("files");
+        fs::create_dir_all(dir.join("sub"))?;
+        fs::write(dir.join("a.txt"), b"hello")?;
+        std::os::unix::fs::symlink(&dir, dir.join("sub").join("loop"))?;
+        std::os::unix::fs::symlink(dir.join("a.txt"), dir.join("link.txt"))?;
+
+        let metainfo = TorrentBuilder::new(&dir).build()?;
+        let files = metainfo.info.files.as_ref().unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, vec![ByteBuf::from("a.txt")]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid() -> Result<()> {
+        let dir = temp_dir("invalid")?;
+        assert!(TorrentBuilder::new(&dir).build().is_err());
+        fs::write(dir.join("a"), b"a")?;
+        assert!(TorrentBuilder::new(&dir)
+            .piece_length(1000)
+            .build()
+            .is_err());
+        assert!(TorrentBuilder::new(&dir)
+            .piece_length(8192)
+            .build()
+            .is_err());
+        assert!(TorrentBuilder::new(&dir).build().is_ok());
+        Ok(())
+    }
+
+    #[cfg(feature = "sha2")]
+    #[test]
+    fn test_v2_single_file() -> Result<()> {
+        let path = temp_dir("v2_single_file")?.join("data.bin");
+        fs::write(&path, test_data())?;
+
+        let metainfo = TorrentBuilder::new(&path)
+            .version(Version::V2)
+            .piece_length(32 * 1024)
+            .build()?;
+        let info = &metainfo.info;
+        assert!(!info.is_v1());
+        assert!(info.is_v2());
+        assert_eq!(info.length, None);
+
+        let files = info.file_tree.as_ref().unwrap().files();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].0, vec![&b"data.bin"[..]]);
+        assert_eq!(files[0].1.length, 40000);
+        let root = files[0].1.pieces_root.as_ref().unwrap();
+        assert_eq!(
+            root.as_slice(),
+            &hex("ab671631a9fa97a1fdac651fff6c68773b9acf0735b9c7f6ecdd54cbf1bf5dc2")[..]
+        );
+        assert_eq!(
+            metainfo.piece_layers.as_ref().unwrap()[root.as_slice()].as_slice(),
+            &hex("d9e13d0b676ad681164ef0b7b5910d1328ea83a047cad57e619d76bbe3a08525c878da4f6d2bc3d9e59af3c6ef3aaf72b248998c30a4b77a4e7de79a899daf72")[..]
+        );
+
+        let metainfo = TorrentBuilder::new(&path)
+            .version(Version::V2)
+            .piece_length(64 * 1024)
+            .build()?;
+        let files = metainfo.info.file_tree.as_ref().unwrap().files();
+        assert_eq!(files[0].1.pieces_root.as_ref(), Some(root));
+        assert_eq!(metainfo.piece_layers.map(|l| l.len()), Some(0));
+        Ok(())
+    }
+
+    #[cfg(feature = "sha2")]
+    #[test]
+    fn test_hybrid_dir() -> Result<()> {
+        let dir = temp_dir("hybrid_dir")?.join("files");
+        fs::create_dir_all(dir.join("sub"))?;
+        fs::write(dir.join("sub").join("b.txt"), b"hello")?;
+        fs::write(dir.join("sub").join("c.txt"), b"world")?;
+        fs::write(dir.join("sub").join("empty"), b"")?;
+        fs::write(dir.join("a.bin"), test_data())?;
+
+        let metainfo = TorrentBuilder::new(&dir)
+            .version(Version::Hybrid)
+            .piece_length(32 * 1024)
+            .build()?;
+        let info = &metainfo.info;
+        assert!(info.is_hybrid());
+
+        let files = info.files.as_ref().unwrap();
+        assert_eq!(files.len(), 6);
+        assert!(files[1].is_padding());
+        assert_eq!(files[1].length, 64 * 1024 - 40000);
+        assert_eq!(
+            files[2].path,
+            vec![ByteBuf::from("sub"), ByteBuf::from("b.txt")]
+        );
+        assert!(files[3].is_padding());
+        assert_eq!(files[3].length, 32 * 1024 - 5);
+        assert_eq!(files[5].length, 0);
+        assert!(!files[5].is_padding());
+        assert_eq!(info.piece_count(), 4);
+        assert_eq!(info.total_length(), 4 * 32 * 1024 - (32 * 1024 - 5));
+
+        let tree_files = info.file_tree.as_ref().unwrap().files();
+        assert_eq!(tree_files.len(), 4);
+        assert_eq!(tree_files[1].0, vec![&b"sub"[..], &b"b.txt"[..]]);
+        assert_eq!(tree_files[3].1.pieces_root, None);
+        assert_eq!(metainfo.piece_layers.as_ref().map(BTreeMap::len), Some(1));
+
+        let encoded = metainfo.to_vec()?;
+        assert_eq!(MetaInfo::from_slice(&encoded)?, metainfo);
+        Ok(())
+    }
+}
@@ -0,0 +1,413 @@
+//! Edits metainfo files while preserving the original encoding.
+
+use crate::error::{Error, Result};
+use core::ops::{Deref, DerefMut};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_bytes::Bytes;
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{string::String, vec, vec::Vec};
+#[cfg(feature = "std")]
+use std::{string::String, vec, vec::Vec};
+
+/// A value in a [`DictEditor`].
+#[derive(Clone, Debug)]
+enum Node<'a> {
+    /// An unmodified value from the original data.
+    Raw(&'a [u8]),
+    /// A dictionary from the original data which may have modified entries.
+    Dict(DictEditor<'a>),
+    /// A new or replaced value.
+    Encoded(Vec<u8>),
+}
+
+impl<'a> Node<'a> {
+    fn write_to(&self, out: &mut Vec<u8>) {
+        match self {
+            Node::Raw(raw) => out.extend_from_slice(raw),
+            Node::Dict(dict) => dict.write_to(out),
+            Node::Encoded(encoded) => out.extend_from_slice(encoded),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Entry<'a> {
+    key: Vec<u8>,
+    /// The encoded key (including the length prefix).
+    raw_key: Vec<u8>,
+    node: Node<'a>,
+}
+
+/// Edits the entries of an encoded dictionary.
+///
+/// Entries which are not modified are written with their original bytes.
+/// Nested dictionaries can be edited with [`dict_mut`][DictEditor::dict_mut]
+/// and only the modified entries within them are re-encoded.
+#[derive(Clone, Debug)]
+pub struct DictEditor<'a> {
+    entries: Vec<Entry<'a>>,
+}
+
+impl<'a> DictEditor<'a> {
+    /// Parses the entries of an encoded dictionary.
+    ///
+    /// The values of the entries are not parsed until they are edited.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the data is not a single valid dictionary.
+    pub fn new(s: &'a [u8]) -> Result<Self> {
+        let mut values = crate::raw_values(s);
+        let raw = match values.next() {
+            Some(value) => value?.1,
+            None => return Err(Error::EofWhileParsingValue),
+        };
+        if values.next().is_some() {
+            return Err(Error::TrailingData);
+        }
+        if raw.first() != Some(&b'd') {
+            return Err(Error::InvalidDict);
+        }
+
+        let mut entries = Vec::new();
+        let mut values = crate::raw_values(&raw[1..raw.len() - 1]);
+        while let Some(raw_key) = values.next() {
+            let raw_key = raw_key?.1;
+            let key: &Bytes = crate::from_slice(raw_key)?;
+            let node = match values.next() {
+                Some(value) => Node::Raw(value?.1),
+                None => return Err(Error::KeyWithoutValue),
+            };
+            entries.push(Entry {
+                key: key.to_vec(),
+                raw_key: raw_key.to_vec(),
+                node,
+            });
+        }
+        Ok(DictEditor { entries })
+    }
+
+    fn position(&self, key: &[u8]) -> Option<usize> {
+        self.entries.iter().position(|e| e.key == key)
+    }
+
+    /// Returns true if the dictionary has an entry for the key.
+    #[must_use]
+    pub fn contains_key(&self, key: &[u8]) -> bool {
+        self.position(key).is_some()
+    }
+
+    /// Returns the keys in the order they are encoded.
+    pub fn keys(&self) -> impl Iterator<Item = &[u8]> {
+        self.entries.iter().map(|e| e.key.as_slice())
+    }
+
+    /// Returns the encoded value for the key.
+    #[must_use]
+    pub fn get_raw(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.position(key).map(|idx| {
+            let mut out = Vec::new();
+            self.entries[idx].node.write_to(&mut out);
+            out
+        })
+    }
+
+    /// Deserializes the value for the key.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the value cannot be deserialized into the type.
+    pub fn get<T: DeserializeOwned>(&self, key: &[u8]) -> Result<Option<T>> {
+        self.get_raw(key)
+            .map(|raw| crate::from_slice(&raw))
+            .transpose()
+    }
+
+    /// Sets the value for the key.
+    ///
+    /// An existing entry is replaced in place. A new entry is inserted before
+    /// the first key which sorts after it, so a dictionary with sorted keys
+    /// stays sorted.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the value cannot be serialized.
+    pub fn set<T: ?Sized + Serialize>(&mut self, key: &[u8], value: &T) -> Result<()> {
+        let node = Node::Encoded(crate::to_vec(value)?);
+        match self.position(key) {
+            Some(idx) => self.entries[idx].node = node,
+            None => {
+                let idx = self
+                    .entries
+                    .iter()
+                    .position(|e| e.key.as_slice() > key)
+                    .unwrap_or(self.entries.len());
+                self.entries.insert(
+                    idx,
+                    Entry {
+                        key: key.to_vec(),
+                        raw_key: crate::to_vec(Bytes::new(key))?,
+                        node,
+                    },
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes the entry for the key and returns true if it existed.
+    pub fn remove(&mut self, key: &[u8]) -> bool {
+        match self.position(key) {
+            Some(idx) => {
+                self.entries.remove(idx);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns an editor for the nested dictionary value for the key.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the value is not a dictionary.
+    pub fn dict_mut(&mut self, key: &[u8]) -> Result<Option<&mut DictEditor<'a>>> {
+        let idx = match self.position(key) {
+            Some(idx) => idx,
+            None => return Ok(None),
+        };
+        let node = &mut self.entries[idx].node;
+        match node {
+            Node::Raw(raw) => *node = Node::Dict(DictEditor::new(raw)?),
+            Node::Dict(_) => {}
+            Node::Encoded(_) => return Err(Error::InvalidDict),
+        }
+        match node {
+            Node::Dict(dict) => Ok(Some(dict)),
+            Node::Raw(_) | Node::Encoded(_) => unreachable!(),
+        }
+    }
+
+    fn write_to(&self, out: &mut Vec<u8>) {
+        out.push(b'd');
+        for entry in &self.entries {
+            out.extend_from_slice(&entry.raw_key);
+            entry.node.write_to(out);
+        }
+        out.push(b'e');
+    }
+
+    /// Encodes the dictionary into a new [Vec].
+    #[must_use]
+    pub fn to_vec(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.write_to(&mut out);
+        out
+    }
+}
+
+/// Edits a metainfo file while preserving the bytes of unmodified values.
+///
+/// Modifying the top-level entries (e.g. adding a tracker) keeps the `info`
+/// dictionary byte-for-byte identical, so the info hash does not change.
+/// Entries in the `info` dictionary can also be edited (e.g. to set the
+/// private flag) which creates a torrent with a new info hash.
+///
+/// ```rust
+/// use bt_bencode::torrent::Editor;
+///
+/// let input = b"d8:announce8:http://a4:infod4:name1:a6:pieces0:ee";
+/// let mut editor = Editor::new(input)?;
+/// editor.add_tracker("http://b")?;
+///
+/// assert_eq!(
+///     editor.to_vec(),
+///     &b"d8:announce8:http://a13:announce-listll8:http://ael8:http://bee4:infod4:name1:a6:pieces0:ee"[..]
+/// );
+/// # Ok::<(), bt_bencode::Error>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct Editor<'a> {
+    root: DictEditor<'a>,
+}
+
+impl<'a> Editor<'a> {
+    /// Parses an encoded metainfo file.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the data is not a dictionary with an `info`
+    /// dictionary.
+    pub fn new(s: &'a [u8]) -> Result<Self> {
+        let root = DictEditor::new(s)?;
+        match root.entries.iter().find(|e| e.key == b"info") {
+            Some(Entry {
+                node: Node::Raw(raw),
+                ..
+            }) if raw.first() == Some(&b'd') => {}
+            _ => {
+                return Err(Error::Deserialize(String::from(
+                    "metainfo does not have an info dictionary",
+                )))
+            }
+        }
+        Ok(Editor { root })
+    }
+
+    /// Returns an editor for the `info` dictionary.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the `info` value was replaced with a value
+    /// which is not a dictionary.
+    pub fn info_mut(&mut self) -> Result<&mut DictEditor<'a>> {
+        self.root
+            .dict_mut(b"info")?
+            .ok_or_else(|| Error::Deserialize(String::from("missing info dictionary")))
+    }
+
+    /// Adds a tracker URL as a new tier in the `announce-list`.
+    ///
+    /// If there is no `announce` URL, it is set to the URL. If there is no
+    /// `announce-list`, one is created with the existing `announce` URL as
+    /// the first tier. Nothing is changed if the URL is already listed.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the existing tracker fields are invalid.
+    pub fn add_tracker(&mut self, url: &str) -> Result<()> {
+        let announce: Option<String> = self.root.get(b"announce")?;
+        let announce_list: Option<Vec<Vec<String>>> = self.root.get(b"announce-list")?;
+        if announce.is_none() {
+            self.root.set(b"announce", url)?;
+            if announce_list.is_none() {
+                return Ok(());
+            }
+        }
+
+        let mut tiers =
+            announce_list.unwrap_or_else(|| announce.iter().map(|a| vec![a.clone()]).collect());
+        if tiers.iter().flatten().any(|u| u == url) {
+            return Ok(());
+        }
+        tiers.push(vec![String::from(url)]);
+        self.root.set(b"announce-list", &tiers)
+    }
+
+    /// Sets or removes the `private` flag in the `info` dictionary.
+    ///
+    /// The info hash changes if the flag is changed.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the `info` dictionary cannot be edited.
+    pub fn set_private(&mut self, private: bool) -> Result<()> {
+        let info = self.info_mut()?;
+        if private {
+            info.set(b"private", &1)
+        } else {
+            info.remove(b"private");
+            Ok(())
+        }
+    }
+}
+
+impl<'a> Deref for Editor<'a> {
+    type Target = DictEditor<'a>;
+
+    fn deref(&self) -> &DictEditor<'a> {
+        &self.root
+    }
+}
+
+impl<'a> DerefMut for Editor<'a> {
+    fn deref_mut(&mut self) -> &mut DictEditor<'a> {
+        &mut self.root
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static TORRENT_BYTES: &[u8] =
+        include_bytes!("../../tests/ubuntu-20.04.4-live-server-amd64.iso.torrent");
+
+    #[test]
+    fn test_unmodified() -> Result<()> {
+        let mut editor = Editor::new(TORRENT_BYTES)?;
+        assert_eq!(editor.to_vec(), TORRENT_BYTES);
+        editor.info_mut()?;
+        assert_eq!(editor.to_vec(), TORRENT_BYTES);
+        Ok(())
+    }
+
+    #[test]
+    fn test_preserves_non_canonical_encoding() -> Result<()> {
+        let input = b"d1:ai01e04:infod1:zi-0e1:a0:e1:c3:fooe";
+        let mut editor = Editor::new(input)?;
+        editor.set(b"c", "bar")?;
+        editor.set(b"b", &1)?;
+        assert_eq!(
+            editor.to_vec(),
+            &b"d1:ai01e1:bi1e04:infod1:zi-0e1:a0:e1:c3:bare"[..]
+        );
+
+        editor.set_private(true)?;
+        assert_eq!(
+            editor.to_vec(),
+            &b"d1:ai01e1:bi1e04:infod7:privatei1e1:zi-0e1:a0:e1:c3:bare"[..]
+        );
+        assert!(editor.remove(b"a"));
+        assert!(!editor.remove(b"a"));
+        assert_eq!(editor.get::<String>(b"c")?, Some(String::from("bar")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_add_tracker_and_private() -> Result<()> {
+        let info_hash = crate::torrent::MetaInfo::from_slice(TORRENT_BYTES)?;
+        let mut editor = Editor::new(TORRENT_BYTES)?;
+        editor.add_tracker("udp://tracker.example.com:6969")?;
+        editor.add_tracker("https://torrent.ubuntu.com/announce")?;
+
+        let edited = editor.to_vec();
+        let metainfo = crate::torrent::MetaInfo::from_slice(&edited)?;
+        let tiers = metainfo.announce_list.as_ref().unwrap();
+        assert_eq!(tiers.len(), 3);
+        assert_eq!(tiers[2], vec!["udp://tracker.example.com:6969"]);
+        assert_eq!(metainfo.info, info_hash.info);
+
+        let info = |bytes: &[u8]| -> Result<Vec<u8>> {
+            Ok(DictEditor::new(bytes)?.get_raw(b"info").unwrap())
+        };
+        assert_eq!(info(&edited)?, info(TORRENT_BYTES)?);
+
+        editor.set_private(true)?;
+        let metainfo = crate::torrent::MetaInfo::from_slice(&editor.to_vec())?;
+        assert_eq!(metainfo.info.private, Some(1));
+        editor.set_private(false)?;
+        assert_eq!(info(&editor.to_vec())?, info(TORRENT_BYTES)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_add_tracker_without_announce() -> Result<()> {
+        let mut editor = Editor::new(b"d4:infodee")?;
+        editor.add_tracker("http://a")?;
+        assert_eq!(editor.to_vec(), &b"d8:announce8:http://a4:infodee"[..]);
+        editor.add_tracker("http://a")?;
+        assert_eq!(editor.to_vec(), &b"d8:announce8:http://a4:infodee"[..]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(Editor::new(b"d1:ai1ee").is_err());
+        assert!(Editor::new(b"d4:infoi1ee").is_err());
+        assert!(Editor::new(b"d4:infodeei1e").is_err());
+        assert!(DictEditor::new(b"le").is_err());
+        assert!(DictEditor::new(b"d1:ae").is_err());
+    }
+}
@@ -0,0 +1,193 @@
+//! Decoding of text fields in legacy character encodings.
+
+use super::{Info, MetaInfo};
+use crate::error::{Error, Result};
+use encoding_rs::{Encoding, UTF_8};
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{string::String, vec, vec::Vec};
+#[cfg(feature = "std")]
+use std::{string::String, vec, vec::Vec};
+
+/// Decodes text in an encoding.
+///
+/// A byte order mark is not removed.
+///
+/// # Errors
+///
+/// An error is returned if the bytes are not valid in the encoding.
+pub fn decode_text(bytes: &[u8], encoding: &'static Encoding) -> Result<String> {
+    encoding
+        .decode_without_bom_handling_and_without_replacement(bytes)
+        .map(|s| s.into_owned())
+        .ok_or_else(|| Error::Deserialize(String::from("text is not valid ") + encoding.name()))
+}
+
+/// Decodes text in an encoding, replacing invalid sequences with `U+FFFD`.
+///
+/// A byte order mark is not removed.
+#[must_use]
+pub fn decode_text_lossy(bytes: &[u8], encoding: &'static Encoding) -> String {
+    encoding.decode_without_bom_handling(bytes).0.into_owned()
+}
+
+/// Returns the path components of every file, starting with the name.
+fn raw_paths(info: &Info) -> Vec<Vec<&[u8]>> {
+    let name = info.name.as_slice();
+    if let Some(files) = &info.files {
+        return files
+            .iter()
+            .map(|f| {
+                let mut path = vec![name];
+                path.extend(f.path.iter().map(|c| c.as_slice()));
+                path
+            })
+            .collect();
+    }
+    match (&info.file_tree, info.length) {
+        (Some(tree), None) => {
+            let files = tree.files();
+            if let [(path, _)] = files.as_slice() {
+                if path.len() == 1 && path[0] == name {
+                    return vec![vec![name]];
+                }
+            }
+            files
+                .into_iter()
+                .map(|(path, _)| {
+                    let mut full = vec![name];
+                    full.extend(path);
+                    full
+                })
+                .collect()
+        }
+        _ => vec![vec![name]],
+    }
+}
+
+impl MetaInfo {
+    /// Returns the encoding of the text fields.
+    ///
+    /// The `encoding` field is looked up as a [WHATWG encoding label][labels]
+    /// (e.g. `Shift_JIS` or `GBK`). If the field is missing or the label is
+    /// unknown, UTF-8 is returned.
+    ///
+    /// [labels]: https://encoding.spec.whatwg.org/#names-and-labels
+    #[must_use]
+    pub fn text_encoding(&self) -> &'static Encoding {
+        self.encoding
+            .as_ref()
+            .and_then(|label| Encoding::for_label(label.trim().as_bytes()))
+            .unwrap_or(UTF_8)
+    }
+
+    /// Decodes the name of the torrent.
+    ///
+    /// If `encoding` is `None`, the [`text_encoding`][MetaInfo::text_encoding] is used.
+    ///
+    /// ```rust
+    /// use bt_bencode::torrent::MetaInfo;
+    ///
+    /// let input = b"d8:encoding9:Shift_JIS4:infod6:lengthi1e4:name4:\x83\x65\x83\x5812:piece lengthi16384eee";
+    /// let metainfo = MetaInfo::from_slice(input)?;
+    ///
+    /// assert_eq!(metainfo.decode_name(None)?, "テス");
+    /// assert!(metainfo.decode_name(Some(encoding_rs::UTF_8)).is_err());
+    /// # Ok::<(), bt_bencode::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the name is not valid in the encoding.
+    pub fn decode_name(&self, encoding: Option<&'static Encoding>) -> Result<String> {
+        decode_text(
+            &self.info.name,
+            encoding.unwrap_or_else(|| self.text_encoding()),
+        )
+    }
+
+    /// Decodes the name of the torrent, replacing invalid sequences with `U+FFFD`.
+    ///
+    /// If `encoding` is `None`, the [`text_encoding`][MetaInfo::text_encoding] is used.
+    #[must_use]
+    pub fn decode_name_lossy(&self, encoding: Option<&'static Encoding>) -> String {
+        decode_text_lossy(
+            &self.info.name,
+            encoding.unwrap_or_else(|| self.text_encoding()),
+        )
+    }
+
+    /// Decodes the path components of every file.
+    ///
+    /// The first component of each path is the name. The paths are in the
+    /// same order as [`Info::file_paths`].
+    ///
+    /// If `encoding` is `None`, the [`text_encoding`][MetaInfo::text_encoding] is used.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if any component is not valid in the encoding.
+    pub fn decode_file_paths(
+        &self,
+        encoding: Option<&'static Encoding>,
+    ) -> Result<Vec<Vec<String>>> {
+        let encoding = encoding.unwrap_or_else(|| self.text_encoding());
+        raw_paths(&self.info)
+            .into_iter()
+            .map(|path| path.into_iter().map(|c| decode_text(c, encoding)).collect())
+            .collect()
+    }
+
+    /// Decodes the path components of every file, replacing invalid sequences with `U+FFFD`.
+    ///
+    /// If `encoding` is `None`, the [`text_encoding`][MetaInfo::text_encoding] is used.
+    #[must_use]
+    pub fn decode_file_paths_lossy(&self, encoding: Option<&'static Encoding>) -> Vec<Vec<String>> {
+        let encoding = encoding.unwrap_or_else(|| self.text_encoding());
+        raw_paths(&self.info)
+            .into_iter()
+            .map(|path| {
+                path.into_iter()
+                    .map(|c| decode_text_lossy(c, encoding))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encoding_rs::{GBK, SHIFT_JIS};
+
+    #[test]
+    fn test_text_encoding() -> Result<()> {
+        let mut metainfo =
+            MetaInfo::from_slice(b"d4:infod6:lengthi1e4:name1:a12:piece lengthi16384eee")?;
+        assert_eq!(metainfo.text_encoding(), UTF_8);
+        metainfo.encoding = Some(String::from("gbk"));
+        assert_eq!(metainfo.text_encoding(), GBK);
+        metainfo.encoding = Some(String::from(" shift_jis "));
+        assert_eq!(metainfo.text_encoding(), SHIFT_JIS);
+        metainfo.encoding = Some(String::from("unknown"));
+        assert_eq!(metainfo.text_encoding(), UTF_8);
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_file_paths() -> Result<()> {
+        let input = b"d8:encoding3:GBK4:infod5:filesld6:lengthi1e4:pathl4:\xd6\xd0\xce\xc4eed6:lengthi1e4:pathl1:\xffeee4:name3:dir12:piece lengthi16384eee";
+        let metainfo = MetaInfo::from_slice(input)?;
+        assert!(metainfo.decode_file_paths(None).is_err());
+        assert_eq!(
+            metainfo.decode_file_paths_lossy(None),
+            vec![
+                vec![String::from("dir"), String::from("中文")],
+                vec![String::from("dir"), String::from("\u{fffd}")],
+            ]
+        );
+        assert_eq!(metainfo.decode_name(None)?, "dir");
+        assert_eq!(metainfo.decode_name_lossy(Some(UTF_8)), "dir");
+        Ok(())
+    }
+}
@@ -0,0 +1,256 @@
+//! The `file tree` structure from BitTorrent v2 metainfo files.
+
+use super::FileFlags;
+use crate::key::ByteStrKey;
+use core::fmt;
+use serde::{
+    de::{self, Deserialize, Deserializer, MapAccess, Visitor},
+    ser::{Serialize, SerializeMap, Serializer},
+};
+use serde_bytes::{ByteBuf, Bytes};
+use serde_derive::{Deserialize, Serialize};
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{collections::BTreeMap, vec::Vec};
+#[cfg(feature = "std")]
+use std::{collections::BTreeMap, vec::Vec};
+
+/// The attributes of a file in a [`FileTree`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileAttrs {
+    /// The length of the file.
+    pub length: u64,
+    /// The root hash of the file's merkle tree.
+    ///
+    /// Empty files do not have a root hash.
+    #[serde(
+        rename = "pieces root",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub pieces_root: Option<ByteBuf>,
+    /// The file's attributes from [BEP 47][bep_0047].
+    ///
+    /// [bep_0047]: https://www.bittorrent.org/beps/bep_0047.html
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attr: Option<ByteBuf>,
+    /// The path components of the target relative to the top-level directory
+    /// if the file is a symbolic link.
+    #[serde(
+        rename = "symlink path",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub symlink_path: Option<Vec<ByteBuf>>,
+}
+
+impl FileAttrs {
+    /// Returns the file's attribute flags.
+    #[must_use]
+    pub fn flags(&self) -> FileFlags {
+        super::attr::flags(&self.attr)
+    }
+}
+
+/// A file or a directory in a [`FileTree`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FileTreeNode {
+    /// A file.
+    File(FileAttrs),
+    /// A directory.
+    Dir(FileTree),
+}
+
+impl FileTreeNode {
+    /// If the node is a file, returns the file's attributes.
+    #[must_use]
+    pub fn as_file(&self) -> Option<&FileAttrs> {
+        match self {
+            FileTreeNode::File(attrs) => Some(attrs),
+            FileTreeNode::Dir(_) => None,
+        }
+    }
+
+    /// If the node is a directory, returns the directory's entries.
+    #[must_use]
+    pub fn as_dir(&self) -> Option<&FileTree> {
+        match self {
+            FileTreeNode::File(_) => None,
+            FileTreeNode::Dir(tree) => Some(tree),
+        }
+    }
+}
+
+/// A tree of files and directories keyed by path component.
+///
+/// In the encoded form, a file is a dictionary with a single empty key which
+/// maps to the file's attributes.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FileTree(pub BTreeMap<ByteStrKey, FileTreeNode>);
+
+impl FileTree {
+    /// Returns the files in the tree with their full paths in path order.
+    #[must_use]
+    pub fn files(&self) -> Vec<(Vec<&[u8]>, &FileAttrs)> {
+        fn collect<'a>(
+            tree: &'a FileTree,
+            path: &mut Vec<&'a [u8]>,
+            files: &mut Vec<(Vec<&'a [u8]>, &'a FileAttrs)>,
+        ) {
+            for (name, node) in &tree.0 {
+                path.push(name.as_slice());
+                match node {
+                    FileTreeNode::File(attrs) => files.push((path.clone(), attrs)),
+                    FileTreeNode::Dir(tree) => collect(tree, path, files),
+                }
+                path.pop();
+            }
+        }
+
+        let mut files = Vec::new();
+        collect(self, &mut Vec::new(), &mut files);
+        files
+    }
+
+    /// Returns the total length of all of the files in the tree.
+    #[must_use]
+    pub fn total_length(&self) -> u64 {
+        self.0
+            .values()
+            .map(|node| match node {
+                FileTreeNode::File(attrs) => attrs.length,
+                FileTreeNode::Dir(tree) => tree.total_length(),
+            })
+            .sum()
+    }
+}
+
+impl Serialize for FileTree {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for FileTree {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match FileTreeNode::deserialize(deserializer)? {
+            FileTreeNode::Dir(tree) => Ok(tree),
+            FileTreeNode::File(_) => Err(de::Error::custom("expected a directory")),
+        }
+    }
+}
+
+impl Serialize for FileTreeNode {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            FileTreeNode::File(attrs) => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry(Bytes::new(b""), attrs)?;
+                map.end()
+            }
+            FileTreeNode::Dir(tree) => tree.serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for FileTreeNode {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct NodeVisitor;
+
+        impl<'de> Visitor<'de> for NodeVisitor {
+            type Value = FileTreeNode;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a file tree dictionary")
+            }
+
+            fn visit_map<V>(self, mut map: V) -> Result<Self::Value, V::Error>
+            where
+                V: MapAccess<'de>,
+            {
+                let mut entries = BTreeMap::new();
+                let mut attrs = None;
+                while let Some(name) = map.next_key::<ByteStrKey>()? {
+                    if name.is_empty() {
+                        attrs = Some(map.next_value::<FileAttrs>()?);
+                    } else {
+                        entries.insert(name, map.next_value::<FileTreeNode>()?);
+                    }
+                }
+
+                match attrs {
+                    Some(attrs) if entries.is_empty() => Ok(FileTreeNode::File(attrs)),
+                    Some(_) => Err(de::Error::custom(
+                        "file tree entry is both a file and a directory",
+                    )),
+                    None => Ok(FileTreeNode::Dir(FileTree(entries))),
+                }
+            }
+        }
+
+        deserializer.deserialize_map(NodeVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Result;
+
+    #[cfg(all(feature = "alloc", not(feature = "std")))]
+    use alloc::vec;
+
+    #[test]
+    fn test_file_tree() -> Result<()> {
+        let input = b"d5:c.txtd0:d6:lengthi4e11:pieces root4:rootee3:dird1:ad0:d6:lengthi3eee1:bd0:d6:lengthi0eeeee";
+        let tree: FileTree = crate::from_slice(input)?;
+
+        let files = tree.files();
+        assert_eq!(files.len(), 3);
+        assert_eq!(files[0].0, vec![&b"c.txt"[..]]);
+        assert_eq!(files[0].1.pieces_root, Some(ByteBuf::from(&b"root"[..])));
+        assert_eq!(files[1].0, vec![&b"dir"[..], &b"a"[..]]);
+        assert_eq!(files[2].0, vec![&b"dir"[..], &b"b"[..]]);
+        assert_eq!(files[2].1.pieces_root, None);
+        assert_eq!(tree.total_length(), 7);
+
+        assert_eq!(crate::to_vec(&tree)?, &input[..]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_flags() -> Result<()> {
+        let input = b"d1:ad0:d4:attr1:l6:lengthi0e12:symlink pathl1:beee1:bd0:d6:lengthi0eeee";
+        let tree: FileTree = crate::from_slice(input)?;
+
+        let files = tree.files();
+        assert_eq!(files[0].1.flags(), FileFlags::SYMLINK);
+        assert_eq!(
+            files[0].1.symlink_path,
+            Some(vec![ByteBuf::from(&b"b"[..])])
+        );
+        assert!(files[1].1.flags().is_empty());
+
+        assert_eq!(crate::to_vec(&tree)?, &input[..]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_and_dir() {
+        let input = b"d1:ad0:d6:lengthi3ee1:bd0:d6:lengthi0eeeee";
+        assert!(crate::from_slice::<FileTree>(input).is_err());
+        assert!(crate::from_slice::<FileTree>(b"d0:d6:lengthi3eee").is_err());
+    }
+}
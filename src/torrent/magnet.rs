@@ -0,0 +1,365 @@
+//! Magnet URIs.
+
+use crate::error::{Error, Result};
+use core::{fmt, str::FromStr};
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{string::String, vec::Vec};
+#[cfg(feature = "std")]
+use std::{string::String, vec::Vec};
+
+const HEX: &[u8; 16] = b"0123456789abcdef";
+
+/// The multihash prefix for a SHA-256 digest used in a v2 `urn:btmh:` topic.
+const SHA256_MULTIHASH_PREFIX: &str = "1220";
+
+/// A magnet URI which identifies a torrent by its info hash.
+///
+/// ```rust
+/// use bt_bencode::torrent::MagnetLink;
+///
+/// let link: MagnetLink =
+///     "magnet:?xt=urn:btih:b44a0e20fa5b7cecb77156333b4268dfd7c30afb&dn=ubuntu.iso&tr=https%3A%2F%2Ftorrent.ubuntu.com%2Fannounce".parse()?;
+/// assert_eq!(link.display_name.as_deref(), Some("ubuntu.iso"));
+/// assert_eq!(link.trackers, vec!["https://torrent.ubuntu.com/announce"]);
+/// assert_eq!(
+///     link.to_string(),
+///     "magnet:?xt=urn:btih:b44a0e20fa5b7cecb77156333b4268dfd7c30afb&dn=ubuntu.iso&tr=https%3A%2F%2Ftorrent.ubuntu.com%2Fannounce"
+/// );
+/// # Ok::<(), bt_bencode::Error>(())
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MagnetLink {
+    /// The v1 info hash from a `urn:btih:` exact topic.
+    pub info_hash_v1: Option<[u8; 20]>,
+    /// The v2 info hash from a `urn:btmh:` exact topic.
+    pub info_hash_v2: Option<[u8; 32]>,
+    /// The display name (`dn`).
+    pub display_name: Option<String>,
+    /// The tracker URLs (`tr`).
+    pub trackers: Vec<String>,
+    /// The web seed URLs (`ws`).
+    pub web_seeds: Vec<String>,
+}
+
+impl MagnetLink {
+    /// Creates a magnet link for an encoded metainfo file.
+    ///
+    /// The info hashes are computed from the raw encoding of the `info`
+    /// value. The v2 info hash is only included if the `sha2` feature is
+    /// enabled. The trackers and web seeds are from
+    /// [`MetaInfo::trackers`][super::MetaInfo::trackers] and
+    /// [`MetaInfo::web_seeds`][super::MetaInfo::web_seeds].
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the data is not a valid metainfo file.
+    #[cfg(feature = "sha1")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "sha1")))]
+    pub fn from_metainfo(s: &[u8]) -> Result<Self> {
+        let metainfo = super::MetaInfo::from_slice(s)?;
+        let info_hash_v1 = if metainfo.info.is_v1() {
+            Some(super::info_hash_v1(s)?)
+        } else {
+            None
+        };
+        #[cfg(feature = "sha2")]
+        let info_hash_v2 = if metainfo.info.is_v2() {
+            Some(super::info_hash_v2(s)?)
+        } else {
+            None
+        };
+        #[cfg(not(feature = "sha2"))]
+        let info_hash_v2 = None;
+
+        let trackers = metainfo.trackers().into_iter().map(String::from).collect();
+        let web_seeds = metainfo.web_seeds().into_iter().map(String::from).collect();
+
+        Ok(MagnetLink {
+            info_hash_v1,
+            info_hash_v2,
+            display_name: String::from_utf8(metainfo.info.name.into_vec()).ok(),
+            trackers,
+            web_seeds,
+        })
+    }
+}
+
+fn invalid(msg: &str) -> Error {
+    Error::Deserialize(String::from(msg))
+}
+
+fn write_hex(f: &mut fmt::Formatter<'_>, bytes: &[u8]) -> fmt::Result {
+    for b in bytes {
+        write!(
+            f,
+            "{}{}",
+            char::from(HEX[usize::from(b >> 4)]),
+            char::from(HEX[usize::from(b & 0xf)])
+        )?;
+    }
+    Ok(())
+}
+
+fn write_encoded(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                write!(f, "{}", char::from(b))?;
+            }
+            _ => {
+                write!(
+                    f,
+                    "%{}{}",
+                    char::from(HEX[usize::from(b >> 4)].to_ascii_uppercase()),
+                    char::from(HEX[usize::from(b & 0xf)].to_ascii_uppercase())
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
+
+impl fmt::Display for MagnetLink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("magnet:?")?;
+        let mut sep = "";
+        if let Some(hash) = &self.info_hash_v1 {
+            f.write_str("xt=urn:btih:")?;
+            write_hex(f, hash)?;
+            sep = "&";
+        }
+        if let Some(hash) = &self.info_hash_v2 {
+            write!(f, "{}xt=urn:btmh:{}", sep, SHA256_MULTIHASH_PREFIX)?;
+            write_hex(f, hash)?;
+            sep = "&";
+        }
+        if let Some(name) = &self.display_name {
+            write!(f, "{}dn=", sep)?;
+            write_encoded(f, name)?;
+            sep = "&";
+        }
+        for url in &self.trackers {
+            write!(f, "{}tr=", sep)?;
+            write_encoded(f, url)?;
+            sep = "&";
+        }
+        for url in &self.web_seeds {
+            write!(f, "{}ws=", sep)?;
+            write_encoded(f, url)?;
+            sep = "&";
+        }
+        Ok(())
+    }
+}
+
+fn hex_value(b: u8) -> Option<u8> {
+    match b {
+        b'0'..=b'9' => Some(b - b'0'),
+        b'a'..=b'f' => Some(b - b'a' + 10),
+        b'A'..=b'F' => Some(b - b'A' + 10),
+        _ => None,
+    }
+}
+
+fn decode_hex(s: &str, out: &mut [u8]) -> Result<()> {
+    let s = s.as_bytes();
+    if s.len() != out.len() * 2 {
+        return Err(invalid("invalid info hash length"));
+    }
+    for (b, pair) in out.iter_mut().zip(s.chunks(2)) {
+        match (hex_value(pair[0]), hex_value(pair[1])) {
+            (Some(hi), Some(lo)) => *b = hi << 4 | lo,
+            _ => return Err(invalid("invalid hex in info hash")),
+        }
+    }
+    Ok(())
+}
+
+fn decode_base32(s: &str, out: &mut [u8; 20]) -> Result<()> {
+    let mut bits: u64 = 0;
+    let mut n_bits = 0;
+    let mut idx = 0;
+    for b in s.bytes() {
+        let v = match b {
+            b'A'..=b'Z' => b - b'A',
+            b'a'..=b'z' => b - b'a',
+            b'2'..=b'7' => b - b'2' + 26,
+            _ => return Err(invalid("invalid base32 in info hash")),
+        };
+        bits = bits << 5 | u64::from(v);
+        n_bits += 5;
+        if n_bits >= 8 {
+            n_bits -= 8;
+            out[idx] = (bits >> n_bits) as u8;
+            idx += 1;
+        }
+    }
+    Ok(())
+}
+
+fn decode_component(s: &str) -> Result<String> {
+    let s = s.as_bytes();
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut idx = 0;
+    while idx < s.len() {
+        match s[idx] {
+            b'%' => {
+                let hi = s.get(idx + 1).copied().and_then(hex_value);
+                let lo = s.get(idx + 2).copied().and_then(hex_value);
+                match (hi, lo) {
+                    (Some(hi), Some(lo)) => bytes.push(hi << 4 | lo),
+                    _ => return Err(invalid("invalid percent encoding")),
+                }
+                idx += 3;
+            }
+            b'+' => {
+                bytes.push(b' ');
+                idx += 1;
+            }
+            b => {
+                bytes.push(b);
+                idx += 1;
+            }
+        }
+    }
+    String::from_utf8(bytes).map_err(|e| Error::Utf8Error(e.utf8_error()))
+}
+
+impl FromStr for MagnetLink {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let query =
+            strip_prefix_ignore_case(s, "magnet:?").ok_or_else(|| invalid("not a magnet URI"))?;
+
+        let mut link = MagnetLink::default();
+        for param in query.split('&').filter(|p| !p.is_empty()) {
+            let mut kv = param.splitn(2, '=');
+            let key = kv.next().unwrap_or_default();
+            let value = decode_component(kv.next().unwrap_or_default())?;
+            match key {
+                "xt" => {
+                    if let Some(hash) = strip_prefix_ignore_case(&value, "urn:btih:") {
+                        let mut info_hash = [0; 20];
+                        if hash.len() == 32 {
+                            decode_base32(hash, &mut info_hash)?;
+                        } else {
+                            decode_hex(hash, &mut info_hash)?;
+                        }
+                        link.info_hash_v1 = Some(info_hash);
+                    } else if let Some(hash) = strip_prefix_ignore_case(&value, "urn:btmh:") {
+                        let hash = strip_prefix_ignore_case(hash, SHA256_MULTIHASH_PREFIX)
+                            .ok_or_else(|| invalid("unsupported multihash"))?;
+                        let mut info_hash = [0; 32];
+                        decode_hex(hash, &mut info_hash)?;
+                        link.info_hash_v2 = Some(info_hash);
+                    }
+                }
+                "dn" => link.display_name = Some(value),
+                "tr" => link.trackers.push(value),
+                "ws" => link.web_seeds.push(value),
+                _ => {}
+            }
+        }
+
+        if link.info_hash_v1.is_none() && link.info_hash_v2.is_none() {
+            return Err(invalid("magnet URI does not have an info hash"));
+        }
+        Ok(link)
+    }
+}
+
+fn strip_prefix_ignore_case<'a>(s: &'a str, prefix: &str) -> Option<&'a str> {
+    if s.len() >= prefix.len()
+        && s.is_char_boundary(prefix.len())
+        && s[..prefix.len()].eq_ignore_ascii_case(prefix)
+    {
+        Some(&s[prefix.len()..])
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(all(feature = "alloc", not(feature = "std")))]
+    use alloc::{string::ToString, vec};
+
+    const V1_HASH: [u8; 20] = [
+        0xb4, 0x4a, 0x0e, 0x20, 0xfa, 0x5b, 0x7c, 0xec, 0xb7, 0x71, 0x56, 0x33, 0x3b, 0x42, 0x68,
+        0xdf, 0xd7, 0xc3, 0x0a, 0xfb,
+    ];
+
+    #[test]
+    fn test_parse() -> Result<()> {
+        let link: MagnetLink = "magnet:?xt=urn:btih:B44A0E20FA5B7CECB77156333B4268DFD7C30AFB&dn=a+b%20c&tr=udp%3A%2F%2Fa&tr=udp://b&ws=http%3A%2F%2Fw&x.pe=1.2.3.4:5"
+            .parse()?;
+        assert_eq!(link.info_hash_v1, Some(V1_HASH));
+        assert_eq!(link.info_hash_v2, None);
+        assert_eq!(link.display_name.as_deref(), Some("a b c"));
+        assert_eq!(link.trackers, vec!["udp://a", "udp://b"]);
+        assert_eq!(link.web_seeds, vec!["http://w"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_base32_and_v2() -> Result<()> {
+        let link: MagnetLink =
+            "magnet:?xt=urn:btih:WRFA4IH2LN6OZN3RKYZTWQTI37L4GCX3&xt=urn:btmh:12200101010101010101010101010101010101010101010101010101010101010101"
+                .parse()?;
+        assert_eq!(link.info_hash_v1, Some(V1_HASH));
+        assert_eq!(link.info_hash_v2, Some([1; 32]));
+        assert_eq!(link.to_string().parse::<MagnetLink>()?, link);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        for input in &[
+            "http://example.com",
+            "magnet:\u{e9}?xt",
+            "magnet:?dn=a",
+            "magnet:?xt=urn:btih:b44a",
+            "magnet:?xt=urn:btih:zz4a0e20fa5b7cecb77156333b4268dfd7c30afb",
+            "magnet:?xt=urn:btmh:1114aaaa",
+            "magnet:?xt=urn:btih:b44a0e20fa5b7cecb77156333b4268dfd7c30afb&dn=%zz",
+        ] {
+            assert!(input.parse::<MagnetLink>().is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_display() {
+        let link = MagnetLink {
+            info_hash_v1: None,
+            info_hash_v2: Some([0xab; 32]),
+            display_name: Some(String::from("a b/c")),
+            trackers: vec![String::from("udp://t:1")],
+            web_seeds: vec![String::from("http://w/")],
+        };
+        assert_eq!(
+            link.to_string(),
+            "magnet:?xt=urn:btmh:1220abababababababababababababababababababababababababababababababab&dn=a%20b%2Fc&tr=udp%3A%2F%2Ft%3A1&ws=http%3A%2F%2Fw%2F"
+        );
+    }
+
+    #[cfg(feature = "sha1")]
+    #[test]
+    fn test_from_metainfo() -> Result<()> {
+        let link = MagnetLink::from_metainfo(include_bytes!(
+            "../../tests/ubuntu-20.04.4-live-server-amd64.iso.torrent"
+        ))?;
+        assert_eq!(link.info_hash_v1, Some(V1_HASH));
+        assert_eq!(link.info_hash_v2, None);
+        assert_eq!(
+            link.display_name.as_deref(),
+            Some("ubuntu-20.04.4-live-server-amd64.iso")
+        );
+        assert_eq!(link.trackers[0], "https://torrent.ubuntu.com/announce");
+        assert_eq!(link.trackers.len(), 2);
+        Ok(())
+    }
+}
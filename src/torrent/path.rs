@@ -0,0 +1,106 @@
+//! Validates file paths from metainfo files.
+
+use crate::error::{Error, Result};
+use std::{
+    path::{Component, Path, PathBuf},
+    str,
+    string::String,
+};
+
+fn unsafe_path(reason: &str) -> Error {
+    Error::Deserialize(String::from("unsafe path: ") + reason)
+}
+
+/// Validates a single path component and returns it as a string.
+fn component(bytes: &[u8]) -> Result<&str> {
+    if bytes.is_empty() {
+        return Err(unsafe_path("empty component"));
+    }
+    if bytes.contains(&0) {
+        return Err(unsafe_path("component contains a NUL byte"));
+    }
+    if bytes.contains(&b'/') || bytes.contains(&b'\\') {
+        return Err(unsafe_path("component contains a path separator"));
+    }
+    if bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' {
+        return Err(unsafe_path("component has a drive prefix"));
+    }
+    let s = str::from_utf8(bytes)?;
+    let mut components = Path::new(s).components();
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(c)), None) if c == s => Ok(s),
+        (Some(Component::ParentDir), None) => Err(unsafe_path("parent directory component")),
+        (Some(Component::CurDir), None) | (None, None) => {
+            Err(unsafe_path("current directory component"))
+        }
+        _ => Err(unsafe_path("component is not a plain file name")),
+    }
+}
+
+/// Joins path components from a metainfo file into a relative path.
+///
+/// Every component must be a plain file name. Paths with empty components,
+/// `.` or `..` components, path separators, NUL bytes, drive or root
+/// prefixes, or invalid UTF-8 are rejected, so the returned path can be
+/// joined to a download directory without escaping it.
+///
+/// ```rust
+/// use bt_bencode::torrent::safe_path;
+/// use std::path::PathBuf;
+///
+/// assert_eq!(safe_path(&["dir", "a.txt"])?, PathBuf::from("dir").join("a.txt"));
+/// assert!(safe_path(&["..", "etc", "passwd"]).is_err());
+/// assert!(safe_path(&["/etc"]).is_err());
+/// # Ok::<(), bt_bencode::Error>(())
+/// ```
+///
+/// # Errors
+///
+/// An error is returned if there are no components or if any component is unsafe.
+pub fn safe_path<C: AsRef<[u8]>>(components: &[C]) -> Result<PathBuf> {
+    if components.is_empty() {
+        return Err(unsafe_path("no components"));
+    }
+    let mut path = PathBuf::new();
+    for c in components {
+        path.push(component(c.as_ref())?);
+    }
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_safe_path() -> Result<()> {
+        assert_eq!(safe_path(&["a"])?, PathBuf::from("a"));
+        assert_eq!(
+            safe_path(&[&b"a"[..], b"b c", b".hidden", b"..."])?,
+            ["a", "b c", ".hidden", "..."].iter().collect::<PathBuf>()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_unsafe_path() {
+        let empty: &[&str] = &[];
+        assert!(safe_path(empty).is_err());
+        for components in &[
+            &[&b""[..]][..],
+            &[b"a", b""],
+            &[b"."],
+            &[b".."],
+            &[b"a", b"..", b"b"],
+            &[b"/"],
+            &[b"/etc"],
+            &[b"a/b"],
+            &[b"a\\b"],
+            &[b"C:"],
+            &[b"a\0"],
+            &[b"\xff"],
+        ] {
+            assert!(safe_path(components).is_err(), "{:?}", components);
+        }
+    }
+}
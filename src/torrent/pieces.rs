@@ -0,0 +1,156 @@
+//! Access to the piece hashes in the `pieces` field.
+
+use super::PIECE_HASH_LEN;
+use crate::error::{Error, Result};
+use core::{convert::TryFrom, iter::FusedIterator, ops::Index, slice};
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::string::String;
+#[cfg(feature = "std")]
+use std::string::String;
+
+/// The SHA-1 hashes of each piece in a v1 torrent.
+///
+/// ```rust
+/// use bt_bencode::torrent::Pieces;
+///
+/// let pieces = Pieces::new(&[1; 40])?;
+/// assert_eq!(pieces.len(), 2);
+/// assert_eq!(pieces[1], [1; 20]);
+/// assert_eq!(pieces.iter().count(), 2);
+/// assert!(Pieces::new(&[1; 41]).is_err());
+/// # Ok::<(), bt_bencode::Error>(())
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Pieces<'a>(&'a [u8]);
+
+impl<'a> Pieces<'a> {
+    /// Wraps the value of a `pieces` field.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the length is not a multiple of 20.
+    pub fn new(bytes: &'a [u8]) -> Result<Self> {
+        if bytes.len() % PIECE_HASH_LEN != 0 {
+            return Err(Error::Deserialize(String::from(
+                "pieces length is not a multiple of 20",
+            )));
+        }
+        Ok(Pieces(bytes))
+    }
+
+    /// Returns the number of piece hashes.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.len() / PIECE_HASH_LEN
+    }
+
+    /// Returns true if there are no piece hashes.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the hash for the piece at the index.
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<&'a [u8; 20]> {
+        let start = index.checked_mul(PIECE_HASH_LEN)?;
+        let hash = self.0.get(start..start.checked_add(PIECE_HASH_LEN)?)?;
+        <&[u8; 20]>::try_from(hash).ok()
+    }
+
+    /// Returns an iterator over the piece hashes.
+    #[must_use]
+    pub fn iter(&self) -> PiecesIter<'a> {
+        PiecesIter(self.0.chunks_exact(PIECE_HASH_LEN))
+    }
+
+    /// Returns the concatenated piece hashes.
+    #[must_use]
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+}
+
+impl<'a> Index<usize> for Pieces<'a> {
+    type Output = [u8; 20];
+
+    fn index(&self, index: usize) -> &[u8; 20] {
+        self.get(index).expect("piece index out of bounds")
+    }
+}
+
+impl<'a> IntoIterator for Pieces<'a> {
+    type Item = [u8; 20];
+    type IntoIter = PiecesIter<'a>;
+
+    fn into_iter(self) -> PiecesIter<'a> {
+        self.iter()
+    }
+}
+
+/// An iterator over the hashes in [`Pieces`].
+#[derive(Clone, Debug)]
+pub struct PiecesIter<'a>(slice::ChunksExact<'a, u8>);
+
+fn to_hash(chunk: &[u8]) -> [u8; 20] {
+    let mut hash = [0; 20];
+    hash.copy_from_slice(chunk);
+    hash
+}
+
+impl<'a> Iterator for PiecesIter<'a> {
+    type Item = [u8; 20];
+
+    fn next(&mut self) -> Option<[u8; 20]> {
+        self.0.next().map(to_hash)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+
+    fn nth(&mut self, n: usize) -> Option<[u8; 20]> {
+        self.0.nth(n).map(to_hash)
+    }
+}
+
+impl<'a> DoubleEndedIterator for PiecesIter<'a> {
+    fn next_back(&mut self) -> Option<[u8; 20]> {
+        self.0.next_back().map(to_hash)
+    }
+}
+
+impl<'a> ExactSizeIterator for PiecesIter<'a> {}
+
+impl<'a> FusedIterator for PiecesIter<'a> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pieces() -> Result<()> {
+        let mut bytes = [0; 60];
+        for (idx, b) in bytes.iter_mut().enumerate() {
+            *b = (idx / PIECE_HASH_LEN) as u8;
+        }
+        let pieces = Pieces::new(&bytes)?;
+        assert_eq!(pieces.len(), 3);
+        assert_eq!(pieces.get(2), Some(&[2; 20]));
+        assert_eq!(pieces.get(3), None);
+        assert_eq!(pieces.get(usize::MAX), None);
+        assert_eq!(pieces[0], [0; 20]);
+
+        let mut iter = pieces.iter();
+        assert_eq!(iter.len(), 3);
+        assert_eq!(iter.next(), Some([0; 20]));
+        assert_eq!(iter.next_back(), Some([2; 20]));
+        assert_eq!(iter.next(), Some([1; 20]));
+        assert_eq!(iter.next(), None);
+
+        assert!(Pieces::new(&[])?.is_empty());
+        assert!(Pieces::new(&bytes[..59]).is_err());
+        Ok(())
+    }
+}
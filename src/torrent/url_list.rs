@@ -0,0 +1,130 @@
+//! The `url-list` and `httpseeds` fields for web seeds.
+
+use core::{fmt, slice, str};
+use serde::{
+    de::{self, Deserialize, Deserializer, SeqAccess, Visitor},
+    ser::{Serialize, Serializer},
+};
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{string::String, vec::Vec};
+#[cfg(feature = "std")]
+use std::{string::String, vec::Vec};
+
+/// The web seed URLs from [BEP 19][bep_0019] or HTTP seed URLs from [BEP 17][bep_0017].
+///
+/// The field may be encoded as a single URL or as a list of URLs. The shape
+/// is kept so that the field is serialized the same way.
+///
+/// [bep_0017]: https://www.bittorrent.org/beps/bep_0017.html
+/// [bep_0019]: https://www.bittorrent.org/beps/bep_0019.html
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum UrlList {
+    /// A single URL.
+    One(String),
+    /// A list of URLs.
+    Many(Vec<String>),
+}
+
+impl UrlList {
+    /// Returns the URLs as a slice.
+    #[must_use]
+    pub fn as_slice(&self) -> &[String] {
+        match self {
+            UrlList::One(url) => slice::from_ref(url),
+            UrlList::Many(urls) => urls.as_slice(),
+        }
+    }
+
+    /// Returns an iterator over the non-empty URLs.
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.as_slice()
+            .iter()
+            .map(String::as_str)
+            .filter(|url| !url.is_empty())
+    }
+}
+
+impl Serialize for UrlList {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            UrlList::One(url) => url.serialize(serializer),
+            UrlList::Many(urls) => urls.serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for UrlList {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct UrlListVisitor;
+
+        impl<'de> Visitor<'de> for UrlListVisitor {
+            type Value = UrlList;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a URL or a list of URLs")
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<UrlList, E> {
+                Ok(UrlList::One(String::from(value)))
+            }
+
+            fn visit_bytes<E>(self, value: &[u8]) -> Result<UrlList, E>
+            where
+                E: de::Error,
+            {
+                str::from_utf8(value)
+                    .map(|url| UrlList::One(String::from(url)))
+                    .map_err(|_| de::Error::invalid_value(de::Unexpected::Bytes(value), &self))
+            }
+
+            fn visit_seq<V>(self, mut seq: V) -> Result<UrlList, V::Error>
+            where
+                V: SeqAccess<'de>,
+            {
+                let mut urls = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+                while let Some(url) = seq.next_element()? {
+                    urls.push(url);
+                }
+                Ok(UrlList::Many(urls))
+            }
+        }
+
+        deserializer.deserialize_any(UrlListVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Result;
+
+    #[cfg(all(feature = "alloc", not(feature = "std")))]
+    use alloc::vec;
+
+    #[test]
+    fn test_shapes() -> Result<()> {
+        let one: UrlList = crate::from_slice(b"8:http://a")?;
+        assert_eq!(one, UrlList::One(String::from("http://a")));
+        assert_eq!(one.iter().collect::<Vec<_>>(), vec!["http://a"]);
+        assert_eq!(crate::to_vec(&one)?, b"8:http://a");
+
+        let many: UrlList = crate::from_slice(b"l8:http://a0:8:http://be")?;
+        assert_eq!(many.as_slice().len(), 3);
+        assert_eq!(
+            many.iter().collect::<Vec<_>>(),
+            vec!["http://a", "http://b"]
+        );
+        assert_eq!(crate::to_vec(&many)?, b"l8:http://a0:8:http://be");
+
+        assert!(crate::from_slice::<UrlList>(b"i1e").is_err());
+        assert!(crate::from_slice::<UrlList>(b"1:\xff").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,174 @@
+//! Types for HTTP tracker responses.
+//!
+//! The scrape response from [BEP 48][bep_0048] is a dictionary of files keyed
+//! by their 20 byte info hashes. The keys are arbitrary bytes, so they are
+//! deserialized as [`Id20`] values instead of strings.
+//!
+//! ```rust
+//! use bt_bencode::{id::Id20, tracker::ScrapeResponse};
+//!
+//! let input = b"d5:filesd20:\xb4\x4a\x0e\x20\xfa\x5b\x7c\xec\xb7\x71\x56\x33\x3b\x42\x68\xdf\xd7\xc3\x0a\xfbd8:completei5e10:downloadedi50e10:incompletei10eeee";
+//! let scrape = ScrapeResponse::from_slice(input)?;
+//!
+//! let info_hash: Id20 = "b44a0e20fa5b7cecb77156333b4268dfd7c30afb".parse()?;
+//! assert_eq!(scrape.get(&info_hash).map(|f| f.complete), Some(5));
+//! assert_eq!(scrape.to_vec()?, &input[..]);
+//! # Ok::<(), bt_bencode::Error>(())
+//! ```
+//!
+//! [bep_0048]: https://www.bittorrent.org/beps/bep_0048.html
+
+use crate::{
+    error::{Error, Result},
+    id::Id20,
+};
+use serde_derive::{Deserialize, Serialize};
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+#[cfg(feature = "std")]
+use std::{collections::BTreeMap, string::String, vec::Vec};
+
+/// The statistics for a torrent in a scrape response.
+///
+/// The counts may also be encoded as byte strings containing integers.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScrapeFile {
+    /// The number of peers with the entire torrent.
+    #[serde(default, with = "crate::serde_helpers::lenient_int")]
+    pub complete: u64,
+    /// The number of times the tracker has registered a completion.
+    #[serde(default, with = "crate::serde_helpers::lenient_int")]
+    pub downloaded: u64,
+    /// The number of peers which do not have the entire torrent.
+    #[serde(default, with = "crate::serde_helpers::lenient_int")]
+    pub incomplete: u64,
+    /// The torrent's name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+/// Additional information in a scrape response.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScrapeFlags {
+    /// The minimum number of seconds between scrapes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_request_interval: Option<u64>,
+}
+
+/// A scrape response.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScrapeResponse {
+    /// The reason the scrape failed.
+    #[serde(
+        rename = "failure reason",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub failure_reason: Option<String>,
+    /// The statistics for each torrent keyed by info hash.
+    #[serde(default)]
+    pub files: BTreeMap<Id20, ScrapeFile>,
+    /// Additional information.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flags: Option<ScrapeFlags>,
+}
+
+impl ScrapeResponse {
+    /// Returns the statistics for a torrent.
+    #[must_use]
+    pub fn get(&self, info_hash: &Id20) -> Option<&ScrapeFile> {
+        self.files.get(info_hash)
+    }
+
+    /// Deserializes a scrape response from a slice of bytes.
+    ///
+    /// # Errors
+    ///
+    /// Deserialization can fail if the data is not a valid scrape response,
+    /// including when a `files` key is not 20 bytes long.
+    pub fn from_slice(s: &[u8]) -> Result<Self> {
+        crate::from_slice(s)
+    }
+
+    /// Serializes the scrape response with [`to_vec()`][crate::to_vec].
+    pub fn to_vec(&self) -> Result<Vec<u8>> {
+        crate::to_vec(self)
+    }
+
+    /// Returns the failure reason as an error if the scrape failed.
+    ///
+    /// # Errors
+    ///
+    /// An error with the failure reason is returned if it is present.
+    pub fn into_result(self) -> Result<Self> {
+        match self.failure_reason {
+            Some(reason) => Err(Error::Deserialize(reason)),
+            None => Ok(self),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scrape() -> Result<()> {
+        let input = b"d5:filesd20:\xff\xfe\xfd\xfc\xfb\xfa\xf9\xf8\xf7\xf6\xf5\xf4\xf3\xf2\xf1\xf0\xef\xee\xed\xecd8:completei1e10:downloadedi2e10:incompletei3e4:name4:spame20:abcdefghij0123456789d8:completei0eee5:flagsd20:min_request_intervali900eee";
+        let scrape = ScrapeResponse::from_slice(input)?;
+        assert_eq!(scrape.files.len(), 2);
+
+        let mut non_utf8 = [0; 20];
+        for (i, b) in non_utf8.iter_mut().enumerate() {
+            *b = 0xff - i as u8;
+        }
+        assert_eq!(
+            scrape.get(&Id20::new(non_utf8)),
+            Some(&ScrapeFile {
+                complete: 1,
+                downloaded: 2,
+                incomplete: 3,
+                name: Some(String::from("spam")),
+            })
+        );
+        assert_eq!(
+            scrape.get(&Id20::new(*b"abcdefghij0123456789")),
+            Some(&ScrapeFile::default())
+        );
+        assert_eq!(
+            scrape.flags.as_ref().and_then(|f| f.min_request_interval),
+            Some(900)
+        );
+        assert_eq!(
+            scrape.to_vec()?,
+            &b"d5:filesd20:abcdefghij0123456789d8:completei0e10:downloadedi0e10:incompletei0ee20:\xff\xfe\xfd\xfc\xfb\xfa\xf9\xf8\xf7\xf6\xf5\xf4\xf3\xf2\xf1\xf0\xef\xee\xed\xecd8:completei1e10:downloadedi2e10:incompletei3e4:name4:spamee5:flagsd20:min_request_intervali900eee"[..]
+        );
+        assert!(scrape.into_result().is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_scrape_string_counts() -> Result<()> {
+        let file: ScrapeFile =
+            crate::from_slice(b"d8:complete1:410:downloadedi5e10:incomplete2:12e")?;
+        assert_eq!(file.complete, 4);
+        assert_eq!(file.downloaded, 5);
+        assert_eq!(file.incomplete, 12);
+        assert_eq!(
+            crate::to_vec(&file)?,
+            &b"d8:completei4e10:downloadedi5e10:incompletei12ee"[..]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_failure() -> Result<()> {
+        let scrape = ScrapeResponse::from_slice(b"d14:failure reason6:denyede")?;
+        assert!(scrape.files.is_empty());
+        assert!(scrape.into_result().is_err());
+
+        assert!(ScrapeResponse::from_slice(b"d5:filesd3:abcdeee").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,347 @@
+//! Transcodes between Bencode and other formats without building a [`Value`][crate::Value].
+//!
+//! The [`Deserializer`] is self-describing, so it can drive any Serde
+//! serializer through [`Transcoder`] (or a generic transcoder such as
+//! `serde_transcode`). Byte strings are passed to the serializer as strings,
+//! with a [`NonUtf8Policy`] deciding how byte strings which are not valid
+//! UTF-8 are converted, which lets formats like JSON accept them.
+
+use crate::{
+    de::Deserializer,
+    error::{Error, Result},
+    value::NonUtf8Policy,
+};
+use core::{cell::RefCell, fmt};
+use serde::{
+    de::{self, DeserializeSeed, Visitor},
+    ser::{self, Serialize, SerializeMap, SerializeSeq, Serializer},
+};
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{string::String, vec::Vec};
+#[cfg(feature = "std")]
+use std::{string::String, vec::Vec};
+
+/// Serializes the data from a deserializer into a serializer.
+///
+/// The deserializer is driven with `deserialize_any`, and the transcoder can
+/// only be serialized once.
+pub struct Transcoder<D> {
+    de: RefCell<Option<D>>,
+    policy: NonUtf8Policy,
+}
+
+impl<D> fmt::Debug for Transcoder<D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Transcoder")
+            .field("policy", &self.policy)
+            .finish()
+    }
+}
+
+impl<'de, D> Transcoder<D>
+where
+    D: de::Deserializer<'de>,
+{
+    /// Creates a transcoder with the given policy for non-UTF-8 byte strings.
+    #[must_use]
+    pub fn new(de: D, policy: NonUtf8Policy) -> Self {
+        Self {
+            de: RefCell::new(Some(de)),
+            policy,
+        }
+    }
+}
+
+impl<'de, D> Serialize for Transcoder<D>
+where
+    D: de::Deserializer<'de>,
+{
+    fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let de = self
+            .de
+            .borrow_mut()
+            .take()
+            .ok_or_else(|| ser::Error::custom("transcoder can only be serialized once"))?;
+        let visitor = TranscodeVisitor {
+            ser: serializer,
+            policy: self.policy,
+        };
+        de.deserialize_any(visitor)
+            .unwrap_or_else(|e| Err(ser::Error::custom(e)))
+    }
+}
+
+struct TranscodeVisitor<S> {
+    ser: S,
+    policy: NonUtf8Policy,
+}
+
+impl<'de, S> Visitor<'de> for TranscodeVisitor<S>
+where
+    S: Serializer,
+{
+    type Value = core::result::Result<S::Ok, S::Error>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("any value")
+    }
+
+    fn visit_bool<E>(self, v: bool) -> core::result::Result<Self::Value, E> {
+        Ok(self.ser.serialize_bool(v))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> core::result::Result<Self::Value, E> {
+        Ok(self.ser.serialize_i64(v))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> core::result::Result<Self::Value, E> {
+        Ok(self.ser.serialize_u64(v))
+    }
+
+    fn visit_f64<E>(self, v: f64) -> core::result::Result<Self::Value, E> {
+        Ok(self.ser.serialize_f64(v))
+    }
+
+    fn visit_str<E>(self, v: &str) -> core::result::Result<Self::Value, E> {
+        Ok(self.ser.serialize_str(v))
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> core::result::Result<Self::Value, E> {
+        Ok(self.ser.serialize_str(&self.policy.to_string(v)))
+    }
+
+    fn visit_unit<E>(self) -> core::result::Result<Self::Value, E> {
+        Ok(self.ser.serialize_unit())
+    }
+
+    fn visit_none<E>(self) -> core::result::Result<Self::Value, E> {
+        Ok(self.ser.serialize_none())
+    }
+
+    fn visit_some<D>(self, d: D) -> core::result::Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        Ok(self.ser.serialize_some(&Transcoder::new(d, self.policy)))
+    }
+
+    /// Passes an integer outside of the 128 bit ranges to the serializer as a
+    /// newtype struct of its digits.
+    #[cfg(feature = "big-int")]
+    fn visit_newtype_struct<D>(self, d: D) -> core::result::Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let n = crate::value::deserialize_big_int(d)?;
+        Ok(n.serialize(self.ser))
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> core::result::Result<Self::Value, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        let mut s = match self.ser.serialize_seq(seq.size_hint()) {
+            Ok(s) => s,
+            Err(e) => return Ok(Err(e)),
+        };
+        while let Some(()) = seq.next_element_seed(SeqSeed {
+            ser: &mut s,
+            policy: self.policy,
+        })? {}
+        Ok(s.end())
+    }
+
+    fn visit_map<A>(self, mut map: A) -> core::result::Result<Self::Value, A::Error>
+    where
+        A: de::MapAccess<'de>,
+    {
+        let mut m = match self.ser.serialize_map(map.size_hint()) {
+            Ok(m) => m,
+            Err(e) => return Ok(Err(e)),
+        };
+        while let Some(()) = map.next_key_seed(KeySeed {
+            ser: &mut m,
+            policy: self.policy,
+        })? {
+            map.next_value_seed(ValueSeed {
+                ser: &mut m,
+                policy: self.policy,
+            })?;
+        }
+        Ok(m.end())
+    }
+}
+
+struct SeqSeed<'a, S> {
+    ser: &'a mut S,
+    policy: NonUtf8Policy,
+}
+
+impl<'de, 'a, S> DeserializeSeed<'de> for SeqSeed<'a, S>
+where
+    S: SerializeSeq,
+{
+    type Value = ();
+
+    fn deserialize<D>(self, d: D) -> core::result::Result<(), D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        self.ser
+            .serialize_element(&Transcoder::new(d, self.policy))
+            .map_err(de::Error::custom)
+    }
+}
+
+struct KeySeed<'a, S> {
+    ser: &'a mut S,
+    policy: NonUtf8Policy,
+}
+
+impl<'de, 'a, S> DeserializeSeed<'de> for KeySeed<'a, S>
+where
+    S: SerializeMap,
+{
+    type Value = ();
+
+    fn deserialize<D>(self, d: D) -> core::result::Result<(), D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        self.ser
+            .serialize_key(&Transcoder::new(d, self.policy))
+            .map_err(de::Error::custom)
+    }
+}
+
+struct ValueSeed<'a, S> {
+    ser: &'a mut S,
+    policy: NonUtf8Policy,
+}
+
+impl<'de, 'a, S> DeserializeSeed<'de> for ValueSeed<'a, S>
+where
+    S: SerializeMap,
+{
+    type Value = ();
+
+    fn deserialize<D>(self, d: D) -> core::result::Result<(), D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        self.ser
+            .serialize_value(&Transcoder::new(d, self.policy))
+            .map_err(de::Error::custom)
+    }
+}
+
+/// Transcodes a Bencode value into a JSON string.
+///
+/// Byte strings become JSON strings, with `policy` deciding how byte strings
+/// which are not valid UTF-8 are converted.
+///
+/// ```rust
+/// use bt_bencode::{transcode_to_json, value::NonUtf8Policy};
+///
+/// let json = transcode_to_json(b"d1:ai1e1:bl2:\xab\xcdee", NonUtf8Policy::Hex)?;
+/// assert_eq!(json, r#"{"a":1,"b":["abcd"]}"#);
+/// # Ok::<(), bt_bencode::Error>(())
+/// ```
+///
+/// # Errors
+///
+/// An error is returned if the input is not exactly one valid Bencode value.
+pub fn transcode_to_json(input: &[u8], policy: NonUtf8Policy) -> Result<String> {
+    let mut de = Deserializer::from_slice(input);
+    let json = serde_json::to_string(&Transcoder::new(&mut de, policy))
+        .map_err(<Error as de::Error>::custom)?;
+    de.end()?;
+    Ok(json)
+}
+
+/// Transcodes a JSON value into Bencode.
+///
+/// Dictionary keys are sorted as Bencode requires.
+///
+/// ```rust
+/// use bt_bencode::transcode_from_json;
+///
+/// let bencode = transcode_from_json(r#"{"b": ["x", -1], "a": 1}"#)?;
+/// assert_eq!(bencode, b"d1:ai1e1:bl1:xi-1eee");
+/// # Ok::<(), bt_bencode::Error>(())
+/// ```
+///
+/// # Errors
+///
+/// An error is returned if the input is not valid JSON or contains `null`,
+/// booleans, or non-integer numbers, which cannot be represented in Bencode.
+pub fn transcode_from_json(input: &str) -> Result<Vec<u8>> {
+    let mut de = serde_json::Deserializer::from_str(input);
+    let bytes = crate::to_vec(&Transcoder::new(&mut de, NonUtf8Policy::default()))?;
+    de.end().map_err(<Error as de::Error>::custom)?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_json() -> Result<()> {
+        assert_eq!(
+            transcode_to_json(b"d1:ai-1e1:bli1e1:\xffe2:\xff\xfe1:ce", NonUtf8Policy::Hex)?,
+            r#"{"a":-1,"b":[1,"ff"],"fffe":"c"}"#
+        );
+        assert_eq!(
+            transcode_to_json(b"l1:\xffe", NonUtf8Policy::Lossy)?,
+            "[\"\u{fffd}\"]"
+        );
+        assert!(transcode_to_json(b"i1ei2e", NonUtf8Policy::Lossy).is_err());
+        assert!(transcode_to_json(b"l", NonUtf8Policy::Lossy).is_err());
+        Ok(())
+    }
+
+    #[cfg(feature = "big-int")]
+    #[test]
+    fn test_to_json_big_int() -> Result<()> {
+        let input = b"li-340282366920938463463374607431768211457e2:abe";
+        assert_eq!(
+            transcode_to_json(input, NonUtf8Policy::Hex)?,
+            r#"["-340282366920938463463374607431768211457","ab"]"#
+        );
+        let value: crate::Value = crate::from_slice(input)?;
+        assert_eq!(
+            serde_json::to_string(&value).map_err(<Error as de::Error>::custom)?,
+            r#"["-340282366920938463463374607431768211457",[97,98]]"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_json() -> Result<()> {
+        assert_eq!(
+            transcode_from_json(r#"{"z": {"y": [], "x": "w"}, "a": [1, -2]}"#)?,
+            &b"d1:ali1ei-2ee1:zd1:x1:w1:yleee"[..]
+        );
+        assert!(transcode_from_json("null").is_err());
+        assert!(transcode_from_json("[true]").is_err());
+        assert!(transcode_from_json(r#"{"a": 1.5}"#).is_err());
+        assert!(transcode_from_json("[1] [2]").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_serde_transcode() -> Result<()> {
+        let input = &b"d1:ad1:bli1ei-1e0:ee1:c1:\xffe"[..];
+        let mut de = Deserializer::from_slice(input);
+        let mut out = Vec::new();
+        serde_transcode::transcode(&mut de, &mut crate::Serializer::new(&mut out))?;
+        de.end()?;
+        assert_eq!(out, input);
+        Ok(())
+    }
+}
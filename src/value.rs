@@ -1,11 +1,16 @@
 //! Represents valid Bencode data.
 
-use crate::error::Error;
+use crate::{error::Error, key::ByteStrKey};
+use core::{
+    cmp::Ordering,
+    convert::TryFrom,
+    hash::{Hash, Hasher},
+    mem,
+};
 use serde::{
     de::{Deserialize, DeserializeOwned, MapAccess, SeqAccess, Visitor},
     ser::Serialize,
 };
-use serde_bytes::ByteBuf;
 
 #[cfg(all(feature = "alloc", not(feature = "std")))]
 use alloc::{collections::BTreeMap, fmt, str, str::FromStr, string::String, vec::Vec};
@@ -13,12 +18,322 @@ use alloc::{collections::BTreeMap, fmt, str, str::FromStr, string::String, vec::
 use std::{collections::BTreeMap, fmt, str, str::FromStr, string::String, vec::Vec};
 
 /// Represents a valid Bencode number.
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+///
+/// Integers are stored as [`Number::Signed`] or [`Number::Unsigned`] when they
+/// fit in 64 bits. The 128 bit variants are only used for integers outside of
+/// the 64 bit ranges. With the `big-int` feature, `Number::Big` is used for
+/// integers outside of the 128 bit ranges, and `Number` is not [`Copy`].
+///
+/// Numbers are compared and hashed by their integer value, so
+/// `Number::Signed(3)` is equal to `Number::Unsigned(3)`.
+///
+/// ```rust
+/// use bt_bencode::value::Number;
+///
+/// assert_eq!(Number::Signed(3), Number::Unsigned(3));
+/// assert!(Number::Signed(-1) < Number::Unsigned(0));
+/// assert_eq!(Number::Signed(3).as_u64(), Some(3));
+/// assert_eq!(Number::Signed(-3).as_u64(), None);
+/// ```
+#[derive(Clone, Debug)]
+#[cfg_attr(not(feature = "big-int"), derive(Copy))]
 pub enum Number {
     /// A signed integer.
     Signed(i64),
     /// An unsigned integer.
     Unsigned(u64),
+    /// A signed integer less than [`i64::MIN`][i64::MIN].
+    Signed128(i128),
+    /// An unsigned integer greater than [`u64::MAX`][u64::MAX].
+    Unsigned128(u128),
+    /// An integer less than [`i128::MIN`] or greater than [`u128::MAX`].
+    #[cfg(feature = "big-int")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "big-int")))]
+    Big(BigDigits),
+}
+
+/// Which variant [`Number::normalize()`] uses for a non-negative integer which
+/// fits in both an [i64] and an [u64].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum NumberPolicy {
+    /// The integer is stored as [`Number::Signed`].
+    #[default]
+    Signed,
+    /// The integer is stored as [`Number::Unsigned`], which is the variant
+    /// used when a non-negative integer is deserialized.
+    Unsigned,
+}
+
+impl From<i128> for Number {
+    fn from(value: i128) -> Self {
+        if value >= i128::from(i64::MIN) && value <= i128::from(i64::MAX) {
+            Number::Signed(value as i64)
+        } else if value < 0 {
+            Number::Signed128(value)
+        } else {
+            Number::from(value as u128)
+        }
+    }
+}
+
+impl From<u128> for Number {
+    fn from(value: u128) -> Self {
+        if value <= u128::from(u64::MAX) {
+            Number::Unsigned(value as u64)
+        } else {
+            Number::Unsigned128(value)
+        }
+    }
+}
+
+impl Number {
+    /// Returns the number as an [i64] if it is in range.
+    #[must_use]
+    pub fn as_i64(&self) -> Option<i64> {
+        match self.canonical() {
+            Canonical::Negative(n) if n >= i128::from(i64::MIN) => Some(n as i64),
+            Canonical::NonNegative(n) if n <= i64::MAX as u128 => Some(n as i64),
+            _ => None,
+        }
+    }
+
+    /// Returns the number as an [u64] if it is in range.
+    #[must_use]
+    pub fn as_u64(&self) -> Option<u64> {
+        match self.canonical() {
+            Canonical::NonNegative(n) if n <= u128::from(u64::MAX) => Some(n as u64),
+            _ => None,
+        }
+    }
+
+    /// Returns the number as an [i128] if it is in range.
+    #[must_use]
+    pub fn as_i128(&self) -> Option<i128> {
+        match self.canonical() {
+            Canonical::Negative(n) => Some(n),
+            Canonical::NonNegative(n) if n <= i128::MAX as u128 => Some(n as i128),
+            _ => None,
+        }
+    }
+
+    /// Returns the number as an [u128] if it is in range.
+    #[must_use]
+    pub fn as_u128(&self) -> Option<u128> {
+        match self.canonical() {
+            Canonical::NonNegative(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    /// Stores the number in the smallest variant which can hold it.
+    ///
+    /// Integers in the 64 bit ranges become [`Number::Signed`] or
+    /// [`Number::Unsigned`], and integers in the 128 bit ranges become
+    /// [`Number::Signed128`] or [`Number::Unsigned128`]. The policy chooses
+    /// the variant for a non-negative integer which fits in both an [i64] and
+    /// an [u64].
+    ///
+    /// ```rust
+    /// use bt_bencode::value::{Number, NumberPolicy};
+    ///
+    /// let mut n = Number::Unsigned(3);
+    /// n.normalize(NumberPolicy::Signed);
+    /// assert!(matches!(n, Number::Signed(3)));
+    /// n.normalize(NumberPolicy::Unsigned);
+    /// assert!(matches!(n, Number::Unsigned(3)));
+    /// ```
+    pub fn normalize(&mut self, policy: NumberPolicy) {
+        let n = match self.canonical() {
+            Canonical::Negative(n) => Number::from(n),
+            Canonical::NonNegative(n) => match policy {
+                NumberPolicy::Signed if n <= i64::MAX as u128 => Number::Signed(n as i64),
+                _ => Number::from(n),
+            },
+            #[cfg(feature = "big-int")]
+            Canonical::Big(_) => return,
+        };
+        *self = n;
+    }
+
+    /// Returns true if the number is less than zero.
+    #[must_use]
+    pub fn is_negative(&self) -> bool {
+        match *self {
+            Number::Signed(n) => n < 0,
+            Number::Unsigned(_) | Number::Unsigned128(_) => false,
+            Number::Signed128(n) => n < 0,
+            #[cfg(feature = "big-int")]
+            Number::Big(ref n) => n.is_negative(),
+        }
+    }
+
+    /// Returns the number in a form which is the same for equal numbers.
+    fn canonical(&self) -> Canonical {
+        match *self {
+            Number::Signed(n) if n < 0 => Canonical::Negative(i128::from(n)),
+            Number::Signed(n) => Canonical::NonNegative(n as u128),
+            Number::Unsigned(n) => Canonical::NonNegative(u128::from(n)),
+            Number::Signed128(n) if n < 0 => Canonical::Negative(n),
+            Number::Signed128(n) => Canonical::NonNegative(n as u128),
+            Number::Unsigned128(n) => Canonical::NonNegative(n),
+            #[cfg(feature = "big-int")]
+            Number::Big(ref n) => {
+                if n.is_negative() {
+                    n.as_str()
+                        .parse::<i128>()
+                        .ok()
+                        .filter(|n| *n < 0)
+                        .map_or_else(|| Canonical::Big(n.clone()), Canonical::Negative)
+                } else {
+                    n.as_str()
+                        .parse()
+                        .ok()
+                        .map_or_else(|| Canonical::Big(n.clone()), Canonical::NonNegative)
+                }
+            }
+        }
+    }
+
+    /// Returns the number's decimal digits, formatted in the buffer if needed.
+    pub(crate) fn format<'a>(&'a self, buf: &'a mut itoa::Buffer) -> &'a str {
+        match *self {
+            Number::Signed(n) => buf.format(n),
+            Number::Unsigned(n) => buf.format(n),
+            Number::Signed128(n) => buf.format(n),
+            Number::Unsigned128(n) => buf.format(n),
+            #[cfg(feature = "big-int")]
+            Number::Big(ref n) => n.as_str(),
+        }
+    }
+}
+
+/// A number where negative numbers are always [`Canonical::Negative`], other
+/// numbers in the 128 bit ranges are always [`Canonical::NonNegative`], and
+/// only numbers outside of the 128 bit ranges are [`Canonical::Big`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum Canonical {
+    Negative(i128),
+    NonNegative(u128),
+    #[cfg(feature = "big-int")]
+    Big(BigDigits),
+}
+
+impl Canonical {
+    fn cmp(self, other: Self) -> Ordering {
+        match (self, other) {
+            (Canonical::Negative(a), Canonical::Negative(b)) => a.cmp(&b),
+            (Canonical::NonNegative(a), Canonical::NonNegative(b)) => a.cmp(&b),
+            (Canonical::Negative(_), Canonical::NonNegative(_)) => Ordering::Less,
+            (Canonical::NonNegative(_), Canonical::Negative(_)) => Ordering::Greater,
+            #[cfg(feature = "big-int")]
+            (Canonical::Big(a), Canonical::Big(b)) => match (a.is_negative(), b.is_negative()) {
+                (true, true) => cmp_digits(b.as_str(), a.as_str()),
+                (false, false) => cmp_digits(a.as_str(), b.as_str()),
+                (true, false) => Ordering::Less,
+                (false, true) => Ordering::Greater,
+            },
+            #[cfg(feature = "big-int")]
+            (Canonical::Big(a), _) => {
+                if a.is_negative() {
+                    Ordering::Less
+                } else {
+                    Ordering::Greater
+                }
+            }
+            #[cfg(feature = "big-int")]
+            (_, Canonical::Big(b)) => {
+                if b.is_negative() {
+                    Ordering::Greater
+                } else {
+                    Ordering::Less
+                }
+            }
+        }
+    }
+}
+
+/// Compares the digits of two integers with the same sign and without
+/// leading zeros.
+#[cfg(feature = "big-int")]
+fn cmp_digits(a: &str, b: &str) -> Ordering {
+    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+}
+
+impl PartialEq for Number {
+    fn eq(&self, other: &Self) -> bool {
+        self.canonical() == other.canonical()
+    }
+}
+
+impl Eq for Number {}
+
+impl Hash for Number {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.canonical().hash(state);
+    }
+}
+
+impl PartialOrd for Number {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Number {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.canonical().cmp(other.canonical())
+    }
+}
+
+impl FromStr for Number {
+    type Err = Error;
+
+    /// Parses the decimal digits of an integer.
+    ///
+    /// A negative number is parsed as a signed integer, and any other number is
+    /// parsed as an unsigned integer. With the `big-int` feature, a number
+    /// outside of the 128 bit ranges is parsed as a `Number::Big`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.starts_with('-') {
+            match s.parse() {
+                Ok(n) => Ok(Number::Signed(n)),
+                Err(_) => match s.parse::<i128>() {
+                    Ok(n) => Ok(Number::from(n)),
+                    #[cfg(feature = "big-int")]
+                    Err(err) => s.parse().map(Number::Big).map_err(|_| Error::from(err)),
+                    #[cfg(not(feature = "big-int"))]
+                    Err(err) => Err(Error::from(err)),
+                },
+            }
+        } else {
+            match s.parse() {
+                Ok(n) => Ok(Number::Unsigned(n)),
+                Err(_) => match s.parse::<u128>() {
+                    Ok(n) => Ok(Number::from(n)),
+                    #[cfg(feature = "big-int")]
+                    Err(err) => s.parse().map(Number::Big).map_err(|_| Error::from(err)),
+                    #[cfg(not(feature = "big-int"))]
+                    Err(err) => Err(Error::from(err)),
+                },
+            }
+        }
+    }
+}
+
+impl Serialize for Number {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match *self {
+            Number::Signed(n) => serializer.serialize_i64(n),
+            Number::Unsigned(n) => serializer.serialize_u64(n),
+            Number::Signed128(n) => serializer.serialize_i128(n),
+            Number::Unsigned128(n) => serializer.serialize_u128(n),
+            #[cfg(feature = "big-int")]
+            Number::Big(ref n) => serializer.serialize_newtype_struct(BIG_INT_TOKEN, n.as_str()),
+        }
+    }
 }
 
 impl From<isize> for Number {
@@ -81,29 +396,78 @@ impl From<u8> for Number {
     }
 }
 
+macro_rules! impl_try_from_number {
+    ($as_int:ident: $($t:ty),*) => {
+        $(
+            impl TryFrom<Number> for $t {
+                type Error = Error;
+
+                fn try_from(value: Number) -> Result<Self, Self::Error> {
+                    <$t>::try_from(&value)
+                }
+            }
+
+            impl<'a> TryFrom<&'a Number> for $t {
+                type Error = Error;
+
+                /// Converts the number if it is in the range of the type.
+                fn try_from(value: &'a Number) -> Result<Self, Self::Error> {
+                    value
+                        .$as_int()
+                        .and_then(|n| <$t>::try_from(n).ok())
+                        .ok_or_else(|| {
+                            Error::Deserialize(String::from(concat!(
+                                "integer out of range for ",
+                                stringify!($t)
+                            )))
+                        })
+                }
+            }
+        )*
+    };
+}
+
+impl_try_from_number!(as_i128: i8, i16, i32, i64, i128, isize);
+impl_try_from_number!(as_u128: u8, u16, u32, u64, u128, usize);
+
 /// Represents a valid Bencode value.
 ///
 /// It is useful when it is unknown what the data may contain (e.g. when different kinds of
 /// messages can be received in a network packet).
-#[derive(Clone, Debug, PartialEq)]
+///
+/// Values are ordered by their canonical encodings.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Value {
     /// A byte string.
     ///
     /// Encoded strings can contain non-UTF-8 bytes, so a byte string is used to represent
     /// "strings".
-    ByteStr(ByteBuf),
+    ByteStr(SmallBytes),
     /// An integer which can be signed or unsigned.
     Int(Number),
     /// A list of values.
     List(Vec<Value>),
     /// A dictionary of values.
-    Dict(BTreeMap<ByteBuf, Value>),
+    ///
+    /// The keys implement [`Borrow<[u8]>`][core::borrow::Borrow], so entries
+    /// can be looked up with byte slices without allocating.
+    ///
+    /// ```rust
+    /// use bt_bencode::Value;
+    ///
+    /// let value: Value = bt_bencode::from_slice(b"d1:q4:pinge")?;
+    /// let dict = value.as_dict().unwrap();
+    /// assert_eq!(dict.get(&b"q"[..]), Some(&Value::from("ping")));
+    /// assert_eq!(dict.get("q".as_bytes()), Some(&Value::from("ping")));
+    /// # Ok::<(), bt_bencode::Error>(())
+    /// ```
+    Dict(BTreeMap<ByteStrKey, Value>),
 }
 
 impl Value {
     /// If the value is a byte string, returns a reference to the underlying value.
     #[must_use]
-    pub fn as_byte_str(&self) -> Option<&ByteBuf> {
+    pub fn as_byte_str(&self) -> Option<&SmallBytes> {
         match self {
             Value::ByteStr(b) => Some(b),
             _ => None,
@@ -112,7 +476,7 @@ impl Value {
 
     /// If the value is a byte string, returns a mutable reference to the underlying value.
     #[must_use]
-    pub fn as_byte_str_mut(&mut self) -> Option<&mut ByteBuf> {
+    pub fn as_byte_str_mut(&mut self) -> Option<&mut SmallBytes> {
         match self {
             Value::ByteStr(ref mut b) => Some(b),
             _ => None,
@@ -184,7 +548,7 @@ impl Value {
 
     /// If the value is a dictionary, returns a reference to the underlying value.
     #[must_use]
-    pub fn as_dict(&self) -> Option<&BTreeMap<ByteBuf, Value>> {
+    pub fn as_dict(&self) -> Option<&BTreeMap<ByteStrKey, Value>> {
         match self {
             Value::Dict(d) => Some(d),
             _ => None,
@@ -193,7 +557,7 @@ impl Value {
 
     /// If the value is a dictionary, returns a mutable reference to the underlying value.
     #[must_use]
-    pub fn as_dict_mut(&mut self) -> Option<&mut BTreeMap<ByteBuf, Value>> {
+    pub fn as_dict_mut(&mut self) -> Option<&mut BTreeMap<ByteStrKey, Value>> {
         match self {
             Value::Dict(ref mut d) => Some(d),
             _ => None,
@@ -241,6 +605,107 @@ impl Value {
     pub fn is_dict(&self) -> bool {
         self.as_dict().is_some()
     }
+
+    /// If the value is a byte string, returns the underlying value.
+    ///
+    /// # Errors
+    ///
+    /// If the value is not a byte string, the value is returned.
+    pub fn try_into_bytes(self) -> Result<SmallBytes, Value> {
+        match self {
+            Value::ByteStr(b) => Ok(b),
+            _ => Err(self),
+        }
+    }
+
+    /// If the value is a number, returns the underlying value.
+    ///
+    /// # Errors
+    ///
+    /// If the value is not a number, the value is returned.
+    pub fn try_into_int(self) -> Result<Number, Value> {
+        match self {
+            Value::Int(n) => Ok(n),
+            _ => Err(self),
+        }
+    }
+
+    /// If the value is an array, returns the underlying value.
+    ///
+    /// # Errors
+    ///
+    /// If the value is not an array, the value is returned.
+    pub fn try_into_list(self) -> Result<Vec<Value>, Value> {
+        match self {
+            Value::List(l) => Ok(l),
+            _ => Err(self),
+        }
+    }
+
+    /// If the value is a dictionary, returns the underlying value.
+    ///
+    /// # Errors
+    ///
+    /// If the value is not a dictionary, the value is returned.
+    ///
+    /// ```rust
+    /// use bt_bencode::Value;
+    ///
+    /// let value: Value = bt_bencode::from_slice(b"li1ee")?;
+    /// let value = match value.try_into_dict() {
+    ///     Ok(_) => panic!(),
+    ///     Err(value) => value,
+    /// };
+    /// assert_eq!(value.try_into_list().map(|l| l.len()), Ok(1));
+    /// # Ok::<(), bt_bencode::Error>(())
+    /// ```
+    pub fn try_into_dict(self) -> Result<BTreeMap<ByteStrKey, Value>, Value> {
+        match self {
+            Value::Dict(d) => Ok(d),
+            _ => Err(self),
+        }
+    }
+
+    /// Takes the value and leaves an empty value of the same kind.
+    ///
+    /// A byte string is replaced with an empty byte string, an integer with
+    /// zero, a list with an empty list, and a dictionary with an empty
+    /// dictionary.
+    ///
+    /// ```rust
+    /// use bt_bencode::Value;
+    ///
+    /// let mut value: Value = bt_bencode::from_slice(b"d1:rd2:id2:abee")?;
+    /// let r = value["r"].take();
+    /// assert_eq!(bt_bencode::to_vec(&r)?, b"d2:id2:abe");
+    /// assert_eq!(bt_bencode::to_vec(&value)?, b"d1:rdee");
+    /// # Ok::<(), bt_bencode::Error>(())
+    /// ```
+    #[must_use]
+    pub fn take(&mut self) -> Value {
+        let empty = match self {
+            Value::ByteStr(_) => Value::ByteStr(SmallBytes::new()),
+            Value::Int(_) => Value::Int(Number::Unsigned(0)),
+            Value::List(_) => Value::List(Vec::new()),
+            Value::Dict(_) => Value::Dict(BTreeMap::new()),
+        };
+        mem::replace(self, empty)
+    }
+
+    /// Replaces the value and returns the previous value.
+    pub fn replace<V>(&mut self, value: V) -> Value
+    where
+        V: Into<Value>,
+    {
+        mem::replace(self, value.into())
+    }
+}
+
+impl Default for Value {
+    /// Returns an empty dictionary.
+    fn default() -> Self {
+        Value::Dict(BTreeMap::new())
+    }
 }
 
 impl From<i8> for Value {
@@ -307,29 +772,29 @@ impl FromStr for Value {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(Value::ByteStr(ByteBuf::from(String::from(s))))
+        Ok(Value::ByteStr(SmallBytes::from(s)))
     }
 }
 
 impl<'a> From<&'a str> for Value {
     fn from(other: &'a str) -> Value {
-        Value::ByteStr(ByteBuf::from(other))
+        Value::ByteStr(SmallBytes::from(other))
     }
 }
 
 impl From<String> for Value {
     fn from(other: String) -> Value {
-        Value::ByteStr(ByteBuf::from(other))
+        Value::ByteStr(SmallBytes::from(other))
     }
 }
 
 impl<V: Into<Value>> From<Vec<V>> for Value {
     fn from(other: Vec<V>) -> Value {
-        Value::List(other.into_iter().map(core::convert::Into::into).collect())
+        Value::List(other.into_iter().map(Into::into).collect())
     }
 }
 
-impl<K: Into<ByteBuf>, V: Into<Value>> From<BTreeMap<K, V>> for Value {
+impl<K: Into<ByteStrKey>, V: Into<Value>> From<BTreeMap<K, V>> for Value {
     fn from(other: BTreeMap<K, V>) -> Value {
         Value::Dict(
             other
@@ -366,24 +831,43 @@ impl<'de> Deserialize<'de> for Value {
                 Ok(Value::Int(Number::Unsigned(value)))
             }
 
+            #[inline]
+            fn visit_i128<E>(self, value: i128) -> Result<Self::Value, E> {
+                Ok(Value::Int(Number::from(value)))
+            }
+
+            #[cfg(feature = "big-int")]
+            #[inline]
+            fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                deserialize_big_int(deserializer).map(Value::Int)
+            }
+
+            #[inline]
+            fn visit_u128<E>(self, value: u128) -> Result<Self::Value, E> {
+                Ok(Value::Int(Number::from(value)))
+            }
+
             #[inline]
             fn visit_str<E>(self, value: &str) -> Result<Self::Value, E> {
-                Ok(Value::ByteStr(ByteBuf::from(String::from(value))))
+                Ok(Value::ByteStr(SmallBytes::from(value)))
             }
 
             #[inline]
             fn visit_string<E>(self, value: String) -> Result<Self::Value, E> {
-                Ok(Value::ByteStr(ByteBuf::from(value)))
+                Ok(Value::ByteStr(SmallBytes::from(value)))
             }
 
             #[inline]
             fn visit_bytes<E>(self, value: &[u8]) -> Result<Self::Value, E> {
-                Ok(Value::ByteStr(ByteBuf::from(value)))
+                Ok(Value::ByteStr(SmallBytes::from(value)))
             }
 
             #[inline]
             fn visit_byte_buf<E>(self, value: Vec<u8>) -> Result<Self::Value, E> {
-                Ok(Value::ByteStr(ByteBuf::from(value)))
+                Ok(Value::ByteStr(SmallBytes::from(value)))
             }
 
             #[inline]
@@ -431,21 +915,69 @@ impl Serialize for Value {
     {
         match self {
             Value::ByteStr(ref b) => b.serialize(serializer),
-            Value::Int(i) => match i {
-                Number::Signed(s) => s.serialize(serializer),
-                Number::Unsigned(u) => u.serialize(serializer),
-            },
+            Value::Int(i) => i.serialize(serializer),
             Value::List(l) => l.serialize(serializer),
             Value::Dict(d) => d.serialize(serializer),
         }
     }
 }
 
+#[cfg(feature = "arbitrary")]
+mod arbitrary;
+#[cfg(feature = "big-int")]
+mod big;
+mod bytes;
+mod cmp;
 mod de;
+mod diff;
+mod entry;
+mod extra;
 mod index;
+mod iter;
+#[cfg(feature = "json")]
+mod json;
+mod lazy;
+mod merge;
+#[cfg(feature = "indexmap")]
+mod ordered;
+mod partial_eq;
+pub(crate) mod pretty;
+#[cfg(feature = "quickcheck")]
+mod quickcheck;
+pub(crate) mod raw;
 mod ser;
-
+mod size;
+mod spanned;
+#[cfg(feature = "proptest")]
+#[cfg_attr(docsrs, doc(cfg(feature = "proptest")))]
+pub mod strategy;
+mod verbatim;
+mod walk;
+
+#[cfg(feature = "big-int")]
+#[cfg_attr(docsrs, doc(cfg(feature = "big-int")))]
+pub use big::BigDigits;
+#[cfg(feature = "big-int")]
+pub(crate) use big::{deserialize_big_int, TOKEN as BIG_INT_TOKEN};
+pub use bytes::SmallBytes;
+pub use diff::{diff, DiffEntry};
+pub use entry::Entry;
+pub use extra::Extra;
 pub use index::Index;
+pub use iter::{DictIter, IntoIter, Iter};
+#[cfg(feature = "json")]
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+pub use json::NonUtf8Policy;
+pub use lazy::LazyDict;
+pub use merge::MergeStrategy;
+#[cfg(feature = "indexmap")]
+#[cfg_attr(docsrs, doc(cfg(feature = "indexmap")))]
+pub use ordered::OrderedValue;
+pub use pretty::to_string_pretty;
+pub use raw::{to_raw_value, RawValue, RawValueBuf};
+pub use spanned::{SpannedEntry, SpannedKind, SpannedValue};
+pub use verbatim::{VerbatimEntry, VerbatimKind, VerbatimValue};
+pub use walk::Walk;
 
 impl Value {
     /// Used to get a reference to a value with an index.
@@ -507,7 +1039,7 @@ mod tests {
     fn test_deserialize_string() -> Result<()> {
         let input = "4:spam";
         let v: Value = crate::de::from_slice(input.as_bytes())?;
-        assert_eq!(v, Value::ByteStr(ByteBuf::from(String::from("spam"))));
+        assert_eq!(v, Value::ByteStr(SmallBytes::from(String::from("spam"))));
         Ok(())
     }
 
@@ -535,6 +1067,282 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_deserialize_integer_128() -> Result<()> {
+        let input = b"li-9223372036854775809ei18446744073709551616ei-9223372036854775808ee";
+        let v: Value = crate::de::from_slice(input)?;
+        assert_eq!(
+            v,
+            Value::List(vec![
+                Value::Int(Number::Signed128(-9_223_372_036_854_775_809)),
+                Value::Int(Number::Unsigned128(18_446_744_073_709_551_616)),
+                Value::Int(Number::Signed(i64::MIN)),
+            ])
+        );
+        assert_eq!(crate::to_vec(&v)?, &input[..]);
+        assert_eq!(to_value(&v)?, v);
+        #[cfg(not(feature = "big-int"))]
+        assert!(
+            crate::de::from_slice::<Value>(b"i340282366920938463463374607431768211456e").is_err()
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "big-int")]
+    #[test]
+    fn test_deserialize_integer_big() -> Result<()> {
+        let input = b"li340282366920938463463374607431768211456ei-170141183460469231731687303715884105729ee";
+        let v: Value = crate::de::from_slice(input)?;
+        assert_eq!(
+            v,
+            Value::List(vec![
+                Value::Int(Number::Big(
+                    "340282366920938463463374607431768211456".parse()?
+                )),
+                Value::Int(Number::Big(
+                    "-170141183460469231731687303715884105729".parse()?
+                )),
+            ])
+        );
+        assert_eq!(crate::to_vec(&v)?, &input[..]);
+        assert_eq!(to_value(&v)?, v);
+        assert_eq!(from_value::<Value>(v.clone())?, v);
+        assert!(
+            crate::de::from_slice::<u64>(b"i340282366920938463463374607431768211456e").is_err()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_into() -> Result<()> {
+        let value: Value = crate::de::from_slice(b"d1:a2:xy1:bi3e1:cli1ee1:ddee")?;
+        let mut d = value.try_into_dict().unwrap();
+        let a = d.remove(&b"a"[..]).unwrap();
+        let a = a.try_into_int().unwrap_err();
+        assert_eq!(a.try_into_bytes().ok(), Some(SmallBytes::from(&b"xy"[..])));
+        let b = d.remove(&b"b"[..]).unwrap();
+        let b = b.try_into_list().unwrap_err();
+        assert_eq!(b.try_into_int().ok(), Some(Number::Unsigned(3)));
+        let c = d.remove(&b"c"[..]).unwrap();
+        let c = c.try_into_dict().unwrap_err();
+        assert_eq!(c.try_into_list().ok(), Some(vec![Value::from(1_u64)]));
+        let e = d.remove(&b"d"[..]).unwrap();
+        let e = e.try_into_bytes().unwrap_err();
+        assert!(e.try_into_dict().unwrap().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_take_replace() -> Result<()> {
+        let mut value: Value = crate::de::from_slice(b"d1:a2:xy1:bi3e1:cli1ee1:dd1:ei1eee")?;
+        assert_eq!(value["a"].take(), "xy");
+        assert_eq!(value["b"].take(), 3_u64);
+        assert_eq!(value["c"].take(), Value::List(vec![Value::from(1_u64)]));
+        let d = value["d"].take();
+        assert_eq!(crate::to_vec(&d)?, b"d1:ei1ee");
+        assert_eq!(crate::to_vec(&value)?, b"d1:a0:1:bi0e1:cle1:ddee");
+
+        assert_eq!(value["a"].replace("z"), "");
+        assert_eq!(value["a"], "z");
+        assert_eq!(Value::default(), Value::Dict(BTreeMap::new()));
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_number_cmp() -> Result<()> {
+        use core::hash::BuildHasher;
+        use std::collections::hash_map::RandomState;
+
+        let state = RandomState::new();
+        let hash = |n: &Number| {
+            let mut hasher = state.build_hasher();
+            n.hash(&mut hasher);
+            hasher.finish()
+        };
+
+        let equal = [
+            (Number::Signed(3), Number::Unsigned(3)),
+            (Number::Signed128(3), Number::Unsigned128(3)),
+            (Number::Signed(-3), Number::Signed128(-3)),
+        ];
+        for (a, b) in &equal {
+            assert_eq!(a, b);
+            assert_eq!(hash(a), hash(b));
+            assert_eq!(a.cmp(b), Ordering::Equal);
+        }
+
+        let ascending = [
+            Number::Signed128(i128::from(i64::MIN) - 1),
+            Number::Signed(-1),
+            Number::Unsigned(0),
+            Number::Signed(1),
+            Number::Unsigned128(u128::from(u64::MAX) + 1),
+        ];
+        for w in ascending.windows(2) {
+            assert!(w[0] < w[1], "{:?} < {:?}", w[0], w[1]);
+            assert!(w[1] > w[0]);
+            assert_ne!(w[0], w[1]);
+        }
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(all(feature = "std", feature = "big-int"))]
+    fn test_number_cmp_big() -> Result<()> {
+        use core::hash::BuildHasher;
+        use std::collections::hash_map::RandomState;
+
+        let state = RandomState::new();
+        let hash = |n: &Number| {
+            let mut hasher = state.build_hasher();
+            n.hash(&mut hasher);
+            hasher.finish()
+        };
+
+        let equal = [
+            (Number::Big("-3".parse()?), Number::Signed(-3)),
+            (Number::Big("3".parse()?), Number::Unsigned(3)),
+        ];
+        for (a, b) in &equal {
+            assert_eq!(a, b);
+            assert_eq!(hash(a), hash(b));
+            assert_eq!(a.cmp(b), Ordering::Equal);
+        }
+
+        let big: Number = "340282366920938463463374607431768211456".parse()?;
+        let big_neg: Number = "-170141183460469231731687303715884105729".parse()?;
+
+        let ascending = [
+            Number::Big("-3402823669209384634633746074317682114560".parse()?),
+            big_neg,
+            Number::Signed128(i128::from(i64::MIN) - 1),
+            Number::Unsigned128(u128::from(u64::MAX) + 1),
+            big,
+            Number::Big("3402823669209384634633746074317682114560".parse()?),
+        ];
+        for w in ascending.windows(2) {
+            assert!(w[0] < w[1], "{:?} < {:?}", w[0], w[1]);
+            assert!(w[1] > w[0]);
+            assert_ne!(w[0], w[1]);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_number_as_int() {
+        assert_eq!(Number::Unsigned(3).as_i64(), Some(3));
+        assert_eq!(Number::Signed(3).as_u64(), Some(3));
+        assert_eq!(Number::Signed(-3).as_u64(), None);
+        assert_eq!(Number::Unsigned(u64::MAX).as_i64(), None);
+        assert_eq!(Number::Unsigned(u64::MAX).as_u64(), Some(u64::MAX));
+        assert_eq!(Number::Signed128(-3).as_i64(), Some(-3));
+        assert_eq!(Number::Unsigned128(3).as_u64(), Some(3));
+        assert_eq!(Number::Signed(-3).as_i128(), Some(-3));
+        assert_eq!(Number::Unsigned(3).as_u128(), Some(3));
+        assert_eq!(Number::Unsigned128(u128::MAX).as_i128(), None);
+        assert!(Number::Signed(-3).is_negative());
+        assert!(!Number::Signed(0).is_negative());
+    }
+
+    #[test]
+    fn test_try_from_number() -> Result<()> {
+        assert_eq!(u8::try_from(Number::Signed(255))?, 255);
+        assert!(u8::try_from(Number::Unsigned(256)).is_err());
+        assert!(u32::try_from(Number::Signed(-1)).is_err());
+        assert_eq!(i8::try_from(&Number::Signed(-128))?, -128);
+        assert!(i8::try_from(&Number::Signed(-129)).is_err());
+        assert_eq!(i64::try_from(Number::Unsigned(3))?, 3);
+        assert!(i64::try_from(Number::Unsigned(u64::MAX)).is_err());
+        assert_eq!(usize::try_from(Number::Unsigned(7))?, 7);
+        assert_eq!(u128::try_from(Number::Unsigned128(u128::MAX))?, u128::MAX);
+        assert_eq!(i128::try_from(Number::Signed128(i128::MIN))?, i128::MIN);
+        #[cfg(feature = "big-int")]
+        {
+            let big: Number = "340282366920938463463374607431768211456".parse()?;
+            assert!(u128::try_from(&big).is_err());
+        }
+        match u16::try_from(Number::Unsigned(65_536)) {
+            Err(Error::Deserialize(msg)) => assert_eq!(msg, "integer out of range for u16"),
+            _ => panic!(),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_number_normalize() -> Result<()> {
+        use core::mem;
+
+        let cases = [
+            (Number::Unsigned(3), Number::Signed(3), Number::Unsigned(3)),
+            (Number::Signed(-3), Number::Signed(-3), Number::Signed(-3)),
+            (
+                Number::Unsigned(u64::MAX),
+                Number::Unsigned(u64::MAX),
+                Number::Unsigned(u64::MAX),
+            ),
+            (
+                Number::Signed128(-3),
+                Number::Signed(-3),
+                Number::Signed(-3),
+            ),
+            (
+                Number::Unsigned128(3),
+                Number::Signed(3),
+                Number::Unsigned(3),
+            ),
+        ];
+        for (n, signed, unsigned) in &cases {
+            let mut m = Number::clone(n);
+            m.normalize(NumberPolicy::Signed);
+            assert_eq!(m, *signed);
+            assert_eq!(mem::discriminant(&m), mem::discriminant(signed));
+            let mut m = Number::clone(n);
+            m.normalize(NumberPolicy::Unsigned);
+            assert_eq!(m, *unsigned);
+            assert_eq!(mem::discriminant(&m), mem::discriminant(unsigned));
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "big-int")]
+    #[test]
+    fn test_number_normalize_big() -> Result<()> {
+        let mut m = Number::Big("3".parse()?);
+        m.normalize(NumberPolicy::Signed);
+        assert!(matches!(m, Number::Signed(3)));
+        let mut m = Number::Big("-170141183460469231731687303715884105728".parse()?);
+        m.normalize(NumberPolicy::Unsigned);
+        assert!(matches!(m, Number::Signed128(i128::MIN)));
+
+        let big: Number = "340282366920938463463374607431768211456".parse()?;
+        let mut m = big.clone();
+        m.normalize(NumberPolicy::default());
+        assert_eq!(m, big);
+        Ok(())
+    }
+
+    #[test]
+    fn test_number_from_128() {
+        assert_eq!(Number::from(1_i128), Number::Signed(1));
+        assert_eq!(Number::from(-1_i128), Number::Signed(-1));
+        assert_eq!(
+            Number::from(i128::from(u64::MAX)),
+            Number::Unsigned(u64::MAX)
+        );
+        assert_eq!(
+            Number::from(i128::from(u64::MAX) + 1),
+            Number::Unsigned128(u128::from(u64::MAX) + 1)
+        );
+        assert_eq!(
+            Number::from(i128::from(i64::MIN) - 1),
+            Number::Signed128(i128::from(i64::MIN) - 1)
+        );
+        assert_eq!(Number::from(1_u128), Number::Unsigned(1));
+        assert_eq!("-0".parse::<Number>().ok(), Some(Number::Signed(0)));
+        assert_eq!("1".parse::<Number>().ok(), Some(Number::Unsigned(1)));
+    }
+
     #[test]
     fn test_deserialize_list() -> Result<()> {
         let input = "l4:spam4:eggse";
@@ -542,8 +1350,8 @@ mod tests {
         assert_eq!(
             v,
             Value::List(vec![
-                Value::ByteStr(ByteBuf::from(String::from("spam"))),
-                Value::ByteStr(ByteBuf::from(String::from("eggs"))),
+                Value::ByteStr(SmallBytes::from(String::from("spam"))),
+                Value::ByteStr(SmallBytes::from(String::from("eggs"))),
             ])
         );
         Ok(())
@@ -556,12 +1364,12 @@ mod tests {
 
         let mut expected = BTreeMap::new();
         expected.insert(
-            ByteBuf::from(String::from("cow")),
-            Value::ByteStr(ByteBuf::from(String::from("moo"))),
+            ByteStrKey::from(String::from("cow")),
+            Value::ByteStr(SmallBytes::from(String::from("moo"))),
         );
         expected.insert(
-            ByteBuf::from(String::from("spam")),
-            Value::ByteStr(ByteBuf::from(String::from("eggs"))),
+            ByteStrKey::from(String::from("spam")),
+            Value::ByteStr(SmallBytes::from(String::from("eggs"))),
         );
         assert_eq!(v, Value::Dict(expected));
         Ok(())
@@ -573,10 +1381,10 @@ mod tests {
         let v: Value = crate::de::from_slice(input.as_bytes())?;
         let mut expected = BTreeMap::new();
         expected.insert(
-            ByteBuf::from(String::from("spam")),
+            ByteStrKey::from(String::from("spam")),
             Value::List(vec![
-                Value::ByteStr(ByteBuf::from(String::from("a"))),
-                Value::ByteStr(ByteBuf::from(String::from("b"))),
+                Value::ByteStr(SmallBytes::from(String::from("a"))),
+                Value::ByteStr(SmallBytes::from(String::from("b"))),
             ]),
         );
         assert_eq!(v, Value::Dict(expected));
@@ -587,7 +1395,8 @@ mod tests {
     #[cfg(feature = "std")]
     fn test_serialize_string() -> Result<()> {
         let expected = "4:spam";
-        let v: Vec<u8> = crate::ser::to_vec(&Value::ByteStr(ByteBuf::from(String::from("spam"))))?;
+        let v: Vec<u8> =
+            crate::ser::to_vec(&Value::ByteStr(SmallBytes::from(String::from("spam"))))?;
         assert_eq!(v, expected.to_string().into_bytes());
         Ok(())
     }
@@ -624,8 +1433,8 @@ mod tests {
     fn test_serialize_list() -> Result<()> {
         let expected = "l4:spam4:eggse";
         let v: Vec<u8> = crate::ser::to_vec(&Value::List(vec![
-            Value::ByteStr(ByteBuf::from(String::from("spam"))),
-            Value::ByteStr(ByteBuf::from(String::from("eggs"))),
+            Value::ByteStr(SmallBytes::from(String::from("spam"))),
+            Value::ByteStr(SmallBytes::from(String::from("eggs"))),
         ]))?;
         assert_eq!(v, expected.to_string().into_bytes());
         Ok(())
@@ -637,12 +1446,12 @@ mod tests {
         let expected = "d3:cow3:moo4:spam4:eggse";
         let mut dict = BTreeMap::new();
         dict.insert(
-            ByteBuf::from(String::from("cow")),
-            Value::ByteStr(ByteBuf::from(String::from("moo"))),
+            ByteStrKey::from(String::from("cow")),
+            Value::ByteStr(SmallBytes::from(String::from("moo"))),
         );
         dict.insert(
-            ByteBuf::from(String::from("spam")),
-            Value::ByteStr(ByteBuf::from(String::from("eggs"))),
+            ByteStrKey::from(String::from("spam")),
+            Value::ByteStr(SmallBytes::from(String::from("eggs"))),
         );
         let v: Vec<u8> = crate::ser::to_vec(&Value::Dict(dict))?;
         assert_eq!(v, expected.to_string().into_bytes());
@@ -655,10 +1464,10 @@ mod tests {
         let expected = "d4:spaml1:a1:bee";
         let mut dict = BTreeMap::new();
         dict.insert(
-            ByteBuf::from(String::from("spam")),
+            ByteStrKey::from(String::from("spam")),
             Value::List(vec![
-                Value::ByteStr(ByteBuf::from(String::from("a"))),
-                Value::ByteStr(ByteBuf::from(String::from("b"))),
+                Value::ByteStr(SmallBytes::from(String::from("a"))),
+                Value::ByteStr(SmallBytes::from(String::from("b"))),
             ]),
         );
         let v: Vec<u8> = crate::ser::to_vec(&Value::Dict(dict))?;
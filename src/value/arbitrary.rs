@@ -0,0 +1,95 @@
+//! [`arbitrary::Arbitrary`] implementations for fuzzing.
+
+use super::{Number, SmallBytes, Value};
+use crate::key::ByteStrKey;
+use ::arbitrary::{Arbitrary, Result, Unstructured};
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{collections::BTreeMap, vec::Vec};
+#[cfg(feature = "std")]
+use std::{collections::BTreeMap, vec::Vec};
+
+/// The maximum nesting of lists and dictionaries in a generated value.
+const MAX_DEPTH: usize = 8;
+
+impl<'a> Arbitrary<'a> for Number {
+    /// Generates a number in the form it would be deserialized in.
+    ///
+    /// Non-negative numbers are always [`Number::Unsigned`] and
+    /// [`Number::Signed`] is only used for negative numbers, so generated
+    /// values compare equal after an encode and decode round trip.
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        if bool::arbitrary(u)? {
+            Ok(Number::Unsigned(u64::arbitrary(u)?))
+        } else {
+            Ok(Number::Signed(u.int_in_range(i64::MIN..=-1)?))
+        }
+    }
+
+    fn size_hint(_depth: usize) -> (usize, Option<usize>) {
+        (1, Some(9))
+    }
+}
+
+fn arbitrary_value(u: &mut Unstructured<'_>, depth: usize) -> Result<Value> {
+    let kinds = if depth < MAX_DEPTH { 4 } else { 2 };
+    Ok(match u.choose_index(kinds)? {
+        0 => Value::ByteStr(SmallBytes::from(<&[u8]>::arbitrary(u)?)),
+        1 => Value::Int(Number::arbitrary(u)?),
+        2 => {
+            let len = u.arbitrary_len::<u8>()?;
+            let mut list = Vec::with_capacity(len);
+            for _ in 0..len {
+                list.push(arbitrary_value(u, depth + 1)?);
+            }
+            Value::List(list)
+        }
+        _ => {
+            let len = u.arbitrary_len::<(u8, u8)>()?;
+            let mut dict = BTreeMap::new();
+            for _ in 0..len {
+                let key = ByteStrKey::from(<&[u8]>::arbitrary(u)?);
+                dict.insert(key, arbitrary_value(u, depth + 1)?);
+            }
+            Value::Dict(dict)
+        }
+    })
+}
+
+impl<'a> Arbitrary<'a> for Value {
+    /// Generates a value with at most 8 levels of nested lists and dictionaries.
+    ///
+    /// Numbers are generated as described in [`Number::arbitrary()`].
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        arbitrary_value(u, 0)
+    }
+
+    fn size_hint(_depth: usize) -> (usize, Option<usize>) {
+        (1, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() -> crate::Result<()> {
+        let mut seed = 0x2545_f491_4f6c_dd1d_u64;
+        for len in 0..64 {
+            let data: Vec<u8> = (0..len * 16)
+                .map(|_| {
+                    seed ^= seed << 13;
+                    seed ^= seed >> 7;
+                    seed ^= seed << 17;
+                    seed as u8
+                })
+                .collect();
+            let value = Value::arbitrary_take_rest(Unstructured::new(&data))
+                .map_err(<crate::Error as serde::de::Error>::custom)?;
+            let encoded = crate::to_vec(&value)?;
+            assert_eq!(crate::from_slice::<Value>(&encoded)?, value);
+        }
+        Ok(())
+    }
+}
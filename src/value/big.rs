@@ -0,0 +1,137 @@
+//! Integers outside of the 128 bit ranges.
+
+use super::Number;
+use crate::error::Error;
+use core::{fmt, str::FromStr};
+use serde::de::Visitor;
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{boxed::Box, string::String};
+#[cfg(feature = "std")]
+use std::{boxed::Box, string::String};
+
+/// The newtype struct name used to serialize the digits of a [`Number::Big`].
+///
+/// The serializers in this crate write the digits as an integer. Other
+/// serializers see a newtype struct which wraps the digits as a string.
+pub(crate) const TOKEN: &str = "$bt_bencode::private::BigDigits";
+
+/// An integer with any number of digits.
+///
+/// Bencode does not limit the size of integers. A `BigDigits` stores the
+/// decimal digits of an integer which does not fit in a 128 bit integer, so
+/// the integer can be encoded again without loss. It does not support
+/// arithmetic; the digits from [`as_str()`][BigDigits::as_str] can be parsed
+/// by a big integer crate instead.
+///
+/// ```rust
+/// use bt_bencode::{value::Number, Value};
+///
+/// let input = b"i-123456789012345678901234567890123456789012345678901234567890e";
+/// let value: Value = bt_bencode::from_slice(input)?;
+/// match value.as_number() {
+///     Some(Number::Big(n)) => {
+///         assert!(n.is_negative());
+///         assert_eq!(n.as_str().len(), 61);
+///     }
+///     _ => panic!(),
+/// }
+/// assert_eq!(bt_bencode::to_vec(&value)?, &input[..]);
+/// # Ok::<(), bt_bencode::Error>(())
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BigDigits(Box<str>);
+
+impl BigDigits {
+    /// Returns the decimal digits with a leading `-` if the integer is negative.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns true if the integer is negative.
+    #[must_use]
+    pub fn is_negative(&self) -> bool {
+        self.0.starts_with('-')
+    }
+}
+
+impl FromStr for BigDigits {
+    type Err = Error;
+
+    /// Parses the decimal digits of an integer with an optional leading `-`.
+    ///
+    /// Leading zeros are removed.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, s),
+        };
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(Error::InvalidInteger);
+        }
+        let digits = digits.trim_start_matches('0');
+        if digits.is_empty() {
+            return Ok(BigDigits(Box::from("0")));
+        }
+        let mut n = String::with_capacity(digits.len() + 1);
+        if negative {
+            n.push('-');
+        }
+        n.push_str(digits);
+        Ok(BigDigits(n.into_boxed_str()))
+    }
+}
+
+impl fmt::Display for BigDigits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Deserializes the decimal digits of a [`Number::Big`].
+///
+/// A deserializer in this crate passes an integer which does not fit in 128
+/// bits to [`Visitor::visit_newtype_struct()`] with the digits as a string.
+pub(crate) fn deserialize_big_int<'de, D>(deserializer: D) -> Result<Number, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    struct DigitsVisitor;
+
+    impl<'de> Visitor<'de> for DigitsVisitor {
+        type Value = Number;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("the decimal digits of an integer")
+        }
+
+        fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            value.parse().map(Number::Big).map_err(E::custom)
+        }
+    }
+
+    deserializer.deserialize_str(DigitsVisitor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Result;
+
+    #[test]
+    fn test_big_int_from_str() -> Result<()> {
+        let n: BigDigits = "-000123".parse()?;
+        assert_eq!(n.as_str(), "-123");
+        assert!(n.is_negative());
+        assert_eq!("-0".parse::<BigDigits>()?.as_str(), "0");
+        assert!("".parse::<BigDigits>().is_err());
+        assert!("-".parse::<BigDigits>().is_err());
+        assert!("+1".parse::<BigDigits>().is_err());
+        assert!("1a".parse::<BigDigits>().is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,426 @@
+//! A byte string which stores short values inline.
+
+use super::pretty::{write_byte_str, MAX_BYTE_STR_LEN};
+use crate::key::ByteStrKey;
+use core::{
+    borrow::{Borrow, BorrowMut},
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    ops::{Deref, DerefMut},
+};
+use serde::{
+    de::{Deserialize, Deserializer, SeqAccess, Visitor},
+    ser::{Serialize, Serializer},
+};
+use serde_bytes::ByteBuf;
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{string::String, vec::Vec};
+#[cfg(feature = "std")]
+use std::{string::String, vec::Vec};
+
+/// The maximum length of a byte string which is stored inline.
+const INLINE_CAP: usize = 22;
+
+#[derive(Clone)]
+enum Repr {
+    Inline { len: u8, data: [u8; INLINE_CAP] },
+    Heap(Vec<u8>),
+}
+
+/// A byte string which stores short values inline instead of allocating.
+///
+/// Most dictionary keys and many values in real Bencode data (e.g. `"ip"`,
+/// `"port"`, or a 20 byte node ID) are only a few bytes long. Byte strings of
+/// up to 22 bytes are stored within the value itself. Longer byte strings are
+/// stored in a heap allocated buffer.
+///
+/// The type dereferences to a `[u8]` slice.
+///
+/// The [Debug][fmt::Debug] output shows printable ASCII characters as is and
+/// other bytes as hex escapes. Byte strings longer than 64 bytes, such as the
+/// `pieces` field of a torrent, are truncated. The precision sets a different
+/// maximum length.
+///
+/// ```rust
+/// use bt_bencode::Value;
+///
+/// let value: Value = bt_bencode::from_slice(b"l4:spam6:\x00\x01abcde")?;
+/// assert_eq!(format!("{:?}", value[0]), r#"ByteStr("spam")"#);
+/// assert_eq!(format!("{:.4?}", value[1]), r#"ByteStr("\x00\x01ab"... (6 bytes))"#);
+/// # Ok::<(), bt_bencode::Error>(())
+/// ```
+#[derive(Clone)]
+pub struct SmallBytes(Repr);
+
+impl SmallBytes {
+    /// Constructs an empty byte string.
+    #[must_use]
+    pub fn new() -> Self {
+        SmallBytes(Repr::Inline {
+            len: 0,
+            data: [0; INLINE_CAP],
+        })
+    }
+
+    /// Returns the bytes as a slice.
+    #[must_use]
+    pub fn as_slice(&self) -> &[u8] {
+        match &self.0 {
+            Repr::Inline { len, data } => &data[..usize::from(*len)],
+            Repr::Heap(v) => v.as_slice(),
+        }
+    }
+
+    /// Returns the bytes as a mutable slice.
+    #[must_use]
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        match &mut self.0 {
+            Repr::Inline { len, data } => &mut data[..usize::from(*len)],
+            Repr::Heap(v) => v.as_mut_slice(),
+        }
+    }
+
+    /// Returns true if the bytes are stored inline.
+    #[must_use]
+    pub fn is_inline(&self) -> bool {
+        match self.0 {
+            Repr::Inline { .. } => true,
+            Repr::Heap(_) => false,
+        }
+    }
+
+    /// Returns the number of bytes allocated on the heap.
+    pub(crate) fn heap_capacity(&self) -> usize {
+        match &self.0 {
+            Repr::Inline { .. } => 0,
+            Repr::Heap(v) => v.capacity(),
+        }
+    }
+
+    /// Frees any excess capacity of the heap allocated buffer.
+    ///
+    /// Byte strings which are short enough are moved inline.
+    pub fn shrink_to_fit(&mut self) {
+        if let Repr::Heap(v) = &mut self.0 {
+            if v.len() <= INLINE_CAP {
+                *self = SmallBytes::from(v.as_slice());
+            } else {
+                v.shrink_to_fit();
+            }
+        }
+    }
+
+    /// Converts the byte string into a `Vec<u8>`.
+    #[must_use]
+    pub fn into_vec(self) -> Vec<u8> {
+        match self.0 {
+            Repr::Inline { len, data } => data[..usize::from(len)].to_vec(),
+            Repr::Heap(v) => v,
+        }
+    }
+}
+
+impl Default for SmallBytes {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deref for SmallBytes {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl DerefMut for SmallBytes {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut [u8] {
+        self.as_mut_slice()
+    }
+}
+
+impl AsRef<[u8]> for SmallBytes {
+    fn as_ref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl AsMut<[u8]> for SmallBytes {
+    fn as_mut(&mut self) -> &mut [u8] {
+        self.as_mut_slice()
+    }
+}
+
+impl Borrow<[u8]> for SmallBytes {
+    fn borrow(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl BorrowMut<[u8]> for SmallBytes {
+    fn borrow_mut(&mut self) -> &mut [u8] {
+        self.as_mut_slice()
+    }
+}
+
+impl fmt::Debug for SmallBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let max_len = f.precision().unwrap_or(MAX_BYTE_STR_LEN);
+        write_byte_str(f, self.as_slice(), max_len)
+    }
+}
+
+impl PartialEq for SmallBytes {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl Eq for SmallBytes {}
+
+impl PartialEq<[u8]> for SmallBytes {
+    fn eq(&self, other: &[u8]) -> bool {
+        self.as_slice() == other
+    }
+}
+
+impl<'a> PartialEq<&'a [u8]> for SmallBytes {
+    fn eq(&self, other: &&'a [u8]) -> bool {
+        self.as_slice() == *other
+    }
+}
+
+impl PartialEq<ByteBuf> for SmallBytes {
+    fn eq(&self, other: &ByteBuf) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl PartialOrd for SmallBytes {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SmallBytes {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_slice().cmp(other.as_slice())
+    }
+}
+
+impl Hash for SmallBytes {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_slice().hash(state);
+    }
+}
+
+impl<'a> From<&'a [u8]> for SmallBytes {
+    fn from(other: &'a [u8]) -> Self {
+        if other.len() <= INLINE_CAP {
+            let mut data = [0; INLINE_CAP];
+            data[..other.len()].copy_from_slice(other);
+            #[allow(clippy::cast_possible_truncation)]
+            SmallBytes(Repr::Inline {
+                len: other.len() as u8,
+                data,
+            })
+        } else {
+            SmallBytes(Repr::Heap(other.to_vec()))
+        }
+    }
+}
+
+impl<'a> From<&'a str> for SmallBytes {
+    fn from(other: &'a str) -> Self {
+        SmallBytes::from(other.as_bytes())
+    }
+}
+
+impl From<Vec<u8>> for SmallBytes {
+    fn from(other: Vec<u8>) -> Self {
+        if other.len() <= INLINE_CAP {
+            SmallBytes::from(other.as_slice())
+        } else {
+            SmallBytes(Repr::Heap(other))
+        }
+    }
+}
+
+impl From<String> for SmallBytes {
+    fn from(other: String) -> Self {
+        SmallBytes::from(other.into_bytes())
+    }
+}
+
+impl From<ByteBuf> for SmallBytes {
+    fn from(other: ByteBuf) -> Self {
+        SmallBytes::from(other.into_vec())
+    }
+}
+
+impl From<ByteStrKey> for SmallBytes {
+    fn from(other: ByteStrKey) -> Self {
+        other.0
+    }
+}
+
+impl From<SmallBytes> for Vec<u8> {
+    fn from(other: SmallBytes) -> Self {
+        other.into_vec()
+    }
+}
+
+impl From<SmallBytes> for ByteBuf {
+    fn from(other: SmallBytes) -> Self {
+        ByteBuf::from(other.into_vec())
+    }
+}
+
+impl From<SmallBytes> for ByteStrKey {
+    fn from(other: SmallBytes) -> Self {
+        ByteStrKey(other)
+    }
+}
+
+impl Serialize for SmallBytes {
+    #[inline]
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_bytes(self.as_slice())
+    }
+}
+
+impl<'de> Deserialize<'de> for SmallBytes {
+    #[inline]
+    fn deserialize<D>(deserializer: D) -> Result<SmallBytes, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct SmallBytesVisitor;
+
+        impl<'de> Visitor<'de> for SmallBytesVisitor {
+            type Value = SmallBytes;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a byte string")
+            }
+
+            #[inline]
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E> {
+                Ok(SmallBytes::from(value))
+            }
+
+            #[inline]
+            fn visit_string<E>(self, value: String) -> Result<Self::Value, E> {
+                Ok(SmallBytes::from(value))
+            }
+
+            #[inline]
+            fn visit_bytes<E>(self, value: &[u8]) -> Result<Self::Value, E> {
+                Ok(SmallBytes::from(value))
+            }
+
+            #[inline]
+            fn visit_byte_buf<E>(self, value: Vec<u8>) -> Result<Self::Value, E> {
+                Ok(SmallBytes::from(value))
+            }
+
+            fn visit_seq<V>(self, mut visitor: V) -> Result<Self::Value, V::Error>
+            where
+                V: SeqAccess<'de>,
+            {
+                let mut bytes = Vec::with_capacity(visitor.size_hint().unwrap_or(0));
+                while let Some(b) = visitor.next_element()? {
+                    bytes.push(b);
+                }
+                Ok(SmallBytes::from(bytes))
+            }
+        }
+
+        deserializer.deserialize_byte_buf(SmallBytesVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(all(feature = "alloc", not(feature = "std")))]
+    use alloc::{format, vec};
+    #[cfg(feature = "std")]
+    use std::{format, vec};
+
+    #[test]
+    fn test_inline_and_heap() {
+        let short = SmallBytes::from(&b"abcdefghij0123456789"[..]);
+        assert!(short.is_inline());
+        assert_eq!(short, &b"abcdefghij0123456789"[..]);
+
+        let long = SmallBytes::from(vec![7; INLINE_CAP + 1]);
+        assert!(!long.is_inline());
+        assert_eq!(long.len(), INLINE_CAP + 1);
+
+        let empty = SmallBytes::new();
+        assert!(empty.is_inline());
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn test_mutate() {
+        let mut bytes = SmallBytes::from("spam");
+        bytes[0] = b'S';
+        assert_eq!(bytes.into_vec(), b"Spam");
+    }
+
+    #[test]
+    fn test_eq_and_ord_ignore_repr() {
+        let inline = SmallBytes::from(&b"a"[..]);
+        let heap = SmallBytes(Repr::Heap(b"a".to_vec()));
+        assert_eq!(inline, heap);
+        assert!(SmallBytes::from("b") > heap);
+        assert!(SmallBytes::from("") < heap);
+    }
+
+    #[test]
+    fn test_debug() {
+        let bytes = SmallBytes::from(&b"a\"\\\n\xff"[..]);
+        assert_eq!(format!("{:?}", bytes), r#""a\"\\\n\xff""#);
+
+        let long = SmallBytes::from(vec![b'a'; MAX_BYTE_STR_LEN + 1]);
+        let s = format!("{:?}", long);
+        assert_eq!(s.matches('a').count(), MAX_BYTE_STR_LEN);
+        assert!(s.ends_with("\"... (65 bytes)"));
+        assert_eq!(format!("{:.1?}", long), r#""a"... (65 bytes)"#);
+        assert_eq!(format!("{:.0?}", SmallBytes::new()), r#""""#);
+    }
+
+    #[test]
+    fn test_shrink_to_fit() {
+        let mut bytes = SmallBytes(Repr::Heap(Vec::with_capacity(64)));
+        bytes.shrink_to_fit();
+        assert!(bytes.is_inline());
+
+        let mut v = Vec::with_capacity(64);
+        v.extend_from_slice(&[7; INLINE_CAP + 1]);
+        let mut bytes = SmallBytes(Repr::Heap(v));
+        bytes.shrink_to_fit();
+        assert!(!bytes.is_inline());
+        assert_eq!(bytes.heap_capacity(), INLINE_CAP + 1);
+    }
+
+    #[test]
+    fn test_value_byte_str_is_inline() -> crate::Result<()> {
+        let value: crate::Value = crate::from_slice(b"l2:ip30:abcdefghij0123456789abcdefghije")?;
+        let list = value.as_array().unwrap();
+        assert!(list[0].as_byte_str().unwrap().is_inline());
+        assert!(!list[1].as_byte_str().unwrap().is_inline());
+        Ok(())
+    }
+}
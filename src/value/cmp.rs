@@ -0,0 +1,155 @@
+//! Orders [Value]s by their canonical encodings.
+
+use super::Value;
+use core::{cmp::Ordering, iter};
+
+impl PartialOrd for Value {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Value {
+    /// Compares the canonical encodings of the values byte by byte.
+    ///
+    /// The values are not encoded. The order is the same as comparing the
+    /// output of [`to_vec()`][crate::to_vec()], so byte strings are ordered
+    /// before dictionaries, then integers and then lists, and a byte string
+    /// with a length of `10` is ordered before a byte string with a length of
+    /// `2`.
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Value::ByteStr(a), Value::ByteStr(b)) => cmp_byte_str(a, b),
+            (Value::Int(a), Value::Int(b)) => {
+                let mut a_buf = itoa::Buffer::new();
+                let mut b_buf = itoa::Buffer::new();
+                let a = a.format(&mut a_buf).bytes().chain(iter::once(b'e'));
+                let b = b.format(&mut b_buf).bytes().chain(iter::once(b'e'));
+                a.cmp(b)
+            }
+            (Value::List(a), Value::List(b)) => {
+                let mut a = a.iter();
+                let mut b = b.iter();
+                loop {
+                    match (a.next(), b.next()) {
+                        (Some(a), Some(b)) => match a.cmp(b) {
+                            Ordering::Equal => {}
+                            ord => return ord,
+                        },
+                        (None, None) => return Ordering::Equal,
+                        (None, Some(b)) => return b'e'.cmp(&first_byte(b)),
+                        (Some(a), None) => return first_byte(a).cmp(&b'e'),
+                    }
+                }
+            }
+            (Value::Dict(a), Value::Dict(b)) => {
+                let mut a = a.iter();
+                let mut b = b.iter();
+                loop {
+                    match (a.next(), b.next()) {
+                        (Some((a_key, a_value)), Some((b_key, b_value))) => {
+                            match cmp_byte_str(a_key, b_key).then_with(|| a_value.cmp(b_value)) {
+                                Ordering::Equal => {}
+                                ord => return ord,
+                            }
+                        }
+                        (None, None) => return Ordering::Equal,
+                        // A key starts with a digit which is ordered before `e`.
+                        (None, Some(_)) => return Ordering::Greater,
+                        (Some(_), None) => return Ordering::Less,
+                    }
+                }
+            }
+            _ => first_byte(self).cmp(&first_byte(other)),
+        }
+    }
+}
+
+/// Returns the first byte of the value's encoding.
+fn first_byte(value: &Value) -> u8 {
+    match value {
+        Value::ByteStr(b) => {
+            let mut buf = itoa::Buffer::new();
+            buf.format(b.len()).as_bytes()[0]
+        }
+        Value::Int(_) => b'i',
+        Value::List(_) => b'l',
+        Value::Dict(_) => b'd',
+    }
+}
+
+fn cmp_byte_str(a: &[u8], b: &[u8]) -> Ordering {
+    if a.len() == b.len() {
+        return a.cmp(b);
+    }
+    let mut a_buf = itoa::Buffer::new();
+    let mut b_buf = itoa::Buffer::new();
+    let a_len = a_buf.format(a.len()).bytes().chain(iter::once(b':'));
+    let b_len = b_buf.format(b.len()).bytes().chain(iter::once(b':'));
+    a_len.cmp(b_len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Result;
+
+    #[cfg(all(feature = "alloc", not(feature = "std")))]
+    use alloc::vec::Vec;
+    #[cfg(feature = "std")]
+    use std::vec::Vec;
+
+    #[test]
+    fn test_cmp_matches_encoding() -> Result<()> {
+        let inputs: &[&[u8]] = &[
+            b"0:",
+            b"1:a",
+            b"1:b",
+            b"10:aaaaaaaaaa",
+            b"2:aa",
+            b"de",
+            b"d1:ai1ee",
+            b"d1:ai2ee",
+            b"d1:ai1e1:bi1ee",
+            b"d2:aai1ee",
+            b"i-1e",
+            b"i-10e",
+            b"i-2e",
+            b"i0e",
+            b"i1e",
+            b"i10e",
+            b"i2e",
+            b"i340282366920938463463374607431768211455e",
+            b"le",
+            b"l0:e",
+            b"ldee",
+            b"li1ee",
+            b"li1ei2ee",
+            b"llee",
+            b"lli1eee",
+        ];
+        let values = inputs
+            .iter()
+            .map(|input| crate::from_slice(input))
+            .collect::<Result<Vec<Value>>>()?;
+        for a in &values {
+            for b in &values {
+                assert_eq!(
+                    a.cmp(b),
+                    crate::to_vec(a)?.cmp(&crate::to_vec(b)?),
+                    "{:?} {:?}",
+                    a,
+                    b
+                );
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_cmp_numbers() {
+        assert_eq!(Value::from(3_i64).cmp(&Value::from(3_u64)), Ordering::Equal);
+        assert_eq!(Value::from(10_u64).cmp(&Value::from(9_u64)), Ordering::Less);
+        assert_eq!(Value::from(-1_i64).cmp(&Value::from(0_u64)), Ordering::Less);
+    }
+}
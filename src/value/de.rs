@@ -1,10 +1,9 @@
 //! Deserializes from a [Value].
 
 use super::{Number, Value};
-use crate::error::Error;
+use crate::{error::Error, key::ByteStrKey};
 use serde::de::{DeserializeSeed, IntoDeserializer, MapAccess, SeqAccess, Visitor};
 use serde::forward_to_deserialize_any;
-use serde_bytes::ByteBuf;
 
 #[cfg(all(feature = "alloc", not(feature = "std")))]
 use alloc::{borrow::Cow, collections::BTreeMap, vec};
@@ -24,6 +23,10 @@ impl<'de> serde::Deserializer<'de> for Value {
             Value::Int(n) => match n {
                 Number::Signed(s) => visitor.visit_i64(s),
                 Number::Unsigned(u) => visitor.visit_u64(u),
+                Number::Signed128(s) => visitor.visit_i128(s),
+                Number::Unsigned128(u) => visitor.visit_u128(u),
+                #[cfg(feature = "big-int")]
+                Number::Big(n) => visitor.visit_newtype_struct(n.as_str().into_deserializer()),
             },
             Value::List(l) => {
                 let len = l.len();
@@ -68,11 +71,21 @@ impl<'de> serde::Deserializer<'de> for Value {
 
         char str string bytes byte_buf
 
-        seq map
+        map
 
         struct enum identifier ignored_any
     }
 
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::ByteStr(bytes) => crate::de::visit_byte_seq(&bytes, visitor),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
     #[inline]
     fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
@@ -84,12 +97,15 @@ impl<'de> serde::Deserializer<'de> for Value {
     #[inline]
     fn deserialize_newtype_struct<V>(
         self,
-        _name: &'static str,
+        name: &'static str,
         visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
+        if name == super::raw::TOKEN {
+            return visitor.visit_byte_buf(crate::to_vec(&self)?);
+        }
         visitor.visit_newtype_struct(self)
     }
 
@@ -98,7 +114,7 @@ impl<'de> serde::Deserializer<'de> for Value {
     where
         V: Visitor<'de>,
     {
-        self.deserialize_seq(visitor)
+        self.deserialize_any(visitor)
     }
 
     #[inline]
@@ -111,7 +127,7 @@ impl<'de> serde::Deserializer<'de> for Value {
     where
         V: Visitor<'de>,
     {
-        self.deserialize_seq(visitor)
+        self.deserialize_any(visitor)
     }
 
     #[inline]
@@ -154,7 +170,7 @@ impl<'de> SeqAccess<'de> for ListDeserializer {
 }
 
 struct DictDeserializer {
-    iter: <BTreeMap<ByteBuf, Value> as IntoIterator>::IntoIter,
+    iter: <BTreeMap<ByteStrKey, Value> as IntoIterator>::IntoIter,
     value: Option<Value>,
 }
 
@@ -196,7 +212,7 @@ impl<'de> MapAccess<'de> for DictDeserializer {
 }
 
 struct DictKey<'a> {
-    key: Cow<'a, ByteBuf>,
+    key: Cow<'a, ByteStrKey>,
 }
 
 impl<'de> serde::Deserializer<'de> for DictKey<'de> {
@@ -207,7 +223,7 @@ impl<'de> serde::Deserializer<'de> for DictKey<'de> {
         V: Visitor<'de>,
     {
         match self.key {
-            Cow::Borrowed(bytes) => visitor.visit_borrowed_bytes(bytes),
+            Cow::Borrowed(bytes) => visitor.visit_borrowed_bytes(bytes.as_slice()),
             Cow::Owned(bytes) => visitor.visit_byte_buf(bytes.into_vec()),
         }
     }
@@ -251,6 +267,10 @@ impl<'de> serde::Deserializer<'de> for &'de Value {
             Value::Int(n) => match n {
                 Number::Signed(s) => visitor.visit_i64(*s),
                 Number::Unsigned(u) => visitor.visit_u64(*u),
+                Number::Signed128(s) => visitor.visit_i128(*s),
+                Number::Unsigned128(u) => visitor.visit_u128(*u),
+                #[cfg(feature = "big-int")]
+                Number::Big(n) => visitor.visit_newtype_struct(n.as_str().into_deserializer()),
             },
             Value::List(l) => {
                 let len = l.len();
@@ -295,11 +315,21 @@ impl<'de> serde::Deserializer<'de> for &'de Value {
 
         char str string bytes byte_buf
 
-        seq map
+        map
 
         struct enum identifier ignored_any
     }
 
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::ByteStr(bytes) => crate::de::visit_byte_seq(bytes, visitor),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
     #[inline]
     fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
@@ -311,12 +341,15 @@ impl<'de> serde::Deserializer<'de> for &'de Value {
     #[inline]
     fn deserialize_newtype_struct<V>(
         self,
-        _name: &'static str,
+        name: &'static str,
         visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
+        if name == super::raw::TOKEN {
+            return visitor.visit_byte_buf(crate::to_vec(&self)?);
+        }
         visitor.visit_newtype_struct(self)
     }
 
@@ -325,7 +358,7 @@ impl<'de> serde::Deserializer<'de> for &'de Value {
     where
         V: Visitor<'de>,
     {
-        self.deserialize_seq(visitor)
+        self.deserialize_any(visitor)
     }
 
     #[inline]
@@ -338,7 +371,7 @@ impl<'de> serde::Deserializer<'de> for &'de Value {
     where
         V: Visitor<'de>,
     {
-        self.deserialize_seq(visitor)
+        self.deserialize_any(visitor)
     }
 
     #[inline]
@@ -373,7 +406,7 @@ impl<'a> SeqAccess<'a> for ListRefDeserializer<'a> {
 }
 
 struct DictRefDeserializer<'a> {
-    iter: <&'a BTreeMap<ByteBuf, Value> as IntoIterator>::IntoIter,
+    iter: <&'a BTreeMap<ByteStrKey, Value> as IntoIterator>::IntoIter,
     value: Option<&'a Value>,
 }
 
@@ -417,7 +450,8 @@ impl<'a> MapAccess<'a> for DictRefDeserializer<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{error::Result, from_value};
+    use crate::{error::Result, from_value, value::SmallBytes};
+    use serde_bytes::ByteBuf;
 
     #[cfg(all(feature = "alloc", not(feature = "std")))]
     use alloc::{string::String, vec, vec::Vec};
@@ -426,7 +460,7 @@ mod tests {
 
     #[test]
     fn test_deserialize_string() -> Result<()> {
-        let v = Value::ByteStr(ByteBuf::from(String::from("spam")));
+        let v = Value::ByteStr(SmallBytes::from(String::from("spam")));
         let s: String = from_value(v)?;
         assert_eq!("spam", s);
         Ok(())
@@ -434,12 +468,26 @@ mod tests {
 
     #[test]
     fn test_deserialize_byte_str() -> Result<()> {
-        let v = Value::ByteStr(ByteBuf::from(String::from("spam")));
+        let v = Value::ByteStr(SmallBytes::from(String::from("spam")));
         let b: ByteBuf = from_value(v)?;
         assert_eq!(ByteBuf::from(String::from("spam")), b);
         Ok(())
     }
 
+    #[test]
+    fn test_deserialize_byte_str_as_vec() -> Result<()> {
+        let v = Value::ByteStr(SmallBytes::from(String::from("spam")));
+        let b: Vec<u8> = serde::Deserialize::deserialize(&v)?;
+        assert_eq!(b, b"spam");
+        assert!(<Vec<u64> as serde::Deserialize>::deserialize(&v).is_err());
+        assert!(<(u8, u8) as serde::Deserialize>::deserialize(&v).is_err());
+        assert!(<[u8; 4] as serde::Deserialize>::deserialize(&v).is_err());
+        let b: Vec<u8> = from_value(v.clone())?;
+        assert_eq!(b, b"spam");
+        assert!(from_value::<Vec<u16>>(v).is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_deserialize_integer_1() -> Result<()> {
         let v = Value::Int(Number::Unsigned(3));
@@ -467,8 +515,8 @@ mod tests {
     #[test]
     fn test_deserialize_list() -> Result<()> {
         let v = Value::List(vec![
-            Value::ByteStr(ByteBuf::from(String::from("spam"))),
-            Value::ByteStr(ByteBuf::from(String::from("eggs"))),
+            Value::ByteStr(SmallBytes::from(String::from("spam"))),
+            Value::ByteStr(SmallBytes::from(String::from("eggs"))),
         ]);
         let v: Vec<String> = from_value(v)?;
         assert_eq!(v, vec!["spam", "eggs"]);
@@ -479,12 +527,12 @@ mod tests {
     fn test_deserialize_dict_1() -> Result<()> {
         let mut m = BTreeMap::new();
         m.insert(
-            ByteBuf::from(String::from("cow")),
-            Value::ByteStr(ByteBuf::from(String::from("moo"))),
+            ByteStrKey::from(String::from("cow")),
+            Value::ByteStr(SmallBytes::from(String::from("moo"))),
         );
         m.insert(
-            ByteBuf::from(String::from("spam")),
-            Value::ByteStr(ByteBuf::from(String::from("eggs"))),
+            ByteStrKey::from(String::from("spam")),
+            Value::ByteStr(SmallBytes::from(String::from("eggs"))),
         );
         let d = Value::Dict(m);
         let d: BTreeMap<String, String> = from_value(d)?;
@@ -502,12 +550,12 @@ mod tests {
 
         let mut m = BTreeMap::new();
         m.insert(
-            ByteBuf::from(String::from("cow")),
-            Value::ByteStr(ByteBuf::from(String::from("moo"))),
+            ByteStrKey::from(String::from("cow")),
+            Value::ByteStr(SmallBytes::from(String::from("moo"))),
         );
         m.insert(
-            ByteBuf::from(String::from("spam")),
-            Value::ByteStr(ByteBuf::from(String::from("eggs"))),
+            ByteStrKey::from(String::from("spam")),
+            Value::ByteStr(SmallBytes::from(String::from("eggs"))),
         );
         let d = Value::Dict(m);
         let d = BTreeMap::<&str, &str>::deserialize(&d)?;
@@ -523,10 +571,10 @@ mod tests {
     fn test_deserialize_dict_2() -> Result<()> {
         let mut m = BTreeMap::new();
         m.insert(
-            ByteBuf::from(String::from("spam")),
+            ByteStrKey::from(String::from("spam")),
             Value::List(vec![
-                Value::ByteStr(ByteBuf::from(String::from("a"))),
-                Value::ByteStr(ByteBuf::from(String::from("b"))),
+                Value::ByteStr(SmallBytes::from(String::from("a"))),
+                Value::ByteStr(SmallBytes::from(String::from("b"))),
             ]),
         );
         let d = Value::Dict(m);
@@ -547,10 +595,10 @@ mod tests {
 
         let mut m = BTreeMap::new();
         m.insert(
-            ByteBuf::from(String::from("spam")),
+            ByteStrKey::from(String::from("spam")),
             Value::List(vec![
-                Value::ByteStr(ByteBuf::from(String::from("a"))),
-                Value::ByteStr(ByteBuf::from(String::from("b"))),
+                Value::ByteStr(SmallBytes::from(String::from("a"))),
+                Value::ByteStr(SmallBytes::from(String::from("b"))),
             ]),
         );
         let d = Value::Dict(m);
@@ -568,10 +616,10 @@ mod tests {
 
         let mut m = BTreeMap::new();
         m.insert(
-            ByteBuf::from(String::from("spam")),
+            ByteStrKey::from(String::from("spam")),
             Value::List(vec![
-                Value::ByteStr(ByteBuf::from(String::from("a"))),
-                Value::ByteStr(ByteBuf::from(String::from("b"))),
+                Value::ByteStr(SmallBytes::from(String::from("a"))),
+                Value::ByteStr(SmallBytes::from(String::from("b"))),
             ]),
         );
         let d = Value::Dict(m);
@@ -587,10 +635,10 @@ mod tests {
     fn test_deserialize_dict_3() -> Result<()> {
         let mut m = BTreeMap::new();
         m.insert(
-            ByteBuf::from(String::from("spam")),
+            ByteStrKey::from(String::from("spam")),
             Value::List(vec![
-                Value::ByteStr(ByteBuf::from(String::from("a"))),
-                Value::ByteStr(ByteBuf::from(String::from("b"))),
+                Value::ByteStr(SmallBytes::from(String::from("a"))),
+                Value::ByteStr(SmallBytes::from(String::from("b"))),
             ]),
         );
         let d = Value::Dict(m);
@@ -600,8 +648,8 @@ mod tests {
         expected.insert(
             String::from("spam"),
             Value::List(vec![
-                Value::ByteStr(ByteBuf::from(String::from("a"))),
-                Value::ByteStr(ByteBuf::from(String::from("b"))),
+                Value::ByteStr(SmallBytes::from(String::from("a"))),
+                Value::ByteStr(SmallBytes::from(String::from("b"))),
             ]),
         );
         assert_eq!(d, expected);
@@ -612,10 +660,10 @@ mod tests {
     fn test_deserialize_dict_4() -> Result<()> {
         let mut m = BTreeMap::new();
         m.insert(
-            ByteBuf::from(String::from("spam")),
+            ByteStrKey::from(String::from("spam")),
             Value::List(vec![
-                Value::ByteStr(ByteBuf::from(String::from("a"))),
-                Value::ByteStr(ByteBuf::from(String::from("b"))),
+                Value::ByteStr(SmallBytes::from(String::from("a"))),
+                Value::ByteStr(SmallBytes::from(String::from("b"))),
             ]),
         );
         let d = Value::Dict(m);
@@ -625,11 +673,49 @@ mod tests {
         expected.insert(
             String::from("spam"),
             vec![
-                Value::ByteStr(ByteBuf::from(String::from("a"))),
-                Value::ByteStr(ByteBuf::from(String::from("b"))),
+                Value::ByteStr(SmallBytes::from(String::from("a"))),
+                Value::ByteStr(SmallBytes::from(String::from("b"))),
             ],
         );
         assert_eq!(d, expected);
         Ok(())
     }
+
+    #[test]
+    fn test_deserialize_flatten() -> Result<()> {
+        use serde::Deserialize;
+        use serde_derive::Deserialize;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Inner {
+            a: i64,
+            b: ByteBuf,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Outer {
+            c: String,
+            #[serde(flatten)]
+            inner: Inner,
+            #[serde(flatten)]
+            rest: BTreeMap<String, Value>,
+        }
+
+        let v: Value = crate::from_slice(b"d1:ai-1e1:b1:\xff1:c4:spam1:dle1:ei1ee")?;
+        let mut rest = BTreeMap::new();
+        rest.insert(String::from("d"), Value::List(Vec::new()));
+        rest.insert(String::from("e"), Value::from(1u64));
+        let expected = Outer {
+            c: String::from("spam"),
+            inner: Inner {
+                a: -1,
+                b: ByteBuf::from(vec![0xff]),
+            },
+            rest,
+        };
+
+        assert_eq!(Outer::deserialize(&v)?, expected);
+        assert_eq!(from_value::<Outer>(v)?, expected);
+        Ok(())
+    }
 }
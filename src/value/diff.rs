@@ -0,0 +1,219 @@
+//! Structural differences between two [Value]s.
+
+use super::Value;
+use crate::de::PathSegment;
+use core::cmp::Ordering;
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::vec::Vec;
+
+/// A difference between two values, returned by [`diff()`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DiffEntry<'a> {
+    /// A value is only in the second value.
+    Added {
+        /// The path to the value.
+        path: Vec<PathSegment<'a>>,
+        /// The value in the second value.
+        value: &'a Value,
+    },
+    /// A value is only in the first value.
+    Removed {
+        /// The path to the value.
+        path: Vec<PathSegment<'a>>,
+        /// The value in the first value.
+        value: &'a Value,
+    },
+    /// A value is different in the second value.
+    Changed {
+        /// The path to the value.
+        path: Vec<PathSegment<'a>>,
+        /// The value in the first value.
+        old: &'a Value,
+        /// The value in the second value.
+        new: &'a Value,
+    },
+}
+
+impl<'a> DiffEntry<'a> {
+    /// Returns the path to the value which is different.
+    #[must_use]
+    pub fn path(&self) -> &[PathSegment<'a>] {
+        match self {
+            DiffEntry::Added { path, .. }
+            | DiffEntry::Removed { path, .. }
+            | DiffEntry::Changed { path, .. } => path,
+        }
+    }
+}
+
+/// Returns the differences between two values.
+///
+/// Dictionaries are compared entry by entry, and lists are compared element
+/// by element at the same index, so an element inserted at the start of a
+/// list changes every later element. Any other values which are not equal,
+/// including values of different kinds, are [`DiffEntry::Changed`]. The
+/// entries are ordered by path.
+///
+/// ```rust
+/// use bt_bencode::value::{diff, DiffEntry};
+/// use bt_bencode::{PathSegment, Value};
+///
+/// let a: Value = bt_bencode::from_slice(b"d1:ai1e1:bli1eee")?;
+/// let b: Value = bt_bencode::from_slice(b"d1:ai2e1:bli1ei2ee1:c0:e")?;
+///
+/// let diffs = diff(&a, &b);
+/// assert_eq!(diffs.len(), 3);
+/// assert_eq!(
+///     diffs[0],
+///     DiffEntry::Changed {
+///         path: vec![PathSegment::Key(b"a")],
+///         old: &a["a"],
+///         new: &b["a"],
+///     }
+/// );
+/// assert_eq!(diffs[1].path(), [PathSegment::Key(b"b"), PathSegment::Index(1)]);
+/// assert_eq!(diffs[2].path(), [PathSegment::Key(b"c")]);
+/// # Ok::<(), bt_bencode::Error>(())
+/// ```
+#[must_use]
+pub fn diff<'a>(a: &'a Value, b: &'a Value) -> Vec<DiffEntry<'a>> {
+    let mut diffs = Vec::new();
+    let mut path = Vec::new();
+    diff_values(a, b, &mut path, &mut diffs);
+    diffs
+}
+
+fn diff_values<'a>(
+    a: &'a Value,
+    b: &'a Value,
+    path: &mut Vec<PathSegment<'a>>,
+    diffs: &mut Vec<DiffEntry<'a>>,
+) {
+    match (a, b) {
+        (Value::List(a), Value::List(b)) => {
+            for (i, (a, b)) in a.iter().zip(b).enumerate() {
+                path.push(PathSegment::Index(i));
+                diff_values(a, b, path, diffs);
+                path.pop();
+            }
+            for (i, value) in a.iter().enumerate().skip(b.len()) {
+                path.push(PathSegment::Index(i));
+                diffs.push(DiffEntry::Removed {
+                    path: path.clone(),
+                    value,
+                });
+                path.pop();
+            }
+            for (i, value) in b.iter().enumerate().skip(a.len()) {
+                path.push(PathSegment::Index(i));
+                diffs.push(DiffEntry::Added {
+                    path: path.clone(),
+                    value,
+                });
+                path.pop();
+            }
+        }
+        (Value::Dict(a), Value::Dict(b)) => {
+            let mut a = a.iter().peekable();
+            let mut b = b.iter().peekable();
+            loop {
+                let ord = match (a.peek(), b.peek()) {
+                    (Some((a_key, _)), Some((b_key, _))) => a_key.cmp(b_key),
+                    (Some(_), None) => Ordering::Less,
+                    (None, Some(_)) => Ordering::Greater,
+                    (None, None) => break,
+                };
+                match ord {
+                    Ordering::Less => {
+                        let (key, value) = a.next().expect("entry is peeked");
+                        path.push(PathSegment::Key(key));
+                        diffs.push(DiffEntry::Removed {
+                            path: path.clone(),
+                            value,
+                        });
+                    }
+                    Ordering::Greater => {
+                        let (key, value) = b.next().expect("entry is peeked");
+                        path.push(PathSegment::Key(key));
+                        diffs.push(DiffEntry::Added {
+                            path: path.clone(),
+                            value,
+                        });
+                    }
+                    Ordering::Equal => {
+                        let (key, a_value) = a.next().expect("entry is peeked");
+                        let (_, b_value) = b.next().expect("entry is peeked");
+                        path.push(PathSegment::Key(key));
+                        diff_values(a_value, b_value, path, diffs);
+                    }
+                }
+                path.pop();
+            }
+        }
+        _ => {
+            if a != b {
+                diffs.push(DiffEntry::Changed {
+                    path: path.clone(),
+                    old: a,
+                    new: b,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Result;
+
+    #[cfg(all(feature = "alloc", not(feature = "std")))]
+    use alloc::vec;
+    #[cfg(feature = "std")]
+    use std::vec;
+
+    #[test]
+    fn test_diff() -> Result<()> {
+        let a: Value = crate::from_slice(b"d1:ai1e1:bli1ei2ee1:cd1:di1ee1:xi0ee")?;
+        let b: Value = crate::from_slice(b"d1:ai1e1:bli1ee1:cd1:d0:e1:yi0ee")?;
+        assert!(diff(&a, &a).is_empty());
+
+        let diffs = diff(&a, &b);
+        assert_eq!(
+            diffs,
+            [
+                DiffEntry::Removed {
+                    path: vec![PathSegment::Key(b"b"), PathSegment::Index(1)],
+                    value: &a["b"][1],
+                },
+                DiffEntry::Changed {
+                    path: vec![PathSegment::Key(b"c"), PathSegment::Key(b"d")],
+                    old: &a["c"]["d"],
+                    new: &b["c"]["d"],
+                },
+                DiffEntry::Removed {
+                    path: vec![PathSegment::Key(b"x")],
+                    value: &a["x"],
+                },
+                DiffEntry::Added {
+                    path: vec![PathSegment::Key(b"y")],
+                    value: &b["y"],
+                },
+            ]
+        );
+
+        let diffs = diff(&a, &b["a"]);
+        assert_eq!(
+            diffs,
+            [DiffEntry::Changed {
+                path: vec![],
+                old: &a,
+                new: &b["a"],
+            }]
+        );
+        Ok(())
+    }
+}
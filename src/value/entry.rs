@@ -0,0 +1,174 @@
+//! Entries in a [Value] dictionary.
+
+use super::Value;
+use crate::key::ByteStrKey;
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{
+    collections::{btree_map, BTreeMap},
+    vec::Vec,
+};
+#[cfg(feature = "std")]
+use std::{
+    collections::{btree_map, BTreeMap},
+    vec::Vec,
+};
+
+/// An entry in a dictionary which may be vacant or occupied.
+///
+/// Returned by [`Value::entry()`].
+#[derive(Debug)]
+pub struct Entry<'a>(btree_map::Entry<'a, ByteStrKey, Value>);
+
+impl<'a> Entry<'a> {
+    /// Returns the entry's key.
+    #[must_use]
+    pub fn key(&self) -> &[u8] {
+        self.0.key()
+    }
+
+    /// Inserts the value if the entry is vacant and returns a mutable
+    /// reference to the entry's value.
+    pub fn or_insert<V>(self, default: V) -> &'a mut Value
+    where
+        V: Into<Value>,
+    {
+        self.0.or_insert_with(|| default.into())
+    }
+
+    /// Inserts the result of the function if the entry is vacant and returns
+    /// a mutable reference to the entry's value.
+    pub fn or_insert_with<F, V>(self, default: F) -> &'a mut Value
+    where
+        F: FnOnce() -> V,
+        V: Into<Value>,
+    {
+        self.0.or_insert_with(|| default().into())
+    }
+
+    /// Inserts an empty dictionary if the entry is vacant and returns a
+    /// mutable reference to the entry's dictionary.
+    ///
+    /// If the entry is occupied by a value which is not a dictionary, `None`
+    /// is returned and the value is unchanged.
+    pub fn or_insert_dict(self) -> Option<&'a mut BTreeMap<ByteStrKey, Value>> {
+        self.0
+            .or_insert_with(|| Value::Dict(BTreeMap::new()))
+            .as_dict_mut()
+    }
+
+    /// Inserts an empty list if the entry is vacant and returns a mutable
+    /// reference to the entry's list.
+    ///
+    /// If the entry is occupied by a value which is not a list, `None` is
+    /// returned and the value is unchanged.
+    pub fn or_insert_list(self) -> Option<&'a mut Vec<Value>> {
+        self.0
+            .or_insert_with(|| Value::List(Vec::new()))
+            .as_array_mut()
+    }
+
+    /// Calls the function with the entry's value if the entry is occupied.
+    #[must_use]
+    pub fn and_modify<F>(self, f: F) -> Self
+    where
+        F: FnOnce(&mut Value),
+    {
+        Entry(self.0.and_modify(f))
+    }
+}
+
+impl Value {
+    /// Returns the entry for a key if the value is a dictionary.
+    ///
+    /// If the value is not a dictionary, `None` is returned.
+    ///
+    /// ```rust
+    /// use bt_bencode::{value::Entry, Value};
+    ///
+    /// let mut resp: Value = bt_bencode::from_slice(b"d1:t2:aa1:y1:re")?;
+    /// if let Some(r) = resp.entry("r").and_then(Entry::or_insert_dict) {
+    ///     r.insert("id".into(), Value::from("abcdefghij0123456789"));
+    /// }
+    /// assert_eq!(
+    ///     bt_bencode::to_vec(&resp)?,
+    ///     &b"d1:rd2:id20:abcdefghij0123456789e1:t2:aa1:y1:re"[..]
+    /// );
+    ///
+    /// let mut resp: Value = bt_bencode::from_slice(b"d1:ri1ee")?;
+    /// assert!(resp.entry("r").and_then(Entry::or_insert_dict).is_none());
+    /// assert!(Value::from("r").entry("r").is_none());
+    /// # Ok::<(), bt_bencode::Error>(())
+    /// ```
+    pub fn entry<K>(&mut self, key: K) -> Option<Entry<'_>>
+    where
+        K: Into<Vec<u8>>,
+    {
+        match self {
+            Value::Dict(d) => Some(Entry(d.entry(ByteStrKey::from(key.into())))),
+            _ => None,
+        }
+    }
+
+    /// Inserts an entry in a dictionary and returns the previous value for
+    /// the key.
+    ///
+    /// # Errors
+    ///
+    /// If the value is not a dictionary, the inserted value is returned as
+    /// the error.
+    pub fn insert<K, V>(&mut self, key: K, value: V) -> Result<Option<Value>, Value>
+    where
+        K: Into<Vec<u8>>,
+        V: Into<Value>,
+    {
+        match self {
+            Value::Dict(d) => Ok(d.insert(ByteStrKey::from(key.into()), value.into())),
+            _ => Err(value.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Result;
+
+    #[test]
+    fn test_entry() -> Result<()> {
+        let mut value = Value::Dict(BTreeMap::new());
+        assert_eq!(value.entry("a").unwrap().key(), b"a");
+        *value.entry("a").unwrap().or_insert(1_u64) = Value::from(2_u64);
+        value.entry("a").unwrap().or_insert_with(|| 3_u64);
+        value
+            .entry("b")
+            .unwrap()
+            .or_insert_list()
+            .unwrap()
+            .push(Value::from("x"));
+        value
+            .entry("c")
+            .unwrap()
+            .and_modify(|_| unreachable!())
+            .or_insert_with(|| "y");
+        assert!(value
+            .entry("c")
+            .unwrap()
+            .and_modify(|c| *c = Value::from("z"))
+            .or_insert_dict()
+            .is_none());
+        assert!(value.entry("c").unwrap().or_insert_list().is_none());
+        assert_eq!(value.insert("d", 4_u64), Ok(None));
+        assert_eq!(value.insert("d", 5_u64), Ok(Some(Value::from(4_u64))));
+        assert_eq!(crate::to_vec(&value)?, b"d1:ai2e1:bl1:xe1:c1:z1:di5ee");
+        Ok(())
+    }
+
+    #[test]
+    fn test_entry_not_dict() {
+        let mut value = Value::from(1_u64);
+        assert!(value.entry("a").is_none());
+        assert_eq!(value.insert("a", 2_u64), Err(Value::from(2_u64)));
+        assert_eq!(value, Value::from(1_u64));
+    }
+}
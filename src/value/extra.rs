@@ -0,0 +1,201 @@
+//! Collects dictionary entries which are not fields of a struct.
+
+use super::Value;
+use crate::key::ByteStrKey;
+use core::{
+    fmt,
+    ops::{Deref, DerefMut},
+};
+use serde::{
+    de::{MapAccess, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{collections::BTreeMap, vec::Vec};
+#[cfg(feature = "std")]
+use std::{collections::BTreeMap, vec::Vec};
+
+/// The dictionary entries which are not fields of a struct.
+///
+/// With `#[serde(flatten)]`, every entry which is not consumed by another
+/// field of the struct is collected, so extension keys which are not
+/// understood are kept and written back when the struct is serialized.
+///
+/// ```rust
+/// use bt_bencode::value::Extra;
+/// use serde_derive::{Deserialize, Serialize};
+///
+/// #[derive(Deserialize, Serialize)]
+/// struct Info {
+///     name: String,
+///     #[serde(flatten)]
+///     extra: Extra,
+/// }
+///
+/// let input = b"d4:name4:spam6:sourcei3e1:xlee";
+/// let info: Info = bt_bencode::from_slice(input)?;
+///
+/// assert_eq!(info.name, "spam");
+/// assert_eq!(info.extra.len(), 2);
+/// assert_eq!(info.extra.get(b"source"), Some(&bt_bencode::Value::from(3u64)));
+/// assert_eq!(bt_bencode::to_vec(&info)?, &input[..]);
+/// # Ok::<(), bt_bencode::Error>(())
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Extra(pub BTreeMap<ByteStrKey, Value>);
+
+impl Extra {
+    /// Constructs an empty set of entries.
+    #[must_use]
+    pub fn new() -> Self {
+        Extra(BTreeMap::new())
+    }
+
+    /// Returns the value for a key.
+    #[must_use]
+    pub fn get(&self, key: &[u8]) -> Option<&Value> {
+        self.0.get(key)
+    }
+
+    /// Returns a mutable reference to the value for a key.
+    pub fn get_mut(&mut self, key: &[u8]) -> Option<&mut Value> {
+        self.0.get_mut(key)
+    }
+
+    /// Inserts an entry and returns the previous value for the key.
+    pub fn insert<K, V>(&mut self, key: K, value: V) -> Option<Value>
+    where
+        K: Into<Vec<u8>>,
+        V: Into<Value>,
+    {
+        self.0.insert(ByteStrKey::from(key.into()), value.into())
+    }
+
+    /// Removes an entry and returns the value.
+    pub fn remove(&mut self, key: &[u8]) -> Option<Value> {
+        self.0.remove(key)
+    }
+
+    /// Returns the entries.
+    #[must_use]
+    pub fn into_inner(self) -> BTreeMap<ByteStrKey, Value> {
+        self.0
+    }
+}
+
+impl Deref for Extra {
+    type Target = BTreeMap<ByteStrKey, Value>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for Extra {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl From<BTreeMap<ByteStrKey, Value>> for Extra {
+    fn from(entries: BTreeMap<ByteStrKey, Value>) -> Self {
+        Extra(entries)
+    }
+}
+
+impl From<Extra> for Value {
+    fn from(extra: Extra) -> Self {
+        Value::Dict(extra.0)
+    }
+}
+
+impl Serialize for Extra {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Extra {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct ExtraVisitor;
+
+        impl<'de> Visitor<'de> for ExtraVisitor {
+            type Value = Extra;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a dictionary")
+            }
+
+            fn visit_map<V>(self, mut visitor: V) -> Result<Extra, V::Error>
+            where
+                V: MapAccess<'de>,
+            {
+                let mut entries = BTreeMap::new();
+                while let Some((key, value)) = visitor.next_entry()? {
+                    entries.insert(key, value);
+                }
+                Ok(Extra(entries))
+            }
+        }
+
+        deserializer.deserialize_map(ExtraVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Result;
+    use serde_derive::{Deserialize, Serialize};
+
+    #[cfg(all(feature = "alloc", not(feature = "std")))]
+    use alloc::string::String;
+    #[cfg(feature = "std")]
+    use std::string::String;
+
+    #[derive(Debug, Deserialize, Serialize)]
+    struct File {
+        length: u64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        path: Option<String>,
+        #[serde(flatten)]
+        extra: Extra,
+    }
+
+    #[test]
+    fn test_flatten() -> Result<()> {
+        let input = &b"d6:lengthi5e3:md532:0123456789abcdef0123456789abcdef1:zd1:ai-1eee"[..];
+        let file: File = crate::from_slice(input)?;
+        assert_eq!(file.length, 5);
+        assert_eq!(file.path, None);
+        assert_eq!(
+            file.extra.keys().map(|k| &k[..]).collect::<Vec<_>>(),
+            [&b"md5"[..], b"z"]
+        );
+        assert_eq!(
+            file.extra.get(b"z").and_then(|z| z.get("a")),
+            Some(&Value::from(-1))
+        );
+        assert_eq!(crate::to_vec(&file)?, input);
+        Ok(())
+    }
+
+    #[test]
+    fn test_edit() -> Result<()> {
+        let mut extra = Extra::new();
+        extra.insert("b", 1u64);
+        extra.insert("a", "x");
+        assert_eq!(crate::to_vec(&extra)?, b"d1:a1:x1:bi1ee");
+        assert_eq!(extra.remove(b"b"), Some(Value::from(1u64)));
+        assert!(extra.get(b"b").is_none());
+        assert_eq!(Value::from(extra), crate::from_slice::<Value>(b"d1:a1:xe")?);
+        Ok(())
+    }
+}
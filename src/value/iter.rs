@@ -0,0 +1,246 @@
+//! Iterates over the values nested in a [Value].
+
+use super::Value;
+use crate::{
+    de::{PathSegment, PathSegmentBuf},
+    key::ByteStrKey,
+};
+use core::{iter, slice};
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{collections::btree_map, vec};
+#[cfg(feature = "std")]
+use std::{collections::btree_map, vec};
+
+impl Value {
+    /// Returns an iterator over the elements of a list.
+    ///
+    /// If the value is not a list, the iterator is empty.
+    pub fn iter_list(&self) -> slice::Iter<'_, Value> {
+        match self {
+            Value::List(l) => l.iter(),
+            _ => [].iter(),
+        }
+    }
+
+    /// Returns an iterator over the entries of a dictionary in key order.
+    ///
+    /// If the value is not a dictionary, the iterator is empty.
+    ///
+    /// ```rust
+    /// use bt_bencode::Value;
+    ///
+    /// let value: Value = bt_bencode::from_slice(b"d1:ai1e1:bi2ee")?;
+    /// let keys: Vec<&[u8]> = value.iter_dict().map(|(k, _)| k).collect();
+    /// assert_eq!(keys, [b"a", b"b"]);
+    /// assert_eq!(value.iter_list().count(), 0);
+    /// # Ok::<(), bt_bencode::Error>(())
+    /// ```
+    #[must_use]
+    pub fn iter_dict(&self) -> DictIter<'_> {
+        DictIter(match self {
+            Value::Dict(d) => Some(d.iter()),
+            _ => None,
+        })
+    }
+
+    /// Returns an iterator over the elements of a list or the entries of a
+    /// dictionary with their paths.
+    ///
+    /// If the value is not a list or dictionary, the iterator is empty.
+    ///
+    /// ```rust
+    /// use bt_bencode::{PathSegment, Value};
+    ///
+    /// let value: Value = bt_bencode::from_slice(b"l1:a1:be")?;
+    /// for (segment, value) in &value {
+    ///     match segment {
+    ///         PathSegment::Index(0) => assert_eq!(value, "a"),
+    ///         PathSegment::Index(1) => assert_eq!(value, "b"),
+    ///         _ => panic!(),
+    ///     }
+    /// }
+    /// # Ok::<(), bt_bencode::Error>(())
+    /// ```
+    #[must_use]
+    pub fn iter(&self) -> Iter<'_> {
+        Iter(match self {
+            Value::List(l) => IterKind::List(l.iter().enumerate()),
+            Value::Dict(d) => IterKind::Dict(d.iter()),
+            Value::ByteStr(_) | Value::Int(_) => IterKind::Empty,
+        })
+    }
+}
+
+/// An iterator over the entries of a dictionary [Value].
+///
+/// Returned by [`Value::iter_dict()`].
+#[derive(Clone, Debug)]
+pub struct DictIter<'a>(Option<btree_map::Iter<'a, ByteStrKey, Value>>);
+
+impl<'a> Iterator for DictIter<'a> {
+    type Item = (&'a [u8], &'a Value);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0
+            .as_mut()
+            .and_then(Iterator::next)
+            .map(|(k, v)| (k.as_slice(), v))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.as_ref().map_or((0, Some(0)), Iterator::size_hint)
+    }
+}
+
+/// An iterator over the nested values of a [Value] with their paths.
+///
+/// Returned by [`Value::iter()`].
+#[derive(Clone, Debug)]
+pub struct Iter<'a>(IterKind<'a>);
+
+#[derive(Clone, Debug)]
+enum IterKind<'a> {
+    List(iter::Enumerate<slice::Iter<'a, Value>>),
+    Dict(btree_map::Iter<'a, ByteStrKey, Value>),
+    Empty,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = (PathSegment<'a>, &'a Value);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.0 {
+            IterKind::List(l) => l.next().map(|(i, v)| (PathSegment::Index(i), v)),
+            IterKind::Dict(d) => d.next().map(|(k, v)| (PathSegment::Key(k.as_slice()), v)),
+            IterKind::Empty => None,
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.0 {
+            IterKind::List(l) => l.size_hint(),
+            IterKind::Dict(d) => d.size_hint(),
+            IterKind::Empty => (0, Some(0)),
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a Value {
+    type Item = (PathSegment<'a>, &'a Value);
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An owning iterator over the nested values of a [Value] with their paths.
+#[derive(Debug)]
+pub struct IntoIter(IntoIterKind);
+
+#[derive(Debug)]
+enum IntoIterKind {
+    List(iter::Enumerate<vec::IntoIter<Value>>),
+    Dict(btree_map::IntoIter<ByteStrKey, Value>),
+    Empty,
+}
+
+impl Iterator for IntoIter {
+    type Item = (PathSegmentBuf, Value);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.0 {
+            IntoIterKind::List(l) => l.next().map(|(i, v)| (PathSegmentBuf::Index(i), v)),
+            IntoIterKind::Dict(d) => d
+                .next()
+                .map(|(k, v)| (PathSegmentBuf::Key(k.into_vec()), v)),
+            IntoIterKind::Empty => None,
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.0 {
+            IntoIterKind::List(l) => l.size_hint(),
+            IntoIterKind::Dict(d) => d.size_hint(),
+            IntoIterKind::Empty => (0, Some(0)),
+        }
+    }
+}
+
+impl IntoIterator for Value {
+    type Item = (PathSegmentBuf, Value);
+    type IntoIter = IntoIter;
+
+    /// Returns an iterator over the elements of a list or the entries of a
+    /// dictionary with their paths.
+    ///
+    /// If the value is not a list or dictionary, the iterator is empty.
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(match self {
+            Value::List(l) => IntoIterKind::List(l.into_iter().enumerate()),
+            Value::Dict(d) => IntoIterKind::Dict(d.into_iter()),
+            Value::ByteStr(_) | Value::Int(_) => IntoIterKind::Empty,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Result;
+
+    #[cfg(all(feature = "alloc", not(feature = "std")))]
+    use alloc::vec::Vec;
+    #[cfg(feature = "std")]
+    use std::vec::Vec;
+
+    #[test]
+    fn test_iter() -> Result<()> {
+        let list: Value = crate::from_slice(b"li1ei2ee")?;
+        let dict: Value = crate::from_slice(b"d1:ai1e1:bi2ee")?;
+        let int = Value::from(1_u64);
+
+        assert_eq!(list.iter_list().count(), 2);
+        assert_eq!(dict.iter_list().count(), 0);
+        assert_eq!(dict.iter_dict().count(), 2);
+        assert_eq!(list.iter_dict().size_hint(), (0, Some(0)));
+
+        let items: Vec<_> = list.iter().collect();
+        assert_eq!(
+            items,
+            [
+                (PathSegment::Index(0), &Value::from(1_u64)),
+                (PathSegment::Index(1), &Value::from(2_u64)),
+            ]
+        );
+        let items: Vec<_> = (&dict).into_iter().collect();
+        assert_eq!(
+            items,
+            [
+                (PathSegment::Key(b"a"), &Value::from(1_u64)),
+                (PathSegment::Key(b"b"), &Value::from(2_u64)),
+            ]
+        );
+        assert_eq!(int.iter().count(), 0);
+
+        let items: Vec<_> = dict.into_iter().collect();
+        assert_eq!(
+            items,
+            [
+                (PathSegmentBuf::Key(b"a".to_vec()), Value::from(1_u64)),
+                (PathSegmentBuf::Key(b"b".to_vec()), Value::from(2_u64)),
+            ]
+        );
+        let items: Vec<_> = list.into_iter().collect();
+        assert_eq!(
+            items,
+            [
+                (PathSegmentBuf::Index(0), Value::from(1_u64)),
+                (PathSegmentBuf::Index(1), Value::from(2_u64)),
+            ]
+        );
+        assert_eq!(int.into_iter().size_hint(), (0, Some(0)));
+        Ok(())
+    }
+}
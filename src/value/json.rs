@@ -0,0 +1,199 @@
+//! Conversions between [`Value`] and [`serde_json::Value`].
+
+use super::{Number, SmallBytes, Value};
+use crate::{error::Error, key::ByteStrKey};
+use core::convert::TryFrom;
+use serde_json::{Map, Value as JsonValue};
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{format, string::String, vec::Vec};
+#[cfg(feature = "std")]
+use std::{format, string::String, vec::Vec};
+
+const BASE64_CHARS: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// How byte strings which are not valid UTF-8 are converted to JSON strings.
+///
+/// Byte strings which are valid UTF-8 are always converted to the same JSON
+/// string. Dictionary keys are converted in the same way as values.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum NonUtf8Policy {
+    /// Invalid UTF-8 sequences are replaced with `U+FFFD`.
+    #[default]
+    Lossy,
+    /// The bytes are formatted as lowercase hex.
+    Hex,
+    /// The bytes are encoded as standard base64 with padding.
+    Base64,
+}
+
+fn encode_base64(bytes: &[u8]) -> String {
+    let mut s = String::with_capacity((bytes.len() + 2) / 3 * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, b)| n | u32::from(*b) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                s.push(char::from(
+                    BASE64_CHARS[(n >> (18 - 6 * i) & 0x3f) as usize],
+                ));
+            } else {
+                s.push('=');
+            }
+        }
+    }
+    s
+}
+
+impl NonUtf8Policy {
+    /// Converts a byte string to a string.
+    #[must_use]
+    pub fn to_string(self, bytes: &[u8]) -> String {
+        match core::str::from_utf8(bytes) {
+            Ok(s) => String::from(s),
+            Err(_) => match self {
+                NonUtf8Policy::Lossy => String::from_utf8_lossy(bytes).into_owned(),
+                NonUtf8Policy::Hex => crate::serde_helpers::hex::encode(bytes),
+                NonUtf8Policy::Base64 => encode_base64(bytes),
+            },
+        }
+    }
+}
+
+impl Value {
+    /// Converts the value to a JSON value.
+    ///
+    /// Byte strings become JSON strings with `policy` deciding how bytes which
+    /// are not valid UTF-8 are converted. Integers become JSON numbers (or
+    /// strings of decimal digits for integers outside of the 64 bit ranges),
+    /// lists become arrays, and dictionaries become objects.
+    ///
+    /// ```rust
+    /// use bt_bencode::{value::NonUtf8Policy, Value};
+    ///
+    /// let value: Value = bt_bencode::from_slice(b"d4:hash2:\xab\x014:name4:spame")?;
+    ///
+    /// assert_eq!(
+    ///     value.to_json(NonUtf8Policy::Hex),
+    ///     serde_json::json!({"hash": "ab01", "name": "spam"})
+    /// );
+    /// assert_eq!(
+    ///     value.to_json(NonUtf8Policy::Base64),
+    ///     serde_json::json!({"hash": "qwE=", "name": "spam"})
+    /// );
+    /// # Ok::<(), bt_bencode::Error>(())
+    /// ```
+    #[must_use]
+    pub fn to_json(&self, policy: NonUtf8Policy) -> JsonValue {
+        match self {
+            Value::ByteStr(b) => JsonValue::String(policy.to_string(b)),
+            Value::Int(Number::Signed(n)) => JsonValue::from(*n),
+            Value::Int(Number::Unsigned(n)) => JsonValue::from(*n),
+            Value::Int(n) => JsonValue::String(String::from(n.format(&mut itoa::Buffer::new()))),
+            Value::List(l) => JsonValue::Array(l.iter().map(|v| v.to_json(policy)).collect()),
+            Value::Dict(d) => JsonValue::Object(
+                d.iter()
+                    .map(|(k, v)| (policy.to_string(k), v.to_json(policy)))
+                    .collect::<Map<String, JsonValue>>(),
+            ),
+        }
+    }
+}
+
+impl<'a> From<&'a Value> for JsonValue {
+    /// Converts the value with the [`Lossy`][NonUtf8Policy::Lossy] policy.
+    fn from(value: &'a Value) -> Self {
+        value.to_json(NonUtf8Policy::Lossy)
+    }
+}
+
+impl From<Value> for JsonValue {
+    /// Converts the value with the [`Lossy`][NonUtf8Policy::Lossy] policy.
+    fn from(value: Value) -> Self {
+        value.to_json(NonUtf8Policy::Lossy)
+    }
+}
+
+impl TryFrom<JsonValue> for Value {
+    type Error = Error;
+
+    /// Converts a JSON value.
+    ///
+    /// Strings become byte strings, integers become integers, arrays become
+    /// lists, and objects become dictionaries. `null`, booleans, and
+    /// non-integer numbers cannot be represented and are errors.
+    fn try_from(value: JsonValue) -> Result<Self, Error> {
+        match value {
+            JsonValue::String(s) => Ok(Value::ByteStr(SmallBytes::from(s))),
+            JsonValue::Number(n) => {
+                if let Some(n) = n.as_u64() {
+                    Ok(Value::Int(Number::Unsigned(n)))
+                } else if let Some(n) = n.as_i64() {
+                    Ok(Value::Int(Number::Signed(n)))
+                } else {
+                    Err(Error::Deserialize(format!(
+                        "JSON number {} is not an integer",
+                        n
+                    )))
+                }
+            }
+            JsonValue::Array(a) => a
+                .into_iter()
+                .map(Value::try_from)
+                .collect::<Result<Vec<_>, _>>()
+                .map(Value::List),
+            JsonValue::Object(o) => o
+                .into_iter()
+                .map(|(k, v)| Ok((ByteStrKey::from(k.into_bytes()), Value::try_from(v)?)))
+                .collect::<Result<_, Error>>()
+                .map(Value::Dict),
+            JsonValue::Null => Err(Error::UnsupportedType),
+            JsonValue::Bool(_) => Err(Error::UnsupportedType),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Result;
+    use serde_json::json;
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(encode_base64(b""), "");
+        assert_eq!(encode_base64(b"f"), "Zg==");
+        assert_eq!(encode_base64(b"fo"), "Zm8=");
+        assert_eq!(encode_base64(b"foo"), "Zm9v");
+        assert_eq!(encode_base64(b"foobar"), "Zm9vYmFy");
+        assert_eq!(encode_base64(&[0xff, 0xfe, 0xfd, 0xfc]), "//79/A==");
+    }
+
+    #[test]
+    fn test_to_json() -> Result<()> {
+        let value: Value = crate::from_slice(b"d1:ai-1e1:bli1e1:\xffe2:\xff\xfe1:ce")?;
+        assert_eq!(
+            JsonValue::from(&value),
+            json!({"a": -1, "b": [1, "\u{fffd}"], "\u{fffd}\u{fffd}": "c"})
+        );
+        assert_eq!(
+            value.to_json(NonUtf8Policy::Hex),
+            json!({"a": -1, "b": [1, "ff"], "fffe": "c"})
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_json() -> Result<()> {
+        let value = Value::try_from(json!({"b": [1, -2, "x"], "a": {}}))?;
+        assert_eq!(crate::to_vec(&value)?, b"d1:ade1:bli1ei-2e1:xee");
+        assert_eq!(JsonValue::from(value), json!({"b": [1, -2, "x"], "a": {}}));
+
+        assert!(Value::try_from(json!(null)).is_err());
+        assert!(Value::try_from(json!([true])).is_err());
+        assert!(Value::try_from(json!({"a": 1.5})).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,174 @@
+//! A dictionary view which does not parse values until they are requested.
+
+use super::RawValue;
+use crate::error::{Error, Result};
+use serde::{de, Deserialize, Deserializer};
+use serde_bytes::Bytes;
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::vec::Vec;
+
+/// Indexes the keys of an encoded dictionary to the raw encoding of their values.
+///
+/// Only the structure of the values is checked when the view is created. The
+/// values are not decoded until they are requested, which is useful when only
+/// a few keys need to be inspected (e.g. the `y` and `q` keys of a KRPC
+/// message before it is forwarded).
+///
+/// ```rust
+/// use bt_bencode::value::LazyDict;
+///
+/// let msg = b"d1:ad2:id20:abcdefghij0123456789e1:q4:ping1:t2:aa1:y1:qe";
+/// let dict = LazyDict::new(msg)?;
+///
+/// assert_eq!(dict.get(b"y"), Some(&b"1:q"[..]));
+/// assert_eq!(dict.get_as::<&str>(b"q")?, Some("ping"));
+/// assert_eq!(dict.get_as::<&str>(b"r")?, None);
+/// # Ok::<(), bt_bencode::Error>(())
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LazyDict<'a> {
+    raw: &'a [u8],
+    entries: Vec<(&'a [u8], &'a [u8])>,
+}
+
+impl<'a> LazyDict<'a> {
+    /// Indexes the entries of an encoded dictionary.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the data is not a single valid dictionary.
+    pub fn new(s: &'a [u8]) -> Result<Self> {
+        let raw = RawValue::from_slice(s)?.as_bytes();
+        if raw.first() != Some(&b'd') {
+            return Err(Error::InvalidDict);
+        }
+
+        let mut entries = Vec::new();
+        let mut values = crate::raw_values(&raw[1..raw.len() - 1]);
+        while let Some(raw_key) = values.next() {
+            let key: &Bytes = crate::from_slice(raw_key?.1)?;
+            match values.next() {
+                Some(value) => entries.push((&**key, value?.1)),
+                None => return Err(Error::KeyWithoutValue),
+            }
+        }
+        Ok(LazyDict { raw, entries })
+    }
+
+    /// Returns the raw encoding of the whole dictionary.
+    #[must_use]
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.raw
+    }
+
+    /// Returns the number of entries.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if there are no entries.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns true if the dictionary has an entry for the key.
+    #[must_use]
+    pub fn contains_key(&self, key: &[u8]) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Returns the raw encoding of the value for the key.
+    ///
+    /// If the key is repeated, the last value is returned.
+    #[must_use]
+    pub fn get(&self, key: &[u8]) -> Option<&'a [u8]> {
+        self.entries
+            .iter()
+            .rev()
+            .find(|(k, _)| *k == key)
+            .map(|(_, v)| *v)
+    }
+
+    /// Decodes the value for the key.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the value cannot be deserialized into an instance of `T`.
+    pub fn get_as<T>(&self, key: &[u8]) -> Result<Option<T>>
+    where
+        T: Deserialize<'a>,
+    {
+        self.get(key).map(crate::from_slice).transpose()
+    }
+
+    /// Returns the keys in the order they are encoded.
+    pub fn keys(&self) -> impl Iterator<Item = &'a [u8]> + '_ {
+        self.entries.iter().map(|(k, _)| *k)
+    }
+
+    /// Returns the keys and raw values in the order they are encoded.
+    pub fn iter(&self) -> impl Iterator<Item = (&'a [u8], &'a [u8])> + '_ {
+        self.entries.iter().copied()
+    }
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for LazyDict<'a> {
+    /// Borrows the raw encoding of the dictionary from the input.
+    ///
+    /// See [`RawValue`].
+    fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = RawValue::deserialize(deserializer)?;
+        LazyDict::new(raw.as_bytes()).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lazy_dict() -> Result<()> {
+        let input = &b"d1:bli1e0:e1:a3:xyz1:bi2ee"[..];
+        let dict = LazyDict::new(input)?;
+        assert_eq!(dict.len(), 3);
+        assert_eq!(dict.as_bytes(), input);
+        assert_eq!(dict.keys().collect::<Vec<_>>(), [&b"b"[..], b"a", b"b"]);
+        assert_eq!(dict.get(b"b"), Some(&b"i2e"[..]));
+        assert_eq!(dict.get_as::<u8>(b"b")?, Some(2));
+        assert!(dict.get_as::<u8>(b"a").is_err());
+        assert!(!dict.contains_key(b"c"));
+        assert_eq!(dict.iter().next(), Some((&b"b"[..], &b"li1e0:e"[..])));
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(LazyDict::new(b"").is_err());
+        assert!(LazyDict::new(b"le").is_err());
+        assert!(LazyDict::new(b"d1:a").is_err());
+        assert!(LazyDict::new(b"di1ei1ee").is_err());
+        assert!(LazyDict::new(b"de0:").is_err());
+        assert!(LazyDict::new(b"de").map(|d| d.is_empty()).unwrap_or(false));
+    }
+
+    #[test]
+    fn test_deserialize() -> Result<()> {
+        #[derive(serde_derive::Deserialize)]
+        struct Packet<'a> {
+            #[serde(borrow)]
+            a: LazyDict<'a>,
+        }
+
+        let packet: Packet<'_> = crate::from_slice(b"d1:ad2:id1:xee")?;
+        assert_eq!(packet.a.get_as::<&str>(b"id")?, Some("x"));
+        Ok(())
+    }
+}
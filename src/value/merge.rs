@@ -0,0 +1,99 @@
+//! Merges one [Value] into another.
+
+use super::Value;
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::collections::btree_map;
+#[cfg(feature = "std")]
+use std::collections::btree_map;
+
+/// How [`Value::merge()`] combines two lists.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum MergeStrategy {
+    /// The merged list replaces the existing list.
+    #[default]
+    Replace,
+    /// The merged list's elements are appended to the existing list.
+    Append,
+}
+
+impl Value {
+    /// Merges another value into the value.
+    ///
+    /// If both values are dictionaries, each entry in `other` is merged into
+    /// the entry with the same key, or inserted if there is no entry with the
+    /// key. If both values are lists, the strategy decides how they are
+    /// combined. Otherwise, `other` replaces the value.
+    ///
+    /// ```rust
+    /// use bt_bencode::value::MergeStrategy;
+    /// use bt_bencode::Value;
+    ///
+    /// let mut settings: Value = bt_bencode::from_slice(b"d8:intervali1800e8:trackersl1:aee")?;
+    /// let session: Value = bt_bencode::from_slice(b"d4:porti6881e8:trackersl1:bee")?;
+    ///
+    /// let mut replaced = settings.clone();
+    /// replaced.merge(session.clone(), MergeStrategy::Replace);
+    /// assert_eq!(
+    ///     bt_bencode::to_vec(&replaced)?,
+    ///     &b"d8:intervali1800e4:porti6881e8:trackersl1:bee"[..]
+    /// );
+    ///
+    /// settings.merge(session, MergeStrategy::Append);
+    /// assert_eq!(
+    ///     bt_bencode::to_vec(&settings)?,
+    ///     &b"d8:intervali1800e4:porti6881e8:trackersl1:a1:bee"[..]
+    /// );
+    /// # Ok::<(), bt_bencode::Error>(())
+    /// ```
+    pub fn merge(&mut self, other: Value, strategy: MergeStrategy) {
+        match (self, other) {
+            (Value::Dict(dict), Value::Dict(other)) => {
+                for (key, value) in other {
+                    match dict.entry(key) {
+                        btree_map::Entry::Occupied(mut entry) => {
+                            entry.get_mut().merge(value, strategy);
+                        }
+                        btree_map::Entry::Vacant(entry) => {
+                            entry.insert(value);
+                        }
+                    }
+                }
+            }
+            (Value::List(list), Value::List(mut other)) if strategy == MergeStrategy::Append => {
+                list.append(&mut other);
+            }
+            (this, other) => *this = other,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Result;
+
+    #[test]
+    fn test_merge() -> Result<()> {
+        let mut value: Value = crate::from_slice(b"d1:ad1:bi1e1:cli1eee1:di1e1:eli1eee")?;
+        let other: Value = crate::from_slice(b"d1:ad1:cli2ee1:fi3ee1:d2:xy1:eli2eee")?;
+
+        let mut replaced = value.clone();
+        replaced.merge(other.clone(), MergeStrategy::default());
+        assert_eq!(
+            crate::to_vec(&replaced)?,
+            &b"d1:ad1:bi1e1:cli2ee1:fi3ee1:d2:xy1:eli2eee"[..]
+        );
+
+        value.merge(other, MergeStrategy::Append);
+        assert_eq!(
+            crate::to_vec(&value)?,
+            &b"d1:ad1:bi1e1:cli1ei2ee1:fi3ee1:d2:xy1:eli1ei2eee"[..]
+        );
+
+        let mut value = Value::from(1_u64);
+        value.merge(Value::from("x"), MergeStrategy::Append);
+        assert_eq!(value, "x");
+        Ok(())
+    }
+}
@@ -0,0 +1,296 @@
+//! A [Value] variant which keeps dictionary entries in their original order.
+
+use super::{Number, SmallBytes, Value};
+use crate::{key::ByteStrKey, write::Write};
+use core::fmt;
+use indexmap::IndexMap;
+use serde::{
+    de::{MapAccess, SeqAccess, Visitor},
+    Deserialize, Serialize,
+};
+use std::{string::String, vec::Vec};
+
+/// Represents a Bencode value where dictionaries keep their entries in
+/// insertion order instead of sorted order.
+///
+/// When deserialized, dictionary entries are kept in the order they were
+/// encoded, so unsorted dictionaries sent by misbehaving peers can be
+/// inspected and written back byte for byte with [`OrderedValue::to_vec()`].
+/// If a key is repeated, the last value is kept at the position of the first
+/// occurrence.
+///
+/// Serializing with [Serialize] (e.g. with [`crate::to_vec()`]) sorts the
+/// dictionary keys as Bencode requires.
+///
+/// ```rust
+/// use bt_bencode::value::OrderedValue;
+///
+/// let input = b"d1:bi1e1:ai2ee";
+/// let value: OrderedValue = bt_bencode::from_slice(input)?;
+///
+/// assert!(!value.is_sorted());
+/// assert_eq!(value.to_vec(), input);
+/// assert_eq!(bt_bencode::to_vec(&value)?, b"d1:ai2e1:bi1ee");
+/// # Ok::<(), bt_bencode::Error>(())
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OrderedValue {
+    /// A byte string.
+    ByteStr(SmallBytes),
+    /// An integer.
+    Int(Number),
+    /// A list of values.
+    List(Vec<OrderedValue>),
+    /// A dictionary of values in insertion order.
+    Dict(IndexMap<ByteStrKey, OrderedValue>),
+}
+
+impl OrderedValue {
+    /// If the value is a dictionary, returns the entries.
+    #[must_use]
+    pub fn as_dict(&self) -> Option<&IndexMap<ByteStrKey, OrderedValue>> {
+        match self {
+            OrderedValue::Dict(d) => Some(d),
+            _ => None,
+        }
+    }
+
+    /// If the value is a dictionary, returns a mutable reference to the entries.
+    pub fn as_dict_mut(&mut self) -> Option<&mut IndexMap<ByteStrKey, OrderedValue>> {
+        match self {
+            OrderedValue::Dict(d) => Some(d),
+            _ => None,
+        }
+    }
+
+    /// Returns the value for a key if the value is a dictionary.
+    #[must_use]
+    pub fn get(&self, key: &[u8]) -> Option<&OrderedValue> {
+        self.as_dict().and_then(|d| d.get(key))
+    }
+
+    /// Returns true if the keys of every dictionary, including nested ones,
+    /// are in sorted order.
+    #[must_use]
+    pub fn is_sorted(&self) -> bool {
+        match self {
+            OrderedValue::ByteStr(_) | OrderedValue::Int(_) => true,
+            OrderedValue::List(l) => l.iter().all(OrderedValue::is_sorted),
+            OrderedValue::Dict(d) => {
+                d.keys().zip(d.keys().skip(1)).all(|(a, b)| a < b)
+                    && d.values().all(OrderedValue::is_sorted)
+            }
+        }
+    }
+
+    /// Encodes the value with dictionary entries in their stored order.
+    #[must_use]
+    pub fn to_vec(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        self.write_ordered(&mut buf);
+        buf
+    }
+
+    fn write_ordered(&self, buf: &mut Vec<u8>) {
+        match self {
+            OrderedValue::ByteStr(b) => write_byte_str(buf, b),
+            OrderedValue::Int(n) => {
+                let mut num = itoa::Buffer::new();
+                buf.extend_from_slice(b"i");
+                buf.extend_from_slice(n.format(&mut num).as_bytes());
+                buf.extend_from_slice(b"e");
+            }
+            OrderedValue::List(l) => {
+                buf.extend_from_slice(b"l");
+                for v in l {
+                    v.write_ordered(buf);
+                }
+                buf.extend_from_slice(b"e");
+            }
+            OrderedValue::Dict(d) => {
+                buf.extend_from_slice(b"d");
+                for (k, v) in d {
+                    write_byte_str(buf, k);
+                    v.write_ordered(buf);
+                }
+                buf.extend_from_slice(b"e");
+            }
+        }
+    }
+}
+
+fn write_byte_str(buf: &mut Vec<u8>, bytes: &[u8]) {
+    let mut len = itoa::Buffer::new();
+    // Writing to a `Vec` cannot fail.
+    let _ = buf.write_all_vectored(&[len.format(bytes.len()).as_bytes(), b":", bytes]);
+}
+
+impl From<Value> for OrderedValue {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::ByteStr(b) => OrderedValue::ByteStr(b),
+            Value::Int(n) => OrderedValue::Int(n),
+            Value::List(l) => OrderedValue::List(l.into_iter().map(OrderedValue::from).collect()),
+            Value::Dict(d) => OrderedValue::Dict(
+                d.into_iter()
+                    .map(|(k, v)| (k, OrderedValue::from(v)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl From<OrderedValue> for Value {
+    /// Converts the value, sorting dictionary keys.
+    fn from(value: OrderedValue) -> Self {
+        match value {
+            OrderedValue::ByteStr(b) => Value::ByteStr(b),
+            OrderedValue::Int(n) => Value::Int(n),
+            OrderedValue::List(l) => Value::List(l.into_iter().map(Value::from).collect()),
+            OrderedValue::Dict(d) => Value::Dict(
+                d.into_iter()
+                    .map(|(k, v)| (ByteStrKey::from(k.0), Value::from(v)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl Serialize for OrderedValue {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            OrderedValue::ByteStr(b) => b.serialize(serializer),
+            OrderedValue::Int(n) => n.serialize(serializer),
+            OrderedValue::List(l) => l.serialize(serializer),
+            OrderedValue::Dict(d) => d.serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for OrderedValue {
+    fn deserialize<T>(deserializer: T) -> Result<OrderedValue, T::Error>
+    where
+        T: serde::Deserializer<'de>,
+    {
+        struct OrderedValueVisitor;
+
+        impl<'de> Visitor<'de> for OrderedValueVisitor {
+            type Value = OrderedValue;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("any valid Bencode value")
+            }
+
+            fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E> {
+                Ok(OrderedValue::Int(Number::Signed(value)))
+            }
+
+            fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E> {
+                Ok(OrderedValue::Int(Number::Unsigned(value)))
+            }
+
+            fn visit_i128<E>(self, value: i128) -> Result<Self::Value, E> {
+                Ok(OrderedValue::Int(Number::from(value)))
+            }
+
+            fn visit_u128<E>(self, value: u128) -> Result<Self::Value, E> {
+                Ok(OrderedValue::Int(Number::from(value)))
+            }
+
+            #[cfg(feature = "big-int")]
+            fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                super::deserialize_big_int(deserializer).map(OrderedValue::Int)
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E> {
+                Ok(OrderedValue::ByteStr(SmallBytes::from(value)))
+            }
+
+            fn visit_string<E>(self, value: String) -> Result<Self::Value, E> {
+                Ok(OrderedValue::ByteStr(SmallBytes::from(value)))
+            }
+
+            fn visit_bytes<E>(self, value: &[u8]) -> Result<Self::Value, E> {
+                Ok(OrderedValue::ByteStr(SmallBytes::from(value)))
+            }
+
+            fn visit_byte_buf<E>(self, value: Vec<u8>) -> Result<Self::Value, E> {
+                Ok(OrderedValue::ByteStr(SmallBytes::from(value)))
+            }
+
+            fn visit_seq<V>(self, mut visitor: V) -> Result<Self::Value, V::Error>
+            where
+                V: SeqAccess<'de>,
+            {
+                let mut list = Vec::with_capacity(visitor.size_hint().unwrap_or(0));
+                while let Some(elem) = visitor.next_element()? {
+                    list.push(elem);
+                }
+                Ok(OrderedValue::List(list))
+            }
+
+            fn visit_map<V>(self, mut visitor: V) -> Result<Self::Value, V::Error>
+            where
+                V: MapAccess<'de>,
+            {
+                let mut dict = IndexMap::with_capacity(visitor.size_hint().unwrap_or(0) / 2);
+                while let Some((key, value)) = visitor.next_entry::<ByteStrKey, _>()? {
+                    dict.insert(key, value);
+                }
+                Ok(OrderedValue::Dict(dict))
+            }
+        }
+
+        deserializer.deserialize_any(OrderedValueVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Result;
+
+    #[test]
+    fn test_round_trip() -> Result<()> {
+        let input = &b"d1:zli1ei-1ed1:y0:1:x0:ee1:a4:spame"[..];
+        let value: OrderedValue = crate::from_slice(input)?;
+        assert!(!value.is_sorted());
+        assert_eq!(value.to_vec(), input);
+        assert_eq!(
+            value.as_dict().unwrap().keys().collect::<Vec<_>>(),
+            [&ByteStrKey::from("z"), &ByteStrKey::from("a")]
+        );
+        assert_eq!(value.get(b"a"), Some(&OrderedValue::ByteStr("spam".into())));
+
+        let sorted = crate::to_vec(&value)?;
+        assert_eq!(sorted, &b"d1:a4:spam1:zli1ei-1ed1:x0:1:y0:eee"[..]);
+        let sorted: OrderedValue = crate::from_slice(&sorted)?;
+        assert!(sorted.is_sorted());
+        Ok(())
+    }
+
+    #[test]
+    fn test_duplicate_keys() -> Result<()> {
+        let value: OrderedValue = crate::from_slice(b"d1:bi1e1:ai2e1:bi3ee")?;
+        assert_eq!(value.to_vec(), b"d1:bi3e1:ai2ee");
+        Ok(())
+    }
+
+    #[test]
+    fn test_value_conversion() -> Result<()> {
+        let value: Value = crate::from_slice(b"d1:ad1:b0:e1:cl1:dee")?;
+        let ordered = OrderedValue::from(value.clone());
+        assert!(ordered.is_sorted());
+        assert_eq!(Value::from(ordered), value);
+
+        let ordered: OrderedValue = crate::from_slice(b"d1:c0:1:a0:e")?;
+        assert_eq!(crate::to_vec(&Value::from(ordered))?, b"d1:a0:1:c0:e");
+        Ok(())
+    }
+}
@@ -0,0 +1,95 @@
+//! Compares a [Value] with primitive types.
+
+use super::{Number, Value};
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{string::String, vec::Vec};
+#[cfg(feature = "std")]
+use std::{string::String, vec::Vec};
+
+fn eq_bytes(value: &Value, other: &[u8]) -> bool {
+    match value {
+        Value::ByteStr(b) => b.as_slice() == other,
+        _ => false,
+    }
+}
+
+fn eq_number(value: &Value, other: Number) -> bool {
+    match value {
+        Value::Int(n) => *n == other,
+        _ => false,
+    }
+}
+
+macro_rules! impl_partial_eq_bytes {
+    ($($t:ty => $as_bytes:ident),*) => {
+        $(
+            impl PartialEq<$t> for Value {
+                fn eq(&self, other: &$t) -> bool {
+                    eq_bytes(self, other.$as_bytes())
+                }
+            }
+
+            impl PartialEq<Value> for $t {
+                fn eq(&self, other: &Value) -> bool {
+                    eq_bytes(other, self.$as_bytes())
+                }
+            }
+        )*
+    };
+}
+
+impl_partial_eq_bytes! {
+    str => as_bytes,
+    &str => as_bytes,
+    String => as_bytes,
+    [u8] => as_ref,
+    &[u8] => as_ref,
+    Vec<u8> => as_slice
+}
+
+macro_rules! impl_partial_eq_number {
+    ($($t:ty),*) => {
+        $(
+            impl PartialEq<$t> for Value {
+                fn eq(&self, other: &$t) -> bool {
+                    eq_number(self, Number::from(*other))
+                }
+            }
+
+            impl PartialEq<Value> for $t {
+                fn eq(&self, other: &Value) -> bool {
+                    eq_number(other, Number::from(*self))
+                }
+            }
+        )*
+    };
+}
+
+impl_partial_eq_number!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Result;
+
+    #[test]
+    fn test_partial_eq() -> Result<()> {
+        let value: Value = crate::from_slice(b"d1:q4:ping1:ti3e1:ui-1ee")?;
+        assert_eq!(value["q"], "ping");
+        assert_eq!("ping", value["q"]);
+        assert_eq!(value["q"], String::from("ping"));
+        assert_eq!(value["q"], &b"ping"[..]);
+        assert_eq!(b"ping".to_vec(), value["q"]);
+        assert_ne!(value["q"], "pong");
+        assert_ne!(value["t"], "3");
+
+        assert_eq!(value["t"], 3_i64);
+        assert_eq!(value["t"], 3_u64);
+        assert_eq!(3_u8, value["t"]);
+        assert_eq!(value["u"], -1_i32);
+        assert_ne!(value["u"], 3_u64);
+        assert_ne!(value["q"], 3_u64);
+        Ok(())
+    }
+}
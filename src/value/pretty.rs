@@ -0,0 +1,202 @@
+//! Renders a [Value] as indented, human readable text.
+
+use super::{Number, Value};
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{string::String, vec::Vec};
+#[cfg(feature = "std")]
+use std::{string::String, vec::Vec};
+
+/// The number of bytes of a byte string which are rendered before the rest
+/// is truncated.
+pub(crate) const MAX_BYTE_STR_LEN: usize = 64;
+
+/// Writes a byte string in double quotes.
+///
+/// Printable ASCII characters are written as is and other bytes are written
+/// as hex escapes. If the byte string is longer than `max_len`, only the
+/// first `max_len` bytes are written, followed by the total length.
+pub(crate) fn write_byte_str<W>(w: &mut W, bytes: &[u8], max_len: usize) -> core::fmt::Result
+where
+    W: core::fmt::Write,
+{
+    let shown = if bytes.len() > max_len {
+        &bytes[..max_len]
+    } else {
+        bytes
+    };
+    w.write_char('"')?;
+    for &b in shown {
+        match b {
+            b'"' => w.write_str("\\\"")?,
+            b'\\' => w.write_str("\\\\")?,
+            b'\n' => w.write_str("\\n")?,
+            b'\r' => w.write_str("\\r")?,
+            b'\t' => w.write_str("\\t")?,
+            b' '..=b'~' => w.write_char(char::from(b))?,
+            _ => write!(w, "\\x{:02x}", b)?,
+        }
+    }
+    w.write_char('"')?;
+    if shown.len() < bytes.len() {
+        write!(w, "... ({} bytes)", bytes.len())?;
+    }
+    Ok(())
+}
+
+/// Builds the indented rendering of a value from its structural elements.
+#[derive(Debug, Default)]
+pub(crate) struct PrettyPrinter {
+    out: String,
+    /// For every open list or dictionary, whether it is a dictionary and
+    /// whether it has any entries.
+    stack: Vec<(bool, bool)>,
+    after_key: bool,
+}
+
+impl PrettyPrinter {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn into_string(self) -> String {
+        self.out
+    }
+
+    fn start_item(&mut self) {
+        if self.after_key {
+            self.after_key = false;
+            return;
+        }
+        if let Some((_, has_items)) = self.stack.last_mut() {
+            if *has_items {
+                self.out.push(',');
+            }
+            *has_items = true;
+            self.out.push('\n');
+            self.indent();
+        }
+    }
+
+    fn indent(&mut self) {
+        for _ in 0..self.stack.len() {
+            self.out.push_str("  ");
+        }
+    }
+
+    pub(crate) fn dict_start(&mut self) {
+        self.start_item();
+        self.out.push('{');
+        self.stack.push((true, false));
+    }
+
+    pub(crate) fn list_start(&mut self) {
+        self.start_item();
+        self.out.push('[');
+        self.stack.push((false, false));
+    }
+
+    pub(crate) fn key(&mut self, key: &[u8]) {
+        self.start_item();
+        let _ = write_byte_str(&mut self.out, key, MAX_BYTE_STR_LEN);
+        self.out.push_str(": ");
+        self.after_key = true;
+    }
+
+    pub(crate) fn byte_str(&mut self, value: &[u8]) {
+        self.start_item();
+        let _ = write_byte_str(&mut self.out, value, MAX_BYTE_STR_LEN);
+    }
+
+    pub(crate) fn int(&mut self, value: &Number) {
+        self.start_item();
+        let mut buf = itoa::Buffer::new();
+        self.out.push_str(value.format(&mut buf));
+    }
+
+    pub(crate) fn end(&mut self) {
+        if let Some((is_dict, has_items)) = self.stack.pop() {
+            if has_items {
+                self.out.push('\n');
+                self.indent();
+            }
+            self.out.push(if is_dict { '}' } else { ']' });
+        }
+    }
+
+    fn value(&mut self, value: &Value) {
+        match value {
+            Value::ByteStr(b) => self.byte_str(b),
+            Value::Int(n) => self.int(n),
+            Value::List(l) => {
+                self.list_start();
+                for v in l {
+                    self.value(v);
+                }
+                self.end();
+            }
+            Value::Dict(d) => {
+                self.dict_start();
+                for (k, v) in d {
+                    self.key(k);
+                    self.value(v);
+                }
+                self.end();
+            }
+        }
+    }
+}
+
+/// Renders a value as indented text for debugging.
+///
+/// Dictionaries are rendered like JSON objects and lists like JSON arrays.
+/// Byte strings are rendered in double quotes with printable ASCII characters
+/// as is and other bytes as hex escapes. Byte strings longer than 64 bytes
+/// are truncated, so the output is not meant to be parsed.
+///
+/// ```rust
+/// use bt_bencode::Value;
+///
+/// let value: Value = bt_bencode::from_slice(b"d1:ad2:id4:\x01\x02ab1:ti-1ee1:ble1:y1:qe")?;
+/// assert_eq!(
+///     bt_bencode::value::to_string_pretty(&value),
+///     r#"{
+///   "a": {
+///     "id": "\x01\x02ab",
+///     "t": -1
+///   },
+///   "b": [],
+///   "y": "q"
+/// }"#
+/// );
+/// # Ok::<(), bt_bencode::Error>(())
+/// ```
+#[must_use]
+pub fn to_string_pretty(value: &Value) -> String {
+    let mut printer = PrettyPrinter::new();
+    printer.value(value);
+    printer.into_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{error::Result, value::SmallBytes};
+
+    #[test]
+    fn test_to_string_pretty() -> Result<()> {
+        let value: Value = crate::from_slice(b"li1el1:\"0:ed1:\\l2:\n\teee")?;
+        assert_eq!(
+            to_string_pretty(&value),
+            "[\n  1,\n  [\n    \"\\\"\",\n    \"\"\n  ],\n  {\n    \"\\\\\": [\n      \"\\n\\t\"\n    ]\n  }\n]"
+        );
+        assert_eq!(to_string_pretty(&Value::from(2_u64)), "2");
+
+        let long = Value::ByteStr(SmallBytes::from(&[0xff_u8; MAX_BYTE_STR_LEN + 1][..]));
+        let s = to_string_pretty(&long);
+        assert!(s.starts_with("\"\\xff"));
+        assert!(s.ends_with("\"... (65 bytes)"));
+        assert_eq!(s.matches("\\xff").count(), MAX_BYTE_STR_LEN);
+        Ok(())
+    }
+}
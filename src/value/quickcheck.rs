@@ -0,0 +1,141 @@
+//! [`quickcheck::Arbitrary`] implementations for property tests.
+
+use super::{Number, SmallBytes, Value};
+use crate::key::ByteStrKey;
+use ::quickcheck::{Arbitrary, Gen};
+use std::{boxed::Box, collections::BTreeMap, vec::Vec};
+
+/// The maximum nesting of lists and dictionaries in a generated value.
+const MAX_DEPTH: usize = 4;
+
+impl Arbitrary for Number {
+    /// Generates a number in the form it would be deserialized in.
+    ///
+    /// Non-negative numbers are always [`Number::Unsigned`] and
+    /// [`Number::Signed`] is only used for negative numbers, so generated
+    /// values compare equal after an encode and decode round trip.
+    fn arbitrary(g: &mut Gen) -> Self {
+        let n = i64::arbitrary(g);
+        if n < 0 {
+            Number::Signed(n)
+        } else {
+            Number::Unsigned(u64::arbitrary(g))
+        }
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        match *self {
+            Number::Signed(n) => Box::new(n.shrink().filter(|n| *n < 0).map(Number::Signed)),
+            Number::Unsigned(n) => Box::new(n.shrink().map(Number::Unsigned)),
+            Number::Signed128(_) | Number::Unsigned128(_) => quickcheck::empty_shrinker(),
+            #[cfg(feature = "big-int")]
+            Number::Big(_) => quickcheck::empty_shrinker(),
+        }
+    }
+}
+
+/// Returns a collection length which shrinks quickly with the depth, so the
+/// total size of a generated value stays close to the generator's size.
+fn collection_len(g: &mut Gen, depth: usize) -> usize {
+    usize::arbitrary(g) % ((g.size() >> (2 * (depth + 1))) + 1)
+}
+
+fn arbitrary_value(g: &mut Gen, depth: usize) -> Value {
+    let kinds: &[u8] = if depth < MAX_DEPTH {
+        &[0, 1, 2, 3]
+    } else {
+        &[0, 1]
+    };
+    match g.choose(kinds) {
+        Some(0) => Value::ByteStr(SmallBytes::from(Vec::<u8>::arbitrary(g))),
+        Some(1) => Value::Int(Number::arbitrary(g)),
+        Some(2) => {
+            let len = collection_len(g, depth);
+            Value::List((0..len).map(|_| arbitrary_value(g, depth + 1)).collect())
+        }
+        _ => {
+            let len = collection_len(g, depth);
+            Value::Dict(
+                (0..len)
+                    .map(|_| {
+                        (
+                            ByteStrKey::from(Vec::<u8>::arbitrary(g)),
+                            arbitrary_value(g, depth + 1),
+                        )
+                    })
+                    .collect(),
+            )
+        }
+    }
+}
+
+impl Arbitrary for Value {
+    /// Generates a value with at most 4 levels of nested lists and dictionaries.
+    ///
+    /// The generator's size bounds the length of byte strings and
+    /// collections. Numbers are generated as described in
+    /// [`Number::arbitrary()`].
+    fn arbitrary(g: &mut Gen) -> Self {
+        arbitrary_value(g, 0)
+    }
+
+    /// Shrinks byte strings, numbers, and collections, and replaces
+    /// collections with their elements.
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        match self {
+            Value::ByteStr(b) => Box::new(
+                b.to_vec()
+                    .shrink()
+                    .map(|b| Value::ByteStr(SmallBytes::from(b))),
+            ),
+            Value::Int(n) => Box::new(n.shrink().map(Value::Int)),
+            Value::List(l) => {
+                let elements = l.clone();
+                Box::new(elements.into_iter().chain(l.shrink().map(Value::List)))
+            }
+            Value::Dict(d) => {
+                let values = d.values().cloned().collect::<Vec<_>>();
+                let entries = d
+                    .iter()
+                    .map(|(k, v)| (k.to_vec(), v.clone()))
+                    .collect::<Vec<_>>();
+                Box::new(values.into_iter().chain(entries.shrink().map(|entries| {
+                    Value::Dict(
+                        entries
+                            .into_iter()
+                            .map(|(k, v)| (ByteStrKey::from(k), v))
+                            .collect::<BTreeMap<_, _>>(),
+                    )
+                })))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::quickcheck::QuickCheck;
+
+    #[test]
+    fn test_round_trip() {
+        fn prop(value: Value) -> bool {
+            let encoded = crate::to_vec(&value).unwrap();
+            crate::from_slice::<Value>(&encoded).unwrap() == value
+        }
+        QuickCheck::new()
+            .tests(200)
+            .quickcheck(prop as fn(Value) -> bool);
+    }
+
+    #[test]
+    fn test_shrink() {
+        let value = Value::List(vec![Value::Int(Number::Signed(-5)), Value::from("ab")]);
+        let shrunk = value.shrink().collect::<Vec<_>>();
+        assert!(shrunk.contains(&Value::Int(Number::Signed(-5))));
+        assert!(shrunk.contains(&Value::List(Vec::new())));
+        for value in Value::Int(Number::Signed(-5)).shrink() {
+            assert!(matches!(value, Value::Int(Number::Signed(n)) if n < 0));
+        }
+    }
+}
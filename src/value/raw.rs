@@ -0,0 +1,345 @@
+//! Captures the raw encoding of a value.
+
+use crate::error::{Error, Result};
+use core::{fmt, fmt::Write as _};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use serde_bytes::Bytes;
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::vec::Vec;
+
+/// The newtype struct name used by the serializers and deserializers in this
+/// crate to recognize raw values.
+pub(crate) const TOKEN: &str = "$bt_bencode::private::RawValue";
+
+/// Validates that the bytes are exactly one encoded value.
+fn validate(bytes: &[u8]) -> Result<()> {
+    let mut values = crate::raw_values(bytes);
+    match (values.next(), values.next()) {
+        (Some(Ok(_)), None) => Ok(()),
+        (Some(Err(err)), _) | (_, Some(Err(err))) => Err(err),
+        (None, _) => Err(Error::EofWhileParsingValue),
+        (Some(Ok(_)), Some(Ok(_))) => Err(Error::TrailingData),
+    }
+}
+
+fn fmt_raw(name: &str, bytes: &[u8], f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(name)?;
+    f.write_str("(b\"")?;
+    for b in bytes {
+        for c in core::ascii::escape_default(*b) {
+            f.write_char(char::from(c))?;
+        }
+    }
+    f.write_str("\")")
+}
+
+/// The raw encoding of a value borrowed from the input.
+///
+/// When used as a field, the field's value is not parsed. The exact bytes are
+/// captured during deserialization and written back verbatim during
+/// serialization, so a value (e.g. an `info` dictionary) can be passed
+/// through untouched or parsed later with [`RawValue::parse()`].
+///
+/// Borrowing requires deserializing from a slice (e.g. with
+/// [`from_slice()`][crate::from_slice]). Use [`RawValueBuf`] to deserialize
+/// from a reader.
+///
+/// ```rust
+/// use bt_bencode::value::RawValue;
+/// use serde_derive::{Deserialize, Serialize};
+///
+/// #[derive(Deserialize, Serialize)]
+/// struct Torrent<'a> {
+///     announce: &'a str,
+///     #[serde(borrow)]
+///     info: RawValue<'a>,
+/// }
+///
+/// let input = b"d8:announce8:http://a4:infod4:name1:a4:zzzz0:ee";
+/// let torrent: Torrent<'_> = bt_bencode::from_slice(input)?;
+/// assert_eq!(torrent.info.as_bytes(), b"d4:name1:a4:zzzz0:e");
+/// assert_eq!(bt_bencode::to_vec(&torrent)?, &input[..]);
+/// # Ok::<(), bt_bencode::Error>(())
+/// ```
+///
+/// The serialization is only verbatim with this crate's serializers. Other
+/// serializers see a newtype struct containing the raw bytes.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RawValue<'a>(&'a [u8]);
+
+impl<'a> RawValue<'a> {
+    /// Wraps the raw encoding of a value.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the bytes are not exactly one encoded value.
+    pub fn from_slice(bytes: &'a [u8]) -> Result<Self> {
+        validate(bytes)?;
+        Ok(RawValue(bytes))
+    }
+
+    /// Returns the raw encoding.
+    #[must_use]
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+
+    /// Deserializes an instance of `T` from the raw encoding.
+    ///
+    /// # Errors
+    ///
+    /// Deserialization can fail if the data cannot be deserialized into an instance of `T`.
+    pub fn parse<T>(&self) -> Result<T>
+    where
+        T: Deserialize<'a>,
+    {
+        crate::from_slice(self.0)
+    }
+}
+
+impl<'a> AsRef<[u8]> for RawValue<'a> {
+    fn as_ref(&self) -> &[u8] {
+        self.0
+    }
+}
+
+impl<'a> fmt::Debug for RawValue<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_raw("RawValue", self.0, f)
+    }
+}
+
+impl<'a> Serialize for RawValue<'a> {
+    fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_newtype_struct(TOKEN, Bytes::new(self.0))
+    }
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for RawValue<'a> {
+    fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct RawValueVisitor;
+
+        impl<'de> de::Visitor<'de> for RawValueVisitor {
+            type Value = RawValue<'de>;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a raw value borrowed from the input")
+            }
+
+            fn visit_borrowed_bytes<E>(self, v: &'de [u8]) -> core::result::Result<Self::Value, E> {
+                Ok(RawValue(v))
+            }
+        }
+
+        deserializer.deserialize_newtype_struct(TOKEN, RawValueVisitor)
+    }
+}
+
+/// The owned raw encoding of a value.
+///
+/// See [`RawValue`].
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct RawValueBuf(Vec<u8>);
+
+impl RawValueBuf {
+    /// Wraps the raw encoding of a value.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the bytes are not exactly one encoded value.
+    pub fn from_vec(bytes: Vec<u8>) -> Result<Self> {
+        validate(&bytes)?;
+        Ok(RawValueBuf(bytes))
+    }
+
+    /// Returns the raw encoding.
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Returns a borrowed raw value.
+    #[must_use]
+    pub fn as_raw_value(&self) -> RawValue<'_> {
+        RawValue(&self.0)
+    }
+
+    /// Returns the raw encoding.
+    #[must_use]
+    pub fn into_vec(self) -> Vec<u8> {
+        self.0
+    }
+
+    /// Deserializes an instance of `T` from the raw encoding.
+    ///
+    /// # Errors
+    ///
+    /// Deserialization can fail if the data cannot be deserialized into an instance of `T`.
+    pub fn parse<'a, T>(&'a self) -> Result<T>
+    where
+        T: Deserialize<'a>,
+    {
+        crate::from_slice(&self.0)
+    }
+}
+
+impl AsRef<[u8]> for RawValueBuf {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl fmt::Debug for RawValueBuf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_raw("RawValueBuf", &self.0, f)
+    }
+}
+
+impl<'a> From<RawValue<'a>> for RawValueBuf {
+    fn from(value: RawValue<'a>) -> Self {
+        RawValueBuf(value.0.to_vec())
+    }
+}
+
+impl Serialize for RawValueBuf {
+    fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_newtype_struct(TOKEN, Bytes::new(&self.0))
+    }
+}
+
+impl<'de> Deserialize<'de> for RawValueBuf {
+    fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct RawValueBufVisitor;
+
+        impl<'de> de::Visitor<'de> for RawValueBufVisitor {
+            type Value = RawValueBuf;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a raw value")
+            }
+
+            fn visit_bytes<E>(self, v: &[u8]) -> core::result::Result<Self::Value, E> {
+                Ok(RawValueBuf(v.to_vec()))
+            }
+
+            fn visit_byte_buf<E>(self, v: Vec<u8>) -> core::result::Result<Self::Value, E> {
+                Ok(RawValueBuf(v))
+            }
+
+            // Deserializers which do not recognize the token, such as the
+            // buffered content used for `#[serde(flatten)]`, pass the value
+            // through as a newtype struct. The value is re-encoded, so
+            // dictionary keys are sorted in the captured bytes.
+            fn visit_newtype_struct<D>(self, d: D) -> core::result::Result<Self::Value, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                let value = crate::Value::deserialize(d)?;
+                crate::to_vec(&value)
+                    .map(RawValueBuf)
+                    .map_err(de::Error::custom)
+            }
+        }
+
+        deserializer.deserialize_newtype_struct(TOKEN, RawValueBufVisitor)
+    }
+}
+
+/// Serializes an instance of `T` into a [`RawValueBuf`].
+///
+/// # Errors
+///
+/// Serialization can fail if `T`'s implementation of
+/// [Serialize][serde::ser::Serialize] decides to fail, if `T` contains
+/// unsupported types for serialization, or if `T` contains a map with
+/// non-string keys.
+pub fn to_raw_value<T>(value: &T) -> Result<RawValueBuf>
+where
+    T: ?Sized + Serialize,
+{
+    crate::to_vec(value).map(RawValueBuf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Value;
+    use serde_derive::{Deserialize, Serialize};
+
+    #[cfg(all(feature = "alloc", not(feature = "std")))]
+    use alloc::format;
+
+    #[derive(Debug, Deserialize, Serialize)]
+    struct Message<'a> {
+        #[serde(borrow)]
+        a: RawValue<'a>,
+        b: RawValueBuf,
+        #[serde(borrow)]
+        c: Option<RawValue<'a>>,
+    }
+
+    #[test]
+    fn test_round_trip() -> Result<()> {
+        let input = &b"d1:ad1:zi1e1:yi2ee1:bli-1e3:abce1:c0:e"[..];
+        let msg: Message<'_> = crate::from_slice(input)?;
+        assert_eq!(msg.a.as_bytes(), b"d1:zi1e1:yi2ee");
+        assert_eq!(msg.b.as_bytes(), b"li-1e3:abce");
+        assert_eq!(msg.c.map(|c| c.as_bytes()), Some(&b"0:"[..]));
+        assert_eq!(crate::to_vec(&msg)?, input);
+
+        let b: Vec<Value> = msg.b.parse()?;
+        assert_eq!(b, [Value::from(-1), Value::from("abc")]);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_reader() -> Result<()> {
+        #[derive(Deserialize)]
+        struct Owned {
+            a: RawValueBuf,
+        }
+
+        let msg: Owned = crate::from_reader(&b"d1:ali1eee"[..])?;
+        assert_eq!(msg.a.as_bytes(), b"li1ee");
+        Ok(())
+    }
+
+    #[test]
+    fn test_value() -> Result<()> {
+        let value: Value = crate::from_slice(b"d1:ad1:bi1eee")?;
+        let raw: RawValueBuf = crate::from_value(value["a"].clone())?;
+        assert_eq!(raw.as_bytes(), b"d1:bi1ee");
+        assert_eq!(crate::to_value(&raw)?, value["a"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate() -> Result<()> {
+        assert_eq!(RawValue::from_slice(b"le")?.as_bytes(), b"le");
+        assert!(RawValue::from_slice(b"").is_err());
+        assert!(RawValue::from_slice(b"l").is_err());
+        assert!(RawValueBuf::from_vec(b"i1ei2e".to_vec()).is_err());
+        assert_eq!(to_raw_value(&[1, 2])?.as_bytes(), b"li1ei2ee");
+        assert_eq!(
+            format!("{:?}", RawValue::from_slice(b"1:\xff")?),
+            r#"RawValue(b"1:\xff")"#
+        );
+        Ok(())
+    }
+}
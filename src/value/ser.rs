@@ -1,7 +1,8 @@
 //! Serializes into a [Value].
 
-use super::{Number, Value};
+use super::{Number, SmallBytes, Value};
 use crate::error::{Error, Result};
+use crate::key::ByteStrKey;
 use serde::{ser, Serialize};
 use serde_bytes::ByteBuf;
 
@@ -69,6 +70,16 @@ impl ser::Serializer for Serializer {
         Ok(Value::Int(Number::Unsigned(value)))
     }
 
+    #[inline]
+    fn serialize_i128(self, value: i128) -> Result<Self::Ok> {
+        Ok(Value::Int(Number::from(value)))
+    }
+
+    #[inline]
+    fn serialize_u128(self, value: u128) -> Result<Self::Ok> {
+        Ok(Value::Int(Number::from(value)))
+    }
+
     #[inline]
     fn serialize_f32(self, _value: f32) -> Result<Self::Ok> {
         Err(Error::UnsupportedType)
@@ -87,12 +98,12 @@ impl ser::Serializer for Serializer {
 
     #[inline]
     fn serialize_str(self, value: &str) -> Result<Self::Ok> {
-        Ok(Value::ByteStr(ByteBuf::from(value)))
+        Ok(Value::ByteStr(SmallBytes::from(value)))
     }
 
     #[inline]
     fn serialize_bytes(self, value: &[u8]) -> Result<Self::Ok> {
-        Ok(Value::ByteStr(ByteBuf::from(value)))
+        Ok(Value::ByteStr(SmallBytes::from(value)))
     }
 
     #[inline]
@@ -129,10 +140,20 @@ impl ser::Serializer for Serializer {
     }
 
     #[inline]
-    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<Self::Ok>
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<Self::Ok>
     where
         T: ?Sized + Serialize,
     {
+        if name == super::raw::TOKEN {
+            let raw = value.serialize(&mut DictKeySerializer {})?;
+            return crate::from_slice(&raw);
+        }
+        #[cfg(feature = "big-int")]
+        if name == super::BIG_INT_TOKEN {
+            let digits = value.serialize(&mut DictKeySerializer {})?;
+            let digits = core::str::from_utf8(&digits).map_err(|_| Error::InvalidInteger)?;
+            return digits.parse().map(|n| Value::Int(Number::Big(n)));
+        }
         value.serialize(self)
     }
 
@@ -235,7 +256,7 @@ impl ser::SerializeSeq for SerializeList {
 }
 
 pub(super) struct SerializeDict {
-    dict: BTreeMap<ByteBuf, Value>,
+    dict: BTreeMap<ByteStrKey, Value>,
     current_key: Option<ByteBuf>,
 }
 
@@ -262,7 +283,7 @@ impl ser::SerializeMap for SerializeDict {
     {
         let key = self.current_key.take().ok_or(Error::ValueWithoutKey)?;
         let value = super::to_value(value)?;
-        self.dict.insert(key, value);
+        self.dict.insert(ByteStrKey::from(key), value);
         Ok(())
     }
 
@@ -283,7 +304,7 @@ impl ser::SerializeStruct for SerializeDict {
     {
         let key = key.serialize(&mut DictKeySerializer)?;
         let value = super::to_value(value)?;
-        self.dict.insert(key, value);
+        self.dict.insert(ByteStrKey::from(key), value);
         Ok(())
     }
 
@@ -343,6 +364,14 @@ impl ser::Serializer for &mut DictKeySerializer {
         Err(Error::UnsupportedType)
     }
 
+    fn serialize_i128(self, _value: i128) -> Result<Self::Ok> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_u128(self, _value: u128) -> Result<Self::Ok> {
+        Err(Error::UnsupportedType)
+    }
+
     fn serialize_f32(self, _value: f32) -> Result<Self::Ok> {
         Err(Error::UnsupportedType)
     }
@@ -553,7 +582,7 @@ mod tests {
 
     #[test]
     fn test_serialize_u64_greater_than_i64_max() {
-        let value: u64 = (i64::max_value() as u64) + 1;
+        let value: u64 = (i64::MAX as u64) + 1;
         assert_eq!(
             to_value(&value).unwrap(),
             Value::Int(Number::Unsigned(value))
@@ -577,7 +606,7 @@ mod tests {
         let value: char = 'a';
         assert_eq!(
             to_value(&value).unwrap(),
-            Value::ByteStr(ByteBuf::from("a"))
+            Value::ByteStr(SmallBytes::from("a"))
         );
     }
 
@@ -586,7 +615,7 @@ mod tests {
         let value: &str = "Hello world!";
         assert_eq!(
             to_value(&value).unwrap(),
-            Value::ByteStr(ByteBuf::from(value))
+            Value::ByteStr(SmallBytes::from(value))
         );
     }
 
@@ -595,14 +624,14 @@ mod tests {
         let value: &str = "";
         assert_eq!(
             to_value(&value).unwrap(),
-            Value::ByteStr(ByteBuf::from(value))
+            Value::ByteStr(SmallBytes::from(value))
         );
     }
 
     #[test]
     fn test_serialize_bytes() {
         let value = ByteBuf::from(String::from("123").into_bytes());
-        assert_eq!(to_value(&&value).unwrap(), Value::ByteStr(value));
+        assert_eq!(to_value(&&value).unwrap(), Value::ByteStr(value.into()));
     }
 
     #[test]
@@ -731,12 +760,12 @@ mod tests {
         };
         let mut expected = BTreeMap::new();
         expected.insert(
-            ByteBuf::from(String::from("int")),
+            ByteStrKey::from(String::from("int")),
             Value::Int(Number::Unsigned(3)),
         );
         expected.insert(
-            ByteBuf::from(String::from("s")),
-            Value::ByteStr(ByteBuf::from(String::from("Hello, World!"))),
+            ByteStrKey::from(String::from("s")),
+            Value::ByteStr(SmallBytes::from(String::from("Hello, World!"))),
         );
 
         assert_eq!(to_value(&test).unwrap(), Value::Dict(expected));
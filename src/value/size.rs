@@ -0,0 +1,153 @@
+//! Accounts for the memory used by a [Value].
+
+#[cfg(feature = "big-int")]
+use super::Number;
+use super::Value;
+use crate::key::ByteStrKey;
+use core::mem::{self, size_of};
+
+impl Value {
+    /// Returns the approximate number of bytes used by the value.
+    ///
+    /// The count includes the value itself and the heap allocations owned by
+    /// the value and every nested value. The overhead of dictionary nodes is
+    /// not known, so each entry is counted as the size of its key and value.
+    ///
+    /// ```rust
+    /// use bt_bencode::Value;
+    ///
+    /// let value = Value::from("a".repeat(100));
+    /// assert!(value.deep_byte_len() >= 100);
+    /// # Ok::<(), bt_bencode::Error>(())
+    /// ```
+    #[must_use]
+    pub fn deep_byte_len(&self) -> usize {
+        size_of::<Value>() + self.heap_byte_len()
+    }
+
+    /// Returns the number of values, including the value itself and every
+    /// nested value.
+    ///
+    /// Dictionary keys are not counted.
+    ///
+    /// ```rust
+    /// use bt_bencode::Value;
+    ///
+    /// let value: Value = bt_bencode::from_slice(b"d1:ali1ei2ee1:b0:e")?;
+    /// assert_eq!(value.node_count(), 5);
+    /// # Ok::<(), bt_bencode::Error>(())
+    /// ```
+    #[must_use]
+    pub fn node_count(&self) -> usize {
+        match self {
+            Value::ByteStr(_) | Value::Int(_) => 1,
+            Value::List(l) => 1 + l.iter().map(Value::node_count).sum::<usize>(),
+            Value::Dict(d) => 1 + d.values().map(Value::node_count).sum::<usize>(),
+        }
+    }
+
+    /// Frees the excess capacity of every list, byte string and dictionary
+    /// key in the value.
+    ///
+    /// Values parsed from a reader may hold more memory than they need,
+    /// because buffers grow while the data is read. Shrinking a value which
+    /// is kept for a long time reclaims the memory.
+    ///
+    /// ```rust
+    /// use bt_bencode::Value;
+    ///
+    /// let mut value: Value = bt_bencode::from_slice(b"li1ei2ei3ee")?;
+    /// let len = value.deep_byte_len();
+    /// value.shrink_to_fit();
+    /// assert!(value.deep_byte_len() <= len);
+    /// # Ok::<(), bt_bencode::Error>(())
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        match self {
+            Value::ByteStr(b) => b.shrink_to_fit(),
+            Value::Int(_) => {}
+            Value::List(l) => {
+                l.shrink_to_fit();
+                l.iter_mut().for_each(Value::shrink_to_fit);
+            }
+            Value::Dict(d) => {
+                *d = mem::take(d)
+                    .into_iter()
+                    .map(|(mut k, mut v)| {
+                        k.0.shrink_to_fit();
+                        v.shrink_to_fit();
+                        (k, v)
+                    })
+                    .collect();
+            }
+        }
+    }
+
+    /// Returns the number of bytes allocated on the heap by the value and
+    /// every nested value.
+    fn heap_byte_len(&self) -> usize {
+        match self {
+            Value::ByteStr(b) => b.heap_capacity(),
+            #[cfg(feature = "big-int")]
+            Value::Int(Number::Big(n)) => n.as_str().len(),
+            Value::Int(_) => 0,
+            Value::List(l) => {
+                l.capacity() * size_of::<Value>()
+                    + l.iter().map(Value::heap_byte_len).sum::<usize>()
+            }
+            Value::Dict(d) => d
+                .iter()
+                .map(|(k, v)| {
+                    size_of::<ByteStrKey>()
+                        + k.0.heap_capacity()
+                        + size_of::<Value>()
+                        + v.heap_byte_len()
+                })
+                .sum(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Result;
+
+    #[cfg(all(feature = "alloc", not(feature = "std")))]
+    use alloc::{collections::BTreeMap, vec, vec::Vec};
+    #[cfg(feature = "std")]
+    use std::{collections::BTreeMap, vec, vec::Vec};
+
+    #[test]
+    fn test_size() -> Result<()> {
+        let value = Value::from(1_u64);
+        assert_eq!(value.deep_byte_len(), size_of::<Value>());
+        assert_eq!(value.node_count(), 1);
+
+        let value = Value::List(vec![Value::from("ab")]);
+        assert_eq!(value.deep_byte_len(), 2 * size_of::<Value>());
+        assert_eq!(value.node_count(), 2);
+
+        let input = [&b"d1:a30:"[..], &[0; 30], b"e"].concat();
+        let value: Value = crate::from_slice(&input)?;
+        assert!(value.deep_byte_len() > 2 * size_of::<Value>() + 30);
+        assert_eq!(value.node_count(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_shrink_to_fit() -> Result<()> {
+        let mut list = Vec::with_capacity(8);
+        list.push(Value::from("a".repeat(30)));
+        let mut dict = BTreeMap::new();
+        dict.insert(ByteStrKey::from(b"k".to_vec()), Value::List(list));
+        let mut value = Value::Dict(dict);
+        let len = value.deep_byte_len();
+
+        let expected = value.clone();
+        value.shrink_to_fit();
+        assert_eq!(value, expected);
+        assert_eq!(value.deep_byte_len(), len - 7 * size_of::<Value>());
+        Ok(())
+    }
+}
@@ -0,0 +1,240 @@
+//! Values annotated with the byte ranges they were parsed from.
+
+use super::{Number, SmallBytes, Value};
+use crate::{
+    error::{Error, Result},
+    key::ByteStrKey,
+    read::{Read, SliceRead},
+};
+use core::ops::Range;
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{collections::BTreeMap, vec::Vec};
+#[cfg(feature = "std")]
+use std::{collections::BTreeMap, vec::Vec};
+
+/// A value with the byte range of its encoding in the parsed input.
+///
+/// ```rust
+/// use bt_bencode::value::SpannedValue;
+///
+/// let input = b"d8:announce8:http://a4:infod6:lengthi1eee";
+/// let value = SpannedValue::from_slice(input)?;
+///
+/// let info = value.get(b"info").unwrap();
+/// assert_eq!(info.span(), 27..40);
+/// assert_eq!(info.raw(input), b"d6:lengthi1ee");
+///
+/// let entry = value.entry(b"announce").unwrap();
+/// assert_eq!(&input[entry.key_span.clone()], b"8:announce");
+/// # Ok::<(), bt_bencode::Error>(())
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SpannedValue {
+    span: Range<usize>,
+    kind: SpannedKind,
+}
+
+/// The kinds of [`SpannedValue`]s.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SpannedKind {
+    /// A byte string.
+    ByteStr(SmallBytes),
+    /// An integer.
+    Int(Number),
+    /// A list of values.
+    List(Vec<SpannedValue>),
+    /// A dictionary of values.
+    ///
+    /// If a key is repeated, the last entry is kept.
+    Dict(BTreeMap<ByteStrKey, SpannedEntry>),
+}
+
+/// A dictionary entry in a [`SpannedValue`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SpannedEntry {
+    /// The byte range of the encoded key.
+    pub key_span: Range<usize>,
+    /// The value.
+    pub value: SpannedValue,
+}
+
+impl SpannedValue {
+    /// Parses a value and records the byte range of every nested value.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the input is not exactly one valid value.
+    pub fn from_slice(input: &[u8]) -> Result<Self> {
+        let mut read = SliceRead::new(input);
+        let mut buf = Vec::new();
+        let value = parse(&mut read, &mut buf)?;
+        if read.peek().is_some() {
+            return Err(Error::TrailingData);
+        }
+        Ok(value)
+    }
+
+    /// Returns the byte range of the encoded value.
+    #[must_use]
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    /// Returns the kind of value.
+    #[must_use]
+    pub fn kind(&self) -> &SpannedKind {
+        &self.kind
+    }
+
+    /// Returns the encoded value from the input it was parsed from.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the input is shorter than the span.
+    #[must_use]
+    pub fn raw<'a>(&self, input: &'a [u8]) -> &'a [u8] {
+        &input[self.span()]
+    }
+
+    /// Returns the dictionary entry for a key if the value is a dictionary.
+    #[must_use]
+    pub fn entry(&self, key: &[u8]) -> Option<&SpannedEntry> {
+        match &self.kind {
+            SpannedKind::Dict(d) => d.get(key),
+            _ => None,
+        }
+    }
+
+    /// Returns the value for a key if the value is a dictionary.
+    #[must_use]
+    pub fn get(&self, key: &[u8]) -> Option<&SpannedValue> {
+        self.entry(key).map(|entry| &entry.value)
+    }
+
+    /// Returns the element at an index if the value is a list.
+    #[must_use]
+    pub fn get_index(&self, index: usize) -> Option<&SpannedValue> {
+        match &self.kind {
+            SpannedKind::List(l) => l.get(index),
+            _ => None,
+        }
+    }
+
+    /// Returns the innermost value whose span contains the byte offset.
+    #[must_use]
+    pub fn find(&self, offset: usize) -> Option<&SpannedValue> {
+        if !self.span.contains(&offset) {
+            return None;
+        }
+        let child = match &self.kind {
+            SpannedKind::ByteStr(_) | SpannedKind::Int(_) => None,
+            SpannedKind::List(l) => l.iter().find_map(|v| v.find(offset)),
+            SpannedKind::Dict(d) => d.values().find_map(|e| e.value.find(offset)),
+        };
+        Some(child.unwrap_or(self))
+    }
+}
+
+impl From<SpannedValue> for Value {
+    fn from(value: SpannedValue) -> Self {
+        match value.kind {
+            SpannedKind::ByteStr(b) => Value::ByteStr(b),
+            SpannedKind::Int(n) => Value::Int(n),
+            SpannedKind::List(l) => Value::List(l.into_iter().map(Value::from).collect()),
+            SpannedKind::Dict(d) => Value::Dict(
+                d.into_iter()
+                    .map(|(k, e)| (ByteStrKey::from(k.0), Value::from(e.value)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+fn parse(read: &mut SliceRead<'_>, buf: &mut Vec<u8>) -> Result<SpannedValue> {
+    let start = read.byte_offset();
+    let kind = match read.peek().ok_or(Error::EofWhileParsingValue)?? {
+        b'0'..=b'9' => {
+            buf.clear();
+            SpannedKind::ByteStr(SmallBytes::from(&*read.parse_byte_str(buf)?))
+        }
+        b'i' => {
+            read.next();
+            buf.clear();
+            let num_str = read.parse_integer(buf)?;
+            SpannedKind::Int(num_str.parse()?)
+        }
+        b'l' => {
+            read.next();
+            let mut list = Vec::new();
+            while read.peek().ok_or(Error::EofWhileParsingValue)?? != b'e' {
+                list.push(parse(read, buf)?);
+            }
+            read.next();
+            SpannedKind::List(list)
+        }
+        b'd' => {
+            read.next();
+            let mut dict = BTreeMap::new();
+            loop {
+                let key_start = read.byte_offset();
+                match read.peek().ok_or(Error::EofWhileParsingValue)?? {
+                    b'e' => break,
+                    b'0'..=b'9' => {}
+                    _ => return Err(Error::KeyMustBeAByteStr),
+                }
+                buf.clear();
+                let key = ByteStrKey::from(&*read.parse_byte_str(buf)?);
+                let key_span = key_start..read.byte_offset();
+                let value = parse(read, buf)?;
+                dict.insert(key, SpannedEntry { key_span, value });
+            }
+            read.next();
+            SpannedKind::Dict(dict)
+        }
+        _ => return Err(Error::ExpectedSomeValue),
+    };
+    Ok(SpannedValue {
+        span: start..read.byte_offset(),
+        kind,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spans() -> Result<()> {
+        let input = &b"d1:ali-1e0:e1:bd1:ci42eee"[..];
+        let value = SpannedValue::from_slice(input)?;
+        assert_eq!(value.span(), 0..input.len());
+
+        let a = value.get(b"a").unwrap();
+        assert_eq!(a.raw(input), b"li-1e0:e");
+        assert_eq!(a.get_index(0).unwrap().raw(input), b"i-1e");
+        assert_eq!(a.get_index(1).unwrap().span(), 9..11);
+        assert!(a.get_index(2).is_none());
+
+        let c = value.get(b"b").and_then(|b| b.entry(b"c")).unwrap();
+        assert_eq!(&input[c.key_span.clone()], b"1:c");
+        assert_eq!(c.value.raw(input), b"i42e");
+        assert_eq!(c.value.kind(), &SpannedKind::Int(Number::Unsigned(42)));
+
+        assert_eq!(value.find(19).map(SpannedValue::span), Some(19..23));
+        assert_eq!(value.find(12).map(SpannedValue::span), Some(0..25));
+        assert!(value.find(25).is_none());
+
+        assert_eq!(Value::from(value), crate::from_slice::<Value>(input)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(SpannedValue::from_slice(b"").is_err());
+        assert!(SpannedValue::from_slice(b"li1e").is_err());
+        assert!(SpannedValue::from_slice(b"di1ei2ee").is_err());
+        assert!(SpannedValue::from_slice(b"i1ei2e").is_err());
+        assert!(SpannedValue::from_slice(b"x").is_err());
+    }
+}
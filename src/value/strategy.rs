@@ -0,0 +1,140 @@
+//! [proptest][mod@proptest] strategies which generate [Value]s.
+//!
+//! ```rust
+//! use bt_bencode::{value::strategy::ValueStrategy, Value};
+//! use proptest::prelude::*;
+//!
+//! proptest!(|(value in ValueStrategy::default().depth(3).boxed())| {
+//!     let encoded = bt_bencode::to_vec(&value).unwrap();
+//!     prop_assert_eq!(bt_bencode::from_slice::<Value>(&encoded).unwrap(), value);
+//! });
+//! ```
+
+use super::{Number, SmallBytes, Value};
+use crate::key::ByteStrKey;
+use proptest::{
+    arbitrary::Arbitrary,
+    collection::{btree_map, vec},
+    prelude::*,
+    strategy::BoxedStrategy,
+};
+
+/// Configures the size of values generated by [`ValueStrategy::boxed()`].
+///
+/// Numbers are generated in the form they would be deserialized in:
+/// [`Number::Signed`] is only used for negative numbers, so generated values
+/// compare equal after an encode and decode round trip.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ValueStrategy {
+    depth: u32,
+    desired_size: u32,
+    max_collection_len: usize,
+    max_byte_str_len: usize,
+}
+
+impl Default for ValueStrategy {
+    /// Returns a configuration with a depth of 4, a desired size of 64
+    /// values, collections of at most 8 entries, and byte strings of at most
+    /// 32 bytes.
+    fn default() -> Self {
+        Self {
+            depth: 4,
+            desired_size: 64,
+            max_collection_len: 8,
+            max_byte_str_len: 32,
+        }
+    }
+}
+
+impl ValueStrategy {
+    /// Sets the maximum nesting of lists and dictionaries.
+    #[must_use]
+    pub fn depth(mut self, depth: u32) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    /// Sets the desired total number of values in a generated value.
+    #[must_use]
+    pub fn desired_size(mut self, desired_size: u32) -> Self {
+        self.desired_size = desired_size;
+        self
+    }
+
+    /// Sets the maximum number of entries in a list or dictionary.
+    #[must_use]
+    pub fn max_collection_len(mut self, max_collection_len: usize) -> Self {
+        self.max_collection_len = max_collection_len;
+        self
+    }
+
+    /// Sets the maximum length of byte strings and dictionary keys.
+    #[must_use]
+    pub fn max_byte_str_len(mut self, max_byte_str_len: usize) -> Self {
+        self.max_byte_str_len = max_byte_str_len;
+        self
+    }
+
+    /// Returns the strategy.
+    pub fn boxed(self) -> BoxedStrategy<Value> {
+        let byte_str_len = 0..=self.max_byte_str_len;
+        let collection_len = 0..=self.max_collection_len;
+        let leaf = prop_oneof![
+            vec(any::<u8>(), byte_str_len.clone())
+                .prop_map(|b| Value::ByteStr(SmallBytes::from(b))),
+            number().prop_map(Value::Int),
+        ];
+        #[allow(clippy::cast_possible_truncation)]
+        let branch_size = self.max_collection_len as u32;
+        leaf.prop_recursive(self.depth, self.desired_size, branch_size, move |inner| {
+            prop_oneof![
+                vec(inner.clone(), collection_len.clone()).prop_map(Value::List),
+                btree_map(
+                    vec(any::<u8>(), byte_str_len.clone()).prop_map(ByteStrKey::from),
+                    inner,
+                    collection_len.clone(),
+                )
+                .prop_map(Value::Dict),
+            ]
+        })
+        .boxed()
+    }
+}
+
+/// Returns a strategy for numbers.
+///
+/// Non-negative numbers are always [`Number::Unsigned`].
+pub fn number() -> BoxedStrategy<Number> {
+    prop_oneof![
+        any::<u64>().prop_map(Number::Unsigned),
+        (i64::MIN..0).prop_map(Number::Signed),
+    ]
+    .boxed()
+}
+
+impl Arbitrary for Value {
+    type Parameters = ValueStrategy;
+    type Strategy = BoxedStrategy<Value>;
+
+    fn arbitrary_with(args: ValueStrategy) -> Self::Strategy {
+        args.boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn test_round_trip(value in any::<Value>()) {
+            let encoded = crate::to_vec(&value).unwrap();
+            prop_assert_eq!(crate::from_slice::<Value>(&encoded).unwrap(), value);
+        }
+
+        #[test]
+        fn test_depth(value in ValueStrategy::default().depth(0).boxed()) {
+            prop_assert!(matches!(value, Value::ByteStr(_) | Value::Int(_)));
+        }
+    }
+}
@@ -0,0 +1,424 @@
+//! Values which remember how they were encoded.
+
+use super::{Number, SmallBytes, Value};
+use crate::{
+    error::{Error, Result},
+    key::ByteStrKey,
+    read::{Read, SliceRead},
+    write::Write,
+};
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{boxed::Box, vec::Vec};
+#[cfg(feature = "std")]
+use std::{boxed::Box, vec::Vec};
+
+/// A value which can be written back exactly as it was parsed.
+///
+/// Dictionary entries are kept in their encoded order, including repeated
+/// keys, and integers or byte string lengths which are not in canonical form
+/// (such as `i03e`, `i-0e` or `04:spam`) keep their original spelling. A
+/// message which is only inspected is written back byte for byte with
+/// [`VerbatimValue::to_vec()`], so signatures and hashes over forwarded data
+/// stay valid.
+///
+/// Values which are modified through [`VerbatimValue::kind_mut()`] lose their
+/// original spelling and are written in canonical form. Unmodified values
+/// nested inside them keep their spelling.
+///
+/// ```rust
+/// use bt_bencode::value::{VerbatimKind, VerbatimValue};
+///
+/// let input = b"d1:bi01e1:ai2e1:bi3ee";
+/// let mut value = VerbatimValue::from_slice(input)?;
+///
+/// assert!(!value.is_canonical());
+/// assert_eq!(value.to_vec(), input);
+/// assert_eq!(value.get(b"b").and_then(|b| b.as_u64()), Some(3));
+///
+/// if let VerbatimKind::Dict(entries) = value.kind_mut() {
+///     entries.retain(|entry| entry.key() != b"a");
+/// }
+/// assert_eq!(value.to_vec(), b"d1:bi01e1:bi3ee");
+/// # Ok::<(), bt_bencode::Error>(())
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerbatimValue {
+    kind: VerbatimKind,
+    /// The original encoding of a byte string or integer if it is not canonical.
+    spelling: Option<Box<[u8]>>,
+}
+
+/// The kinds of [`VerbatimValue`]s.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VerbatimKind {
+    /// A byte string.
+    ByteStr(SmallBytes),
+    /// An integer.
+    Int(Number),
+    /// A list of values.
+    List(Vec<VerbatimValue>),
+    /// A dictionary of entries in encoded order.
+    Dict(Vec<VerbatimEntry>),
+}
+
+/// A dictionary entry in a [`VerbatimValue`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerbatimEntry {
+    key: SmallBytes,
+    /// The original encoding of the key if it is not canonical.
+    key_spelling: Option<Box<[u8]>>,
+    /// The value.
+    pub value: VerbatimValue,
+}
+
+impl VerbatimEntry {
+    /// Constructs an entry with a canonically encoded key.
+    pub fn new<K, V>(key: K, value: V) -> Self
+    where
+        K: Into<SmallBytes>,
+        V: Into<VerbatimValue>,
+    {
+        VerbatimEntry {
+            key: key.into(),
+            key_spelling: None,
+            value: value.into(),
+        }
+    }
+
+    /// Returns the key.
+    #[must_use]
+    pub fn key(&self) -> &[u8] {
+        &self.key
+    }
+}
+
+impl VerbatimValue {
+    /// Parses a value and remembers its encoding.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the input is not exactly one valid value.
+    pub fn from_slice(input: &[u8]) -> Result<Self> {
+        let mut read = SliceRead::new(input);
+        let mut buf = Vec::new();
+        let value = parse(input, &mut read, &mut buf)?;
+        if read.peek().is_some() {
+            return Err(Error::TrailingData);
+        }
+        Ok(value)
+    }
+
+    /// Returns the kind of value.
+    #[must_use]
+    pub fn kind(&self) -> &VerbatimKind {
+        &self.kind
+    }
+
+    /// Returns a mutable reference to the kind of value.
+    ///
+    /// The original spelling of the value is discarded.
+    pub fn kind_mut(&mut self) -> &mut VerbatimKind {
+        self.spelling = None;
+        &mut self.kind
+    }
+
+    /// Returns the value for a key if the value is a dictionary.
+    ///
+    /// If a key is repeated, the last value is returned.
+    #[must_use]
+    pub fn get(&self, key: &[u8]) -> Option<&VerbatimValue> {
+        match &self.kind {
+            VerbatimKind::Dict(d) => d.iter().rev().find(|e| e.key() == key).map(|e| &e.value),
+            _ => None,
+        }
+    }
+
+    /// Returns a mutable reference to the value for a key if the value is a dictionary.
+    ///
+    /// If a key is repeated, the last value is returned.
+    pub fn get_mut(&mut self, key: &[u8]) -> Option<&mut VerbatimValue> {
+        match &mut self.kind {
+            VerbatimKind::Dict(d) => d
+                .iter_mut()
+                .rev()
+                .find(|e| e.key() == key)
+                .map(|e| &mut e.value),
+            _ => None,
+        }
+    }
+
+    /// If the value is a byte string, returns the bytes.
+    #[must_use]
+    pub fn as_byte_str(&self) -> Option<&[u8]> {
+        match &self.kind {
+            VerbatimKind::ByteStr(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    /// If the value is a [u64], returns the underlying value.
+    #[must_use]
+    pub fn as_u64(&self) -> Option<u64> {
+        match self.kind {
+            VerbatimKind::Int(Number::Unsigned(n)) => Some(n),
+            _ => None,
+        }
+    }
+
+    /// If the value is a [i64], returns the underlying value.
+    #[must_use]
+    pub fn as_i64(&self) -> Option<i64> {
+        match self.kind {
+            VerbatimKind::Int(Number::Signed(n)) => Some(n),
+            _ => None,
+        }
+    }
+
+    /// Returns true if writing the value produces the same bytes as
+    /// serializing the equivalent [Value].
+    ///
+    /// A value is canonical if no integer or byte string kept a non-canonical
+    /// spelling and every dictionary has unique keys in sorted order.
+    #[must_use]
+    pub fn is_canonical(&self) -> bool {
+        if self.spelling.is_some() {
+            return false;
+        }
+        match &self.kind {
+            VerbatimKind::ByteStr(_) | VerbatimKind::Int(_) => true,
+            VerbatimKind::List(l) => l.iter().all(VerbatimValue::is_canonical),
+            VerbatimKind::Dict(d) => {
+                d.iter().zip(d.iter().skip(1)).all(|(a, b)| a.key < b.key)
+                    && d.iter()
+                        .all(|e| e.key_spelling.is_none() && e.value.is_canonical())
+            }
+        }
+    }
+
+    /// Encodes the value with the original spellings and dictionary order.
+    #[must_use]
+    pub fn to_vec(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        self.write_verbatim(&mut buf);
+        buf
+    }
+
+    fn write_verbatim(&self, buf: &mut Vec<u8>) {
+        if let Some(spelling) = &self.spelling {
+            buf.extend_from_slice(spelling);
+            return;
+        }
+        match &self.kind {
+            VerbatimKind::ByteStr(b) => write_byte_str(buf, b),
+            VerbatimKind::Int(n) => write_int(buf, n),
+            VerbatimKind::List(l) => {
+                buf.extend_from_slice(b"l");
+                for v in l {
+                    v.write_verbatim(buf);
+                }
+                buf.extend_from_slice(b"e");
+            }
+            VerbatimKind::Dict(d) => {
+                buf.extend_from_slice(b"d");
+                for e in d {
+                    match &e.key_spelling {
+                        Some(spelling) => buf.extend_from_slice(spelling),
+                        None => write_byte_str(buf, &e.key),
+                    }
+                    e.value.write_verbatim(buf);
+                }
+                buf.extend_from_slice(b"e");
+            }
+        }
+    }
+}
+
+fn write_byte_str(buf: &mut Vec<u8>, bytes: &[u8]) {
+    let mut len = itoa::Buffer::new();
+    // Writing to a `Vec` cannot fail.
+    let _ = buf.write_all_vectored(&[len.format(bytes.len()).as_bytes(), b":", bytes]);
+}
+
+fn write_int(buf: &mut Vec<u8>, n: &Number) {
+    let mut num = itoa::Buffer::new();
+    buf.extend_from_slice(b"i");
+    buf.extend_from_slice(n.format(&mut num).as_bytes());
+    buf.extend_from_slice(b"e");
+}
+
+/// Returns the original encoding if it differs from the canonical encoding.
+fn spelling(raw: &[u8], canonical: &[u8]) -> Option<Box<[u8]>> {
+    if raw == canonical {
+        None
+    } else {
+        Some(raw.into())
+    }
+}
+
+/// Parses a byte string and returns it with its non-canonical spelling.
+fn parse_byte_str(
+    input: &[u8],
+    read: &mut SliceRead<'_>,
+    buf: &mut Vec<u8>,
+) -> Result<(SmallBytes, Option<Box<[u8]>>)> {
+    let start = read.byte_offset();
+    buf.clear();
+    let bytes = SmallBytes::from(&*read.parse_byte_str(buf)?);
+    let raw = &input[start..read.byte_offset()];
+    buf.clear();
+    write_byte_str(buf, &bytes);
+    let spelling = spelling(raw, buf);
+    Ok((bytes, spelling))
+}
+
+fn parse(input: &[u8], read: &mut SliceRead<'_>, buf: &mut Vec<u8>) -> Result<VerbatimValue> {
+    let start = read.byte_offset();
+    let (kind, spelling) = match read.peek().ok_or(Error::EofWhileParsingValue)?? {
+        b'0'..=b'9' => {
+            let (bytes, spelling) = parse_byte_str(input, read, buf)?;
+            (VerbatimKind::ByteStr(bytes), spelling)
+        }
+        b'i' => {
+            read.next();
+            buf.clear();
+            let num_str = read.parse_integer(buf)?;
+            let n: Number = num_str.parse()?;
+            buf.clear();
+            write_int(buf, &n);
+            let spelling = spelling(&input[start..read.byte_offset()], buf);
+            (VerbatimKind::Int(n), spelling)
+        }
+        b'l' => {
+            read.next();
+            let mut list = Vec::new();
+            while read.peek().ok_or(Error::EofWhileParsingValue)?? != b'e' {
+                list.push(parse(input, read, buf)?);
+            }
+            read.next();
+            (VerbatimKind::List(list), None)
+        }
+        b'd' => {
+            read.next();
+            let mut dict = Vec::new();
+            loop {
+                match read.peek().ok_or(Error::EofWhileParsingValue)?? {
+                    b'e' => break,
+                    b'0'..=b'9' => {}
+                    _ => return Err(Error::KeyMustBeAByteStr),
+                }
+                let (key, key_spelling) = parse_byte_str(input, read, buf)?;
+                let value = parse(input, read, buf)?;
+                dict.push(VerbatimEntry {
+                    key,
+                    key_spelling,
+                    value,
+                });
+            }
+            read.next();
+            (VerbatimKind::Dict(dict), None)
+        }
+        _ => return Err(Error::ExpectedSomeValue),
+    };
+    Ok(VerbatimValue { kind, spelling })
+}
+
+impl From<VerbatimKind> for VerbatimValue {
+    fn from(kind: VerbatimKind) -> Self {
+        VerbatimValue {
+            kind,
+            spelling: None,
+        }
+    }
+}
+
+impl From<Value> for VerbatimValue {
+    fn from(value: Value) -> Self {
+        VerbatimValue::from(match value {
+            Value::ByteStr(b) => VerbatimKind::ByteStr(b),
+            Value::Int(n) => VerbatimKind::Int(n),
+            Value::List(l) => VerbatimKind::List(l.into_iter().map(VerbatimValue::from).collect()),
+            Value::Dict(d) => VerbatimKind::Dict(
+                d.into_iter()
+                    .map(|(k, v)| VerbatimEntry::new(k, v))
+                    .collect(),
+            ),
+        })
+    }
+}
+
+impl From<VerbatimValue> for Value {
+    /// Converts the value, sorting dictionary keys.
+    ///
+    /// If a key is repeated, the last value is kept.
+    fn from(value: VerbatimValue) -> Self {
+        match value.kind {
+            VerbatimKind::ByteStr(b) => Value::ByteStr(b),
+            VerbatimKind::Int(n) => Value::Int(n),
+            VerbatimKind::List(l) => Value::List(l.into_iter().map(Value::from).collect()),
+            VerbatimKind::Dict(d) => Value::Dict(
+                d.into_iter()
+                    .map(|e| (ByteStrKey::from(e.key), Value::from(e.value)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() -> Result<()> {
+        for input in &[
+            &b"d1:zli1ei-1ed1:y0:1:x0:ee1:a4:spame"[..],
+            b"d1:ai1e1:bi2e1:ai3ee",
+            b"li03ei-0ei-01e004:spam2:xye",
+            b"d01:ai00ee",
+        ] {
+            let value = VerbatimValue::from_slice(input)?;
+            assert!(!value.is_canonical(), "{:?}", input);
+            assert_eq!(value.to_vec(), *input);
+        }
+
+        let input = b"d1:ali-1e0:e1:bd1:ci42eee";
+        let value = VerbatimValue::from_slice(input)?;
+        assert!(value.is_canonical());
+        assert_eq!(value.to_vec(), input);
+        assert_eq!(
+            Value::from(value.clone()),
+            crate::from_slice::<Value>(input)?
+        );
+        assert_eq!(
+            VerbatimValue::from(crate::from_slice::<Value>(input)?),
+            value
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_modify() -> Result<()> {
+        let mut value = VerbatimValue::from_slice(b"d1:ali01ee1:bi02e1:ci03ee")?;
+        assert_eq!(value.get(b"c").and_then(VerbatimValue::as_u64), Some(3));
+
+        *value.get_mut(b"b").unwrap().kind_mut() = VerbatimKind::Int(Number::Signed(-2));
+        if let Some(VerbatimKind::List(l)) = value.get_mut(b"a").map(VerbatimValue::kind_mut) {
+            l.push(VerbatimValue::from(Value::from("x")));
+        }
+        if let VerbatimKind::Dict(d) = value.kind_mut() {
+            d.push(VerbatimEntry::new("d", Value::from(4)));
+        }
+        assert_eq!(value.to_vec(), b"d1:ali01e1:xe1:bi-2e1:ci03e1:di4ee");
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(VerbatimValue::from_slice(b"").is_err());
+        assert!(VerbatimValue::from_slice(b"li1e").is_err());
+        assert!(VerbatimValue::from_slice(b"di1ei2ee").is_err());
+        assert!(VerbatimValue::from_slice(b"i1ei2e").is_err());
+        assert!(VerbatimValue::from_slice(b"ie").is_err());
+    }
+}
@@ -0,0 +1,343 @@
+//! Depth-first traversal of a [`Value`].
+
+use super::{Number, NumberPolicy, SmallBytes, Value};
+use crate::de::PathSegment;
+use core::mem;
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::vec::Vec;
+
+/// Controls how [`Value::walk()`] and [`Value::walk_mut()`] continue after a
+/// value is visited.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Walk {
+    /// Visits the values nested in the value.
+    Continue,
+    /// Does not visit the values nested in the value.
+    SkipChildren,
+    /// Does not visit any more values.
+    Stop,
+}
+
+impl Value {
+    /// Visits the value and every nested value in depth-first order.
+    ///
+    /// The closure is called with the path to each value and the value. A
+    /// list or dictionary is visited before the values nested in it, and
+    /// dictionary entries are visited in key order. The path and value borrow
+    /// from `self`, so they can be kept after the closure returns.
+    ///
+    /// ```rust
+    /// use bt_bencode::value::Walk;
+    /// use bt_bencode::{PathSegment, Value};
+    ///
+    /// let value: Value = bt_bencode::from_slice(b"d1:ali1ei2ee1:bd1:ci3eee")?;
+    ///
+    /// let mut sum = 0;
+    /// value.walk(|path, value| {
+    ///     if path.first() == Some(&PathSegment::Key(b"b")) {
+    ///         return Walk::SkipChildren;
+    ///     }
+    ///     sum += value.as_u64().unwrap_or(0);
+    ///     Walk::Continue
+    /// });
+    /// assert_eq!(sum, 3);
+    /// # Ok::<(), bt_bencode::Error>(())
+    /// ```
+    pub fn walk<'a, F>(&'a self, mut f: F)
+    where
+        F: FnMut(&[PathSegment<'a>], &'a Value) -> Walk,
+    {
+        let mut path = Vec::new();
+        walk(self, &mut path, &mut f);
+    }
+
+    /// Visits the value and every nested value in depth-first order with
+    /// mutable references.
+    ///
+    /// The values nested in a list or dictionary are visited after the
+    /// closure returns for the list or dictionary, so the closure may replace
+    /// a value and the replacement's nested values are visited.
+    ///
+    /// ```rust
+    /// use bt_bencode::value::Walk;
+    /// use bt_bencode::Value;
+    ///
+    /// let mut value: Value = bt_bencode::from_slice(b"d1:a3:abc1:bl5:abcdeee")?;
+    ///
+    /// value.walk_mut(|_, value| {
+    ///     if let Some(bytes) = value.as_byte_str_mut() {
+    ///         if bytes.len() > 4 {
+    ///             *value = Value::from("redacted");
+    ///         }
+    ///     }
+    ///     Walk::Continue
+    /// });
+    /// assert_eq!(bt_bencode::to_vec(&value)?, b"d1:a3:abc1:bl8:redactedee");
+    /// # Ok::<(), bt_bencode::Error>(())
+    /// ```
+    pub fn walk_mut<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&[PathSegment<'_>], &mut Value) -> Walk,
+    {
+        let mut path = Vec::new();
+        walk_mut(self, &mut path, &mut f);
+    }
+
+    /// Returns the value with every byte string transformed.
+    ///
+    /// Byte string values nested in lists and dictionaries are transformed
+    /// but dictionary keys are not. Use [`Value::walk_mut()`] to transform
+    /// only the byte strings at certain paths.
+    ///
+    /// ```rust
+    /// use bt_bencode::Value;
+    ///
+    /// let value: Value = bt_bencode::from_slice(b"d1:ali1e2:abe1:b2:cde")?;
+    /// let value = value.map_bytes(|bytes| bytes.to_ascii_uppercase().into());
+    /// assert_eq!(bt_bencode::to_vec(&value)?, b"d1:ali1e2:ABe1:b2:CDe");
+    /// # Ok::<(), bt_bencode::Error>(())
+    /// ```
+    #[must_use]
+    pub fn map_bytes<F>(mut self, mut f: F) -> Value
+    where
+        F: FnMut(SmallBytes) -> SmallBytes,
+    {
+        self.map_bytes_mut(|bytes| *bytes = f(mem::take(bytes)));
+        self
+    }
+
+    /// Transforms every byte string in place.
+    ///
+    /// Byte string values nested in lists and dictionaries are transformed
+    /// but dictionary keys are not.
+    pub fn map_bytes_mut<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&mut SmallBytes),
+    {
+        self.walk_mut(|_, value| {
+            if let Value::ByteStr(bytes) = value {
+                f(bytes);
+            }
+            Walk::Continue
+        });
+    }
+
+    /// Returns the value with every integer transformed.
+    ///
+    /// ```rust
+    /// use bt_bencode::{value::Number, Value};
+    ///
+    /// let value: Value = bt_bencode::from_slice(b"d1:ai1e1:bli-2eee")?;
+    /// let value = value.map_ints(|n| match n {
+    ///     Number::Signed(n) => Number::Signed(n.saturating_mul(10)),
+    ///     Number::Unsigned(n) => Number::Unsigned(n.saturating_mul(10)),
+    ///     n => n,
+    /// });
+    /// assert_eq!(bt_bencode::to_vec(&value)?, b"d1:ai10e1:bli-20eee");
+    /// # Ok::<(), bt_bencode::Error>(())
+    /// ```
+    #[must_use]
+    pub fn map_ints<F>(mut self, mut f: F) -> Value
+    where
+        F: FnMut(Number) -> Number,
+    {
+        self.map_ints_mut(|n| *n = f(mem::replace(n, Number::Unsigned(0))));
+        self
+    }
+
+    /// Transforms every integer in place.
+    pub fn map_ints_mut<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&mut Number),
+    {
+        self.walk_mut(|_, value| {
+            if let Value::Int(n) = value {
+                f(n);
+            }
+            Walk::Continue
+        });
+    }
+
+    /// Stores every integer in the smallest variant which can hold it.
+    ///
+    /// See [`Number::normalize()`].
+    ///
+    /// ```rust
+    /// use bt_bencode::value::{Number, NumberPolicy};
+    /// use bt_bencode::Value;
+    ///
+    /// let mut value: Value = bt_bencode::from_slice(b"li1ei-2ee")?;
+    /// value.normalize_numbers(NumberPolicy::Signed);
+    /// assert!(matches!(value[0].as_number(), Some(Number::Signed(1))));
+    /// # Ok::<(), bt_bencode::Error>(())
+    /// ```
+    pub fn normalize_numbers(&mut self, policy: NumberPolicy) {
+        self.map_ints_mut(|n| n.normalize(policy));
+    }
+}
+
+/// Returns false if the walk should stop.
+fn walk<'a, F>(value: &'a Value, path: &mut Vec<PathSegment<'a>>, f: &mut F) -> bool
+where
+    F: FnMut(&[PathSegment<'a>], &'a Value) -> Walk,
+{
+    match f(path, value) {
+        Walk::Continue => {}
+        Walk::SkipChildren => return true,
+        Walk::Stop => return false,
+    }
+    match value {
+        Value::ByteStr(_) | Value::Int(_) => true,
+        Value::List(l) => l.iter().enumerate().all(|(i, v)| {
+            path.push(PathSegment::Index(i));
+            let more = walk(v, path, f);
+            path.pop();
+            more
+        }),
+        Value::Dict(d) => d.iter().all(|(k, v)| {
+            path.push(PathSegment::Key(k));
+            let more = walk(v, path, f);
+            path.pop();
+            more
+        }),
+    }
+}
+
+/// Returns false if the walk should stop.
+fn walk_mut<'a, F>(value: &'a mut Value, path: &mut Vec<PathSegment<'a>>, f: &mut F) -> bool
+where
+    F: FnMut(&[PathSegment<'_>], &mut Value) -> Walk,
+{
+    match f(path, value) {
+        Walk::Continue => {}
+        Walk::SkipChildren => return true,
+        Walk::Stop => return false,
+    }
+    match value {
+        Value::ByteStr(_) | Value::Int(_) => true,
+        Value::List(l) => l.iter_mut().enumerate().all(|(i, v)| {
+            path.push(PathSegment::Index(i));
+            let more = walk_mut(v, path, f);
+            path.pop();
+            more
+        }),
+        Value::Dict(d) => d.iter_mut().all(|(k, v)| {
+            path.push(PathSegment::Key(k));
+            let more = walk_mut(v, path, f);
+            path.pop();
+            more
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Result;
+
+    #[cfg(all(feature = "alloc", not(feature = "std")))]
+    use alloc::vec;
+
+    #[test]
+    fn test_walk() -> Result<()> {
+        let value: Value = crate::from_slice(b"d1:ali1ei2ee1:bd1:ci3ee1:d0:e")?;
+
+        let mut paths = Vec::new();
+        value.walk(|path, _| {
+            paths.push(path.to_vec());
+            Walk::Continue
+        });
+        assert_eq!(
+            paths,
+            [
+                vec![],
+                vec![PathSegment::Key(b"a")],
+                vec![PathSegment::Key(b"a"), PathSegment::Index(0)],
+                vec![PathSegment::Key(b"a"), PathSegment::Index(1)],
+                vec![PathSegment::Key(b"b")],
+                vec![PathSegment::Key(b"b"), PathSegment::Key(b"c")],
+                vec![PathSegment::Key(b"d")],
+            ]
+        );
+
+        let mut count = 0;
+        value.walk(|path, _| {
+            count += 1;
+            if path == [PathSegment::Key(b"a"), PathSegment::Index(0)] {
+                Walk::Stop
+            } else {
+                Walk::Continue
+            }
+        });
+        assert_eq!(count, 3);
+
+        let mut count = 0;
+        value.walk(|_, value| {
+            count += 1;
+            if value.is_array() {
+                Walk::SkipChildren
+            } else {
+                Walk::Continue
+            }
+        });
+        assert_eq!(count, 5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_walk_mut() -> Result<()> {
+        let mut value: Value = crate::from_slice(b"d1:ali1ei2ee1:bd1:ci3eee")?;
+        value.walk_mut(|path, value| {
+            if path == [PathSegment::Key(b"b")] {
+                *value = Value::List(vec![Value::from(4_u64), Value::from(5_u64)]);
+            } else if let Some(n) = value.as_u64() {
+                *value = Value::from(n * 10);
+            }
+            Walk::Continue
+        });
+        assert_eq!(crate::to_vec(&value)?, b"d1:ali10ei20ee1:bli40ei50eee");
+
+        value.walk_mut(|_, value| {
+            if value.is_array() {
+                value.as_array_mut().unwrap().clear();
+                Walk::Stop
+            } else {
+                Walk::Continue
+            }
+        });
+        assert_eq!(crate::to_vec(&value)?, b"d1:ale1:bli40ei50eee");
+        Ok(())
+    }
+
+    #[test]
+    fn test_map() -> Result<()> {
+        let input = b"d1:ali1e0:e1:bd2:cd2:xye1:di-3ee";
+        let value: Value = crate::from_slice(input)?;
+
+        let mut lens = Vec::new();
+        let mapped = value.clone().map_bytes(|bytes| {
+            lens.push(bytes.len());
+            SmallBytes::from(&b"zz"[..])
+        });
+        assert_eq!(lens, [0, 2]);
+        assert_eq!(
+            crate::to_vec(&mapped)?,
+            b"d1:ali1e2:zze1:bd2:cd2:zze1:di-3ee"
+        );
+
+        let mut mapped = value.clone().map_ints(|n| match n {
+            Number::Signed(n) => Number::Unsigned(n.wrapping_abs() as u64),
+            Number::Unsigned(n) => Number::Signed(-(n as i64)),
+            n => n,
+        });
+        assert_eq!(crate::to_vec(&mapped)?, b"d1:ali-1e0:e1:bd2:cd2:xye1:di3ee");
+
+        mapped.map_ints_mut(|n| *n = Number::Unsigned(0));
+        mapped.map_bytes_mut(|bytes| bytes.as_mut_slice().reverse());
+        assert_eq!(crate::to_vec(&mapped)?, b"d1:ali0e0:e1:bd2:cd2:yxe1:di0ee");
+        Ok(())
+    }
+}
@@ -1,6 +1,6 @@
 //! [Write] trait and helpers to write bytes for the serializer.
 
-#[cfg(feature = "std")]
+#[cfg(any(feature = "std", feature = "bytes"))]
 use crate::error::Error;
 
 #[cfg(feature = "std")]
@@ -19,6 +19,22 @@ pub trait Write {
     ///
     /// If the bytes could not be written, a Bencode error is returned.
     fn write_all(&mut self, buf: &[u8]) -> Result<()>;
+
+    /// Writes all of the bytes from each buffer in order.
+    ///
+    /// The default implementation calls [`Write::write_all()`] for each
+    /// buffer. Implementations for sinks which support vectored IO can write
+    /// the buffers with fewer calls.
+    ///
+    /// # Errors
+    ///
+    /// If the bytes could not be written, a Bencode error is returned.
+    fn write_all_vectored(&mut self, bufs: &[&[u8]]) -> Result<()> {
+        for buf in bufs {
+            self.write_all(buf)?;
+        }
+        Ok(())
+    }
 }
 
 /// A wrapper to implement this crate's [Write] trait for [`std::io::Write`] trait implementations.
@@ -52,6 +68,90 @@ where
     fn write_all(&mut self, buf: &[u8]) -> Result<()> {
         self.writer.write_all(buf).map_err(Error::IoError)
     }
+
+    fn write_all_vectored(&mut self, mut bufs: &[&[u8]]) -> Result<()> {
+        const MAX_SLICES: usize = 4;
+
+        // The number of bytes already written from the first buffer
+        let mut offset = 0;
+        loop {
+            while let Some(first) = bufs.first() {
+                if offset < first.len() {
+                    break;
+                }
+                offset = 0;
+                bufs = &bufs[1..];
+            }
+            if bufs.is_empty() {
+                return Ok(());
+            }
+
+            let count = core::cmp::min(bufs.len(), MAX_SLICES);
+            let mut slices = [io::IoSlice::new(&[]); MAX_SLICES];
+            slices[0] = io::IoSlice::new(&bufs[0][offset..]);
+            for (slice, buf) in slices[1..count].iter_mut().zip(&bufs[1..count]) {
+                *slice = io::IoSlice::new(buf);
+            }
+
+            let mut n = match self.writer.write_vectored(&slices[..count]) {
+                Ok(0) => return Err(Error::IoError(io::ErrorKind::WriteZero.into())),
+                Ok(n) => n,
+                Err(ref err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(Error::IoError(err)),
+            };
+
+            n += offset;
+            offset = 0;
+            while let Some(first) = bufs.first() {
+                if n < first.len() {
+                    offset = n;
+                    break;
+                }
+                n -= first.len();
+                bufs = &bufs[1..];
+            }
+        }
+    }
+}
+
+/// A wrapper to implement this crate's [Write] trait for [`bytes::BufMut`] implementations.
+#[cfg(feature = "bytes")]
+#[derive(Debug)]
+#[allow(clippy::module_name_repetitions)]
+pub struct BufMutWrite<B> {
+    buf: B,
+}
+
+#[cfg(feature = "bytes")]
+impl<B> BufMutWrite<B>
+where
+    B: bytes::BufMut,
+{
+    /// Instantiates a new writer.
+    pub fn new(buf: B) -> Self {
+        Self { buf }
+    }
+
+    /// Returns the underlying buffer.
+    pub fn into_inner(self) -> B {
+        self.buf
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl<B> Write for BufMutWrite<B>
+where
+    B: bytes::BufMut,
+{
+    fn write_all(&mut self, buf: &[u8]) -> Result<()> {
+        if self.buf.remaining_mut() < buf.len() {
+            return Err(Error::Serialize(
+                "buffer does not have enough remaining capacity".into(),
+            ));
+        }
+        self.buf.put_slice(buf);
+        Ok(())
+    }
 }
 
 impl Write for Vec<u8> {
@@ -59,6 +159,14 @@ impl Write for Vec<u8> {
         self.extend_from_slice(buf);
         Ok(())
     }
+
+    fn write_all_vectored(&mut self, bufs: &[&[u8]]) -> Result<()> {
+        self.reserve(bufs.iter().map(|buf| buf.len()).sum());
+        for buf in bufs {
+            self.extend_from_slice(buf);
+        }
+        Ok(())
+    }
 }
 
 impl Write for &mut Vec<u8> {
@@ -66,4 +174,215 @@ impl Write for &mut Vec<u8> {
         self.extend_from_slice(buf);
         Ok(())
     }
+
+    fn write_all_vectored(&mut self, bufs: &[&[u8]]) -> Result<()> {
+        (**self).write_all_vectored(bufs)
+    }
+}
+
+/// A [Write] target which collects the output in a list of bounded size segments.
+///
+/// Serializing a very large value (e.g. a multi-hundred megabyte v2 torrent)
+/// into a [Vec] requires a single contiguous allocation for the entire
+/// output. `Segments` instead stores the output in multiple buffers which are
+/// each at most the segment size. The segments can be written out in order
+/// (e.g. with vectored IO) or copied into a contiguous buffer if needed.
+#[derive(Clone, Debug)]
+pub struct Segments {
+    full: Vec<Vec<u8>>,
+    last: Vec<u8>,
+    segment_size: usize,
+}
+
+impl Segments {
+    /// The default maximum number of bytes in a segment.
+    pub const DEFAULT_SEGMENT_SIZE: usize = 64 * 1024;
+
+    /// Constructs an empty list of segments with the [default segment size][Segments::DEFAULT_SEGMENT_SIZE].
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_segment_size(Self::DEFAULT_SEGMENT_SIZE)
+    }
+
+    /// Constructs an empty list of segments with a maximum number of bytes per segment.
+    ///
+    /// A segment size of `0` is treated as `1`.
+    #[must_use]
+    pub fn with_segment_size(segment_size: usize) -> Self {
+        Self {
+            full: Vec::new(),
+            last: Vec::new(),
+            segment_size: core::cmp::max(segment_size, 1),
+        }
+    }
+
+    /// Returns the total number of bytes in all of the segments.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.full.iter().map(Vec::len).sum::<usize>() + self.last.len()
+    }
+
+    /// Returns true if no bytes have been written.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.full.is_empty() && self.last.is_empty()
+    }
+
+    /// Returns an iterator over the segments in order.
+    pub fn iter(&self) -> impl Iterator<Item = &[u8]> {
+        self.full
+            .iter()
+            .map(Vec::as_slice)
+            .chain(core::iter::once(self.last.as_slice()))
+            .filter(|s| !s.is_empty())
+    }
+
+    /// Returns the segments in order.
+    #[must_use]
+    pub fn into_segments(self) -> Vec<Vec<u8>> {
+        let mut segments = self.full;
+        if !self.last.is_empty() {
+            segments.push(self.last);
+        }
+        segments
+    }
+
+    /// Copies all of the segments into a single contiguous buffer.
+    #[must_use]
+    pub fn to_vec(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.len());
+        for segment in self.iter() {
+            buf.extend_from_slice(segment);
+        }
+        buf
+    }
+
+    /// Writes all of the segments in order to the writer.
+    ///
+    /// # Errors
+    ///
+    /// If the bytes could not be written, an IO error is returned.
+    #[cfg(feature = "std")]
+    pub fn write_to<W>(&self, mut writer: W) -> io::Result<()>
+    where
+        W: io::Write,
+    {
+        for segment in self.iter() {
+            writer.write_all(segment)?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for Segments {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Write for Segments {
+    fn write_all(&mut self, mut buf: &[u8]) -> Result<()> {
+        while !buf.is_empty() {
+            let space = self.segment_size - self.last.len();
+            if space == 0 {
+                let full = core::mem::take(&mut self.last);
+                self.full.push(full);
+                continue;
+            }
+
+            let n = core::cmp::min(space, buf.len());
+            self.last.extend_from_slice(&buf[..n]);
+            buf = &buf[n..];
+        }
+        Ok(())
+    }
+}
+
+impl Write for &mut Segments {
+    #[inline]
+    fn write_all(&mut self, buf: &[u8]) -> Result<()> {
+        (**self).write_all(buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "bytes")]
+    fn test_buf_mut_write() -> Result<()> {
+        let mut out = [0; 6];
+        let mut writer = BufMutWrite::new(&mut out[..]);
+        writer.write_all_vectored(&[b"ab", b"cd"])?;
+        assert!(writer.write_all(b"efg").is_err());
+        writer.write_all(b"ef")?;
+        assert_eq!(&out, b"abcdef");
+        Ok(())
+    }
+
+    #[test]
+    fn test_segments_bounded() -> Result<()> {
+        let mut segments = Segments::with_segment_size(4);
+        segments.write_all(b"ab")?;
+        segments.write_all(b"cdefghij")?;
+        segments.write_all(b"")?;
+
+        assert_eq!(segments.len(), 10);
+        assert_eq!(
+            segments.iter().collect::<Vec<_>>(),
+            [&b"abcd"[..], &b"efgh"[..], &b"ij"[..]]
+        );
+        assert_eq!(segments.to_vec(), b"abcdefghij");
+        assert_eq!(segments.into_segments().len(), 3);
+        Ok(())
+    }
+
+    #[cfg(feature = "std")]
+    #[derive(Default)]
+    struct ShortWrites {
+        output: Vec<u8>,
+        calls: usize,
+    }
+
+    #[cfg(feature = "std")]
+    impl io::Write for ShortWrites {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.write_vectored(&[io::IoSlice::new(buf)])
+        }
+
+        fn write_vectored(&mut self, bufs: &[io::IoSlice<'_>]) -> io::Result<usize> {
+            // Accepts at most 3 bytes per call
+            self.calls += 1;
+            let mut n = 0;
+            for buf in bufs {
+                let len = core::cmp::min(buf.len(), 3 - n);
+                self.output.extend_from_slice(&buf[..len]);
+                n += len;
+            }
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_io_write_vectored() -> Result<()> {
+        let mut writer = IoWrite::new(ShortWrites::default());
+        writer.write_all_vectored(&[b"", b"4:", b"", b"spam", b"", b"i1e", b""])?;
+        assert_eq!(writer.writer.output, b"4:spami1e");
+        assert_eq!(writer.writer.calls, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_segments_empty() {
+        let segments = Segments::new();
+        assert!(segments.is_empty());
+        assert_eq!(segments.iter().count(), 0);
+        assert!(segments.into_segments().is_empty());
+    }
 }